@@ -0,0 +1,465 @@
+//! Which outer bindings each function closes over
+//!
+//! ```js
+//! let count = 0;
+//! const config = load();
+//! function bump() {
+//!     count += config.step;
+//!     return () => this.render(count);
+//! }
+//! ```
+//! `bump` captures `count`, which it mutates, and `config`, which it
+//! only reads. The arrow captures `count` as well, and since an arrow
+//! has no `this` of its own both the arrow and `bump` use `this`.
+//!
+//! A capture is a reference to a binding declared outside of the
+//! function, so a function also captures everything its nested
+//! functions capture from outside of it. Globals are not captures
+//! since they do not resolve to a binding.
+use std::collections::HashMap;
+
+use crate::analysis::scope::{BindingId, ReferenceId, ScopeId, ScopeKind, ScopeTree};
+use crate::analysis::visit::{walk_expr, walk_func, Visit};
+use crate::expr::{ArrowFuncExpr, Expr, MetaProp, PropKey};
+use crate::{Class, ClassMember, Func, Ident, Program};
+
+/// Find the captures of every function and arrow function in
+/// `program`, `scopes` must come from `scope::analyze` of the same
+/// program
+pub fn analyze<'a, T>(program: &'a Program<T>, scopes: &ScopeTree<'a, T>) -> Captures<'a, T>
+where
+    T: AsRef<str>,
+{
+    let mut functions = Vec::new();
+    let mut by_scope = HashMap::new();
+    for (index, scope) in scopes.scopes().iter().enumerate() {
+        let node = match scope.kind {
+            ScopeKind::Func(func) => FunctionNode::Func(func),
+            ScopeKind::Arrow(arrow) => FunctionNode::Arrow(arrow),
+            _ => continue,
+        };
+        by_scope.insert(ScopeId(index), functions.len());
+        functions.push(FunctionCaptures {
+            node,
+            scope: ScopeId(index),
+            captures: Vec::new(),
+            uses_this: false,
+            uses_arguments: false,
+            uses_super: false,
+            uses_new_target: false,
+        });
+    }
+    for (index, reference) in scopes.references().iter().enumerate() {
+        let binding = match reference.binding {
+            Some(binding) => binding,
+            None => continue,
+        };
+        // every function between the reference and the declaration
+        let declared = scopes.binding(binding).scope;
+        let mut current = reference.scope;
+        while current != declared {
+            if let Some(function) = by_scope.get(&current) {
+                functions[*function].add(binding, ReferenceId(index), reference.kind.is_write());
+            }
+            current = match scopes.scope(current).parent {
+                Some(parent) => parent,
+                None => break,
+            };
+        }
+    }
+
+    let by_node = functions
+        .iter()
+        .enumerate()
+        .map(|(index, function)| (function.node.address(), index))
+        .collect();
+    let mut lexical = Lexical {
+        scopes,
+        functions: &mut functions,
+        by_node: &by_node,
+        stack: Vec::new(),
+    };
+    lexical.visit_program(program);
+    Captures { functions, by_node }
+}
+
+/// The result of `analyze`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Captures<'a, T> {
+    functions: Vec<FunctionCaptures<'a, T>>,
+    /// The index of each function by the address of its node
+    by_node: HashMap<usize, usize>,
+}
+
+impl<'a, T> Captures<'a, T> {
+    /// Every function and arrow function in source order
+    pub fn functions(&self) -> &[FunctionCaptures<'a, T>] {
+        &self.functions
+    }
+    /// Look up a function by identity, `None` when it is not part of
+    /// the analyzed program
+    pub fn func(&self, func: &Func<T>) -> Option<&FunctionCaptures<'a, T>> {
+        self.by_node
+            .get(&(func as *const Func<T> as usize))
+            .map(|index| &self.functions[*index])
+    }
+    /// Look up an arrow function by identity
+    pub fn arrow(&self, arrow: &ArrowFuncExpr<T>) -> Option<&FunctionCaptures<'a, T>> {
+        self.by_node
+            .get(&(arrow as *const ArrowFuncExpr<T> as usize))
+            .map(|index| &self.functions[*index])
+    }
+}
+
+/// What one function closes over
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionCaptures<'a, T> {
+    pub node: FunctionNode<'a, T>,
+    pub scope: ScopeId,
+    /// The outer bindings, in the order they are first referenced
+    pub captures: Vec<Capture>,
+    /// `this` is used here or, lexically, in a nested arrow function
+    pub uses_this: bool,
+    /// `arguments` is used without being declared
+    pub uses_arguments: bool,
+    /// `super.prop` or `super()`
+    pub uses_super: bool,
+    pub uses_new_target: bool,
+}
+
+impl<T> FunctionCaptures<'_, T> {
+    /// The capture of `binding`, `None` when it is not captured
+    pub fn capture(&self, binding: BindingId) -> Option<&Capture> {
+        self.captures
+            .iter()
+            .find(|capture| capture.binding == binding)
+    }
+    /// If any captured binding is written
+    pub fn mutates_captures(&self) -> bool {
+        self.captures.iter().any(|capture| capture.mutated)
+    }
+
+    fn add(&mut self, binding: BindingId, reference: ReferenceId, write: bool) {
+        match self
+            .captures
+            .iter_mut()
+            .find(|capture| capture.binding == binding)
+        {
+            Some(capture) => {
+                capture.references.push(reference);
+                capture.mutated |= write;
+            }
+            None => self.captures.push(Capture {
+                binding,
+                references: vec![reference],
+                mutated: write,
+            }),
+        }
+    }
+}
+
+/// A binding from outside of a function
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capture {
+    pub binding: BindingId,
+    /// The references inside of the function, including nested ones
+    pub references: Vec<ReferenceId>,
+    /// If any of the references writes the binding
+    pub mutated: bool,
+}
+
+/// A function or arrow function
+#[derive(Debug, PartialEq)]
+pub enum FunctionNode<'a, T> {
+    Func(&'a Func<T>),
+    Arrow(&'a ArrowFuncExpr<T>),
+}
+
+impl<T> FunctionNode<'_, T> {
+    fn address(self) -> usize {
+        match self {
+            FunctionNode::Func(func) => func as *const Func<T> as usize,
+            FunctionNode::Arrow(arrow) => arrow as *const ArrowFuncExpr<T> as usize,
+        }
+    }
+}
+
+impl<T> Clone for FunctionNode<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for FunctionNode<'_, T> {}
+
+/// Finds the uses of `this`, `arguments`, `super` and `new.target`
+struct Lexical<'s, 'a, T> {
+    scopes: &'s ScopeTree<'a, T>,
+    functions: &'s mut [FunctionCaptures<'a, T>],
+    by_node: &'s HashMap<usize, usize>,
+    /// The functions being visited, `None` for the class field
+    /// initializers and static blocks that have a `this` of their own
+    stack: Vec<Option<usize>>,
+}
+
+impl<'a, T> Lexical<'_, 'a, T>
+where
+    T: AsRef<str>,
+{
+    /// Mark the innermost non-arrow function and the arrows inside of it
+    fn mark(&mut self, mark: fn(&mut FunctionCaptures<'a, T>)) {
+        for entry in self.stack.iter().rev() {
+            let function = match entry {
+                Some(function) => &mut self.functions[*function],
+                None => break,
+            };
+            mark(function);
+            if let FunctionNode::Func(_) = function.node {
+                break;
+            }
+        }
+    }
+
+    /// Visit with `function` on top of the stack
+    fn nested(&mut self, function: Option<usize>, visit: impl FnOnce(&mut Self)) {
+        self.stack.push(function);
+        visit(self);
+        self.stack.pop();
+    }
+}
+
+impl<'a, T> Visit<'a, T> for Lexical<'_, 'a, T>
+where
+    T: AsRef<str>,
+{
+    fn visit_expr(&mut self, expr: &'a Expr<T>) {
+        match expr {
+            Expr::This => self.mark(|function| function.uses_this = true),
+            Expr::Super => self.mark(|function| function.uses_super = true),
+            Expr::MetaProp(MetaProp::NewTarget) => {
+                self.mark(|function| function.uses_new_target = true)
+            }
+            Expr::ArrowFunc(arrow) => {
+                let function = self
+                    .by_node
+                    .get(&(arrow as *const ArrowFuncExpr<T> as usize))
+                    .copied();
+                self.nested(function, |this| walk_expr(this, expr));
+            }
+            _ => walk_expr(self, expr),
+        }
+    }
+
+    fn visit_func(&mut self, func: &'a Func<T>) {
+        let function = self
+            .by_node
+            .get(&(func as *const Func<T> as usize))
+            .copied();
+        self.nested(function, |this| walk_func(this, func));
+    }
+
+    fn visit_class(&mut self, class: &'a Class<T>) {
+        for decorator in &class.decorators {
+            self.visit_expr(&decorator.expr);
+        }
+        if let Some(super_class) = &class.super_class {
+            self.visit_expr(super_class);
+        }
+        for member in &class.body.0 {
+            match member {
+                ClassMember::Prop(prop) => self.visit_prop(prop),
+                ClassMember::Field(field) => {
+                    for decorator in &field.decorators {
+                        self.visit_expr(&decorator.expr);
+                    }
+                    if field.computed {
+                        match &field.key {
+                            PropKey::Expr(expr) => self.visit_expr(expr),
+                            PropKey::Pat(pat) => self.visit_pat(pat),
+                            PropKey::Lit(_) | PropKey::Private(_) => {}
+                        }
+                    }
+                    if let Some(value) = &field.value {
+                        self.nested(None, |this| this.visit_expr(value));
+                    }
+                }
+                ClassMember::StaticBlock(body) => {
+                    self.nested(None, |this| this.visit_parts(&body.0))
+                }
+            }
+        }
+    }
+
+    fn visit_ident(&mut self, ident: &'a Ident<T>) {
+        if ident.name.as_ref() != "arguments" {
+            return;
+        }
+        let undeclared = self
+            .scopes
+            .reference_of(ident)
+            .is_some_and(|reference| self.scopes.reference(reference).binding.is_none());
+        if undeclared {
+            self.mark(|function| function.uses_arguments = true);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::scope;
+    use crate::decl::Decl;
+    use crate::expr::{AssignExpr, AssignLeft, Lit};
+    use crate::stmt::Stmt;
+    use crate::transform::build::{arrow, call, expr_stmt, ident, ident_expr, member, var};
+    use crate::{AssignOp, ClassBody, ClassField, FuncBody, ProgramPart, VarKind};
+
+    fn func(name: &str, body: Vec<ProgramPart<String>>) -> ProgramPart<String> {
+        ProgramPart::Decl(Decl::Func(Func {
+            id: Some(ident(name)),
+            type_params: Vec::new(),
+            params: Vec::new(),
+            return_type: None,
+            body: Some(FuncBody(body)),
+            generator: false,
+            is_async: false,
+        }))
+    }
+
+    fn func_of(part: &ProgramPart<String>) -> &Func<String> {
+        match part {
+            ProgramPart::Decl(Decl::Func(func)) => func,
+            _ => panic!("not a function declaration"),
+        }
+    }
+
+    fn binding(scopes: &ScopeTree<'_, String>, name: &str) -> BindingId {
+        scopes.lookup(scopes.root(), name).unwrap()
+    }
+
+    #[test]
+    fn reads_and_writes_of_outer_bindings() {
+        // the example of the module documentation
+        let program = Program::script(vec![
+            var(
+                VarKind::Let,
+                vec![("count", Some(Expr::Lit(Lit::Number("0".to_string()))))],
+            ),
+            var(
+                VarKind::Const,
+                vec![("config", Some(call(ident_expr("load"), Vec::new())))],
+            ),
+            func(
+                "bump",
+                vec![
+                    expr_stmt(Expr::Assign(AssignExpr {
+                        operator: AssignOp::PlusEqual,
+                        left: AssignLeft::Expr(Box::new(ident_expr("count"))),
+                        right: Box::new(member(ident_expr("config"), "step")),
+                    })),
+                    ProgramPart::Stmt(Stmt::Return(Some(arrow(
+                        &[],
+                        call(member(Expr::This, "render"), vec![ident_expr("count")]),
+                    )))),
+                ],
+            ),
+        ]);
+        let scopes = scope::analyze(&program);
+        let captures = analyze(&program, &scopes);
+        let (count, config) = (binding(&scopes, "count"), binding(&scopes, "config"));
+        let bump = captures.func(func_of(&program.parts()[2])).unwrap();
+        assert_eq!(bump.captures.len(), 2);
+        let captured = bump.capture(count).unwrap();
+        assert!(captured.mutated);
+        // the compound assignment and the read in the arrow
+        assert_eq!(captured.references.len(), 2);
+        assert!(!bump.capture(config).unwrap().mutated);
+        assert!(bump.mutates_captures());
+        assert!(bump.uses_this);
+        let inner = &captures.functions()[1];
+        assert!(matches!(inner.node, FunctionNode::Arrow(_)));
+        assert_eq!(inner.captures.len(), 1);
+        assert!(!inner.capture(count).unwrap().mutated);
+        assert!(inner.capture(config).is_none());
+        assert!(inner.uses_this);
+    }
+
+    #[test]
+    fn globals_and_locals_are_not_captures() {
+        let program = Program::script(vec![func(
+            "f",
+            vec![
+                var(VarKind::Let, vec![("local", None)]),
+                expr_stmt(call(ident_expr("global"), vec![ident_expr("local")])),
+            ],
+        )]);
+        let scopes = scope::analyze(&program);
+        let captures = analyze(&program, &scopes);
+        let f = captures.func(func_of(&program.parts()[0])).unwrap();
+        assert!(f.captures.is_empty());
+        assert!(!f.uses_this && !f.uses_arguments);
+    }
+
+    #[test]
+    fn arguments_of_the_enclosing_function() {
+        let program = Program::script(vec![
+            func("f", vec![expr_stmt(arrow(&[], ident_expr("arguments")))]),
+            func(
+                "g",
+                vec![
+                    var(VarKind::Let, vec![("arguments", None)]),
+                    expr_stmt(ident_expr("arguments")),
+                ],
+            ),
+        ]);
+        let scopes = scope::analyze(&program);
+        let captures = analyze(&program, &scopes);
+        let functions = captures.functions();
+        assert_eq!(functions.len(), 3);
+        // `f` and its arrow
+        assert!(functions[0].uses_arguments);
+        assert!(functions[1].uses_arguments);
+        // a declared `arguments` is a binding like any other
+        assert!(!functions[2].uses_arguments);
+    }
+
+    #[test]
+    fn class_fields_have_their_own_this() {
+        let field = ClassField {
+            key: PropKey::Expr(ident_expr("x")),
+            value: Some(Expr::This),
+            computed: false,
+            is_static: false,
+            decorators: Vec::new(),
+            accessibility: None,
+            is_readonly: false,
+            is_optional: false,
+            type_annotation: None,
+        };
+        let class = Class {
+            id: Some(ident("C")),
+            type_params: Vec::new(),
+            super_class: Some(Box::new(Expr::This)),
+            super_type_args: Vec::new(),
+            implements: Vec::new(),
+            body: ClassBody(vec![ClassMember::Field(field)]),
+            is_abstract: false,
+            decorators: Vec::new(),
+        };
+        let program = Program::script(vec![
+            func("f", vec![ProgramPart::Decl(Decl::Class(class.clone()))]),
+            func(
+                "g",
+                vec![ProgramPart::Decl(Decl::Class(Class {
+                    super_class: None,
+                    ..class
+                }))],
+            ),
+        ]);
+        let scopes = scope::analyze(&program);
+        let captures = analyze(&program, &scopes);
+        // the heritage is evaluated in `f`, the field is not
+        assert!(captures.functions()[0].uses_this);
+        assert!(!captures.functions()[1].uses_this);
+    }
+}
@@ -0,0 +1,1251 @@
+//! Control flow graphs for a program and its functions
+//!
+//! Each graph is a list of basic blocks, the nodes in a block run in
+//! order and the edges out of the last one say where control can go
+//! next
+//! ```js
+//! if (ready) {
+//!     start();
+//! } else {
+//!     wait();
+//! }
+//! done();
+//! ```
+//! has an entry block that evaluates `ready` with a `True` edge to
+//! the block for `start()` and a `False` edge to the one for `wait()`,
+//! both of which continue to the block for `done()`.
+//!
+//! Nodes are recorded at the finest level that can branch: an
+//! expression without a `&&`, `||`, `??`, `?:`, logical assignment or
+//! optional chain in it is a single node, while one with a branch is
+//! split and its operands are recorded before it, so its own node only
+//! stands for the work left once they are evaluated. A statement is
+//! recorded through its expressions, except for `return`, `throw`,
+//! `break`, `continue`, `debugger`, empty and invalid statements,
+//! which are nodes of their own.
+//!
+//! Inside a `try` block every block that can throw gets an `Exception`
+//! edge to the `catch` or `finally` clause. A `finally` clause is built
+//! once for every way of leaving its `try`, so that a `return` through
+//! it still returns, which means its nodes can appear in several blocks.
+//!
+//! Some parts of the language are not covered
+//! - nested functions, arrow functions and classes are single nodes,
+//!   build their own graphs with `func`, `arrow` or `all`
+//! - destructuring defaults and branches in JSX, class heritage or
+//!   `import()` arguments do not split their node
+//! - only nodes inside of a `try` get `Exception` edges, elsewhere
+//!   only `throw` statements lead to the exit
+use crate::analysis::visit::{walk_expr, walk_func, Visit};
+use crate::decl::{Decl, VarDecl};
+use crate::expr::{
+    ArrowFuncBody, ArrowFuncExpr, AssignExpr, AssignLeft, Expr, Lit, LogicalExpr, ObjProp, PropKey,
+    PropValue,
+};
+use crate::pat::Pat;
+use crate::stmt::{ForStmt, LoopInit, Stmt, SwitchStmt, TryStmt};
+use crate::{AssignOp, Class, Func, LogicalOp, MemberIndexer, Program, ProgramPart};
+
+/// Build the graph for the top level of `program`
+pub fn program<T>(program: &Program<T>) -> Cfg<'_, T>
+where
+    T: AsRef<str>,
+{
+    let mut builder = Builder::new();
+    builder.parts(program.parts());
+    builder.finish(CfgOwner::Program(program))
+}
+
+/// Build the graph for the body of `func`, a function without a body
+/// goes straight from the entry to the exit
+pub fn func<T>(func: &Func<T>) -> Cfg<'_, T>
+where
+    T: AsRef<str>,
+{
+    let mut builder = Builder::new();
+    if let Some(body) = &func.body {
+        builder.parts(&body.0);
+    }
+    builder.finish(CfgOwner::Func(func))
+}
+
+/// Build the graph for the body of `arrow`
+pub fn arrow<T>(arrow: &ArrowFuncExpr<T>) -> Cfg<'_, T>
+where
+    T: AsRef<str>,
+{
+    let mut builder = Builder::new();
+    match &arrow.body {
+        ArrowFuncBody::FuncBody(body) => builder.parts(&body.0),
+        ArrowFuncBody::Expr(expr) => builder.expr(expr),
+    }
+    builder.finish(CfgOwner::Arrow(arrow))
+}
+
+/// Build the graph for the top level of `program` followed by one for
+/// every function and arrow function in it, in source order
+pub fn all<T>(program: &Program<T>) -> Vec<Cfg<'_, T>>
+where
+    T: AsRef<str>,
+{
+    let mut collect = Collect {
+        cfgs: vec![self::program(program)],
+    };
+    collect.visit_parts(program.parts());
+    collect.cfgs
+}
+
+/// A control flow graph
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cfg<'a, T> {
+    owner: CfgOwner<'a, T>,
+    blocks: Vec<BasicBlock<'a, T>>,
+}
+
+impl<'a, T> Cfg<'a, T> {
+    /// The code this graph was built for
+    pub fn owner(&self) -> CfgOwner<'a, T> {
+        self.owner
+    }
+    /// Where control starts
+    pub fn entry(&self) -> BlockId {
+        BlockId(0)
+    }
+    /// Where control leaves, by returning, falling off the end or
+    /// throwing out of the last `try`. This block is always empty
+    pub fn exit(&self) -> BlockId {
+        BlockId(1)
+    }
+    /// Every block, a `BlockId` is an index into this
+    pub fn blocks(&self) -> &[BasicBlock<'a, T>] {
+        &self.blocks
+    }
+    pub fn block(&self, id: BlockId) -> &BasicBlock<'a, T> {
+        &self.blocks[id.0]
+    }
+    /// The blocks with an edge to `id` and the kind of that edge
+    pub fn predecessors(&self, id: BlockId) -> Vec<(BlockId, EdgeKind)> {
+        self.blocks
+            .iter()
+            .enumerate()
+            .flat_map(|(from, block)| {
+                block
+                    .edges
+                    .iter()
+                    .filter(move |edge| edge.target == id)
+                    .map(move |edge| (BlockId(from), edge.kind))
+            })
+            .collect()
+    }
+    /// For every block, if it can be reached from the entry
+    pub fn reachable(&self) -> Vec<bool> {
+        let mut seen = vec![false; self.blocks.len()];
+        let mut stack = vec![self.entry()];
+        while let Some(id) = stack.pop() {
+            if std::mem::replace(&mut seen[id.0], true) {
+                continue;
+            }
+            stack.extend(self.blocks[id.0].edges.iter().map(|edge| edge.target));
+        }
+        seen
+    }
+    /// The blocks with nodes in them that can not be reached from the
+    /// entry, like the code after a `return`
+    pub fn unreachable(&self) -> Vec<BlockId> {
+        self.reachable()
+            .into_iter()
+            .enumerate()
+            .filter(|(id, reachable)| !reachable && !self.blocks[*id].nodes.is_empty())
+            .map(|(id, _)| BlockId(id))
+            .collect()
+    }
+}
+
+/// The code a `Cfg` was built for
+#[derive(Debug, PartialEq)]
+pub enum CfgOwner<'a, T> {
+    Program(&'a Program<T>),
+    Func(&'a Func<T>),
+    Arrow(&'a ArrowFuncExpr<T>),
+}
+
+impl<T> Clone for CfgOwner<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for CfgOwner<'_, T> {}
+
+/// The index of a block in `Cfg::blocks`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlockId(pub(crate) usize);
+
+impl BlockId {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// A run of nodes that always execute together
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasicBlock<'a, T> {
+    pub nodes: Vec<CfgNode<'a, T>>,
+    /// Where control can go after the last node
+    pub edges: Vec<Edge>,
+}
+
+/// A step in a `BasicBlock`
+#[derive(Debug, PartialEq)]
+pub enum CfgNode<'a, T> {
+    Expr(&'a Expr<T>),
+    /// A `return`, `throw`, `break`, `continue`, `debugger`, empty or
+    /// invalid statement, or the head of a for-in or for-of loop that
+    /// takes the next element
+    Stmt(&'a Stmt<T>),
+    /// A declaration other than a variable declaration
+    Decl(&'a Decl<T>),
+    /// A single variable declarator, after its initializer
+    VarDecl(&'a VarDecl<T>),
+    /// The parameter of a `catch` clause, or the target of a logical
+    /// assignment being read
+    Pat(&'a Pat<T>),
+}
+
+impl<T> Clone for CfgNode<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for CfgNode<'_, T> {}
+
+/// A way for control to move from one block to another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    pub target: BlockId,
+    pub kind: EdgeKind,
+}
+
+/// Why control takes an `Edge`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EdgeKind {
+    /// Falling through to the next block
+    Normal,
+    /// The last node was truthy, or a for-in or for-of loop has
+    /// another element
+    True,
+    /// The last node was falsy, or a for-in or for-of loop is done
+    False,
+    /// The last node was `null` or `undefined`, for `??`, `??=` and
+    /// optional chains
+    Nullish,
+    /// The last node was not `null` or `undefined`
+    NonNullish,
+    Break,
+    Continue,
+    Return,
+    /// A `throw` statement
+    Throw,
+    /// Any other node throwing inside of a `try`
+    Exception,
+}
+
+/// Where `break` and `continue` go
+struct Target<'a, T> {
+    labels: Vec<&'a T>,
+    /// If an unlabeled `break` can target this, false for a labeled
+    /// statement that is not a loop or a switch
+    breakable: bool,
+    break_to: BlockId,
+    /// Only loops can be continued
+    continue_to: Option<BlockId>,
+    /// The number of handlers around the target
+    depth: usize,
+}
+
+enum Handler {
+    Catch(BlockId),
+    /// An index into `Builder::finalizers`
+    Finally(usize),
+}
+
+/// A `finally` clause being built and the ways out of its `try` that
+/// run it, each with the block that starts its copy of the clause
+struct Finalizer {
+    pending: Vec<(Pending, BlockId)>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct Pending {
+    to: Continuation,
+    kind: EdgeKind,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Continuation {
+    /// A jump to a block outside the `try`, with the depth of its target
+    Jump(BlockId, usize),
+    /// An exception for the handlers outside the `try`
+    Throw,
+}
+
+struct Builder<'a, T> {
+    blocks: Vec<BasicBlock<'a, T>>,
+    current: BlockId,
+    targets: Vec<Target<'a, T>>,
+    /// The labels of a labeled statement whose loop has not started yet
+    labels: Vec<&'a T>,
+    handlers: Vec<Handler>,
+    finalizers: Vec<Finalizer>,
+    /// Where the optional chains being built short circuit to
+    chains: Vec<BlockId>,
+}
+
+impl<'a, T> Builder<'a, T>
+where
+    T: AsRef<str>,
+{
+    fn new() -> Self {
+        let mut builder = Builder {
+            blocks: Vec::new(),
+            current: BlockId(0),
+            targets: Vec::new(),
+            labels: Vec::new(),
+            handlers: Vec::new(),
+            finalizers: Vec::new(),
+            chains: Vec::new(),
+        };
+        let entry = builder.new_block();
+        builder.new_block();
+        builder.current = entry;
+        builder
+    }
+
+    fn finish(mut self, owner: CfgOwner<'a, T>) -> Cfg<'a, T> {
+        self.goto(BlockId(1));
+        Cfg {
+            owner,
+            blocks: self.blocks,
+        }
+    }
+
+    fn new_block(&mut self) -> BlockId {
+        self.blocks.push(BasicBlock {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        });
+        BlockId(self.blocks.len() - 1)
+    }
+
+    fn edge(&mut self, from: BlockId, target: BlockId, kind: EdgeKind) {
+        let edge = Edge { target, kind };
+        let edges = &mut self.blocks[from.0].edges;
+        if !edges.contains(&edge) {
+            edges.push(edge);
+        }
+    }
+
+    fn goto(&mut self, target: BlockId) {
+        self.edge(self.current, target, EdgeKind::Normal);
+    }
+
+    /// End the current block with a branch on its last node
+    fn branch(&mut self, (yes, yes_kind): (BlockId, EdgeKind), (no, no_kind): (BlockId, EdgeKind)) {
+        self.edge(self.current, yes, yes_kind);
+        self.edge(self.current, no, no_kind);
+    }
+
+    fn node(&mut self, node: CfgNode<'a, T>) {
+        self.blocks[self.current.0].nodes.push(node);
+        let can_throw = match node {
+            CfgNode::Expr(_) => true,
+            CfgNode::Stmt(stmt) => matches!(stmt, Stmt::ForIn(_) | Stmt::ForOf(_)),
+            CfgNode::Decl(decl) => matches!(decl, Decl::Class(_) | Decl::Export(_)),
+            CfgNode::VarDecl(decl) => decl.init.is_some() || !matches!(decl.id, Pat::Ident(_)),
+            CfgNode::Pat(pat) => !matches!(pat, Pat::Ident(_)),
+        };
+        let caught = self.blocks[self.current.0]
+            .edges
+            .iter()
+            .any(|edge| edge.kind == EdgeKind::Exception);
+        if can_throw && !caught && !self.handlers.is_empty() {
+            self.throw(EdgeKind::Exception);
+        }
+    }
+
+    /// Send an exception from the current block to the innermost handler
+    fn throw(&mut self, kind: EdgeKind) {
+        match self.handlers.last() {
+            Some(Handler::Catch(catch)) => self.edge(self.current, *catch, kind),
+            Some(Handler::Finally(index)) => {
+                let entry = self.pending(
+                    *index,
+                    Pending {
+                        to: Continuation::Throw,
+                        kind,
+                    },
+                );
+                self.edge(self.current, entry, kind);
+            }
+            None => self.edge(self.current, BlockId(1), kind),
+        }
+    }
+
+    /// Jump from the current block to `target`, through every `finally`
+    /// clause between here and there
+    fn jump(&mut self, target: BlockId, kind: EdgeKind, depth: usize) {
+        let finally = self.handlers[depth..]
+            .iter()
+            .rev()
+            .find_map(|handler| match handler {
+                Handler::Finally(index) => Some(*index),
+                Handler::Catch(_) => None,
+            });
+        match finally {
+            Some(index) => {
+                let entry = self.pending(
+                    index,
+                    Pending {
+                        to: Continuation::Jump(target, depth),
+                        kind,
+                    },
+                );
+                self.edge(self.current, entry, kind);
+            }
+            None => self.edge(self.current, target, kind),
+        }
+    }
+
+    /// The block that starts the copy of a `finally` clause for `pending`
+    fn pending(&mut self, index: usize, pending: Pending) -> BlockId {
+        let existing = self.finalizers[index]
+            .pending
+            .iter()
+            .find(|(other, _)| *other == pending)
+            .map(|(_, entry)| *entry);
+        existing.unwrap_or_else(|| {
+            let entry = self.new_block();
+            self.finalizers[index].pending.push((pending, entry));
+            entry
+        })
+    }
+
+    /// Leave the current block after a jump, anything that follows
+    /// goes in a block nothing reaches
+    fn dead_end(&mut self) {
+        self.current = self.new_block();
+    }
+
+    fn parts(&mut self, parts: &'a [ProgramPart<T>]) {
+        for part in parts {
+            match part {
+                ProgramPart::Dir(_) => {}
+                ProgramPart::Decl(decl) => self.decl(decl),
+                ProgramPart::Stmt(stmt) => self.stmt(stmt),
+            }
+        }
+    }
+
+    fn decl(&mut self, decl: &'a Decl<T>) {
+        match decl {
+            Decl::Var(_, decls) => self.var_decls(decls),
+            _ => self.node(CfgNode::Decl(decl)),
+        }
+    }
+
+    fn var_decls(&mut self, decls: &'a [VarDecl<T>]) {
+        for decl in decls {
+            if let Some(init) = &decl.init {
+                if has_branch(init) {
+                    self.expr(init);
+                }
+            }
+            self.node(CfgNode::VarDecl(decl));
+        }
+    }
+
+    fn stmt(&mut self, stmt: &'a Stmt<T>) {
+        match stmt {
+            Stmt::Expr(expr) => self.expr(expr),
+            Stmt::Block(block) => self.parts(&block.0),
+            Stmt::Empty | Stmt::Debugger | Stmt::Invalid(_) => self.node(CfgNode::Stmt(stmt)),
+            Stmt::With(with) => {
+                self.expr(&with.object);
+                self.stmt(&with.body);
+            }
+            Stmt::Return(arg) => {
+                if let Some(arg) = arg {
+                    self.expr(arg);
+                }
+                self.node(CfgNode::Stmt(stmt));
+                self.jump(BlockId(1), EdgeKind::Return, 0);
+                self.dead_end();
+            }
+            Stmt::Throw(arg) => {
+                self.expr(arg);
+                self.node(CfgNode::Stmt(stmt));
+                self.throw(EdgeKind::Throw);
+                self.dead_end();
+            }
+            Stmt::Labeled(labeled) => {
+                self.labels.push(&labeled.label.name);
+                match &*labeled.body {
+                    Stmt::Labeled(_)
+                    | Stmt::Switch(_)
+                    | Stmt::While(_)
+                    | Stmt::DoWhile(_)
+                    | Stmt::For(_)
+                    | Stmt::ForIn(_)
+                    | Stmt::ForOf(_) => self.stmt(&labeled.body),
+                    body => {
+                        let after = self.new_block();
+                        let labels = std::mem::take(&mut self.labels);
+                        self.targets.push(Target {
+                            labels,
+                            breakable: false,
+                            break_to: after,
+                            continue_to: None,
+                            depth: self.handlers.len(),
+                        });
+                        self.stmt(body);
+                        self.targets.pop();
+                        self.goto(after);
+                        self.current = after;
+                    }
+                }
+            }
+            Stmt::Break(label) => {
+                self.node(CfgNode::Stmt(stmt));
+                let target = self
+                    .target(label.as_ref().map(|label| &label.name), false)
+                    .map(|target| (target.break_to, target.depth));
+                if let Some((to, depth)) = target {
+                    self.jump(to, EdgeKind::Break, depth);
+                }
+                self.dead_end();
+            }
+            Stmt::Continue(label) => {
+                self.node(CfgNode::Stmt(stmt));
+                let target = self
+                    .target(label.as_ref().map(|label| &label.name), true)
+                    .and_then(|target| Some((target.continue_to?, target.depth)));
+                if let Some((to, depth)) = target {
+                    self.jump(to, EdgeKind::Continue, depth);
+                }
+                self.dead_end();
+            }
+            Stmt::If(stmt) => {
+                let consequent = self.new_block();
+                let after = self.new_block();
+                let alternate = if stmt.alternate.is_some() {
+                    self.new_block()
+                } else {
+                    after
+                };
+                self.condition(&stmt.test, consequent, alternate);
+                self.current = consequent;
+                self.stmt(&stmt.consequent);
+                self.goto(after);
+                if let Some(stmt) = &stmt.alternate {
+                    self.current = alternate;
+                    self.stmt(stmt);
+                    self.goto(after);
+                }
+                self.current = after;
+            }
+            Stmt::Switch(switch) => self.switch(switch),
+            Stmt::Try(stmt) => self.try_stmt(stmt),
+            Stmt::While(stmt) => {
+                let labels = std::mem::take(&mut self.labels);
+                let test = self.new_block();
+                let body = self.new_block();
+                let after = self.new_block();
+                self.goto(test);
+                self.current = test;
+                self.condition(&stmt.test, body, after);
+                self.current = body;
+                self.loop_body(&stmt.body, labels, after, test);
+                self.goto(test);
+                self.current = after;
+            }
+            Stmt::DoWhile(stmt) => {
+                let labels = std::mem::take(&mut self.labels);
+                let body = self.new_block();
+                let test = self.new_block();
+                let after = self.new_block();
+                self.goto(body);
+                self.current = body;
+                self.loop_body(&stmt.body, labels, after, test);
+                self.goto(test);
+                self.current = test;
+                self.condition(&stmt.test, body, after);
+                self.current = after;
+            }
+            Stmt::For(stmt) => self.for_stmt(stmt),
+            Stmt::ForIn(for_in) => self.iteration(stmt, &for_in.right, &for_in.body),
+            Stmt::ForOf(for_of) => self.iteration(stmt, &for_of.right, &for_of.body),
+            Stmt::Var(decls) => self.var_decls(decls),
+        }
+    }
+
+    /// The innermost target of a `break` or `continue`
+    fn target(&self, label: Option<&T>, is_continue: bool) -> Option<&Target<'a, T>> {
+        self.targets.iter().rev().find(|target| {
+            if is_continue && target.continue_to.is_none() {
+                return false;
+            }
+            match label {
+                Some(label) => target
+                    .labels
+                    .iter()
+                    .any(|other| other.as_ref() == label.as_ref()),
+                None => target.breakable,
+            }
+        })
+    }
+
+    fn loop_body(
+        &mut self,
+        body: &'a Stmt<T>,
+        labels: Vec<&'a T>,
+        break_to: BlockId,
+        continue_to: BlockId,
+    ) {
+        self.targets.push(Target {
+            labels,
+            breakable: true,
+            break_to,
+            continue_to: Some(continue_to),
+            depth: self.handlers.len(),
+        });
+        self.stmt(body);
+        self.targets.pop();
+    }
+
+    fn for_stmt(&mut self, stmt: &'a ForStmt<T>) {
+        let labels = std::mem::take(&mut self.labels);
+        match &stmt.init {
+            Some(LoopInit::Variable(_, decls)) => self.var_decls(decls),
+            Some(LoopInit::Expr(expr)) => self.expr(expr),
+            None => {}
+        }
+        let test = self.new_block();
+        let body = self.new_block();
+        let update = self.new_block();
+        let after = self.new_block();
+        self.goto(test);
+        self.current = test;
+        match &stmt.test {
+            Some(expr) => self.condition(expr, body, after),
+            None => self.goto(body),
+        }
+        self.current = body;
+        self.loop_body(&stmt.body, labels, after, update);
+        self.goto(update);
+        self.current = update;
+        if let Some(expr) = &stmt.update {
+            self.expr(expr);
+        }
+        self.goto(test);
+        self.current = after;
+    }
+
+    /// A for-in or for-of loop, its head takes the next element and
+    /// assigns the left side
+    fn iteration(&mut self, stmt: &'a Stmt<T>, right: &'a Expr<T>, body: &'a Stmt<T>) {
+        let labels = std::mem::take(&mut self.labels);
+        self.expr(right);
+        let head = self.new_block();
+        let first = self.new_block();
+        let after = self.new_block();
+        self.goto(head);
+        self.current = head;
+        self.node(CfgNode::Stmt(stmt));
+        self.branch((first, EdgeKind::True), (after, EdgeKind::False));
+        self.current = first;
+        self.loop_body(body, labels, after, head);
+        self.goto(head);
+        self.current = after;
+    }
+
+    /// The case tests run in order, skipping `default`, and each case
+    /// body falls through to the next one
+    fn switch(&mut self, switch: &'a SwitchStmt<T>) {
+        let labels = std::mem::take(&mut self.labels);
+        self.expr(&switch.discriminant);
+        let bodies: Vec<_> = switch.cases.iter().map(|_| self.new_block()).collect();
+        let after = self.new_block();
+        for (case, body) in switch.cases.iter().zip(&bodies) {
+            if let Some(test) = &case.test {
+                let next = self.new_block();
+                self.expr(test);
+                self.branch((*body, EdgeKind::True), (next, EdgeKind::False));
+                self.current = next;
+            }
+        }
+        let default = switch
+            .cases
+            .iter()
+            .position(|case| case.test.is_none())
+            .map_or(after, |index| bodies[index]);
+        self.goto(default);
+        self.targets.push(Target {
+            labels,
+            breakable: true,
+            break_to: after,
+            continue_to: None,
+            depth: self.handlers.len(),
+        });
+        for (index, case) in switch.cases.iter().enumerate() {
+            self.current = bodies[index];
+            self.parts(&case.consequent);
+            self.goto(bodies.get(index + 1).copied().unwrap_or(after));
+        }
+        self.targets.pop();
+        self.current = after;
+    }
+
+    fn try_stmt(&mut self, stmt: &'a TryStmt<T>) {
+        let after = self.new_block();
+        let finally = stmt.finalizer.as_ref().map(|_| {
+            self.finalizers.push(Finalizer {
+                pending: Vec::new(),
+            });
+            self.handlers
+                .push(Handler::Finally(self.finalizers.len() - 1));
+            self.new_block()
+        });
+        let done = finally.unwrap_or(after);
+        let catch = stmt.handler.as_ref().map(|_| {
+            let entry = self.new_block();
+            self.handlers.push(Handler::Catch(entry));
+            entry
+        });
+        let block = self.new_block();
+        self.goto(block);
+        self.current = block;
+        self.parts(&stmt.block.0);
+        self.goto(done);
+        if let (Some(entry), Some(handler)) = (catch, &stmt.handler) {
+            self.handlers.pop();
+            self.current = entry;
+            if let Some(param) = &handler.param {
+                self.node(CfgNode::Pat(param));
+            }
+            self.parts(&handler.body.0);
+            self.goto(done);
+        }
+        if let (Some(entry), Some(finalizer)) = (finally, &stmt.finalizer) {
+            self.handlers.pop();
+            let pending = self.finalizers.pop().map(|f| f.pending).unwrap_or_default();
+            for (pending, start) in pending {
+                self.current = start;
+                self.parts(&finalizer.0);
+                match pending.to {
+                    Continuation::Jump(target, depth) => self.jump(target, pending.kind, depth),
+                    Continuation::Throw => self.throw(pending.kind),
+                }
+            }
+            self.current = entry;
+            self.parts(&finalizer.0);
+            self.goto(after);
+        }
+        self.current = after;
+    }
+
+    /// Evaluate `test` and branch on it
+    fn condition(&mut self, test: &'a Expr<T>, yes: BlockId, no: BlockId) {
+        self.expr(test);
+        self.branch((yes, EdgeKind::True), (no, EdgeKind::False));
+    }
+
+    fn expr(&mut self, expr: &'a Expr<T>) {
+        if !has_branch(expr) {
+            return self.node(CfgNode::Expr(expr));
+        }
+        if let Expr::Assign(assign) = expr {
+            if is_logical_assign(assign.operator) {
+                return self.logical_assign(expr, assign);
+            }
+        }
+        self.operands(expr);
+        self.node(CfgNode::Expr(expr));
+    }
+
+    /// Record the operands of `expr` in evaluation order
+    fn operands(&mut self, expr: &'a Expr<T>) {
+        match expr {
+            Expr::Logical(logical) => self.logical(logical),
+            Expr::Conditional(conditional) => {
+                let consequent = self.new_block();
+                let alternate = self.new_block();
+                let after = self.new_block();
+                self.condition(&conditional.test, consequent, alternate);
+                self.current = consequent;
+                self.expr(&conditional.consequent);
+                self.goto(after);
+                self.current = alternate;
+                self.expr(&conditional.alternate);
+                self.goto(after);
+                self.current = after;
+            }
+            Expr::OptionalChain(inner) => {
+                let end = self.new_block();
+                self.chains.push(end);
+                self.expr(inner);
+                self.chains.pop();
+                self.goto(end);
+                self.current = end;
+            }
+            Expr::Member(member) => {
+                self.expr(&member.object);
+                if matches!(
+                    member.indexer,
+                    MemberIndexer::Optional | MemberIndexer::OptionalComputed
+                ) {
+                    self.short_circuit();
+                }
+                if matches!(
+                    member.indexer,
+                    MemberIndexer::Computed | MemberIndexer::OptionalComputed
+                ) {
+                    self.expr(&member.property);
+                }
+            }
+            Expr::Call(call) => {
+                self.expr(&call.callee);
+                if call.optional {
+                    self.short_circuit();
+                }
+                self.exprs(&call.arguments);
+            }
+            Expr::New(new) => {
+                self.expr(&new.callee);
+                self.exprs(&new.arguments);
+            }
+            Expr::Array(elements) => {
+                for element in elements.iter().flatten() {
+                    self.expr(element);
+                }
+            }
+            Expr::Obj(props) => {
+                for prop in props {
+                    match prop {
+                        ObjProp::Prop(prop) => {
+                            if let (true, PropKey::Expr(key)) = (prop.computed, &prop.key) {
+                                self.expr(key);
+                            }
+                            if let PropValue::Expr(value) = &prop.value {
+                                self.expr(value);
+                            }
+                        }
+                        ObjProp::Spread(expr) => self.expr(expr),
+                    }
+                }
+            }
+            Expr::Assign(assign) => {
+                if let AssignLeft::Expr(left) = &assign.left {
+                    self.operands(left);
+                }
+                self.expr(&assign.right);
+            }
+            Expr::Binary(binary) => {
+                self.expr(&binary.left);
+                self.expr(&binary.right);
+            }
+            Expr::Await(arg) | Expr::Spread(arg) => self.expr(arg),
+            Expr::Unary(unary) => self.expr(&unary.argument),
+            Expr::Update(update) => self.expr(&update.argument),
+            Expr::Yield(yield_expr) => {
+                if let Some(arg) = &yield_expr.argument {
+                    self.expr(arg);
+                }
+            }
+            Expr::Sequence(exprs) => self.exprs(exprs),
+            Expr::TaggedTemplate(tagged) => {
+                self.expr(&tagged.tag);
+                self.exprs(&tagged.quasi.expressions);
+            }
+            Expr::Lit(Lit::Template(template)) => self.exprs(&template.expressions),
+            _ => {}
+        }
+    }
+
+    fn exprs(&mut self, exprs: &'a [Expr<T>]) {
+        for expr in exprs {
+            self.expr(expr);
+        }
+    }
+
+    fn logical(&mut self, logical: &'a LogicalExpr<T>) {
+        self.expr(&logical.left);
+        let right = self.new_block();
+        let after = self.new_block();
+        self.branch_logical(logical.operator, right, after);
+        self.current = right;
+        self.expr(&logical.right);
+        self.goto(after);
+        self.current = after;
+    }
+
+    /// `a ||= b` only assigns when `b` is evaluated, so the assignment
+    /// is recorded on that path
+    fn logical_assign(&mut self, expr: &'a Expr<T>, assign: &'a AssignExpr<T>) {
+        match &assign.left {
+            AssignLeft::Expr(left) => self.expr(left),
+            AssignLeft::Pat(pat) => self.node(CfgNode::Pat(pat)),
+        }
+        let right = self.new_block();
+        let after = self.new_block();
+        let operator = match assign.operator {
+            AssignOp::DoubleAmpersandEqual => LogicalOp::And,
+            AssignOp::DoublePipeEqual => LogicalOp::Or,
+            _ => LogicalOp::NullishCoalescing,
+        };
+        self.branch_logical(operator, right, after);
+        self.current = right;
+        self.expr(&assign.right);
+        self.node(CfgNode::Expr(expr));
+        self.goto(after);
+        self.current = after;
+    }
+
+    /// Branch to `right` when the operator needs its right side
+    fn branch_logical(&mut self, operator: LogicalOp, right: BlockId, after: BlockId) {
+        match operator {
+            LogicalOp::And => self.branch((right, EdgeKind::True), (after, EdgeKind::False)),
+            LogicalOp::Or => self.branch((after, EdgeKind::True), (right, EdgeKind::False)),
+            LogicalOp::NullishCoalescing => {
+                self.branch((right, EdgeKind::Nullish), (after, EdgeKind::NonNullish))
+            }
+        }
+    }
+
+    /// A `?.` that skips the rest of its chain on `null` or `undefined`
+    fn short_circuit(&mut self) {
+        if let Some(end) = self.chains.last().copied() {
+            let next = self.new_block();
+            self.branch((end, EdgeKind::Nullish), (next, EdgeKind::NonNullish));
+            self.current = next;
+        }
+    }
+}
+
+fn is_logical_assign(operator: AssignOp) -> bool {
+    matches!(
+        operator,
+        AssignOp::DoubleAmpersandEqual
+            | AssignOp::DoublePipeEqual
+            | AssignOp::DoubleQuestionmarkEqual
+    )
+}
+
+/// If `expr` has a node that branches, outside of nested functions
+/// and classes
+fn has_branch<T>(expr: &Expr<T>) -> bool {
+    let mut finder = BranchFinder { found: false };
+    finder.visit_expr(expr);
+    finder.found
+}
+
+struct BranchFinder {
+    found: bool,
+}
+
+impl<'a, T: 'a> Visit<'a, T> for BranchFinder {
+    fn visit_expr(&mut self, expr: &'a Expr<T>) {
+        if self.found {
+            return;
+        }
+        match expr {
+            Expr::Logical(_) | Expr::Conditional(_) => self.found = true,
+            Expr::Assign(assign) if is_logical_assign(assign.operator) => self.found = true,
+            Expr::Member(member)
+                if matches!(
+                    member.indexer,
+                    MemberIndexer::Optional | MemberIndexer::OptionalComputed
+                ) =>
+            {
+                self.found = true
+            }
+            Expr::Call(call) if call.optional => self.found = true,
+            Expr::ArrowFunc(_) => {}
+            _ => walk_expr(self, expr),
+        }
+    }
+
+    fn visit_func(&mut self, _func: &'a Func<T>) {}
+
+    fn visit_class(&mut self, _class: &'a Class<T>) {}
+}
+
+struct Collect<'a, T> {
+    cfgs: Vec<Cfg<'a, T>>,
+}
+
+impl<'a, T> Visit<'a, T> for Collect<'a, T>
+where
+    T: AsRef<str>,
+{
+    fn visit_func(&mut self, func: &'a Func<T>) {
+        self.cfgs.push(self::func(func));
+        walk_func(self, func);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr<T>) {
+        if let Expr::ArrowFunc(arrow) = expr {
+            self.cfgs.push(self::arrow(arrow));
+        }
+        walk_expr(self, expr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::CallExpr;
+    use crate::stmt::{BlockStmt, CatchClause, IfStmt, WhileStmt};
+    use crate::transform::build::{arrow, call, expr_stmt, ident, ident_expr};
+    use crate::FuncBody;
+
+    fn run(name: &str) -> ProgramPart<String> {
+        expr_stmt(call(ident_expr(name), Vec::new()))
+    }
+
+    fn call_of(name: &str) -> Expr<String> {
+        call(ident_expr(name), Vec::new())
+    }
+
+    fn block(parts: Vec<ProgramPart<String>>) -> Stmt<String> {
+        Stmt::Block(BlockStmt(parts))
+    }
+
+    fn if_stmt(
+        test: &str,
+        consequent: Stmt<String>,
+        alternate: Option<Stmt<String>>,
+    ) -> Stmt<String> {
+        Stmt::If(IfStmt {
+            test: call_of(test),
+            consequent: Box::new(consequent),
+            alternate: alternate.map(Box::new),
+        })
+    }
+
+    fn func_decl(name: &str, body: Vec<ProgramPart<String>>) -> Func<String> {
+        Func {
+            id: Some(ident(name)),
+            type_params: Vec::new(),
+            params: Vec::new(),
+            return_type: None,
+            body: Some(FuncBody(body)),
+            generator: false,
+            is_async: false,
+        }
+    }
+
+    /// The names of the functions called by the nodes of a block
+    fn calls<'a>(graph: &Cfg<'a, String>, id: BlockId) -> Vec<&'a str> {
+        graph
+            .block(id)
+            .nodes
+            .iter()
+            .filter_map(|node| match node {
+                CfgNode::Expr(Expr::Call(CallExpr { callee, .. })) => match &**callee {
+                    Expr::Ident(ident) => Some(ident.name.as_str()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The blocks that call `name`
+    fn blocks_calling(graph: &Cfg<'_, String>, name: &str) -> Vec<BlockId> {
+        (0..graph.blocks().len())
+            .map(BlockId)
+            .filter(|id| calls(graph, *id).contains(&name))
+            .collect()
+    }
+
+    fn calling(graph: &Cfg<'_, String>, name: &str) -> BlockId {
+        let blocks = blocks_calling(graph, name);
+        assert_eq!(blocks.len(), 1, "{} is called in {:?}", name, blocks);
+        blocks[0]
+    }
+
+    fn edges(graph: &Cfg<'_, String>, id: BlockId) -> Vec<(BlockId, EdgeKind)> {
+        graph
+            .block(id)
+            .edges
+            .iter()
+            .map(|edge| (edge.target, edge.kind))
+            .collect()
+    }
+
+    #[test]
+    fn if_else_branches() {
+        let program = Program::script(vec![
+            ProgramPart::Stmt(if_stmt(
+                "ready",
+                block(vec![run("start")]),
+                Some(block(vec![run("wait")])),
+            )),
+            run("done"),
+        ]);
+        let graph = self::program(&program);
+        let test = calling(&graph, "ready");
+        let (start, wait, done) = (
+            calling(&graph, "start"),
+            calling(&graph, "wait"),
+            calling(&graph, "done"),
+        );
+        assert_eq!(test, graph.entry());
+        assert_eq!(
+            edges(&graph, test),
+            vec![(start, EdgeKind::True), (wait, EdgeKind::False)]
+        );
+        assert_eq!(edges(&graph, start), vec![(done, EdgeKind::Normal)]);
+        assert_eq!(edges(&graph, wait), vec![(done, EdgeKind::Normal)]);
+        assert_eq!(edges(&graph, done), vec![(graph.exit(), EdgeKind::Normal)]);
+        assert!(graph.block(graph.exit()).nodes.is_empty());
+    }
+
+    #[test]
+    fn code_after_return_is_unreachable() {
+        let func = func_decl(
+            "f",
+            vec![
+                run("first"),
+                ProgramPart::Stmt(Stmt::Return(None)),
+                run("dead"),
+            ],
+        );
+        let graph = self::func(&func);
+        let first = calling(&graph, "first");
+        assert_eq!(edges(&graph, first), vec![(graph.exit(), EdgeKind::Return)]);
+        assert_eq!(graph.unreachable(), vec![calling(&graph, "dead")]);
+    }
+
+    #[test]
+    fn loops_break_and_continue() {
+        let program = Program::script(vec![
+            ProgramPart::Stmt(Stmt::While(WhileStmt {
+                test: call_of("more"),
+                body: Box::new(block(vec![
+                    ProgramPart::Stmt(if_stmt("stop", Stmt::Break(None), None)),
+                    ProgramPart::Stmt(if_stmt("skip", Stmt::Continue(None), None)),
+                    run("step"),
+                ])),
+            })),
+            run("done"),
+        ]);
+        let graph = self::program(&program);
+        let (more, stop, skip, step, done) = (
+            calling(&graph, "more"),
+            calling(&graph, "stop"),
+            calling(&graph, "skip"),
+            calling(&graph, "step"),
+            calling(&graph, "done"),
+        );
+        assert_eq!(edges(&graph, more)[1], (done, EdgeKind::False));
+        assert_eq!(edges(&graph, step), vec![(more, EdgeKind::Normal)]);
+        let kinds = |from: BlockId| -> Vec<EdgeKind> {
+            let (target, _) = edges(&graph, from)[0];
+            edges(&graph, target)
+                .into_iter()
+                .map(|(_, kind)| kind)
+                .collect()
+        };
+        assert_eq!(kinds(stop), vec![EdgeKind::Break]);
+        assert_eq!(kinds(skip), vec![EdgeKind::Continue]);
+        assert!(graph.unreachable().is_empty());
+    }
+
+    #[test]
+    fn logical_operands_are_split() {
+        let program = Program::script(vec![
+            expr_stmt(Expr::Logical(LogicalExpr {
+                operator: LogicalOp::And,
+                left: Box::new(call_of("check")),
+                right: Box::new(call_of("act")),
+            })),
+            run("done"),
+        ]);
+        let graph = self::program(&program);
+        let (check, act) = (calling(&graph, "check"), calling(&graph, "act"));
+        let (right, kind) = edges(&graph, check)[0];
+        assert_eq!((right, kind), (act, EdgeKind::True));
+        let (after, kind) = edges(&graph, check)[1];
+        assert_eq!(kind, EdgeKind::False);
+        // the `&&` itself is evaluated where both paths meet
+        assert!(matches!(
+            graph.block(after).nodes[..],
+            [CfgNode::Expr(Expr::Logical(_)), ..]
+        ));
+        assert_eq!(edges(&graph, act), vec![(after, EdgeKind::Normal)]);
+    }
+
+    #[test]
+    fn try_blocks_throw_to_their_handlers() {
+        let program = Program::script(vec![ProgramPart::Stmt(Stmt::Try(TryStmt {
+            block: BlockStmt(vec![run("risky")]),
+            handler: Some(CatchClause {
+                param: Some(Pat::Ident(ident("error"))),
+                body: BlockStmt(vec![run("handle")]),
+            }),
+            finalizer: None,
+        }))]);
+        let graph = self::program(&program);
+        let (risky, handle) = (calling(&graph, "risky"), calling(&graph, "handle"));
+        assert!(edges(&graph, risky).contains(&(handle, EdgeKind::Exception)));
+        assert!(matches!(
+            graph.block(handle).nodes[0],
+            CfgNode::Pat(Pat::Ident(_))
+        ));
+    }
+
+    #[test]
+    fn finally_is_built_for_every_exit() {
+        let func = func_decl(
+            "f",
+            vec![ProgramPart::Stmt(Stmt::Try(TryStmt {
+                block: BlockStmt(vec![
+                    ProgramPart::Stmt(if_stmt("early", Stmt::Return(None), None)),
+                    run("work"),
+                ]),
+                handler: None,
+                finalizer: Some(BlockStmt(vec![run("cleanup")])),
+            }))],
+        );
+        let graph = self::func(&func);
+        let cleanups = blocks_calling(&graph, "cleanup");
+        assert!(cleanups.len() >= 3, "{:?}", cleanups);
+        // the copy after the `return` keeps returning
+        assert!(cleanups
+            .iter()
+            .any(|id| edges(&graph, *id) == vec![(graph.exit(), EdgeKind::Return)]));
+        // and the one for an exception keeps throwing
+        assert!(cleanups
+            .iter()
+            .any(|id| edges(&graph, *id) == vec![(graph.exit(), EdgeKind::Exception)]));
+    }
+
+    #[test]
+    fn all_graphs_in_source_order() {
+        let program = Program::script(vec![
+            ProgramPart::Decl(Decl::Func(func_decl("outer", vec![run("inner")]))),
+            expr_stmt(arrow(&[], call_of("body"))),
+        ]);
+        let graphs = all(&program);
+        assert_eq!(graphs.len(), 3);
+        assert!(matches!(graphs[0].owner(), CfgOwner::Program(_)));
+        assert!(matches!(graphs[1].owner(), CfgOwner::Func(_)));
+        assert!(matches!(graphs[2].owner(), CfgOwner::Arrow(_)));
+        assert_eq!(blocks_calling(&graphs[1], "inner").len(), 1);
+        assert_eq!(blocks_calling(&graphs[2], "body").len(), 1);
+        // nested functions are a single node of the program
+        assert!(blocks_calling(&graphs[0], "inner").is_empty());
+    }
+}
@@ -0,0 +1,572 @@
+//! Reaching definitions and the use-def chains built from them
+//!
+//! ```js
+//! let x = 1;
+//! if (flag) {
+//!     x = 2;
+//! }
+//! use(x);
+//! ```
+//! both `x = 1` and `x = 2` reach `use(x)`, so that read has two
+//! definitions while each definition has the one use.
+//!
+//! A definition is a reference that writes its binding or, for the
+//! bindings that have a value before any code in their function runs,
+//! the value at the entry: parameters, function and class declarations,
+//! imports and `var`s that start out as `undefined`.
+//!
+//! The flow follows the control flow graph of the function that
+//! declares a binding. A function can be called at any time, so a read
+//! inside of a closure is reached by every definition of its binding,
+//! and a binding that is written inside of a closure can change at any
+//! point of its own function too.
+//!
+//! Within a single node of the control flow graph the references are
+//! taken in evaluation order, see `cfg` for how coarse those nodes are.
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::analysis::cfg::{self, BlockId, Cfg, CfgNode, CfgOwner};
+use crate::analysis::scope::{BindingId, BindingKind, ReferenceId, ScopeId, ScopeKind, ScopeTree};
+use crate::analysis::visit::{walk_decl, walk_expr, walk_jsx_element, walk_prop, Visit};
+use crate::decl::{Decl, ModExport, ModuleExportName, NamedExportDecl};
+use crate::expr::{Expr, Prop, PropKey, PropValue};
+use crate::jsx::{JsxElement, JsxElementName};
+use crate::stmt::{LoopLeft, Stmt};
+use crate::{Class, ClassMember, Func, Ident, Program};
+
+/// Find the reaching definitions of every read in `program`, `scopes`
+/// must come from `scope::analyze` of the same program
+pub fn analyze<'a, T>(program: &'a Program<T>, scopes: &ScopeTree<'a, T>) -> DefUse
+where
+    T: AsRef<str>,
+{
+    let binding_count = scopes.bindings().len();
+    let mut definitions = vec![Vec::new(); binding_count];
+    for (index, binding) in scopes.bindings().iter().enumerate() {
+        if has_entry_value(binding.kind) {
+            definitions[index].push(Def::Entry(BindingId(index)));
+        }
+    }
+    // writes from outside of the function that declares the binding
+    let mut foreign = vec![Vec::new(); binding_count];
+    for (index, reference) in scopes.references().iter().enumerate() {
+        let binding = match reference.binding {
+            Some(binding) => binding,
+            None => continue,
+        };
+        if reference.kind.is_write() {
+            let def = Def::Ref(ReferenceId(index));
+            definitions[binding.index()].push(def);
+            if !is_local(scopes, binding, reference.scope) {
+                foreign[binding.index()].push(def);
+            }
+        }
+    }
+    let mut reaching: Vec<Vec<Def>> = scopes
+        .references()
+        .iter()
+        .map(|reference| match reference.binding {
+            Some(binding) if reference.kind.is_read() => definitions[binding.index()].clone(),
+            _ => Vec::new(),
+        })
+        .collect();
+
+    let owners: HashMap<usize, ScopeId> = scopes
+        .scopes()
+        .iter()
+        .enumerate()
+        .filter_map(|(index, scope)| Some((owner_address(scope.kind)?, ScopeId(index))))
+        .collect();
+    // reads inside of a graph of their own function, everything else
+    // keeps every definition
+    let mut flowed: HashMap<ReferenceId, BTreeSet<Def>> = HashMap::new();
+    for graph in cfg::all(program) {
+        let owner = match owners.get(&cfg_owner_address(graph.owner())) {
+            Some(owner) => *owner,
+            None => continue,
+        };
+        let flow = Flow {
+            scopes,
+            owner,
+            foreign: &foreign,
+        };
+        flow.solve(&graph, &mut flowed);
+    }
+    for (id, defs) in flowed {
+        reaching[id.index()] = defs.into_iter().collect();
+    }
+
+    let mut uses: HashMap<Def, Vec<ReferenceId>> = HashMap::new();
+    for (index, defs) in reaching.iter_mut().enumerate() {
+        defs.sort();
+        defs.dedup();
+        for def in defs.iter() {
+            uses.entry(*def).or_default().push(ReferenceId(index));
+        }
+    }
+    DefUse {
+        definitions,
+        reaching,
+        uses,
+    }
+}
+
+/// The result of `analyze`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefUse {
+    /// Every definition of each binding
+    definitions: Vec<Vec<Def>>,
+    /// The definitions that reach each reference, empty for writes
+    reaching: Vec<Vec<Def>>,
+    uses: HashMap<Def, Vec<ReferenceId>>,
+}
+
+impl DefUse {
+    /// The definitions that can provide the value `read` sees, this is
+    /// empty for references that only write and for globals
+    pub fn definitions(&self, read: ReferenceId) -> &[Def] {
+        &self.reaching[read.index()]
+    }
+    /// The reads that can see the value of `def`
+    pub fn uses(&self, def: Def) -> &[ReferenceId] {
+        self.uses.get(&def).map_or(&[], Vec::as_slice)
+    }
+    /// Every definition of `binding`
+    pub fn definitions_of(&self, binding: BindingId) -> &[Def] {
+        &self.definitions[binding.index()]
+    }
+}
+
+/// Somewhere a binding gets a value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Def {
+    /// The value at the start of the binding's function
+    Entry(BindingId),
+    /// A reference that writes the binding
+    Ref(ReferenceId),
+}
+
+fn has_entry_value(kind: BindingKind) -> bool {
+    matches!(
+        kind,
+        BindingKind::Var
+            | BindingKind::Func
+            | BindingKind::Class
+            | BindingKind::Param
+            | BindingKind::Import
+            | BindingKind::Enum
+    )
+}
+
+/// If code in `scope` runs as part of the function that declares `binding`
+fn is_local<T>(scopes: &ScopeTree<'_, T>, binding: BindingId, scope: ScopeId) -> bool
+where
+    T: AsRef<str>,
+{
+    let declared = scopes.binding(binding).scope;
+    scopes.function_scope(declared) == scopes.function_scope(scope)
+}
+
+fn owner_address<T>(kind: ScopeKind<'_, T>) -> Option<usize> {
+    match kind {
+        ScopeKind::Program(program) => Some(program as *const _ as usize),
+        ScopeKind::Func(func) => Some(func as *const _ as usize),
+        ScopeKind::Arrow(arrow) => Some(arrow as *const _ as usize),
+        _ => None,
+    }
+}
+
+fn cfg_owner_address<T>(owner: CfgOwner<'_, T>) -> usize {
+    match owner {
+        CfgOwner::Program(program) => program as *const _ as usize,
+        CfgOwner::Func(func) => func as *const _ as usize,
+        CfgOwner::Arrow(arrow) => arrow as *const _ as usize,
+    }
+}
+
+/// Reaching definitions through one control flow graph
+struct Flow<'s, 'a, T> {
+    scopes: &'s ScopeTree<'a, T>,
+    /// The function scope the graph belongs to
+    owner: ScopeId,
+    foreign: &'s [Vec<Def>],
+}
+
+impl<'a, T> Flow<'_, 'a, T>
+where
+    T: AsRef<str>,
+{
+    fn solve(&self, graph: &Cfg<'a, T>, flowed: &mut HashMap<ReferenceId, BTreeSet<Def>>) {
+        let split: HashSet<usize> = graph
+            .blocks()
+            .iter()
+            .flat_map(|block| &block.nodes)
+            .filter_map(|node| match node {
+                CfgNode::Expr(expr) => Some(*expr as *const Expr<T> as usize),
+                _ => None,
+            })
+            .collect();
+        let events: Vec<Vec<ReferenceId>> = graph
+            .blocks()
+            .iter()
+            .map(|block| {
+                block
+                    .nodes
+                    .iter()
+                    .flat_map(|node| self.node_references(*node, &split))
+                    .collect()
+            })
+            .collect();
+
+        let entry: BTreeSet<Def> = self
+            .scopes
+            .bindings()
+            .iter()
+            .enumerate()
+            .filter(|(_, binding)| {
+                has_entry_value(binding.kind)
+                    && self.scopes.function_scope(binding.scope) == self.owner
+            })
+            .map(|(index, _)| Def::Entry(BindingId(index)))
+            .collect();
+        let count = graph.blocks().len();
+        let mut inputs = vec![BTreeSet::new(); count];
+        let mut outputs = vec![BTreeSet::new(); count];
+        inputs[graph.entry().index()] = entry.clone();
+        let mut work: Vec<usize> = (0..count).collect();
+        while let Some(index) = work.pop() {
+            let mut input = if index == graph.entry().index() {
+                entry.clone()
+            } else {
+                BTreeSet::new()
+            };
+            for (from, _) in graph.predecessors(BlockId(index)) {
+                input.extend(outputs[from.index()].iter().copied());
+            }
+            let mut output = input.clone();
+            self.transfer(&events[index], &mut output, None);
+            inputs[index] = input;
+            if output != outputs[index] {
+                outputs[index] = output;
+                for edge in &graph.blocks()[index].edges {
+                    if !work.contains(&edge.target.index()) {
+                        work.push(edge.target.index());
+                    }
+                }
+            }
+        }
+        for (index, input) in inputs.into_iter().enumerate() {
+            let mut state = input;
+            self.transfer(&events[index], &mut state, Some(flowed));
+        }
+    }
+
+    /// Replay the references of a block over `state`, recording what
+    /// each read sees when `flowed` is given
+    fn transfer(
+        &self,
+        events: &[ReferenceId],
+        state: &mut BTreeSet<Def>,
+        mut flowed: Option<&mut HashMap<ReferenceId, BTreeSet<Def>>>,
+    ) {
+        for id in events {
+            let reference = self.scopes.reference(*id);
+            let binding = match reference.binding {
+                Some(binding) => binding,
+                None => continue,
+            };
+            if reference.kind.is_read() {
+                if let Some(flowed) = flowed.as_deref_mut() {
+                    // a node copied into more than one block, like the
+                    // body of a `finally`, sees the union of its copies
+                    let seen = flowed.entry(*id).or_default();
+                    seen.extend(
+                        state
+                            .iter()
+                            .filter(|def| self.binding_of(**def) == Some(binding)),
+                    );
+                    seen.extend(self.foreign[binding.index()].iter().copied());
+                }
+            }
+            if reference.kind.is_write() {
+                state.retain(|def| self.binding_of(*def) != Some(binding));
+                state.insert(Def::Ref(*id));
+            }
+        }
+    }
+
+    fn binding_of(&self, def: Def) -> Option<BindingId> {
+        match def {
+            Def::Entry(binding) => Some(binding),
+            Def::Ref(id) => self.scopes.reference(id).binding,
+        }
+    }
+
+    /// The references made by `node` to bindings of this graph's
+    /// function, in evaluation order
+    fn node_references(&self, node: CfgNode<'a, T>, split: &HashSet<usize>) -> Vec<ReferenceId> {
+        let mut gather = Gather {
+            scopes: self.scopes,
+            split,
+            root: 0,
+            found: Vec::new(),
+        };
+        match node {
+            CfgNode::Expr(expr) => {
+                gather.root = expr as *const Expr<T> as usize;
+                gather.visit_expr(expr);
+            }
+            CfgNode::Stmt(Stmt::ForIn(stmt)) => gather.loop_left(&stmt.left),
+            CfgNode::Stmt(Stmt::ForOf(stmt)) => gather.loop_left(&stmt.left),
+            CfgNode::Stmt(_) => {}
+            CfgNode::Decl(decl) => gather.visit_decl(decl),
+            CfgNode::VarDecl(decl) => {
+                if let Some(init) = &decl.init {
+                    gather.visit_expr(init);
+                }
+                gather.visit_pat(&decl.id);
+            }
+            CfgNode::Pat(pat) => gather.visit_pat(pat),
+        }
+        let mut found = gather.found;
+        found.sort();
+        found.retain(|id| {
+            let reference = self.scopes.reference(*id);
+            reference.binding.is_some_and(|binding| {
+                let declared = self.scopes.binding(binding).scope;
+                self.scopes.function_scope(declared) == self.owner
+                    && self.scopes.function_scope(reference.scope) == self.owner
+            })
+        });
+        found
+    }
+}
+
+/// Collects the references in a node, without entering the operands
+/// that are nodes of their own, nested functions or class members
+struct Gather<'s, 'a, T> {
+    scopes: &'s ScopeTree<'a, T>,
+    split: &'s HashSet<usize>,
+    /// The address of the expression being gathered
+    root: usize,
+    found: Vec<ReferenceId>,
+}
+
+impl<'a, T> Gather<'_, 'a, T>
+where
+    T: AsRef<str>,
+{
+    fn loop_left(&mut self, left: &'a LoopLeft<T>) {
+        match left {
+            LoopLeft::Variable(_, decl) => self.visit_pat(&decl.id),
+            LoopLeft::Pat(pat) => self.visit_pat(pat),
+            LoopLeft::Expr(expr) => self.visit_expr(expr),
+        }
+    }
+}
+
+impl<'a, T> Visit<'a, T> for Gather<'_, 'a, T>
+where
+    T: AsRef<str>,
+{
+    fn visit_decl(&mut self, decl: &'a Decl<T>) {
+        if let Decl::Export(export) = decl {
+            if let ModExport::Named(NamedExportDecl::Specifier(specifiers, None, _)) = &**export {
+                for specifier in specifiers {
+                    if let ModuleExportName::Ident(local) = &specifier.local {
+                        self.visit_ident(local);
+                    }
+                }
+                return;
+            }
+        }
+        walk_decl(self, decl);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr<T>) {
+        let address = expr as *const Expr<T> as usize;
+        if address != self.root && self.split.contains(&address) {
+            return;
+        }
+        match expr {
+            Expr::ArrowFunc(_) => {}
+            _ => walk_expr(self, expr),
+        }
+    }
+
+    fn visit_func(&mut self, _func: &'a Func<T>) {}
+
+    /// The decorators, the super class and the computed keys are
+    /// evaluated where the class is, its members run later
+    fn visit_class(&mut self, class: &'a Class<T>) {
+        for decorator in &class.decorators {
+            self.visit_expr(&decorator.expr);
+        }
+        if let Some(super_class) = &class.super_class {
+            self.visit_expr(super_class);
+        }
+        for member in &class.body.0 {
+            let (decorators, computed, key) = match member {
+                ClassMember::Prop(prop) => (&prop.decorators, prop.computed, &prop.key),
+                ClassMember::Field(field) => (&field.decorators, field.computed, &field.key),
+                ClassMember::StaticBlock(_) => continue,
+            };
+            for decorator in decorators {
+                self.visit_expr(&decorator.expr);
+            }
+            match key {
+                PropKey::Expr(key) if computed => self.visit_expr(key),
+                PropKey::Pat(key) if computed => self.visit_pat(key),
+                _ => {}
+            }
+        }
+    }
+
+    fn visit_prop(&mut self, prop: &'a Prop<T>) {
+        match (&prop.value, &prop.key) {
+            (PropValue::None, PropKey::Expr(key)) if prop.short_hand => self.visit_expr(key),
+            (PropValue::None, PropKey::Pat(key)) if prop.short_hand => self.visit_pat(key),
+            _ => walk_prop(self, prop),
+        }
+    }
+
+    fn visit_jsx_element(&mut self, element: &'a JsxElement<T>) {
+        if let JsxElementName::Ident(ident) = &element.name {
+            self.visit_ident(ident);
+        }
+        walk_jsx_element(self, element);
+    }
+
+    fn visit_ident(&mut self, ident: &'a Ident<T>) {
+        if let Some(id) = self.scopes.reference_of(ident) {
+            self.found.push(id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::scope;
+    use crate::expr::Lit;
+    use crate::stmt::{BlockStmt, IfStmt};
+    use crate::transform::build::{assign, call, expr_stmt, ident, ident_expr, var};
+    use crate::{ClassBody, FuncBody, ProgramPart, VarKind};
+
+    fn number(raw: &str) -> Expr<String> {
+        Expr::Lit(Lit::Number(raw.to_string()))
+    }
+
+    fn class(name: &str, super_class: Expr<String>) -> ProgramPart<String> {
+        ProgramPart::Decl(Decl::Class(Class {
+            id: Some(ident(name)),
+            type_params: Vec::new(),
+            super_class: Some(Box::new(super_class)),
+            super_type_args: Vec::new(),
+            implements: Vec::new(),
+            body: ClassBody(Vec::new()),
+            is_abstract: false,
+            decorators: Vec::new(),
+        }))
+    }
+
+    fn func(name: &str, body: Vec<ProgramPart<String>>) -> ProgramPart<String> {
+        ProgramPart::Decl(Decl::Func(Func {
+            id: Some(ident(name)),
+            type_params: Vec::new(),
+            params: Vec::new(),
+            return_type: None,
+            body: Some(FuncBody(body)),
+            generator: false,
+            is_async: false,
+        }))
+    }
+
+    fn use_x() -> ProgramPart<String> {
+        expr_stmt(call(ident_expr("use"), vec![ident_expr("x")]))
+    }
+
+    /// The references to `x`, in source order
+    fn references(scopes: &ScopeTree<'_, String>) -> Vec<ReferenceId> {
+        (0..scopes.references().len())
+            .map(ReferenceId)
+            .filter(|id| scopes.reference(*id).ident.name == "x")
+            .collect()
+    }
+
+    #[test]
+    fn branches_merge_their_definitions() {
+        let program = Program::script(vec![
+            var(VarKind::Let, vec![("x", Some(number("1")))]),
+            ProgramPart::Stmt(Stmt::If(IfStmt {
+                test: ident_expr("flag"),
+                consequent: Box::new(Stmt::Block(BlockStmt(vec![expr_stmt(assign(
+                    ident_expr("x"),
+                    number("2"),
+                ))]))),
+                alternate: None,
+            })),
+            use_x(),
+        ]);
+        let scopes = scope::analyze(&program);
+        let flow = analyze(&program, &scopes);
+        let references = references(&scopes);
+        let (init, write, read) = (references[0], references[1], references[2]);
+        assert_eq!(flow.definitions(read), &[Def::Ref(init), Def::Ref(write)]);
+        assert_eq!(flow.uses(Def::Ref(init)), &[read]);
+        assert_eq!(flow.uses(Def::Ref(write)), &[read]);
+        assert!(flow.definitions(write).is_empty());
+    }
+
+    #[test]
+    fn writes_kill_earlier_definitions() {
+        let program = Program::script(vec![
+            var(VarKind::Let, vec![("x", Some(number("1")))]),
+            expr_stmt(assign(ident_expr("x"), number("2"))),
+            class("C", ident_expr("x")),
+            use_x(),
+        ]);
+        let scopes = scope::analyze(&program);
+        let flow = analyze(&program, &scopes);
+        let references = references(&scopes);
+        let (init, write, heritage, read) =
+            (references[0], references[1], references[2], references[3]);
+        assert_eq!(flow.definitions(heritage), &[Def::Ref(write)]);
+        assert_eq!(flow.definitions(read), &[Def::Ref(write)]);
+        assert!(flow.uses(Def::Ref(init)).is_empty());
+    }
+
+    #[test]
+    fn closures_see_every_definition() {
+        let program = Program::script(vec![
+            var(VarKind::Let, vec![("x", Some(number("1")))]),
+            func("f", vec![use_x()]),
+            expr_stmt(assign(ident_expr("x"), number("2"))),
+        ]);
+        let scopes = scope::analyze(&program);
+        let flow = analyze(&program, &scopes);
+        let references = references(&scopes);
+        let (init, read, write) = (references[0], references[1], references[2]);
+        assert_eq!(flow.definitions(read), &[Def::Ref(init), Def::Ref(write)]);
+    }
+
+    #[test]
+    fn writes_in_closures_reach_every_read() {
+        let program = Program::script(vec![
+            var(VarKind::Var, vec![("x", Some(number("1")))]),
+            func("f", vec![expr_stmt(assign(ident_expr("x"), number("2")))]),
+            use_x(),
+        ]);
+        let scopes = scope::analyze(&program);
+        let flow = analyze(&program, &scopes);
+        let references = references(&scopes);
+        let (init, write, read) = (references[0], references[1], references[2]);
+        let binding = scopes.reference(read).binding.unwrap();
+        // the declaration replaces the `undefined` at the entry
+        assert_eq!(flow.definitions(read), &[Def::Ref(init), Def::Ref(write)]);
+        assert_eq!(
+            flow.definitions_of(binding),
+            &[Def::Entry(binding), Def::Ref(init), Def::Ref(write)]
+        );
+    }
+}
@@ -0,0 +1,7 @@
+pub mod capture;
+pub mod cfg;
+pub mod dataflow;
+pub mod purity;
+pub mod scope;
+pub mod strict;
+pub mod visit;
@@ -0,0 +1,730 @@
+//! Which expressions may have a side effect
+//!
+//! ```js
+//! const limit = Math.max(min, 10);
+//! const view = /* @__PURE__ */ createView(load());
+//! const size = config.size;
+//! ```
+//! with `Math.max` as a known pure global, the call to it is pure, it
+//! has no side effect and reading `min` has none either. The annotated
+//! call to `createView` is pure if its arguments are pure, and since
+//! `load()` is impure so is the declaration's value: removing it has to
+//! keep the call to `load`. `config.size` is impure, `size` could be a
+//! getter.
+//!
+//! An expression is classified by what evaluating it does on its own
+//! and by the expressions it evaluates: the arguments of a call, the
+//! operands of an operator or the elements of an array or object. It
+//! is impure when it may do something on its own
+//! - calling or constructing anything other than a known pure global
+//!   or an annotated call, or spreading arguments into one
+//! - reading a property of anything other than a known pure global,
+//!   which may run a getter or throw on `null`
+//! - assignments, updates, `delete`, `await`, `yield`, tagged
+//!   templates, dynamic imports and JSX
+//! - an operator that converts an object operand to a primitive, which
+//!   calls its `valueOf` or `toString`, or that throws, like `in`,
+//!   `instanceof` or mixing a bigint with a number
+//! - reading a global that is not known, which throws when it is not
+//!   defined, except as the operand of `typeof`
+//! - spreading into an array or object
+//! - a class with a super class, decorators or a static block
+//!
+//! Reading a declared binding is taken to be pure, even before its
+//! declaration runs. Function bodies are not evaluated when the function
+//! is, so a function expression is pure whatever its body does.
+//!
+//! The tree does not keep comments, so the annotated calls are given in
+//! the options.
+use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
+
+use crate::analysis::scope::ScopeTree;
+use crate::analysis::visit::{walk_expr, walk_prop, Visit};
+use crate::expr::{Expr, Lit, ObjProp, Prop, PropKey, PropValue};
+use crate::pat::Pat;
+use crate::{BinaryOp, Class, ClassMember, Ident, MemberIndexer, Program, UnaryOp};
+
+/// Classify every expression in `program`, `scopes` must come from
+/// `scope::analyze` of the same program
+pub fn analyze<'a, T>(
+    program: &'a Program<T>,
+    scopes: &ScopeTree<'a, T>,
+    options: &PurityOptions<'a, T>,
+) -> Purities<'a, T>
+where
+    T: AsRef<str>,
+{
+    let mut classify = Classify {
+        scopes,
+        options,
+        annotated: options
+            .annotated
+            .iter()
+            .map(|expr| address(*expr))
+            .collect(),
+        by_expr: HashMap::new(),
+    };
+    classify.visit_program(program);
+    Purities {
+        by_expr: classify.by_expr,
+        program: PhantomData,
+    }
+}
+
+/// What the analysis can not tell from the tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct PurityOptions<'a, T> {
+    /// Globals that can be read, and called or constructed when they
+    /// are functions, without a side effect, as paths like `Math.max`.
+    /// The objects along a path can be read as well.
+    pub pure_globals: Vec<String>,
+    /// The calls and `new` expressions marked with a `/* @__PURE__ */`
+    /// or `/* #__PURE__ */` comment
+    pub annotated: Vec<&'a Expr<T>>,
+}
+
+impl<T> Default for PurityOptions<'_, T> {
+    fn default() -> Self {
+        PurityOptions {
+            pure_globals: ["undefined", "NaN", "Infinity"]
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            annotated: Vec::new(),
+        }
+    }
+}
+
+/// The result of `analyze`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Purities<'a, T> {
+    by_expr: HashMap<usize, Purity>,
+    program: PhantomData<&'a Expr<T>>,
+}
+
+impl<T> Purities<'_, T> {
+    /// Look up an expression by identity, one that is not part of the
+    /// analyzed program is impure
+    pub fn purity(&self, expr: &Expr<T>) -> Purity {
+        self.by_expr
+            .get(&address(expr))
+            .copied()
+            .unwrap_or(Purity::Impure)
+    }
+    /// The purity of defining `class`, for a class declaration which is
+    /// not an expression
+    pub fn class(&self, class: &Class<T>) -> Purity {
+        if is_pure_class(class) {
+            combine(
+                class_operands(class)
+                    .into_iter()
+                    .map(|operand| self.purity(operand)),
+            )
+        } else {
+            Purity::Impure
+        }
+    }
+    /// If `expr` can be removed without changing what the program does
+    pub fn is_pure(&self, expr: &Expr<T>) -> bool {
+        self.purity(expr) == Purity::Pure
+    }
+    /// What has to be kept of `expr` when its value is not used, in
+    /// evaluation order: nothing when it is pure, the side effects of
+    /// its operands when only they are impure and otherwise `expr`
+    /// itself. A conditional, logical or optional chain expression is
+    /// kept whole, the operands it skips must stay skipped.
+    pub fn side_effects<'e>(&self, expr: &'e Expr<T>) -> Vec<&'e Expr<T>> {
+        match self.purity(expr) {
+            Purity::Pure => Vec::new(),
+            Purity::PureIfArgsPure
+                if !matches!(
+                    expr,
+                    Expr::Conditional(_) | Expr::Logical(_) | Expr::OptionalChain(_)
+                ) =>
+            {
+                operands(expr)
+                    .into_iter()
+                    .flat_map(|operand| self.side_effects(operand))
+                    .collect()
+            }
+            _ => vec![expr],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Purity {
+    /// Evaluating it has no side effect
+    Pure,
+    /// Evaluating it has no side effect of its own, only those of the
+    /// arguments or operands it evaluates
+    PureIfArgsPure,
+    /// Evaluating it may have a side effect
+    Impure,
+}
+
+fn address<T>(expr: &Expr<T>) -> usize {
+    expr as *const Expr<T> as usize
+}
+
+/// The purity of an expression whose own evaluation is pure
+fn combine(operands: impl IntoIterator<Item = Purity>) -> Purity {
+    if operands.into_iter().all(|operand| operand == Purity::Pure) {
+        Purity::Pure
+    } else {
+        Purity::PureIfArgsPure
+    }
+}
+
+/// The expressions evaluated as part of evaluating `expr`, in order
+fn operands<T>(expr: &Expr<T>) -> Vec<&Expr<T>> {
+    match expr {
+        Expr::Lit(Lit::Template(template)) => template.expressions.iter().collect(),
+        Expr::Array(items) => items.iter().flatten().collect(),
+        Expr::Obj(props) => props
+            .iter()
+            .flat_map(|prop| match prop {
+                ObjProp::Prop(prop) => prop_operands(prop, true),
+                ObjProp::Spread(expr) => vec![expr],
+            })
+            .collect(),
+        Expr::Class(class) => class_operands(class),
+        Expr::Unary(unary) => vec![&*unary.argument],
+        Expr::Binary(binary) => vec![&*binary.left, &*binary.right],
+        Expr::Logical(logical) => vec![&*logical.left, &*logical.right],
+        Expr::Conditional(conditional) => vec![
+            &*conditional.test,
+            &*conditional.consequent,
+            &*conditional.alternate,
+        ],
+        Expr::Sequence(exprs) => exprs.iter().collect(),
+        Expr::Call(call) => call.arguments.iter().collect(),
+        Expr::New(new) => new.arguments.iter().collect(),
+        Expr::Member(member) => match member.indexer {
+            MemberIndexer::Computed | MemberIndexer::OptionalComputed => {
+                vec![&*member.object, &*member.property]
+            }
+            MemberIndexer::Period | MemberIndexer::Optional => vec![&*member.object],
+        },
+        Expr::OptionalChain(expr) => vec![&**expr],
+        _ => Vec::new(),
+    }
+}
+
+/// The computed keys and static field values of `class`
+fn class_operands<T>(class: &Class<T>) -> Vec<&Expr<T>> {
+    class
+        .body
+        .0
+        .iter()
+        .flat_map(|member| match member {
+            ClassMember::Prop(prop) => prop_operands(prop, false),
+            ClassMember::Field(field) => {
+                let mut operands = Vec::new();
+                if let (true, PropKey::Expr(key)) = (field.computed, &field.key) {
+                    operands.push(key);
+                }
+                if let (true, Some(value)) = (field.is_static, &field.value) {
+                    operands.push(value);
+                }
+                operands
+            }
+            ClassMember::StaticBlock(_) => Vec::new(),
+        })
+        .collect()
+}
+
+/// The computed key of `prop`, and its value when `with_value` since a
+/// method's value is a function that is not evaluated
+fn prop_operands<T>(prop: &Prop<T>, with_value: bool) -> Vec<&Expr<T>> {
+    let mut operands = Vec::new();
+    if let (true, PropKey::Expr(key)) = (prop.computed || prop.short_hand, &prop.key) {
+        operands.push(key);
+    }
+    if let (true, PropValue::Expr(value)) = (with_value, &prop.value) {
+        operands.push(value);
+    }
+    operands
+}
+
+/// If `expr` always evaluates to a string, number, boolean, `null` or
+/// `undefined`, which operators convert without calling any code or
+/// throwing
+fn is_plain<T>(expr: &Expr<T>) -> bool {
+    match expr {
+        Expr::Lit(Lit::Template(template)) => template.expressions.iter().all(is_plain),
+        Expr::Lit(Lit::BigInt(_) | Lit::RegEx(_)) => false,
+        Expr::Lit(_) => true,
+        Expr::Unary(unary) => match unary.operator {
+            UnaryOp::Minus | UnaryOp::Plus | UnaryOp::Tilde => is_plain(&unary.argument),
+            UnaryOp::Not | UnaryOp::TypeOf | UnaryOp::Void | UnaryOp::Delete => true,
+        },
+        Expr::Binary(binary) => {
+            is_comparison(binary.operator) || { is_plain(&binary.left) && is_plain(&binary.right) }
+        }
+        Expr::Logical(logical) => is_plain(&logical.left) && is_plain(&logical.right),
+        Expr::Conditional(conditional) => {
+            is_plain(&conditional.consequent) && is_plain(&conditional.alternate)
+        }
+        Expr::Sequence(exprs) => exprs.last().is_some_and(is_plain),
+        _ => false,
+    }
+}
+
+/// If `operator` always evaluates to a boolean
+fn is_comparison(operator: BinaryOp) -> bool {
+    matches!(
+        operator,
+        BinaryOp::Equal
+            | BinaryOp::NotEqual
+            | BinaryOp::StrictEqual
+            | BinaryOp::StrictNotEqual
+            | BinaryOp::LessThan
+            | BinaryOp::GreaterThan
+            | BinaryOp::LessThanEqual
+            | BinaryOp::GreaterThanEqual
+            | BinaryOp::In
+            | BinaryOp::InstanceOf
+    )
+}
+
+/// If a computed key converts to a property key without calling any
+/// code
+fn is_plain_key<T>(key: &PropKey<T>) -> bool {
+    match key {
+        PropKey::Lit(_) => true,
+        PropKey::Expr(key) => is_plain(key),
+        PropKey::Pat(_) | PropKey::Private(_) => false,
+    }
+}
+
+struct Classify<'s, 'a, T> {
+    scopes: &'s ScopeTree<'a, T>,
+    options: &'s PurityOptions<'a, T>,
+    annotated: HashSet<usize>,
+    by_expr: HashMap<usize, Purity>,
+}
+
+impl<'a, T> Visit<'a, T> for Classify<'_, 'a, T>
+where
+    T: AsRef<str> + 'a,
+{
+    fn visit_expr(&mut self, expr: &'a Expr<T>) {
+        walk_expr(self, expr);
+        if let Expr::Unary(unary) = expr {
+            // `typeof` does not throw for a global that is not defined
+            if let (UnaryOp::TypeOf, Expr::Ident(_)) = (unary.operator, &*unary.argument) {
+                self.by_expr.insert(address(&unary.argument), Purity::Pure);
+            }
+        }
+        let purity = match expr {
+            Expr::OptionalChain(inner) => self.purity(inner),
+            _ if self.is_pure_itself(expr) => combine(
+                operands(expr)
+                    .into_iter()
+                    .map(|operand| self.purity(operand)),
+            ),
+            _ => Purity::Impure,
+        };
+        self.by_expr.insert(address(expr), purity);
+    }
+
+    /// Shorthand properties read the variable with the same name
+    fn visit_prop(&mut self, prop: &'a Prop<T>) {
+        match (&prop.value, &prop.key) {
+            (PropValue::None, PropKey::Expr(key)) if prop.short_hand => self.visit_expr(key),
+            _ => walk_prop(self, prop),
+        }
+    }
+}
+
+impl<T> Classify<'_, '_, T>
+where
+    T: AsRef<str>,
+{
+    fn purity(&self, expr: &Expr<T>) -> Purity {
+        self.by_expr
+            .get(&address(expr))
+            .copied()
+            .unwrap_or(Purity::Impure)
+    }
+
+    /// If evaluating `expr` has no side effect besides those of its
+    /// operands
+    fn is_pure_itself(&self, expr: &Expr<T>) -> bool {
+        match expr {
+            Expr::Lit(Lit::Template(template)) => template.expressions.iter().all(is_plain),
+            Expr::Lit(_)
+            | Expr::Func(_)
+            | Expr::ArrowFunc(_)
+            | Expr::This
+            | Expr::Super
+            | Expr::MetaProp(_)
+            | Expr::PrivateName(_) => true,
+            Expr::Ident(ident) => self.is_pure_ident(ident),
+            Expr::Array(items) => !items
+                .iter()
+                .flatten()
+                .any(|item| matches!(item, Expr::Spread(_))),
+            Expr::Obj(props) => props.iter().all(|prop| match prop {
+                ObjProp::Prop(prop) => {
+                    prop.decorators.is_empty()
+                        && (!prop.computed || is_plain_key(&prop.key))
+                        && match (&prop.value, &prop.key) {
+                            (PropValue::Pat(_), _) => false,
+                            (PropValue::None, PropKey::Pat(Pat::Ident(key))) => {
+                                self.is_pure_ident(key)
+                            }
+                            _ => true,
+                        }
+                }
+                ObjProp::Spread(_) => false,
+            }),
+            Expr::Class(class) => is_pure_class(class),
+            Expr::Unary(unary) => match unary.operator {
+                UnaryOp::Delete => false,
+                UnaryOp::Not | UnaryOp::TypeOf | UnaryOp::Void => true,
+                UnaryOp::Minus | UnaryOp::Plus | UnaryOp::Tilde => is_plain(&unary.argument),
+            },
+            Expr::Binary(binary) => match binary.operator {
+                BinaryOp::StrictEqual | BinaryOp::StrictNotEqual => true,
+                BinaryOp::In | BinaryOp::InstanceOf => false,
+                _ => is_plain(&binary.left) && is_plain(&binary.right),
+            },
+            Expr::Logical(_) | Expr::Conditional(_) | Expr::Sequence(_) => true,
+            Expr::Member(_) => self.is_pure_global(expr, false),
+            Expr::Call(call) => {
+                (self.annotated.contains(&address(expr)) || self.is_pure_global(&call.callee, true))
+                    && !call
+                        .arguments
+                        .iter()
+                        .any(|arg| matches!(arg, Expr::Spread(_)))
+            }
+            Expr::New(new) => {
+                (self.annotated.contains(&address(expr)) || self.is_pure_global(&new.callee, true))
+                    && !new
+                        .arguments
+                        .iter()
+                        .any(|arg| matches!(arg, Expr::Spread(_)))
+            }
+            _ => false,
+        }
+    }
+
+    /// A declared binding, or a known global
+    fn is_pure_ident(&self, ident: &Ident<T>) -> bool {
+        !self.is_global(ident) || self.is_known(ident.name.as_ref(), false)
+    }
+
+    fn is_global(&self, ident: &Ident<T>) -> bool {
+        match self.scopes.reference_of(ident) {
+            Some(reference) => self.scopes.reference(reference).binding.is_none(),
+            None => true,
+        }
+    }
+
+    /// If `expr` is one of the known pure globals or, unless `exact`,
+    /// an object on the path to one
+    fn is_pure_global(&self, expr: &Expr<T>, exact: bool) -> bool {
+        match self.path(expr) {
+            Some(path) => self.is_known(&path, exact),
+            None => false,
+        }
+    }
+
+    fn is_known(&self, path: &str, exact: bool) -> bool {
+        self.options.pure_globals.iter().any(|known| {
+            known == path
+                || !exact && known.starts_with(path) && known[path.len()..].starts_with('.')
+        })
+    }
+
+    /// The dotted path of a global or a property of one
+    fn path(&self, expr: &Expr<T>) -> Option<String> {
+        match expr {
+            Expr::Ident(ident) if self.is_global(ident) => Some(ident.name.as_ref().to_string()),
+            Expr::Member(member) => match (member.indexer, &*member.property) {
+                (MemberIndexer::Period, Expr::Ident(property)) => {
+                    let mut path = self.path(&member.object)?;
+                    path.push('.');
+                    path.push_str(property.name.as_ref());
+                    Some(path)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// If defining `class` runs no code besides its computed keys and
+/// static field values
+fn is_pure_class<T>(class: &Class<T>) -> bool {
+    class.decorators.is_empty()
+        && class.super_class.is_none()
+        && class.body.0.iter().all(|member| match member {
+            ClassMember::Prop(prop) => {
+                prop.decorators.is_empty() && (!prop.computed || is_plain_key(&prop.key))
+            }
+            ClassMember::Field(field) => {
+                field.decorators.is_empty() && (!field.computed || is_plain_key(&field.key))
+            }
+            ClassMember::StaticBlock(body) => body.0.is_empty(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::scope;
+    use crate::decl::Decl;
+    use crate::expr::{BinaryExpr, NewExpr, UnaryExpr};
+    use crate::transform::build::{arrow, call, expr_stmt, ident, ident_expr, member, string, var};
+    use crate::{ClassBody, ClassField, ProgramPart, VarKind};
+
+    fn number(raw: &str) -> Expr<String> {
+        Expr::Lit(Lit::Number(raw.to_string()))
+    }
+
+    fn binary(operator: BinaryOp, left: Expr<String>, right: Expr<String>) -> Expr<String> {
+        Expr::Binary(BinaryExpr {
+            operator,
+            left: Box::new(left),
+            right: Box::new(right),
+        })
+    }
+
+    fn type_of(argument: Expr<String>) -> Expr<String> {
+        Expr::Unary(UnaryExpr {
+            operator: UnaryOp::TypeOf,
+            prefix: true,
+            argument: Box::new(argument),
+        })
+    }
+
+    /// `let a, b;` followed by a statement for each of `exprs`
+    fn program(exprs: Vec<Expr<String>>) -> Program<String> {
+        let mut parts = vec![var(VarKind::Let, vec![("a", None), ("b", None)])];
+        parts.extend(exprs.into_iter().map(expr_stmt));
+        Program::script(parts)
+    }
+
+    fn statements(program: &Program<String>) -> Vec<&Expr<String>> {
+        let parts = match program {
+            Program::Mod(parts, _) | Program::Script(parts, _) => parts,
+        };
+        parts
+            .iter()
+            .filter_map(|part| match part {
+                ProgramPart::Stmt(crate::stmt::Stmt::Expr(expr)) => Some(expr),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The purity of each expression statement of `program`, the
+    /// statements at `annotated` are annotated calls
+    fn classify(
+        program: &Program<String>,
+        pure_globals: &[&str],
+        annotated: &[usize],
+    ) -> Vec<Purity> {
+        let scopes = scope::analyze(program);
+        let exprs = statements(program);
+        let mut options = PurityOptions::default();
+        options
+            .pure_globals
+            .extend(pure_globals.iter().map(|name| name.to_string()));
+        options.annotated = annotated.iter().map(|index| exprs[*index]).collect();
+        let purities = analyze(program, &scopes, &options);
+        exprs.iter().map(|expr| purities.purity(expr)).collect()
+    }
+
+    #[test]
+    fn reads_of_bindings_and_globals() {
+        let program = program(vec![
+            number("1"),
+            ident_expr("a"),
+            ident_expr("undefined"),
+            ident_expr("missing"),
+            type_of(ident_expr("missing")),
+            // the body of a function is not evaluated
+            arrow(&[], call(ident_expr("f"), Vec::new())),
+            Expr::Array(vec![
+                Some(ident_expr("a")),
+                None,
+                Some(ident_expr("missing")),
+            ]),
+        ]);
+        assert_eq!(
+            classify(&program, &[], &[]),
+            vec![
+                Purity::Pure,
+                Purity::Pure,
+                Purity::Pure,
+                Purity::Impure,
+                Purity::Pure,
+                Purity::Pure,
+                Purity::PureIfArgsPure,
+            ]
+        );
+    }
+
+    #[test]
+    fn operators_that_may_convert_or_throw() {
+        let program = program(vec![
+            binary(BinaryOp::Plus, number("1"), string("a")),
+            // `a` may be an object with a `valueOf`
+            binary(BinaryOp::Plus, ident_expr("a"), number("1")),
+            binary(BinaryOp::StrictEqual, ident_expr("a"), ident_expr("b")),
+            binary(BinaryOp::In, string("x"), ident_expr("a")),
+            binary(
+                BinaryOp::Minus,
+                binary(BinaryOp::LessThan, ident_expr("a"), ident_expr("b")),
+                number("1"),
+            ),
+        ]);
+        assert_eq!(
+            classify(&program, &[], &[]),
+            vec![
+                Purity::Pure,
+                Purity::Impure,
+                Purity::Pure,
+                Purity::Impure,
+                // the comparison is plain but it converts `a` and `b`
+                Purity::PureIfArgsPure,
+            ]
+        );
+    }
+
+    #[test]
+    fn calls_and_property_reads() {
+        let calls = program(vec![
+            call(
+                member(ident_expr("Math"), "max"),
+                vec![ident_expr("a"), number("1")],
+            ),
+            call(
+                member(ident_expr("Math"), "max"),
+                vec![ident_expr("missing")],
+            ),
+            member(ident_expr("Math"), "PI"),
+            member(ident_expr("a"), "size"),
+            call(ident_expr("create"), vec![ident_expr("a")]),
+            Expr::New(NewExpr {
+                callee: Box::new(ident_expr("View")),
+                arguments: vec![call(ident_expr("load"), Vec::new())],
+            }),
+            call(ident_expr("create"), Vec::new()),
+        ]);
+        assert_eq!(
+            classify(&calls, &["Math.max", "Math.PI"], &[5]),
+            vec![
+                Purity::Pure,
+                Purity::PureIfArgsPure,
+                Purity::Pure,
+                Purity::Impure,
+                Purity::Impure,
+                Purity::PureIfArgsPure,
+                Purity::Impure,
+            ]
+        );
+        // a path to a known global can be read but not called
+        let paths = program(vec![
+            ident_expr("Math"),
+            call(ident_expr("Math"), Vec::new()),
+        ]);
+        assert_eq!(
+            classify(&paths, &["Math.max"], &[]),
+            vec![Purity::Pure, Purity::Impure]
+        );
+    }
+
+    #[test]
+    fn side_effects_of_operands() {
+        let program = program(vec![
+            Expr::Array(vec![
+                Some(call(ident_expr("f"), Vec::new())),
+                Some(Expr::Array(vec![
+                    Some(ident_expr("a")),
+                    Some(call(ident_expr("g"), Vec::new())),
+                ])),
+            ]),
+            Expr::Conditional(crate::expr::ConditionalExpr {
+                test: Box::new(ident_expr("a")),
+                consequent: Box::new(call(ident_expr("f"), Vec::new())),
+                alternate: Box::new(number("0")),
+            }),
+        ]);
+        let scopes = scope::analyze(&program);
+        let purities = analyze(&program, &scopes, &PurityOptions::default());
+        let exprs = statements(&program);
+        let array = match exprs[0] {
+            Expr::Array(items) => items,
+            _ => unreachable!(),
+        };
+        let inner = match &array[1] {
+            Some(Expr::Array(items)) => items,
+            _ => unreachable!(),
+        };
+        let effects = purities.side_effects(exprs[0]);
+        assert_eq!(effects.len(), 2);
+        assert!(std::ptr::eq(effects[0], array[0].as_ref().unwrap()));
+        assert!(std::ptr::eq(effects[1], inner[1].as_ref().unwrap()));
+        // the call is only evaluated when `a` is truthy
+        let effects = purities.side_effects(exprs[1]);
+        assert_eq!(effects.len(), 1);
+        assert!(std::ptr::eq(effects[0], exprs[1]));
+        // an expression that is not part of the program
+        assert!(!purities.is_pure(&number("1")));
+    }
+
+    fn class(super_class: Option<&str>, static_value: Expr<String>) -> Class<String> {
+        Class {
+            id: Some(ident("A")),
+            type_params: Vec::new(),
+            super_class: super_class.map(|name| Box::new(ident_expr(name))),
+            super_type_args: Vec::new(),
+            implements: Vec::new(),
+            body: ClassBody(vec![ClassMember::Field(ClassField {
+                key: PropKey::Expr(ident_expr("x")),
+                value: Some(static_value),
+                computed: false,
+                is_static: true,
+                decorators: Vec::new(),
+                accessibility: None,
+                is_readonly: false,
+                is_optional: false,
+                type_annotation: None,
+            })]),
+            is_abstract: false,
+            decorators: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn class_definitions() {
+        let program = Program::script(vec![
+            ProgramPart::Decl(Decl::Class(class(None, number("1")))),
+            ProgramPart::Decl(Decl::Class(class(None, call(ident_expr("f"), Vec::new())))),
+            ProgramPart::Decl(Decl::Class(class(Some("Base"), number("1")))),
+        ]);
+        let scopes = scope::analyze(&program);
+        let purities = analyze(&program, &scopes, &PurityOptions::default());
+        let purities: Vec<Purity> = match &program {
+            Program::Script(parts, _) => parts
+                .iter()
+                .map(|part| match part {
+                    ProgramPart::Decl(Decl::Class(class)) => purities.class(class),
+                    _ => unreachable!(),
+                })
+                .collect(),
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            purities,
+            vec![Purity::Pure, Purity::PureIfArgsPure, Purity::Impure]
+        );
+    }
+}
@@ -0,0 +1,1017 @@
+//! Scopes, the bindings declared in them and the references to those
+//! bindings
+//!
+//! ```js
+//! let count = 0;
+//! function bump(by = 1) {
+//!     count += by;
+//! }
+//! ```
+//! has a program scope with the bindings `count` and `bump` and a
+//! function scope with `by`. The `count` in `bump` resolves to the
+//! outer binding as a read and write, `by` to the parameter as a read.
+//!
+//! Every identifier that reads or writes a binding is a `Reference`,
+//! including the name in a declaration that gives it a value, so
+//! `let x = 1` has an `Init` reference to `x` while `var x;` has none.
+//! References to names that are not declared anywhere, like globals,
+//! have no binding.
+//!
+//! Some parts of the language are not covered
+//! - parameters share a scope with the function body, so a parameter
+//!   default can see the body's declarations
+//! - `arguments` is not declared and resolves like a global
+//! - `with` statements are ignored
+//! - `declare` statements bind their names but are not visited
+use std::collections::HashMap;
+
+use crate::analysis::visit::{walk_decl, walk_expr, walk_jsx_element, walk_prop, walk_stmt, Visit};
+use crate::decl::{
+    Decl, DefaultExportDecl, ImportSpecifier, ModExport, ModuleExportName, ModuleRef,
+    NamedExportDecl, VarDecl,
+};
+use crate::expr::{
+    ArrowFuncBody, ArrowFuncExpr, AssignLeft, Expr, ObjProp, Prop, PropKey, PropValue,
+};
+use crate::jsx::{JsxElement, JsxElementName};
+use crate::pat::{ArrayPatPart, ObjPatPart, Pat};
+use crate::stmt::{LoopInit, LoopLeft, Stmt};
+use crate::{AssignOp, Class, ClassMember, Func, FuncArg, Ident, Program, ProgramPart, VarKind};
+
+/// Find every scope, binding and reference in `program`
+pub fn analyze<T>(program: &Program<T>) -> ScopeTree<'_, T>
+where
+    T: AsRef<str>,
+{
+    let mut resolver = Resolver {
+        tree: ScopeTree {
+            scopes: Vec::new(),
+            bindings: Vec::new(),
+            references: Vec::new(),
+            by_ident: HashMap::new(),
+        },
+        current: ScopeId(0),
+        mode: PatMode::Declare,
+    };
+    resolver.visit_program(program);
+    resolver.tree
+}
+
+/// The result of `analyze`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScopeTree<'a, T> {
+    scopes: Vec<Scope<'a, T>>,
+    bindings: Vec<Binding<'a, T>>,
+    references: Vec<Reference<'a, T>>,
+    /// The address of each identifier that is a reference
+    by_ident: HashMap<usize, ReferenceId>,
+}
+
+impl<'a, T> ScopeTree<'a, T>
+where
+    T: AsRef<str>,
+{
+    /// The program scope
+    pub fn root(&self) -> ScopeId {
+        ScopeId(0)
+    }
+    /// Every scope, parents before their children
+    pub fn scopes(&self) -> &[Scope<'a, T>] {
+        &self.scopes
+    }
+    pub fn scope(&self, id: ScopeId) -> &Scope<'a, T> {
+        &self.scopes[id.0]
+    }
+    /// Every binding in the order it was declared
+    pub fn bindings(&self) -> &[Binding<'a, T>] {
+        &self.bindings
+    }
+    pub fn binding(&self, id: BindingId) -> &Binding<'a, T> {
+        &self.bindings[id.0]
+    }
+    /// Every reference in evaluation order, so the value written by an
+    /// assignment comes after the reads on its right side
+    pub fn references(&self) -> &[Reference<'a, T>] {
+        &self.references
+    }
+    pub fn reference(&self, id: ReferenceId) -> &Reference<'a, T> {
+        &self.references[id.0]
+    }
+    /// The reference made by `ident`, if it is one
+    pub fn reference_of(&self, ident: &Ident<T>) -> Option<ReferenceId> {
+        self.by_ident.get(&address(ident)).copied()
+    }
+    /// The references that resolve to `binding`
+    pub fn references_to(&self, binding: BindingId) -> Vec<ReferenceId> {
+        self.references
+            .iter()
+            .enumerate()
+            .filter(|(_, reference)| reference.binding == Some(binding))
+            .map(|(id, _)| ReferenceId(id))
+            .collect()
+    }
+    /// The references that do not resolve to any binding, like globals
+    pub fn unresolved(&self) -> Vec<ReferenceId> {
+        self.references
+            .iter()
+            .enumerate()
+            .filter(|(_, reference)| reference.binding.is_none())
+            .map(|(id, _)| ReferenceId(id))
+            .collect()
+    }
+    /// The binding `name` refers to from inside of `scope`
+    pub fn lookup(&self, scope: ScopeId, name: &str) -> Option<BindingId> {
+        let mut scope = Some(scope);
+        while let Some(id) = scope {
+            let found = self.scopes[id.0]
+                .bindings
+                .iter()
+                .find(|binding| self.bindings[binding.0].name.as_ref() == name);
+            if found.is_some() {
+                return found.copied();
+            }
+            scope = self.scopes[id.0].parent;
+        }
+        None
+    }
+    /// The closest program, function, arrow function or class scope
+    /// around `scope`, code in different ones of these can run at
+    /// different times
+    pub fn function_scope(&self, mut scope: ScopeId) -> ScopeId {
+        while !self.scopes[scope.0].kind.is_function() {
+            match self.scopes[scope.0].parent {
+                Some(parent) => scope = parent,
+                None => break,
+            }
+        }
+        scope
+    }
+    /// If `binding` is written anywhere after it is declared
+    /// ```js
+    /// let a = 1; // not reassigned
+    /// let b = 1; b += 1; // reassigned
+    /// ```
+    pub fn is_reassigned(&self, binding: BindingId) -> bool {
+        self.references.iter().any(|reference| {
+            reference.binding == Some(binding)
+                && matches!(reference.kind, RefKind::Write | RefKind::ReadWrite)
+        })
+    }
+}
+
+fn address<T>(ident: &Ident<T>) -> usize {
+    ident as *const Ident<T> as usize
+}
+
+/// The index of a scope in `ScopeTree::scopes`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ScopeId(pub(crate) usize);
+
+impl ScopeId {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// The index of a binding in `ScopeTree::bindings`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BindingId(pub(crate) usize);
+
+impl BindingId {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// The index of a reference in `ScopeTree::references`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ReferenceId(pub(crate) usize);
+
+impl ReferenceId {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scope<'a, T> {
+    pub kind: ScopeKind<'a, T>,
+    pub parent: Option<ScopeId>,
+    pub bindings: Vec<BindingId>,
+}
+
+/// What introduced a scope
+#[derive(Debug, PartialEq)]
+pub enum ScopeKind<'a, T> {
+    Program(&'a Program<T>),
+    Func(&'a Func<T>),
+    Arrow(&'a ArrowFuncExpr<T>),
+    /// A class body, with the name of a class expression
+    Class(&'a Class<T>),
+    /// A block, class static block or namespace body
+    Block,
+    /// A `for` loop that declares `let`, `const` or `using` bindings
+    For,
+    /// The cases of a `switch`
+    Switch,
+    /// A `catch` clause parameter
+    Catch,
+}
+
+impl<T> ScopeKind<'_, T> {
+    /// If this is a program, function, arrow function or class scope
+    pub fn is_function(&self) -> bool {
+        matches!(
+            self,
+            ScopeKind::Program(_) | ScopeKind::Func(_) | ScopeKind::Arrow(_) | ScopeKind::Class(_)
+        )
+    }
+}
+
+impl<T> Clone for ScopeKind<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ScopeKind<'_, T> {}
+
+/// A declared name
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binding<'a, T> {
+    pub name: &'a T,
+    /// The identifier in the first declaration
+    pub ident: &'a Ident<T>,
+    pub kind: BindingKind,
+    pub scope: ScopeId,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingKind {
+    Var,
+    Let,
+    Const,
+    /// `using` or `await using`
+    Using,
+    /// A function declaration or the name of a function expression
+    Func,
+    /// A class declaration or the name of a class expression
+    Class,
+    Param,
+    CatchParam,
+    /// An import, including `import x = require('x')`
+    Import,
+    /// A typescript enum declaration
+    Enum,
+}
+
+impl BindingKind {
+    fn from_var(kind: VarKind) -> Self {
+        match kind {
+            VarKind::Var => BindingKind::Var,
+            VarKind::Let => BindingKind::Let,
+            VarKind::Const => BindingKind::Const,
+            VarKind::Using | VarKind::AwaitUsing => BindingKind::Using,
+        }
+    }
+}
+
+/// An identifier that reads or writes a name
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reference<'a, T> {
+    pub ident: &'a Ident<T>,
+    pub kind: RefKind,
+    /// The scope the identifier appears in
+    pub scope: ScopeId,
+    /// The binding the name resolves to, `None` for a global
+    pub binding: Option<BindingId>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefKind {
+    Read,
+    /// An assignment, or the left side of a for-in or for-of loop
+    Write,
+    /// A compound assignment or update like `x += 1` or `x++`
+    ReadWrite,
+    /// The value a declaration starts with
+    Init,
+}
+
+impl RefKind {
+    pub fn is_read(self) -> bool {
+        matches!(self, RefKind::Read | RefKind::ReadWrite)
+    }
+    pub fn is_write(self) -> bool {
+        !matches!(self, RefKind::Read)
+    }
+}
+
+/// What the identifiers in a pattern mean
+#[derive(Clone, Copy)]
+enum PatMode {
+    /// Declared up front, like parameters, so not references
+    Declare,
+    Ref(RefKind),
+}
+
+struct Resolver<'a, T> {
+    tree: ScopeTree<'a, T>,
+    current: ScopeId,
+    mode: PatMode,
+}
+
+impl<'a, T> Resolver<'a, T>
+where
+    T: AsRef<str>,
+{
+    fn push_scope(&mut self, kind: ScopeKind<'a, T>) {
+        let parent = if self.tree.scopes.is_empty() {
+            None
+        } else {
+            Some(self.current)
+        };
+        self.tree.scopes.push(Scope {
+            kind,
+            parent,
+            bindings: Vec::new(),
+        });
+        self.current = ScopeId(self.tree.scopes.len() - 1);
+    }
+
+    fn pop_scope(&mut self) {
+        if let Some(parent) = self.tree.scopes[self.current.0].parent {
+            self.current = parent;
+        }
+    }
+
+    fn declare(&mut self, ident: &'a Ident<T>, kind: BindingKind) {
+        let scope = &self.tree.scopes[self.current.0];
+        let exists = scope
+            .bindings
+            .iter()
+            .any(|id| self.tree.bindings[id.0].name.as_ref() == ident.name.as_ref());
+        if exists {
+            return;
+        }
+        self.tree.bindings.push(Binding {
+            name: &ident.name,
+            ident,
+            kind,
+            scope: self.current,
+        });
+        let id = BindingId(self.tree.bindings.len() - 1);
+        self.tree.scopes[self.current.0].bindings.push(id);
+    }
+
+    fn reference(&mut self, ident: &'a Ident<T>, kind: RefKind) {
+        let binding = self.tree.lookup(self.current, ident.name.as_ref());
+        self.tree.references.push(Reference {
+            ident,
+            kind,
+            scope: self.current,
+            binding,
+        });
+        let id = ReferenceId(self.tree.references.len() - 1);
+        self.tree.by_ident.insert(address(ident), id);
+    }
+
+    /// Declare the `var` and function declarations that belong to the
+    /// current function scope, followed by the lexical ones in `parts`
+    fn declare_function_body(&mut self, parts: &'a [ProgramPart<T>]) {
+        for part in parts {
+            self.declare_vars(part);
+        }
+        self.declare_lexical(parts);
+    }
+
+    /// Declare every `var` in `part`, without entering nested functions
+    fn declare_vars(&mut self, part: &'a ProgramPart<T>) {
+        match part {
+            ProgramPart::Decl(decl) => self.declare_var_decl(decl),
+            ProgramPart::Stmt(stmt) => self.declare_vars_in(stmt),
+            ProgramPart::Dir(_) => {}
+        }
+    }
+
+    fn declare_var_decl(&mut self, decl: &'a Decl<T>) {
+        match decl {
+            Decl::Var(VarKind::Var, decls) => self.declare_pats(decls, BindingKind::Var),
+            Decl::Export(export) => {
+                if let ModExport::Named(NamedExportDecl::Decl(decl)) = &**export {
+                    self.declare_var_decl(decl);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn declare_vars_in(&mut self, stmt: &'a Stmt<T>) {
+        match stmt {
+            Stmt::Var(decls) => self.declare_pats(decls, BindingKind::Var),
+            Stmt::Block(block) => {
+                for part in &block.0 {
+                    self.declare_vars(part);
+                }
+            }
+            Stmt::With(with) => self.declare_vars_in(&with.body),
+            Stmt::Labeled(labeled) => self.declare_vars_in(&labeled.body),
+            Stmt::If(stmt) => {
+                self.declare_vars_in(&stmt.consequent);
+                if let Some(alternate) = &stmt.alternate {
+                    self.declare_vars_in(alternate);
+                }
+            }
+            Stmt::Switch(switch) => {
+                for part in switch.cases.iter().flat_map(|case| &case.consequent) {
+                    self.declare_vars(part);
+                }
+            }
+            Stmt::Try(stmt) => {
+                let blocks = std::iter::once(&stmt.block)
+                    .chain(stmt.handler.as_ref().map(|handler| &handler.body))
+                    .chain(&stmt.finalizer);
+                for part in blocks.flat_map(|block| &block.0) {
+                    self.declare_vars(part);
+                }
+            }
+            Stmt::While(stmt) => self.declare_vars_in(&stmt.body),
+            Stmt::DoWhile(stmt) => self.declare_vars_in(&stmt.body),
+            Stmt::For(stmt) => {
+                if let Some(LoopInit::Variable(VarKind::Var, decls)) = &stmt.init {
+                    self.declare_pats(decls, BindingKind::Var);
+                }
+                self.declare_vars_in(&stmt.body);
+            }
+            Stmt::ForIn(stmt) => {
+                if let LoopLeft::Variable(VarKind::Var, decl) = &stmt.left {
+                    self.declare_pat(&decl.id, BindingKind::Var);
+                }
+                self.declare_vars_in(&stmt.body);
+            }
+            Stmt::ForOf(stmt) => {
+                if let LoopLeft::Variable(VarKind::Var, decl) = &stmt.left {
+                    self.declare_pat(&decl.id, BindingKind::Var);
+                }
+                self.declare_vars_in(&stmt.body);
+            }
+            _ => {}
+        }
+    }
+
+    /// Declare the `let`, `const`, `using`, class, function and import
+    /// declarations that are directly in `parts`
+    fn declare_lexical(&mut self, parts: &'a [ProgramPart<T>]) {
+        for part in parts {
+            if let ProgramPart::Decl(decl) = part {
+                self.declare_lexical_decl(decl);
+            }
+        }
+    }
+
+    fn declare_lexical_decl(&mut self, decl: &'a Decl<T>) {
+        match decl {
+            Decl::Var(VarKind::Var, _) | Decl::Invalid(_) => {}
+            Decl::Var(kind, decls) => self.declare_pats(decls, BindingKind::from_var(*kind)),
+            Decl::Func(func) => {
+                if let Some(id) = &func.id {
+                    self.declare(id, BindingKind::Func);
+                }
+            }
+            Decl::Class(class) => {
+                if let Some(id) = &class.id {
+                    self.declare(id, BindingKind::Class);
+                }
+            }
+            Decl::Import(import) => {
+                for specifier in &import.specifiers {
+                    match specifier {
+                        ImportSpecifier::Normal(specs) => {
+                            for spec in specs {
+                                match (&spec.alias, &spec.imported) {
+                                    (Some(alias), _) | (None, ModuleExportName::Ident(alias)) => {
+                                        self.declare(alias, BindingKind::Import)
+                                    }
+                                    (None, ModuleExportName::String(_)) => {}
+                                }
+                            }
+                        }
+                        ImportSpecifier::Default(id) | ImportSpecifier::Namespace(id) => {
+                            self.declare(id, BindingKind::Import)
+                        }
+                    }
+                }
+            }
+            Decl::ImportEquals(import) => self.declare(&import.id, BindingKind::Import),
+            Decl::Export(export) => match &**export {
+                ModExport::Named(NamedExportDecl::Decl(decl))
+                | ModExport::Default(DefaultExportDecl::Decl(decl)) => {
+                    self.declare_lexical_decl(decl)
+                }
+                _ => {}
+            },
+            Decl::Declare(decl) => {
+                if let Decl::Var(VarKind::Var, decls) = &**decl {
+                    self.declare_pats(decls, BindingKind::Var);
+                }
+                self.declare_lexical_decl(decl);
+            }
+            Decl::Enum(decl) => self.declare(&decl.id, BindingKind::Enum),
+            Decl::Module(_) | Decl::Interface(_) | Decl::TypeAlias(_) => {}
+        }
+    }
+
+    fn declare_pats(&mut self, decls: &'a [VarDecl<T>], kind: BindingKind) {
+        for decl in decls {
+            self.declare_pat(&decl.id, kind);
+        }
+    }
+
+    fn declare_pat(&mut self, pat: &'a Pat<T>, kind: BindingKind) {
+        for ident in pat.bound_names() {
+            self.declare(ident, kind);
+        }
+    }
+
+    fn declare_params(&mut self, params: &'a [FuncArg<T>]) {
+        for param in params {
+            self.declare_param(param);
+        }
+    }
+
+    fn declare_param(&mut self, param: &'a FuncArg<T>) {
+        match param {
+            FuncArg::Pat(pat) => self.declare_pat(pat, BindingKind::Param),
+            FuncArg::Expr(Expr::Ident(ident)) => self.declare(ident, BindingKind::Param),
+            FuncArg::Expr(Expr::Assign(assign)) => match &assign.left {
+                AssignLeft::Pat(pat) => self.declare_pat(pat, BindingKind::Param),
+                AssignLeft::Expr(expr) => {
+                    if let Expr::Ident(ident) = &**expr {
+                        self.declare(ident, BindingKind::Param);
+                    }
+                }
+            },
+            FuncArg::Expr(_) => {}
+            FuncArg::Decorated(decorated) => self.declare_param(&decorated.arg),
+            FuncArg::Typed(typed) => self.declare_pat(&typed.pat, BindingKind::Param),
+        }
+    }
+
+    /// A function or arrow function, `name` is the name of a function
+    /// expression which is only visible inside of it
+    fn function(
+        &mut self,
+        kind: ScopeKind<'a, T>,
+        name: Option<&'a Ident<T>>,
+        params: &'a [FuncArg<T>],
+        body: FunctionBody<'a, T>,
+    ) {
+        self.push_scope(kind);
+        if let Some(name) = name {
+            self.declare(name, BindingKind::Func);
+        }
+        self.declare_params(params);
+        if let FunctionBody::Parts(parts) = body {
+            self.declare_function_body(parts);
+        }
+        for param in params {
+            self.visit_func_arg(param);
+        }
+        match body {
+            FunctionBody::Parts(parts) => self.visit_parts(parts),
+            FunctionBody::Expr(expr) => self.visit_expr(expr),
+            FunctionBody::None => {}
+        }
+        self.pop_scope();
+    }
+
+    /// A class, `is_expr` when its name is only visible inside of it
+    fn class(&mut self, class: &'a Class<T>, is_expr: bool) {
+        for decorator in &class.decorators {
+            self.visit_expr(&decorator.expr);
+        }
+        if let Some(super_class) = &class.super_class {
+            self.visit_expr(super_class);
+        }
+        self.push_scope(ScopeKind::Class(class));
+        if let (true, Some(id)) = (is_expr, &class.id) {
+            self.declare(id, BindingKind::Class);
+        }
+        for member in &class.body.0 {
+            match member {
+                ClassMember::Prop(prop) => self.visit_prop(prop),
+                ClassMember::Field(field) => {
+                    for decorator in &field.decorators {
+                        self.visit_expr(&decorator.expr);
+                    }
+                    if field.computed {
+                        self.computed_key(&field.key);
+                    }
+                    if let Some(value) = &field.value {
+                        self.visit_expr(value);
+                    }
+                }
+                ClassMember::StaticBlock(body) => {
+                    self.push_scope(ScopeKind::Block);
+                    self.declare_function_body(&body.0);
+                    self.visit_parts(&body.0);
+                    self.pop_scope();
+                }
+            }
+        }
+        self.pop_scope();
+    }
+
+    /// A block with its own lexical scope
+    fn block(&mut self, parts: &'a [ProgramPart<T>]) {
+        self.push_scope(ScopeKind::Block);
+        self.declare_lexical(parts);
+        self.visit_parts(parts);
+        self.pop_scope();
+    }
+
+    /// Open the scope of a for loop head that declares `let`, `const`
+    /// or `using` bindings, returns if it did
+    fn loop_scope(&mut self, kind: VarKind, pats: &[&'a Pat<T>]) -> bool {
+        if kind == VarKind::Var {
+            return false;
+        }
+        self.push_scope(ScopeKind::For);
+        for pat in pats {
+            self.declare_pat(pat, BindingKind::from_var(kind));
+        }
+        true
+    }
+
+    fn with_mode(&mut self, mode: PatMode, f: impl FnOnce(&mut Self)) {
+        let outer = std::mem::replace(&mut self.mode, mode);
+        f(self);
+        self.mode = outer;
+    }
+
+    fn var_decls(&mut self, kind: VarKind, decls: &'a [VarDecl<T>]) {
+        for decl in decls {
+            if let Some(init) = &decl.init {
+                self.visit_expr(init);
+            }
+            // `var x;` keeps the value x already had
+            let mode = if decl.init.is_some() || kind != VarKind::Var {
+                PatMode::Ref(RefKind::Init)
+            } else {
+                PatMode::Declare
+            };
+            self.with_mode(mode, |this| this.visit_pat(&decl.id));
+        }
+    }
+
+    /// An identifier in a pattern
+    fn pat_ident(&mut self, ident: &'a Ident<T>) {
+        if let PatMode::Ref(kind) = self.mode {
+            self.reference(ident, kind);
+        }
+    }
+
+    /// An expression in a pattern or on the left of an assignment,
+    /// where identifiers are written instead of read
+    fn target(&mut self, expr: &'a Expr<T>) {
+        match expr {
+            Expr::Ident(ident) => self.pat_ident(ident),
+            Expr::Assign(assign) => {
+                self.visit_expr(&assign.right);
+                match &assign.left {
+                    AssignLeft::Pat(pat) => self.visit_pat(pat),
+                    AssignLeft::Expr(expr) => self.target(expr),
+                }
+            }
+            Expr::Array(elements) => {
+                for element in elements.iter().flatten() {
+                    self.target(element);
+                }
+            }
+            Expr::Obj(props) => {
+                for prop in props {
+                    match prop {
+                        ObjProp::Prop(prop) => self.target_prop(prop),
+                        ObjProp::Spread(expr) => self.target(expr),
+                    }
+                }
+            }
+            Expr::Spread(expr) => self.target(expr),
+            _ => self.visit_expr(expr),
+        }
+    }
+
+    fn target_prop(&mut self, prop: &'a Prop<T>) {
+        if prop.computed {
+            self.computed_key(&prop.key);
+        }
+        match &prop.value {
+            PropValue::Pat(pat) => self.visit_pat(pat),
+            PropValue::Expr(expr) => self.target(expr),
+            PropValue::None => match &prop.key {
+                PropKey::Pat(pat) => self.visit_pat(pat),
+                PropKey::Expr(expr) => self.target(expr),
+                PropKey::Lit(_) | PropKey::Private(_) => {}
+            },
+        }
+    }
+
+    fn computed_key(&mut self, key: &'a PropKey<T>) {
+        match key {
+            PropKey::Expr(expr) => self.visit_expr(expr),
+            PropKey::Pat(pat) => {
+                self.with_mode(PatMode::Ref(RefKind::Read), |this| this.visit_pat(pat))
+            }
+            PropKey::Lit(_) | PropKey::Private(_) => {}
+        }
+    }
+
+    fn loop_left(&mut self, left: &'a LoopLeft<T>) {
+        match left {
+            LoopLeft::Variable(_, decl) => {
+                self.with_mode(PatMode::Ref(RefKind::Init), |this| this.visit_pat(&decl.id))
+            }
+            LoopLeft::Pat(pat) => {
+                self.with_mode(PatMode::Ref(RefKind::Write), |this| this.visit_pat(pat))
+            }
+            LoopLeft::Expr(expr) => {
+                self.with_mode(PatMode::Ref(RefKind::Write), |this| this.target(expr))
+            }
+        }
+    }
+
+    /// A for-in or for-of loop
+    fn iteration(&mut self, left: &'a LoopLeft<T>, right: &'a Expr<T>, body: &'a Stmt<T>) {
+        let lexical = match left {
+            LoopLeft::Variable(kind, decl) => self.loop_scope(*kind, &[&decl.id]),
+            _ => false,
+        };
+        self.visit_expr(right);
+        self.loop_left(left);
+        self.visit_stmt(body);
+        if lexical {
+            self.pop_scope();
+        }
+    }
+}
+
+enum FunctionBody<'a, T> {
+    Parts(&'a [ProgramPart<T>]),
+    Expr(&'a Expr<T>),
+    None,
+}
+
+impl<T> Clone for FunctionBody<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for FunctionBody<'_, T> {}
+
+impl<'a, T> Visit<'a, T> for Resolver<'a, T>
+where
+    T: AsRef<str>,
+{
+    fn visit_program(&mut self, program: &'a Program<T>) {
+        self.push_scope(ScopeKind::Program(program));
+        self.declare_function_body(program.parts());
+        self.visit_parts(program.parts());
+    }
+
+    fn visit_decl(&mut self, decl: &'a Decl<T>) {
+        match decl {
+            Decl::Var(kind, decls) => self.var_decls(*kind, decls),
+            Decl::Func(func) => self.visit_func(func),
+            Decl::Class(class) => self.class(class, false),
+            Decl::Export(export) => match &**export {
+                ModExport::Named(NamedExportDecl::Specifier(specifiers, None, _)) => {
+                    for specifier in specifiers {
+                        if let ModuleExportName::Ident(local) = &specifier.local {
+                            self.reference(local, RefKind::Read);
+                        }
+                    }
+                }
+                ModExport::Named(NamedExportDecl::Decl(decl))
+                | ModExport::Default(DefaultExportDecl::Decl(decl)) => self.visit_decl(decl),
+                ModExport::Default(DefaultExportDecl::Expr(expr)) | ModExport::Assign(expr) => {
+                    self.visit_expr(expr)
+                }
+                ModExport::Named(NamedExportDecl::Specifier(..)) | ModExport::All { .. } => {}
+            },
+            Decl::ImportEquals(import) => {
+                if let ModuleRef::Entity(expr) = &import.module_ref {
+                    self.visit_expr(expr);
+                }
+            }
+            Decl::Module(module) => {
+                if let Some(body) = &module.body {
+                    self.push_scope(ScopeKind::Block);
+                    self.declare_function_body(body);
+                    self.visit_parts(body);
+                    self.pop_scope();
+                }
+            }
+            Decl::Enum(_) => walk_decl(self, decl),
+            Decl::Import(_)
+            | Decl::Declare(_)
+            | Decl::Interface(_)
+            | Decl::TypeAlias(_)
+            | Decl::Invalid(_) => {}
+        }
+    }
+
+    fn visit_stmt(&mut self, stmt: &'a Stmt<T>) {
+        match stmt {
+            Stmt::Block(block) => self.block(&block.0),
+            Stmt::Var(decls) => self.var_decls(VarKind::Var, decls),
+            Stmt::For(stmt) => {
+                let lexical = match &stmt.init {
+                    Some(LoopInit::Variable(kind, decls)) => {
+                        let pats: Vec<_> = decls.iter().map(|decl| &decl.id).collect();
+                        self.loop_scope(*kind, &pats)
+                    }
+                    _ => false,
+                };
+                match &stmt.init {
+                    Some(LoopInit::Variable(kind, decls)) => self.var_decls(*kind, decls),
+                    Some(LoopInit::Expr(expr)) => self.visit_expr(expr),
+                    None => {}
+                }
+                if let Some(test) = &stmt.test {
+                    self.visit_expr(test);
+                }
+                if let Some(update) = &stmt.update {
+                    self.visit_expr(update);
+                }
+                self.visit_stmt(&stmt.body);
+                if lexical {
+                    self.pop_scope();
+                }
+            }
+            Stmt::ForIn(stmt) => self.iteration(&stmt.left, &stmt.right, &stmt.body),
+            Stmt::ForOf(stmt) => self.iteration(&stmt.left, &stmt.right, &stmt.body),
+            Stmt::Switch(switch) => {
+                self.visit_expr(&switch.discriminant);
+                self.push_scope(ScopeKind::Switch);
+                for case in &switch.cases {
+                    self.declare_lexical(&case.consequent);
+                }
+                for case in &switch.cases {
+                    if let Some(test) = &case.test {
+                        self.visit_expr(test);
+                    }
+                    self.visit_parts(&case.consequent);
+                }
+                self.pop_scope();
+            }
+            Stmt::Try(stmt) => {
+                self.block(&stmt.block.0);
+                if let Some(handler) = &stmt.handler {
+                    self.push_scope(ScopeKind::Catch);
+                    if let Some(param) = &handler.param {
+                        self.declare_pat(param, BindingKind::CatchParam);
+                        self.with_mode(PatMode::Ref(RefKind::Init), |this| this.visit_pat(param));
+                    }
+                    self.block(&handler.body.0);
+                    self.pop_scope();
+                }
+                if let Some(finalizer) = &stmt.finalizer {
+                    self.block(&finalizer.0);
+                }
+            }
+            _ => walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr<T>) {
+        match expr {
+            Expr::Func(func) => self.function(
+                ScopeKind::Func(func),
+                func.id.as_ref(),
+                &func.params,
+                func.body
+                    .as_ref()
+                    .map_or(FunctionBody::None, |body| FunctionBody::Parts(&body.0)),
+            ),
+            Expr::ArrowFunc(arrow) => self.function(
+                ScopeKind::Arrow(arrow),
+                None,
+                &arrow.params,
+                match &arrow.body {
+                    ArrowFuncBody::FuncBody(body) => FunctionBody::Parts(&body.0),
+                    ArrowFuncBody::Expr(expr) => FunctionBody::Expr(expr),
+                },
+            ),
+            Expr::Class(class) => self.class(class, true),
+            Expr::Assign(assign) => {
+                self.visit_expr(&assign.right);
+                let kind = match assign.operator {
+                    AssignOp::Equal => RefKind::Write,
+                    _ => RefKind::ReadWrite,
+                };
+                self.with_mode(PatMode::Ref(kind), |this| match &assign.left {
+                    AssignLeft::Pat(pat) => this.visit_pat(pat),
+                    AssignLeft::Expr(expr) => this.target(expr),
+                });
+            }
+            Expr::Update(update) => match &*update.argument {
+                Expr::Ident(ident) => self.reference(ident, RefKind::ReadWrite),
+                argument => self.visit_expr(argument),
+            },
+            _ => walk_expr(self, expr),
+        }
+    }
+
+    fn visit_func(&mut self, func: &'a Func<T>) {
+        let body = func
+            .body
+            .as_ref()
+            .map_or(FunctionBody::None, |body| FunctionBody::Parts(&body.0));
+        self.function(ScopeKind::Func(func), None, &func.params, body);
+    }
+
+    fn visit_func_arg(&mut self, arg: &'a FuncArg<T>) {
+        match arg {
+            FuncArg::Pat(pat) => self.with_mode(PatMode::Declare, |this| this.visit_pat(pat)),
+            FuncArg::Expr(Expr::Ident(_)) => {}
+            FuncArg::Expr(Expr::Assign(assign)) => {
+                self.visit_expr(&assign.right);
+                self.with_mode(PatMode::Declare, |this| match &assign.left {
+                    AssignLeft::Pat(pat) => this.visit_pat(pat),
+                    AssignLeft::Expr(expr) => this.target(expr),
+                });
+            }
+            FuncArg::Expr(expr) => self.visit_expr(expr),
+            FuncArg::Decorated(decorated) => {
+                for decorator in &decorated.decorators {
+                    self.visit_expr(&decorator.expr);
+                }
+                self.visit_func_arg(&decorated.arg);
+            }
+            FuncArg::Typed(typed) => {
+                if let Some(default) = &typed.default {
+                    self.visit_expr(default);
+                }
+                self.with_mode(PatMode::Declare, |this| this.visit_pat(&typed.pat));
+            }
+        }
+    }
+
+    fn visit_class(&mut self, class: &'a Class<T>) {
+        self.class(class, false);
+    }
+
+    /// Shorthand properties read the variable with the same name
+    fn visit_prop(&mut self, prop: &'a Prop<T>) {
+        match (&prop.value, &prop.key) {
+            (PropValue::None, PropKey::Expr(key)) if prop.short_hand => self.visit_expr(key),
+            (PropValue::None, PropKey::Pat(Pat::Ident(key))) if prop.short_hand => {
+                self.reference(key, RefKind::Read)
+            }
+            _ => walk_prop(self, prop),
+        }
+    }
+
+    fn visit_pat(&mut self, pat: &'a Pat<T>) {
+        match pat {
+            Pat::Ident(ident) => self.pat_ident(ident),
+            Pat::Obj(parts) => {
+                for part in parts {
+                    match part {
+                        ObjPatPart::Assign(prop) => self.target_prop(prop),
+                        ObjPatPart::Rest(pat) => self.visit_pat(pat),
+                    }
+                }
+            }
+            Pat::Array(parts) => {
+                for part in parts.iter().flatten() {
+                    match part {
+                        ArrayPatPart::Pat(pat) => self.visit_pat(pat),
+                        ArrayPatPart::Expr(expr) => self.target(expr),
+                    }
+                }
+            }
+            Pat::RestElement(pat) => self.visit_pat(pat),
+            Pat::Assign(assign) => {
+                self.visit_expr(&assign.right);
+                self.visit_pat(&assign.left);
+            }
+            Pat::Invalid(_) => {}
+        }
+    }
+
+    /// Component names like `<Thing />` read a variable
+    fn visit_jsx_element(&mut self, element: &'a JsxElement<T>) {
+        if let JsxElementName::Ident(ident) = &element.name {
+            if !element.name.is_intrinsic() {
+                self.reference(ident, RefKind::Read);
+            }
+        }
+        walk_jsx_element(self, element);
+    }
+
+    fn visit_ident(&mut self, ident: &'a Ident<T>) {
+        self.reference(ident, RefKind::Read);
+    }
+}
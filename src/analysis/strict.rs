@@ -0,0 +1,299 @@
+//! Which functions, classes and programs are strict mode code
+//!
+//! ```js
+//! function sloppy() {
+//!     function inner() {}
+//! }
+//! function strict() {
+//!     'use strict';
+//!     const inner = () => {};
+//! }
+//! class Thing {
+//!     method() {}
+//! }
+//! ```
+//! `sloppy` and its `inner` are sloppy mode, `strict` is strict because
+//! of its directive and the arrow inherits that, while the class body
+//! and `method` are always strict.
+//!
+//! A directive only counts in the prologue, the run of directives at
+//! the start of a body, and only when its raw text is exactly
+//! `use strict`, so `'use\x20strict'` does not make anything strict.
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::analysis::visit::{walk_class, walk_expr, walk_func, walk_program, Visit};
+use crate::expr::{ArrowFuncBody, ArrowFuncExpr, Expr};
+use crate::pat::Pat;
+use crate::{Class, Func, FuncArg, Program, ProgramPart};
+
+/// Find the strictness of `program` and of every function, arrow
+/// function and class in it
+pub fn analyze<T>(program: &Program<T>) -> StrictMode<'_, T>
+where
+    T: AsRef<str>,
+{
+    let mut analysis = Analysis {
+        strict: false,
+        program: StrictReason::Sloppy,
+        scopes: Vec::new(),
+        errors: Vec::new(),
+    };
+    analysis.visit_program(program);
+    let by_node = analysis
+        .scopes
+        .iter()
+        .enumerate()
+        .map(|(index, scope)| (scope.node.address(), index))
+        .collect();
+    StrictMode {
+        program: analysis.program,
+        scopes: analysis.scopes,
+        by_node,
+        errors: analysis.errors,
+    }
+}
+
+/// The result of `analyze`
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrictMode<'a, T> {
+    program: StrictReason,
+    scopes: Vec<Scope<'a, T>>,
+    /// The index of each scope by the address of its node
+    by_node: HashMap<usize, usize>,
+    errors: Vec<StrictError<'a, T>>,
+}
+
+impl<'a, T> StrictMode<'a, T> {
+    /// If the top level of the program is strict
+    pub fn program_is_strict(&self) -> bool {
+        self.program.is_strict()
+    }
+    /// Why the top level of the program is or is not strict
+    pub fn program_reason(&self) -> StrictReason {
+        self.program
+    }
+    /// Every function, arrow function and class in source order
+    pub fn scopes(&self) -> &[Scope<'a, T>] {
+        &self.scopes
+    }
+    /// The functions with a `'use strict'` directive that is not allowed
+    pub fn errors(&self) -> &[StrictError<'a, T>] {
+        &self.errors
+    }
+    /// Look up a function by identity, `None` when it is not part of
+    /// the analyzed program
+    pub fn func(&self, func: &Func<T>) -> Option<&Scope<'a, T>> {
+        self.lookup(ScopeNode::Func(func))
+    }
+    /// Look up an arrow function by identity
+    pub fn arrow(&self, arrow: &ArrowFuncExpr<T>) -> Option<&Scope<'a, T>> {
+        self.lookup(ScopeNode::Arrow(arrow))
+    }
+    /// Look up a class by identity
+    pub fn class(&self, class: &Class<T>) -> Option<&Scope<'a, T>> {
+        self.lookup(ScopeNode::Class(class))
+    }
+
+    fn lookup(&self, node: ScopeNode<'_, T>) -> Option<&Scope<'a, T>> {
+        self.by_node
+            .get(&node.address())
+            .map(|index| &self.scopes[*index])
+    }
+}
+
+/// A function, arrow function or class and its strictness
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scope<'a, T> {
+    pub node: ScopeNode<'a, T>,
+    pub strict: bool,
+    pub reason: StrictReason,
+}
+
+/// A node that can change the strictness of the code inside it
+#[derive(Debug, PartialEq)]
+pub enum ScopeNode<'a, T> {
+    Func(&'a Func<T>),
+    Arrow(&'a ArrowFuncExpr<T>),
+    /// The whole class, including its heritage and decorators
+    Class(&'a Class<T>),
+}
+
+impl<T> Clone for ScopeNode<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ScopeNode<'_, T> {}
+
+impl<T> ScopeNode<'_, T> {
+    fn address(self) -> usize {
+        match self {
+            ScopeNode::Func(func) => func as *const Func<T> as usize,
+            ScopeNode::Arrow(arrow) => arrow as *const ArrowFuncExpr<T> as usize,
+            ScopeNode::Class(class) => class as *const Class<T> as usize,
+        }
+    }
+}
+
+/// Where the strictness of a scope comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrictReason {
+    /// A `'use strict'` directive in its own prologue
+    Directive,
+    /// The top level of a module
+    Module,
+    /// A class, which is always strict
+    Class,
+    /// Nested in strict code
+    Inherited,
+    /// Not strict
+    Sloppy,
+}
+
+impl StrictReason {
+    pub fn is_strict(self) -> bool {
+        !matches!(self, StrictReason::Sloppy)
+    }
+}
+
+/// A `'use strict'` directive that is not allowed
+#[derive(Debug, Clone, PartialEq)]
+pub enum StrictError<'a, T> {
+    /// A function with default values, destructuring or a rest
+    /// element in its parameters
+    /// ```js
+    /// function f(a = 0) {
+    ///     'use strict';
+    /// }
+    /// ```
+    NonSimpleParams(ScopeNode<'a, T>),
+}
+
+impl<T> fmt::Display for StrictError<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StrictError::NonSimpleParams(_) => f.write_str(
+                "'use strict' is not allowed in a function with a non-simple parameter list",
+            ),
+        }
+    }
+}
+
+impl<T> std::error::Error for StrictError<'_, T> where T: fmt::Debug {}
+
+/// If `parts` starts with a `'use strict'` directive
+pub fn has_use_strict<T>(parts: &[ProgramPart<T>]) -> bool
+where
+    T: AsRef<str>,
+{
+    parts
+        .iter()
+        .map_while(|part| match part {
+            ProgramPart::Dir(dir) => Some(dir),
+            _ => None,
+        })
+        .any(|dir| dir.dir.as_ref() == "use strict")
+}
+
+/// If every parameter is a plain identifier without a default value
+pub fn is_simple_params<T>(params: &[FuncArg<T>]) -> bool {
+    params.iter().all(|param| match param {
+        FuncArg::Pat(Pat::Ident(_)) | FuncArg::Expr(Expr::Ident(_)) => true,
+        FuncArg::Decorated(decorated) => is_simple_params(std::slice::from_ref(&decorated.arg)),
+        _ => false,
+    })
+}
+
+struct Analysis<'a, T> {
+    /// If the code currently being visited is strict
+    strict: bool,
+    program: StrictReason,
+    scopes: Vec<Scope<'a, T>>,
+    errors: Vec<StrictError<'a, T>>,
+}
+
+impl<'a, T> Analysis<'a, T>
+where
+    T: AsRef<str>,
+{
+    /// Record a function scope and visit it with its strictness,
+    /// a directive only counts when the code around it is sloppy
+    fn enter_function(
+        &mut self,
+        node: ScopeNode<'a, T>,
+        params: &[FuncArg<T>],
+        body: Option<&[ProgramPart<T>]>,
+        walk: impl FnOnce(&mut Self),
+    ) {
+        let directive = body.is_some_and(has_use_strict);
+        if directive && !is_simple_params(params) {
+            self.errors.push(StrictError::NonSimpleParams(node));
+        }
+        let reason = if self.strict {
+            StrictReason::Inherited
+        } else if directive {
+            StrictReason::Directive
+        } else {
+            StrictReason::Sloppy
+        };
+        self.scopes.push(Scope {
+            node,
+            strict: reason.is_strict(),
+            reason,
+        });
+        let outer = std::mem::replace(&mut self.strict, reason.is_strict());
+        walk(self);
+        self.strict = outer;
+    }
+}
+
+impl<'a, T> Visit<'a, T> for Analysis<'a, T>
+where
+    T: AsRef<str>,
+{
+    fn visit_program(&mut self, program: &'a Program<T>) {
+        self.program = if program.is_module() {
+            StrictReason::Module
+        } else if has_use_strict(program.parts()) {
+            StrictReason::Directive
+        } else {
+            StrictReason::Sloppy
+        };
+        self.strict = self.program.is_strict();
+        walk_program(self, program);
+    }
+
+    fn visit_func(&mut self, func: &'a Func<T>) {
+        let body = func.body.as_ref().map(|body| body.0.as_slice());
+        self.enter_function(ScopeNode::Func(func), &func.params, body, |this| {
+            walk_func(this, func)
+        });
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr<T>) {
+        let arrow = match expr {
+            Expr::ArrowFunc(arrow) => arrow,
+            _ => return walk_expr(self, expr),
+        };
+        let body = match &arrow.body {
+            ArrowFuncBody::FuncBody(body) => Some(body.0.as_slice()),
+            ArrowFuncBody::Expr(_) => None,
+        };
+        self.enter_function(ScopeNode::Arrow(arrow), &arrow.params, body, |this| {
+            walk_expr(this, expr)
+        });
+    }
+
+    fn visit_class(&mut self, class: &'a Class<T>) {
+        self.scopes.push(Scope {
+            node: ScopeNode::Class(class),
+            strict: true,
+            reason: StrictReason::Class,
+        });
+        let outer = std::mem::replace(&mut self.strict, true);
+        walk_class(self, class);
+        self.strict = outer;
+    }
+}
@@ -0,0 +1,517 @@
+//! Read only traversal of a `Program`
+//!
+//! The same walk as `transform::visit::VisitMut` over shared
+//! references that live as long as the program, so a visitor
+//! can keep hold of the nodes it has seen.
+use crate::decl::{Decl, DefaultExportDecl, ModExport, ModuleRef, NamedExportDecl};
+use crate::expr::{ArrowFuncBody, AssignLeft, Expr, Lit, ObjProp, Prop, PropKey, PropValue};
+use crate::jsx::{JsxAttr, JsxAttrValue, JsxChild, JsxElement, JsxElementName};
+use crate::pat::{ArrayPatPart, ObjPatPart, Pat};
+use crate::stmt::{LoopInit, LoopLeft, Stmt};
+use crate::{Class, ClassMember, Func, FuncArg, Ident, MemberIndexer, Program, ProgramPart};
+
+pub trait Visit<'a, T: 'a> {
+    fn visit_program(&mut self, program: &'a Program<T>) {
+        walk_program(self, program)
+    }
+    /// Any list of statements: a program, function or block body,
+    /// a switch case or a namespace
+    fn visit_parts(&mut self, parts: &'a [ProgramPart<T>]) {
+        walk_parts(self, parts)
+    }
+    fn visit_part(&mut self, part: &'a ProgramPart<T>) {
+        walk_part(self, part)
+    }
+    fn visit_decl(&mut self, decl: &'a Decl<T>) {
+        walk_decl(self, decl)
+    }
+    fn visit_stmt(&mut self, stmt: &'a Stmt<T>) {
+        walk_stmt(self, stmt)
+    }
+    fn visit_expr(&mut self, expr: &'a Expr<T>) {
+        walk_expr(self, expr)
+    }
+    fn visit_pat(&mut self, pat: &'a Pat<T>) {
+        walk_pat(self, pat)
+    }
+    fn visit_func(&mut self, func: &'a Func<T>) {
+        walk_func(self, func)
+    }
+    fn visit_func_arg(&mut self, arg: &'a FuncArg<T>) {
+        walk_func_arg(self, arg)
+    }
+    fn visit_class(&mut self, class: &'a Class<T>) {
+        walk_class(self, class)
+    }
+    fn visit_prop(&mut self, prop: &'a Prop<T>) {
+        walk_prop(self, prop)
+    }
+    /// The tag name is not visited unless it is a `JsxMemberExpr`,
+    /// whose object is always a reference, see
+    /// `JsxElementName::is_intrinsic` for telling the others apart
+    fn visit_jsx_element(&mut self, element: &'a JsxElement<T>) {
+        walk_jsx_element(self, element)
+    }
+    /// An identifier in a reference or binding position
+    fn visit_ident(&mut self, _ident: &'a Ident<T>) {}
+}
+
+pub fn walk_program<'a, T, V>(v: &mut V, program: &'a Program<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    match program {
+        Program::Mod(parts, _) | Program::Script(parts, _) => v.visit_parts(parts),
+    }
+}
+
+pub fn walk_parts<'a, T, V>(v: &mut V, parts: &'a [ProgramPart<T>])
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    for part in parts {
+        v.visit_part(part);
+    }
+}
+
+pub fn walk_part<'a, T, V>(v: &mut V, part: &'a ProgramPart<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    match part {
+        ProgramPart::Dir(_) => {}
+        ProgramPart::Decl(decl) => v.visit_decl(decl),
+        ProgramPart::Stmt(stmt) => v.visit_stmt(stmt),
+    }
+}
+
+pub fn walk_decl<'a, T, V>(v: &mut V, decl: &'a Decl<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    match decl {
+        Decl::Var(_, decls) => {
+            for decl in decls {
+                v.visit_pat(&decl.id);
+                if let Some(init) = &decl.init {
+                    v.visit_expr(init);
+                }
+            }
+        }
+        Decl::Func(func) => v.visit_func(func),
+        Decl::Class(class) => v.visit_class(class),
+        Decl::Import(_) | Decl::Invalid(_) => {}
+        Decl::Export(export) => match &**export {
+            ModExport::Default(DefaultExportDecl::Decl(decl)) => v.visit_decl(decl),
+            ModExport::Default(DefaultExportDecl::Expr(expr)) => v.visit_expr(expr),
+            ModExport::Named(NamedExportDecl::Decl(decl)) => v.visit_decl(decl),
+            ModExport::Named(NamedExportDecl::Specifier(..)) | ModExport::All { .. } => {}
+            ModExport::Assign(expr) => v.visit_expr(expr),
+        },
+        Decl::ImportEquals(import) => {
+            if let ModuleRef::Entity(expr) = &import.module_ref {
+                v.visit_expr(expr);
+            }
+        }
+        Decl::Declare(decl) => v.visit_decl(decl),
+        Decl::Module(module) => {
+            if let Some(body) = &module.body {
+                v.visit_parts(body);
+            }
+        }
+        Decl::Interface(_) | Decl::TypeAlias(_) => {}
+        Decl::Enum(decl) => {
+            for member in &decl.members {
+                if let Some(init) = &member.init {
+                    v.visit_expr(init);
+                }
+            }
+        }
+    }
+}
+
+pub fn walk_stmt<'a, T, V>(v: &mut V, stmt: &'a Stmt<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    match stmt {
+        Stmt::Expr(expr) | Stmt::Throw(expr) => v.visit_expr(expr),
+        Stmt::Block(block) => v.visit_parts(&block.0),
+        Stmt::Empty | Stmt::Debugger | Stmt::Break(_) | Stmt::Continue(_) | Stmt::Invalid(_) => {}
+        Stmt::With(with) => {
+            v.visit_expr(&with.object);
+            v.visit_stmt(&with.body);
+        }
+        Stmt::Return(expr) => {
+            if let Some(expr) = expr {
+                v.visit_expr(expr);
+            }
+        }
+        Stmt::Labeled(labeled) => v.visit_stmt(&labeled.body),
+        Stmt::If(stmt) => {
+            v.visit_expr(&stmt.test);
+            v.visit_stmt(&stmt.consequent);
+            if let Some(alternate) = &stmt.alternate {
+                v.visit_stmt(alternate);
+            }
+        }
+        Stmt::Switch(switch) => {
+            v.visit_expr(&switch.discriminant);
+            for case in &switch.cases {
+                if let Some(test) = &case.test {
+                    v.visit_expr(test);
+                }
+                v.visit_parts(&case.consequent);
+            }
+        }
+        Stmt::Try(stmt) => {
+            v.visit_parts(&stmt.block.0);
+            if let Some(handler) = &stmt.handler {
+                if let Some(param) = &handler.param {
+                    v.visit_pat(param);
+                }
+                v.visit_parts(&handler.body.0);
+            }
+            if let Some(finalizer) = &stmt.finalizer {
+                v.visit_parts(&finalizer.0);
+            }
+        }
+        Stmt::While(stmt) => {
+            v.visit_expr(&stmt.test);
+            v.visit_stmt(&stmt.body);
+        }
+        Stmt::DoWhile(stmt) => {
+            v.visit_stmt(&stmt.body);
+            v.visit_expr(&stmt.test);
+        }
+        Stmt::For(stmt) => {
+            match &stmt.init {
+                Some(LoopInit::Variable(_, decls)) => {
+                    for decl in decls {
+                        v.visit_pat(&decl.id);
+                        if let Some(init) = &decl.init {
+                            v.visit_expr(init);
+                        }
+                    }
+                }
+                Some(LoopInit::Expr(expr)) => v.visit_expr(expr),
+                None => {}
+            }
+            if let Some(test) = &stmt.test {
+                v.visit_expr(test);
+            }
+            if let Some(update) = &stmt.update {
+                v.visit_expr(update);
+            }
+            v.visit_stmt(&stmt.body);
+        }
+        Stmt::ForIn(stmt) => {
+            walk_loop_left(v, &stmt.left);
+            v.visit_expr(&stmt.right);
+            v.visit_stmt(&stmt.body);
+        }
+        Stmt::ForOf(stmt) => {
+            walk_loop_left(v, &stmt.left);
+            v.visit_expr(&stmt.right);
+            v.visit_stmt(&stmt.body);
+        }
+        Stmt::Var(decls) => {
+            for decl in decls {
+                v.visit_pat(&decl.id);
+                if let Some(init) = &decl.init {
+                    v.visit_expr(init);
+                }
+            }
+        }
+    }
+}
+
+fn walk_loop_left<'a, T, V>(v: &mut V, left: &'a LoopLeft<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    match left {
+        LoopLeft::Expr(expr) => v.visit_expr(expr),
+        LoopLeft::Variable(_, decl) => {
+            v.visit_pat(&decl.id);
+            if let Some(init) = &decl.init {
+                v.visit_expr(init);
+            }
+        }
+        LoopLeft::Pat(pat) => v.visit_pat(pat),
+    }
+}
+
+pub fn walk_expr<'a, T, V>(v: &mut V, expr: &'a Expr<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    match expr {
+        Expr::Array(elements) => {
+            for element in elements.iter().flatten() {
+                v.visit_expr(element);
+            }
+        }
+        Expr::ArrowFunc(arrow) => {
+            for param in &arrow.params {
+                v.visit_func_arg(param);
+            }
+            match &arrow.body {
+                ArrowFuncBody::FuncBody(body) => v.visit_parts(&body.0),
+                ArrowFuncBody::Expr(expr) => v.visit_expr(expr),
+            }
+        }
+        Expr::ArrowParamPlaceHolder(args, _) => {
+            for arg in args {
+                v.visit_func_arg(arg);
+            }
+        }
+        Expr::Assign(assign) => {
+            match &assign.left {
+                AssignLeft::Pat(pat) => v.visit_pat(pat),
+                AssignLeft::Expr(expr) => v.visit_expr(expr),
+            }
+            v.visit_expr(&assign.right);
+        }
+        Expr::Await(expr) | Expr::Spread(expr) | Expr::OptionalChain(expr) => v.visit_expr(expr),
+        Expr::Binary(binary) => {
+            v.visit_expr(&binary.left);
+            v.visit_expr(&binary.right);
+        }
+        Expr::Class(class) => v.visit_class(class),
+        Expr::Call(call) => {
+            v.visit_expr(&call.callee);
+            for arg in &call.arguments {
+                v.visit_expr(arg);
+            }
+        }
+        Expr::Conditional(cond) => {
+            v.visit_expr(&cond.test);
+            v.visit_expr(&cond.consequent);
+            v.visit_expr(&cond.alternate);
+        }
+        Expr::Func(func) => v.visit_func(func),
+        Expr::Ident(ident) => v.visit_ident(ident),
+        Expr::Import(import) => {
+            v.visit_expr(&import.source);
+            if let Some(options) = &import.options {
+                v.visit_expr(options);
+            }
+        }
+        Expr::JsxElement(element) => v.visit_jsx_element(element),
+        Expr::JsxFragment(fragment) => walk_jsx_children(v, &fragment.children),
+        Expr::Lit(Lit::Template(template)) => {
+            for expr in &template.expressions {
+                v.visit_expr(expr);
+            }
+        }
+        Expr::Invalid(_)
+        | Expr::Lit(_)
+        | Expr::MetaProp(_)
+        | Expr::PrivateName(_)
+        | Expr::Super
+        | Expr::This => {}
+        Expr::Logical(logical) => {
+            v.visit_expr(&logical.left);
+            v.visit_expr(&logical.right);
+        }
+        Expr::Member(member) => {
+            v.visit_expr(&member.object);
+            if matches!(
+                member.indexer,
+                MemberIndexer::Computed | MemberIndexer::OptionalComputed
+            ) {
+                v.visit_expr(&member.property);
+            }
+        }
+        Expr::New(new) => {
+            v.visit_expr(&new.callee);
+            for arg in &new.arguments {
+                v.visit_expr(arg);
+            }
+        }
+        Expr::Obj(props) => {
+            for prop in props {
+                match prop {
+                    ObjProp::Prop(prop) => v.visit_prop(prop),
+                    ObjProp::Spread(expr) => v.visit_expr(expr),
+                }
+            }
+        }
+        Expr::Sequence(exprs) => {
+            for expr in exprs {
+                v.visit_expr(expr);
+            }
+        }
+        Expr::TaggedTemplate(tagged) => {
+            v.visit_expr(&tagged.tag);
+            for expr in &tagged.quasi.expressions {
+                v.visit_expr(expr);
+            }
+        }
+        Expr::Unary(unary) => v.visit_expr(&unary.argument),
+        Expr::Update(update) => v.visit_expr(&update.argument),
+        Expr::Yield(yield_expr) => {
+            if let Some(arg) = &yield_expr.argument {
+                v.visit_expr(arg);
+            }
+        }
+    }
+}
+
+pub fn walk_pat<'a, T, V>(v: &mut V, pat: &'a Pat<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    match pat {
+        Pat::Ident(ident) => v.visit_ident(ident),
+        Pat::Obj(parts) => {
+            for part in parts {
+                match part {
+                    ObjPatPart::Assign(prop) => v.visit_prop(prop),
+                    ObjPatPart::Rest(pat) => v.visit_pat(pat),
+                }
+            }
+        }
+        Pat::Array(parts) => {
+            for part in parts.iter().flatten() {
+                match part {
+                    ArrayPatPart::Pat(pat) => v.visit_pat(pat),
+                    ArrayPatPart::Expr(expr) => v.visit_expr(expr),
+                }
+            }
+        }
+        Pat::RestElement(pat) => v.visit_pat(pat),
+        Pat::Assign(assign) => {
+            v.visit_pat(&assign.left);
+            v.visit_expr(&assign.right);
+        }
+        Pat::Invalid(_) => {}
+    }
+}
+
+pub fn walk_func<'a, T, V>(v: &mut V, func: &'a Func<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    for param in &func.params {
+        v.visit_func_arg(param);
+    }
+    if let Some(body) = &func.body {
+        v.visit_parts(&body.0);
+    }
+}
+
+pub fn walk_func_arg<'a, T, V>(v: &mut V, arg: &'a FuncArg<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    match arg {
+        FuncArg::Expr(expr) => v.visit_expr(expr),
+        FuncArg::Pat(pat) => v.visit_pat(pat),
+        FuncArg::Decorated(decorated) => {
+            for decorator in &decorated.decorators {
+                v.visit_expr(&decorator.expr);
+            }
+            v.visit_func_arg(&decorated.arg);
+        }
+        FuncArg::Typed(typed) => {
+            v.visit_pat(&typed.pat);
+            if let Some(default) = &typed.default {
+                v.visit_expr(default);
+            }
+        }
+    }
+}
+
+pub fn walk_class<'a, T, V>(v: &mut V, class: &'a Class<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    for decorator in &class.decorators {
+        v.visit_expr(&decorator.expr);
+    }
+    if let Some(super_class) = &class.super_class {
+        v.visit_expr(super_class);
+    }
+    for member in &class.body.0 {
+        match member {
+            ClassMember::Prop(prop) => v.visit_prop(prop),
+            ClassMember::Field(field) => {
+                for decorator in &field.decorators {
+                    v.visit_expr(&decorator.expr);
+                }
+                if field.computed {
+                    walk_prop_key(v, &field.key);
+                }
+                if let Some(value) = &field.value {
+                    v.visit_expr(value);
+                }
+            }
+            ClassMember::StaticBlock(body) => v.visit_parts(&body.0),
+        }
+    }
+}
+
+pub fn walk_prop<'a, T, V>(v: &mut V, prop: &'a Prop<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    for decorator in &prop.decorators {
+        v.visit_expr(&decorator.expr);
+    }
+    if prop.computed {
+        walk_prop_key(v, &prop.key);
+    }
+    match &prop.value {
+        PropValue::Expr(expr) => v.visit_expr(expr),
+        PropValue::Pat(pat) => v.visit_pat(pat),
+        PropValue::None => {}
+    }
+}
+
+fn walk_prop_key<'a, T, V>(v: &mut V, key: &'a PropKey<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    match key {
+        PropKey::Expr(expr) => v.visit_expr(expr),
+        PropKey::Pat(pat) => v.visit_pat(pat),
+        PropKey::Lit(_) | PropKey::Private(_) => {}
+    }
+}
+
+pub fn walk_jsx_element<'a, T, V>(v: &mut V, element: &'a JsxElement<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    if let JsxElementName::Member(member) = &element.name {
+        v.visit_ident(&member.object);
+    }
+    for attr in &element.attributes {
+        match attr {
+            JsxAttr::Attr(attr) => match &attr.value {
+                Some(JsxAttrValue::Expr(expr)) => v.visit_expr(expr),
+                Some(JsxAttrValue::Element(element)) => v.visit_jsx_element(element),
+                Some(JsxAttrValue::Fragment(fragment)) => walk_jsx_children(v, &fragment.children),
+                Some(JsxAttrValue::String(_)) | None => {}
+            },
+            JsxAttr::Spread(expr) => v.visit_expr(expr),
+        }
+    }
+    walk_jsx_children(v, &element.children);
+}
+
+fn walk_jsx_children<'a, T, V>(v: &mut V, children: &'a [JsxChild<T>])
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    for child in children {
+        match child {
+            JsxChild::Text(_) | JsxChild::Expr(None) => {}
+            JsxChild::Expr(Some(expr)) | JsxChild::Spread(expr) => v.visit_expr(expr),
+            JsxChild::Element(element) => v.visit_jsx_element(element),
+            JsxChild::Fragment(fragment) => walk_jsx_children(v, &fragment.children),
+        }
+    }
+}
@@ -0,0 +1,371 @@
+//! Decoding of the escapes in string literals and template elements
+//!
+//! The source text of a string (the "raw" value) is not the value seen
+//! at runtime (the "cooked" value), the escapes need to be decoded first.
+//! Cooked values are sequences of UTF-16 code units since escapes like
+//! `'\uD800'` can produce lone surrogates that a `String` cannot hold.
+//! ```js
+//! 'a\x62c\u{64}\145\
+//! f' // abcdef
+//! ```
+use std::fmt;
+
+/// The rules to apply while cooking
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CookMode {
+    /// A string literal in sloppy mode code
+    Sloppy,
+    /// A string literal in strict mode code, legacy octal
+    /// escapes and `\8`/`\9` are errors
+    Strict,
+    /// A template element, legacy octal escapes and `\8`/`\9`
+    /// are errors and line terminators are normalized to `\n`
+    Template,
+}
+
+/// Decode the escapes in the raw text between the quotes of a
+/// string literal or the delimiters of a template element
+pub fn cook(raw: &str, mode: CookMode) -> Result<Vec<u16>, CookError> {
+    let mut ret = Vec::with_capacity(raw.len());
+    let mut chars = raw.char_indices().peekable();
+    while let Some((index, ch)) = chars.next() {
+        match ch {
+            '\\' => {}
+            '\r' if mode == CookMode::Template => {
+                chars.next_if(|(_, ch)| *ch == '\n');
+                ret.push(b'\n' as u16);
+                continue;
+            }
+            ch => {
+                push_char(&mut ret, ch);
+                continue;
+            }
+        }
+        let (_, escaped) = chars.next().ok_or(CookError {
+            kind: CookErrorKind::UnterminatedEscape,
+            index,
+            len: 1,
+        })?;
+        let simple = match escaped {
+            'b' => Some(0x08),
+            't' => Some(0x09),
+            'n' => Some(0x0A),
+            'v' => Some(0x0B),
+            'f' => Some(0x0C),
+            'r' => Some(0x0D),
+            _ => None,
+        };
+        if let Some(unit) = simple {
+            ret.push(unit);
+            continue;
+        }
+        match escaped {
+            // line continuations
+            '\r' => {
+                chars.next_if(|(_, ch)| *ch == '\n');
+            }
+            '\n' | '\u{2028}' | '\u{2029}' => {}
+            '0' if !matches!(chars.peek(), Some((_, '0'..='9'))) => ret.push(0),
+            '0'..='7' => {
+                let mut value = escaped.to_digit(8).unwrap_or(0);
+                // `\377` is the largest three digit escape
+                let max_len = if value <= 3 { 3 } else { 2 };
+                let mut len = 2;
+                while len <= max_len {
+                    match chars.next_if(|(_, ch)| ch.is_digit(8)) {
+                        Some((_, digit)) => value = value * 8 + digit.to_digit(8).unwrap_or(0),
+                        None => break,
+                    }
+                    len += 1;
+                }
+                if mode != CookMode::Sloppy {
+                    return Err(CookError {
+                        kind: CookErrorKind::LegacyOctalEscape,
+                        index,
+                        len,
+                    });
+                }
+                ret.push(value as u16);
+            }
+            '8' | '9' => {
+                if mode != CookMode::Sloppy {
+                    return Err(CookError {
+                        kind: CookErrorKind::NonOctalDecimalEscape,
+                        index,
+                        len: 2,
+                    });
+                }
+                ret.push(escaped as u16);
+            }
+            'x' => {
+                let value = hex_digits(&mut chars, 2).ok_or_else(|| CookError {
+                    kind: CookErrorKind::InvalidHexEscape,
+                    index,
+                    len: escape_len(raw, index),
+                })?;
+                ret.push(value as u16);
+            }
+            'u' => {
+                let value = unicode_escape(&mut chars).ok_or_else(|| CookError {
+                    kind: CookErrorKind::InvalidUnicodeEscape,
+                    index,
+                    len: escape_len(raw, index),
+                })?;
+                if value > 0x10FFFF {
+                    return Err(CookError {
+                        kind: CookErrorKind::CodePointOutOfRange,
+                        index,
+                        len: escape_len(raw, index),
+                    });
+                }
+                push_code_point(&mut ret, value);
+            }
+            ch => push_char(&mut ret, ch),
+        }
+    }
+    Ok(ret)
+}
+
+/// The raw value of a template element as seen by `String.raw`
+/// or a tag function, line terminators are normalized to `\n`
+pub fn template_raw(raw: &str) -> Vec<u16> {
+    let mut ret = Vec::with_capacity(raw.len());
+    let mut chars = raw.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == '\r' {
+            chars.next_if_eq(&'\n');
+            ret.push(b'\n' as u16);
+        } else {
+            push_char(&mut ret, ch);
+        }
+    }
+    ret
+}
+
+/// A problem with an escape sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CookError {
+    pub kind: CookErrorKind,
+    /// The byte offset of the `\` starting the escape
+    pub index: usize,
+    /// The length of the escape in bytes
+    pub len: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CookErrorKind {
+    /// A `\` at the very end of the text
+    UnterminatedEscape,
+    /// `\x` not followed by two hex digits
+    InvalidHexEscape,
+    /// `\u` not followed by four hex digits or `{hex digits}`
+    InvalidUnicodeEscape,
+    /// `\u{110000}` or larger
+    CodePointOutOfRange,
+    /// `\1` through `\377` or `\0` followed by a digit
+    LegacyOctalEscape,
+    /// `\8` or `\9`
+    NonOctalDecimalEscape,
+}
+
+impl fmt::Display for CookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self.kind {
+            CookErrorKind::UnterminatedEscape => "unterminated escape sequence",
+            CookErrorKind::InvalidHexEscape => "invalid hexadecimal escape sequence",
+            CookErrorKind::InvalidUnicodeEscape => "invalid unicode escape sequence",
+            CookErrorKind::CodePointOutOfRange => "code point out of range",
+            CookErrorKind::LegacyOctalEscape => "octal escape sequences are not allowed here",
+            CookErrorKind::NonOctalDecimalEscape => "\\8 and \\9 are not allowed here",
+        };
+        write!(f, "{} at {}", msg, self.index)
+    }
+}
+
+impl std::error::Error for CookError {}
+
+type Chars<'a> = std::iter::Peekable<std::str::CharIndices<'a>>;
+
+fn hex_digits(chars: &mut Chars, count: usize) -> Option<u32> {
+    let mut value = 0;
+    for _ in 0..count {
+        let (_, ch) = chars.next_if(|(_, ch)| ch.is_ascii_hexdigit())?;
+        value = value * 16 + ch.to_digit(16)?;
+    }
+    Some(value)
+}
+
+/// The digits after `\u`, either `XXXX` or `{X...}`
+fn unicode_escape(chars: &mut Chars) -> Option<u32> {
+    if chars.next_if(|(_, ch)| *ch == '{').is_none() {
+        return hex_digits(chars, 4);
+    }
+    let mut value: u32 = 0;
+    let mut any = false;
+    while let Some((_, ch)) = chars.next_if(|(_, ch)| ch.is_ascii_hexdigit()) {
+        // saturate instead of overflowing, anything this
+        // large is already out of range
+        value = value.saturating_mul(16).saturating_add(ch.to_digit(16)?);
+        any = true;
+    }
+    chars.next_if(|(_, ch)| *ch == '}')?;
+    if any {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+/// The length of the malformed escape starting at `index`, it covers
+/// the `\`, the escape letter and any hex digits or braces that follow
+fn escape_len(raw: &str, index: usize) -> usize {
+    let rest = &raw[index + 2..];
+    let len = rest
+        .char_indices()
+        .find(|(_, ch)| !(ch.is_ascii_hexdigit() || *ch == '{' || *ch == '}'))
+        .map(|(i, _)| i)
+        .unwrap_or(rest.len());
+    len + 2
+}
+
+fn push_char(units: &mut Vec<u16>, ch: char) {
+    let mut buf = [0; 2];
+    units.extend_from_slice(ch.encode_utf16(&mut buf));
+}
+
+/// Push a code point that may be a lone surrogate
+fn push_code_point(units: &mut Vec<u16>, value: u32) {
+    match char::from_u32(value) {
+        Some(ch) => push_char(units, ch),
+        None => units.push(value as u16),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn units(s: &str) -> Vec<u16> {
+        s.encode_utf16().collect()
+    }
+
+    fn error(raw: &str, mode: CookMode) -> (CookErrorKind, usize, usize) {
+        let err = cook(raw, mode).unwrap_err();
+        (err.kind, err.index, err.len)
+    }
+
+    #[test]
+    fn simple_escapes() {
+        assert_eq!(
+            cook("a\\b\\t\\n\\v\\f\\r\\'\\\"\\\\\\q", CookMode::Strict).unwrap(),
+            units("a\u{8}\t\n\u{b}\u{c}\r'\"\\q")
+        );
+        assert_eq!(
+            cook("a\\x62c\\u{64}\\u0065", CookMode::Strict).unwrap(),
+            units("abcde")
+        );
+        assert_eq!(cook("\\0", CookMode::Strict).unwrap(), vec![0]);
+        assert_eq!(cook("\\u{1F600}", CookMode::Strict).unwrap(), units("😀"));
+        assert_eq!(cook("\\u{000041}", CookMode::Strict).unwrap(), units("A"));
+    }
+
+    #[test]
+    fn line_continuations() {
+        assert_eq!(cook("a\\\nb", CookMode::Strict).unwrap(), units("ab"));
+        assert_eq!(cook("a\\\r\nb", CookMode::Strict).unwrap(), units("ab"));
+        assert_eq!(cook("a\\\u{2028}b", CookMode::Strict).unwrap(), units("ab"));
+        assert_eq!(cook("a\\\u{2029}b", CookMode::Strict).unwrap(), units("ab"));
+    }
+
+    #[test]
+    fn template_line_terminators() {
+        assert_eq!(
+            cook("a\r\nb\rc", CookMode::Template).unwrap(),
+            units("a\nb\nc")
+        );
+        assert_eq!(cook("a\r\nb", CookMode::Sloppy).unwrap(), units("a\r\nb"));
+        assert_eq!(template_raw("a\r\n\\n\rb"), units("a\n\\n\nb"));
+    }
+
+    #[test]
+    fn legacy_octal() {
+        assert_eq!(cook("\\1", CookMode::Sloppy).unwrap(), vec![1]);
+        assert_eq!(cook("\\101", CookMode::Sloppy).unwrap(), units("A"));
+        assert_eq!(cook("\\377", CookMode::Sloppy).unwrap(), vec![0xFF]);
+        // only two digits fit after a leading 4 to 7
+        assert_eq!(cook("\\400", CookMode::Sloppy).unwrap(), units(" 0"));
+        assert_eq!(cook("\\08", CookMode::Sloppy).unwrap(), units("\u{0}8"));
+        assert_eq!(cook("\\8\\9", CookMode::Sloppy).unwrap(), units("89"));
+        assert_eq!(
+            error("a\\101", CookMode::Strict),
+            (CookErrorKind::LegacyOctalEscape, 1, 4)
+        );
+        assert_eq!(
+            error("\\08", CookMode::Strict),
+            (CookErrorKind::LegacyOctalEscape, 0, 2)
+        );
+        assert_eq!(
+            error("\\7", CookMode::Template),
+            (CookErrorKind::LegacyOctalEscape, 0, 2)
+        );
+        assert_eq!(
+            error("\\8", CookMode::Strict),
+            (CookErrorKind::NonOctalDecimalEscape, 0, 2)
+        );
+        assert_eq!(
+            error("\\9", CookMode::Template),
+            (CookErrorKind::NonOctalDecimalEscape, 0, 2)
+        );
+    }
+
+    #[test]
+    fn lone_surrogates() {
+        assert_eq!(cook("\\uD800", CookMode::Strict).unwrap(), vec![0xD800]);
+        assert_eq!(
+            cook("\\uDC00x", CookMode::Strict).unwrap(),
+            vec![0xDC00, 0x78]
+        );
+        assert_eq!(cook("\\u{D83D}", CookMode::Strict).unwrap(), vec![0xD83D]);
+        // an escaped pair is the same code units as the character itself
+        assert_eq!(
+            cook("\\uD83D\\uDE00", CookMode::Strict).unwrap(),
+            units("😀")
+        );
+        assert_eq!(
+            cook("\\u{10FFFF}", CookMode::Strict).unwrap(),
+            vec![0xDBFF, 0xDFFF]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            error("ab\\", CookMode::Sloppy),
+            (CookErrorKind::UnterminatedEscape, 2, 1)
+        );
+        assert_eq!(
+            error("\\x4", CookMode::Sloppy).0,
+            CookErrorKind::InvalidHexEscape
+        );
+        assert_eq!(
+            error("\\xg0", CookMode::Sloppy).0,
+            CookErrorKind::InvalidHexEscape
+        );
+        assert_eq!(
+            error("\\u12", CookMode::Sloppy).0,
+            CookErrorKind::InvalidUnicodeEscape
+        );
+        assert_eq!(
+            error("\\u{}", CookMode::Sloppy).0,
+            CookErrorKind::InvalidUnicodeEscape
+        );
+        assert_eq!(
+            error("\\u{41", CookMode::Sloppy).0,
+            CookErrorKind::InvalidUnicodeEscape
+        );
+        assert_eq!(
+            error("x\\u{110000}", CookMode::Sloppy),
+            (CookErrorKind::CodePointOutOfRange, 1, 10)
+        );
+    }
+}
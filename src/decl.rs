@@ -1,7 +1,10 @@
-use crate::expr::{Expr, Lit};
+use crate::cook::CookError;
+use crate::expr::{Expr, Lit, StringLit};
 use crate::pat::Pat;
-use crate::{Class, Func, Ident};
+use crate::ts::{allocate_type_params, TsType, TsTypeMember, TsTypeParam, TsTypeRef};
+use crate::{Class, Func, Ident, ProgramPart};
 use crate::{IntoAllocated, VarKind};
+use std::fmt;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -38,6 +41,49 @@ pub enum Decl<T> {
     /// export function thing() {}
     /// ```
     Export(Box<ModExport<T>>),
+    /// A typescript import alias declaration
+    /// ```ts
+    /// import fs = require('fs');
+    /// import Thing = Stuff.Thing;
+    /// ```
+    ImportEquals(Box<ImportEqualsDecl<T>>),
+    /// An ambient declaration, describing a value that
+    /// exists at runtime without providing it
+    /// ```ts
+    /// declare const thing;
+    /// declare function stuff(a);
+    /// declare class Thing {}
+    /// ```
+    Declare(Box<Decl<T>>),
+    /// A typescript namespace or module declaration
+    /// ```ts
+    /// namespace Stuff.Things {}
+    /// declare module 'stuff' {}
+    /// declare global {}
+    /// ```
+    Module(Box<ModuleDecl<T>>),
+    /// A typescript interface declaration
+    /// ```ts
+    /// interface Thing extends Stuff {
+    ///     name: string;
+    /// }
+    /// ```
+    Interface(Box<InterfaceDecl<T>>),
+    /// A typescript type alias
+    /// ```ts
+    /// type Pair<T> = [T, T];
+    /// ```
+    TypeAlias(Box<TypeAliasDecl<T>>),
+    /// A typescript enum declaration
+    /// ```ts
+    /// enum Color {
+    ///     Red,
+    ///     Green = 'green',
+    /// }
+    /// ```
+    Enum(Box<EnumDecl<T>>),
+    /// Source that could not be parsed as a declaration
+    Invalid(T),
 }
 
 impl<T> IntoAllocated for Decl<T>
@@ -55,6 +101,13 @@ where
             Decl::Class(inner) => Decl::Class(inner.into_allocated()),
             Decl::Import(inner) => Decl::Import(inner.into_allocated()),
             Decl::Export(inner) => Decl::Export(inner.into_allocated()),
+            Decl::ImportEquals(inner) => Decl::ImportEquals(inner.into_allocated()),
+            Decl::Declare(inner) => Decl::Declare(inner.into_allocated()),
+            Decl::Module(inner) => Decl::Module(inner.into_allocated()),
+            Decl::Interface(inner) => Decl::Interface(inner.into_allocated()),
+            Decl::TypeAlias(inner) => Decl::TypeAlias(inner.into_allocated()),
+            Decl::Enum(inner) => Decl::Enum(inner.into_allocated()),
+            Decl::Invalid(text) => Decl::Invalid(text.to_string()),
         }
     }
 }
@@ -64,6 +117,8 @@ where
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct VarDecl<T> {
     pub id: Pat<T>,
+    /// `let x: number;`
+    pub type_annotation: Option<TsType<T>>,
     pub init: Option<Expr<T>>,
 }
 
@@ -76,6 +131,7 @@ where
     fn into_allocated(self) -> Self::Allocated {
         VarDecl {
             id: self.id.into_allocated(),
+            type_annotation: self.type_annotation.into_allocated(),
             init: self.init.map(|i| i.into_allocated()),
         }
     }
@@ -87,11 +143,22 @@ where
 /// ```js
 /// import {Thing} from './stuff.js';
 /// ```
+/// or, when `is_type_only` is set, an import that
+/// only brings in types and is erased entirely
+/// ```ts
+/// import type {Thing} from './stuff.js';
+/// ```
+/// `attributes` holds the entries of a trailing `with` clause
+/// ```js
+/// import data from './data.json' with {type: 'json'};
+/// ```
 #[derive(PartialEq, Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct ModImport<T> {
     pub specifiers: Vec<ImportSpecifier<T>>,
     pub source: Lit<T>,
+    pub is_type_only: bool,
+    pub attributes: Vec<ImportAttribute<T>>,
 }
 
 impl<T> IntoAllocated for ModImport<T>
@@ -108,6 +175,61 @@ where
                 .map(|s| s.into_allocated())
                 .collect(),
             source: self.source.into_allocated(),
+            is_type_only: self.is_type_only,
+            attributes: self
+                .attributes
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+        }
+    }
+}
+
+/// A single entry of the `with` clause of an import or re-export
+/// ```js
+/// import data from './data.json' with {type: 'json'};
+/// export * from './data.json' with {'type': 'json'};
+/// ```
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct ImportAttribute<T> {
+    pub key: ImportAttributeKey<T>,
+    pub value: StringLit<T>,
+}
+
+impl<T> IntoAllocated for ImportAttribute<T>
+where
+    T: ToString,
+{
+    type Allocated = ImportAttribute<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        ImportAttribute {
+            key: self.key.into_allocated(),
+            value: self.value.into_allocated(),
+        }
+    }
+}
+
+/// The key of an import attribute, either an identifier
+/// or a string
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum ImportAttributeKey<T> {
+    Ident(Ident<T>),
+    String(StringLit<T>),
+}
+
+impl<T> IntoAllocated for ImportAttributeKey<T>
+where
+    T: ToString,
+{
+    type Allocated = ImportAttributeKey<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            ImportAttributeKey::Ident(inner) => ImportAttributeKey::Ident(inner.into_allocated()),
+            ImportAttributeKey::String(inner) => ImportAttributeKey::String(inner.into_allocated()),
         }
     }
 }
@@ -157,11 +279,17 @@ where
     }
 }
 
+/// A single name in curly braces of an import, when
+/// `is_type_only` is set it was prefixed with `type`
+/// ```ts
+/// import {type Thing, stuff, 'a-b' as ab} from './stuff.js';
+/// ```
 #[derive(PartialEq, Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct NormalImportSpec<T> {
     pub alias: Option<Ident<T>>,
-    pub imported: Ident<T>,
+    pub imported: ModuleExportName<T>,
+    pub is_type_only: bool,
 }
 
 impl<T> IntoAllocated for NormalImportSpec<T>
@@ -174,6 +302,7 @@ where
         NormalImportSpec {
             alias: self.alias.map(|i| i.into_allocated()),
             imported: self.imported.into_allocated(),
+            is_type_only: self.is_type_only,
         }
     }
 }
@@ -201,11 +330,19 @@ pub enum ModExport<T> {
     Named(NamedExportDecl<T>),
     /// ```js
     /// export * from 'mod';
+    /// //or
+    /// export * as stuff from 'mod' with {type: 'json'};
     /// ```
     All {
-        alias: Option<Ident<T>>,
+        alias: Option<ModuleExportName<T>>,
         name: Lit<T>,
+        attributes: Vec<ImportAttribute<T>>,
     },
+    /// A typescript export assignment
+    /// ```ts
+    /// export = thing;
+    /// ```
+    Assign(Expr<T>),
 }
 
 impl<T> IntoAllocated for ModExport<T>
@@ -218,10 +355,19 @@ where
         match self {
             ModExport::Default(inner) => ModExport::Default(inner.into_allocated()),
             ModExport::Named(inner) => ModExport::Named(inner.into_allocated()),
-            ModExport::All { alias, name } => ModExport::All {
+            ModExport::All {
+                alias,
+                name,
+                attributes,
+            } => ModExport::All {
                 alias: alias.map(|i| i.into_allocated()),
                 name: name.into_allocated(),
+                attributes: attributes
+                    .into_iter()
+                    .map(IntoAllocated::into_allocated)
+                    .collect(),
             },
+            ModExport::Assign(inner) => ModExport::Assign(inner.into_allocated()),
         }
     }
 }
@@ -230,11 +376,18 @@ where
 /// ```js
 /// export function thing() {}
 /// export {stuff} from 'place';
+/// export {data} from './data.json' with {type: 'json'};
+/// ```
+/// a re-export's source is followed by its import attributes
 #[derive(PartialEq, Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum NamedExportDecl<T> {
     Decl(Decl<T>),
-    Specifier(Vec<ExportSpecifier<T>>, Option<Lit<T>>),
+    Specifier(
+        Vec<ExportSpecifier<T>>,
+        Option<Lit<T>>,
+        Vec<ImportAttribute<T>>,
+    ),
 }
 
 impl<T> IntoAllocated for NamedExportDecl<T>
@@ -246,9 +399,13 @@ where
     fn into_allocated(self) -> Self::Allocated {
         match self {
             NamedExportDecl::Decl(inner) => NamedExportDecl::Decl(inner.into_allocated()),
-            NamedExportDecl::Specifier(specs, lit) => NamedExportDecl::Specifier(
+            NamedExportDecl::Specifier(specs, lit, attributes) => NamedExportDecl::Specifier(
                 specs.into_iter().map(|s| s.into_allocated()).collect(),
                 lit.map(|l| l.into_allocated()),
+                attributes
+                    .into_iter()
+                    .map(IntoAllocated::into_allocated)
+                    .collect(),
             ),
         }
     }
@@ -286,12 +443,18 @@ where
 /// export {Thing} from 'place';
 /// //aliased
 /// export {Stuff as NewThing} from 'place'
+/// //string names
+/// export {Stuff as 'new-thing', 'a-b'} from 'place'
 /// ```
+/// `is_type_only` is set for `export {type Thing}` and for
+/// every specifier of an `export type {Thing}` list
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct ExportSpecifier<T> {
-    pub local: Ident<T>,
-    pub alias: Option<Ident<T>>,
+    /// Only a string when re-exporting from another module
+    pub local: ModuleExportName<T>,
+    pub alias: Option<ModuleExportName<T>>,
+    pub is_type_only: bool,
 }
 
 impl<T> IntoAllocated for ExportSpecifier<T>
@@ -304,6 +467,367 @@ where
         ExportSpecifier {
             local: self.local.into_allocated(),
             alias: self.alias.map(|a| a.into_allocated()),
+            is_type_only: self.is_type_only,
+        }
+    }
+}
+
+/// The name of an export as seen by other modules,
+/// either an identifier or a string
+/// ```js
+/// export {thing as "a-b"};
+/// import {"a-b" as thing} from './stuff.js';
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum ModuleExportName<T> {
+    Ident(Ident<T>),
+    String(StringLit<T>),
+}
+
+impl<T> IntoAllocated for ModuleExportName<T>
+where
+    T: ToString,
+{
+    type Allocated = ModuleExportName<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            ModuleExportName::Ident(inner) => ModuleExportName::Ident(inner.into_allocated()),
+            ModuleExportName::String(inner) => ModuleExportName::String(inner.into_allocated()),
+        }
+    }
+}
+
+impl<T> From<Ident<T>> for ModuleExportName<T> {
+    fn from(other: Ident<T>) -> Self {
+        ModuleExportName::Ident(other)
+    }
+}
+
+impl<T> ModuleExportName<T> {
+    /// The identifier, if this name is not a string
+    pub fn as_ident(&self) -> Option<&Ident<T>> {
+        match self {
+            ModuleExportName::Ident(inner) => Some(inner),
+            ModuleExportName::String(_) => None,
+        }
+    }
+}
+
+impl<T> ModuleExportName<T>
+where
+    T: AsRef<str>,
+{
+    /// The name other modules use, a string name is cooked and
+    /// must be well formed unicode, without any lone surrogates
+    pub fn value(&self) -> Result<String, ModuleExportNameError> {
+        match self {
+            ModuleExportName::Ident(inner) => Ok(inner.name.as_ref().to_string()),
+            ModuleExportName::String(inner) => {
+                // module code is always strict
+                let units = inner.cooked(true).map_err(ModuleExportNameError::Cook)?;
+                String::from_utf16(&units).map_err(|_| ModuleExportNameError::LoneSurrogate)
+            }
+        }
+    }
+}
+
+/// A string export name that is not allowed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModuleExportNameError {
+    /// An escape sequence in the string is malformed
+    Cook(CookError),
+    /// The cooked string contains an unpaired surrogate
+    LoneSurrogate,
+}
+
+impl fmt::Display for ModuleExportNameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModuleExportNameError::Cook(inner) => inner.fmt(f),
+            ModuleExportNameError::LoneSurrogate => {
+                f.write_str("module export names must not contain lone surrogates")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ModuleExportNameError {}
+
+/// A typescript import alias, either of an external
+/// module or of an entity in a namespace
+/// ```ts
+/// import fs = require('fs');
+/// import type Thing = Stuff.Thing;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct ImportEqualsDecl<T> {
+    pub id: Ident<T>,
+    pub module_ref: ModuleRef<T>,
+    pub is_type_only: bool,
+}
+
+impl<T> IntoAllocated for ImportEqualsDecl<T>
+where
+    T: ToString,
+{
+    type Allocated = ImportEqualsDecl<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        ImportEqualsDecl {
+            id: self.id.into_allocated(),
+            module_ref: self.module_ref.into_allocated(),
+            is_type_only: self.is_type_only,
+        }
+    }
+}
+
+/// The right hand side of an `ImportEqualsDecl`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum ModuleRef<T> {
+    /// ```ts
+    /// import fs = require('fs');
+    /// ```
+    Require(Lit<T>),
+    /// An identifier or a chain of member accesses
+    /// ```ts
+    /// import Thing = Stuff.Thing;
+    /// ```
+    Entity(Expr<T>),
+}
+
+impl<T> IntoAllocated for ModuleRef<T>
+where
+    T: ToString,
+{
+    type Allocated = ModuleRef<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            ModuleRef::Require(inner) => ModuleRef::Require(inner.into_allocated()),
+            ModuleRef::Entity(inner) => ModuleRef::Entity(inner.into_allocated()),
+        }
+    }
+}
+
+/// A typescript namespace or module declaration, the body will be
+/// `None` for a shorthand ambient module
+/// ```ts
+/// namespace Stuff {
+///     export const thing = 0;
+/// }
+/// declare module 'stuff';
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct ModuleDecl<T> {
+    pub name: ModuleName<T>,
+    pub body: Option<Vec<ProgramPart<T>>>,
+}
+
+impl<T> IntoAllocated for ModuleDecl<T>
+where
+    T: ToString,
+{
+    type Allocated = ModuleDecl<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        ModuleDecl {
+            name: self.name.into_allocated(),
+            body: self
+                .body
+                .map(|b| b.into_iter().map(|p| p.into_allocated()).collect()),
+        }
+    }
+}
+
+/// The name of a `ModuleDecl`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum ModuleName<T> {
+    /// A namespace name, with one entry per dotted segment
+    /// ```ts
+    /// namespace Stuff.Things {}
+    /// ```
+    Namespace(Vec<Ident<T>>),
+    /// An ambient external module
+    /// ```ts
+    /// declare module 'stuff' {}
+    /// ```
+    Lit(Lit<T>),
+    /// A global augmentation
+    /// ```ts
+    /// declare global {}
+    /// ```
+    Global,
+}
+
+impl<T> IntoAllocated for ModuleName<T>
+where
+    T: ToString,
+{
+    type Allocated = ModuleName<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            ModuleName::Namespace(inner) => {
+                ModuleName::Namespace(inner.into_iter().map(|i| i.into_allocated()).collect())
+            }
+            ModuleName::Lit(inner) => ModuleName::Lit(inner.into_allocated()),
+            ModuleName::Global => ModuleName::Global,
+        }
+    }
+}
+
+/// A typescript interface declaration
+/// ```ts
+/// interface Thing<T> extends Stuff, Other<T> {
+///     name: string;
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct InterfaceDecl<T> {
+    pub id: Ident<T>,
+    pub type_params: Vec<TsTypeParam<T>>,
+    pub extends: Vec<TsTypeRef<T>>,
+    pub body: Vec<TsTypeMember<T>>,
+}
+
+impl<T> IntoAllocated for InterfaceDecl<T>
+where
+    T: ToString,
+{
+    type Allocated = InterfaceDecl<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        InterfaceDecl {
+            id: self.id.into_allocated(),
+            type_params: allocate_type_params(self.type_params),
+            extends: self
+                .extends
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            body: self
+                .body
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+        }
+    }
+}
+
+/// A typescript type alias
+/// ```ts
+/// type Pair<T> = [T, T];
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TypeAliasDecl<T> {
+    pub id: Ident<T>,
+    pub type_params: Vec<TsTypeParam<T>>,
+    pub type_annotation: TsType<T>,
+}
+
+impl<T> IntoAllocated for TypeAliasDecl<T>
+where
+    T: ToString,
+{
+    type Allocated = TypeAliasDecl<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TypeAliasDecl {
+            id: self.id.into_allocated(),
+            type_params: allocate_type_params(self.type_params),
+            type_annotation: self.type_annotation.into_allocated(),
+        }
+    }
+}
+
+/// A typescript enum, `const enum` when `is_const` is set
+/// ```ts
+/// const enum Color {
+///     Red,
+///     Green = 'green',
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct EnumDecl<T> {
+    pub is_const: bool,
+    pub id: Ident<T>,
+    pub members: Vec<EnumMember<T>>,
+}
+
+impl<T> IntoAllocated for EnumDecl<T>
+where
+    T: ToString,
+{
+    type Allocated = EnumDecl<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        EnumDecl {
+            is_const: self.is_const,
+            id: self.id.into_allocated(),
+            members: self
+                .members
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+        }
+    }
+}
+
+/// A single member of an `EnumDecl` with its optional initializer
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct EnumMember<T> {
+    pub id: EnumMemberName<T>,
+    pub init: Option<Expr<T>>,
+}
+
+impl<T> IntoAllocated for EnumMember<T>
+where
+    T: ToString,
+{
+    type Allocated = EnumMember<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        EnumMember {
+            id: self.id.into_allocated(),
+            init: self.init.into_allocated(),
+        }
+    }
+}
+
+/// The name of an `EnumMember`
+/// ```ts
+/// enum Thing {
+///     stuff,
+///     'other stuff',
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum EnumMemberName<T> {
+    Ident(Ident<T>),
+    String(StringLit<T>),
+}
+
+impl<T> IntoAllocated for EnumMemberName<T>
+where
+    T: ToString,
+{
+    type Allocated = EnumMemberName<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            EnumMemberName::Ident(inner) => EnumMemberName::Ident(inner.into_allocated()),
+            EnumMemberName::String(inner) => EnumMemberName::String(inner.into_allocated()),
         }
     }
 }
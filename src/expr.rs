@@ -1,8 +1,13 @@
+use crate::cook::{cook, template_raw, CookError, CookMode};
+use crate::jsx::{JsxElement, JsxFragment};
+use crate::number::{NumberError, NumericLit};
 use crate::pat::Pat;
+use crate::regex::{self, Regex, RegexError};
+use crate::ts::{allocate_type_params, Accessibility, TsType, TsTypeParam};
 use crate::{
     AssignOp, BinaryOp, IntoAllocated, LogicalOp, MemberIndexer, PropKind, UnaryOp, UpdateOp,
 };
-use crate::{Class, Func, FuncArg, FuncBody, Ident};
+use crate::{Class, Decorator, Func, FuncArg, FuncBody, Ident};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -44,6 +49,21 @@ pub enum Expr<T> {
     Func(Func<T>),
     /// An identifier
     Ident(Ident<T>),
+    /// A dynamic import
+    /// ```js
+    /// const stuff = await import('./stuff.js');
+    /// ```
+    Import(ImportCall<T>),
+    /// Source that could not be parsed as an expression, kept so an
+    /// editor can still work with the rest of a half typed file
+    /// ```js
+    /// let x = 1 + ;
+    /// ```
+    Invalid(T),
+    /// A JSX element, see `JsxElement`
+    JsxElement(JsxElement<T>),
+    /// A JSX fragment, see `JsxFragment`
+    JsxFragment(JsxFragment<T>),
     /// A literal value, see `Literal`
     Lit(Lit<T>),
     /// A specialized `BinaryExpr` for logical evaluation
@@ -58,8 +78,8 @@ pub enum Expr<T> {
     /// c.stuff;
     /// ```
     Member(MemberExpr<T>),
-    /// currently just `new.target`
-    MetaProp(MetaProp<T>),
+    /// `new.target` or `import.meta`
+    MetaProp(MetaProp),
     /// ```js
     /// var a = true ? 'stuff' : 'things';
     /// ```
@@ -67,6 +87,13 @@ pub enum Expr<T> {
     /// Calling a constructor
     New(NewExpr<T>),
     Obj(ObjExpr<T>),
+    /// A private class member name, as the property of a
+    /// `MemberExpr` or the left side of `in`
+    /// ```js
+    /// this.#secret;
+    /// #secret in obj;
+    /// ```
+    PrivateName(PrivateName<T>),
     /// Any sequence of expressions separated with a comma
     Sequence(SequenceExpr<T>),
     /// `...` followed by an `Expr`
@@ -121,12 +148,16 @@ where
             Expr::Conditional(inner) => Expr::Conditional(inner.into_allocated()),
             Expr::Func(inner) => Expr::Func(inner.into_allocated()),
             Expr::Ident(inner) => Expr::Ident(inner.into_allocated()),
+            Expr::Import(inner) => Expr::Import(inner.into_allocated()),
+            Expr::JsxElement(inner) => Expr::JsxElement(inner.into_allocated()),
+            Expr::JsxFragment(inner) => Expr::JsxFragment(inner.into_allocated()),
             Expr::Lit(inner) => Expr::Lit(inner.into_allocated()),
             Expr::Logical(inner) => Expr::Logical(inner.into_allocated()),
             Expr::Member(inner) => Expr::Member(inner.into_allocated()),
-            Expr::MetaProp(inner) => Expr::MetaProp(inner.into_allocated()),
+            Expr::MetaProp(inner) => Expr::MetaProp(inner),
             Expr::New(inner) => Expr::New(inner.into_allocated()),
             Expr::Obj(inner) => Expr::Obj(inner.into_iter().map(|p| p.into_allocated()).collect()),
+            Expr::PrivateName(inner) => Expr::PrivateName(inner.into_allocated()),
             Expr::Sequence(inner) => {
                 Expr::Sequence(inner.into_iter().map(|e| e.into_allocated()).collect())
             }
@@ -138,6 +169,7 @@ where
             Expr::Update(inner) => Expr::Update(inner.into_allocated()),
             Expr::Yield(inner) => Expr::Yield(inner.into_allocated()),
             Expr::OptionalChain(inner) => Expr::OptionalChain(inner.into_allocated()),
+            Expr::Invalid(text) => Expr::Invalid(text.to_string()),
         }
     }
 }
@@ -185,6 +217,14 @@ pub struct Prop<T> {
     pub computed: bool,
     pub short_hand: bool,
     pub is_static: bool,
+    /// `abstract thing();` inside of an abstract class
+    pub is_abstract: bool,
+    /// Only allowed on class methods and accessors
+    pub decorators: Vec<Decorator<T>>,
+    /// `private thing();` in a class
+    pub accessibility: Option<Accessibility>,
+    /// `thing?();` in a class
+    pub is_optional: bool,
 }
 
 impl<T> IntoAllocated for Prop<T>
@@ -202,6 +242,14 @@ where
             computed: self.computed,
             short_hand: self.short_hand,
             is_static: self.is_static,
+            is_abstract: self.is_abstract,
+            decorators: self
+                .decorators
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            accessibility: self.accessibility,
+            is_optional: self.is_optional,
         }
     }
 }
@@ -213,6 +261,8 @@ pub enum PropKey<T> {
     Lit(Lit<T>),
     Expr(Expr<T>),
     Pat(Pat<T>),
+    /// `#secret`, only in a class body
+    Private(PrivateName<T>),
 }
 
 impl<T> IntoAllocated for PropKey<T>
@@ -226,6 +276,27 @@ where
             PropKey::Lit(inner) => PropKey::Lit(inner.into_allocated()),
             PropKey::Expr(inner) => PropKey::Expr(inner.into_allocated()),
             PropKey::Pat(inner) => PropKey::Pat(inner.into_allocated()),
+            PropKey::Private(inner) => PropKey::Private(inner.into_allocated()),
+        }
+    }
+}
+
+/// The name of a private class member without the leading `#`
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct PrivateName<T> {
+    pub id: Ident<T>,
+}
+
+impl<T> IntoAllocated for PrivateName<T>
+where
+    T: ToString,
+{
+    type Allocated = PrivateName<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        PrivateName {
+            id: self.id.into_allocated(),
         }
     }
 }
@@ -529,7 +600,9 @@ pub type SequenceExpr<T> = Vec<Expr<T>>;
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct ArrowFuncExpr<T> {
     pub id: Option<Ident<T>>,
+    pub type_params: Vec<TsTypeParam<T>>,
     pub params: Vec<FuncArg<T>>,
+    pub return_type: Option<TsType<T>>,
     pub body: ArrowFuncBody<T>,
     pub expression: bool,
     pub generator: bool,
@@ -545,11 +618,13 @@ where
     fn into_allocated(self) -> Self::Allocated {
         ArrowFuncExpr {
             id: self.id.map(|i| i.into_allocated()),
+            type_params: allocate_type_params(self.type_params),
             params: self
                 .params
                 .into_iter()
                 .map(|p| p.into_allocated())
                 .collect(),
+            return_type: self.return_type.into_allocated(),
             body: self.body.into_allocated(),
             expression: self.expression,
             generator: self.generator,
@@ -710,33 +785,67 @@ impl<T> TemplateElement<T> {
     }
 }
 
-/// pretty much just `new.target`
+impl<T> TemplateElement<T>
+where
+    T: AsRef<str>,
+{
+    /// The runtime value of this element as UTF-16 code units, a tagged
+    /// template receives `undefined` when this is an error
+    pub fn cooked(&self) -> Result<Vec<u16>, CookError> {
+        cook(self.content.as_ref(), CookMode::Template)
+    }
+
+    /// The value `String.raw` would see as UTF-16 code units
+    pub fn raw(&self) -> Vec<u16> {
+        template_raw(self.content.as_ref())
+    }
+}
+
+/// A meta property, a keyword followed by a
+/// property that is only valid in that position
+#[derive(PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum MetaProp {
+    /// ```js
+    /// function Thing(one, two) {
+    ///     if (!new.target) {
+    ///         return new Thing(one, two);
+    ///     }
+    ///     this.one = one;
+    ///     this.two = two;
+    /// }
+    /// ```
+    NewTarget,
+    /// Information about the current module, only valid in a module
+    /// ```js
+    /// const data = new URL('./data.json', import.meta.url);
+    /// ```
+    ImportMeta,
+}
+
+/// A call to `import()`, `options` is the optional second argument
+/// that carries the import attributes
 /// ```js
-/// function Thing(one, two) {
-///     if (!new.target) {
-///         return new Thing(one, two);
-///     }
-///     this.one = one;
-///     this.two = two;
-/// }
+/// import('./stuff.js');
+/// import('./data.json', {with: {type: 'json'}});
 /// ```
 #[derive(PartialEq, Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
-pub struct MetaProp<T> {
-    pub meta: Ident<T>,
-    pub property: Ident<T>,
+pub struct ImportCall<T> {
+    pub source: Box<Expr<T>>,
+    pub options: Option<Box<Expr<T>>>,
 }
 
-impl<T> IntoAllocated for MetaProp<T>
+impl<T> IntoAllocated for ImportCall<T>
 where
     T: ToString,
 {
-    type Allocated = MetaProp<String>;
+    type Allocated = ImportCall<String>;
 
     fn into_allocated(self) -> Self::Allocated {
-        MetaProp {
-            meta: self.meta.into_allocated(),
-            property: self.property.into_allocated(),
+        ImportCall {
+            source: self.source.into_allocated(),
+            options: self.options.map(|o| o.into_allocated()),
         }
     }
 }
@@ -759,6 +868,10 @@ pub enum Lit<T> {
     /// `0o7`
     /// `0b1`
     Number(T),
+    /// The raw text includes the `n` suffix
+    /// `10n`
+    /// `0xffn`
+    BigInt(T),
     /// `true`
     /// `false`
     Boolean(bool),
@@ -781,6 +894,7 @@ where
             Lit::Null => Lit::Null,
             Lit::String(inner) => Lit::String(inner.into_allocated()),
             Lit::Number(inner) => Lit::Number(inner.to_string()),
+            Lit::BigInt(inner) => Lit::BigInt(inner.to_string()),
             Lit::Boolean(inner) => Lit::Boolean(inner),
             Lit::RegEx(inner) => Lit::RegEx(inner.into_allocated()),
             Lit::Template(inner) => Lit::Template(inner.into_allocated()),
@@ -792,6 +906,9 @@ impl<T> Lit<T> {
     pub fn number_from(s: T) -> Self {
         Lit::Number(s)
     }
+    pub fn big_int_from(s: T) -> Self {
+        Lit::BigInt(s)
+    }
     pub fn single_string_from(s: T) -> Self {
         Lit::String(StringLit::single_from(s))
     }
@@ -821,6 +938,20 @@ where
     }
 }
 
+impl<T> Lit<T>
+where
+    T: AsRef<str>,
+{
+    /// Analyze a `Number` or `BigInt` literal, `None` for
+    /// any other kind of literal
+    pub fn numeric(&self) -> Option<Result<NumericLit, NumberError>> {
+        match self {
+            Lit::Number(raw) | Lit::BigInt(raw) => Some(NumericLit::parse(raw.as_ref())),
+            _ => None,
+        }
+    }
+}
+
 impl<T> StringLit<T> {
     pub fn double_from(s: T) -> StringLit<T> {
         StringLit::Double(s)
@@ -851,6 +982,21 @@ where
             StringLit::Double(ref d) => o.eq(d.as_ref()),
         }
     }
+
+    /// The runtime value of this string as UTF-16 code units
+    pub fn cooked(&self, strict: bool) -> Result<Vec<u16>, CookError> {
+        let raw = match self {
+            StringLit::Single(s) | StringLit::Double(s) => s.as_ref(),
+        };
+        cook(
+            raw,
+            if strict {
+                CookMode::Strict
+            } else {
+                CookMode::Sloppy
+            },
+        )
+    }
 }
 /// A regular expression literal
 #[derive(PartialEq, Debug, Clone)]
@@ -882,3 +1028,14 @@ impl<T> RegEx<T> {
         }
     }
 }
+
+impl<T> RegEx<T>
+where
+    T: AsRef<str>,
+{
+    /// Validate the flags and parse the pattern into a tree
+    pub fn parse(&self) -> Result<Regex, RegexError> {
+        let flags = self.flags.as_ref().map(|f| f.as_ref()).unwrap_or("");
+        regex::parse(self.pattern.as_ref(), flags)
+    }
+}
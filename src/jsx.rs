@@ -0,0 +1,296 @@
+//! JSX extensions to `Expr`, these only appear in
+//! trees for `.jsx` or `.tsx` sources
+use crate::expr::{Expr, StringLit};
+use crate::{Ident, IntoAllocated};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// An element with a tag name
+/// ```jsx
+/// <div className="thing">{stuff}</div>
+/// <Input value={value} />
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct JsxElement<T> {
+    pub name: JsxElementName<T>,
+    pub attributes: Vec<JsxAttr<T>>,
+    pub children: Vec<JsxChild<T>>,
+    /// `<a />` instead of `<a></a>`, always without children
+    pub self_closing: bool,
+}
+
+impl<T> IntoAllocated for JsxElement<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxElement<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        JsxElement {
+            name: self.name.into_allocated(),
+            attributes: self
+                .attributes
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            children: self
+                .children
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            self_closing: self.self_closing,
+        }
+    }
+}
+
+/// An element without a tag name
+/// ```jsx
+/// <><li>one</li><li>two</li></>
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct JsxFragment<T> {
+    pub children: Vec<JsxChild<T>>,
+}
+
+impl<T> IntoAllocated for JsxFragment<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxFragment<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        JsxFragment {
+            children: self
+                .children
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+        }
+    }
+}
+
+/// The tag name of an element
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum JsxElementName<T> {
+    /// `<div>` or `<Thing>`, may contain `-`
+    Ident(Ident<T>),
+    /// `<svg:rect>`
+    Namespaced(JsxNamespacedName<T>),
+    /// `<Context.Provider>`
+    Member(JsxMemberExpr<T>),
+}
+
+impl<T> IntoAllocated for JsxElementName<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxElementName<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            JsxElementName::Ident(inner) => JsxElementName::Ident(inner.into_allocated()),
+            JsxElementName::Namespaced(inner) => JsxElementName::Namespaced(inner.into_allocated()),
+            JsxElementName::Member(inner) => JsxElementName::Member(inner.into_allocated()),
+        }
+    }
+}
+
+impl<T> JsxElementName<T>
+where
+    T: AsRef<str>,
+{
+    /// A built in tag like `div` or `my-element` that is
+    /// referred to by its name rather than by a binding in scope
+    pub fn is_intrinsic(&self) -> bool {
+        match self {
+            JsxElementName::Ident(ident) => is_intrinsic_name(ident.name.as_ref()),
+            JsxElementName::Namespaced(_) => true,
+            JsxElementName::Member(_) => false,
+        }
+    }
+}
+
+fn is_intrinsic_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase()) || name.contains('-')
+}
+
+/// `namespace:name`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct JsxNamespacedName<T> {
+    pub namespace: Ident<T>,
+    pub name: Ident<T>,
+}
+
+impl<T> IntoAllocated for JsxNamespacedName<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxNamespacedName<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        JsxNamespacedName {
+            namespace: self.namespace.into_allocated(),
+            name: self.name.into_allocated(),
+        }
+    }
+}
+
+/// `object.a.b`, there is always at least one property
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct JsxMemberExpr<T> {
+    pub object: Ident<T>,
+    pub properties: Vec<Ident<T>>,
+}
+
+impl<T> IntoAllocated for JsxMemberExpr<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxMemberExpr<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        JsxMemberExpr {
+            object: self.object.into_allocated(),
+            properties: self
+                .properties
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+        }
+    }
+}
+
+/// A single entry in the attribute list of an element
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum JsxAttr<T> {
+    /// `name="value"`, `name={value}` or just `name`
+    Attr(JsxAttribute<T>),
+    /// `{...props}`
+    Spread(Expr<T>),
+}
+
+impl<T> IntoAllocated for JsxAttr<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxAttr<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            JsxAttr::Attr(inner) => JsxAttr::Attr(inner.into_allocated()),
+            JsxAttr::Spread(inner) => JsxAttr::Spread(inner.into_allocated()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct JsxAttribute<T> {
+    pub name: JsxAttrName<T>,
+    /// `None` for a bare attribute like `<input disabled />`
+    pub value: Option<JsxAttrValue<T>>,
+}
+
+impl<T> IntoAllocated for JsxAttribute<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxAttribute<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        JsxAttribute {
+            name: self.name.into_allocated(),
+            value: self.value.map(IntoAllocated::into_allocated),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum JsxAttrName<T> {
+    /// `className` or `aria-label`
+    Ident(Ident<T>),
+    /// `xlink:href`
+    Namespaced(JsxNamespacedName<T>),
+}
+
+impl<T> IntoAllocated for JsxAttrName<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxAttrName<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            JsxAttrName::Ident(inner) => JsxAttrName::Ident(inner.into_allocated()),
+            JsxAttrName::Namespaced(inner) => JsxAttrName::Namespaced(inner.into_allocated()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum JsxAttrValue<T> {
+    /// A quoted string, the content is the raw source text
+    /// which has HTML entities but no escape sequences
+    String(StringLit<T>),
+    /// `{value}`
+    Expr(Expr<T>),
+    Element(JsxElement<T>),
+    Fragment(JsxFragment<T>),
+}
+
+impl<T> IntoAllocated for JsxAttrValue<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxAttrValue<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            JsxAttrValue::String(inner) => JsxAttrValue::String(inner.into_allocated()),
+            JsxAttrValue::Expr(inner) => JsxAttrValue::Expr(inner.into_allocated()),
+            JsxAttrValue::Element(inner) => JsxAttrValue::Element(inner.into_allocated()),
+            JsxAttrValue::Fragment(inner) => JsxAttrValue::Fragment(inner.into_allocated()),
+        }
+    }
+}
+
+/// A single child of an element or fragment
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum JsxChild<T> {
+    /// The raw source text between tags, including any
+    /// whitespace and HTML entities
+    Text(T),
+    /// `{value}`, `None` for an empty container like `{/* comment */}`
+    Expr(Option<Expr<T>>),
+    /// `{...children}`
+    Spread(Expr<T>),
+    Element(JsxElement<T>),
+    Fragment(JsxFragment<T>),
+}
+
+impl<T> IntoAllocated for JsxChild<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxChild<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            JsxChild::Text(inner) => JsxChild::Text(inner.to_string()),
+            JsxChild::Expr(inner) => JsxChild::Expr(inner.map(IntoAllocated::into_allocated)),
+            JsxChild::Spread(inner) => JsxChild::Spread(inner.into_allocated()),
+            JsxChild::Element(inner) => JsxChild::Element(inner.into_allocated()),
+            JsxChild::Fragment(inner) => JsxChild::Fragment(inner.into_allocated()),
+        }
+    }
+}
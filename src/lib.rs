@@ -1,8 +1,16 @@
+pub mod analysis;
+pub mod cook;
 pub mod decl;
 pub mod expr;
+pub mod jsx;
+pub mod modules;
+pub mod number;
 pub mod pat;
+pub mod regex;
 pub mod spanned;
 pub mod stmt;
+pub mod transform;
+pub mod ts;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -10,9 +18,10 @@ use serde::{Deserialize, Serialize};
 use std::{borrow::Cow, fmt::Debug};
 
 use decl::Decl;
-use expr::{Expr, Lit, Prop};
+use expr::{Expr, Lit, Prop, PropKey};
 use pat::Pat;
 use stmt::Stmt;
+use ts::{allocate_type_params, Accessibility, TsType, TsTypeParam, TsTypeRef};
 
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
@@ -56,13 +65,19 @@ impl<'a> From<Cow<'a, str>> for Ident<Cow<'a, str>> {
 /// It is essentially a collection of `ProgramPart`s
 /// with a flag denoting if the representation is
 /// a ES6 Mod or a Script.
+///
+/// The second field is the hashbang, the text after `#!` on
+/// the first line
+/// ```js
+/// #!/usr/bin/env node
+/// ```
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum Program<T> {
     /// An ES6 Mod
-    Mod(Vec<ProgramPart<T>>),
+    Mod(Vec<ProgramPart<T>>, Option<T>),
     /// Not an ES6 Mod
-    Script(Vec<ProgramPart<T>>),
+    Script(Vec<ProgramPart<T>>, Option<T>),
 }
 
 impl<T> IntoAllocated for Program<T>
@@ -73,22 +88,89 @@ where
 
     fn into_allocated(self) -> Self::Allocated {
         match self {
-            Program::Mod(inner) => {
-                Program::Mod(inner.into_iter().map(|p| p.into_allocated()).collect())
-            }
-            Program::Script(inner) => {
-                Program::Script(inner.into_iter().map(|p| p.into_allocated()).collect())
-            }
+            Program::Mod(inner, hashbang) => Program::Mod(
+                inner.into_iter().map(|p| p.into_allocated()).collect(),
+                hashbang.map(|h| h.to_string()),
+            ),
+            Program::Script(inner, hashbang) => Program::Script(
+                inner.into_iter().map(|p| p.into_allocated()).collect(),
+                hashbang.map(|h| h.to_string()),
+            ),
         }
     }
 }
 
 impl<T> Program<T> {
     pub fn module(parts: Vec<ProgramPart<T>>) -> Self {
-        Program::Mod(parts)
+        Program::Mod(parts, None)
     }
     pub fn script(parts: Vec<ProgramPart<T>>) -> Self {
-        Program::Script(parts)
+        Program::Script(parts, None)
+    }
+    pub fn with_hashbang(self, hashbang: T) -> Self {
+        match self {
+            Program::Mod(parts, _) => Program::Mod(parts, Some(hashbang)),
+            Program::Script(parts, _) => Program::Script(parts, Some(hashbang)),
+        }
+    }
+    pub fn is_module(&self) -> bool {
+        matches!(self, Program::Mod(..))
+    }
+    pub fn parts(&self) -> &[ProgramPart<T>] {
+        match self {
+            Program::Mod(parts, _) | Program::Script(parts, _) => parts,
+        }
+    }
+    pub fn parts_mut(&mut self) -> &mut Vec<ProgramPart<T>> {
+        match self {
+            Program::Mod(parts, _) | Program::Script(parts, _) => parts,
+        }
+    }
+    pub fn hashbang(&self) -> Option<&T> {
+        match self {
+            Program::Mod(_, hashbang) | Program::Script(_, hashbang) => hashbang.as_ref(),
+        }
+    }
+}
+
+/// A whole source file, the `Program` along with the
+/// parts of the file that are not javascript
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct SourceFile<T> {
+    /// Where the file was loaded from, like a path or url
+    pub name: Option<String>,
+    /// The file starts with a `U+FEFF` byte order mark
+    pub bom: bool,
+    pub program: Program<T>,
+}
+
+impl<T> IntoAllocated for SourceFile<T>
+where
+    T: ToString,
+{
+    type Allocated = SourceFile<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        SourceFile {
+            name: self.name,
+            bom: self.bom,
+            program: self.program.into_allocated(),
+        }
+    }
+}
+
+impl<T> SourceFile<T> {
+    pub fn new(program: Program<T>) -> Self {
+        Self {
+            name: None,
+            bom: false,
+            program,
+        }
+    }
+    /// See `Program::hashbang`
+    pub fn hashbang(&self) -> Option<&T> {
+        self.program.hashbang()
     }
 }
 
@@ -162,12 +244,25 @@ where
 /// var x = function() {}
 /// let y = function q() {}
 /// ```
+/// The body will be `None` for a signature without an
+/// implementation
+/// ```ts
+/// declare function thing(a);
+/// function overloaded(a);
+/// function overloaded(a, b) {}
+/// ```
+/// `type_params` and `return_type` hold the typescript annotations
+/// ```ts
+/// function first<T>(list: T[]): T {}
+/// ```
 #[derive(PartialEq, Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Func<T> {
     pub id: Option<Ident<T>>,
+    pub type_params: Vec<TsTypeParam<T>>,
     pub params: Vec<FuncArg<T>>,
-    pub body: FuncBody<T>,
+    pub return_type: Option<TsType<T>>,
+    pub body: Option<FuncBody<T>>,
     pub generator: bool,
     pub is_async: bool,
 }
@@ -181,12 +276,14 @@ where
     fn into_allocated(self) -> Self::Allocated {
         Func {
             id: self.id.map(IntoAllocated::into_allocated),
+            type_params: allocate_type_params(self.type_params),
             params: self
                 .params
                 .into_iter()
                 .map(|p| p.into_allocated())
                 .collect(),
-            body: self.body.into_allocated(),
+            return_type: self.return_type.into_allocated(),
+            body: self.body.map(IntoAllocated::into_allocated),
             generator: self.generator,
             is_async: self.is_async,
         }
@@ -203,12 +300,37 @@ impl<T> Func<T> {
     ) -> Self {
         Func {
             id,
+            type_params: Vec::new(),
             params,
-            body,
+            return_type: None,
+            body: Some(body),
             generator,
             is_async,
         }
     }
+
+    /// Create a function signature without a body
+    pub fn signature(
+        id: Option<Ident<T>>,
+        params: Vec<FuncArg<T>>,
+        generator: bool,
+        is_async: bool,
+    ) -> Self {
+        Func {
+            id,
+            type_params: Vec::new(),
+            params,
+            return_type: None,
+            body: None,
+            generator,
+            is_async,
+        }
+    }
+
+    /// If this function has no body
+    pub fn is_signature(&self) -> bool {
+        self.body.is_none()
+    }
 }
 
 /// A single function argument from a function signature
@@ -217,6 +339,21 @@ impl<T> Func<T> {
 pub enum FuncArg<T> {
     Expr(Expr<T>),
     Pat(Pat<T>),
+    /// A constructor parameter with typescript style decorators
+    /// ```ts
+    /// class Thing {
+    ///     constructor(@inject(Db) db) {}
+    /// }
+    /// ```
+    Decorated(Box<DecoratedArg<T>>),
+    /// A parameter with typescript annotations or modifiers
+    /// ```ts
+    /// function thing(a: string, b?: number, ...c: boolean[]) {}
+    /// class Thing {
+    ///     constructor(private readonly db: Db) {}
+    /// }
+    /// ```
+    Typed(Box<TypedArg<T>>),
 }
 
 impl<T> IntoAllocated for FuncArg<T>
@@ -229,6 +366,68 @@ where
         match self {
             FuncArg::Expr(inner) => FuncArg::Expr(inner.into_allocated()),
             FuncArg::Pat(inner) => FuncArg::Pat(inner.into_allocated()),
+            FuncArg::Decorated(inner) => FuncArg::Decorated(inner.into_allocated()),
+            FuncArg::Typed(inner) => FuncArg::Typed(inner.into_allocated()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct DecoratedArg<T> {
+    pub decorators: Vec<Decorator<T>>,
+    pub arg: FuncArg<T>,
+}
+
+impl<T> IntoAllocated for DecoratedArg<T>
+where
+    T: ToString,
+{
+    type Allocated = DecoratedArg<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        DecoratedArg {
+            decorators: self
+                .decorators
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            arg: self.arg.into_allocated(),
+        }
+    }
+}
+
+/// The parts of a `FuncArg::Typed`, `pat` is a `Pat::RestElement`
+/// for a rest parameter and `default` holds the initializer since
+/// the annotation sits between the pattern and the `=`
+/// ```ts
+/// function thing(a?: number, b: string = 'b') {}
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TypedArg<T> {
+    pub accessibility: Option<Accessibility>,
+    pub is_readonly: bool,
+    pub pat: Pat<T>,
+    pub is_optional: bool,
+    pub type_annotation: Option<TsType<T>>,
+    pub default: Option<Expr<T>>,
+}
+
+impl<T> IntoAllocated for TypedArg<T>
+where
+    T: ToString,
+{
+    type Allocated = TypedArg<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TypedArg {
+            accessibility: self.accessibility,
+            is_readonly: self.is_readonly,
+            pat: self.pat.into_allocated(),
+            is_optional: self.is_optional,
+            type_annotation: self.type_annotation.into_allocated(),
+            default: self.default.into_allocated(),
         }
     }
 }
@@ -288,8 +487,17 @@ where
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Class<T> {
     pub id: Option<Ident<T>>,
+    /// `class Thing<T> {}`
+    pub type_params: Vec<TsTypeParam<T>>,
     pub super_class: Option<Box<Expr<T>>>,
+    /// `class Thing extends Stuff<string> {}`
+    pub super_type_args: Vec<TsType<T>>,
+    /// `class Thing implements Stuff, Other {}`
+    pub implements: Vec<TsTypeRef<T>>,
     pub body: ClassBody<T>,
+    /// `abstract class Thing {}`
+    pub is_abstract: bool,
+    pub decorators: Vec<Decorator<T>>,
 }
 
 impl<T> IntoAllocated for Class<T>
@@ -301,15 +509,32 @@ where
     fn into_allocated(self) -> Self::Allocated {
         Class {
             id: self.id.map(IntoAllocated::into_allocated),
+            type_params: allocate_type_params(self.type_params),
             super_class: self.super_class.map(IntoAllocated::into_allocated),
+            super_type_args: self
+                .super_type_args
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            implements: self
+                .implements
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
             body: self.body.into_allocated(),
+            is_abstract: self.is_abstract,
+            decorators: self
+                .decorators
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
-pub struct ClassBody<T>(pub Vec<Prop<T>>);
+pub struct ClassBody<T>(pub Vec<ClassMember<T>>);
 
 impl<T> IntoAllocated for ClassBody<T>
 where
@@ -331,8 +556,127 @@ impl<T> Class<T> {
     pub fn new(id: Option<Ident<T>>, super_class: Option<Expr<T>>, body: Vec<Prop<T>>) -> Class<T> {
         Class {
             id,
+            type_params: Vec::new(),
             super_class: super_class.map(Box::new),
-            body: ClassBody(body),
+            super_type_args: Vec::new(),
+            implements: Vec::new(),
+            body: ClassBody(body.into_iter().map(ClassMember::Prop).collect()),
+            is_abstract: false,
+            decorators: Vec::new(),
+        }
+    }
+}
+
+/// `@` followed by an expression, attached to a class,
+/// class member or constructor parameter
+/// ```js
+/// @injectable
+/// class Thing {
+///     @validate(positive)
+///     count = 0;
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Decorator<T> {
+    pub expr: Expr<T>,
+}
+
+impl<T> IntoAllocated for Decorator<T>
+where
+    T: ToString,
+{
+    type Allocated = Decorator<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        Decorator {
+            expr: self.expr.into_allocated(),
+        }
+    }
+}
+
+/// A single part of a class body
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum ClassMember<T> {
+    /// A method, accessor or constructor
+    /// ```js
+    /// class Thing {
+    ///     constructor() {}
+    ///     get #stuff() {}
+    /// }
+    /// ```
+    Prop(Prop<T>),
+    /// A field with an optional initializer
+    /// ```js
+    /// class Thing {
+    ///     x = 1;
+    ///     static #count;
+    /// }
+    /// ```
+    Field(ClassField<T>),
+    /// A static initialization block
+    /// ```js
+    /// class Thing {
+    ///     static {
+    ///         Thing.ready = true;
+    ///     }
+    /// }
+    /// ```
+    StaticBlock(FuncBody<T>),
+}
+
+impl<T> IntoAllocated for ClassMember<T>
+where
+    T: ToString,
+{
+    type Allocated = ClassMember<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            ClassMember::Prop(inner) => ClassMember::Prop(inner.into_allocated()),
+            ClassMember::Field(inner) => ClassMember::Field(inner.into_allocated()),
+            ClassMember::StaticBlock(inner) => ClassMember::StaticBlock(inner.into_allocated()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct ClassField<T> {
+    pub key: PropKey<T>,
+    pub value: Option<Expr<T>>,
+    pub computed: bool,
+    pub is_static: bool,
+    pub decorators: Vec<Decorator<T>>,
+    /// `private readonly x?: number;`
+    pub accessibility: Option<Accessibility>,
+    pub is_readonly: bool,
+    pub is_optional: bool,
+    pub type_annotation: Option<TsType<T>>,
+}
+
+impl<T> IntoAllocated for ClassField<T>
+where
+    T: ToString,
+{
+    type Allocated = ClassField<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        ClassField {
+            key: self.key.into_allocated(),
+            value: self.value.map(IntoAllocated::into_allocated),
+            computed: self.computed,
+            is_static: self.is_static,
+            decorators: self
+                .decorators
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            accessibility: self.accessibility,
+            is_readonly: self.is_readonly,
+            is_optional: self.is_optional,
+            type_annotation: self.type_annotation.into_allocated(),
         }
     }
 }
@@ -348,7 +692,7 @@ pub enum MemberIndexer {
     OptionalComputed,
 }
 
-/// The kind of variable being defined (`var`/`let`/`const`)
+/// The kind of variable being defined (`var`/`let`/`const`/`using`)
 #[derive(Debug, Clone, PartialEq, Copy)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[cfg_attr(
@@ -359,6 +703,21 @@ pub enum VarKind {
     Var,
     Let,
     Const,
+    /// Only in a block, a function or module body, or the head
+    /// of a `for` or `for...of` loop
+    Using,
+    #[cfg_attr(
+        all(feature = "serde", feature = "esprima"),
+        serde(rename = "await using")
+    )]
+    AwaitUsing,
+}
+
+impl VarKind {
+    /// `using` or `await using`
+    pub fn is_using(&self) -> bool {
+        matches!(self, VarKind::Using | VarKind::AwaitUsing)
+    }
 }
 
 /// The available operators for assignment Exprs
@@ -487,22 +846,34 @@ where
 
 pub mod prelude {
     pub use crate::decl::{
-        Decl, DefaultExportDecl, ExportSpecifier, ImportSpecifier, ModExport, ModImport,
-        NamedExportDecl, NormalImportSpec, VarDecl,
+        Decl, DefaultExportDecl, EnumDecl, EnumMember, EnumMemberName, ExportSpecifier,
+        ImportAttribute, ImportAttributeKey, ImportEqualsDecl, ImportSpecifier, InterfaceDecl,
+        ModExport, ModImport, ModuleDecl, ModuleExportName, ModuleName, ModuleRef, NamedExportDecl,
+        NormalImportSpec, TypeAliasDecl, VarDecl,
     };
     pub use crate::expr::{
         ArrayExpr, ArrowFuncBody, ArrowFuncExpr, AssignExpr, AssignLeft, BinaryExpr, CallExpr,
-        ConditionalExpr, Expr, Lit, LogicalExpr, MemberExpr, MetaProp, NewExpr, ObjExpr, ObjProp,
-        Prop, PropKey, PropValue, RegEx, StringLit, TaggedTemplateExpr, TemplateElement,
-        TemplateLit, UnaryExpr, UpdateExpr, YieldExpr,
+        ConditionalExpr, Expr, ImportCall, Lit, LogicalExpr, MemberExpr, MetaProp, NewExpr,
+        ObjExpr, ObjProp, PrivateName, Prop, PropKey, PropValue, RegEx, StringLit,
+        TaggedTemplateExpr, TemplateElement, TemplateLit, UnaryExpr, UpdateExpr, YieldExpr,
+    };
+    pub use crate::jsx::{
+        JsxAttr, JsxAttrName, JsxAttrValue, JsxAttribute, JsxChild, JsxElement, JsxElementName,
+        JsxFragment, JsxMemberExpr, JsxNamespacedName,
     };
     pub use crate::pat::{ArrayPatPart, AssignPat, ObjPat, ObjPatPart, Pat};
     pub use crate::stmt::{
         BlockStmt, CatchClause, DoWhileStmt, ForInStmt, ForOfStmt, ForStmt, IfStmt, LabeledStmt,
         LoopInit, LoopLeft, Stmt, SwitchCase, SwitchStmt, TryStmt, WhileStmt, WithStmt,
     };
+    pub use crate::ts::{
+        Accessibility, TsCallSignature, TsConditionalType, TsFuncType, TsIndexSignature,
+        TsIndexedType, TsKeyword, TsMethodSignature, TsPredicateParam, TsPropSignature, TsType,
+        TsTypeMember, TsTypeOperator, TsTypeParam, TsTypePredicate, TsTypeRef,
+    };
     pub use crate::{
-        AssignOp, BinaryOp, Class, ClassBody, Dir, Func, FuncArg, FuncBody, Ident, LogicalOp,
-        Program, ProgramPart, PropKind, UnaryOp, UpdateOp, VarKind,
+        AssignOp, BinaryOp, Class, ClassBody, ClassField, ClassMember, DecoratedArg, Decorator,
+        Dir, Func, FuncArg, FuncBody, Ident, LogicalOp, Program, ProgramPart, PropKind, SourceFile,
+        TypedArg, UnaryOp, UpdateOp, VarKind,
     };
 }
@@ -0,0 +1,962 @@
+//! The public API of a module, for comparing two versions of it
+//!
+//! ```js
+//! import {parse} from './parser.js';
+//! export function render() {}
+//! export const VERSION = '1.0';
+//! export {parse, parse as default};
+//! export * from './util.js';
+//! ```
+//! exports `render` as a function, `VERSION` as a `const`, and both
+//! `parse` and `default` as re-exports of `parse` from `./parser.js`.
+//! The names `./util.js` exports are only known with the other module,
+//! so the `export *` is kept as a star export.
+//!
+//! Diffing the surface of two releases lists the exports that were
+//! removed, added or changed, a change being a different kind or a
+//! different type only-ness. Where an export is declared can move
+//! without it being a change. Re-exports are compared by their
+//! specifier as written, so `./util.js` and `./util` differ until
+//! `ExportSurface::resolve_sources` maps both to the file they name.
+use std::collections::HashMap;
+
+use crate::spanned::decl::{
+    Decl, DefaultExportDeclValue, ExportSpecifier, ImportSpecifier, ModExportSpecifier, ModuleName,
+    ModuleRef, NamedExportDecl,
+};
+use crate::spanned::expr::{AssignLeft, Expr, Lit, Prop, PropKey, PropValue};
+use crate::spanned::pat::{ArrayPatPart, ObjPatPart, Pat};
+use crate::spanned::{Ident, Node, Program, ProgramPart, SourceLocation};
+use crate::VarKind;
+
+/// The name TypeScript gives the value of `export = thing`
+pub const ASSIGNMENT_EXPORT: &str = "export=";
+
+/// Every export of `program`, a script has none
+pub fn surface<T>(program: &Program<T>) -> ExportSurface
+where
+    T: AsRef<str>,
+{
+    let parts = match program {
+        Program::Mod(parts, _) => parts,
+        Program::Script(..) => return ExportSurface::default(),
+    };
+    let mut locals = HashMap::new();
+    for part in parts {
+        if let ProgramPart::Decl(decl) = part {
+            declare(decl, &mut locals);
+        }
+    }
+    let mut surface = ExportSurface::default();
+    for part in parts {
+        if let ProgramPart::Decl(Decl::Export { export, .. }) = part {
+            surface.export(&export.spec, &locals);
+        }
+    }
+    surface
+}
+
+/// What a module exports
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExportSurface {
+    /// The named exports, including `default`, in source order
+    pub exports: Vec<Export>,
+    /// The modules whose exports are forwarded by `export * from`
+    pub stars: Vec<StarExport>,
+}
+
+impl ExportSurface {
+    /// The export named `name`
+    pub fn get(&self, name: &str) -> Option<&Export> {
+        self.exports.iter().find(|export| export.name == name)
+    }
+
+    /// Replace the specifier of every re-export and star export with
+    /// what `resolve` maps it to, like the path `Resolver::resolve`
+    /// finds, so `diff` compares where names come from instead of how
+    /// the specifier is written. A specifier `resolve` returns `None`
+    /// for is kept
+    pub fn resolve_sources<F>(&mut self, mut resolve: F)
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        let sources = self
+            .exports
+            .iter_mut()
+            .filter_map(|export| match &mut export.kind {
+                ExportKind::ReExport { source, .. } => Some(source),
+                _ => None,
+            })
+            .chain(self.stars.iter_mut().map(|star| &mut star.source));
+        for source in sources {
+            if let Some(resolved) = resolve(source) {
+                *source = resolved;
+            }
+        }
+    }
+
+    /// What changed from `self` to `newer`: the removed and changed
+    /// exports in the order of `self`, then the added exports in the
+    /// order of `newer`, then the star exports that went away or were
+    /// added
+    pub fn diff(&self, newer: &ExportSurface) -> Vec<ExportChange> {
+        let mut changes = Vec::new();
+        for old in &self.exports {
+            match newer.get(&old.name) {
+                None => changes.push(ExportChange::Removed(old.clone())),
+                Some(new) if new.kind != old.kind || new.is_type_only != old.is_type_only => {
+                    changes.push(ExportChange::Changed {
+                        old: old.clone(),
+                        new: new.clone(),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        for new in &newer.exports {
+            if self.get(&new.name).is_none() {
+                changes.push(ExportChange::Added(new.clone()));
+            }
+        }
+        for old in &self.stars {
+            if !newer.stars.iter().any(|star| star.source == old.source) {
+                changes.push(ExportChange::StarRemoved(old.clone()));
+            }
+        }
+        for new in &newer.stars {
+            if !self.stars.iter().any(|star| star.source == new.source) {
+                changes.push(ExportChange::StarAdded(new.clone()));
+            }
+        }
+        changes
+    }
+
+    fn export<T>(&mut self, spec: &ModExportSpecifier<T>, locals: &HashMap<String, Local>)
+    where
+        T: AsRef<str>,
+    {
+        match spec {
+            ModExportSpecifier::Default { value, .. } => {
+                let (kind, loc) = match value {
+                    DefaultExportDeclValue::Decl(decl) => match declared(decl).into_iter().next() {
+                        Some((_, local)) => (local.kind, local.loc),
+                        None => (ExportKind::Expr, decl.loc()),
+                    },
+                    DefaultExportDeclValue::Expr(expr) => default_expr(expr, locals),
+                };
+                self.push("default".to_string(), kind, loc, false);
+            }
+            ModExportSpecifier::Named(NamedExportDecl::Decl(decl)) => {
+                for (name, local) in declared(decl) {
+                    self.push(name, local.kind, local.loc, local.is_type_only);
+                }
+            }
+            ModExportSpecifier::Named(NamedExportDecl::Specifier(spec)) => {
+                let source = spec.source.as_ref().map(|source| specifier(&source.module));
+                for entry in &spec.list.elements {
+                    let specifier = &entry.item;
+                    let (local, exported) = match names(specifier) {
+                        Some(names) => names,
+                        None => continue,
+                    };
+                    let is_type_only =
+                        spec.keyword_type.is_some() || specifier.keyword_type.is_some();
+                    match &source {
+                        Some(source) => self.push(
+                            exported,
+                            ExportKind::ReExport {
+                                source: source.clone(),
+                                imported: Some(local),
+                            },
+                            specifier.loc(),
+                            is_type_only,
+                        ),
+                        None => match locals.get(&local) {
+                            Some(declared) => self.push(
+                                exported,
+                                declared.kind.clone(),
+                                declared.loc,
+                                is_type_only || declared.is_type_only,
+                            ),
+                            None => self.push(
+                                exported,
+                                ExportKind::Undeclared,
+                                specifier.loc(),
+                                is_type_only,
+                            ),
+                        },
+                    }
+                }
+            }
+            ModExportSpecifier::All { alias, name, .. } => {
+                let source = specifier(name);
+                match alias {
+                    Some(alias) => {
+                        if let Ok(exported) = alias.name.value() {
+                            self.push(
+                                exported,
+                                ExportKind::ReExport {
+                                    source,
+                                    imported: None,
+                                },
+                                spec.loc(),
+                                false,
+                            )
+                        }
+                    }
+                    None => self.stars.push(StarExport {
+                        source,
+                        loc: spec.loc(),
+                    }),
+                }
+            }
+            ModExportSpecifier::Assign { expr, .. } => {
+                let (kind, loc) = default_expr(expr, locals);
+                self.push(ASSIGNMENT_EXPORT.to_string(), kind, loc, false);
+            }
+        }
+    }
+
+    /// Add an export, the first one wins for names that are exported
+    /// more than once, like the overloads of a function
+    fn push(&mut self, name: String, kind: ExportKind, loc: SourceLocation, is_type_only: bool) {
+        if self.get(&name).is_some() {
+            return;
+        }
+        self.exports.push(Export {
+            name,
+            kind,
+            loc,
+            is_type_only,
+        });
+    }
+}
+
+/// One exported name
+#[derive(Debug, Clone, PartialEq)]
+pub struct Export {
+    pub name: String,
+    pub kind: ExportKind,
+    /// Where the exported binding is declared, for a re-export this is
+    /// the specifier that names the other module
+    pub loc: SourceLocation,
+    /// `export type {a}`, `export {type a}` or an export of a type only
+    /// import, which is erased from the JavaScript
+    pub is_type_only: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportKind {
+    Function,
+    Class,
+    Var(VarKind),
+    /// A typescript namespace
+    Namespace,
+    /// A typescript interface, always type only
+    Interface,
+    /// A typescript type alias, always type only
+    TypeAlias,
+    /// A typescript enum
+    Enum,
+    /// A name from another module, `None` for its namespace object,
+    /// `source` is the cooked specifier unless
+    /// `ExportSurface::resolve_sources` mapped it
+    /// ```js
+    /// export {a} from './a.js';
+    /// export * as b from './b.js';
+    /// import c from './c.js';
+    /// export {c};
+    /// ```
+    ReExport {
+        source: String,
+        imported: Option<String>,
+    },
+    /// `import a = B.c`
+    Alias,
+    /// The value of `export default` or `export =` that is not a
+    /// declaration or an identifier
+    Expr,
+    /// A local export of a name the module does not declare
+    Undeclared,
+}
+
+/// `export * from`
+#[derive(Debug, Clone, PartialEq)]
+pub struct StarExport {
+    /// The cooked specifier of the other module, or what
+    /// `ExportSurface::resolve_sources` mapped it to
+    pub source: String,
+    pub loc: SourceLocation,
+}
+
+/// A difference between two export surfaces
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportChange {
+    Removed(Export),
+    Added(Export),
+    Changed { old: Export, new: Export },
+    StarRemoved(StarExport),
+    StarAdded(StarExport),
+}
+
+impl ExportChange {
+    /// If importers of the old surface may no longer work, anything
+    /// other than an addition, a type only export becoming a value of
+    /// the same kind or a variable changing between `var`, `let` and
+    /// `const`, which importers can only read either way
+    pub fn is_breaking(&self) -> bool {
+        match self {
+            ExportChange::Removed(_) | ExportChange::StarRemoved(_) => true,
+            ExportChange::Added(_) | ExportChange::StarAdded(_) => false,
+            ExportChange::Changed { old, new } => {
+                !old.kind.is_compatible(&new.kind) || new.is_type_only
+            }
+        }
+    }
+}
+
+impl ExportKind {
+    /// If an importer sees no difference between the two kinds, the
+    /// kinds of variable only differ for assignments inside the module
+    pub fn is_compatible(&self, other: &ExportKind) -> bool {
+        match (self, other) {
+            (ExportKind::Var(_), ExportKind::Var(_)) => true,
+            _ => self == other,
+        }
+    }
+}
+
+/// A top level binding
+#[derive(Debug, Clone, PartialEq)]
+struct Local {
+    kind: ExportKind,
+    loc: SourceLocation,
+    is_type_only: bool,
+}
+
+impl Local {
+    fn new(kind: ExportKind, loc: SourceLocation) -> Self {
+        Local {
+            kind,
+            loc,
+            is_type_only: false,
+        }
+    }
+}
+
+/// Add the bindings a top level declaration creates to `locals`
+fn declare<T>(decl: &Decl<T>, locals: &mut HashMap<String, Local>)
+where
+    T: AsRef<str>,
+{
+    let declared = match decl {
+        Decl::Import { import, .. } => {
+            let source = specifier(&import.source);
+            let mut declared = Vec::new();
+            for entry in &import.specifiers {
+                match &entry.item {
+                    ImportSpecifier::Normal(specifiers) => {
+                        for entry in &specifiers.specs {
+                            let spec = &entry.item;
+                            let imported = match spec.imported.value() {
+                                Ok(imported) => imported,
+                                Err(_) => continue,
+                            };
+                            let local = match &spec.alias {
+                                Some(alias) => name(&alias.ident),
+                                None => imported.clone(),
+                            };
+                            let kind = ExportKind::ReExport {
+                                source: source.clone(),
+                                imported: Some(imported),
+                            };
+                            declared.push((
+                                local,
+                                Local {
+                                    kind,
+                                    loc: spec.loc(),
+                                    is_type_only: import.keyword_type.is_some()
+                                        || spec.keyword_type.is_some(),
+                                },
+                            ));
+                        }
+                    }
+                    ImportSpecifier::Default(spec) => declared.push((
+                        name(&spec.id),
+                        Local {
+                            kind: ExportKind::ReExport {
+                                source: source.clone(),
+                                imported: Some("default".to_string()),
+                            },
+                            loc: spec.loc(),
+                            is_type_only: import.keyword_type.is_some(),
+                        },
+                    )),
+                    ImportSpecifier::Namespace(spec) => declared.push((
+                        name(&spec.ident),
+                        Local {
+                            kind: ExportKind::ReExport {
+                                source: source.clone(),
+                                imported: None,
+                            },
+                            loc: spec.loc(),
+                            is_type_only: import.keyword_type.is_some(),
+                        },
+                    )),
+                }
+            }
+            declared
+        }
+        Decl::Export { export, .. } => match &export.spec {
+            ModExportSpecifier::Named(NamedExportDecl::Decl(decl))
+            | ModExportSpecifier::Default {
+                value: DefaultExportDeclValue::Decl(decl),
+                ..
+            } => declared(decl),
+            _ => Vec::new(),
+        },
+        decl => declared(decl),
+    };
+    for (name, local) in declared {
+        locals.entry(name).or_insert(local);
+    }
+}
+
+/// The bindings a declaration creates, in source order
+fn declared<T>(decl: &Decl<T>) -> Vec<(String, Local)>
+where
+    T: AsRef<str>,
+{
+    match decl {
+        Decl::Var { decls, .. } => {
+            let kind = ExportKind::Var(decls.keyword.clone().into());
+            let mut declared = Vec::new();
+            for entry in &decls.decls {
+                let mut idents = Vec::new();
+                bound_names(&entry.item.id, &mut idents);
+                for ident in idents {
+                    declared.push((name(ident), Local::new(kind.clone(), entry.item.loc())));
+                }
+            }
+            declared
+        }
+        Decl::Func(func) => match &func.id {
+            Some(id) => vec![(name(id), Local::new(ExportKind::Function, func.loc()))],
+            None => Vec::new(),
+        },
+        Decl::Class(class) => match &class.id {
+            Some(id) => vec![(name(id), Local::new(ExportKind::Class, class.loc()))],
+            None => Vec::new(),
+        },
+        Decl::ImportEquals { import, .. } => {
+            let kind = match &import.module_ref {
+                ModuleRef::Require(require) => ExportKind::ReExport {
+                    source: specifier(&require.module),
+                    imported: None,
+                },
+                ModuleRef::Entity(_) => ExportKind::Alias,
+            };
+            vec![(
+                name(&import.id),
+                Local {
+                    kind,
+                    loc: import.loc(),
+                    is_type_only: import.keyword_type.is_some(),
+                },
+            )]
+        }
+        Decl::Declare { decl, .. } => declared(decl),
+        Decl::Module { module, .. } => match &module.name {
+            ModuleName::Namespace(namespace) => vec![(
+                name(&namespace.first),
+                Local::new(ExportKind::Namespace, module.loc()),
+            )],
+            ModuleName::Lit(_) | ModuleName::Global(_) => Vec::new(),
+        },
+        Decl::Interface(interface) => vec![(
+            name(&interface.id),
+            Local {
+                kind: ExportKind::Interface,
+                loc: interface.loc(),
+                is_type_only: true,
+            },
+        )],
+        Decl::TypeAlias { decl, .. } => vec![(
+            name(&decl.id),
+            Local {
+                kind: ExportKind::TypeAlias,
+                loc: decl.loc(),
+                is_type_only: true,
+            },
+        )],
+        Decl::Enum(decl) => vec![(name(&decl.id), Local::new(ExportKind::Enum, decl.loc()))],
+        Decl::Import { .. } | Decl::Export { .. } | Decl::Invalid(_) => Vec::new(),
+    }
+}
+
+/// The kind of `export default expr`, which is the kind of the binding
+/// when `expr` is a top level identifier
+fn default_expr<T>(expr: &Expr<T>, locals: &HashMap<String, Local>) -> (ExportKind, SourceLocation)
+where
+    T: AsRef<str>,
+{
+    match expr {
+        Expr::Ident(ident) => match locals.get(ident.name().as_ref()) {
+            Some(local) => (local.kind.clone(), local.loc),
+            None => (ExportKind::Undeclared, expr.loc()),
+        },
+        _ => (ExportKind::Expr, expr.loc()),
+    }
+}
+
+/// The local and exported name of `export {local as exported}`
+fn names<T>(specifier: &ExportSpecifier<T>) -> Option<(String, String)>
+where
+    T: AsRef<str>,
+{
+    let local = specifier.local.value().ok()?;
+    let exported = match &specifier.alias {
+        Some(alias) => alias.name.value().ok()?,
+        None => local.clone(),
+    };
+    Some((local, exported))
+}
+
+fn name<T>(ident: &Ident<T>) -> String
+where
+    T: AsRef<str>,
+{
+    ident.name().as_ref().to_string()
+}
+
+/// The cooked value of a module specifier, or its source text when
+/// it can not be cooked
+fn specifier<T>(source: &Lit<T>) -> String
+where
+    T: AsRef<str>,
+{
+    match source {
+        // module code is always strict
+        Lit::String(string) => match string.cooked(true) {
+            Ok(units) => String::from_utf16_lossy(&units),
+            Err(_) => string.content.source.as_ref().to_string(),
+        },
+        _ => String::new(),
+    }
+}
+
+/// The identifiers a pattern binds, like `Pat::bound_names` of the
+/// unspanned tree
+fn bound_names<'a, T>(pat: &'a Pat<T>, names: &mut Vec<&'a Ident<T>>) {
+    match pat {
+        Pat::Ident(ident) => names.push(ident),
+        Pat::Obj(obj) => {
+            for entry in &obj.props {
+                match &entry.item {
+                    ObjPatPart::Assign(Prop::Init(prop)) => match &prop.value {
+                        Some(PropValue::Pat(pat)) => bound_names(pat, names),
+                        Some(PropValue::Expr(Expr::Ident(ident))) => names.push(ident),
+                        Some(PropValue::Expr(Expr::Assign(assign))) => {
+                            if let AssignLeft::Pat(pat) = &assign.left {
+                                bound_names(pat, names)
+                            }
+                        }
+                        Some(_) => {}
+                        None => match &prop.key.value {
+                            PropKey::Pat(pat) => bound_names(pat, names),
+                            PropKey::Expr(Expr::Ident(ident)) => names.push(ident),
+                            _ => {}
+                        },
+                    },
+                    ObjPatPart::Assign(_) => {}
+                    ObjPatPart::Rest(rest) => bound_names(&rest.pat, names),
+                }
+            }
+        }
+        Pat::Array(array) => {
+            for part in array
+                .elements
+                .iter()
+                .filter_map(|entry| entry.item.as_ref())
+            {
+                match part {
+                    ArrayPatPart::Pat(pat) => bound_names(pat, names),
+                    ArrayPatPart::Expr(Expr::Ident(ident)) => names.push(ident),
+                    ArrayPatPart::Expr(_) => {}
+                    ArrayPatPart::Rest(rest) => bound_names(&rest.pat, names),
+                }
+            }
+        }
+        Pat::Assign(assign) => bound_names(&assign.left, names),
+        Pat::Invalid(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spanned::decl::{
+        ExportAlias, ModExport, ModImport, NamedExportSource, NamedExportSpec, NormalImportSpec,
+        NormalImportSpecs, VarDecl, VarDecls,
+    };
+    use crate::spanned::decl::{ExportList, ModuleExportName};
+    use crate::spanned::expr::StringLit;
+    use crate::spanned::tokens::Quote;
+    use crate::spanned::{Class, ClassBody, Func, FuncBody, ListEntry, Position, Slice};
+
+    /// Every node of a test program is on its own line
+    fn at(line: u32) -> Position {
+        Position::new(line, 0)
+    }
+
+    fn ident(name: &'static str, line: u32) -> Ident<&'static str> {
+        Ident::from(Slice {
+            source: name,
+            loc: SourceLocation::new(line, 0, line, name.len() as u32),
+        })
+    }
+
+    fn string(value: &'static str, line: u32) -> Lit<&'static str> {
+        Lit::String(StringLit {
+            open_quote: Quote::Single(at(line).into()),
+            content: Slice {
+                source: value,
+                loc: SourceLocation::new(line, 1, line, value.len() as u32 + 1),
+            },
+            close_quote: Quote::Single(at(line).into()),
+        })
+    }
+
+    fn entry<T>(item: T) -> ListEntry<T> {
+        ListEntry { item, comma: None }
+    }
+
+    fn func(name: &'static str, line: u32) -> Decl<&'static str> {
+        Decl::Func(Func {
+            keyword: at(line).into(),
+            id: Some(ident(name, line)),
+            type_params: None,
+            open_paren: at(line).into(),
+            params: Vec::new(),
+            close_paren: at(line).into(),
+            return_type: None,
+            body: Some(FuncBody {
+                open_brace: at(line).into(),
+                stmts: Vec::new(),
+                close_brace: at(line).into(),
+            }),
+            star: None,
+            keyword_async: None,
+            semi_colon: None,
+        })
+    }
+
+    fn class(name: &'static str, line: u32) -> Decl<&'static str> {
+        Decl::Class(Class {
+            decorators: Vec::new(),
+            keyword_abstract: None,
+            keyword: at(line).into(),
+            id: Some(ident(name, line)),
+            type_params: None,
+            super_class: None,
+            implements: None,
+            body: ClassBody {
+                open_brace: at(line).into(),
+                members: Vec::new(),
+                close_brace: at(line).into(),
+            },
+        })
+    }
+
+    fn var(keyword: crate::spanned::VarKind, name: &'static str, line: u32) -> Decl<&'static str> {
+        Decl::Var {
+            decls: VarDecls {
+                keyword,
+                decls: vec![entry(VarDecl {
+                    id: Pat::Ident(ident(name, line)),
+                    type_annotation: None,
+                    eq: None,
+                    init: None,
+                })],
+            },
+            semi_colon: None,
+        }
+    }
+
+    fn import(name: &'static str, source: &'static str, line: u32) -> ProgramPart<&'static str> {
+        let spec = NormalImportSpec {
+            keyword_type: None,
+            imported: ModuleExportName::Ident(ident(name, line)),
+            alias: None,
+        };
+        ProgramPart::Decl(Decl::Import {
+            import: Box::new(ModImport {
+                keyword_import: at(line).into(),
+                keyword_type: None,
+                specifiers: vec![entry(ImportSpecifier::Normal(NormalImportSpecs {
+                    open_brace: at(line).into(),
+                    specs: vec![entry(spec)],
+                    close_brace: at(line).into(),
+                }))],
+                keyword_from: Some(at(line).into()),
+                source: string(source, line),
+                attributes: None,
+            }),
+            semi_colon: None,
+        })
+    }
+
+    fn export(spec: ModExportSpecifier<&'static str>, line: u32) -> ProgramPart<&'static str> {
+        ProgramPart::Decl(Decl::Export {
+            export: Box::new(ModExport {
+                keyword: at(line).into(),
+                spec,
+            }),
+            semi_colon: None,
+        })
+    }
+
+    fn export_decl(decl: Decl<&'static str>, line: u32) -> ProgramPart<&'static str> {
+        export(ModExportSpecifier::Named(NamedExportDecl::Decl(decl)), line)
+    }
+
+    /// `export {local as alias} from 'source'`, `type` when `is_type_only`
+    fn export_list(
+        names: &[(&'static str, Option<&'static str>)],
+        source: Option<&'static str>,
+        is_type_only: bool,
+        line: u32,
+    ) -> ProgramPart<&'static str> {
+        let elements = names
+            .iter()
+            .map(|(local, alias)| {
+                entry(ExportSpecifier {
+                    keyword_type: None,
+                    local: ModuleExportName::Ident(ident(local, line)),
+                    alias: alias.map(|alias| ExportAlias {
+                        keyword: at(line).into(),
+                        name: ModuleExportName::Ident(ident(alias, line)),
+                    }),
+                })
+            })
+            .collect();
+        let spec = NamedExportSpec {
+            keyword_type: is_type_only.then(|| at(line).into()),
+            list: ExportList {
+                open_brace: at(line).into(),
+                elements,
+                close_brace: at(line).into(),
+            },
+            source: source.map(|source| NamedExportSource {
+                keyword_from: at(line).into(),
+                module: string(source, line),
+                attributes: None,
+            }),
+        };
+        export(
+            ModExportSpecifier::Named(NamedExportDecl::Specifier(spec)),
+            line,
+        )
+    }
+
+    fn export_all(source: &'static str, line: u32) -> ProgramPart<&'static str> {
+        export(
+            ModExportSpecifier::All {
+                star: at(line).into(),
+                alias: None,
+                keyword: at(line).into(),
+                name: string(source, line),
+                attributes: None,
+            },
+            line,
+        )
+    }
+
+    fn re_export(source: &str, imported: &str) -> ExportKind {
+        ExportKind::ReExport {
+            source: source.to_string(),
+            imported: Some(imported.to_string()),
+        }
+    }
+
+    fn kinds(surface: &ExportSurface) -> Vec<(&str, &ExportKind, bool)> {
+        surface
+            .exports
+            .iter()
+            .map(|export| (export.name.as_str(), &export.kind, export.is_type_only))
+            .collect()
+    }
+
+    /// The example of the module documentation
+    fn example() -> Program<&'static str> {
+        Program::Mod(
+            vec![
+                import("parse", "./parser.js", 1),
+                export_decl(func("render", 2), 2),
+                export_decl(
+                    var(crate::spanned::VarKind::Const(at(3).into()), "VERSION", 3),
+                    3,
+                ),
+                export_list(
+                    &[("parse", None), ("parse", Some("default"))],
+                    None,
+                    false,
+                    4,
+                ),
+                export_all("./util.js", 5),
+            ],
+            None,
+        )
+    }
+
+    #[test]
+    fn surface_of_a_module() {
+        let surface = surface(&example());
+        assert_eq!(
+            kinds(&surface),
+            vec![
+                ("render", &ExportKind::Function, false),
+                ("VERSION", &ExportKind::Var(VarKind::Const), false),
+                ("parse", &re_export("./parser.js", "parse"), false),
+                ("default", &re_export("./parser.js", "parse"), false),
+            ]
+        );
+        // a re-export of an import is located at the import
+        assert_eq!(surface.get("parse").unwrap().loc.start.line, 1);
+        assert_eq!(surface.get("render").unwrap().loc.start.line, 2);
+        assert_eq!(surface.stars.len(), 1);
+        assert_eq!(surface.stars[0].source, "./util.js");
+    }
+
+    #[test]
+    fn scripts_export_nothing() {
+        let program = match example() {
+            Program::Mod(parts, hashbang) => Program::Script(parts, hashbang),
+            script => script,
+        };
+        assert_eq!(surface(&program), ExportSurface::default());
+    }
+
+    #[test]
+    fn local_export_lists() {
+        let program = Program::Mod(
+            vec![
+                ProgramPart::Decl(class("Widget", 1)),
+                export_list(&[("Widget", None), ("missing", None)], None, false, 2),
+                export_list(&[("Widget", Some("Type"))], None, true, 3),
+                // the first export of a name wins
+                export_decl(func("Widget", 4), 4),
+            ],
+            None,
+        );
+        let surface = surface(&program);
+        assert_eq!(
+            kinds(&surface),
+            vec![
+                ("Widget", &ExportKind::Class, false),
+                ("missing", &ExportKind::Undeclared, false),
+                ("Type", &ExportKind::Class, true),
+            ]
+        );
+        assert_eq!(surface.get("Widget").unwrap().loc.start.line, 1);
+    }
+
+    #[test]
+    fn diff_of_two_versions() {
+        let old = surface(&Program::Mod(
+            vec![
+                export_decl(func("render", 1), 1),
+                export_decl(
+                    var(crate::spanned::VarKind::Let(at(2).into()), "count", 2),
+                    2,
+                ),
+                export_decl(class("Widget", 3), 3),
+                export_list(&[("Props", None)], Some("./types.js"), true, 4),
+                export_all("./util.js", 5),
+            ],
+            None,
+        ));
+        let new = surface(&Program::Mod(
+            vec![
+                export_decl(
+                    var(crate::spanned::VarKind::Const(at(1).into()), "count", 1),
+                    1,
+                ),
+                export_decl(func("Widget", 2), 2),
+                export_list(&[("Props", None)], Some("./types.js"), false, 3),
+                export_decl(func("hydrate", 4), 4),
+                export_all("./helpers.js", 5),
+            ],
+            None,
+        ));
+        let changes = old.diff(&new);
+        let summary: Vec<(String, bool)> = changes
+            .iter()
+            .map(|change| {
+                let name = match change {
+                    ExportChange::Removed(export) => format!("-{}", export.name),
+                    ExportChange::Added(export) => format!("+{}", export.name),
+                    ExportChange::Changed { old, .. } => format!("~{}", old.name),
+                    ExportChange::StarRemoved(star) => format!("-* {}", star.source),
+                    ExportChange::StarAdded(star) => format!("+* {}", star.source),
+                };
+                (name, change.is_breaking())
+            })
+            .collect();
+        let expected = [
+            ("-render", true),
+            // `let` to `const` is read the same by importers
+            ("~count", false),
+            ("~Widget", true),
+            // a type only export becoming a value
+            ("~Props", false),
+            ("+hydrate", false),
+            ("-* ./util.js", true),
+            ("+* ./helpers.js", false),
+        ];
+        assert_eq!(
+            summary,
+            expected
+                .iter()
+                .map(|(name, breaking)| (name.to_string(), *breaking))
+                .collect::<Vec<_>>()
+        );
+        // the reverse makes `Props` type only, which is breaking
+        assert!(new
+            .diff(&old)
+            .iter()
+            .any(|change| matches!(change, ExportChange::Changed { new, .. }
+                if new.name == "Props" && change.is_breaking())));
+        assert!(old.diff(&old).is_empty());
+    }
+
+    #[test]
+    fn resolved_sources_are_compared() {
+        let old = surface(&Program::Mod(
+            vec![
+                export_list(&[("a", None)], Some("./a"), false, 1),
+                export_all("./util", 2),
+            ],
+            None,
+        ));
+        let mut new = surface(&Program::Mod(
+            vec![
+                export_list(&[("a", None)], Some("./a.js"), false, 1),
+                export_all("./util.js", 2),
+            ],
+            None,
+        ));
+        assert_eq!(old.diff(&new).len(), 3);
+        let mut old = old;
+        for surface in [&mut old, &mut new] {
+            surface.resolve_sources(|source| {
+                Some(format!(
+                    "/src/{}.js",
+                    source.trim_start_matches("./").trim_end_matches(".js")
+                ))
+            });
+        }
+        assert_eq!(new.get("a").unwrap().kind, re_export("/src/a.js", "a"));
+        assert!(old.diff(&new).is_empty());
+    }
+}
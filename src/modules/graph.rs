@@ -0,0 +1,1075 @@
+//! The imports and re-exports between a set of modules
+//!
+//! ```js
+//! // main.js
+//! import {render} from './view.js';
+//! // view.js
+//! export {render} from './dom.js';
+//! // dom.js
+//! export function render() {}
+//! ```
+//! has an edge from `main.js` to `view.js` importing `render` and one
+//! from `view.js` to `dom.js` re-exporting it. Resolving the import in
+//! `main.js` follows the re-export to the function declared in `dom.js`.
+//!
+//! Specifiers are matched to modules by the resolve function given to
+//! `ModuleGraph::build`. A dynamic import is only an edge when its
+//! source is a string literal, and neither dynamic imports nor type only
+//! imports are part of the evaluation order or of cycles.
+use std::collections::HashMap;
+
+use crate::analysis::visit::{walk_expr, Visit};
+use crate::decl::{
+    Decl, DefaultExportDecl, ExportSpecifier, ImportSpecifier, ModExport, ModuleExportName,
+    ModuleName, ModuleRef, NamedExportDecl,
+};
+use crate::expr::{Expr, Lit};
+use crate::{Program, ProgramPart};
+
+/// The binding name of a default export that does not declare a name,
+/// like `export default 1` or `export default function() {}`
+pub const DEFAULT_BINDING: &str = "*default*";
+
+/// Every import, re-export and dynamic import in `program`, in source
+/// order
+pub fn dependencies<T>(program: &Program<T>) -> Vec<Dependency<'_, T>>
+where
+    T: AsRef<str>,
+{
+    collect(program).0
+}
+
+/// Something a module loads from another module
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dependency<'a, T> {
+    /// The cooked value of `source`
+    pub specifier: String,
+    pub source: &'a Lit<T>,
+    pub kind: DependencyKind,
+    /// The names taken from the other module, a side effect import,
+    /// a dynamic import or an `export *` has none
+    pub names: Vec<ImportedName>,
+    /// `import type` or `export type`, which are erased
+    pub is_type_only: bool,
+}
+
+impl<T> Dependency<'_, T> {
+    /// If the other module is evaluated before this one
+    pub fn is_evaluated(&self) -> bool {
+        !self.is_type_only && self.kind != DependencyKind::Dynamic
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DependencyKind {
+    /// `import {a} from 'a'` or `import a = require('a')`
+    Import,
+    /// `export {a} from 'a'`
+    ReExport,
+    /// `export * from 'a'` or `export * as a from 'a'`
+    ExportAll,
+    /// `import('a')`
+    Dynamic,
+}
+
+/// A name one module takes from another, names imported with `type`
+/// are left out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportedName {
+    /// `import {a as b}`, `import b` which imports `default`, or
+    /// `export {a as b} from`, where the local name is the name it
+    /// is exported as
+    Name { imported: String, local: String },
+    /// `import * as ns`, `import ns = require()` or `export * as ns from`
+    Namespace { local: String },
+}
+
+/// What a module exports, in the normalized form of the spec's
+/// export entries
+#[derive(Debug, Clone, PartialEq)]
+enum ExportEntry {
+    /// A binding declared in the module
+    Local { exported: String, local: String },
+    /// A name of another module, or its namespace for `None`
+    Indirect {
+        exported: String,
+        dependency: usize,
+        imported: Option<String>,
+    },
+    /// `export * from`
+    Star { dependency: usize },
+}
+
+/// One named module
+#[derive(Debug, Clone, PartialEq)]
+pub struct Module<'a, T> {
+    pub name: String,
+    pub program: &'a Program<T>,
+    pub dependencies: Vec<Dependency<'a, T>>,
+    exports: Vec<ExportEntry>,
+}
+
+/// The index of a module in `ModuleGraph::modules`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ModuleId(pub(crate) usize);
+
+impl ModuleId {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// A dependency and the module it resolved to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    pub from: ModuleId,
+    /// The index in the `dependencies` of `from`
+    pub dependency: usize,
+    /// `None` when the specifier is not one of the modules
+    pub to: Option<ModuleId>,
+}
+
+/// Where an imported or exported name comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution {
+    /// A binding declared at the top level of `module`, see
+    /// `DEFAULT_BINDING` for default exports without a name
+    Binding {
+        module: ModuleId,
+        name: String,
+    },
+    /// The namespace object of a module
+    Namespace(ModuleId),
+    /// A name from a specifier that is not one of the modules, or its
+    /// namespace for `None`
+    External {
+        specifier: String,
+        name: Option<String>,
+    },
+    NotFound,
+    /// More than one `export *` provides the name
+    Ambiguous,
+    /// The re-exports lead back to themselves
+    Circular,
+}
+
+/// An imported or re-exported name and where it comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedImport {
+    /// The index in the module's `dependencies`
+    pub dependency: usize,
+    pub name: ImportedName,
+    pub resolution: Resolution,
+}
+
+/// A set of modules and the edges between them
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleGraph<'a, T> {
+    modules: Vec<Module<'a, T>>,
+    edges: Vec<Edge>,
+    /// The index of the first edge of each module
+    offsets: Vec<usize>,
+    by_name: HashMap<String, ModuleId>,
+}
+
+impl<'a, T> ModuleGraph<'a, T>
+where
+    T: AsRef<str>,
+{
+    /// Build a graph where a specifier is the name of the module it
+    /// refers to
+    pub fn new<N>(modules: impl IntoIterator<Item = (N, &'a Program<T>)>) -> Self
+    where
+        N: Into<String>,
+    {
+        Self::build(modules, |_, specifier| Some(specifier.to_string()))
+    }
+
+    /// Build a graph with `resolve` mapping the name of the importing
+    /// module and a specifier to the name of the imported module, the
+    /// module names should be unique
+    pub fn build<N, R>(
+        modules: impl IntoIterator<Item = (N, &'a Program<T>)>,
+        mut resolve: R,
+    ) -> Self
+    where
+        N: Into<String>,
+        R: FnMut(&str, &str) -> Option<String>,
+    {
+        let modules: Vec<Module<'a, T>> = modules
+            .into_iter()
+            .map(|(name, program)| {
+                let (dependencies, exports) = collect(program);
+                Module {
+                    name: name.into(),
+                    program,
+                    dependencies,
+                    exports,
+                }
+            })
+            .collect();
+        let by_name: HashMap<String, ModuleId> = modules
+            .iter()
+            .enumerate()
+            .map(|(index, module)| (module.name.clone(), ModuleId(index)))
+            .collect();
+        let mut edges = Vec::new();
+        let mut offsets = Vec::with_capacity(modules.len());
+        for (index, module) in modules.iter().enumerate() {
+            offsets.push(edges.len());
+            for (dependency, found) in module.dependencies.iter().enumerate() {
+                let to = resolve(&module.name, &found.specifier)
+                    .and_then(|name| by_name.get(&name).copied());
+                edges.push(Edge {
+                    from: ModuleId(index),
+                    dependency,
+                    to,
+                });
+            }
+        }
+        ModuleGraph {
+            modules,
+            edges,
+            offsets,
+            by_name,
+        }
+    }
+}
+
+impl<'a, T> ModuleGraph<'a, T> {
+    /// Every module in the order they were given
+    pub fn modules(&self) -> &[Module<'a, T>] {
+        &self.modules
+    }
+    pub fn module(&self, id: ModuleId) -> &Module<'a, T> {
+        &self.modules[id.0]
+    }
+    /// Look up a module by name
+    pub fn id(&self, name: &str) -> Option<ModuleId> {
+        self.by_name.get(name).copied()
+    }
+    /// One edge for every dependency of every module
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+    pub fn edges_from(&self, module: ModuleId) -> Vec<&Edge> {
+        self.edges
+            .iter()
+            .filter(|edge| edge.from == module)
+            .collect()
+    }
+    pub fn edges_to(&self, module: ModuleId) -> Vec<&Edge> {
+        self.edges
+            .iter()
+            .filter(|edge| edge.to == Some(module))
+            .collect()
+    }
+    /// The edges whose specifier is not one of the modules
+    pub fn unresolved(&self) -> Vec<&Edge> {
+        self.edges.iter().filter(|edge| edge.to.is_none()).collect()
+    }
+    /// The module a dependency resolved to
+    pub fn target(&self, module: ModuleId, dependency: usize) -> Option<ModuleId> {
+        self.edges[self.offsets[module.0] + dependency].to
+    }
+
+    /// The order modules are evaluated in, each module after its
+    /// dependencies except where a cycle makes that impossible, the
+    /// same way an engine does when starting at each module in turn
+    pub fn order(&self) -> Vec<ModuleId> {
+        let successors = self.successors();
+        let mut visited = vec![false; self.modules.len()];
+        let mut order = Vec::with_capacity(self.modules.len());
+        for start in 0..self.modules.len() {
+            if visited[start] {
+                continue;
+            }
+            visited[start] = true;
+            let mut work = vec![(start, 0)];
+            while let Some((node, next)) = work.last_mut() {
+                let node = *node;
+                match successors[node].get(*next) {
+                    Some(target) => {
+                        *next += 1;
+                        if !visited[target.0] {
+                            visited[target.0] = true;
+                            work.push((target.0, 0));
+                        }
+                    }
+                    None => {
+                        work.pop();
+                        order.push(ModuleId(node));
+                    }
+                }
+            }
+        }
+        order
+    }
+
+    /// The groups of modules that import each other, directly or
+    /// through other modules, a module that imports itself is a group
+    /// of one
+    pub fn cycles(&self) -> Vec<Vec<ModuleId>> {
+        // Tarjan's strongly connected components
+        const UNVISITED: usize = usize::MAX;
+        let successors = self.successors();
+        let count = self.modules.len();
+        let mut index = vec![UNVISITED; count];
+        let mut low = vec![0; count];
+        let mut on_stack = vec![false; count];
+        let mut stack = Vec::new();
+        let mut next_index = 0;
+        let mut cycles = Vec::new();
+        for start in 0..count {
+            if index[start] != UNVISITED {
+                continue;
+            }
+            index[start] = next_index;
+            low[start] = next_index;
+            next_index += 1;
+            stack.push(start);
+            on_stack[start] = true;
+            let mut work = vec![(start, 0)];
+            while let Some((node, next)) = work.last_mut() {
+                let node = *node;
+                if let Some(target) = successors[node].get(*next) {
+                    *next += 1;
+                    let target = target.0;
+                    if index[target] == UNVISITED {
+                        index[target] = next_index;
+                        low[target] = next_index;
+                        next_index += 1;
+                        stack.push(target);
+                        on_stack[target] = true;
+                        work.push((target, 0));
+                    } else if on_stack[target] {
+                        low[node] = low[node].min(index[target]);
+                    }
+                    continue;
+                }
+                work.pop();
+                if let Some((parent, _)) = work.last() {
+                    low[*parent] = low[*parent].min(low[node]);
+                }
+                if low[node] != index[node] {
+                    continue;
+                }
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(ModuleId(member));
+                    if member == node {
+                        break;
+                    }
+                }
+                if component.len() > 1 || successors[node].contains(&ModuleId(node)) {
+                    component.reverse();
+                    cycles.push(component);
+                }
+            }
+        }
+        cycles
+    }
+
+    /// The modules each module evaluates first
+    fn successors(&self) -> Vec<Vec<ModuleId>> {
+        let mut successors = vec![Vec::new(); self.modules.len()];
+        for edge in &self.edges {
+            let dependency = &self.modules[edge.from.0].dependencies[edge.dependency];
+            if let (Some(to), true) = (edge.to, dependency.is_evaluated()) {
+                successors[edge.from.0].push(to);
+            }
+        }
+        successors
+    }
+
+    /// Find where the export `name` of `module` comes from, following
+    /// re-exports and `export *` the way the spec's `ResolveExport` does
+    pub fn resolve_export(&self, module: ModuleId, name: &str) -> Resolution {
+        self.resolve_in(module, name, &mut Vec::new())
+    }
+
+    /// Every name `module` exports, including the names from its
+    /// `export *` that resolve to a single binding
+    pub fn export_names(&self, module: ModuleId) -> Vec<String> {
+        let mut names = Vec::new();
+        self.collect_export_names(module, &mut Vec::new(), &mut names);
+        names.retain(|name| {
+            !matches!(
+                self.resolve_export(module, name),
+                Resolution::NotFound | Resolution::Ambiguous | Resolution::Circular
+            )
+        });
+        names
+    }
+
+    fn collect_export_names(
+        &self,
+        module: ModuleId,
+        visited: &mut Vec<ModuleId>,
+        names: &mut Vec<String>,
+    ) {
+        if visited.contains(&module) {
+            return;
+        }
+        visited.push(module);
+        for entry in &self.modules[module.0].exports {
+            match entry {
+                ExportEntry::Local { exported, .. } | ExportEntry::Indirect { exported, .. } => {
+                    if !names.contains(exported) {
+                        names.push(exported.clone());
+                    }
+                }
+                ExportEntry::Star { dependency } => {
+                    if let Some(target) = self.target(module, *dependency) {
+                        let mut forwarded = Vec::new();
+                        self.collect_export_names(target, visited, &mut forwarded);
+                        for name in forwarded {
+                            if name != "default" && !names.contains(&name) {
+                                names.push(name);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Resolve every name `module` imports or re-exports
+    pub fn resolve_imports(&self, module: ModuleId) -> Vec<ResolvedImport> {
+        let mut resolved = Vec::new();
+        for (index, dependency) in self.modules[module.0].dependencies.iter().enumerate() {
+            for name in &dependency.names {
+                let imported = match name {
+                    ImportedName::Name { imported, .. } => Some(imported.as_str()),
+                    ImportedName::Namespace { .. } => None,
+                };
+                resolved.push(ResolvedImport {
+                    dependency: index,
+                    name: name.clone(),
+                    resolution: self.resolve_dependency(module, index, imported, &mut Vec::new()),
+                });
+            }
+        }
+        resolved
+    }
+
+    fn resolve_in(
+        &self,
+        module: ModuleId,
+        name: &str,
+        seen: &mut Vec<(ModuleId, String)>,
+    ) -> Resolution {
+        if seen
+            .iter()
+            .any(|(other, other_name)| *other == module && other_name == name)
+        {
+            return Resolution::Circular;
+        }
+        seen.push((module, name.to_string()));
+        let exports = &self.modules[module.0].exports;
+        for entry in exports {
+            match entry {
+                ExportEntry::Local { exported, local } if exported == name => {
+                    return Resolution::Binding {
+                        module,
+                        name: local.clone(),
+                    };
+                }
+                ExportEntry::Indirect {
+                    exported,
+                    dependency,
+                    imported,
+                } if exported == name => {
+                    return self.resolve_dependency(module, *dependency, imported.as_deref(), seen);
+                }
+                _ => {}
+            }
+        }
+        // `export *` never forwards a default export
+        if name == "default" {
+            return Resolution::NotFound;
+        }
+        let mut star = None;
+        for entry in exports {
+            let dependency = match entry {
+                ExportEntry::Star { dependency } => *dependency,
+                _ => continue,
+            };
+            match self.resolve_dependency(module, dependency, Some(name), seen) {
+                Resolution::Ambiguous => return Resolution::Ambiguous,
+                Resolution::NotFound | Resolution::Circular => {}
+                resolution => match &star {
+                    None => star = Some(resolution),
+                    Some(found) if *found != resolution => return Resolution::Ambiguous,
+                    Some(_) => {}
+                },
+            }
+        }
+        star.unwrap_or(Resolution::NotFound)
+    }
+
+    fn resolve_dependency(
+        &self,
+        module: ModuleId,
+        dependency: usize,
+        imported: Option<&str>,
+        seen: &mut Vec<(ModuleId, String)>,
+    ) -> Resolution {
+        match (self.target(module, dependency), imported) {
+            (Some(target), Some(name)) => self.resolve_in(target, name, seen),
+            (Some(target), None) => Resolution::Namespace(target),
+            (None, name) => Resolution::External {
+                specifier: self.modules[module.0].dependencies[dependency]
+                    .specifier
+                    .clone(),
+                name: name.map(str::to_string),
+            },
+        }
+    }
+}
+
+fn collect<T>(program: &Program<T>) -> (Vec<Dependency<'_, T>>, Vec<ExportEntry>)
+where
+    T: AsRef<str>,
+{
+    let mut collect = Collect {
+        dependencies: Vec::new(),
+        exports: Vec::new(),
+    };
+    for part in program.parts() {
+        if let ProgramPart::Decl(decl) = part {
+            collect.decl(decl);
+        }
+        collect.visit_part(part);
+    }
+    // exporting an imported name forwards the import
+    let mut imports = HashMap::new();
+    for (index, dependency) in collect.dependencies.iter().enumerate() {
+        if dependency.kind != DependencyKind::Import {
+            continue;
+        }
+        for name in &dependency.names {
+            match name {
+                ImportedName::Name { imported, local } => {
+                    imports.insert(local.clone(), (index, Some(imported.clone())))
+                }
+                ImportedName::Namespace { local } => imports.insert(local.clone(), (index, None)),
+            };
+        }
+    }
+    let exports = collect
+        .exports
+        .into_iter()
+        .map(|entry| match entry {
+            ExportEntry::Local { exported, local } => match imports.get(&local) {
+                Some((dependency, imported)) => ExportEntry::Indirect {
+                    exported,
+                    dependency: *dependency,
+                    imported: imported.clone(),
+                },
+                None => ExportEntry::Local { exported, local },
+            },
+            entry => entry,
+        })
+        .collect();
+    (collect.dependencies, exports)
+}
+
+/// Finds the dependencies of a module and its export entries
+struct Collect<'a, T> {
+    dependencies: Vec<Dependency<'a, T>>,
+    exports: Vec<ExportEntry>,
+}
+
+impl<'a, T> Collect<'a, T>
+where
+    T: AsRef<str>,
+{
+    /// A top level declaration
+    fn decl(&mut self, decl: &'a Decl<T>) {
+        match decl {
+            Decl::Import(import) => {
+                let mut names = Vec::new();
+                for specifier in &import.specifiers {
+                    match specifier {
+                        ImportSpecifier::Normal(specifiers) => {
+                            for specifier in specifiers.iter().filter(|s| !s.is_type_only) {
+                                let imported = match specifier.imported.value() {
+                                    Ok(imported) => imported,
+                                    Err(_) => continue,
+                                };
+                                let local = match &specifier.alias {
+                                    Some(alias) => alias.name.as_ref().to_string(),
+                                    None => imported.clone(),
+                                };
+                                names.push(ImportedName::Name { imported, local });
+                            }
+                        }
+                        ImportSpecifier::Default(local) => names.push(ImportedName::Name {
+                            imported: "default".to_string(),
+                            local: local.name.as_ref().to_string(),
+                        }),
+                        ImportSpecifier::Namespace(local) => names.push(ImportedName::Namespace {
+                            local: local.name.as_ref().to_string(),
+                        }),
+                    }
+                }
+                self.push(
+                    &import.source,
+                    DependencyKind::Import,
+                    names,
+                    import.is_type_only,
+                );
+            }
+            Decl::ImportEquals(import) => {
+                if let ModuleRef::Require(source) = &import.module_ref {
+                    let names = vec![ImportedName::Namespace {
+                        local: import.id.name.as_ref().to_string(),
+                    }];
+                    self.push(source, DependencyKind::Import, names, import.is_type_only);
+                }
+            }
+            Decl::Export(export) => self.export(export),
+            _ => {}
+        }
+    }
+
+    fn export(&mut self, export: &'a ModExport<T>) {
+        match export {
+            ModExport::Default(DefaultExportDecl::Decl(decl)) => {
+                let local = declared_names(decl)
+                    .into_iter()
+                    .next()
+                    .unwrap_or_else(|| DEFAULT_BINDING.to_string());
+                self.exports.push(ExportEntry::Local {
+                    exported: "default".to_string(),
+                    local,
+                });
+            }
+            ModExport::Default(DefaultExportDecl::Expr(_)) => {
+                self.exports.push(ExportEntry::Local {
+                    exported: "default".to_string(),
+                    local: DEFAULT_BINDING.to_string(),
+                })
+            }
+            ModExport::Named(NamedExportDecl::Decl(decl)) => {
+                for name in declared_names(decl) {
+                    self.exports.push(ExportEntry::Local {
+                        exported: name.clone(),
+                        local: name,
+                    });
+                }
+            }
+            ModExport::Named(NamedExportDecl::Specifier(specifiers, None, _)) => {
+                for (local, exported) in specifier_names(specifiers) {
+                    self.exports.push(ExportEntry::Local { exported, local });
+                }
+            }
+            ModExport::Named(NamedExportDecl::Specifier(specifiers, Some(source), _)) => {
+                let pairs = specifier_names(specifiers);
+                let names = pairs
+                    .iter()
+                    .map(|(imported, exported)| ImportedName::Name {
+                        imported: imported.clone(),
+                        local: exported.clone(),
+                    })
+                    .collect();
+                let is_type_only =
+                    !specifiers.is_empty() && specifiers.iter().all(|s| s.is_type_only);
+                let dependency =
+                    match self.push(source, DependencyKind::ReExport, names, is_type_only) {
+                        Some(dependency) => dependency,
+                        None => return,
+                    };
+                for (imported, exported) in pairs {
+                    self.exports.push(ExportEntry::Indirect {
+                        exported,
+                        dependency,
+                        imported: Some(imported),
+                    });
+                }
+            }
+            ModExport::All { alias, name, .. } => {
+                let alias = alias.as_ref().map(ModuleExportName::value);
+                let names = match &alias {
+                    Some(Ok(alias)) => vec![ImportedName::Namespace {
+                        local: alias.clone(),
+                    }],
+                    _ => Vec::new(),
+                };
+                let dependency = match self.push(name, DependencyKind::ExportAll, names, false) {
+                    Some(dependency) => dependency,
+                    None => return,
+                };
+                match alias {
+                    Some(Ok(exported)) => self.exports.push(ExportEntry::Indirect {
+                        exported,
+                        dependency,
+                        imported: None,
+                    }),
+                    Some(Err(_)) => {}
+                    None => self.exports.push(ExportEntry::Star { dependency }),
+                }
+            }
+            ModExport::Assign(_) => {}
+        }
+    }
+
+    fn push(
+        &mut self,
+        source: &'a Lit<T>,
+        kind: DependencyKind,
+        names: Vec<ImportedName>,
+        is_type_only: bool,
+    ) -> Option<usize> {
+        let specifier = match source {
+            // module code is always strict
+            Lit::String(string) => String::from_utf16_lossy(&string.cooked(true).ok()?),
+            _ => return None,
+        };
+        let index = self.dependencies.len();
+        self.dependencies.push(Dependency {
+            specifier,
+            source,
+            kind,
+            names,
+            is_type_only,
+        });
+        Some(index)
+    }
+}
+
+impl<'a, T> Visit<'a, T> for Collect<'a, T>
+where
+    T: AsRef<str>,
+{
+    fn visit_expr(&mut self, expr: &'a Expr<T>) {
+        if let Expr::Import(import) = expr {
+            if let Expr::Lit(source) = &*import.source {
+                self.push(source, DependencyKind::Dynamic, Vec::new(), false);
+            }
+        }
+        walk_expr(self, expr)
+    }
+}
+
+/// The names a declaration binds at the top level
+pub(crate) fn declared_names<T>(decl: &Decl<T>) -> Vec<String>
+where
+    T: AsRef<str>,
+{
+    match decl {
+        Decl::Var(_, decls) => decls
+            .iter()
+            .flat_map(|decl| decl.id.bound_names())
+            .map(|ident| ident.name.as_ref().to_string())
+            .collect(),
+        Decl::Func(func) => func
+            .id
+            .iter()
+            .map(|id| id.name.as_ref().to_string())
+            .collect(),
+        Decl::Class(class) => class
+            .id
+            .iter()
+            .map(|id| id.name.as_ref().to_string())
+            .collect(),
+        Decl::Module(module) => match &module.name {
+            ModuleName::Namespace(path) => path
+                .first()
+                .map(|id| id.name.as_ref().to_string())
+                .into_iter()
+                .collect(),
+            _ => Vec::new(),
+        },
+        Decl::Interface(decl) => vec![decl.id.name.as_ref().to_string()],
+        Decl::TypeAlias(decl) => vec![decl.id.name.as_ref().to_string()],
+        Decl::Enum(decl) => vec![decl.id.name.as_ref().to_string()],
+        Decl::Declare(decl) => declared_names(decl),
+        Decl::Import(_) | Decl::Export(_) | Decl::ImportEquals(_) | Decl::Invalid(_) => Vec::new(),
+    }
+}
+
+/// The local and exported names of the specifiers that are not type only
+fn specifier_names<T>(specifiers: &[ExportSpecifier<T>]) -> Vec<(String, String)>
+where
+    T: AsRef<str>,
+{
+    specifiers
+        .iter()
+        .filter(|specifier| !specifier.is_type_only)
+        .filter_map(|specifier| {
+            let local = specifier.local.value().ok()?;
+            let exported = match &specifier.alias {
+                Some(alias) => alias.value().ok()?,
+                None => local.clone(),
+            };
+            Some((local, exported))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decl::{ImportAttribute, ModImport, NormalImportSpec};
+    use crate::expr::{ImportCall, StringLit};
+    use crate::transform::build::{expr_stmt, ident, var};
+    use crate::{Ident, VarKind};
+
+    fn source(specifier: &str) -> Lit<String> {
+        Lit::String(StringLit::Single(specifier.to_string()))
+    }
+
+    fn name(name: &str) -> ModuleExportName<String> {
+        ModuleExportName::Ident(ident(name))
+    }
+
+    fn import(specifiers: Vec<ImportSpecifier<String>>, specifier: &str) -> ProgramPart<String> {
+        ProgramPart::Decl(Decl::Import(Box::new(ModImport {
+            specifiers,
+            source: source(specifier),
+            is_type_only: false,
+            attributes: Vec::<ImportAttribute<String>>::new(),
+        })))
+    }
+
+    fn named(imported: &str, alias: Option<&str>) -> ImportSpecifier<String> {
+        ImportSpecifier::Normal(vec![NormalImportSpec {
+            alias: alias.map(ident),
+            imported: name(imported),
+            is_type_only: false,
+        }])
+    }
+
+    fn export(decl: Decl<String>) -> ProgramPart<String> {
+        ProgramPart::Decl(Decl::Export(Box::new(ModExport::Named(
+            NamedExportDecl::Decl(decl),
+        ))))
+    }
+
+    fn export_const(names: &[&str]) -> ProgramPart<String> {
+        match var(
+            VarKind::Const,
+            names.iter().map(|name| (*name, None)).collect(),
+        ) {
+            ProgramPart::Decl(decl) => export(decl),
+            part => part,
+        }
+    }
+
+    fn re_export(local: &str, alias: Option<&str>, specifier: &str) -> ProgramPart<String> {
+        let specifiers = vec![ExportSpecifier {
+            local: name(local),
+            alias: alias.map(name),
+            is_type_only: false,
+        }];
+        ProgramPart::Decl(Decl::Export(Box::new(ModExport::Named(
+            NamedExportDecl::Specifier(specifiers, Some(source(specifier)), Vec::new()),
+        ))))
+    }
+
+    fn export_all(alias: Option<&str>, specifier: &str) -> ProgramPart<String> {
+        ProgramPart::Decl(Decl::Export(Box::new(ModExport::All {
+            alias: alias.map(name),
+            name: source(specifier),
+            attributes: Vec::new(),
+        })))
+    }
+
+    fn binding(module: usize, name: &str) -> Resolution {
+        Resolution::Binding {
+            module: ModuleId(module),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn dependencies_in_source_order() {
+        let program = Program::module(vec![
+            import(
+                vec![
+                    ImportSpecifier::Default(ident("main")),
+                    named("a", Some("b")),
+                ],
+                "./a.js",
+            ),
+            import(vec![ImportSpecifier::Namespace(ident("ns"))], "./ns.js"),
+            import(Vec::new(), "./effect.js"),
+            re_export("c", Some("d"), "./c.js"),
+            export_all(None, "./star.js"),
+            export_all(Some("all"), "./all.js"),
+            expr_stmt(Expr::Import(ImportCall {
+                source: Box::new(Expr::Lit(source("./lazy.js"))),
+                options: None,
+            })),
+        ]);
+        let found: Vec<_> = dependencies(&program)
+            .into_iter()
+            .map(|dependency| (dependency.specifier, dependency.kind, dependency.names))
+            .collect();
+        let name = |imported: &str, local: &str| ImportedName::Name {
+            imported: imported.to_string(),
+            local: local.to_string(),
+        };
+        let namespace = |local: &str| ImportedName::Namespace {
+            local: local.to_string(),
+        };
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "./a.js".to_string(),
+                    DependencyKind::Import,
+                    vec![name("default", "main"), name("a", "b")]
+                ),
+                (
+                    "./ns.js".to_string(),
+                    DependencyKind::Import,
+                    vec![namespace("ns")]
+                ),
+                (
+                    "./effect.js".to_string(),
+                    DependencyKind::Import,
+                    Vec::new()
+                ),
+                (
+                    "./c.js".to_string(),
+                    DependencyKind::ReExport,
+                    vec![name("c", "d")]
+                ),
+                (
+                    "./star.js".to_string(),
+                    DependencyKind::ExportAll,
+                    Vec::new()
+                ),
+                (
+                    "./all.js".to_string(),
+                    DependencyKind::ExportAll,
+                    vec![namespace("all")]
+                ),
+                ("./lazy.js".to_string(), DependencyKind::Dynamic, Vec::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn imports_follow_re_exports() {
+        let main = Program::module(vec![import(vec![named("render", None)], "view")]);
+        let view = Program::module(vec![re_export("render", None, "dom")]);
+        let dom = Program::module(vec![export_const(&["render"])]);
+        let graph = ModuleGraph::new([("main", &main), ("view", &view), ("dom", &dom)]);
+        let resolved = graph.resolve_imports(ModuleId(0));
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].resolution, binding(2, "render"));
+        assert_eq!(graph.order(), vec![ModuleId(2), ModuleId(1), ModuleId(0)]);
+        assert!(graph.cycles().is_empty());
+        assert_eq!(graph.edges_to(ModuleId(2)).len(), 1);
+    }
+
+    #[test]
+    fn ambiguous_star_exports() {
+        // `index` gets `shared` from both `a` and `b`, and `same` from
+        // both but through the one binding in `c`
+        let index = Program::module(vec![
+            export_all(None, "a"),
+            export_all(None, "b"),
+            export_const(&["own"]),
+        ]);
+        let a = Program::module(vec![
+            export_const(&["shared", "only_a"]),
+            export_all(None, "c"),
+        ]);
+        let b = Program::module(vec![export_const(&["shared"]), export_all(None, "c")]);
+        let c = Program::module(vec![export_const(&["same", "own"])]);
+        let graph = ModuleGraph::new([("index", &index), ("a", &a), ("b", &b), ("c", &c)]);
+        let index = ModuleId(0);
+        assert_eq!(graph.resolve_export(index, "shared"), Resolution::Ambiguous);
+        assert_eq!(graph.resolve_export(index, "same"), binding(3, "same"));
+        assert_eq!(graph.resolve_export(index, "only_a"), binding(1, "only_a"));
+        // a local export shadows the ones from `export *`
+        assert_eq!(graph.resolve_export(index, "own"), binding(0, "own"));
+        assert_eq!(graph.resolve_export(index, "missing"), Resolution::NotFound);
+        let mut names = graph.export_names(index);
+        names.sort();
+        assert_eq!(names, vec!["only_a", "own", "same"]);
+    }
+
+    #[test]
+    fn star_exports_skip_default() {
+        let index = Program::module(vec![export_all(None, "lib")]);
+        let lib = Program::module(vec![ProgramPart::Decl(Decl::Export(Box::new(
+            ModExport::Default(DefaultExportDecl::Expr(Expr::Lit(Lit::Null))),
+        )))]);
+        let graph = ModuleGraph::new([("index", &index), ("lib", &lib)]);
+        assert_eq!(
+            graph.resolve_export(ModuleId(1), "default"),
+            binding(1, DEFAULT_BINDING)
+        );
+        assert_eq!(
+            graph.resolve_export(ModuleId(0), "default"),
+            Resolution::NotFound
+        );
+        assert!(graph.export_names(ModuleId(0)).is_empty());
+    }
+
+    #[test]
+    fn circular_and_external_resolutions() {
+        let a = Program::module(vec![
+            re_export("x", None, "b"),
+            export_all(Some("ns"), "b"),
+            re_export("y", None, "react"),
+        ]);
+        let b = Program::module(vec![re_export("x", None, "a")]);
+        let graph = ModuleGraph::new([("a", &a), ("b", &b)]);
+        assert_eq!(graph.resolve_export(ModuleId(0), "x"), Resolution::Circular);
+        assert_eq!(
+            graph.resolve_export(ModuleId(0), "ns"),
+            Resolution::Namespace(ModuleId(1))
+        );
+        assert_eq!(
+            graph.resolve_export(ModuleId(0), "y"),
+            Resolution::External {
+                specifier: "react".to_string(),
+                name: Some("y".to_string()),
+            }
+        );
+        assert_eq!(graph.unresolved().len(), 1);
+        assert_eq!(graph.cycles(), vec![vec![ModuleId(0), ModuleId(1)]]);
+    }
+
+    #[test]
+    fn dynamic_imports_are_not_evaluated_first() {
+        let main = Program::module(vec![expr_stmt(Expr::Import(ImportCall {
+            source: Box::new(Expr::Lit(source("lazy"))),
+            options: None,
+        }))]);
+        let lazy = Program::module(vec![import(vec![named("a", None)], "main")]);
+        let graph = ModuleGraph::new([("main", &main), ("lazy", &lazy)]);
+        assert_eq!(graph.target(ModuleId(0), 0), Some(ModuleId(1)));
+        assert!(graph.cycles().is_empty());
+        assert_eq!(graph.order(), vec![ModuleId(0), ModuleId(1)]);
+    }
+
+    #[test]
+    fn exported_imports_are_forwarded() {
+        let index = Program::module(vec![
+            import(vec![named("a", Some("local"))], "lib"),
+            ProgramPart::Decl(Decl::Export(Box::new(ModExport::Named(
+                NamedExportDecl::Specifier(
+                    vec![ExportSpecifier {
+                        local: ModuleExportName::Ident(Ident::from("local".to_string())),
+                        alias: Some(name("b")),
+                        is_type_only: false,
+                    }],
+                    None,
+                    Vec::new(),
+                ),
+            )))),
+        ]);
+        let lib = Program::module(vec![export_const(&["a"])]);
+        let graph = ModuleGraph::new([("index", &index), ("lib", &lib)]);
+        assert_eq!(graph.resolve_export(ModuleId(0), "b"), binding(1, "a"));
+    }
+}
@@ -0,0 +1,336 @@
+//! Just enough JSON to read `package.json` and `tsconfig.json`, which
+//! also allows the comments and trailing commas `tsconfig.json` does
+use std::fmt;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    /// The entries in source order, which matters for conditions
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// The value of `key`, when this is an object that has it
+    pub(crate) fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(entries) => entries
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Text that is not JSON
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct JsonError {
+    pub(crate) message: &'static str,
+    /// The byte offset of the problem
+    pub(crate) index: usize,
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.message, self.index)
+    }
+}
+
+pub(crate) fn parse(text: &str) -> Result<Json, JsonError> {
+    let mut parser = Parser {
+        text,
+        chars: text.char_indices().peekable(),
+    };
+    let value = parser.value()?;
+    parser.skip_trivia()?;
+    match parser.chars.peek() {
+        Some((index, _)) => Err(JsonError {
+            message: "unexpected text after the value",
+            index: *index,
+        }),
+        None => Ok(value),
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl Parser<'_> {
+    fn value(&mut self) -> Result<Json, JsonError> {
+        self.skip_trivia()?;
+        let (index, ch) = match self.chars.peek() {
+            Some(next) => *next,
+            None => return Err(self.error("unexpected end of text")),
+        };
+        match ch {
+            '{' => self.object(),
+            '[' => self.array(),
+            '"' => self.string().map(Json::String),
+            't' => self.keyword("true", Json::Bool(true)),
+            'f' => self.keyword("false", Json::Bool(false)),
+            'n' => self.keyword("null", Json::Null),
+            '-' | '0'..='9' => self.number(),
+            _ => Err(JsonError {
+                message: "expected a value",
+                index,
+            }),
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, JsonError> {
+        self.chars.next();
+        let mut entries = Vec::new();
+        loop {
+            self.skip_trivia()?;
+            match self.chars.peek() {
+                Some((_, '}')) => {
+                    self.chars.next();
+                    return Ok(Json::Object(entries));
+                }
+                Some((_, '"')) => {}
+                _ => return Err(self.error("expected a key")),
+            }
+            let key = self.string()?;
+            self.skip_trivia()?;
+            self.expect(':')?;
+            let value = self.value()?;
+            entries.push((key, value));
+            if !self.separator('}')? {
+                self.expect('}')?;
+                return Ok(Json::Object(entries));
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, JsonError> {
+        self.chars.next();
+        let mut items = Vec::new();
+        loop {
+            self.skip_trivia()?;
+            if let Some((_, ']')) = self.chars.peek() {
+                self.chars.next();
+                return Ok(Json::Array(items));
+            }
+            items.push(self.value()?);
+            if !self.separator(']')? {
+                self.expect(']')?;
+                return Ok(Json::Array(items));
+            }
+        }
+    }
+
+    /// Consume a `,` if there is one, a trailing comma before `close`
+    /// is allowed
+    fn separator(&mut self, close: char) -> Result<bool, JsonError> {
+        self.skip_trivia()?;
+        match self.chars.peek() {
+            Some((_, ',')) => {
+                self.chars.next();
+                Ok(true)
+            }
+            Some((_, ch)) if *ch == close => Ok(false),
+            _ => Err(self.error("expected a `,`")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.chars.next();
+        let mut value = String::new();
+        loop {
+            let (index, ch) = match self.chars.next() {
+                Some(next) => next,
+                None => return Err(self.error("unterminated string")),
+            };
+            match ch {
+                '"' => return Ok(value),
+                '\\' => {
+                    let escaped = match self.chars.next() {
+                        Some((_, escaped)) => escaped,
+                        None => return Err(self.error("unterminated string")),
+                    };
+                    match escaped {
+                        '"' | '\\' | '/' => value.push(escaped),
+                        'b' => value.push('\u{8}'),
+                        'f' => value.push('\u{c}'),
+                        'n' => value.push('\n'),
+                        'r' => value.push('\r'),
+                        't' => value.push('\t'),
+                        'u' => {
+                            let unit = self.hex()?;
+                            let code = if (0xD800..0xDC00).contains(&unit) {
+                                // the second half of a surrogate pair
+                                match (self.chars.next(), self.chars.next()) {
+                                    (Some((_, '\\')), Some((_, 'u'))) => {}
+                                    _ => {
+                                        return Err(JsonError {
+                                            message: "unpaired surrogate",
+                                            index,
+                                        })
+                                    }
+                                }
+                                let low = self.hex()?;
+                                if !(0xDC00..0xE000).contains(&low) {
+                                    return Err(JsonError {
+                                        message: "unpaired surrogate",
+                                        index,
+                                    });
+                                }
+                                0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00)
+                            } else {
+                                unit
+                            };
+                            match char::from_u32(code) {
+                                Some(ch) => value.push(ch),
+                                None => {
+                                    return Err(JsonError {
+                                        message: "invalid unicode escape",
+                                        index,
+                                    })
+                                }
+                            }
+                        }
+                        _ => {
+                            return Err(JsonError {
+                                message: "invalid escape",
+                                index,
+                            })
+                        }
+                    }
+                }
+                _ => value.push(ch),
+            }
+        }
+    }
+
+    fn hex(&mut self) -> Result<u32, JsonError> {
+        let mut value = 0;
+        for _ in 0..4 {
+            let digit = self
+                .chars
+                .next()
+                .and_then(|(_, ch)| ch.to_digit(16))
+                .ok_or_else(|| self.error("invalid unicode escape"))?;
+            value = value * 16 + digit;
+        }
+        Ok(value)
+    }
+
+    fn number(&mut self) -> Result<Json, JsonError> {
+        let start = self.position();
+        while let Some((_, ch)) = self.chars.peek() {
+            if !matches!(ch, '-' | '+' | '.' | 'e' | 'E' | '0'..='9') {
+                break;
+            }
+            self.chars.next();
+        }
+        let end = self.position();
+        self.text[start..end]
+            .parse()
+            .map(Json::Number)
+            .map_err(|_| JsonError {
+                message: "invalid number",
+                index: start,
+            })
+    }
+
+    fn keyword(&mut self, word: &str, value: Json) -> Result<Json, JsonError> {
+        let start = self.position();
+        if !self.text[start..].starts_with(word) {
+            return Err(self.error("expected a value"));
+        }
+        for _ in word.chars() {
+            self.chars.next();
+        }
+        Ok(value)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), JsonError> {
+        match self.chars.peek() {
+            Some((_, ch)) if *ch == expected => {
+                self.chars.next();
+                Ok(())
+            }
+            _ => Err(self.error(match expected {
+                ':' => "expected a `:`",
+                '}' => "expected a `}`",
+                _ => "expected a `]`",
+            })),
+        }
+    }
+
+    /// Skip whitespace and comments
+    fn skip_trivia(&mut self) -> Result<(), JsonError> {
+        loop {
+            match self.chars.peek() {
+                Some((_, ch)) if ch.is_whitespace() => {
+                    self.chars.next();
+                }
+                Some((index, '/')) => {
+                    let index = *index;
+                    self.chars.next();
+                    match self.chars.next() {
+                        Some((_, '/')) => {
+                            for (_, ch) in self.chars.by_ref() {
+                                if ch == '\n' {
+                                    break;
+                                }
+                            }
+                        }
+                        Some((_, '*')) => {
+                            let mut star = false;
+                            loop {
+                                match self.chars.next() {
+                                    Some((_, '/')) if star => break,
+                                    Some((_, ch)) => star = ch == '*',
+                                    None => {
+                                        return Err(JsonError {
+                                            message: "unterminated comment",
+                                            index,
+                                        })
+                                    }
+                                }
+                            }
+                        }
+                        _ => {
+                            return Err(JsonError {
+                                message: "expected a value",
+                                index,
+                            })
+                        }
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// The byte offset of the next character
+    fn position(&mut self) -> usize {
+        match self.chars.peek() {
+            Some((index, _)) => *index,
+            None => self.text.len(),
+        }
+    }
+
+    fn error(&mut self, message: &'static str) -> JsonError {
+        JsonError {
+            message,
+            index: self.position(),
+        }
+    }
+}
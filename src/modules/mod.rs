@@ -0,0 +1,4 @@
+pub mod exports;
+pub mod graph;
+pub(crate) mod json;
+pub mod resolve;
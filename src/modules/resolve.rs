@@ -0,0 +1,1059 @@
+//! Finding the file an import specifier refers to, the way Node and
+//! TypeScript do
+//!
+//! ```js
+//! import {render} from './view';
+//! import {useState} from 'preact/hooks';
+//! import {db} from '#db';
+//! import {config} from '@app/config';
+//! ```
+//! `./view` is probed with each extension, like `./view.ts`, and then
+//! as a directory with an index file. `preact/hooks` is looked up in the
+//! `node_modules` directories above the importing file and goes through
+//! the package's `exports`, or its `main` field when it has no
+//! `exports`. `#db` goes through the `imports` of the nearest
+//! `package.json` and `@app/config` through the `paths` and `baseUrl`
+//! of a `tsconfig.json`, see `ResolveOptions::from_tsconfig`. A
+//! `ModuleGraph` of files can name its modules by their paths and
+//! resolve specifiers with `Resolver::resolve`.
+//!
+//! Only the files on disk are used. Some parts are not covered
+//! - symlinks are not resolved to their real path
+//! - `exports` keys ending in `/`, a long removed form, are ignored
+//! - `tsconfig.json` files are only extended through relative paths
+//! - inherited `paths` stay relative to the `baseUrl` of the file that
+//!   declares them
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::modules::json::{self, Json};
+use crate::spanned::decl::{Decl, ModExportSpecifier, ModuleRef, NamedExportDecl};
+use crate::spanned::expr::{Expr, Lit};
+use crate::spanned::visit::{walk_expr, Visit};
+use crate::spanned::{Node, Program, ProgramPart, SliceError};
+
+/// The node modules that have no file, with or without a `node:` prefix
+const BUILTINS: &[&str] = &[
+    "assert",
+    "async_hooks",
+    "buffer",
+    "child_process",
+    "cluster",
+    "console",
+    "constants",
+    "crypto",
+    "dgram",
+    "diagnostics_channel",
+    "dns",
+    "domain",
+    "events",
+    "fs",
+    "http",
+    "http2",
+    "https",
+    "inspector",
+    "module",
+    "net",
+    "os",
+    "path",
+    "perf_hooks",
+    "process",
+    "punycode",
+    "querystring",
+    "readline",
+    "repl",
+    "stream",
+    "string_decoder",
+    "sys",
+    "timers",
+    "tls",
+    "trace_events",
+    "tty",
+    "url",
+    "util",
+    "v8",
+    "vm",
+    "wasi",
+    "worker_threads",
+    "zlib",
+];
+
+/// How specifiers are resolved
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolveOptions {
+    /// Tried in order after a path without a matching file, and after
+    /// the `index` of a directory
+    pub extensions: Vec<String>,
+    /// The conditions that match in `exports` and `imports`, besides
+    /// `default` which always does
+    pub conditions: Vec<String>,
+    /// The `package.json` fields naming the entry of a package without
+    /// `exports`
+    pub main_fields: Vec<String>,
+    /// Bare specifiers are also looked up in this directory
+    pub base_url: Option<PathBuf>,
+    pub paths: Vec<PathAlias>,
+}
+
+impl Default for ResolveOptions {
+    fn default() -> Self {
+        ResolveOptions {
+            extensions: [
+                ".ts", ".tsx", ".mts", ".cts", ".js", ".jsx", ".mjs", ".cjs", ".json",
+            ]
+            .iter()
+            .map(|extension| extension.to_string())
+            .collect(),
+            conditions: vec!["import".to_string(), "node".to_string()],
+            main_fields: vec!["main".to_string()],
+            base_url: None,
+            paths: Vec::new(),
+        }
+    }
+}
+
+impl ResolveOptions {
+    /// The default options with the `baseUrl` and `paths` of a
+    /// `tsconfig.json` and the files it extends
+    pub fn from_tsconfig(path: &Path) -> Result<Self, ResolveError> {
+        let mut options = ResolveOptions::default();
+        options.apply_tsconfig(path, &mut Vec::new())?;
+        Ok(options)
+    }
+
+    fn apply_tsconfig(&mut self, path: &Path, seen: &mut Vec<PathBuf>) -> Result<(), ResolveError> {
+        if seen.iter().any(|other| other == path) {
+            return Ok(());
+        }
+        seen.push(path.to_path_buf());
+        let config = read_json(path)?;
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let bases = match config.get("extends") {
+            Some(Json::String(base)) => vec![base.as_str()],
+            Some(Json::Array(bases)) => bases.iter().filter_map(Json::as_str).collect(),
+            _ => Vec::new(),
+        };
+        for base in bases.into_iter().filter(|base| base.starts_with('.')) {
+            // `./tsconfig.base` is `./tsconfig.base.json`
+            let base = if base.ends_with(".json") {
+                dir.join(base)
+            } else {
+                with_suffix(&dir.join(base), ".json")
+            };
+            self.apply_tsconfig(&base, seen)?;
+        }
+        let compiler = match config.get("compilerOptions") {
+            Some(compiler) => compiler,
+            None => return Ok(()),
+        };
+        if let Some(base_url) = compiler.get("baseUrl").and_then(Json::as_str) {
+            self.base_url = Some(normalize(&dir.join(base_url)));
+        }
+        if let Some(Json::Object(paths)) = compiler.get("paths") {
+            let base = self.base_url.clone().unwrap_or_else(|| dir.to_path_buf());
+            self.paths = paths
+                .iter()
+                .map(|(pattern, targets)| PathAlias {
+                    pattern: pattern.clone(),
+                    targets: match targets {
+                        Json::Array(targets) => targets
+                            .iter()
+                            .filter_map(Json::as_str)
+                            .map(|target| {
+                                normalize(&base.join(target)).to_string_lossy().into_owned()
+                            })
+                            .collect(),
+                        _ => Vec::new(),
+                    },
+                })
+                .collect();
+        }
+        Ok(())
+    }
+}
+
+/// An entry of `compilerOptions.paths`
+/// ```json
+/// {"@app/*": ["src/*"]}
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathAlias {
+    /// The specifier to match, with at most one `*`
+    pub pattern: String,
+    /// The paths to try in order, the `*` is replaced with the text it
+    /// matched in the pattern
+    pub targets: Vec<String>,
+}
+
+/// A specifier that does not lead to a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolveError {
+    /// No file matches the specifier
+    NotFound(String),
+    /// A node builtin like `fs` or `node:path`
+    Builtin(String),
+    /// The `exports` or `imports` of a package do not include the path
+    NotExported { package: PathBuf, subpath: String },
+    /// A `package.json` or `tsconfig.json` that could not be read
+    InvalidConfig { path: PathBuf, message: String },
+    /// The source is not a well formed string
+    InvalidSpecifier,
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolveError::NotFound(specifier) => write!(f, "cannot find module '{}'", specifier),
+            ResolveError::Builtin(specifier) => {
+                write!(f, "'{}' is a builtin module without a file", specifier)
+            }
+            ResolveError::NotExported { package, subpath } => write!(
+                f,
+                "'{}' is not exported by the package at {}",
+                subpath,
+                package.display()
+            ),
+            ResolveError::InvalidConfig { path, message } => {
+                write!(f, "invalid config {}: {}", path.display(), message)
+            }
+            ResolveError::InvalidSpecifier => f.write_str("module specifiers must be strings"),
+        }
+    }
+}
+
+impl std::error::Error for ResolveError {}
+
+/// Resolves specifiers, remembering the results and the files it has
+/// looked at
+#[derive(Debug, Clone)]
+pub struct Resolver {
+    options: ResolveOptions,
+    /// Keyed by the importing directory and the specifier
+    resolved: HashMap<(PathBuf, String), Result<PathBuf, ResolveError>>,
+    /// Keyed by the package directory, `None` without a `package.json`
+    packages: HashMap<PathBuf, Result<Option<Json>, ResolveError>>,
+    files: HashMap<PathBuf, bool>,
+    dirs: HashMap<PathBuf, bool>,
+}
+
+impl Resolver {
+    pub fn new(options: ResolveOptions) -> Self {
+        Resolver {
+            options,
+            resolved: HashMap::new(),
+            packages: HashMap::new(),
+            files: HashMap::new(),
+            dirs: HashMap::new(),
+        }
+    }
+
+    pub fn options(&self) -> &ResolveOptions {
+        &self.options
+    }
+
+    /// Find the file `specifier` refers to when imported from the file
+    /// at `importer`
+    pub fn resolve(&mut self, importer: &Path, specifier: &str) -> Result<PathBuf, ResolveError> {
+        let dir = importer.parent().unwrap_or_else(|| Path::new(""));
+        let key = (dir.to_path_buf(), specifier.to_string());
+        if let Some(resolved) = self.resolved.get(&key) {
+            return resolved.clone();
+        }
+        let resolved = self.resolve_in(dir, specifier).map(|path| normalize(&path));
+        self.resolved.insert(key, resolved.clone());
+        resolved
+    }
+
+    /// Resolve the source of an import or export, with the location of
+    /// the source in the error
+    pub fn resolve_source<T>(
+        &mut self,
+        importer: &Path,
+        source: &Lit<T>,
+    ) -> Result<PathBuf, SliceError<ResolveError>>
+    where
+        T: AsRef<str>,
+    {
+        let string = match source {
+            Lit::String(string) => string,
+            _ => {
+                return Err(SliceError {
+                    error: ResolveError::InvalidSpecifier,
+                    loc: source.loc(),
+                })
+            }
+        };
+        // module code is always strict
+        let units = string.cooked(true).map_err(|error| SliceError {
+            error: ResolveError::InvalidSpecifier,
+            loc: error.loc,
+        })?;
+        let specifier = String::from_utf16_lossy(&units);
+        self.resolve(importer, &specifier)
+            .map_err(|error| SliceError {
+                error,
+                loc: string.content.loc,
+            })
+    }
+
+    /// Resolve the source of every import and re-export at the top level
+    /// of the file at `path` and of every `import()` with a string
+    /// source, in source order
+    pub fn resolve_program<T>(
+        &mut self,
+        path: &Path,
+        program: &Program<T>,
+    ) -> Vec<Result<PathBuf, SliceError<ResolveError>>>
+    where
+        T: AsRef<str>,
+    {
+        let mut sources = Sources(Vec::new());
+        for part in program.parts() {
+            if let ProgramPart::Decl(decl) = part {
+                if let Some(source) = static_source(decl) {
+                    sources.0.push(source);
+                }
+            }
+            sources.visit_part(part);
+        }
+        sources
+            .0
+            .into_iter()
+            .map(|source| self.resolve_source(path, source))
+            .collect()
+    }
+
+    fn resolve_in(&mut self, dir: &Path, specifier: &str) -> Result<PathBuf, ResolveError> {
+        if is_builtin(specifier) {
+            return Err(ResolveError::Builtin(specifier.to_string()));
+        }
+        if specifier.starts_with('#') {
+            return self.package_imports(dir, specifier);
+        }
+        let not_found = || ResolveError::NotFound(specifier.to_string());
+        if is_path(specifier) {
+            return self.load(&dir.join(specifier)).ok_or_else(not_found);
+        }
+        if let Some((targets, matched)) = match_alias(&self.options.paths, specifier) {
+            for target in targets {
+                if let Some(found) = self.load(Path::new(&target.replace('*', &matched))) {
+                    return Ok(found);
+                }
+            }
+        }
+        if let Some(base_url) = self.options.base_url.clone() {
+            if let Some(found) = self.load(&base_url.join(specifier)) {
+                return Ok(found);
+            }
+        }
+        self.node_modules(dir, specifier)
+    }
+
+    /// Find a bare specifier in the `node_modules` above `dir`
+    fn node_modules(&mut self, dir: &Path, specifier: &str) -> Result<PathBuf, ResolveError> {
+        let not_found = || ResolveError::NotFound(specifier.to_string());
+        let (name, subpath) = split_package(specifier).ok_or_else(not_found)?;
+        for ancestor in dir.ancestors() {
+            if ancestor
+                .file_name()
+                .is_some_and(|name| name == "node_modules")
+            {
+                continue;
+            }
+            let package_dir = ancestor.join("node_modules").join(name);
+            if !self.is_dir(&package_dir) {
+                continue;
+            }
+            let package = self.package(&package_dir)?;
+            if let Some(exports) = package.as_ref().and_then(|package| package.get("exports")) {
+                return self.package_exports(&package_dir, exports, &subpath);
+            }
+            let found = if subpath == "." {
+                self.load_dir(&package_dir)
+            } else {
+                self.load(&package_dir.join(&subpath[2..]))
+            };
+            return found.ok_or_else(not_found);
+        }
+        Err(not_found())
+    }
+
+    fn package_exports(
+        &mut self,
+        package_dir: &Path,
+        exports: &Json,
+        subpath: &str,
+    ) -> Result<PathBuf, ResolveError> {
+        let is_map = match exports {
+            Json::Object(entries) => entries.iter().any(|(key, _)| key.starts_with('.')),
+            _ => false,
+        };
+        let target = match exports {
+            Json::Object(entries) if is_map => match_key(entries, subpath),
+            _ if subpath == "." => Some((exports, None)),
+            _ => None,
+        };
+        target
+            .and_then(|(target, matched)| {
+                self.target(package_dir, target, matched.as_deref(), false)
+            })
+            .and_then(|path| self.load_file(&path))
+            .ok_or_else(|| ResolveError::NotExported {
+                package: package_dir.to_path_buf(),
+                subpath: subpath.to_string(),
+            })
+    }
+
+    /// Resolve a `#` specifier through the nearest `package.json`
+    fn package_imports(&mut self, dir: &Path, specifier: &str) -> Result<PathBuf, ResolveError> {
+        for ancestor in dir.ancestors() {
+            if !self.is_file(&ancestor.join("package.json")) {
+                continue;
+            }
+            let package = self.package(ancestor)?;
+            let target = match package.as_ref().and_then(|package| package.get("imports")) {
+                Some(Json::Object(entries)) => match_key(entries, specifier),
+                _ => None,
+            };
+            return target
+                .and_then(|(target, matched)| {
+                    self.target(ancestor, target, matched.as_deref(), true)
+                })
+                .and_then(|path| self.load_file(&path))
+                .ok_or_else(|| ResolveError::NotExported {
+                    package: ancestor.to_path_buf(),
+                    subpath: specifier.to_string(),
+                });
+        }
+        Err(ResolveError::NotFound(specifier.to_string()))
+    }
+
+    /// The path of an `exports` or `imports` target, a target of
+    /// `imports` can also be a bare specifier
+    fn target(
+        &mut self,
+        package_dir: &Path,
+        target: &Json,
+        matched: Option<&str>,
+        imports: bool,
+    ) -> Option<PathBuf> {
+        match target {
+            Json::String(target) => {
+                let target = match matched {
+                    Some(matched) => target.replace('*', matched),
+                    None => target.clone(),
+                };
+                if let Some(relative) = target.strip_prefix("./") {
+                    Some(package_dir.join(relative))
+                } else if imports && !is_path(&target) {
+                    self.node_modules(package_dir, &target).ok()
+                } else {
+                    None
+                }
+            }
+            Json::Array(targets) => targets
+                .iter()
+                .find_map(|target| self.target(package_dir, target, matched, imports)),
+            Json::Object(conditions) => {
+                for (condition, target) in conditions {
+                    if condition != "default" && !self.options.conditions.contains(condition) {
+                        continue;
+                    }
+                    if let Some(found) = self.target(package_dir, target, matched, imports) {
+                        return Some(found);
+                    }
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// A file or a directory
+    fn load(&mut self, path: &Path) -> Option<PathBuf> {
+        self.load_file(path).or_else(|| self.load_dir(path))
+    }
+
+    fn load_file(&mut self, path: &Path) -> Option<PathBuf> {
+        if self.is_file(path) {
+            return Some(path.to_path_buf());
+        }
+        for extension in self.options.extensions.clone() {
+            let path = with_suffix(path, &extension);
+            if self.is_file(&path) {
+                return Some(path);
+            }
+        }
+        // typescript sources are imported with the extension of the
+        // javascript they compile to
+        let replacements: &[&str] = match path.extension().and_then(|extension| extension.to_str())
+        {
+            Some("js") => &["ts", "tsx"],
+            Some("jsx") => &["tsx"],
+            Some("mjs") => &["mts"],
+            Some("cjs") => &["cts"],
+            _ => &[],
+        };
+        for replacement in replacements {
+            let path = path.with_extension(replacement);
+            if self.is_file(&path) {
+                return Some(path);
+            }
+        }
+        None
+    }
+
+    fn load_dir(&mut self, path: &Path) -> Option<PathBuf> {
+        if !self.is_dir(path) {
+            return None;
+        }
+        if let Ok(Some(package)) = self.package(path) {
+            for field in self.options.main_fields.clone() {
+                if let Some(main) = package.get(&field).and_then(Json::as_str) {
+                    let main = path.join(main);
+                    if let Some(found) = self.load_file(&main).or_else(|| self.load_index(&main)) {
+                        return Some(found);
+                    }
+                }
+            }
+        }
+        self.load_index(path)
+    }
+
+    fn load_index(&mut self, path: &Path) -> Option<PathBuf> {
+        let index = path.join("index");
+        for extension in self.options.extensions.clone() {
+            let path = with_suffix(&index, &extension);
+            if self.is_file(&path) {
+                return Some(path);
+            }
+        }
+        None
+    }
+
+    /// The `package.json` in `dir`
+    fn package(&mut self, dir: &Path) -> Result<Option<Json>, ResolveError> {
+        if let Some(package) = self.packages.get(dir) {
+            return package.clone();
+        }
+        let path = dir.join("package.json");
+        let package = if self.is_file(&path) {
+            read_json(&path).map(Some)
+        } else {
+            Ok(None)
+        };
+        self.packages.insert(dir.to_path_buf(), package.clone());
+        package
+    }
+
+    fn is_file(&mut self, path: &Path) -> bool {
+        *self
+            .files
+            .entry(path.to_path_buf())
+            .or_insert_with(|| path.is_file())
+    }
+
+    fn is_dir(&mut self, path: &Path) -> bool {
+        *self
+            .dirs
+            .entry(path.to_path_buf())
+            .or_insert_with(|| path.is_dir())
+    }
+}
+
+fn read_json(path: &Path) -> Result<Json, ResolveError> {
+    let invalid = |message: String| ResolveError::InvalidConfig {
+        path: path.to_path_buf(),
+        message,
+    };
+    let text = fs::read_to_string(path).map_err(|error| invalid(error.to_string()))?;
+    json::parse(&text).map_err(|error| invalid(error.to_string()))
+}
+
+fn is_builtin(specifier: &str) -> bool {
+    if specifier.starts_with("node:") {
+        return true;
+    }
+    let name = specifier.split('/').next().unwrap_or(specifier);
+    BUILTINS.contains(&name)
+}
+
+/// A relative or absolute path rather than a package name
+fn is_path(specifier: &str) -> bool {
+    specifier == "."
+        || specifier == ".."
+        || specifier.starts_with("./")
+        || specifier.starts_with("../")
+        || Path::new(specifier).is_absolute()
+}
+
+/// Split a bare specifier into the package name and the subpath in
+/// the package, `"."` for the package itself
+fn split_package(specifier: &str) -> Option<(&str, String)> {
+    let mut slashes = specifier.match_indices('/').map(|(index, _)| index);
+    let end = if specifier.starts_with('@') {
+        slashes.nth(1)
+    } else {
+        slashes.next()
+    };
+    let (name, rest) = match end {
+        Some(end) => (&specifier[..end], &specifier[end..]),
+        None => (specifier, ""),
+    };
+    if name.is_empty() || (name.starts_with('@') && !name.contains('/')) {
+        return None;
+    }
+    Some((name, format!(".{}", rest)))
+}
+
+/// The `paths` entry for `specifier`, an exact pattern wins over the
+/// pattern with the longest text before its `*`
+fn match_alias(paths: &[PathAlias], specifier: &str) -> Option<(Vec<String>, String)> {
+    let mut best: Option<(&PathAlias, &str, usize)> = None;
+    for alias in paths {
+        match alias.pattern.split_once('*') {
+            None if alias.pattern == specifier => {
+                return Some((alias.targets.clone(), String::new()));
+            }
+            None => {}
+            Some((prefix, suffix)) => {
+                let fits = specifier.len() >= prefix.len() + suffix.len()
+                    && specifier.starts_with(prefix)
+                    && specifier.ends_with(suffix);
+                if fits && best.is_none_or(|(_, _, length)| prefix.len() > length) {
+                    let matched = &specifier[prefix.len()..specifier.len() - suffix.len()];
+                    best = Some((alias, matched, prefix.len()));
+                }
+            }
+        }
+    }
+    best.map(|(alias, matched, _)| (alias.targets.clone(), matched.to_string()))
+}
+
+/// The entry of an `exports` or `imports` map for `key`, with the
+/// text a `*` matched
+fn match_key<'j>(entries: &'j [(String, Json)], key: &str) -> Option<(&'j Json, Option<String>)> {
+    if let Some((_, target)) = entries
+        .iter()
+        .find(|(name, _)| name == key && !name.contains('*'))
+    {
+        return Some((target, None));
+    }
+    let mut best: Option<(&Json, &str, &str)> = None;
+    for (name, target) in entries {
+        let (prefix, suffix) = match name.split_once('*') {
+            Some(split) if !split.1.contains('*') => split,
+            _ => continue,
+        };
+        let fits = key.len() >= name.len() && key.starts_with(prefix) && key.ends_with(suffix);
+        // a longer prefix wins, then a longer pattern
+        let better = best.is_none_or(|(_, best_prefix, best_name)| {
+            prefix.len() > best_prefix.len()
+                || (prefix.len() == best_prefix.len() && name.len() > best_name.len())
+        });
+        if fits && better {
+            best = Some((target, prefix, name));
+        }
+    }
+    best.map(|(target, prefix, name)| {
+        let suffix = name.len() - prefix.len() - 1;
+        (
+            target,
+            Some(key[prefix.len()..key.len() - suffix].to_string()),
+        )
+    })
+}
+
+/// `path` with `suffix` added to the end of its file name
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = OsString::from(path);
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+/// Remove the `.` and `..` parts of a path without looking at the disk
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => {}
+                _ => normalized.push(".."),
+            },
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// The source of an import or re-export
+fn static_source<T>(decl: &Decl<T>) -> Option<&Lit<T>> {
+    match decl {
+        Decl::Import { import, .. } => Some(&import.source),
+        Decl::Export { export, .. } => match &export.spec {
+            ModExportSpecifier::Named(NamedExportDecl::Specifier(spec)) => {
+                spec.source.as_ref().map(|source| &source.module)
+            }
+            ModExportSpecifier::All { name, .. } => Some(name),
+            _ => None,
+        },
+        Decl::ImportEquals { import, .. } => match &import.module_ref {
+            ModuleRef::Require(require) => Some(&require.module),
+            ModuleRef::Entity(_) => None,
+        },
+        _ => None,
+    }
+}
+
+/// The string sources of the `import()` calls in a program
+struct Sources<'a, T>(Vec<&'a Lit<T>>);
+
+impl<'a, T> Visit<'a, T> for Sources<'a, T> {
+    fn visit_expr(&mut self, expr: &'a Expr<T>) {
+        if let Expr::Import(import) = expr {
+            if let Expr::Lit(source @ Lit::String(_)) = &*import.source {
+                self.0.push(source);
+            }
+        }
+        walk_expr(self, expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A directory of files removed when dropped
+    struct Fixture {
+        root: PathBuf,
+    }
+
+    impl Fixture {
+        fn new(name: &str, files: &[(&str, &str)]) -> Self {
+            let root = std::env::temp_dir().join(format!(
+                "resast-resolve-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&root);
+            for (path, contents) in files {
+                let path = root.join(path);
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(path, contents).unwrap();
+            }
+            Fixture { root }
+        }
+
+        fn path(&self, path: &str) -> PathBuf {
+            self.root.join(path)
+        }
+
+        fn resolve(&self, specifier: &str) -> Result<PathBuf, ResolveError> {
+            self.resolve_with(ResolveOptions::default(), specifier)
+        }
+
+        fn resolve_with(
+            &self,
+            options: ResolveOptions,
+            specifier: &str,
+        ) -> Result<PathBuf, ResolveError> {
+            Resolver::new(options).resolve(&self.path("src/main.ts"), specifier)
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+
+    #[test]
+    fn relative_paths() {
+        let fixture = Fixture::new(
+            "relative",
+            &[
+                ("src/main.ts", ""),
+                ("src/view.tsx", ""),
+                ("src/util.js", ""),
+                ("src/lib/index.ts", ""),
+                ("src/pkg/package.json", r#"{"main": "entry"}"#),
+                ("src/pkg/entry.js", ""),
+                ("src/typed.ts", ""),
+                ("shared.json", ""),
+            ],
+        );
+        assert_eq!(fixture.resolve("./view"), Ok(fixture.path("src/view.tsx")));
+        assert_eq!(
+            fixture.resolve("./util.js"),
+            Ok(fixture.path("src/util.js"))
+        );
+        assert_eq!(
+            fixture.resolve("./lib"),
+            Ok(fixture.path("src/lib/index.ts"))
+        );
+        assert_eq!(
+            fixture.resolve("./pkg"),
+            Ok(fixture.path("src/pkg/entry.js"))
+        );
+        assert_eq!(
+            fixture.resolve("./typed.js"),
+            Ok(fixture.path("src/typed.ts"))
+        );
+        assert_eq!(
+            fixture.resolve("../shared"),
+            Ok(fixture.path("shared.json"))
+        );
+        assert_eq!(
+            fixture.resolve("./missing"),
+            Err(ResolveError::NotFound("./missing".to_string()))
+        );
+    }
+
+    #[test]
+    fn builtins() {
+        let fixture = Fixture::new("builtins", &[("src/main.ts", "")]);
+        assert_eq!(
+            fixture.resolve("fs"),
+            Err(ResolveError::Builtin("fs".to_string()))
+        );
+        assert_eq!(
+            fixture.resolve("node:path/posix"),
+            Err(ResolveError::Builtin("node:path/posix".to_string()))
+        );
+    }
+
+    #[test]
+    fn packages_without_exports() {
+        let fixture = Fixture::new(
+            "main",
+            &[
+                ("src/main.ts", ""),
+                (
+                    "node_modules/plain/package.json",
+                    r#"{"main": "lib/plain"}"#,
+                ),
+                ("node_modules/plain/lib/plain.js", ""),
+                ("node_modules/plain/extra.js", ""),
+                ("node_modules/bare/index.js", ""),
+                ("node_modules/@scope/pkg/index.ts", ""),
+            ],
+        );
+        assert_eq!(
+            fixture.resolve("plain"),
+            Ok(fixture.path("node_modules/plain/lib/plain.js"))
+        );
+        assert_eq!(
+            fixture.resolve("plain/extra"),
+            Ok(fixture.path("node_modules/plain/extra.js"))
+        );
+        assert_eq!(
+            fixture.resolve("bare"),
+            Ok(fixture.path("node_modules/bare/index.js"))
+        );
+        assert_eq!(
+            fixture.resolve("@scope/pkg"),
+            Ok(fixture.path("node_modules/@scope/pkg/index.ts"))
+        );
+        assert_eq!(
+            fixture.resolve("absent"),
+            Err(ResolveError::NotFound("absent".to_string()))
+        );
+    }
+
+    #[test]
+    fn exports_conditions() {
+        let fixture = Fixture::new(
+            "exports",
+            &[
+                ("src/main.ts", ""),
+                (
+                    "node_modules/pkg/package.json",
+                    r#"{
+                        "main": "ignored.js",
+                        "exports": {
+                            ".": {"require": "./root.cjs", "import": "./root.mjs"},
+                            "./feature": [{"browser": "./browser.js"}, "./feature.js"],
+                            "./utils/*": {"default": "./dist/utils/*.js"},
+                            "./utils/private/*": null
+                        }
+                    }"#,
+                ),
+                ("node_modules/pkg/root.cjs", ""),
+                ("node_modules/pkg/root.mjs", ""),
+                ("node_modules/pkg/browser.js", ""),
+                ("node_modules/pkg/feature.js", ""),
+                ("node_modules/pkg/dist/utils/math.js", ""),
+                ("node_modules/pkg/dist/utils/private/key.js", ""),
+                (
+                    "node_modules/sugar/package.json",
+                    r#"{"exports": "./only.js"}"#,
+                ),
+                ("node_modules/sugar/only.js", ""),
+            ],
+        );
+        assert_eq!(
+            fixture.resolve("pkg"),
+            Ok(fixture.path("node_modules/pkg/root.mjs"))
+        );
+        let require = ResolveOptions {
+            conditions: vec!["require".to_string()],
+            ..ResolveOptions::default()
+        };
+        assert_eq!(
+            fixture.resolve_with(require, "pkg"),
+            Ok(fixture.path("node_modules/pkg/root.cjs"))
+        );
+        assert_eq!(
+            fixture.resolve("pkg/feature"),
+            Ok(fixture.path("node_modules/pkg/feature.js"))
+        );
+        let browser = ResolveOptions {
+            conditions: vec!["browser".to_string()],
+            ..ResolveOptions::default()
+        };
+        assert_eq!(
+            fixture.resolve_with(browser, "pkg/feature"),
+            Ok(fixture.path("node_modules/pkg/browser.js"))
+        );
+        assert_eq!(
+            fixture.resolve("pkg/utils/math"),
+            Ok(fixture.path("node_modules/pkg/dist/utils/math.js"))
+        );
+        let not_exported = |subpath: &str| ResolveError::NotExported {
+            package: fixture.path("node_modules/pkg"),
+            subpath: subpath.to_string(),
+        };
+        assert_eq!(
+            fixture.resolve("pkg/utils/private/key"),
+            Err(not_exported("./utils/private/key"))
+        );
+        assert_eq!(
+            fixture.resolve("pkg/ignored"),
+            Err(not_exported("./ignored"))
+        );
+        assert_eq!(
+            fixture.resolve("sugar"),
+            Ok(fixture.path("node_modules/sugar/only.js"))
+        );
+    }
+
+    #[test]
+    fn imports() {
+        let fixture = Fixture::new(
+            "imports",
+            &[
+                ("src/main.ts", ""),
+                (
+                    "package.json",
+                    r##"{
+                        "imports": {
+                            "#db": {"node": "./src/db/node.ts", "default": "./src/db/web.ts"},
+                            "#internal/*": "./src/internal/*.ts",
+                            "#dep": "dep"
+                        }
+                    }"##,
+                ),
+                ("src/db/node.ts", ""),
+                ("src/db/web.ts", ""),
+                ("src/internal/a.ts", ""),
+                ("node_modules/dep/index.js", ""),
+            ],
+        );
+        assert_eq!(fixture.resolve("#db"), Ok(fixture.path("src/db/node.ts")));
+        let web = ResolveOptions {
+            conditions: Vec::new(),
+            ..ResolveOptions::default()
+        };
+        assert_eq!(
+            fixture.resolve_with(web, "#db"),
+            Ok(fixture.path("src/db/web.ts"))
+        );
+        assert_eq!(
+            fixture.resolve("#internal/a"),
+            Ok(fixture.path("src/internal/a.ts"))
+        );
+        assert_eq!(
+            fixture.resolve("#dep"),
+            Ok(fixture.path("node_modules/dep/index.js"))
+        );
+        assert_eq!(
+            fixture.resolve("#missing"),
+            Err(ResolveError::NotExported {
+                package: fixture.root.clone(),
+                subpath: "#missing".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn tsconfig_paths() {
+        let fixture = Fixture::new(
+            "paths",
+            &[
+                ("src/main.ts", ""),
+                ("base.json", r#"{"compilerOptions": {"baseUrl": "./src"}}"#),
+                (
+                    "tsconfig.json",
+                    r#"{
+                        // comments are allowed
+                        "extends": "./base",
+                        "compilerOptions": {
+                            "paths": {
+                                "@app/*": ["missing/*", "app/*"],
+                                "@app/special": ["special/entry"],
+                                "config": ["config/index"]
+                            }
+                        }
+                    }"#,
+                ),
+                ("src/app/view.ts", ""),
+                ("src/special/entry.ts", ""),
+                ("src/config/index.ts", ""),
+                ("src/local.ts", ""),
+            ],
+        );
+        let options = ResolveOptions::from_tsconfig(&fixture.path("tsconfig.json")).unwrap();
+        assert_eq!(options.base_url, Some(fixture.path("src")));
+        let resolve = |specifier: &str| fixture.resolve_with(options.clone(), specifier);
+        assert_eq!(resolve("@app/view"), Ok(fixture.path("src/app/view.ts")));
+        assert_eq!(
+            resolve("@app/special"),
+            Ok(fixture.path("src/special/entry.ts"))
+        );
+        assert_eq!(resolve("config"), Ok(fixture.path("src/config/index.ts")));
+        assert_eq!(resolve("local"), Ok(fixture.path("src/local.ts")));
+        assert_eq!(
+            resolve("@app/none"),
+            Err(ResolveError::NotFound("@app/none".to_string()))
+        );
+    }
+
+    #[test]
+    fn tsconfig_extends_dotted_name() {
+        let fixture = Fixture::new(
+            "extends",
+            &[
+                (
+                    "tsconfig.base.json",
+                    r#"{"compilerOptions": {"baseUrl": "./lib"}}"#,
+                ),
+                ("tsconfig.json", r#"{"extends": "./tsconfig.base"}"#),
+                (
+                    "configs/strict.json",
+                    r#"{"extends": "../tsconfig.base.json"}"#,
+                ),
+            ],
+        );
+        let options = ResolveOptions::from_tsconfig(&fixture.path("tsconfig.json")).unwrap();
+        assert_eq!(options.base_url, Some(fixture.path("lib")));
+        let options = ResolveOptions::from_tsconfig(&fixture.path("configs/strict.json")).unwrap();
+        assert_eq!(options.base_url, Some(fixture.path("lib")));
+    }
+}
@@ -0,0 +1,559 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// An arbitrary precision integer, the value of a `BigInt` literal
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct BigInt {
+    negative: bool,
+    /// Base 2^32 digits, least significant first, without
+    /// any trailing zeros
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    /// Parse a sequence of digits in the provided radix, this
+    /// does not accept a sign, prefix or separators
+    pub fn from_str_radix(digits: &str, radix: u32) -> Option<Self> {
+        if digits.is_empty() || !(2..=36).contains(&radix) {
+            return None;
+        }
+        let mut ret = Self::zero();
+        for ch in digits.chars() {
+            let digit = ch.to_digit(radix)?;
+            ret.mul_small_add(radix, digit);
+        }
+        Some(ret)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// The number of bits needed to represent the magnitude
+    pub fn bit_len(&self) -> u64 {
+        match self.magnitude.last() {
+            Some(last) => {
+                (self.magnitude.len() as u64 - 1) * 32 + (32 - last.leading_zeros() as u64)
+            }
+            None => 0,
+        }
+    }
+
+    /// The nearest `f64`, ties round to even the way `Number(value)` does
+    pub fn to_f64(&self) -> f64 {
+        let bits = self.bit_len();
+        let magnitude = if bits <= 64 {
+            self.low_u64() as f64
+        } else {
+            let shift = bits - 64;
+            let mut top = self.shr_u64(shift);
+            // any bits dropped by the shift only matter to break
+            // a tie, they are folded into the lowest bit which is
+            // well below the 53 bits of an f64 mantissa
+            if has_bits_below(&self.magnitude, shift) {
+                top |= 1;
+            }
+            if shift > i32::MAX as u64 {
+                f64::INFINITY
+            } else {
+                top as f64 * 2f64.powi(shift as i32)
+            }
+        };
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    /// Format the value in the provided radix, lower case
+    /// letters are used for digits above 9
+    pub fn to_str_radix(&self, radix: u32) -> String {
+        assert!((2..=36).contains(&radix), "radix must be between 2 and 36");
+        if self.is_zero() {
+            return "0".to_string();
+        }
+        let mut digits = Vec::new();
+        let mut rest = self.magnitude.clone();
+        while !rest.is_empty() {
+            let rem = div_small(&mut rest, radix);
+            digits.push(std::char::from_digit(rem, radix).unwrap_or('0'));
+        }
+        if self.negative {
+            digits.push('-');
+        }
+        digits.iter().rev().collect()
+    }
+
+    /// The exact value of an integral `f64`, `None` for a
+    /// fraction, `NaN` or an infinity
+    pub fn from_f64(value: f64) -> Option<Self> {
+        if !value.is_finite() || value.fract() != 0.0 {
+            return None;
+        }
+        if value == 0.0 {
+            return Some(Self::zero());
+        }
+        // an integral value is never subnormal
+        let bits = value.abs().to_bits();
+        let exponent = (bits >> 52) as i64 - 1075;
+        let mantissa = bits & ((1 << 52) - 1) | 1 << 52;
+        let mut ret = Self::from(mantissa);
+        ret.magnitude = if exponent >= 0 {
+            shl_magnitude(&ret.magnitude, exponent as u64)
+        } else {
+            shr_magnitude(&ret.magnitude, exponent.unsigned_abs())
+        };
+        ret.negative = value < 0.0;
+        ret.normalize();
+        Some(ret)
+    }
+
+    /// The value as an `i64`, `None` when it does not fit
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.bit_len() > 63 {
+            return None;
+        }
+        let magnitude = self.low_u64() as i64;
+        Some(if self.negative { -magnitude } else { magnitude })
+    }
+
+    /// Truncating division like `a / b`, `None` when dividing by zero
+    pub fn checked_div(&self, other: &BigInt) -> Option<BigInt> {
+        if other.is_zero() {
+            return None;
+        }
+        let mut ret = Self {
+            negative: self.negative != other.negative,
+            magnitude: div_rem_magnitude(&self.magnitude, &other.magnitude).0,
+        };
+        ret.normalize();
+        Some(ret)
+    }
+
+    /// The remainder of `checked_div`, which has the sign of `self`
+    /// like `a % b`
+    pub fn checked_rem(&self, other: &BigInt) -> Option<BigInt> {
+        if other.is_zero() {
+            return None;
+        }
+        let mut ret = Self {
+            negative: self.negative,
+            magnitude: div_rem_magnitude(&self.magnitude, &other.magnitude).1,
+        };
+        ret.normalize();
+        Some(ret)
+    }
+
+    pub fn pow(&self, mut exponent: u32) -> BigInt {
+        let mut ret = BigInt::from(1u64);
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                ret = &ret * &base;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        ret
+    }
+
+    /// Multiply by `2^bits`, a negative `bits` divides rounding towards
+    /// negative infinity, which is `a << b` and `a >> -b`
+    pub fn shift(&self, bits: i64) -> BigInt {
+        let magnitude = if bits >= 0 {
+            shl_magnitude(&self.magnitude, bits as u64)
+        } else {
+            let bits = bits.unsigned_abs();
+            let mut magnitude = shr_magnitude(&self.magnitude, bits);
+            if self.negative && has_bits_below(&self.magnitude, bits) {
+                magnitude = add_magnitude(&magnitude, &[1]);
+            }
+            magnitude
+        };
+        let mut ret = Self {
+            negative: self.negative,
+            magnitude,
+        };
+        ret.normalize();
+        ret
+    }
+
+    fn mul_small_add(&mut self, mul: u32, add: u32) {
+        let mut carry = add as u64;
+        for limb in self.magnitude.iter_mut() {
+            let value = *limb as u64 * mul as u64 + carry;
+            *limb = value as u32;
+            carry = value >> 32;
+        }
+        if carry > 0 {
+            self.magnitude.push(carry as u32);
+        }
+    }
+
+    fn low_u64(&self) -> u64 {
+        let low = self.magnitude.first().copied().unwrap_or(0) as u64;
+        let high = self.magnitude.get(1).copied().unwrap_or(0) as u64;
+        high << 32 | low
+    }
+
+    /// The 64 bits of the magnitude starting at bit `shift`
+    fn shr_u64(&self, shift: u64) -> u64 {
+        let limb = (shift / 32) as usize;
+        let window = (0..3).fold(0u128, |acc, i| {
+            let value = self.magnitude.get(limb + i).copied().unwrap_or(0) as u128;
+            acc | value << (i * 32)
+        });
+        (window >> (shift % 32)) as u64
+    }
+
+    fn normalize(&mut self) {
+        while self.magnitude.last() == Some(&0) {
+            self.magnitude.pop();
+        }
+        if self.magnitude.is_empty() {
+            self.negative = false;
+        }
+    }
+}
+
+/// Divide `digits` in place, returning the remainder
+fn div_small(digits: &mut Vec<u32>, div: u32) -> u32 {
+    let mut rem = 0u64;
+    for limb in digits.iter_mut().rev() {
+        let value = rem << 32 | *limb as u64;
+        *limb = (value / div as u64) as u32;
+        rem = value % div as u64;
+    }
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+    rem as u32
+}
+
+fn compare_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut ret = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+    for i in 0..a.len().max(b.len()) {
+        let sum =
+            a.get(i).copied().unwrap_or(0) as u64 + b.get(i).copied().unwrap_or(0) as u64 + carry;
+        ret.push(sum as u32);
+        carry = sum >> 32;
+    }
+    if carry > 0 {
+        ret.push(carry as u32);
+    }
+    ret
+}
+
+/// `a - b` where `a` is at least `b`
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut ret = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, limb) in a.iter().enumerate() {
+        let mut diff = *limb as i64 - b.get(i).copied().unwrap_or(0) as i64 - borrow;
+        borrow = 0;
+        if diff < 0 {
+            diff += 1 << 32;
+            borrow = 1;
+        }
+        ret.push(diff as u32);
+    }
+    while ret.last() == Some(&0) {
+        ret.pop();
+    }
+    ret
+}
+
+fn mul_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut ret = vec![0u32; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, y) in b.iter().enumerate() {
+            let value = *x as u64 * *y as u64 + ret[i + j] as u64 + carry;
+            ret[i + j] = value as u32;
+            carry = value >> 32;
+        }
+        ret[i + b.len()] = carry as u32;
+    }
+    while ret.last() == Some(&0) {
+        ret.pop();
+    }
+    ret
+}
+
+/// Long division one bit at a time, `b` must not be zero
+fn div_rem_magnitude(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if compare_magnitude(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+    if let [divisor] = b {
+        let mut quotient = a.to_vec();
+        let rem = div_small(&mut quotient, *divisor);
+        let rem = if rem == 0 { Vec::new() } else { vec![rem] };
+        return (quotient, rem);
+    }
+    let mut quotient = vec![0u32; a.len()];
+    let mut rem: Vec<u32> = Vec::new();
+    for bit in (0..a.len() * 32).rev() {
+        rem = shl_magnitude(&rem, 1);
+        if a[bit / 32] >> (bit % 32) & 1 == 1 {
+            match rem.first_mut() {
+                Some(low) => *low |= 1,
+                None => rem.push(1),
+            }
+        }
+        if compare_magnitude(&rem, b) != Ordering::Less {
+            rem = sub_magnitude(&rem, b);
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+    while quotient.last() == Some(&0) {
+        quotient.pop();
+    }
+    (quotient, rem)
+}
+
+fn shl_magnitude(a: &[u32], bits: u64) -> Vec<u32> {
+    if a.is_empty() {
+        return Vec::new();
+    }
+    let limbs = (bits / 32) as usize;
+    let bit = bits % 32;
+    let mut ret = vec![0u32; limbs];
+    let mut carry = 0u32;
+    for limb in a {
+        ret.push(limb << bit | carry);
+        carry = if bit == 0 { 0 } else { limb >> (32 - bit) };
+    }
+    if carry > 0 {
+        ret.push(carry);
+    }
+    ret
+}
+
+/// Shift right, dropping the bits shifted out
+fn shr_magnitude(a: &[u32], bits: u64) -> Vec<u32> {
+    let limbs = (bits / 32) as usize;
+    if limbs >= a.len() {
+        return Vec::new();
+    }
+    let bit = bits % 32;
+    let mut ret: Vec<u32> = (limbs..a.len())
+        .map(|i| {
+            let high = if bit == 0 {
+                0
+            } else {
+                a.get(i + 1).copied().unwrap_or(0) << (32 - bit)
+            };
+            a[i] >> bit | high
+        })
+        .collect();
+    while ret.last() == Some(&0) {
+        ret.pop();
+    }
+    ret
+}
+
+fn has_bits_below(a: &[u32], bits: u64) -> bool {
+    let limbs = (bits / 32) as usize;
+    if a.iter().take(limbs).any(|limb| *limb != 0) {
+        return true;
+    }
+    let bit = bits % 32;
+    bit > 0
+        && a.get(limbs)
+            .is_some_and(|limb| limb & ((1u32 << bit) - 1) != 0)
+}
+
+impl BigInt {
+    /// The value in two's complement with `len` limbs, which must
+    /// leave room for the sign bit
+    fn to_twos_complement(&self, len: usize) -> Vec<u32> {
+        let mut limbs = self.magnitude.clone();
+        limbs.resize(len, 0);
+        if self.negative {
+            let mut carry = true;
+            for limb in limbs.iter_mut() {
+                let (value, overflow) = (!*limb).overflowing_add(carry as u32);
+                *limb = value;
+                carry = overflow;
+            }
+        }
+        limbs
+    }
+
+    fn from_twos_complement(mut limbs: Vec<u32>) -> Self {
+        let negative = limbs.last().is_some_and(|limb| limb >> 31 == 1);
+        if negative {
+            let mut carry = true;
+            for limb in limbs.iter_mut() {
+                let (value, overflow) = (!*limb).overflowing_add(carry as u32);
+                *limb = value;
+                carry = overflow;
+            }
+        }
+        let mut ret = Self {
+            negative,
+            magnitude: limbs,
+        };
+        ret.normalize();
+        ret
+    }
+
+    /// A bitwise operator on the infinite two's complement
+    /// representations, the way `&`, `|` and `^` treat bigints
+    fn bitwise(&self, other: &BigInt, op: fn(u32, u32) -> u32) -> BigInt {
+        let len = self.magnitude.len().max(other.magnitude.len()) + 1;
+        let a = self.to_twos_complement(len);
+        let b = other.to_twos_complement(len);
+        Self::from_twos_complement(a.iter().zip(&b).map(|(a, b)| op(*a, *b)).collect())
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitude(&self.magnitude, &other.magnitude),
+            (true, true) => compare_magnitude(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl std::ops::Add for &BigInt {
+    type Output = BigInt;
+    fn add(self, other: &BigInt) -> BigInt {
+        let mut ret = if self.negative == other.negative {
+            BigInt {
+                negative: self.negative,
+                magnitude: add_magnitude(&self.magnitude, &other.magnitude),
+            }
+        } else if compare_magnitude(&self.magnitude, &other.magnitude) == Ordering::Less {
+            BigInt {
+                negative: other.negative,
+                magnitude: sub_magnitude(&other.magnitude, &self.magnitude),
+            }
+        } else {
+            BigInt {
+                negative: self.negative,
+                magnitude: sub_magnitude(&self.magnitude, &other.magnitude),
+            }
+        };
+        ret.normalize();
+        ret
+    }
+}
+
+impl std::ops::Sub for &BigInt {
+    type Output = BigInt;
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other.clone()
+    }
+}
+
+impl std::ops::Mul for &BigInt {
+    type Output = BigInt;
+    fn mul(self, other: &BigInt) -> BigInt {
+        let mut ret = BigInt {
+            negative: self.negative != other.negative,
+            magnitude: mul_magnitude(&self.magnitude, &other.magnitude),
+        };
+        ret.normalize();
+        ret
+    }
+}
+
+impl std::ops::BitAnd for &BigInt {
+    type Output = BigInt;
+    fn bitand(self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a & b)
+    }
+}
+
+impl std::ops::BitOr for &BigInt {
+    type Output = BigInt;
+    fn bitor(self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a | b)
+    }
+}
+
+impl std::ops::BitXor for &BigInt {
+    type Output = BigInt;
+    fn bitxor(self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a ^ b)
+    }
+}
+
+/// `~a`, which is `-a - 1`
+impl std::ops::Not for &BigInt {
+    type Output = BigInt;
+    fn not(self) -> BigInt {
+        &-self.clone() - &BigInt::from(1u64)
+    }
+}
+
+impl From<u64> for BigInt {
+    fn from(value: u64) -> Self {
+        let mut ret = Self {
+            negative: false,
+            magnitude: vec![value as u32, (value >> 32) as u32],
+        };
+        ret.normalize();
+        ret
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        let mut ret = Self::from(value.unsigned_abs());
+        ret.negative = value < 0;
+        ret.normalize();
+        ret
+    }
+}
+
+impl std::ops::Neg for BigInt {
+    type Output = BigInt;
+    fn neg(mut self) -> Self::Output {
+        self.negative = !self.negative;
+        self.normalize();
+        self
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_str_radix(10))
+    }
+}
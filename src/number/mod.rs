@@ -0,0 +1,427 @@
+//! Analysis of the raw text of numeric literals
+//!
+//! ```js
+//! 1_000.5e-3
+//! 0xff
+//! 0o7_7
+//! 0b1010
+//! 017 // legacy octal
+//! 10n // bigint
+//! ```
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+mod big_int;
+
+pub use big_int::BigInt;
+
+/// The notation a numeric literal was written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum Radix {
+    /// `10`, `1.5`, `1e3`
+    Decimal,
+    /// `0xff`
+    Hex,
+    /// `0o77`
+    Octal,
+    /// `0b11`
+    Binary,
+    /// `077`, not allowed in strict mode
+    LegacyOctal,
+    /// `089`, a decimal with a leading zero, not allowed in strict mode
+    NonOctalDecimal,
+}
+
+impl Radix {
+    /// The numeric base of the digits
+    pub fn base(self) -> u32 {
+        match self {
+            Radix::Decimal | Radix::NonOctalDecimal => 10,
+            Radix::Hex => 16,
+            Radix::Octal | Radix::LegacyOctal => 8,
+            Radix::Binary => 2,
+        }
+    }
+
+    /// If this notation is a syntax error in strict mode code
+    pub fn is_legacy(self) -> bool {
+        matches!(self, Radix::LegacyOctal | Radix::NonOctalDecimal)
+    }
+}
+
+/// A validated numeric literal
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct NumericLit {
+    radix: Radix,
+    is_big_int: bool,
+    has_separators: bool,
+    /// The digits without the prefix, separators or `n` suffix,
+    /// a decimal will also include any fraction and exponent
+    digits: String,
+}
+
+impl NumericLit {
+    /// Validate and classify the raw text of a number or bigint literal
+    pub fn parse(raw: &str) -> Result<Self, NumberError> {
+        let (body, is_big_int) = match raw.strip_suffix('n') {
+            Some(body) => (body, true),
+            None => (raw, false),
+        };
+        let bytes = body.as_bytes();
+        if bytes.is_empty() {
+            return Err(NumberError::MissingDigits { index: 0 });
+        }
+        let prefixed = match bytes {
+            [b'0', b'x' | b'X', ..] => Some(Radix::Hex),
+            [b'0', b'o' | b'O', ..] => Some(Radix::Octal),
+            [b'0', b'b' | b'B', ..] => Some(Radix::Binary),
+            _ => None,
+        };
+        let (radix, digits, has_separators) = if let Some(radix) = prefixed {
+            let scanned = scan_digits(body, 2, radix.base(), true)?;
+            if scanned.end != body.len() {
+                return Err(invalid_digit(body, scanned.end));
+            }
+            (radix, body[2..].replace('_', ""), scanned.separated)
+        } else if bytes.len() > 1 && bytes[0] == b'0' && bytes[1] == b'_' {
+            return Err(NumberError::InvalidSeparator { index: 1 });
+        } else if bytes.len() > 1 && bytes[0] == b'0' && bytes[1].is_ascii_digit() {
+            if is_big_int {
+                return Err(NumberError::InvalidBigInt);
+            }
+            let legacy = scan_digits(body, 1, 10, false)?;
+            if body[1..legacy.end].bytes().all(|b| b < b'8') {
+                if legacy.end != body.len() {
+                    return Err(invalid_digit(body, legacy.end));
+                }
+                (Radix::LegacyOctal, body[1..].to_string(), false)
+            } else {
+                let end = scan_decimal_tail(body, legacy.end, false)?.end;
+                if end != body.len() {
+                    return Err(invalid_digit(body, end));
+                }
+                (Radix::NonOctalDecimal, body.to_string(), false)
+            }
+        } else {
+            let int = scan_digits(body, 0, 10, true)?;
+            let tail = scan_decimal_tail(body, int.end, true)?;
+            if tail.end != body.len() {
+                return Err(invalid_digit(body, tail.end));
+            }
+            if int.end == 0 && !tail.fraction_digits {
+                return Err(NumberError::MissingDigits { index: 0 });
+            }
+            if is_big_int && int.end != body.len() {
+                return Err(NumberError::InvalidBigInt);
+            }
+            (
+                Radix::Decimal,
+                body.replace('_', ""),
+                int.separated || tail.separated,
+            )
+        };
+        Ok(Self {
+            radix,
+            is_big_int,
+            has_separators,
+            digits,
+        })
+    }
+
+    pub fn radix(&self) -> Radix {
+        self.radix
+    }
+
+    /// If the literal had the `n` suffix
+    pub fn is_big_int(&self) -> bool {
+        self.is_big_int
+    }
+
+    /// If the literal contained any `_` separators
+    pub fn has_separators(&self) -> bool {
+        self.has_separators
+    }
+
+    /// The value of the literal as an `f64`, rounded to the nearest
+    /// representable value. For a bigint this is the value of `Number(10n)`
+    pub fn value(&self) -> f64 {
+        match self.radix {
+            Radix::Decimal | Radix::NonOctalDecimal => {
+                let mut digits = self.digits.replace(".e", "e").replace(".E", "E");
+                if digits.ends_with('.') {
+                    digits.pop();
+                }
+                digits.parse().unwrap_or(f64::NAN)
+            }
+            _ => self.integer().to_f64(),
+        }
+    }
+
+    /// The exact value of a bigint literal
+    pub fn big_int(&self) -> Option<BigInt> {
+        if self.is_big_int {
+            Some(self.integer())
+        } else {
+            None
+        }
+    }
+
+    fn integer(&self) -> BigInt {
+        BigInt::from_str_radix(&self.digits, self.radix.base()).unwrap_or_default()
+    }
+}
+
+/// A problem with the raw text of a numeric literal, the
+/// index is the byte offset into that text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NumberError {
+    /// No digits where at least one was required
+    /// ```js
+    /// 0x
+    /// 1e
+    /// ```
+    MissingDigits { index: usize },
+    /// A character that is not a digit in the literal's radix
+    /// ```js
+    /// 0b12
+    /// ```
+    InvalidDigit { index: usize, ch: char },
+    /// A `_` that is not between two digits
+    /// ```js
+    /// 1__0
+    /// 1_
+    /// 0x_f
+    /// 1_.5
+    /// ```
+    InvalidSeparator { index: usize },
+    /// A bigint with a fraction, exponent or a legacy octal form
+    /// ```js
+    /// 1.5n
+    /// 1e3n
+    /// 017n
+    /// ```
+    InvalidBigInt,
+}
+
+impl NumberError {
+    /// The byte offset of the problem, if it has one
+    pub fn index(&self) -> Option<usize> {
+        match self {
+            NumberError::MissingDigits { index }
+            | NumberError::InvalidDigit { index, .. }
+            | NumberError::InvalidSeparator { index } => Some(*index),
+            NumberError::InvalidBigInt => None,
+        }
+    }
+}
+
+impl fmt::Display for NumberError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NumberError::MissingDigits { index } => write!(f, "missing digits at {}", index),
+            NumberError::InvalidDigit { index, ch } => {
+                write!(f, "invalid digit {:?} at {}", ch, index)
+            }
+            NumberError::InvalidSeparator { index } => {
+                write!(f, "numeric separator not allowed at {}", index)
+            }
+            NumberError::InvalidBigInt => {
+                f.write_str("bigint literals must be integers without a legacy octal form")
+            }
+        }
+    }
+}
+
+impl std::error::Error for NumberError {}
+
+struct Scanned {
+    /// The byte offset after the last consumed character
+    end: usize,
+    separated: bool,
+}
+
+struct ScannedTail {
+    end: usize,
+    separated: bool,
+    fraction_digits: bool,
+}
+
+/// Consume digits in `base` starting at `start`. When separators are
+/// allowed each `_` must sit between two digits.
+fn scan_digits(
+    text: &str,
+    start: usize,
+    base: u32,
+    separators: bool,
+) -> Result<Scanned, NumberError> {
+    let bytes = text.as_bytes();
+    let mut end = start;
+    let mut separated = false;
+    while end < bytes.len() {
+        let b = bytes[end];
+        if b == b'_' && separators {
+            let after_digit = end > start && is_digit(bytes[end - 1], base);
+            let before_digit = bytes.get(end + 1).map(|b| is_digit(*b, base));
+            if !after_digit || before_digit != Some(true) {
+                return Err(NumberError::InvalidSeparator { index: end });
+            }
+            separated = true;
+        } else if !is_digit(b, base) {
+            break;
+        }
+        end += 1;
+    }
+    if base != 10 && end == start {
+        return Err(NumberError::MissingDigits { index: start });
+    }
+    Ok(Scanned { end, separated })
+}
+
+/// Consume an optional `.fraction` and `e+exponent` after the
+/// integer part of a decimal
+fn scan_decimal_tail(
+    text: &str,
+    start: usize,
+    separators: bool,
+) -> Result<ScannedTail, NumberError> {
+    let bytes = text.as_bytes();
+    let mut end = start;
+    let mut separated = false;
+    let mut fraction_digits = false;
+    if bytes.get(end) == Some(&b'.') {
+        let fraction = scan_digits(text, end + 1, 10, separators)?;
+        fraction_digits = fraction.end > end + 1;
+        separated |= fraction.separated;
+        end = fraction.end;
+    }
+    if matches!(bytes.get(end), Some(b'e' | b'E')) {
+        let mut exp_start = end + 1;
+        if matches!(bytes.get(exp_start), Some(b'+' | b'-')) {
+            exp_start += 1;
+        }
+        let exponent = scan_digits(text, exp_start, 10, separators)?;
+        if exponent.end == exp_start {
+            return Err(NumberError::MissingDigits { index: exp_start });
+        }
+        separated |= exponent.separated;
+        end = exponent.end;
+    }
+    Ok(ScannedTail {
+        end,
+        separated,
+        fraction_digits,
+    })
+}
+
+fn is_digit(b: u8, base: u32) -> bool {
+    (b as char).is_digit(base)
+}
+
+fn invalid_digit(text: &str, index: usize) -> NumberError {
+    match text[index..].chars().next() {
+        Some('_') => NumberError::InvalidSeparator { index },
+        Some(ch) => NumberError::InvalidDigit { index, ch },
+        None => NumberError::MissingDigits { index },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn radix(raw: &str) -> Radix {
+        NumericLit::parse(raw).unwrap().radix()
+    }
+
+    fn value(raw: &str) -> f64 {
+        NumericLit::parse(raw).unwrap().value()
+    }
+
+    fn error(raw: &str) -> NumberError {
+        NumericLit::parse(raw).unwrap_err()
+    }
+
+    #[test]
+    fn classification() {
+        assert_eq!(radix("0"), Radix::Decimal);
+        assert_eq!(radix("10"), Radix::Decimal);
+        assert_eq!(radix(".5"), Radix::Decimal);
+        assert_eq!(radix("1e3"), Radix::Decimal);
+        assert_eq!(radix("0.5"), Radix::Decimal);
+        assert_eq!(radix("0xFf"), Radix::Hex);
+        assert_eq!(radix("0O17"), Radix::Octal);
+        assert_eq!(radix("0b101"), Radix::Binary);
+        assert_eq!(radix("017"), Radix::LegacyOctal);
+        assert_eq!(radix("089"), Radix::NonOctalDecimal);
+        assert_eq!(radix("08.5"), Radix::NonOctalDecimal);
+        assert!(Radix::LegacyOctal.is_legacy());
+        assert!(Radix::NonOctalDecimal.is_legacy());
+        assert!(!Radix::Octal.is_legacy());
+    }
+
+    #[test]
+    fn separators_and_big_ints() {
+        let lit = NumericLit::parse("1_000n").unwrap();
+        assert!(lit.is_big_int());
+        assert!(lit.has_separators());
+        assert_eq!(lit.big_int(), Some(BigInt::from(1000u64)));
+        assert!(!NumericLit::parse("1000").unwrap().has_separators());
+        assert_eq!(NumericLit::parse("1.5").unwrap().big_int(), None);
+        assert_eq!(
+            NumericLit::parse("0xffff_ffff_ffff_ffff_ffffn")
+                .unwrap()
+                .big_int()
+                .unwrap()
+                .to_str_radix(16),
+            "ffffffffffffffffffff"
+        );
+    }
+
+    #[test]
+    fn values() {
+        assert_eq!(value("1_000.5e-3"), 1.0005);
+        assert_eq!(value("5."), 5.0);
+        assert_eq!(value("1.e2"), 100.0);
+        assert_eq!(value(".25"), 0.25);
+        assert_eq!(value("0xff"), 255.0);
+        assert_eq!(value("0o7_7"), 63.0);
+        assert_eq!(value("0b1010"), 10.0);
+        assert_eq!(value("017"), 15.0);
+        assert_eq!(value("019"), 19.0);
+        assert_eq!(value("10n"), 10.0);
+        assert_eq!(value("0x1fffffffffffff"), 9007199254740991.0);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(error(""), NumberError::MissingDigits { index: 0 });
+        assert_eq!(error("0x"), NumberError::MissingDigits { index: 2 });
+        assert_eq!(error("1e"), NumberError::MissingDigits { index: 2 });
+        assert_eq!(error("1e+"), NumberError::MissingDigits { index: 3 });
+        assert_eq!(error("."), NumberError::MissingDigits { index: 0 });
+        assert_eq!(
+            error("0b12"),
+            NumberError::InvalidDigit { index: 3, ch: '2' }
+        );
+        assert_eq!(
+            error("0o78"),
+            NumberError::InvalidDigit { index: 3, ch: '8' }
+        );
+        assert_eq!(error("0o8"), NumberError::MissingDigits { index: 2 });
+        assert_eq!(error("1__0"), NumberError::InvalidSeparator { index: 1 });
+        assert_eq!(error("1_"), NumberError::InvalidSeparator { index: 1 });
+        assert_eq!(error("0x_f"), NumberError::InvalidSeparator { index: 2 });
+        assert_eq!(error("1_.5"), NumberError::InvalidSeparator { index: 1 });
+        assert_eq!(error("0_1"), NumberError::InvalidSeparator { index: 1 });
+        assert_eq!(error("01_7"), NumberError::InvalidSeparator { index: 2 });
+        assert_eq!(error("1.5n"), NumberError::InvalidBigInt);
+        assert_eq!(error("1e3n"), NumberError::InvalidBigInt);
+        assert_eq!(error("017n"), NumberError::InvalidBigInt);
+        assert_eq!(error("1.5n").index(), None);
+        assert_eq!(error("0b12").index(), Some(3));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::expr::{Expr, Prop};
+use crate::expr::{AssignLeft, Expr, Prop, PropKey, PropValue};
 use crate::{Ident, IntoAllocated};
 
 #[cfg(feature = "serde")]
@@ -14,6 +14,8 @@ pub enum Pat<T> {
     Array(Vec<Option<ArrayPatPart<T>>>),
     RestElement(Box<Pat<T>>),
     Assign(AssignPat<T>),
+    /// Source that could not be parsed as a pattern
+    Invalid(T),
 }
 
 impl<T> IntoAllocated for Pat<T>
@@ -34,6 +36,7 @@ where
             ),
             Pat::RestElement(inner) => Pat::RestElement(inner.into_allocated()),
             Pat::Assign(inner) => Pat::Assign(inner.into_allocated()),
+            Pat::Invalid(text) => Pat::Invalid(text.to_string()),
         }
     }
 }
@@ -42,6 +45,57 @@ impl<T> Pat<T> {
     pub fn ident_from(inner: T) -> Self {
         Self::Ident(Ident { name: inner })
     }
+
+    /// Every identifier this pattern would bind, in source order
+    /// ```js
+    /// let {a, b: [c, ...d], e = 0} = thing;
+    /// ```
+    /// binds `a`, `c`, `d` and `e`
+    pub fn bound_names(&self) -> Vec<&Ident<T>> {
+        let mut names = Vec::new();
+        self.collect_bound_names(&mut names);
+        names
+    }
+
+    fn collect_bound_names<'a>(&'a self, names: &mut Vec<&'a Ident<T>>) {
+        match self {
+            Pat::Ident(ident) => names.push(ident),
+            Pat::Obj(parts) => {
+                for part in parts {
+                    match part {
+                        ObjPatPart::Assign(prop) => match &prop.value {
+                            PropValue::Pat(pat) => pat.collect_bound_names(names),
+                            PropValue::Expr(Expr::Ident(ident)) => names.push(ident),
+                            PropValue::Expr(Expr::Assign(assign)) => {
+                                if let AssignLeft::Pat(pat) = &assign.left {
+                                    pat.collect_bound_names(names)
+                                }
+                            }
+                            PropValue::Expr(_) => {}
+                            PropValue::None => match &prop.key {
+                                PropKey::Pat(pat) => pat.collect_bound_names(names),
+                                PropKey::Expr(Expr::Ident(ident)) => names.push(ident),
+                                _ => {}
+                            },
+                        },
+                        ObjPatPart::Rest(pat) => pat.collect_bound_names(names),
+                    }
+                }
+            }
+            Pat::Array(parts) => {
+                for part in parts.iter().flatten() {
+                    match part {
+                        ArrayPatPart::Pat(pat) => pat.collect_bound_names(names),
+                        ArrayPatPart::Expr(Expr::Ident(ident)) => names.push(ident),
+                        ArrayPatPart::Expr(_) => {}
+                    }
+                }
+            }
+            Pat::RestElement(pat) => pat.collect_bound_names(names),
+            Pat::Assign(assign) => assign.left.collect_bound_names(names),
+            Pat::Invalid(_) => {}
+        }
+    }
 }
 
 #[derive(PartialEq, Debug, Clone)]
@@ -0,0 +1,713 @@
+//! A structured view of regular expression literals
+//!
+//! The pattern of a `RegEx` is parsed into a tree of `Term`s, every node
+//! carries the `Span` of bytes it covers in the pattern text. The
+//! `Display` implementations print the tree back out as pattern text.
+//! ```js
+//! /(?<year>\d{4})-(?<month>\d{2})/u
+//! ```
+//! Without the `u` or `v` flags the web compatibility (Annex B) grammar
+//! is used, so things like `/]/` and `/\8/` are accepted.
+use std::fmt;
+
+mod parser;
+mod printer;
+
+/// A parsed regular expression literal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Regex {
+    pub pattern: Disjunction,
+    pub flags: Flags,
+}
+
+/// Parse the pattern and flags of a regular expression literal
+pub fn parse(pattern: &str, flags: &str) -> Result<Regex, RegexError> {
+    let flags = Flags::parse(flags).map_err(RegexError::Flags)?;
+    let pattern = parser::Parser::new(pattern, &flags)
+        .parse()
+        .map_err(RegexError::Pattern)?;
+    Ok(Regex { pattern, flags })
+}
+
+/// A byte range in the pattern text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+}
+
+/// The flags following the closing `/`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Flags {
+    /// `d`
+    pub has_indices: bool,
+    /// `g`
+    pub global: bool,
+    /// `i`
+    pub ignore_case: bool,
+    /// `m`
+    pub multiline: bool,
+    /// `s`
+    pub dot_all: bool,
+    /// `u`
+    pub unicode: bool,
+    /// `v`
+    pub unicode_sets: bool,
+    /// `y`
+    pub sticky: bool,
+}
+
+impl Flags {
+    /// Validate a set of flags, each of `dgimsuvy` may appear once
+    /// and `u` cannot be combined with `v`
+    pub fn parse(flags: &str) -> Result<Self, FlagsError> {
+        let mut ret = Self::default();
+        for (index, ch) in flags.char_indices() {
+            let flag = match ch {
+                'd' => &mut ret.has_indices,
+                'g' => &mut ret.global,
+                'i' => &mut ret.ignore_case,
+                'm' => &mut ret.multiline,
+                's' => &mut ret.dot_all,
+                'u' => &mut ret.unicode,
+                'v' => &mut ret.unicode_sets,
+                'y' => &mut ret.sticky,
+                _ => {
+                    return Err(FlagsError {
+                        kind: FlagsErrorKind::Invalid(ch),
+                        index,
+                    })
+                }
+            };
+            if *flag {
+                return Err(FlagsError {
+                    kind: FlagsErrorKind::Duplicate(ch),
+                    index,
+                });
+            }
+            *flag = true;
+            if ret.unicode && ret.unicode_sets {
+                return Err(FlagsError {
+                    kind: FlagsErrorKind::UnicodeAndUnicodeSets,
+                    index,
+                });
+            }
+        }
+        Ok(ret)
+    }
+
+    /// If either `u` or `v` is set, enabling the stricter grammar
+    pub fn is_unicode_mode(&self) -> bool {
+        self.unicode || self.unicode_sets
+    }
+}
+
+/// One or more `Alternative`s separated by `|`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disjunction {
+    pub alternatives: Vec<Alternative>,
+    pub span: Span,
+}
+
+/// A sequence of `Term`s, possibly empty
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alternative {
+    pub terms: Vec<Term>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Term {
+    /// `^`, `$`, `\b` or `\B`
+    Assertion(Assertion),
+    /// `(?=a)`, `(?!a)`, `(?<=a)` or `(?<!a)`
+    Lookaround(Lookaround),
+    /// A term followed by a quantifier
+    /// ```js
+    /// /a*/
+    /// /(ab){2,3}?/
+    /// ```
+    Quantified(Quantified),
+    /// A single character, possibly escaped
+    Char(Char),
+    /// `.`
+    Dot(Span),
+    /// `\d`, `\w`, `\s`, `\p{...}` or their negations
+    ClassEscape(ClassEscape),
+    /// `[a-z]`
+    Class(CharClass),
+    /// `(a)` or `(?<name>a)`
+    Group(Group),
+    /// `(?:a)` or `(?i-m:a)`
+    NonCapturing(NonCapturingGroup),
+    /// `\1` or `\k<name>`
+    Backreference(Backreference),
+}
+
+impl Term {
+    pub fn span(&self) -> Span {
+        match self {
+            Term::Assertion(inner) => inner.span,
+            Term::Lookaround(inner) => inner.span,
+            Term::Quantified(inner) => inner.span,
+            Term::Char(inner) => inner.span,
+            Term::Dot(span) => *span,
+            Term::ClassEscape(inner) => inner.span,
+            Term::Class(inner) => inner.span,
+            Term::Group(inner) => inner.span,
+            Term::NonCapturing(inner) => inner.span,
+            Term::Backreference(inner) => inner.span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assertion {
+    pub kind: AssertionKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssertionKind {
+    /// `^`
+    Start,
+    /// `$`
+    End,
+    /// `\b`
+    WordBoundary,
+    /// `\B`
+    NotWordBoundary,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lookaround {
+    pub kind: LookaroundKind,
+    pub body: Disjunction,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LookaroundKind {
+    /// `(?=`
+    Lookahead,
+    /// `(?!`
+    NegativeLookahead,
+    /// `(?<=`
+    Lookbehind,
+    /// `(?<!`
+    NegativeLookbehind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quantified {
+    pub term: Box<Term>,
+    pub quantifier: Quantifier,
+    pub span: Span,
+}
+
+/// How many times the preceding term may repeat
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quantifier {
+    pub min: u32,
+    /// `None` for no upper bound
+    pub max: Option<u32>,
+    /// `false` when followed by `?`
+    pub greedy: bool,
+    /// If written with braces, `{0,}` instead of `*`
+    pub braced: bool,
+    pub span: Span,
+}
+
+/// A character that matches itself
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Char {
+    /// The code point, or code unit for a lone surrogate
+    pub value: u32,
+    pub kind: CharKind,
+    pub span: Span,
+}
+
+/// The way a `Char` was written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharKind {
+    /// `a`
+    Literal,
+    /// `\.`
+    Identity,
+    /// `\n`, `\r`, `\t`, `\v`, `\f` or `\b` in a class
+    Named,
+    /// `\cJ`, holds the letter
+    Control(char),
+    /// `\0`
+    Null,
+    /// `\x0A`
+    Hex,
+    /// `\u000A`
+    Unicode,
+    /// `😀` in unicode mode
+    SurrogatePair,
+    /// `\u{A}`
+    UnicodeBraced,
+    /// `\12`, holds the number of digits
+    LegacyOctal(u8),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassEscape {
+    pub kind: ClassEscapeKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClassEscapeKind {
+    /// `\d`
+    Digit,
+    /// `\D`
+    NotDigit,
+    /// `\w`
+    Word,
+    /// `\W`
+    NotWord,
+    /// `\s`
+    Space,
+    /// `\S`
+    NotSpace,
+    /// `\p{...}` or `\P{...}`
+    Property(UnicodeProperty),
+}
+
+/// A unicode property escape
+/// ```js
+/// /\p{Script=Greek}/u
+/// /\P{Lu}/u
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnicodeProperty {
+    /// `\P`
+    pub negated: bool,
+    pub name: String,
+    pub value: Option<String>,
+}
+
+/// A character class
+/// ```js
+/// /[^a-z\d]/
+/// /[\p{L}--[a-z]]/v
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharClass {
+    pub negated: bool,
+    pub kind: ClassSetKind,
+    pub items: Vec<ClassItem>,
+    pub span: Span,
+}
+
+/// How the items of a class combine, only `Union` is possible
+/// without the `v` flag
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClassSetKind {
+    /// `[ab]`
+    Union,
+    /// `[a&&b]`
+    Intersection,
+    /// `[a--b]`
+    Subtraction,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClassItem {
+    Char(Char),
+    /// `a-z`
+    Range(ClassRange),
+    /// `\d`
+    Escape(ClassEscape),
+    /// A nested class, only with the `v` flag
+    Class(CharClass),
+    /// `\q{abc|d}`, only with the `v` flag
+    Strings(ClassStrings),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassRange {
+    pub start: Char,
+    pub end: Char,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassStrings {
+    pub strings: Vec<Vec<Char>>,
+    pub span: Span,
+}
+
+/// A capturing group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Group {
+    /// The 1 based index used by numbered backreferences
+    pub index: u32,
+    pub name: Option<String>,
+    pub body: Disjunction,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonCapturingGroup {
+    pub modifiers: Option<Modifiers>,
+    pub body: Disjunction,
+    pub span: Span,
+}
+
+/// The flags of a modifier group, `Some(true)` when
+/// enabled and `Some(false)` when disabled
+/// ```js
+/// /(?i-s:a.)/
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub ignore_case: Option<bool>,
+    pub multiline: Option<bool>,
+    pub dot_all: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backreference {
+    pub target: BackreferenceTarget,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackreferenceTarget {
+    /// `\1`
+    Index(u32),
+    /// `\k<name>`
+    Name(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegexError {
+    Pattern(PatternError),
+    Flags(FlagsError),
+}
+
+impl fmt::Display for RegexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegexError::Pattern(inner) => inner.fmt(f),
+            RegexError::Flags(inner) => inner.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for RegexError {}
+
+/// A problem with the flags, the index is a byte offset into the flags
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlagsError {
+    pub kind: FlagsErrorKind,
+    pub index: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlagsErrorKind {
+    Invalid(char),
+    Duplicate(char),
+    /// `u` and `v` together
+    UnicodeAndUnicodeSets,
+}
+
+impl fmt::Display for FlagsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            FlagsErrorKind::Invalid(ch) => write!(f, "invalid regular expression flag {:?}", ch)?,
+            FlagsErrorKind::Duplicate(ch) => {
+                write!(f, "duplicate regular expression flag {:?}", ch)?
+            }
+            FlagsErrorKind::UnicodeAndUnicodeSets => {
+                f.write_str("the u and v flags cannot be combined")?
+            }
+        }
+        write!(f, " at {}", self.index)
+    }
+}
+
+impl std::error::Error for FlagsError {}
+
+/// A problem with the pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternError {
+    pub kind: PatternErrorKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternErrorKind {
+    /// A `\` at the very end of the pattern
+    UnterminatedEscape,
+    /// An escape that is not allowed in unicode mode or in this position
+    InvalidEscape,
+    /// A `(` without a matching `)`
+    UnterminatedGroup,
+    /// A `)` without a matching `(`
+    UnmatchedParen,
+    /// `(?` not followed by a known group kind
+    InvalidGroup,
+    /// A `[` without a matching `]`
+    UnterminatedClass,
+    /// A lone `{`, `}` or `]` in unicode mode
+    LoneBracket,
+    /// A quantifier without anything before it
+    NothingToRepeat,
+    /// A quantifier following a lookbehind or, in unicode
+    /// mode, a lookahead
+    InvalidQuantifierTarget,
+    /// `{3,2}`
+    QuantifierOutOfOrder,
+    /// `[z-a]`
+    RangeOutOfOrder,
+    /// A range with a class escape as one of its ends in unicode mode
+    InvalidClassRange,
+    /// `(?<1>a)`
+    InvalidGroupName,
+    /// The same group name twice in one alternative
+    DuplicateGroupName(String),
+    /// `\k<name>` without a group with that name
+    UnknownGroupName(String),
+    /// `\3` with fewer than 3 groups in unicode mode
+    InvalidBackreference,
+    /// `\p{...}` that is not well formed
+    InvalidPropertyEscape,
+    /// `(?ii:a)` or `(?-:a)`
+    InvalidModifiers,
+    /// Mixing `&&`, `--` and unions in one `v` flag class
+    MixedClassSetOperators,
+    /// A character that must be escaped in a `v` flag class
+    /// such as `(` or a reserved double punctuator like `&&`
+    InvalidClassSetCharacter,
+    /// `\q{...}` outside of a `v` flag class
+    InvalidClassStrings,
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match &self.kind {
+            PatternErrorKind::UnterminatedEscape => "\\ at end of pattern".to_string(),
+            PatternErrorKind::InvalidEscape => "invalid escape".to_string(),
+            PatternErrorKind::UnterminatedGroup => "unterminated group".to_string(),
+            PatternErrorKind::UnmatchedParen => "unmatched ')'".to_string(),
+            PatternErrorKind::InvalidGroup => "invalid group".to_string(),
+            PatternErrorKind::UnterminatedClass => "unterminated character class".to_string(),
+            PatternErrorKind::LoneBracket => "lone quantifier brackets".to_string(),
+            PatternErrorKind::NothingToRepeat => "nothing to repeat".to_string(),
+            PatternErrorKind::InvalidQuantifierTarget => {
+                "this assertion cannot be quantified".to_string()
+            }
+            PatternErrorKind::QuantifierOutOfOrder => {
+                "numbers out of order in {} quantifier".to_string()
+            }
+            PatternErrorKind::RangeOutOfOrder => {
+                "range out of order in character class".to_string()
+            }
+            PatternErrorKind::InvalidClassRange => "invalid character class range".to_string(),
+            PatternErrorKind::InvalidGroupName => "invalid capture group name".to_string(),
+            PatternErrorKind::DuplicateGroupName(name) => {
+                format!("duplicate capture group name {:?}", name)
+            }
+            PatternErrorKind::UnknownGroupName(name) => {
+                format!("undefined capture group name {:?}", name)
+            }
+            PatternErrorKind::InvalidBackreference => "invalid backreference".to_string(),
+            PatternErrorKind::InvalidPropertyEscape => {
+                "invalid unicode property escape".to_string()
+            }
+            PatternErrorKind::InvalidModifiers => "invalid group modifiers".to_string(),
+            PatternErrorKind::MixedClassSetOperators => {
+                "class set operators cannot be mixed".to_string()
+            }
+            PatternErrorKind::InvalidClassSetCharacter => {
+                "invalid character in character class".to_string()
+            }
+            PatternErrorKind::InvalidClassStrings => {
+                "\\q is only allowed in a v flag character class".to_string()
+            }
+        };
+        write!(f, "{} at {}", msg, self.span.start)
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(pattern: &str, flags: &str) {
+        let regex = parse(pattern, flags).unwrap();
+        assert_eq!(regex.to_string(), format!("/{}/{}", pattern, flags));
+    }
+
+    fn pattern_error(pattern: &str, flags: &str) -> PatternErrorKind {
+        match parse(pattern, flags) {
+            Err(RegexError::Pattern(e)) => e.kind,
+            other => panic!(
+                "expected a pattern error for /{}/{}: {:?}",
+                pattern, flags, other
+            ),
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        round_trip("", "");
+        round_trip("a|b|", "");
+        round_trip("^ab*c+?d{2}e{1,}f{1,3}?$", "g");
+        round_trip("\\bx\\B", "");
+        round_trip("(?<year>\\d{4})-(?<month>\\d{2})\\k<year>", "u");
+        round_trip("(?:a)(?=b)(?!c)(?<=d)(?<!e)", "");
+        round_trip("(a)\\1", "");
+        round_trip("[^a-z\\d\\-]", "i");
+        round_trip("\\x41\\u0042\\u{1F600}\\cJ\\0", "u");
+        round_trip("\\p{Script=Greek}\\P{L}", "u");
+        round_trip("[\\p{L}--[a-z]]", "v");
+        round_trip("[[a-z]&&[aeiou]]", "v");
+        round_trip("[\\q{abc|d}]", "v");
+        round_trip("(?i:a)(?-m:b)(?s-i:c)", "");
+        round_trip(".", "dgimsy");
+    }
+
+    #[test]
+    fn annex_b() {
+        round_trip("]", "");
+        round_trip("{", "");
+        round_trip("\\8", "");
+        round_trip("\\k", "");
+        round_trip("(?=a)*", "");
+        round_trip("\\c", "");
+        assert_eq!(pattern_error("]", "u"), PatternErrorKind::LoneBracket);
+        assert_eq!(
+            pattern_error("\\8", "u"),
+            PatternErrorKind::InvalidBackreference
+        );
+        assert_eq!(pattern_error("\\a", "u"), PatternErrorKind::InvalidEscape);
+        assert_eq!(
+            pattern_error("(?=a)*", "u"),
+            PatternErrorKind::InvalidQuantifierTarget
+        );
+    }
+
+    #[test]
+    fn pattern_errors() {
+        assert_eq!(
+            pattern_error("a\\", ""),
+            PatternErrorKind::UnterminatedEscape
+        );
+        assert_eq!(pattern_error("(a", ""), PatternErrorKind::UnterminatedGroup);
+        assert_eq!(pattern_error("a)", ""), PatternErrorKind::UnmatchedParen);
+        assert_eq!(pattern_error("(?a)", ""), PatternErrorKind::InvalidGroup);
+        assert_eq!(pattern_error("[a", ""), PatternErrorKind::UnterminatedClass);
+        assert_eq!(pattern_error("*", ""), PatternErrorKind::NothingToRepeat);
+        assert_eq!(
+            pattern_error("a{3,2}", ""),
+            PatternErrorKind::QuantifierOutOfOrder
+        );
+        assert_eq!(
+            pattern_error("[z-a]", ""),
+            PatternErrorKind::RangeOutOfOrder
+        );
+        assert_eq!(
+            pattern_error("[\\d-z]", "u"),
+            PatternErrorKind::InvalidClassRange
+        );
+        assert_eq!(
+            pattern_error("(?<1>a)", ""),
+            PatternErrorKind::InvalidGroupName
+        );
+        assert_eq!(
+            pattern_error("(?<a>x)(?<a>y)", ""),
+            PatternErrorKind::DuplicateGroupName("a".to_string())
+        );
+        assert_eq!(
+            pattern_error("(?<a>x)\\k<b>", ""),
+            PatternErrorKind::UnknownGroupName("b".to_string())
+        );
+        assert_eq!(
+            pattern_error("(a)\\2", "u"),
+            PatternErrorKind::InvalidBackreference
+        );
+        assert_eq!(
+            pattern_error("\\p{Nope", "u"),
+            PatternErrorKind::InvalidPropertyEscape
+        );
+        assert_eq!(
+            pattern_error("(?ii:a)", ""),
+            PatternErrorKind::InvalidModifiers
+        );
+        assert_eq!(
+            pattern_error("(?-:a)", ""),
+            PatternErrorKind::InvalidModifiers
+        );
+        assert_eq!(
+            pattern_error("[a&&b--c]", "v"),
+            PatternErrorKind::MixedClassSetOperators
+        );
+        assert_eq!(
+            pattern_error("[(]", "v"),
+            PatternErrorKind::InvalidClassSetCharacter
+        );
+        assert_eq!(
+            pattern_error("[\\q{a}]", "u"),
+            PatternErrorKind::InvalidClassStrings
+        );
+    }
+
+    #[test]
+    fn duplicate_names_in_separate_alternatives() {
+        round_trip("(?<a>x)|(?<a>y)", "");
+    }
+
+    #[test]
+    fn error_spans() {
+        let err = match parse("ab)", "") {
+            Err(RegexError::Pattern(e)) => e,
+            other => panic!("{:?}", other),
+        };
+        assert_eq!(err.span, Span::new(2, 3));
+    }
+
+    #[test]
+    fn flags() {
+        let flags = Flags::parse("gimsuy").unwrap();
+        assert!(flags.global && flags.ignore_case && flags.multiline);
+        assert!(flags.dot_all && flags.unicode && flags.sticky);
+        assert!(!flags.has_indices && !flags.unicode_sets);
+        assert!(flags.is_unicode_mode());
+        assert_eq!(Flags::parse("yg").unwrap().to_string(), "gy");
+        assert_eq!(
+            Flags::parse("gx"),
+            Err(FlagsError {
+                kind: FlagsErrorKind::Invalid('x'),
+                index: 1,
+            })
+        );
+        assert_eq!(
+            Flags::parse("gig"),
+            Err(FlagsError {
+                kind: FlagsErrorKind::Duplicate('g'),
+                index: 2,
+            })
+        );
+        assert_eq!(
+            Flags::parse("uv"),
+            Err(FlagsError {
+                kind: FlagsErrorKind::UnicodeAndUnicodeSets,
+                index: 1,
+            })
+        );
+        assert!(matches!(parse("a", "q"), Err(RegexError::Flags(_))));
+    }
+}
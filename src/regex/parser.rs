@@ -0,0 +1,942 @@
+use super::*;
+
+type Res<T> = Result<T, PatternError>;
+
+/// Characters that start a `v` flag class set operator when doubled
+const DOUBLE_PUNCTUATORS: &str = "&!#$%*+,.:;<=>?@^`~";
+
+pub(super) struct Parser<'a> {
+    src: &'a str,
+    chars: Vec<(usize, char)>,
+    pos: usize,
+    /// `u` or `v`
+    unicode: bool,
+    /// `v`
+    sets: bool,
+    /// If the pattern has any named groups, which turns `\k` into a
+    /// backreference outside of unicode mode
+    named_groups: bool,
+    group_count: u32,
+    next_group: u32,
+    /// The names of the groups that could have matched along with
+    /// the current position, used to find duplicates
+    scope: Vec<String>,
+    names: Vec<String>,
+    named_refs: Vec<(String, Span)>,
+}
+
+impl<'a> Parser<'a> {
+    pub(super) fn new(src: &'a str, flags: &Flags) -> Self {
+        let mut ret = Self {
+            src,
+            chars: src.char_indices().collect(),
+            pos: 0,
+            unicode: flags.is_unicode_mode(),
+            sets: flags.unicode_sets,
+            named_groups: false,
+            group_count: 0,
+            next_group: 0,
+            scope: Vec::new(),
+            names: Vec::new(),
+            named_refs: Vec::new(),
+        };
+        ret.count_groups();
+        ret
+    }
+
+    pub(super) fn parse(mut self) -> Res<Disjunction> {
+        let body = self.parse_disjunction()?;
+        if self.peek().is_some() {
+            let start = self.offset();
+            self.bump();
+            return Err(self.error(PatternErrorKind::UnmatchedParen, start));
+        }
+        for (name, span) in &self.named_refs {
+            if !self.names.contains(name) {
+                return Err(PatternError {
+                    kind: PatternErrorKind::UnknownGroupName(name.clone()),
+                    span: *span,
+                });
+            }
+        }
+        Ok(body)
+    }
+
+    /// Backreferences can refer to groups that come after them so
+    /// the groups need to be counted before parsing
+    fn count_groups(&mut self) {
+        let mut class_depth = 0;
+        let mut i = 0;
+        while i < self.chars.len() {
+            match self.chars[i].1 {
+                '\\' => i += 1,
+                '[' if class_depth == 0 || self.sets => class_depth += 1,
+                ']' if class_depth > 0 => class_depth -= 1,
+                '(' if class_depth == 0 => {
+                    if self.char_at(i + 1) != Some('?') {
+                        self.group_count += 1;
+                    } else if self.char_at(i + 2) == Some('<')
+                        && !matches!(self.char_at(i + 3), Some('=' | '!'))
+                    {
+                        self.group_count += 1;
+                        self.named_groups = true;
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    fn parse_disjunction(&mut self) -> Res<Disjunction> {
+        let start = self.offset();
+        let scope_len = self.scope.len();
+        let mut added = Vec::new();
+        let mut alternatives = vec![self.parse_alternative()?];
+        while self.eat('|') {
+            // names in different alternatives never match together
+            added.extend(self.scope.drain(scope_len..));
+            alternatives.push(self.parse_alternative()?);
+        }
+        added.extend(self.scope.drain(scope_len..));
+        self.scope.extend(added);
+        Ok(Disjunction {
+            alternatives,
+            span: Span::new(start, self.offset()),
+        })
+    }
+
+    fn parse_alternative(&mut self) -> Res<Alternative> {
+        let start = self.offset();
+        let mut terms = Vec::new();
+        while let Some(ch) = self.peek() {
+            if ch == '|' || ch == ')' {
+                break;
+            }
+            terms.push(self.parse_term()?);
+        }
+        Ok(Alternative {
+            terms,
+            span: Span::new(start, self.offset()),
+        })
+    }
+
+    fn parse_term(&mut self) -> Res<Term> {
+        let start = self.offset();
+        let assertion = match (self.peek(), self.char_at(self.pos + 1)) {
+            (Some('^'), _) => Some((AssertionKind::Start, 1)),
+            (Some('$'), _) => Some((AssertionKind::End, 1)),
+            (Some('\\'), Some('b')) => Some((AssertionKind::WordBoundary, 2)),
+            (Some('\\'), Some('B')) => Some((AssertionKind::NotWordBoundary, 2)),
+            _ => None,
+        };
+        if let Some((kind, len)) = assertion {
+            self.pos += len;
+            return Ok(Term::Assertion(Assertion {
+                kind,
+                span: Span::new(start, self.offset()),
+            }));
+        }
+        if self.at_quantifier() {
+            let end = self.offset() + 1;
+            return Err(PatternError {
+                kind: PatternErrorKind::NothingToRepeat,
+                span: Span::new(start, end),
+            });
+        }
+        let term = self.parse_atom()?;
+        if let Term::Lookaround(lookaround) = &term {
+            let lookahead = matches!(
+                lookaround.kind,
+                LookaroundKind::Lookahead | LookaroundKind::NegativeLookahead
+            );
+            if self.at_quantifier() && (self.unicode || !lookahead) {
+                return Err(self.error(PatternErrorKind::InvalidQuantifierTarget, start));
+            }
+        }
+        self.parse_quantified(term, start)
+    }
+
+    fn parse_quantified(&mut self, term: Term, start: usize) -> Res<Term> {
+        let quantifier_start = self.offset();
+        let (min, max, braced) = match self.peek() {
+            Some('*') => (0, None, false),
+            Some('+') => (1, None, false),
+            Some('?') => (0, Some(1), false),
+            Some('{') => match self.scan_braces() {
+                Some((min, max, len)) => {
+                    self.pos += len - 1;
+                    (min, max, true)
+                }
+                None if self.unicode => {
+                    self.bump();
+                    return Err(self.error(PatternErrorKind::LoneBracket, quantifier_start));
+                }
+                None => return Ok(term),
+            },
+            _ => return Ok(term),
+        };
+        self.bump();
+        let greedy = !self.eat('?');
+        if let Some(max) = max {
+            if max < min {
+                return Err(self.error(PatternErrorKind::QuantifierOutOfOrder, quantifier_start));
+            }
+        }
+        let end = self.offset();
+        Ok(Term::Quantified(Quantified {
+            term: Box::new(term),
+            quantifier: Quantifier {
+                min,
+                max,
+                greedy,
+                braced,
+                span: Span::new(quantifier_start, end),
+            },
+            span: Span::new(start, end),
+        }))
+    }
+
+    fn at_quantifier(&self) -> bool {
+        match self.peek() {
+            Some('*' | '+' | '?') => true,
+            Some('{') => self.scan_braces().is_some(),
+            _ => false,
+        }
+    }
+
+    /// Look for `{n}`, `{n,}` or `{n,m}` without consuming anything,
+    /// returning the bounds and the number of characters
+    fn scan_braces(&self) -> Option<(u32, Option<u32>, usize)> {
+        let mut i = self.pos + 1;
+        let digits = |i: &mut usize| {
+            let start = *i;
+            let mut value: u32 = 0;
+            while let Some(digit) = self.char_at(*i).and_then(|ch| ch.to_digit(10)) {
+                value = value.saturating_mul(10).saturating_add(digit);
+                *i += 1;
+            }
+            if *i == start {
+                None
+            } else {
+                Some(value)
+            }
+        };
+        let min = digits(&mut i)?;
+        let max = if self.char_at(i) == Some(',') {
+            i += 1;
+            digits(&mut i)
+        } else {
+            Some(min)
+        };
+        if self.char_at(i) != Some('}') {
+            return None;
+        }
+        Some((min, max, i + 1 - self.pos))
+    }
+
+    fn parse_atom(&mut self) -> Res<Term> {
+        let start = self.offset();
+        let ch = match self.bump() {
+            Some(ch) => ch,
+            None => return Err(self.error(PatternErrorKind::UnterminatedGroup, start)),
+        };
+        match ch {
+            '.' => Ok(Term::Dot(Span::new(start, self.offset()))),
+            '[' => Ok(Term::Class(self.parse_class(start)?)),
+            '(' => self.parse_group(start),
+            '\\' => self.parse_atom_escape(start),
+            ']' | '{' | '}' if self.unicode => {
+                Err(self.error(PatternErrorKind::LoneBracket, start))
+            }
+            ch => Ok(Term::Char(self.char(ch as u32, CharKind::Literal, start))),
+        }
+    }
+
+    fn parse_group(&mut self, start: usize) -> Res<Term> {
+        if !self.eat('?') {
+            self.next_group += 1;
+            let index = self.next_group;
+            let body = self.parse_group_body(start)?;
+            return Ok(Term::Group(Group {
+                index,
+                name: None,
+                body,
+                span: Span::new(start, self.offset()),
+            }));
+        }
+        let lookaround = match (self.peek(), self.char_at(self.pos + 1)) {
+            (Some('='), _) => Some((LookaroundKind::Lookahead, 1)),
+            (Some('!'), _) => Some((LookaroundKind::NegativeLookahead, 1)),
+            (Some('<'), Some('=')) => Some((LookaroundKind::Lookbehind, 2)),
+            (Some('<'), Some('!')) => Some((LookaroundKind::NegativeLookbehind, 2)),
+            _ => None,
+        };
+        if let Some((kind, len)) = lookaround {
+            self.pos += len;
+            let body = self.parse_group_body(start)?;
+            return Ok(Term::Lookaround(Lookaround {
+                kind,
+                body,
+                span: Span::new(start, self.offset()),
+            }));
+        }
+        if self.eat('<') {
+            let name_start = self.offset();
+            let name = self.parse_group_name(name_start)?;
+            if self.scope.contains(&name) {
+                return Err(self.error(PatternErrorKind::DuplicateGroupName(name), name_start));
+            }
+            self.scope.push(name.clone());
+            self.names.push(name.clone());
+            self.next_group += 1;
+            let index = self.next_group;
+            let body = self.parse_group_body(start)?;
+            return Ok(Term::Group(Group {
+                index,
+                name: Some(name),
+                body,
+                span: Span::new(start, self.offset()),
+            }));
+        }
+        let modifiers = if self.eat(':') {
+            None
+        } else if matches!(self.peek(), Some('i' | 'm' | 's' | '-')) {
+            Some(self.parse_modifiers()?)
+        } else {
+            self.bump();
+            return Err(self.error(PatternErrorKind::InvalidGroup, start));
+        };
+        let body = self.parse_group_body(start)?;
+        Ok(Term::NonCapturing(NonCapturingGroup {
+            modifiers,
+            body,
+            span: Span::new(start, self.offset()),
+        }))
+    }
+
+    fn parse_group_body(&mut self, start: usize) -> Res<Disjunction> {
+        let body = self.parse_disjunction()?;
+        if !self.eat(')') {
+            return Err(self.error(PatternErrorKind::UnterminatedGroup, start));
+        }
+        Ok(body)
+    }
+
+    /// The flags between `(?` and `:` of a modifier group
+    fn parse_modifiers(&mut self) -> Res<Modifiers> {
+        let start = self.offset();
+        let mut ret = Modifiers::default();
+        let mut enable = true;
+        let mut any = false;
+        loop {
+            let flag = match self.bump() {
+                Some(':') => break,
+                Some('-') if enable => {
+                    enable = false;
+                    continue;
+                }
+                Some('i') => &mut ret.ignore_case,
+                Some('m') => &mut ret.multiline,
+                Some('s') => &mut ret.dot_all,
+                _ => return Err(self.error(PatternErrorKind::InvalidModifiers, start)),
+            };
+            if flag.is_some() {
+                return Err(self.error(PatternErrorKind::InvalidModifiers, start));
+            }
+            *flag = Some(enable);
+            any = true;
+        }
+        if !any {
+            return Err(self.error(PatternErrorKind::InvalidModifiers, start));
+        }
+        Ok(ret)
+    }
+
+    /// The identifier after `<` up to and including the `>`, the
+    /// ID_Start and ID_Continue checks are approximated with
+    /// `char::is_alphabetic` and `char::is_alphanumeric`
+    fn parse_group_name(&mut self, start: usize) -> Res<String> {
+        let mut name = String::new();
+        loop {
+            let ch = match self.bump() {
+                Some('>') if !name.is_empty() => break,
+                Some('\\') if self.eat('u') => self
+                    .parse_unicode_escape(true)?
+                    .and_then(|(value, _)| char::from_u32(value)),
+                ch => ch,
+            };
+            let valid = match ch {
+                Some('$' | '_') => true,
+                Some('\u{200C}' | '\u{200D}') => !name.is_empty(),
+                Some(ch) if name.is_empty() => ch.is_alphabetic(),
+                Some(ch) => ch.is_alphanumeric(),
+                None => false,
+            };
+            match ch {
+                Some(ch) if valid => name.push(ch),
+                _ => return Err(self.error(PatternErrorKind::InvalidGroupName, start)),
+            }
+        }
+        Ok(name)
+    }
+
+    /// An escape outside of a class, the `\` has been consumed
+    fn parse_atom_escape(&mut self, start: usize) -> Res<Term> {
+        let ch = match self.peek() {
+            Some(ch) => ch,
+            None => return Err(self.error(PatternErrorKind::UnterminatedEscape, start)),
+        };
+        match ch {
+            'd' | 'D' | 'w' | 'W' | 's' | 'S' => {
+                Ok(Term::ClassEscape(self.parse_class_escape_kind(start)?))
+            }
+            'p' | 'P' if self.unicode => {
+                Ok(Term::ClassEscape(self.parse_class_escape_kind(start)?))
+            }
+            '1'..='9' => {
+                let digits_start = self.pos;
+                let mut value: u32 = 0;
+                while let Some(digit) = self.peek().and_then(|ch| ch.to_digit(10)) {
+                    value = value.saturating_mul(10).saturating_add(digit);
+                    self.bump();
+                }
+                if value <= self.group_count {
+                    return Ok(Term::Backreference(Backreference {
+                        target: BackreferenceTarget::Index(value),
+                        span: Span::new(start, self.offset()),
+                    }));
+                }
+                if self.unicode {
+                    return Err(self.error(PatternErrorKind::InvalidBackreference, start));
+                }
+                // web compatibility, this is an octal or identity escape
+                self.pos = digits_start;
+                Ok(Term::Char(self.parse_char_escape(start, false)?))
+            }
+            'k' if self.unicode || self.named_groups => {
+                self.bump();
+                if !self.eat('<') {
+                    return Err(self.error(PatternErrorKind::InvalidGroupName, start));
+                }
+                let name = self.parse_group_name(start)?;
+                let span = Span::new(start, self.offset());
+                self.named_refs.push((name.clone(), span));
+                Ok(Term::Backreference(Backreference {
+                    target: BackreferenceTarget::Name(name),
+                    span,
+                }))
+            }
+            _ => Ok(Term::Char(self.parse_char_escape(start, false)?)),
+        }
+    }
+
+    /// `\d`, `\w`, `\s`, `\p{...}` or their negations, the `\` has been consumed
+    fn parse_class_escape_kind(&mut self, start: usize) -> Res<ClassEscape> {
+        let kind = match self.bump() {
+            Some('d') => ClassEscapeKind::Digit,
+            Some('D') => ClassEscapeKind::NotDigit,
+            Some('w') => ClassEscapeKind::Word,
+            Some('W') => ClassEscapeKind::NotWord,
+            Some('s') => ClassEscapeKind::Space,
+            Some('S') => ClassEscapeKind::NotSpace,
+            Some(ch @ ('p' | 'P')) => {
+                ClassEscapeKind::Property(self.parse_property(ch == 'P', start)?)
+            }
+            _ => return Err(self.error(PatternErrorKind::InvalidEscape, start)),
+        };
+        Ok(ClassEscape {
+            kind,
+            span: Span::new(start, self.offset()),
+        })
+    }
+
+    /// The `{Name}` or `{Name=Value}` of a property escape. Only the
+    /// names allowed before `=` and properties of strings are checked,
+    /// the values are not validated against the unicode database
+    fn parse_property(&mut self, negated: bool, start: usize) -> Res<UnicodeProperty> {
+        let invalid = |p: &mut Self| p.error(PatternErrorKind::InvalidPropertyEscape, start);
+        if !self.eat('{') {
+            return Err(invalid(self));
+        }
+        let name = self.property_word();
+        let value = if self.eat('=') {
+            Some(self.property_word())
+        } else {
+            None
+        };
+        if !self.eat('}') || name.is_empty() || value.as_ref().is_some_and(|v| v.is_empty()) {
+            return Err(invalid(self));
+        }
+        if value.is_some()
+            && !matches!(
+                name.as_str(),
+                "General_Category" | "gc" | "Script" | "sc" | "Script_Extensions" | "scx"
+            )
+        {
+            return Err(invalid(self));
+        }
+        let of_strings = matches!(
+            name.as_str(),
+            "Basic_Emoji"
+                | "Emoji_Keycap_Sequence"
+                | "RGI_Emoji_Modifier_Sequence"
+                | "RGI_Emoji_Flag_Sequence"
+                | "RGI_Emoji_Tag_Sequence"
+                | "RGI_Emoji_ZWJ_Sequence"
+                | "RGI_Emoji"
+        );
+        if of_strings && (negated || !self.sets) {
+            return Err(invalid(self));
+        }
+        Ok(UnicodeProperty {
+            negated,
+            name,
+            value,
+        })
+    }
+
+    fn property_word(&mut self) -> String {
+        let mut ret = String::new();
+        while let Some(ch) = self.peek() {
+            if !(ch.is_ascii_alphanumeric() || ch == '_') {
+                break;
+            }
+            ret.push(ch);
+            self.bump();
+        }
+        ret
+    }
+
+    /// An escape that produces a single character, the `\` has been consumed
+    fn parse_char_escape(&mut self, start: usize, in_class: bool) -> Res<Char> {
+        let ch = match self.bump() {
+            Some(ch) => ch,
+            None => return Err(self.error(PatternErrorKind::UnterminatedEscape, start)),
+        };
+        let invalid = |p: &mut Self| p.error(PatternErrorKind::InvalidEscape, start);
+        let (value, kind) = match ch {
+            'f' => (0x0C, CharKind::Named),
+            'n' => (0x0A, CharKind::Named),
+            'r' => (0x0D, CharKind::Named),
+            't' => (0x09, CharKind::Named),
+            'v' => (0x0B, CharKind::Named),
+            'b' if in_class => (0x08, CharKind::Named),
+            'c' => match self.peek() {
+                Some(letter) if letter.is_ascii_alphabetic() => {
+                    self.bump();
+                    (letter as u32 % 32, CharKind::Control(letter))
+                }
+                Some(letter)
+                    if in_class && !self.unicode && (letter.is_ascii_digit() || letter == '_') =>
+                {
+                    self.bump();
+                    (letter as u32 % 32, CharKind::Control(letter))
+                }
+                _ if !self.unicode => {
+                    // web compatibility, the `\` matches itself and
+                    // the `c` is left to be parsed as a literal
+                    self.pos -= 1;
+                    ('\\' as u32, CharKind::Literal)
+                }
+                _ => return Err(invalid(self)),
+            },
+            '0' if !matches!(self.peek(), Some('0'..='9')) => (0, CharKind::Null),
+            '0'..='7' if !self.unicode => {
+                let mut value = ch.to_digit(8).unwrap_or(0);
+                // `\377` is the largest three digit escape
+                let max_len = if value <= 3 { 3 } else { 2 };
+                let mut len = 1;
+                while len < max_len {
+                    match self.peek().and_then(|ch| ch.to_digit(8)) {
+                        Some(digit) => value = value * 8 + digit,
+                        None => break,
+                    }
+                    self.bump();
+                    len += 1;
+                }
+                (value, CharKind::LegacyOctal(len))
+            }
+            'x' => match self.hex_digits(2) {
+                Some(value) => (value, CharKind::Hex),
+                None if self.unicode => return Err(invalid(self)),
+                None => ('x' as u32, CharKind::Identity),
+            },
+            'u' => match self.parse_unicode_escape(self.unicode)? {
+                Some(escape) => escape,
+                None if self.unicode => return Err(invalid(self)),
+                None => ('u' as u32, CharKind::Identity),
+            },
+            ch => {
+                let valid = if self.unicode {
+                    "^$\\.*+?()[]{}|/".contains(ch)
+                        || (in_class && ch == '-')
+                        || (in_class && self.sets && "&-!#%,:;<=>@`~".contains(ch))
+                } else {
+                    !(ch == 'k' && self.named_groups)
+                };
+                if !valid {
+                    return Err(invalid(self));
+                }
+                (ch as u32, CharKind::Identity)
+            }
+        };
+        Ok(self.char(value, kind, start))
+    }
+
+    /// The rest of a `\u` escape, `None` if it is not well formed.
+    /// With `unicode` the braced form is allowed and surrogate pairs
+    /// written as two escapes are combined
+    fn parse_unicode_escape(&mut self, unicode: bool) -> Res<Option<(u32, CharKind)>> {
+        let start = self.pos;
+        if unicode && self.eat('{') {
+            let mut value: u32 = 0;
+            let mut any = false;
+            while let Some(digit) = self.peek().and_then(|ch| ch.to_digit(16)) {
+                value = value.saturating_mul(16).saturating_add(digit);
+                any = true;
+                self.bump();
+            }
+            if !any || !self.eat('}') || value > 0x10FFFF {
+                self.pos = start;
+                return Ok(None);
+            }
+            return Ok(Some((value, CharKind::UnicodeBraced)));
+        }
+        let lead = match self.hex_digits(4) {
+            Some(lead) => lead,
+            None => return Ok(None),
+        };
+        if unicode && (0xD800..0xDC00).contains(&lead) {
+            let trail_start = self.pos;
+            if self.eat('\\') && self.eat('u') {
+                if let Some(trail) = self.hex_digits(4) {
+                    if (0xDC00..0xE000).contains(&trail) {
+                        let value = 0x10000 + ((lead - 0xD800) << 10) + (trail - 0xDC00);
+                        return Ok(Some((value, CharKind::SurrogatePair)));
+                    }
+                }
+            }
+            self.pos = trail_start;
+        }
+        Ok(Some((lead, CharKind::Unicode)))
+    }
+
+    fn hex_digits(&mut self, count: usize) -> Option<u32> {
+        let start = self.pos;
+        let mut value = 0;
+        for _ in 0..count {
+            match self.peek().and_then(|ch| ch.to_digit(16)) {
+                Some(digit) => value = value * 16 + digit,
+                None => {
+                    self.pos = start;
+                    return None;
+                }
+            }
+            self.bump();
+        }
+        Some(value)
+    }
+
+    /// A class after its `[` has been consumed
+    fn parse_class(&mut self, start: usize) -> Res<CharClass> {
+        let negated = self.eat('^');
+        if self.sets {
+            return self.parse_class_set(start, negated);
+        }
+        let mut items = Vec::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error(PatternErrorKind::UnterminatedClass, start)),
+                Some(']') => {
+                    self.bump();
+                    break;
+                }
+                _ => {}
+            }
+            let item_start = self.offset();
+            let first = self.parse_class_atom()?;
+            let is_range =
+                self.peek() == Some('-') && !matches!(self.char_at(self.pos + 1), None | Some(']'));
+            if !is_range {
+                items.push(first);
+                continue;
+            }
+            let dash_start = self.offset();
+            self.bump();
+            let dash = self.char('-' as u32, CharKind::Literal, dash_start);
+            let second = self.parse_class_atom()?;
+            match (first, second) {
+                (ClassItem::Char(first), ClassItem::Char(second)) => {
+                    if first.value > second.value {
+                        return Err(self.error(PatternErrorKind::RangeOutOfOrder, item_start));
+                    }
+                    items.push(ClassItem::Range(ClassRange {
+                        start: first,
+                        end: second,
+                        span: Span::new(item_start, self.offset()),
+                    }));
+                }
+                _ if self.unicode => {
+                    return Err(self.error(PatternErrorKind::InvalidClassRange, item_start))
+                }
+                // web compatibility, `[\d-z]` is `\d`, `-` and `z`
+                (first, second) => {
+                    items.push(first);
+                    items.push(ClassItem::Char(dash));
+                    items.push(second);
+                }
+            }
+        }
+        Ok(CharClass {
+            negated,
+            kind: ClassSetKind::Union,
+            items,
+            span: Span::new(start, self.offset()),
+        })
+    }
+
+    fn parse_class_atom(&mut self) -> Res<ClassItem> {
+        let start = self.offset();
+        match self.bump() {
+            Some('\\') => self.parse_class_escape(start),
+            Some(ch) => Ok(ClassItem::Char(self.char(
+                ch as u32,
+                CharKind::Literal,
+                start,
+            ))),
+            None => Err(self.error(PatternErrorKind::UnterminatedClass, start)),
+        }
+    }
+
+    /// An escape inside of a class, the `\` has been consumed
+    fn parse_class_escape(&mut self, start: usize) -> Res<ClassItem> {
+        match self.peek() {
+            None => Err(self.error(PatternErrorKind::UnterminatedEscape, start)),
+            Some('d' | 'D' | 'w' | 'W' | 's' | 'S') => {
+                Ok(ClassItem::Escape(self.parse_class_escape_kind(start)?))
+            }
+            Some('p' | 'P') if self.unicode => {
+                Ok(ClassItem::Escape(self.parse_class_escape_kind(start)?))
+            }
+            Some('q') if self.char_at(self.pos + 1) == Some('{') => {
+                if !self.sets {
+                    self.pos += 2;
+                    return Err(self.error(PatternErrorKind::InvalidClassStrings, start));
+                }
+                self.pos += 2;
+                self.parse_class_strings(start)
+            }
+            Some(_) => Ok(ClassItem::Char(self.parse_char_escape(start, true)?)),
+        }
+    }
+
+    /// A `v` flag class after its `[` and any `^` have been consumed
+    fn parse_class_set(&mut self, start: usize, negated: bool) -> Res<CharClass> {
+        let mut items = Vec::new();
+        let mut kind = ClassSetKind::Union;
+        if !self.eat(']') {
+            let first = self.parse_class_set_operand(start, true)?;
+            let first_is_range = matches!(first, ClassItem::Range(_));
+            items.push(first);
+            if self.at_str("&&") {
+                kind = ClassSetKind::Intersection;
+            } else if self.at_str("--") {
+                kind = ClassSetKind::Subtraction;
+            }
+            if kind != ClassSetKind::Union && first_is_range {
+                return Err(self.error(PatternErrorKind::MixedClassSetOperators, start));
+            }
+            loop {
+                if self.eat(']') {
+                    break;
+                }
+                let operator_start = self.offset();
+                let operator = match kind {
+                    ClassSetKind::Union => None,
+                    ClassSetKind::Intersection => Some("&&"),
+                    ClassSetKind::Subtraction => Some("--"),
+                };
+                let mixed = match operator {
+                    Some(operator) => !self.eat_str(operator),
+                    None => self.at_str("&&") || self.at_str("--"),
+                };
+                if mixed {
+                    self.pos += 2;
+                    return Err(
+                        self.error(PatternErrorKind::MixedClassSetOperators, operator_start)
+                    );
+                }
+                if operator.is_some() && self.peek() == Some('&') {
+                    self.bump();
+                    return Err(
+                        self.error(PatternErrorKind::InvalidClassSetCharacter, operator_start)
+                    );
+                }
+                items.push(self.parse_class_set_operand(start, operator.is_none())?);
+            }
+        }
+        Ok(CharClass {
+            negated,
+            kind,
+            items,
+            span: Span::new(start, self.offset()),
+        })
+    }
+
+    fn parse_class_set_operand(&mut self, class_start: usize, allow_range: bool) -> Res<ClassItem> {
+        let start = self.offset();
+        let first = match self.peek() {
+            None => return Err(self.error(PatternErrorKind::UnterminatedClass, class_start)),
+            Some('[') => {
+                self.bump();
+                return Ok(ClassItem::Class(self.parse_class(start)?));
+            }
+            Some('\\') => {
+                self.bump();
+                self.parse_class_escape(start)?
+            }
+            Some(_) => ClassItem::Char(self.parse_class_set_char()?),
+        };
+        let first = match first {
+            ClassItem::Char(first) if allow_range && self.peek() == Some('-') => first,
+            item => return Ok(item),
+        };
+        if self.char_at(self.pos + 1) == Some('-') {
+            return Ok(ClassItem::Char(first));
+        }
+        self.bump();
+        let end = match self.peek() {
+            Some('\\') => {
+                let escape_start = self.offset();
+                self.bump();
+                self.parse_class_escape(escape_start)?
+            }
+            Some('[') | None => {
+                return Err(self.error(PatternErrorKind::InvalidClassRange, start));
+            }
+            Some(_) => ClassItem::Char(self.parse_class_set_char()?),
+        };
+        let end = match end {
+            ClassItem::Char(end) => end,
+            _ => return Err(self.error(PatternErrorKind::InvalidClassRange, start)),
+        };
+        if first.value > end.value {
+            return Err(self.error(PatternErrorKind::RangeOutOfOrder, start));
+        }
+        Ok(ClassItem::Range(ClassRange {
+            start: first,
+            end,
+            span: Span::new(start, self.offset()),
+        }))
+    }
+
+    /// An unescaped character in a `v` flag class
+    fn parse_class_set_char(&mut self) -> Res<Char> {
+        let start = self.offset();
+        let ch = match self.bump() {
+            Some(ch) => ch,
+            None => return Err(self.error(PatternErrorKind::UnterminatedClass, start)),
+        };
+        let reserved = DOUBLE_PUNCTUATORS.contains(ch) && self.peek() == Some(ch);
+        if reserved || "()[]{}/-\\|".contains(ch) {
+            return Err(self.error(PatternErrorKind::InvalidClassSetCharacter, start));
+        }
+        Ok(self.char(ch as u32, CharKind::Literal, start))
+    }
+
+    /// The strings of `\q{...}` after the `{` has been consumed
+    fn parse_class_strings(&mut self, start: usize) -> Res<ClassItem> {
+        let mut strings = vec![Vec::new()];
+        loop {
+            match self.peek() {
+                None => return Err(self.error(PatternErrorKind::UnterminatedClass, start)),
+                Some('}') => {
+                    self.bump();
+                    break;
+                }
+                Some('|') => {
+                    self.bump();
+                    strings.push(Vec::new());
+                    continue;
+                }
+                _ => {}
+            }
+            let ch = if self.peek() == Some('\\') {
+                let escape_start = self.offset();
+                self.bump();
+                self.parse_char_escape(escape_start, true)?
+            } else {
+                self.parse_class_set_char()?
+            };
+            if let Some(last) = strings.last_mut() {
+                last.push(ch);
+            }
+        }
+        Ok(ClassItem::Strings(ClassStrings {
+            strings,
+            span: Span::new(start, self.offset()),
+        }))
+    }
+
+    fn char(&self, value: u32, kind: CharKind, start: usize) -> Char {
+        Char {
+            value,
+            kind,
+            span: Span::new(start, self.offset()),
+        }
+    }
+
+    fn error(&self, kind: PatternErrorKind, start: usize) -> PatternError {
+        PatternError {
+            kind,
+            span: Span::new(start, self.offset()),
+        }
+    }
+
+    /// The byte offset of the next character
+    fn offset(&self) -> usize {
+        self.chars
+            .get(self.pos)
+            .map(|(index, _)| *index)
+            .unwrap_or(self.src.len())
+    }
+
+    fn char_at(&self, pos: usize) -> Option<char> {
+        self.chars.get(pos).map(|(_, ch)| *ch)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.char_at(self.pos)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.pos += 1;
+        Some(ch)
+    }
+
+    fn eat(&mut self, ch: char) -> bool {
+        if self.peek() == Some(ch) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn at_str(&self, s: &str) -> bool {
+        s.chars()
+            .enumerate()
+            .all(|(i, ch)| self.char_at(self.pos + i) == Some(ch))
+    }
+
+    fn eat_str(&mut self, s: &str) -> bool {
+        if self.at_str(s) {
+            self.pos += s.chars().count();
+            true
+        } else {
+            false
+        }
+    }
+}
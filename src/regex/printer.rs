@@ -0,0 +1,261 @@
+//! Printing a regex tree back out as pattern text, characters keep
+//! the notation they were written in though hex digits are always
+//! upper case and flags are always in `dgimsuvy` order
+use super::*;
+
+impl fmt::Display for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "/{}/{}", self.pattern, self.flags)
+    }
+}
+
+impl fmt::Display for Flags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flags = [
+            (self.has_indices, 'd'),
+            (self.global, 'g'),
+            (self.ignore_case, 'i'),
+            (self.multiline, 'm'),
+            (self.dot_all, 's'),
+            (self.unicode, 'u'),
+            (self.unicode_sets, 'v'),
+            (self.sticky, 'y'),
+        ];
+        for (set, flag) in flags {
+            if set {
+                write!(f, "{}", flag)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Disjunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, alternative) in self.alternatives.iter().enumerate() {
+            if i > 0 {
+                f.write_str("|")?;
+            }
+            write!(f, "{}", alternative)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Alternative {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for term in &self.terms {
+            write!(f, "{}", term)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Term::Assertion(inner) => inner.fmt(f),
+            Term::Lookaround(inner) => inner.fmt(f),
+            Term::Quantified(inner) => write!(f, "{}{}", inner.term, inner.quantifier),
+            Term::Char(inner) => inner.fmt(f),
+            Term::Dot(_) => f.write_str("."),
+            Term::ClassEscape(inner) => inner.fmt(f),
+            Term::Class(inner) => inner.fmt(f),
+            Term::Group(inner) => inner.fmt(f),
+            Term::NonCapturing(inner) => inner.fmt(f),
+            Term::Backreference(inner) => inner.fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for Assertion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self.kind {
+            AssertionKind::Start => "^",
+            AssertionKind::End => "$",
+            AssertionKind::WordBoundary => "\\b",
+            AssertionKind::NotWordBoundary => "\\B",
+        })
+    }
+}
+
+impl fmt::Display for Lookaround {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let open = match self.kind {
+            LookaroundKind::Lookahead => "(?=",
+            LookaroundKind::NegativeLookahead => "(?!",
+            LookaroundKind::Lookbehind => "(?<=",
+            LookaroundKind::NegativeLookbehind => "(?<!",
+        };
+        write!(f, "{}{})", open, self.body)
+    }
+}
+
+impl fmt::Display for Quantifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.braced, self.min, self.max) {
+            (false, 0, None) => f.write_str("*")?,
+            (false, 1, None) => f.write_str("+")?,
+            (false, 0, Some(1)) => f.write_str("?")?,
+            (_, min, None) => write!(f, "{{{},}}", min)?,
+            (_, min, Some(max)) if min == max => write!(f, "{{{}}}", min)?,
+            (_, min, Some(max)) => write!(f, "{{{},{}}}", min, max)?,
+        }
+        if !self.greedy {
+            f.write_str("?")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Char {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ch = char::from_u32(self.value);
+        match (self.kind, ch) {
+            (CharKind::Literal, Some(ch)) => write!(f, "{}", ch),
+            (CharKind::Identity, Some(ch)) => write!(f, "\\{}", ch),
+            (CharKind::Named, _) => f.write_str(match self.value {
+                0x08 => "\\b",
+                0x09 => "\\t",
+                0x0A => "\\n",
+                0x0B => "\\v",
+                0x0C => "\\f",
+                _ => "\\r",
+            }),
+            (CharKind::Control(letter), _) => write!(f, "\\c{}", letter),
+            (CharKind::Null, _) => f.write_str("\\0"),
+            (CharKind::Hex, _) => write!(f, "\\x{:02X}", self.value),
+            (CharKind::SurrogatePair, _) => {
+                let offset = self.value - 0x10000;
+                write!(
+                    f,
+                    "\\u{:04X}\\u{:04X}",
+                    0xD800 + (offset >> 10),
+                    0xDC00 + (offset & 0x3FF)
+                )
+            }
+            (CharKind::UnicodeBraced, _) => write!(f, "\\u{{{:X}}}", self.value),
+            (CharKind::LegacyOctal(len), _) => {
+                write!(f, "\\{:0width$o}", self.value, width = len as usize)
+            }
+            // lone surrogates can only be written as an escape
+            (CharKind::Unicode, _) | (_, None) => write!(f, "\\u{:04X}", self.value),
+        }
+    }
+}
+
+impl fmt::Display for ClassEscape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ClassEscapeKind::Digit => f.write_str("\\d"),
+            ClassEscapeKind::NotDigit => f.write_str("\\D"),
+            ClassEscapeKind::Word => f.write_str("\\w"),
+            ClassEscapeKind::NotWord => f.write_str("\\W"),
+            ClassEscapeKind::Space => f.write_str("\\s"),
+            ClassEscapeKind::NotSpace => f.write_str("\\S"),
+            ClassEscapeKind::Property(property) => property.fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for UnicodeProperty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let letter = if self.negated { 'P' } else { 'p' };
+        match &self.value {
+            Some(value) => write!(f, "\\{}{{{}={}}}", letter, self.name, value),
+            None => write!(f, "\\{}{{{}}}", letter, self.name),
+        }
+    }
+}
+
+impl fmt::Display for CharClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.negated { "[^" } else { "[" })?;
+        let separator = match self.kind {
+            ClassSetKind::Union => "",
+            ClassSetKind::Intersection => "&&",
+            ClassSetKind::Subtraction => "--",
+        };
+        for (i, item) in self.items.iter().enumerate() {
+            if i > 0 {
+                f.write_str(separator)?;
+            }
+            write!(f, "{}", item)?;
+        }
+        f.write_str("]")
+    }
+}
+
+impl fmt::Display for ClassItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClassItem::Char(inner) => inner.fmt(f),
+            ClassItem::Range(inner) => write!(f, "{}-{}", inner.start, inner.end),
+            ClassItem::Escape(inner) => inner.fmt(f),
+            ClassItem::Class(inner) => inner.fmt(f),
+            ClassItem::Strings(inner) => {
+                f.write_str("\\q{")?;
+                for (i, string) in inner.strings.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str("|")?;
+                    }
+                    for ch in string {
+                        write!(f, "{}", ch)?;
+                    }
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+impl fmt::Display for Group {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "(?<{}>{})", name, self.body),
+            None => write!(f, "({})", self.body),
+        }
+    }
+}
+
+impl fmt::Display for NonCapturingGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.modifiers {
+            Some(modifiers) => write!(f, "(?{}:{})", modifiers, self.body),
+            None => write!(f, "(?:{})", self.body),
+        }
+    }
+}
+
+impl fmt::Display for Modifiers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flags = [
+            (self.ignore_case, 'i'),
+            (self.multiline, 'm'),
+            (self.dot_all, 's'),
+        ];
+        for (value, flag) in flags {
+            if value == Some(true) {
+                write!(f, "{}", flag)?;
+            }
+        }
+        if flags.iter().any(|(value, _)| *value == Some(false)) {
+            f.write_str("-")?;
+        }
+        for (value, flag) in flags {
+            if value == Some(false) {
+                write!(f, "{}", flag)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Backreference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.target {
+            BackreferenceTarget::Index(index) => write!(f, "\\{}", index),
+            BackreferenceTarget::Name(name) => write!(f, "\\k<{}>", name),
+        }
+    }
+}
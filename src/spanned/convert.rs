@@ -5,15 +5,18 @@ use std::borrow::Cow;
 
 use crate::spanned::{
     decl::{
-        Alias, Decl, DefaultExportDeclValue, DefaultImportSpec, ExportSpecifier, ImportSpecifier,
-        ModExport, ModExportSpecifier, ModImport, NamedExportDecl, NamespaceImportSpec,
-        NormalImportSpec, VarDecl,
+        Alias, Decl, DefaultExportDeclValue, DefaultImportSpec, EnumDecl, EnumMember,
+        EnumMemberName, ExportSpecifier, ImportAttribute, ImportAttributeKey, ImportAttributes,
+        ImportEqualsDecl, ImportSpecifier, InterfaceDecl, ModExport, ModExportSpecifier, ModImport,
+        ModuleDecl, ModuleExportName, ModuleName, ModuleRef, NamedExportDecl, NamespaceImportSpec,
+        NamespaceName, NormalImportSpec, TypeAliasDecl, VarDecl,
     },
     expr::{
         ArrowFuncBody, ArrowFuncExpr, AssignExpr, AssignLeft, BinaryExpr, CallExpr,
-        ConditionalExpr, Expr, Lit, LogicalExpr, MemberExpr, MetaProp, NewExpr, ObjProp, Prop,
-        PropInitKey, PropKey, PropMethod, PropValue, RegEx, SequenceExprEntry, StringLit,
-        TaggedTemplateExpr, TemplateElement, TemplateLit, UnaryExpr, UpdateExpr, YieldExpr,
+        ConditionalExpr, Expr, ImportCall, Lit, LogicalExpr, MemberExpr, MetaProp, NewExpr,
+        ObjProp, PrivateName, Prop, PropInitKey, PropKey, PropMethod, PropValue, RegEx,
+        SequenceExprEntry, StringLit, TaggedTemplateExpr, TemplateElement, TemplateLit, UnaryExpr,
+        UpdateExpr, YieldExpr,
     },
     pat::{ArrayElement, ArrayPat, ArrayPatPart, AssignPat, ObjPat, ObjPatPart, Pat},
     stmt::{
@@ -22,10 +25,39 @@ use crate::spanned::{
         WithStmt,
     },
     tokens::{AssignOp, BinaryOp, LogicalOp, UnaryOp, UpdateOp},
-    Class, ClassBody, Dir, Func, FuncArg, FuncArgEntry, FuncBody, Ident, Program, ProgramPart,
-    Slice, VarKind,
+    ts::{
+        Accessibility, TsKeyword, TsPredicateParam, TsType, TsTypeAnnotation, TsTypeArgs,
+        TsTypeMember, TsTypeOperator, TsTypeParam, TsTypeParams, TsTypeRef,
+    },
+    Class, ClassBody, ClassField, ClassMember, Decorator, Dir, Func, FuncArg, FuncArgEntry,
+    FuncBody, Ident, ListEntry, Program, ProgramPart, Slice, SourceFile, VarKind,
 };
 
+/// The parameters of an optional `<...>` list
+fn type_param_list<T>(params: Option<TsTypeParams<T>>) -> Vec<crate::ts::TsTypeParam<T>> {
+    params
+        .map(|p| p.params.into_iter().map(|e| e.item.into()).collect())
+        .unwrap_or_default()
+}
+
+/// The arguments of an optional `<...>` list
+fn type_arg_list<T>(args: Option<TsTypeArgs<T>>) -> Vec<crate::ts::TsType<T>> {
+    args.map(|a| a.args.into_iter().map(|e| e.item.into()).collect())
+        .unwrap_or_default()
+}
+
+/// The type after the `:` of an optional annotation
+fn annotation<T>(annotation: Option<TsTypeAnnotation<T>>) -> Option<crate::ts::TsType<T>> {
+    annotation.map(|a| a.type_annotation.into())
+}
+
+fn namespace_name<T>(name: NamespaceName<T>) -> Vec<crate::Ident<T>> {
+    std::iter::once(name.first)
+        .chain(name.rest.into_iter().map(|(_, i)| i))
+        .map(From::from)
+        .collect()
+}
+
 mod decl {
     use super::*;
 
@@ -44,6 +76,40 @@ mod decl {
                 Decl::Export { export, .. } => {
                     crate::decl::Decl::Export(Box::new((*export).into()))
                 }
+                Decl::ImportEquals { import, .. } => {
+                    crate::decl::Decl::ImportEquals(Box::new((*import).into()))
+                }
+                Decl::Declare { decl, .. } => crate::decl::Decl::Declare(Box::new((*decl).into())),
+                Decl::Module { module, .. } => {
+                    crate::decl::Decl::Module(Box::new((*module).into()))
+                }
+                Decl::Interface(inner) => crate::decl::Decl::Interface(Box::new((*inner).into())),
+                Decl::TypeAlias { decl, .. } => {
+                    crate::decl::Decl::TypeAlias(Box::new((*decl).into()))
+                }
+                Decl::Enum(inner) => crate::decl::Decl::Enum(Box::new((*inner).into())),
+                Decl::Invalid(inner) => crate::decl::Decl::Invalid(inner.source),
+            }
+        }
+    }
+
+    impl<T> From<ModuleDecl<T>> for crate::decl::ModuleDecl<T> {
+        fn from(other: ModuleDecl<T>) -> Self {
+            Self {
+                name: other.name.into(),
+                body: other
+                    .body
+                    .map(|b| b.parts.into_iter().map(From::from).collect()),
+            }
+        }
+    }
+
+    impl<T> From<ModuleName<T>> for crate::decl::ModuleName<T> {
+        fn from(other: ModuleName<T>) -> Self {
+            match other {
+                ModuleName::Namespace(inner) => Self::Namespace(namespace_name(inner)),
+                ModuleName::Lit(inner) => Self::Lit(inner.into()),
+                ModuleName::Global(_) => Self::Global,
             }
         }
     }
@@ -52,20 +118,103 @@ mod decl {
         fn from(other: VarDecl<T>) -> Self {
             Self {
                 id: other.id.into(),
+                type_annotation: annotation(other.type_annotation),
                 init: other.init.map(From::from),
             }
         }
     }
 
+    impl<T> From<InterfaceDecl<T>> for crate::decl::InterfaceDecl<T> {
+        fn from(other: InterfaceDecl<T>) -> Self {
+            Self {
+                id: other.id.into(),
+                type_params: type_param_list(other.type_params),
+                extends: other
+                    .extends
+                    .map(|e| e.types.into_iter().map(|e| e.item.into()).collect())
+                    .unwrap_or_default(),
+                body: other
+                    .body
+                    .members
+                    .into_iter()
+                    .map(|e| e.member.into())
+                    .collect(),
+            }
+        }
+    }
+
+    impl<T> From<TypeAliasDecl<T>> for crate::decl::TypeAliasDecl<T> {
+        fn from(other: TypeAliasDecl<T>) -> Self {
+            Self {
+                id: other.id.into(),
+                type_params: type_param_list(other.type_params),
+                type_annotation: other.type_annotation.into(),
+            }
+        }
+    }
+
+    impl<T> From<EnumDecl<T>> for crate::decl::EnumDecl<T> {
+        fn from(other: EnumDecl<T>) -> Self {
+            Self {
+                is_const: other.keyword_const.is_some(),
+                id: other.id.into(),
+                members: other.members.into_iter().map(|e| e.item.into()).collect(),
+            }
+        }
+    }
+
+    impl<T> From<EnumMember<T>> for crate::decl::EnumMember<T> {
+        fn from(other: EnumMember<T>) -> Self {
+            Self {
+                id: match other.id {
+                    EnumMemberName::Ident(inner) => {
+                        crate::decl::EnumMemberName::Ident(inner.into())
+                    }
+                    EnumMemberName::String(inner) => {
+                        crate::decl::EnumMemberName::String(inner.into())
+                    }
+                },
+                init: other.init.map(|(_, e)| e.into()),
+            }
+        }
+    }
+
     impl<T> From<ModImport<T>> for crate::decl::ModImport<T> {
         fn from(other: ModImport<T>) -> Self {
             Self {
+                is_type_only: other.keyword_type.is_some(),
                 source: other.source.into(),
                 specifiers: other
                     .specifiers
                     .into_iter()
                     .map(|e| e.item.into())
                     .collect(),
+                attributes: attribute_list(other.attributes),
+            }
+        }
+    }
+
+    /// The entries of an optional `with` clause
+    fn attribute_list<T>(
+        attributes: Option<ImportAttributes<T>>,
+    ) -> Vec<crate::decl::ImportAttribute<T>> {
+        attributes
+            .map(|a| a.attributes.into_iter().map(|e| e.item.into()).collect())
+            .unwrap_or_default()
+    }
+
+    impl<T> From<ImportAttribute<T>> for crate::decl::ImportAttribute<T> {
+        fn from(other: ImportAttribute<T>) -> Self {
+            Self {
+                key: match other.key {
+                    ImportAttributeKey::Ident(inner) => {
+                        crate::decl::ImportAttributeKey::Ident(inner.into())
+                    }
+                    ImportAttributeKey::String(inner) => {
+                        crate::decl::ImportAttributeKey::String(inner.into())
+                    }
+                },
+                value: other.value.into(),
             }
         }
     }
@@ -84,10 +233,14 @@ mod decl {
 
     impl<T> From<NormalImportSpec<T>> for crate::decl::NormalImportSpec<T> {
         fn from(other: NormalImportSpec<T>) -> Self {
-            let imported: crate::Ident<T> = other.imported.into();
+            let imported = other.imported.into();
             let alias = other.alias.map(|a| a.ident.into());
 
-            Self { imported, alias }
+            Self {
+                imported,
+                alias,
+                is_type_only: other.keyword_type.is_some(),
+            }
         }
     }
 
@@ -119,10 +272,13 @@ mod decl {
                     alias,
                     keyword: _,
                     name,
+                    attributes,
                 } => Self::All {
-                    alias: alias.map(|a| a.ident.into()),
+                    alias: alias.map(|a| a.name.into()),
                     name: name.into(),
+                    attributes: attribute_list(attributes),
                 },
+                ModExportSpecifier::Assign { eq: _, expr } => Self::Assign(expr.into()),
             }
         }
     }
@@ -130,15 +286,32 @@ mod decl {
         fn from(other: NamedExportDecl<T>) -> Self {
             match other {
                 NamedExportDecl::Decl(inner) => Self::Decl(inner.into()),
-                NamedExportDecl::Specifier(inner) => Self::Specifier(
-                    inner
-                        .list
-                        .elements
-                        .into_iter()
-                        .map(|e| e.item.into())
-                        .collect(),
-                    inner.source.map(|s| s.module.into()),
-                ),
+                NamedExportDecl::Specifier(inner) => {
+                    // `export type {a}` has no list level flag in the
+                    // non-spanned tree so every specifier carries it
+                    let list_is_type_only = inner.keyword_type.is_some();
+                    let (source, attributes) = match inner.source {
+                        Some(source) => (
+                            Some(source.module.into()),
+                            attribute_list(source.attributes),
+                        ),
+                        None => (None, Vec::new()),
+                    };
+                    Self::Specifier(
+                        inner
+                            .list
+                            .elements
+                            .into_iter()
+                            .map(|e| {
+                                let mut spec: crate::decl::ExportSpecifier<T> = e.item.into();
+                                spec.is_type_only |= list_is_type_only;
+                                spec
+                            })
+                            .collect(),
+                        source,
+                        attributes,
+                    )
+                }
             }
         }
     }
@@ -154,10 +327,29 @@ mod decl {
 
     impl<T> From<ExportSpecifier<T>> for crate::decl::ExportSpecifier<T> {
         fn from(other: ExportSpecifier<T>) -> Self {
-            let local: crate::Ident<T> = other.local.into();
             Self {
-                local,
-                alias: other.alias.map(|a| a.ident.into()),
+                local: other.local.into(),
+                alias: other.alias.map(|a| a.name.into()),
+                is_type_only: other.keyword_type.is_some(),
+            }
+        }
+    }
+
+    impl<T> From<ImportEqualsDecl<T>> for crate::decl::ImportEqualsDecl<T> {
+        fn from(other: ImportEqualsDecl<T>) -> Self {
+            Self {
+                id: other.id.into(),
+                module_ref: other.module_ref.into(),
+                is_type_only: other.keyword_type.is_some(),
+            }
+        }
+    }
+
+    impl<T> From<ModuleRef<T>> for crate::decl::ModuleRef<T> {
+        fn from(other: ModuleRef<T>) -> Self {
+            match other {
+                ModuleRef::Require(inner) => Self::Require(inner.module.into()),
+                ModuleRef::Entity(inner) => Self::Entity(inner.into()),
             }
         }
     }
@@ -167,6 +359,15 @@ mod decl {
             other.ident.into()
         }
     }
+
+    impl<T> From<ModuleExportName<T>> for crate::decl::ModuleExportName<T> {
+        fn from(other: ModuleExportName<T>) -> Self {
+            match other {
+                ModuleExportName::Ident(inner) => Self::Ident(inner.into()),
+                ModuleExportName::String(inner) => Self::String(inner.into()),
+            }
+        }
+    }
 }
 
 mod expr {
@@ -201,6 +402,9 @@ mod expr {
                 Expr::Conditional(inner) => Self::Conditional(inner.into()),
                 Expr::Func(inner) => Self::Func(inner.into()),
                 Expr::Ident(inner) => Self::Ident(inner.into()),
+                Expr::Import(inner) => Self::Import(inner.into()),
+                Expr::JsxElement(inner) => Self::JsxElement((*inner).into()),
+                Expr::JsxFragment(inner) => Self::JsxFragment((*inner).into()),
                 Expr::Lit(inner) => Self::Lit(inner.into()),
                 Expr::Logical(inner) => Self::Logical(inner.into()),
                 Expr::Member(inner) => Self::Member(inner.into()),
@@ -209,6 +413,7 @@ mod expr {
                 Expr::Obj(inner) => {
                     Self::Obj(inner.props.into_iter().map(|e| e.item.into()).collect())
                 }
+                Expr::PrivateName(inner) => Self::PrivateName(inner.into()),
                 Expr::Sequence(inner) => {
                     Self::Sequence(inner.into_iter().map(|e| e.item.into()).collect())
                 }
@@ -221,6 +426,7 @@ mod expr {
                 Expr::Yield(inner) => Self::Yield(inner.into()),
                 Expr::Wrapped(inner) => inner.expr.into(),
                 Expr::OptionalChain(inner) => Self::OptionalChain(Box::new((*inner.expr).into())),
+                Expr::Invalid(inner) => Self::Invalid(inner.source),
             }
         }
     }
@@ -248,66 +454,94 @@ mod expr {
                     kind: crate::PropKind::Init,
                     method: false,
                     is_static: false,
+                    is_abstract: false,
+                    decorators: Vec::new(),
+                    accessibility: None,
+                    is_optional: false,
                 },
                 Prop::Method(inner) => Self {
                     computed: inner.id.brackets.is_some(),
                     key: inner.id.into(),
                     value: crate::prelude::PropValue::Expr(crate::Expr::Func(crate::Func {
-                        body: inner.body.into(),
+                        body: inner.body.map(From::from),
                         generator: inner.star.is_some(),
                         id: None,
                         is_async: inner.keyword_async.is_some(),
+                        type_params: type_param_list(inner.type_params),
                         params: inner.params.into_iter().map(|e| e.item.into()).collect(),
+                        return_type: annotation(inner.return_type),
                     })),
                     kind: crate::PropKind::Method,
                     method: true,
                     short_hand: false,
                     is_static: inner.keyword_static.is_some(),
+                    is_abstract: inner.keyword_abstract.is_some(),
+                    decorators: inner.decorators.into_iter().map(From::from).collect(),
+                    accessibility: inner.accessibility.map(From::from),
+                    is_optional: inner.question.is_some(),
                 },
                 Prop::Ctor(inner) => Self {
                     computed: inner.keyword.brackets.is_some(),
                     key: inner.keyword.into(),
                     value: crate::prelude::PropValue::Expr(crate::Expr::Func(crate::Func {
-                        body: inner.body.into(),
+                        body: inner.body.map(From::from),
                         generator: false,
                         id: None,
                         is_async: false,
+                        type_params: Vec::new(),
                         params: inner.params.into_iter().map(|e| e.item.into()).collect(),
+                        return_type: None,
                     })),
                     kind: crate::PropKind::Ctor,
                     is_static: false,
                     method: true,
                     short_hand: false,
+                    is_abstract: false,
+                    decorators: Vec::new(),
+                    accessibility: inner.accessibility.map(From::from),
+                    is_optional: false,
                 },
                 Prop::Get(inner) => Self {
                     computed: inner.id.brackets.is_some(),
                     key: inner.id.into(),
                     value: crate::prelude::PropValue::Expr(crate::Expr::Func(crate::Func {
-                        body: inner.body.into(),
+                        body: inner.body.map(From::from),
                         generator: false,
                         id: None,
                         is_async: false,
+                        type_params: Vec::new(),
                         params: Vec::new(),
+                        return_type: annotation(inner.return_type),
                     })),
                     kind: crate::PropKind::Get,
                     method: false,
                     short_hand: false,
                     is_static: inner.keyword_static.is_some(),
+                    is_abstract: inner.keyword_abstract.is_some(),
+                    decorators: inner.decorators.into_iter().map(From::from).collect(),
+                    accessibility: inner.accessibility.map(From::from),
+                    is_optional: false,
                 },
                 Prop::Set(inner) => Self {
                     computed: inner.id.brackets.is_some(),
                     key: inner.id.into(),
                     value: crate::prelude::PropValue::Expr(crate::Expr::Func(crate::Func {
-                        body: inner.body.into(),
+                        body: inner.body.map(From::from),
                         generator: false,
                         id: None,
                         is_async: false,
+                        type_params: Vec::new(),
                         params: vec![inner.arg.item.into()],
+                        return_type: None,
                     })),
                     kind: crate::PropKind::Set,
                     method: false,
                     short_hand: false,
                     is_static: inner.keyword_static.is_some(),
+                    is_abstract: inner.keyword_abstract.is_some(),
+                    decorators: inner.decorators.into_iter().map(From::from).collect(),
+                    accessibility: inner.accessibility.map(From::from),
+                    is_optional: false,
                 },
             }
         }
@@ -323,8 +557,10 @@ mod expr {
         fn from(other: PropMethod<T>) -> Self {
             crate::Func {
                 id: None,
+                type_params: type_param_list(other.type_params),
                 params: other.params.into_iter().map(|e| e.item.into()).collect(),
-                body: other.body.into(),
+                return_type: annotation(other.return_type),
+                body: other.body.map(From::from),
                 generator: other.star.is_some(),
                 is_async: other.keyword_async.is_some(),
             }
@@ -337,6 +573,15 @@ mod expr {
                 PropKey::Lit(inner) => Self::Lit(inner.into()),
                 PropKey::Expr(inner) => Self::Expr(inner.into()),
                 PropKey::Pat(inner) => Self::Pat(inner.into()),
+                PropKey::Private(inner) => Self::Private(inner.into()),
+            }
+        }
+    }
+
+    impl<T> From<PrivateName<T>> for crate::expr::PrivateName<T> {
+        fn from(other: PrivateName<T>) -> Self {
+            Self {
+                id: other.id.into(),
             }
         }
     }
@@ -465,7 +710,9 @@ mod expr {
             let expression = matches!(&other.body, ArrowFuncBody::Expr(_));
             Self {
                 id: None,
+                type_params: type_param_list(other.type_params),
                 params: other.params.into_iter().map(|e| e.item.into()).collect(),
+                return_type: annotation(other.return_type),
                 body: other.body.into(),
                 expression,
                 generator: other.star.is_some(),
@@ -530,11 +777,20 @@ mod expr {
         }
     }
 
-    impl<T> From<MetaProp<T>> for crate::expr::MetaProp<T> {
-        fn from(other: MetaProp<T>) -> Self {
+    impl From<MetaProp> for crate::expr::MetaProp {
+        fn from(other: MetaProp) -> Self {
+            match other {
+                MetaProp::NewTarget { .. } => Self::NewTarget,
+                MetaProp::ImportMeta { .. } => Self::ImportMeta,
+            }
+        }
+    }
+
+    impl<T> From<ImportCall<T>> for crate::expr::ImportCall<T> {
+        fn from(other: ImportCall<T>) -> Self {
             Self {
-                meta: other.meta.into(),
-                property: other.property.into(),
+                source: Box::new(From::from(*other.source)),
+                options: other.options.map(|o| Box::new(From::from(*o))),
             }
         }
     }
@@ -545,6 +801,7 @@ mod expr {
                 Lit::Null(_inner) => Self::Null,
                 Lit::String(inner) => Self::String(inner.into()),
                 Lit::Number(inner) => Self::Number(inner.source),
+                Lit::BigInt(inner) => Self::BigInt(inner.source),
                 Lit::Boolean(inner) => Self::Boolean(inner.into()),
                 Lit::RegEx(inner) => Self::RegEx(inner.into()),
                 Lit::Template(inner) => Self::Template(inner.into()),
@@ -610,12 +867,14 @@ impl<T> From<Func<T>> for crate::Func<T> {
             generator: other.generator(),
             is_async: other.is_async(),
             id: other.id.map(From::from),
+            type_params: type_param_list(other.type_params),
             params: other
                 .params
                 .into_iter()
                 .map(|e| From::from(e.item))
                 .collect(),
-            body: other.body.into(),
+            return_type: annotation(other.return_type),
+            body: other.body.map(From::from),
         }
     }
 }
@@ -634,6 +893,33 @@ impl<T> From<FuncArg<T>> for crate::FuncArg<T> {
             FuncArg::Rest(inner) => {
                 Self::Pat(crate::pat::Pat::RestElement(Box::new(inner.pat.into())))
             }
+            FuncArg::Decorated(inner) => Self::Decorated(Box::new(crate::DecoratedArg {
+                decorators: inner.decorators.into_iter().map(From::from).collect(),
+                arg: inner.arg.into(),
+            })),
+            FuncArg::Typed(inner) => {
+                let pat = if inner.dots.is_some() {
+                    crate::pat::Pat::RestElement(Box::new(inner.pat.into()))
+                } else {
+                    inner.pat.into()
+                };
+                Self::Typed(Box::new(crate::TypedArg {
+                    accessibility: inner.accessibility.map(From::from),
+                    is_readonly: inner.keyword_readonly.is_some(),
+                    pat,
+                    is_optional: inner.question.is_some(),
+                    type_annotation: annotation(inner.type_annotation),
+                    default: inner.default.map(|(_, e)| e.into()),
+                }))
+            }
+        }
+    }
+}
+
+impl<T> From<Decorator<T>> for crate::Decorator<T> {
+    fn from(other: Decorator<T>) -> Self {
+        Self {
+            expr: other.expr.into(),
         }
     }
 }
@@ -641,8 +927,24 @@ impl<T> From<FuncArg<T>> for crate::FuncArg<T> {
 impl<T> From<Program<T>> for crate::Program<T> {
     fn from(other: Program<T>) -> Self {
         match other {
-            Program::Mod(inner) => Self::Mod(inner.into_iter().map(From::from).collect()),
-            Program::Script(inner) => Self::Script(inner.into_iter().map(From::from).collect()),
+            Program::Mod(inner, hashbang) => Self::Mod(
+                inner.into_iter().map(From::from).collect(),
+                hashbang.map(|h| h.source),
+            ),
+            Program::Script(inner, hashbang) => Self::Script(
+                inner.into_iter().map(From::from).collect(),
+                hashbang.map(|h| h.source),
+            ),
+        }
+    }
+}
+
+impl<T> From<SourceFile<T>> for crate::SourceFile<T> {
+    fn from(other: SourceFile<T>) -> Self {
+        Self {
+            name: other.name,
+            bom: other.bom,
+            program: other.program.into(),
         }
     }
 }
@@ -665,17 +967,58 @@ impl<T> From<FuncBody<T>> for crate::FuncBody<T> {
 
 impl<T> From<Class<T>> for crate::Class<T> {
     fn from(other: Class<T>) -> Self {
+        let (super_class, super_type_args) = match other.super_class {
+            Some(super_class) => (
+                Some(Box::new(From::from(super_class.expr))),
+                type_arg_list(super_class.type_args),
+            ),
+            None => (None, Vec::new()),
+        };
         Self {
             id: other.id.map(From::from),
-            super_class: other.super_class.map(|e| Box::new(From::from(e.expr))),
+            type_params: type_param_list(other.type_params),
+            super_class,
+            super_type_args,
+            implements: other
+                .implements
+                .map(|i| i.types.into_iter().map(|e| e.item.into()).collect())
+                .unwrap_or_default(),
             body: other.body.into(),
+            is_abstract: other.keyword_abstract.is_some(),
+            decorators: other.decorators.into_iter().map(From::from).collect(),
         }
     }
 }
 
 impl<T> From<ClassBody<T>> for crate::ClassBody<T> {
     fn from(other: ClassBody<T>) -> Self {
-        Self(other.props.into_iter().map(From::from).collect())
+        Self(other.members.into_iter().map(From::from).collect())
+    }
+}
+
+impl<T> From<ClassMember<T>> for crate::ClassMember<T> {
+    fn from(other: ClassMember<T>) -> Self {
+        match other {
+            ClassMember::Prop(inner) => Self::Prop(inner.into()),
+            ClassMember::Field(inner) => Self::Field(inner.into()),
+            ClassMember::StaticBlock(inner) => Self::StaticBlock(inner.body.into()),
+        }
+    }
+}
+
+impl<T> From<ClassField<T>> for crate::ClassField<T> {
+    fn from(other: ClassField<T>) -> Self {
+        Self {
+            computed: other.computed(),
+            is_static: other.keyword_static.is_some(),
+            key: other.key.into(),
+            value: other.value.map(From::from),
+            decorators: other.decorators.into_iter().map(From::from).collect(),
+            accessibility: other.accessibility.map(From::from),
+            is_readonly: other.keyword_readonly.is_some(),
+            is_optional: other.question.is_some(),
+            type_annotation: annotation(other.type_annotation),
+        }
     }
 }
 
@@ -685,6 +1028,8 @@ impl From<VarKind> for crate::VarKind {
             VarKind::Var(_) => Self::Var,
             VarKind::Let(_) => Self::Let,
             VarKind::Const(_) => Self::Const,
+            VarKind::Using(_) => Self::Using,
+            VarKind::AwaitUsing(..) => Self::AwaitUsing,
         }
     }
 }
@@ -774,6 +1119,284 @@ impl From<UnaryOp> for crate::UnaryOp {
     }
 }
 
+mod ts {
+    use super::*;
+
+    impl<T> From<TsType<T>> for crate::ts::TsType<T> {
+        fn from(other: TsType<T>) -> Self {
+            match other {
+                TsType::Keyword(inner) => Self::Keyword(inner.into()),
+                TsType::This(_) => Self::This,
+                TsType::Ref(inner) => Self::Ref(inner.into()),
+                TsType::Lit(inner) => Self::Lit(inner.into()),
+                TsType::Array(inner) => Self::Array(Box::new(From::from(*inner.elem_type))),
+                TsType::Tuple(inner) => Self::Tuple(type_list(inner.elems)),
+                TsType::Optional(inner) => Self::Optional(Box::new(From::from(*inner.elem_type))),
+                TsType::Rest(inner) => Self::Rest(Box::new(From::from(*inner.elem_type))),
+                TsType::Union(inner) => Self::Union(
+                    std::iter::once(*inner.first)
+                        .chain(inner.rest.into_iter().map(|(_, t)| t))
+                        .map(From::from)
+                        .collect(),
+                ),
+                TsType::Intersection(inner) => Self::Intersection(
+                    std::iter::once(*inner.first)
+                        .chain(inner.rest.into_iter().map(|(_, t)| t))
+                        .map(From::from)
+                        .collect(),
+                ),
+                TsType::Func(inner) => Self::Func(Box::new(crate::ts::TsFuncType {
+                    is_constructor: inner.keyword_new.is_some(),
+                    type_params: type_param_list(inner.type_params),
+                    params: inner.params.into_iter().map(|e| e.item.into()).collect(),
+                    return_type: inner.return_type.into(),
+                })),
+                TsType::Object(inner) => {
+                    Self::Object(inner.members.into_iter().map(|e| e.member.into()).collect())
+                }
+                TsType::Query(inner) => Self::Query(namespace_name(inner.name)),
+                TsType::Operator(inner) => {
+                    Self::Operator(inner.operator.into(), Box::new(From::from(*inner.operand)))
+                }
+                TsType::Indexed(inner) => Self::Indexed(Box::new(crate::ts::TsIndexedType {
+                    object: From::from(*inner.object),
+                    index: From::from(*inner.index),
+                })),
+                TsType::Conditional(inner) => {
+                    Self::Conditional(Box::new(crate::ts::TsConditionalType {
+                        check_type: inner.check_type.into(),
+                        extends_type: inner.extends_type.into(),
+                        true_type: inner.true_type.into(),
+                        false_type: inner.false_type.into(),
+                    }))
+                }
+                TsType::Infer(inner) => Self::Infer(inner.name.into()),
+                TsType::Predicate(inner) => Self::Predicate(Box::new(crate::ts::TsTypePredicate {
+                    asserts: inner.keyword_asserts.is_some(),
+                    param: inner.param.into(),
+                    type_annotation: inner.is.map(|(_, t)| t.into()),
+                })),
+                TsType::Paren(inner) => From::from(*inner.inner),
+            }
+        }
+    }
+
+    fn type_list<T>(types: Vec<ListEntry<TsType<T>>>) -> Vec<crate::ts::TsType<T>> {
+        types.into_iter().map(|e| e.item.into()).collect()
+    }
+
+    impl From<TsKeyword> for crate::ts::TsKeyword {
+        fn from(other: TsKeyword) -> Self {
+            match other {
+                TsKeyword::Any(_) => Self::Any,
+                TsKeyword::Unknown(_) => Self::Unknown,
+                TsKeyword::Number(_) => Self::Number,
+                TsKeyword::String(_) => Self::String,
+                TsKeyword::Boolean(_) => Self::Boolean,
+                TsKeyword::BigInt(_) => Self::BigInt,
+                TsKeyword::Symbol(_) => Self::Symbol,
+                TsKeyword::Object(_) => Self::Object,
+                TsKeyword::Undefined(_) => Self::Undefined,
+                TsKeyword::Null(_) => Self::Null,
+                TsKeyword::Never(_) => Self::Never,
+                TsKeyword::Void(_) => Self::Void,
+            }
+        }
+    }
+
+    impl From<TsTypeOperator> for crate::ts::TsTypeOperator {
+        fn from(other: TsTypeOperator) -> Self {
+            match other {
+                TsTypeOperator::KeyOf(_) => Self::KeyOf,
+                TsTypeOperator::Unique(_) => Self::Unique,
+                TsTypeOperator::Readonly(_) => Self::Readonly,
+            }
+        }
+    }
+
+    impl From<Accessibility> for crate::ts::Accessibility {
+        fn from(other: Accessibility) -> Self {
+            match other {
+                Accessibility::Public(_) => Self::Public,
+                Accessibility::Protected(_) => Self::Protected,
+                Accessibility::Private(_) => Self::Private,
+            }
+        }
+    }
+
+    impl<T> From<TsTypeRef<T>> for crate::ts::TsTypeRef<T> {
+        fn from(other: TsTypeRef<T>) -> Self {
+            Self {
+                name: namespace_name(other.name),
+                type_args: type_arg_list(other.type_args),
+            }
+        }
+    }
+
+    impl<T> From<TsPredicateParam<T>> for crate::ts::TsPredicateParam<T> {
+        fn from(other: TsPredicateParam<T>) -> Self {
+            match other {
+                TsPredicateParam::Ident(inner) => Self::Ident(inner.into()),
+                TsPredicateParam::This(_) => Self::This,
+            }
+        }
+    }
+
+    impl<T> From<TsTypeParam<T>> for crate::ts::TsTypeParam<T> {
+        fn from(other: TsTypeParam<T>) -> Self {
+            Self {
+                name: other.name.into(),
+                constraint: other.constraint.map(|(_, t)| t.into()),
+                default: other.default.map(|(_, t)| t.into()),
+            }
+        }
+    }
+
+    impl<T> From<TsTypeMember<T>> for crate::ts::TsTypeMember<T> {
+        fn from(other: TsTypeMember<T>) -> Self {
+            match other {
+                TsTypeMember::Prop(inner) => Self::Prop(crate::ts::TsPropSignature {
+                    computed: inner.key.brackets.is_some(),
+                    key: inner.key.into(),
+                    is_optional: inner.question.is_some(),
+                    is_readonly: inner.keyword_readonly.is_some(),
+                    type_annotation: annotation(inner.type_annotation),
+                }),
+                TsTypeMember::Method(inner) => Self::Method(crate::ts::TsMethodSignature {
+                    computed: inner.key.brackets.is_some(),
+                    key: inner.key.into(),
+                    is_optional: inner.question.is_some(),
+                    type_params: type_param_list(inner.type_params),
+                    params: inner.params.into_iter().map(|e| e.item.into()).collect(),
+                    return_type: annotation(inner.return_type),
+                }),
+                TsTypeMember::Call(inner) => Self::Call(crate::ts::TsCallSignature {
+                    is_constructor: inner.keyword_new.is_some(),
+                    type_params: type_param_list(inner.type_params),
+                    params: inner.params.into_iter().map(|e| e.item.into()).collect(),
+                    return_type: annotation(inner.return_type),
+                }),
+                TsTypeMember::Index(inner) => Self::Index(crate::ts::TsIndexSignature {
+                    is_readonly: inner.keyword_readonly.is_some(),
+                    param: inner.param.into(),
+                    param_type: inner.param_type.into(),
+                    type_annotation: inner.type_annotation.type_annotation.into(),
+                }),
+            }
+        }
+    }
+}
+
+mod jsx {
+    use crate::spanned::jsx::{
+        JsxAttr, JsxAttrName, JsxAttrValue, JsxAttribute, JsxChild, JsxElement, JsxElementName,
+        JsxFragment, JsxMemberExpr, JsxNamespacedName,
+    };
+
+    impl<T> From<JsxElement<T>> for crate::jsx::JsxElement<T> {
+        fn from(other: JsxElement<T>) -> Self {
+            Self {
+                self_closing: other.open.end.is_self_closing(),
+                name: other.open.name.into(),
+                attributes: other.open.attributes.into_iter().map(From::from).collect(),
+                children: other.children.into_iter().map(From::from).collect(),
+            }
+        }
+    }
+
+    impl<T> From<JsxFragment<T>> for crate::jsx::JsxFragment<T> {
+        fn from(other: JsxFragment<T>) -> Self {
+            Self {
+                children: other.children.into_iter().map(From::from).collect(),
+            }
+        }
+    }
+
+    impl<T> From<JsxElementName<T>> for crate::jsx::JsxElementName<T> {
+        fn from(other: JsxElementName<T>) -> Self {
+            match other {
+                JsxElementName::Ident(inner) => Self::Ident(inner.into()),
+                JsxElementName::Namespaced(inner) => Self::Namespaced(inner.into()),
+                JsxElementName::Member(inner) => Self::Member(inner.into()),
+            }
+        }
+    }
+
+    impl<T> From<JsxNamespacedName<T>> for crate::jsx::JsxNamespacedName<T> {
+        fn from(other: JsxNamespacedName<T>) -> Self {
+            Self {
+                namespace: other.namespace.into(),
+                name: other.name.into(),
+            }
+        }
+    }
+
+    impl<T> From<JsxMemberExpr<T>> for crate::jsx::JsxMemberExpr<T> {
+        fn from(other: JsxMemberExpr<T>) -> Self {
+            Self {
+                object: other.object.into(),
+                properties: other
+                    .properties
+                    .into_iter()
+                    .map(|p| p.name.into())
+                    .collect(),
+            }
+        }
+    }
+
+    impl<T> From<JsxAttr<T>> for crate::jsx::JsxAttr<T> {
+        fn from(other: JsxAttr<T>) -> Self {
+            match other {
+                JsxAttr::Attr(inner) => Self::Attr(inner.into()),
+                JsxAttr::Spread(inner) => Self::Spread(inner.expr.into()),
+            }
+        }
+    }
+
+    impl<T> From<JsxAttribute<T>> for crate::jsx::JsxAttribute<T> {
+        fn from(other: JsxAttribute<T>) -> Self {
+            Self {
+                name: other.name.into(),
+                value: other.value.and_then(|init| match init.value {
+                    JsxAttrValue::String(inner) => {
+                        Some(crate::jsx::JsxAttrValue::String(inner.into()))
+                    }
+                    JsxAttrValue::Expr(inner) => {
+                        inner.expr.map(|e| crate::jsx::JsxAttrValue::Expr(e.into()))
+                    }
+                    JsxAttrValue::Element(inner) => {
+                        Some(crate::jsx::JsxAttrValue::Element((*inner).into()))
+                    }
+                    JsxAttrValue::Fragment(inner) => {
+                        Some(crate::jsx::JsxAttrValue::Fragment((*inner).into()))
+                    }
+                }),
+            }
+        }
+    }
+
+    impl<T> From<JsxAttrName<T>> for crate::jsx::JsxAttrName<T> {
+        fn from(other: JsxAttrName<T>) -> Self {
+            match other {
+                JsxAttrName::Ident(inner) => Self::Ident(inner.into()),
+                JsxAttrName::Namespaced(inner) => Self::Namespaced(inner.into()),
+            }
+        }
+    }
+
+    impl<T> From<JsxChild<T>> for crate::jsx::JsxChild<T> {
+        fn from(other: JsxChild<T>) -> Self {
+            match other {
+                JsxChild::Text(inner) => Self::Text(inner.source),
+                JsxChild::Expr(inner) => Self::Expr(inner.expr.map(From::from)),
+                JsxChild::Spread(inner) => Self::Spread(inner.expr.into()),
+                JsxChild::Element(inner) => Self::Element(inner.into()),
+                JsxChild::Fragment(inner) => Self::Fragment(inner.into()),
+            }
+        }
+    }
+}
+
 mod pat {
     use super::*;
 
@@ -784,6 +1407,7 @@ mod pat {
                 Pat::Obj(inner) => Self::Obj(inner.into()),
                 Pat::Array(inner) => Self::Array(inner.into()),
                 Pat::Assign(inner) => Self::Assign(inner.into()),
+                Pat::Invalid(inner) => Self::Invalid(inner.source),
             }
         }
     }
@@ -867,6 +1491,7 @@ mod stmt {
                 Stmt::Var { decls, .. } => {
                     Self::Var(decls.decls.into_iter().map(|e| e.item.into()).collect())
                 }
+                Stmt::Invalid(inner) => Self::Invalid(inner.source),
             }
         }
     }
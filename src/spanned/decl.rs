@@ -1,13 +1,19 @@
-use crate::spanned::expr::{Expr, Lit};
+use crate::decl::ModuleExportNameError;
+use crate::spanned::expr::{Expr, Lit, StringLit};
 use crate::spanned::pat::Pat;
 use crate::spanned::VarKind;
-use crate::spanned::{Class, Func, Ident};
+use crate::spanned::{Class, Func, Ident, ProgramPart};
 use crate::IntoAllocated;
 
+use crate::spanned::ts::{TsType, TsTypeAnnotation, TsTypeLit, TsTypeParams, TsTypeRef};
+
 use super::tokens::{
-    As, Asterisk, CloseBrace, Default, Equal, Export, From, Import, OpenBrace, Semicolon, Token,
+    As, Asterisk, CloseBrace, CloseParen, Colon, Const, Declare, Default, Enum, Equal, Export,
+    Extends, From, Global, Import, Interface, Module, Namespace, OpenBrace, OpenParen, Period,
+    Require, Semicolon, Token, Type, With,
 };
-use super::{ListEntry, Node, SourceLocation};
+use super::Position;
+use super::{ListEntry, Node, Slice, SliceError, SourceLocation};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -53,6 +59,59 @@ pub enum Decl<T> {
         export: Box<ModExport<T>>,
         semi_colon: Option<Semicolon>,
     },
+    /// A typescript import alias declaration
+    /// ```ts
+    /// import fs = require('fs');
+    /// import Thing = Stuff.Thing;
+    /// ```
+    ImportEquals {
+        import: Box<ImportEqualsDecl<T>>,
+        semi_colon: Option<Semicolon>,
+    },
+    /// An ambient declaration, describing a value that
+    /// exists at runtime without providing it
+    /// ```ts
+    /// declare const thing;
+    /// declare function stuff(a);
+    /// declare class Thing {}
+    /// ```
+    Declare {
+        keyword: Declare,
+        decl: Box<Decl<T>>,
+    },
+    /// A typescript namespace or module declaration
+    /// ```ts
+    /// namespace Stuff.Things {}
+    /// declare module 'stuff';
+    /// declare global {}
+    /// ```
+    Module {
+        module: Box<ModuleDecl<T>>,
+        semi_colon: Option<Semicolon>,
+    },
+    /// A typescript interface declaration
+    /// ```ts
+    /// interface Thing<T> extends Stuff {
+    ///     value: T;
+    /// }
+    /// ```
+    Interface(Box<InterfaceDecl<T>>),
+    /// A typescript type alias declaration
+    /// ```ts
+    /// type Things<T> = T[];
+    /// ```
+    TypeAlias {
+        decl: Box<TypeAliasDecl<T>>,
+        semi_colon: Option<Semicolon>,
+    },
+    /// A typescript enum declaration
+    /// ```ts
+    /// enum Color { Red, Green = 'green' }
+    /// const enum Flags { A = 1 }
+    /// ```
+    Enum(Box<EnumDecl<T>>),
+    /// Source that could not be parsed as a declaration
+    Invalid(Slice<T>),
 }
 
 impl<T> IntoAllocated for Decl<T>
@@ -76,6 +135,25 @@ where
                 export: export.into_allocated(),
                 semi_colon,
             },
+            Decl::ImportEquals { import, semi_colon } => Decl::ImportEquals {
+                import: import.into_allocated(),
+                semi_colon,
+            },
+            Decl::Declare { keyword, decl } => Decl::Declare {
+                keyword,
+                decl: decl.into_allocated(),
+            },
+            Decl::Module { module, semi_colon } => Decl::Module {
+                module: module.into_allocated(),
+                semi_colon,
+            },
+            Decl::Interface(inner) => Decl::Interface(inner.into_allocated()),
+            Decl::TypeAlias { decl, semi_colon } => Decl::TypeAlias {
+                decl: decl.into_allocated(),
+                semi_colon,
+            },
+            Decl::Enum(inner) => Decl::Enum(inner.into_allocated()),
+            Decl::Invalid(inner) => Decl::Invalid(inner.into_allocated()),
         }
     }
 }
@@ -112,6 +190,40 @@ impl<T> Node for Decl<T> {
                 }
                 export.loc()
             }
+            Decl::ImportEquals { import, semi_colon } => {
+                if let Some(semi) = semi_colon {
+                    return SourceLocation {
+                        start: import.loc().start,
+                        end: semi.end(),
+                    };
+                }
+                import.loc()
+            }
+            Decl::Declare { keyword, decl } => SourceLocation {
+                start: keyword.start(),
+                end: decl.loc().end,
+            },
+            Decl::Module { module, semi_colon } => {
+                if let Some(semi) = semi_colon {
+                    return SourceLocation {
+                        start: module.loc().start,
+                        end: semi.end(),
+                    };
+                }
+                module.loc()
+            }
+            Decl::Interface(inner) => inner.loc(),
+            Decl::TypeAlias { decl, semi_colon } => {
+                if let Some(semi) = semi_colon {
+                    return SourceLocation {
+                        start: decl.loc().start,
+                        end: semi.end(),
+                    };
+                }
+                decl.loc()
+            }
+            Decl::Enum(inner) => inner.loc(),
+            Decl::Invalid(inner) => inner.loc,
         }
     }
 }
@@ -154,6 +266,7 @@ impl<T> Node for VarDecls<T> {
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct VarDecl<T> {
     pub id: Pat<T>,
+    pub type_annotation: Option<TsTypeAnnotation<T>>,
     pub eq: Option<Equal>,
     pub init: Option<Expr<T>>,
 }
@@ -166,6 +279,7 @@ where
     fn into_allocated(self) -> VarDecl<String> {
         VarDecl {
             id: self.id.into_allocated(),
+            type_annotation: self.type_annotation.into_allocated(),
             eq: self.eq,
             init: self.init.map(|i| i.into_allocated()),
         }
@@ -179,6 +293,11 @@ impl<T> Node for VarDecl<T> {
                 start: self.id.loc().start,
                 end: init.loc().end,
             }
+        } else if let Some(annotation) = &self.type_annotation {
+            SourceLocation {
+                start: self.id.loc().start,
+                end: annotation.loc().end,
+            }
         } else {
             self.id.loc()
         }
@@ -227,9 +346,11 @@ impl<T> Node for ModDecl<T> {
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct ModImport<T> {
     pub keyword_import: Import,
+    pub keyword_type: Option<Type>,
     pub specifiers: Vec<ListEntry<ImportSpecifier<T>>>,
     pub keyword_from: Option<From>,
     pub source: Lit<T>,
+    pub attributes: Option<ImportAttributes<T>>,
 }
 
 impl<T> IntoAllocated for ModImport<T>
@@ -240,6 +361,7 @@ where
     fn into_allocated(self) -> ModImport<String> {
         ModImport {
             keyword_import: self.keyword_import,
+            keyword_type: self.keyword_type,
             specifiers: self
                 .specifiers
                 .into_iter()
@@ -247,19 +369,31 @@ where
                 .collect(),
             keyword_from: self.keyword_from,
             source: self.source.into_allocated(),
+            attributes: self.attributes.map(|a| a.into_allocated()),
         }
     }
 }
 
 impl<T> Node for ModImport<T> {
     fn loc(&self) -> SourceLocation {
+        let end = if let Some(attributes) = &self.attributes {
+            attributes.loc().end
+        } else {
+            self.source.loc().end
+        };
         SourceLocation {
             start: self.keyword_import.start(),
-            end: self.source.loc().end,
+            end,
         }
     }
 }
 
+impl<T> ModImport<T> {
+    pub fn is_type_only(&self) -> bool {
+        self.keyword_type.is_some()
+    }
+}
+
 /// The name of the thing being imported
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
@@ -345,10 +479,16 @@ impl<T> Node for NormalImportSpecs<T> {
     }
 }
 
+/// A single name in curly braces of an import
+/// ```ts
+/// import {type Thing, stuff as things, 'a-b' as ab} from './stuff.js';
+/// //      ^^^^^^^^^^  ^^^^^^^^^^^^^^^  ^^^^^^^^^^^
+/// ```
 #[derive(PartialEq, Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct NormalImportSpec<T> {
-    pub imported: Ident<T>,
+    pub keyword_type: Option<Type>,
+    pub imported: ModuleExportName<T>,
     pub alias: Option<Alias<T>>,
 }
 
@@ -359,6 +499,7 @@ where
     type Allocated = NormalImportSpec<String>;
     fn into_allocated(self) -> NormalImportSpec<String> {
         NormalImportSpec {
+            keyword_type: self.keyword_type,
             imported: self.imported.into_allocated(),
             alias: self.alias.map(|a| a.into_allocated()),
         }
@@ -367,14 +508,23 @@ where
 
 impl<T> Node for NormalImportSpec<T> {
     fn loc(&self) -> SourceLocation {
-        if let Some(alias) = &self.alias {
-            SourceLocation {
-                start: self.imported.loc().start,
-                end: alias.loc().end,
-            }
+        let start = if let Some(keyword) = &self.keyword_type {
+            keyword.start()
         } else {
-            self.imported.loc()
-        }
+            self.imported.loc().start
+        };
+        let end = if let Some(alias) = &self.alias {
+            alias.loc().end
+        } else {
+            self.imported.loc().end
+        };
+        SourceLocation { start, end }
+    }
+}
+
+impl<T> NormalImportSpec<T> {
+    pub fn is_type_only(&self) -> bool {
+        self.keyword_type.is_some()
     }
 }
 
@@ -491,10 +641,16 @@ pub enum ModExportSpecifier<T> {
     /// ```
     All {
         star: Asterisk,
-        alias: Option<Alias<T>>,
+        alias: Option<ExportAlias<T>>,
         keyword: From,
         name: Lit<T>,
+        attributes: Option<ImportAttributes<T>>,
     },
+    /// A typescript export assignment
+    /// ```ts
+    /// export = thing;
+    /// ```
+    Assign { eq: Equal, expr: Expr<T> },
 }
 
 impl<T> IntoAllocated for ModExportSpecifier<T>
@@ -514,11 +670,17 @@ where
                 alias,
                 keyword,
                 name,
+                attributes,
             } => ModExportSpecifier::All {
                 star,
                 alias: alias.map(|a| a.into_allocated()),
                 keyword,
                 name: name.into_allocated(),
+                attributes: attributes.map(|a| a.into_allocated()),
+            },
+            ModExportSpecifier::Assign { eq, expr } => ModExportSpecifier::Assign {
+                eq,
+                expr: expr.into_allocated(),
             },
         }
     }
@@ -532,9 +694,21 @@ impl<T> Node for ModExportSpecifier<T> {
                 end: value.loc().end,
             },
             ModExportSpecifier::Named(inner) => inner.loc(),
-            ModExportSpecifier::All { star, name, .. } => SourceLocation {
+            ModExportSpecifier::All {
+                star,
+                name,
+                attributes,
+                ..
+            } => SourceLocation {
                 start: star.start(),
-                end: name.loc().end,
+                end: attributes
+                    .as_ref()
+                    .map(|a| a.loc().end)
+                    .unwrap_or_else(|| name.loc().end),
+            },
+            ModExportSpecifier::Assign { eq, expr } => SourceLocation {
+                start: eq.start(),
+                end: expr.loc().end,
             },
         }
     }
@@ -671,9 +845,16 @@ impl<T> Node for DefaultExportDeclValue<T> {
     }
 }
 
+/// A list of exported names, optionally re-exported from
+/// another module
+/// ```ts
+/// export {thing, type Stuff};
+/// export type {Stuff} from 'place';
+/// ```
 #[derive(PartialEq, Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct NamedExportSpec<T> {
+    pub keyword_type: Option<Type>,
     pub list: ExportList<T>,
     pub source: Option<NamedExportSource<T>>,
 }
@@ -685,6 +866,7 @@ where
     type Allocated = NamedExportSpec<String>;
     fn into_allocated(self) -> NamedExportSpec<String> {
         NamedExportSpec {
+            keyword_type: self.keyword_type,
             list: self.list.into_allocated(),
             source: self.source.map(|s| s.into_allocated()),
         }
@@ -693,14 +875,23 @@ where
 
 impl<T> Node for NamedExportSpec<T> {
     fn loc(&self) -> SourceLocation {
-        if let Some(source) = &self.source {
-            SourceLocation {
-                start: self.list.loc().start,
-                end: source.loc().end,
-            }
+        let start = if let Some(keyword) = &self.keyword_type {
+            keyword.start()
         } else {
-            self.list.loc()
-        }
+            self.list.loc().start
+        };
+        let end = if let Some(source) = &self.source {
+            source.loc().end
+        } else {
+            self.list.loc().end
+        };
+        SourceLocation { start, end }
+    }
+}
+
+impl<T> NamedExportSpec<T> {
+    pub fn is_type_only(&self) -> bool {
+        self.keyword_type.is_some()
     }
 }
 
@@ -709,6 +900,7 @@ impl<T> Node for NamedExportSpec<T> {
 pub struct NamedExportSource<T> {
     pub keyword_from: From,
     pub module: Lit<T>,
+    pub attributes: Option<ImportAttributes<T>>,
 }
 
 impl<T> IntoAllocated for NamedExportSource<T>
@@ -720,15 +912,125 @@ where
         NamedExportSource {
             keyword_from: self.keyword_from,
             module: self.module.into_allocated(),
+            attributes: self.attributes.map(|a| a.into_allocated()),
         }
     }
 }
 
 impl<T> Node for NamedExportSource<T> {
     fn loc(&self) -> SourceLocation {
+        let end = if let Some(attributes) = &self.attributes {
+            attributes.loc().end
+        } else {
+            self.module.loc().end
+        };
         SourceLocation {
             start: self.keyword_from.start(),
-            end: self.module.loc().end,
+            end,
+        }
+    }
+}
+
+/// The `with` clause after the source of an import or re-export
+/// ```js
+/// import data from './data.json' with {type: 'json'};
+/// ```
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct ImportAttributes<T> {
+    pub keyword_with: With,
+    pub open_brace: OpenBrace,
+    pub attributes: Vec<ListEntry<ImportAttribute<T>>>,
+    pub close_brace: CloseBrace,
+}
+
+impl<T> IntoAllocated for ImportAttributes<T>
+where
+    T: ToString,
+{
+    type Allocated = ImportAttributes<String>;
+    fn into_allocated(self) -> ImportAttributes<String> {
+        ImportAttributes {
+            keyword_with: self.keyword_with,
+            open_brace: self.open_brace,
+            attributes: self
+                .attributes
+                .into_iter()
+                .map(|a| a.into_allocated())
+                .collect(),
+            close_brace: self.close_brace,
+        }
+    }
+}
+
+impl<T> Node for ImportAttributes<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.keyword_with.start(),
+            end: self.close_brace.end(),
+        }
+    }
+}
+
+/// A single `key: 'value'` entry of a `with` clause
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct ImportAttribute<T> {
+    pub key: ImportAttributeKey<T>,
+    pub colon: Colon,
+    pub value: StringLit<T>,
+}
+
+impl<T> IntoAllocated for ImportAttribute<T>
+where
+    T: ToString,
+{
+    type Allocated = ImportAttribute<String>;
+    fn into_allocated(self) -> ImportAttribute<String> {
+        ImportAttribute {
+            key: self.key.into_allocated(),
+            colon: self.colon,
+            value: self.value.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for ImportAttribute<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.key.loc().start,
+            end: self.value.loc().end,
+        }
+    }
+}
+
+/// The key of an import attribute, either an identifier
+/// or a string
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum ImportAttributeKey<T> {
+    Ident(Ident<T>),
+    String(StringLit<T>),
+}
+
+impl<T> IntoAllocated for ImportAttributeKey<T>
+where
+    T: ToString,
+{
+    type Allocated = ImportAttributeKey<String>;
+    fn into_allocated(self) -> ImportAttributeKey<String> {
+        match self {
+            ImportAttributeKey::Ident(inner) => ImportAttributeKey::Ident(inner.into_allocated()),
+            ImportAttributeKey::String(inner) => ImportAttributeKey::String(inner.into_allocated()),
+        }
+    }
+}
+
+impl<T> Node for ImportAttributeKey<T> {
+    fn loc(&self) -> SourceLocation {
+        match self {
+            ImportAttributeKey::Ident(inner) => inner.loc(),
+            ImportAttributeKey::String(inner) => inner.loc(),
         }
     }
 }
@@ -775,12 +1077,16 @@ impl<T> Node for ExportList<T> {
 /// export {Thing} from 'place';
 /// //aliased
 /// export {Stuff as NewThing} from 'place'
+/// //string names
+/// export {Stuff as 'new-thing', 'a-b'} from 'place'
 /// ```
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct ExportSpecifier<T> {
-    pub local: Ident<T>,
-    pub alias: Option<Alias<T>>,
+    pub keyword_type: Option<Type>,
+    /// Only a string when re-exporting from another module
+    pub local: ModuleExportName<T>,
+    pub alias: Option<ExportAlias<T>>,
 }
 
 impl<T> IntoAllocated for ExportSpecifier<T>
@@ -791,6 +1097,7 @@ where
 
     fn into_allocated(self) -> Self::Allocated {
         ExportSpecifier {
+            keyword_type: self.keyword_type,
             local: self.local.into_allocated(),
             alias: self.alias.map(|a| a.into_allocated()),
         }
@@ -799,14 +1106,23 @@ where
 
 impl<T> Node for ExportSpecifier<T> {
     fn loc(&self) -> SourceLocation {
-        if let Some(alias) = &self.alias {
-            SourceLocation {
-                start: self.local.loc().start,
-                end: alias.loc().end,
-            }
+        let start = if let Some(keyword) = &self.keyword_type {
+            keyword.start()
         } else {
-            self.local.loc()
-        }
+            self.local.loc().start
+        };
+        let end = if let Some(alias) = &self.alias {
+            alias.loc().end
+        } else {
+            self.local.loc().end
+        };
+        SourceLocation { start, end }
+    }
+}
+
+impl<T> ExportSpecifier<T> {
+    pub fn is_type_only(&self) -> bool {
+        self.keyword_type.is_some()
     }
 }
 
@@ -839,3 +1155,643 @@ impl<T> Node for Alias<T> {
         }
     }
 }
+
+/// The `as` clause of an export, the new name may be a string
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct ExportAlias<T> {
+    pub keyword: As,
+    pub name: ModuleExportName<T>,
+}
+
+impl<T> IntoAllocated for ExportAlias<T>
+where
+    T: ToString,
+{
+    type Allocated = ExportAlias<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        ExportAlias {
+            keyword: self.keyword,
+            name: self.name.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for ExportAlias<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.keyword.start(),
+            end: self.name.loc().end,
+        }
+    }
+}
+
+/// The name of an export as seen by other modules,
+/// either an identifier or a string
+/// ```js
+/// export {thing as "a-b"};
+/// import {"a-b" as thing} from './stuff.js';
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum ModuleExportName<T> {
+    Ident(Ident<T>),
+    String(StringLit<T>),
+}
+
+impl<T> IntoAllocated for ModuleExportName<T>
+where
+    T: ToString,
+{
+    type Allocated = ModuleExportName<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            ModuleExportName::Ident(inner) => ModuleExportName::Ident(inner.into_allocated()),
+            ModuleExportName::String(inner) => ModuleExportName::String(inner.into_allocated()),
+        }
+    }
+}
+
+impl<T> Node for ModuleExportName<T> {
+    fn loc(&self) -> SourceLocation {
+        match self {
+            ModuleExportName::Ident(inner) => inner.loc(),
+            ModuleExportName::String(inner) => inner.loc(),
+        }
+    }
+}
+
+impl<T> ModuleExportName<T>
+where
+    T: AsRef<str>,
+{
+    /// The name other modules use, a string name is cooked and
+    /// must be well formed unicode, without any lone surrogates
+    pub fn value(&self) -> Result<String, SliceError<ModuleExportNameError>> {
+        match self {
+            ModuleExportName::Ident(inner) => Ok(inner.slice.source.as_ref().to_string()),
+            ModuleExportName::String(inner) => {
+                // module code is always strict
+                let units = inner.cooked(true).map_err(|e| SliceError {
+                    error: ModuleExportNameError::Cook(e.error),
+                    loc: e.loc,
+                })?;
+                String::from_utf16(&units).map_err(|_| SliceError {
+                    error: ModuleExportNameError::LoneSurrogate,
+                    loc: inner.loc(),
+                })
+            }
+        }
+    }
+}
+
+/// A typescript import alias, either of an external
+/// module or of an entity in a namespace
+/// ```ts
+/// import fs = require('fs');
+/// import type Thing = Stuff.Thing;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct ImportEqualsDecl<T> {
+    pub keyword_import: Import,
+    pub keyword_type: Option<Type>,
+    pub id: Ident<T>,
+    pub eq: Equal,
+    pub module_ref: ModuleRef<T>,
+}
+
+impl<T> IntoAllocated for ImportEqualsDecl<T>
+where
+    T: ToString,
+{
+    type Allocated = ImportEqualsDecl<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        ImportEqualsDecl {
+            keyword_import: self.keyword_import,
+            keyword_type: self.keyword_type,
+            id: self.id.into_allocated(),
+            eq: self.eq,
+            module_ref: self.module_ref.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for ImportEqualsDecl<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.keyword_import.start(),
+            end: self.module_ref.loc().end,
+        }
+    }
+}
+
+impl<T> ImportEqualsDecl<T> {
+    pub fn is_type_only(&self) -> bool {
+        self.keyword_type.is_some()
+    }
+}
+
+/// The right hand side of an `ImportEqualsDecl`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum ModuleRef<T> {
+    /// ```ts
+    /// import fs = require('fs');
+    /// ```
+    Require(RequireRef<T>),
+    /// An identifier or a chain of member accesses
+    /// ```ts
+    /// import Thing = Stuff.Thing;
+    /// ```
+    Entity(Expr<T>),
+}
+
+impl<T> IntoAllocated for ModuleRef<T>
+where
+    T: ToString,
+{
+    type Allocated = ModuleRef<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            ModuleRef::Require(inner) => ModuleRef::Require(inner.into_allocated()),
+            ModuleRef::Entity(inner) => ModuleRef::Entity(inner.into_allocated()),
+        }
+    }
+}
+
+impl<T> Node for ModuleRef<T> {
+    fn loc(&self) -> SourceLocation {
+        match self {
+            ModuleRef::Require(inner) => inner.loc(),
+            ModuleRef::Entity(inner) => inner.loc(),
+        }
+    }
+}
+
+/// `require('fs')` on the right hand side of an `ImportEqualsDecl`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct RequireRef<T> {
+    pub keyword: Require,
+    pub open_paren: OpenParen,
+    pub module: Lit<T>,
+    pub close_paren: CloseParen,
+}
+
+impl<T> IntoAllocated for RequireRef<T>
+where
+    T: ToString,
+{
+    type Allocated = RequireRef<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        RequireRef {
+            keyword: self.keyword,
+            open_paren: self.open_paren,
+            module: self.module.into_allocated(),
+            close_paren: self.close_paren,
+        }
+    }
+}
+
+impl<T> Node for RequireRef<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.keyword.start(),
+            end: self.close_paren.end(),
+        }
+    }
+}
+
+/// A typescript namespace or module declaration, the body will be
+/// `None` for a shorthand ambient module
+/// ```ts
+/// namespace Stuff {
+///     export const thing = 0;
+/// }
+/// declare module 'stuff';
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct ModuleDecl<T> {
+    /// This will be `None` for `global`
+    pub keyword: Option<ModuleKeyword>,
+    pub name: ModuleName<T>,
+    pub body: Option<ModuleBody<T>>,
+}
+
+impl<T> IntoAllocated for ModuleDecl<T>
+where
+    T: ToString,
+{
+    type Allocated = ModuleDecl<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        ModuleDecl {
+            keyword: self.keyword,
+            name: self.name.into_allocated(),
+            body: self.body.map(|b| b.into_allocated()),
+        }
+    }
+}
+
+impl<T> Node for ModuleDecl<T> {
+    fn loc(&self) -> SourceLocation {
+        let start = if let Some(keyword) = &self.keyword {
+            keyword.start()
+        } else {
+            self.name.loc().start
+        };
+        let end = if let Some(body) = &self.body {
+            body.close_brace.end()
+        } else {
+            self.name.loc().end
+        };
+        SourceLocation { start, end }
+    }
+}
+
+/// The keyword introducing a `ModuleDecl`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum ModuleKeyword {
+    Namespace(Namespace),
+    Module(Module),
+}
+
+impl Token for ModuleKeyword {
+    fn as_str(&self) -> &str {
+        match self {
+            ModuleKeyword::Namespace(inner) => inner.as_str(),
+            ModuleKeyword::Module(inner) => inner.as_str(),
+        }
+    }
+
+    fn start(&self) -> Position {
+        match self {
+            ModuleKeyword::Namespace(inner) => inner.start(),
+            ModuleKeyword::Module(inner) => inner.start(),
+        }
+    }
+
+    fn end(&self) -> Position {
+        match self {
+            ModuleKeyword::Namespace(inner) => inner.end(),
+            ModuleKeyword::Module(inner) => inner.end(),
+        }
+    }
+}
+
+/// The name of a `ModuleDecl`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum ModuleName<T> {
+    /// ```ts
+    /// namespace Stuff.Things {}
+    /// ```
+    Namespace(NamespaceName<T>),
+    /// ```ts
+    /// declare module 'stuff' {}
+    /// ```
+    Lit(Lit<T>),
+    /// ```ts
+    /// declare global {}
+    /// ```
+    Global(Global),
+}
+
+impl<T> IntoAllocated for ModuleName<T>
+where
+    T: ToString,
+{
+    type Allocated = ModuleName<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            ModuleName::Namespace(inner) => ModuleName::Namespace(inner.into_allocated()),
+            ModuleName::Lit(inner) => ModuleName::Lit(inner.into_allocated()),
+            ModuleName::Global(inner) => ModuleName::Global(inner),
+        }
+    }
+}
+
+impl<T> Node for ModuleName<T> {
+    fn loc(&self) -> SourceLocation {
+        match self {
+            ModuleName::Namespace(inner) => inner.loc(),
+            ModuleName::Lit(inner) => inner.loc(),
+            ModuleName::Global(inner) => inner.loc(),
+        }
+    }
+}
+
+/// A possibly dotted namespace name
+/// ```ts
+/// namespace Stuff.Things {}
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct NamespaceName<T> {
+    pub first: Ident<T>,
+    pub rest: Vec<(Period, Ident<T>)>,
+}
+
+impl<T> IntoAllocated for NamespaceName<T>
+where
+    T: ToString,
+{
+    type Allocated = NamespaceName<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        NamespaceName {
+            first: self.first.into_allocated(),
+            rest: self
+                .rest
+                .into_iter()
+                .map(|(p, i)| (p, i.into_allocated()))
+                .collect(),
+        }
+    }
+}
+
+impl<T> Node for NamespaceName<T> {
+    fn loc(&self) -> SourceLocation {
+        let end = if let Some((_, last)) = self.rest.last() {
+            last.loc().end
+        } else {
+            self.first.loc().end
+        };
+        SourceLocation {
+            start: self.first.loc().start,
+            end,
+        }
+    }
+}
+
+/// The block of a `ModuleDecl`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct ModuleBody<T> {
+    pub open_brace: OpenBrace,
+    pub parts: Vec<ProgramPart<T>>,
+    pub close_brace: CloseBrace,
+}
+
+impl<T> IntoAllocated for ModuleBody<T>
+where
+    T: ToString,
+{
+    type Allocated = ModuleBody<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        ModuleBody {
+            open_brace: self.open_brace,
+            parts: self
+                .parts
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            close_brace: self.close_brace,
+        }
+    }
+}
+
+impl<T> Node for ModuleBody<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.open_brace.start(),
+            end: self.close_brace.end(),
+        }
+    }
+}
+
+/// A typescript interface declaration
+/// ```ts
+/// interface Thing<T> extends Stuff {
+///     value: T;
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct InterfaceDecl<T> {
+    pub keyword: Interface,
+    pub id: Ident<T>,
+    pub type_params: Option<TsTypeParams<T>>,
+    pub extends: Option<InterfaceExtends<T>>,
+    pub body: TsTypeLit<T>,
+}
+
+impl<T> IntoAllocated for InterfaceDecl<T>
+where
+    T: ToString,
+{
+    type Allocated = InterfaceDecl<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        InterfaceDecl {
+            keyword: self.keyword,
+            id: self.id.into_allocated(),
+            type_params: self.type_params.into_allocated(),
+            extends: self.extends.into_allocated(),
+            body: self.body.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for InterfaceDecl<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.keyword.start(),
+            end: self.body.loc().end,
+        }
+    }
+}
+
+/// The interfaces an `InterfaceDecl` extends
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct InterfaceExtends<T> {
+    pub keyword: Extends,
+    pub types: Vec<ListEntry<TsTypeRef<T>>>,
+}
+
+impl<T> IntoAllocated for InterfaceExtends<T>
+where
+    T: ToString,
+{
+    type Allocated = InterfaceExtends<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        InterfaceExtends {
+            keyword: self.keyword,
+            types: self
+                .types
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+        }
+    }
+}
+
+/// A typescript type alias declaration
+/// ```ts
+/// type Things<T> = T[];
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TypeAliasDecl<T> {
+    pub keyword: Type,
+    pub id: Ident<T>,
+    pub type_params: Option<TsTypeParams<T>>,
+    pub eq: Equal,
+    pub type_annotation: TsType<T>,
+}
+
+impl<T> IntoAllocated for TypeAliasDecl<T>
+where
+    T: ToString,
+{
+    type Allocated = TypeAliasDecl<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TypeAliasDecl {
+            keyword: self.keyword,
+            id: self.id.into_allocated(),
+            type_params: self.type_params.into_allocated(),
+            eq: self.eq,
+            type_annotation: self.type_annotation.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for TypeAliasDecl<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.keyword.start(),
+            end: self.type_annotation.loc().end,
+        }
+    }
+}
+
+/// A typescript enum declaration
+/// ```ts
+/// const enum Color { Red, Green = 'green' }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct EnumDecl<T> {
+    pub keyword_const: Option<Const>,
+    pub keyword: Enum,
+    pub id: Ident<T>,
+    pub open_brace: OpenBrace,
+    pub members: Vec<ListEntry<EnumMember<T>>>,
+    pub close_brace: CloseBrace,
+}
+
+impl<T> IntoAllocated for EnumDecl<T>
+where
+    T: ToString,
+{
+    type Allocated = EnumDecl<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        EnumDecl {
+            keyword_const: self.keyword_const,
+            keyword: self.keyword,
+            id: self.id.into_allocated(),
+            open_brace: self.open_brace,
+            members: self
+                .members
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            close_brace: self.close_brace,
+        }
+    }
+}
+
+impl<T> Node for EnumDecl<T> {
+    fn loc(&self) -> SourceLocation {
+        let start = if let Some(keyword) = &self.keyword_const {
+            keyword.start()
+        } else {
+            self.keyword.start()
+        };
+        SourceLocation {
+            start,
+            end: self.close_brace.end(),
+        }
+    }
+}
+
+/// A single member of an `EnumDecl` with its optional initializer
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct EnumMember<T> {
+    pub id: EnumMemberName<T>,
+    pub init: Option<(Equal, Expr<T>)>,
+}
+
+impl<T> IntoAllocated for EnumMember<T>
+where
+    T: ToString,
+{
+    type Allocated = EnumMember<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        EnumMember {
+            id: self.id.into_allocated(),
+            init: self.init.map(|(eq, e)| (eq, e.into_allocated())),
+        }
+    }
+}
+
+impl<T> Node for EnumMember<T> {
+    fn loc(&self) -> SourceLocation {
+        let end = if let Some((_, init)) = &self.init {
+            init.loc().end
+        } else {
+            self.id.loc().end
+        };
+        SourceLocation {
+            start: self.id.loc().start,
+            end,
+        }
+    }
+}
+
+/// The name of an `EnumMember`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum EnumMemberName<T> {
+    Ident(Ident<T>),
+    String(StringLit<T>),
+}
+
+impl<T> IntoAllocated for EnumMemberName<T>
+where
+    T: ToString,
+{
+    type Allocated = EnumMemberName<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            EnumMemberName::Ident(inner) => EnumMemberName::Ident(inner.into_allocated()),
+            EnumMemberName::String(inner) => EnumMemberName::String(inner.into_allocated()),
+        }
+    }
+}
+
+impl<T> Node for EnumMemberName<T> {
+    fn loc(&self) -> SourceLocation {
+        match self {
+            EnumMemberName::Ident(inner) => inner.loc(),
+            EnumMemberName::String(inner) => inner.loc(),
+        }
+    }
+}
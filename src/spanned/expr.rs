@@ -1,14 +1,20 @@
+use crate::cook::{cook, template_raw, CookError, CookMode};
+use crate::number::{NumberError, NumericLit};
+use crate::regex::{self, Regex, RegexError};
+use crate::spanned::jsx::{JsxElement, JsxFragment};
 use crate::spanned::pat::Pat;
-use crate::spanned::{Class, Func, FuncArg, FuncBody, Ident};
+use crate::spanned::ts::{Accessibility, TsTypeAnnotation, TsTypeParams};
+use crate::spanned::{Class, Decorator, Func, FuncArg, FuncBody, Ident};
 use crate::IntoAllocated;
 
 use super::tokens::{
-    self, AssignOp, Asterisk, Async, Await, BinaryOp, CloseBrace, CloseBracket, CloseParen, Colon,
-    Comma, Ellipsis, False, FatArrow, ForwardSlash, Get, LogicalOp, New, Null, OpenBrace,
-    OpenBracket, OpenParen, Period, QuasiQuote, QuestionMark, QuestionMarkDot, Quote, Set, Static,
-    Super, This, Token, True, UnaryOp, UpdateOp, Yield,
+    self, Abstract, AssignOp, Asterisk, Async, Await, BinaryOp, CloseBrace, CloseBracket,
+    CloseParen, Colon, Comma, Ellipsis, False, FatArrow, ForwardSlash, Get, Hash, Import,
+    LogicalOp, Meta, New, Null, OpenBrace, OpenBracket, OpenParen, Period, QuasiQuote,
+    QuestionMark, QuestionMarkDot, Quote, Semicolon, Set, Static, Super, Target, This, Token, True,
+    UnaryOp, UpdateOp, Yield,
 };
-use super::{FuncArgEntry, ListEntry, Node, Slice, SourceLocation};
+use super::{FuncArgEntry, ListEntry, Node, Position, Slice, SliceError, SourceLocation};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
@@ -49,6 +55,21 @@ pub enum Expr<T> {
     Func(Func<T>),
     /// An identifier
     Ident(Ident<T>),
+    /// A dynamic import
+    /// ```js
+    /// const stuff = await import('./stuff.js');
+    /// ```
+    Import(ImportCall<T>),
+    /// Source that could not be parsed as an expression, kept so an
+    /// editor can still work with the rest of a half typed file
+    /// ```js
+    /// let x = 1 + ;
+    /// ```
+    Invalid(Slice<T>),
+    /// A JSX element, see `JsxElement`
+    JsxElement(Box<JsxElement<T>>),
+    /// A JSX fragment, see `JsxFragment`
+    JsxFragment(Box<JsxFragment<T>>),
     /// A literal value, see `Literal`
     Lit(Lit<T>),
     /// A specialized `BinaryExpr` for logical evaluation
@@ -63,8 +84,8 @@ pub enum Expr<T> {
     /// c.stuff;
     /// ```
     Member(MemberExpr<T>),
-    /// currently just `new.target`
-    MetaProp(MetaProp<T>),
+    /// `new.target` or `import.meta`
+    MetaProp(MetaProp),
     /// ```js
     /// var a = true ? 'stuff' : 'things';
     /// ```
@@ -72,6 +93,13 @@ pub enum Expr<T> {
     /// Calling a constructor
     New(NewExpr<T>),
     Obj(ObjExpr<T>),
+    /// A private class member name, as the property of a
+    /// `MemberExpr` or the left side of `in`
+    /// ```js
+    /// this.#secret;
+    /// #secret in obj;
+    /// ```
+    PrivateName(PrivateName<T>),
     /// Any sequence of expressions separated with a comma
     Sequence(SequenceExpr<T>),
     /// `...` followed by an `Expr`
@@ -120,12 +148,16 @@ where
             Expr::Conditional(inner) => Expr::Conditional(inner.into_allocated()),
             Expr::Func(inner) => Expr::Func(inner.into_allocated()),
             Expr::Ident(inner) => Expr::Ident(inner.into_allocated()),
+            Expr::Import(inner) => Expr::Import(inner.into_allocated()),
+            Expr::JsxElement(inner) => Expr::JsxElement(inner.into_allocated()),
+            Expr::JsxFragment(inner) => Expr::JsxFragment(inner.into_allocated()),
             Expr::Lit(inner) => Expr::Lit(inner.into_allocated()),
             Expr::Logical(inner) => Expr::Logical(inner.into_allocated()),
             Expr::Member(inner) => Expr::Member(inner.into_allocated()),
-            Expr::MetaProp(inner) => Expr::MetaProp(inner.into_allocated()),
+            Expr::MetaProp(inner) => Expr::MetaProp(inner),
             Expr::New(inner) => Expr::New(inner.into_allocated()),
             Expr::Obj(inner) => Expr::Obj(inner.into_allocated()),
+            Expr::PrivateName(inner) => Expr::PrivateName(inner.into_allocated()),
             Expr::Sequence(inner) => Expr::Sequence(
                 inner
                     .into_iter()
@@ -141,6 +173,7 @@ where
             Expr::Wrapped(inner) => Expr::Wrapped(inner.into_allocated()),
             Expr::Yield(inner) => Expr::Yield(inner.into_allocated()),
             Expr::OptionalChain(inner) => Expr::OptionalChain(inner.into_allocated()),
+            Expr::Invalid(inner) => Expr::Invalid(inner.into_allocated()),
         }
     }
 }
@@ -159,12 +192,16 @@ impl<T> Node for Expr<T> {
             Expr::Conditional(inner) => inner.loc(),
             Expr::Func(inner) => inner.loc(),
             Expr::Ident(inner) => inner.loc(),
+            Expr::Import(inner) => inner.loc(),
+            Expr::JsxElement(inner) => inner.loc(),
+            Expr::JsxFragment(inner) => inner.loc(),
             Expr::Lit(inner) => inner.loc(),
             Expr::Logical(inner) => inner.loc(),
             Expr::Member(inner) => inner.loc(),
             Expr::MetaProp(inner) => inner.loc(),
             Expr::New(inner) => inner.loc(),
             Expr::Obj(inner) => inner.loc(),
+            Expr::PrivateName(inner) => inner.loc(),
             Expr::Sequence(inner) => inner.loc(),
             Expr::Spread(inner) => inner.loc(),
             Expr::Super(inner) => inner.loc(),
@@ -175,6 +212,7 @@ impl<T> Node for Expr<T> {
             Expr::Yield(inner) => inner.loc(),
             Expr::Wrapped(inner) => inner.loc(),
             Expr::OptionalChain(inner) => inner.loc(),
+            Expr::Invalid(inner) => inner.loc,
         }
     }
 }
@@ -373,6 +411,14 @@ impl<T> Prop<T> {
             false
         }
     }
+    pub fn is_abstract(&self) -> bool {
+        match self {
+            Self::Method(meth) => meth.keyword_abstract.is_some(),
+            Self::Get(get) => get.keyword_abstract.is_some(),
+            Self::Set(set) => set.keyword_abstract.is_some(),
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -455,14 +501,22 @@ impl<T> Node for PropInitKey<T> {
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct PropMethod<T> {
+    /// Only allowed on class methods and accessors
+    pub decorators: Vec<Decorator<T>>,
+    pub accessibility: Option<Accessibility>,
+    pub keyword_abstract: Option<Abstract>,
     pub keyword_static: Option<Static>,
     pub keyword_async: Option<Async>,
     pub id: PropInitKey<T>,
     pub star: Option<Asterisk>,
+    pub question: Option<QuestionMark>,
+    pub type_params: Option<TsTypeParams<T>>,
     pub open_paren: OpenParen,
     pub params: Vec<ListEntry<FuncArg<T>>>,
     pub close_paren: CloseParen,
-    pub body: FuncBody<T>,
+    pub return_type: Option<TsTypeAnnotation<T>>,
+    pub body: Option<FuncBody<T>>,
+    pub semi_colon: Option<Semicolon>,
 }
 
 impl<T> IntoAllocated for PropMethod<T>
@@ -472,10 +526,19 @@ where
     type Allocated = PropMethod<String>;
     fn into_allocated(self) -> Self::Allocated {
         PropMethod {
+            decorators: self
+                .decorators
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            accessibility: self.accessibility,
+            keyword_abstract: self.keyword_abstract,
             keyword_static: self.keyword_static,
             keyword_async: self.keyword_async,
             id: self.id.into_allocated(),
             star: self.star,
+            question: self.question,
+            type_params: self.type_params.into_allocated(),
             open_paren: self.open_paren,
             params: self
                 .params
@@ -483,14 +546,24 @@ where
                 .map(IntoAllocated::into_allocated)
                 .collect(),
             close_paren: self.close_paren,
-            body: self.body.into_allocated(),
+            return_type: self.return_type.into_allocated(),
+            body: self.body.map(|b| b.into_allocated()),
+            semi_colon: self.semi_colon,
         }
     }
 }
 
 impl<T> Node for PropMethod<T> {
     fn loc(&self) -> SourceLocation {
-        let start = if let Some(keyword) = &self.keyword_async {
+        let start = if let Some(first) = self.decorators.first() {
+            first.loc().start
+        } else if let Some(accessibility) = &self.accessibility {
+            accessibility.start()
+        } else if let Some(keyword) = &self.keyword_abstract {
+            keyword.start()
+        } else if let Some(keyword) = &self.keyword_static {
+            keyword.start()
+        } else if let Some(keyword) = &self.keyword_async {
             keyword.start()
         } else if let Some(star) = &self.star {
             star.start()
@@ -499,7 +572,12 @@ impl<T> Node for PropMethod<T> {
         };
         SourceLocation {
             start,
-            end: self.body.loc().end,
+            end: method_end(
+                &self.body,
+                &self.semi_colon,
+                &self.return_type,
+                &self.close_paren,
+            ),
         }
     }
 }
@@ -507,11 +585,13 @@ impl<T> Node for PropMethod<T> {
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct PropCtor<T> {
+    pub accessibility: Option<Accessibility>,
     pub keyword: PropInitKey<T>,
     pub open_paren: OpenParen,
     pub params: Vec<ListEntry<FuncArg<T>>>,
     pub close_paren: CloseParen,
-    pub body: FuncBody<T>,
+    pub body: Option<FuncBody<T>>,
+    pub semi_colon: Option<Semicolon>,
 }
 
 impl<T> IntoAllocated for PropCtor<T>
@@ -521,6 +601,7 @@ where
     type Allocated = PropCtor<String>;
     fn into_allocated(self) -> Self::Allocated {
         PropCtor {
+            accessibility: self.accessibility,
             keyword: self.keyword.into_allocated(),
             open_paren: self.open_paren,
             params: self
@@ -529,16 +610,22 @@ where
                 .map(IntoAllocated::into_allocated)
                 .collect(),
             close_paren: self.close_paren,
-            body: self.body.into_allocated(),
+            body: self.body.map(|b| b.into_allocated()),
+            semi_colon: self.semi_colon,
         }
     }
 }
 
 impl<T> Node for PropCtor<T> {
     fn loc(&self) -> SourceLocation {
+        let start = if let Some(accessibility) = &self.accessibility {
+            accessibility.start()
+        } else {
+            self.keyword.loc().start
+        };
         SourceLocation {
-            start: self.keyword.loc().start,
-            end: self.body.loc().end,
+            start,
+            end: method_end(&self.body, &self.semi_colon, &None, &self.close_paren),
         }
     }
 }
@@ -546,12 +633,18 @@ impl<T> Node for PropCtor<T> {
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct PropGet<T> {
+    /// Only allowed on class methods and accessors
+    pub decorators: Vec<Decorator<T>>,
+    pub accessibility: Option<Accessibility>,
+    pub keyword_abstract: Option<Abstract>,
     pub keyword_static: Option<Static>,
     pub keyword_get: Get,
     pub id: PropInitKey<T>,
     pub open_paren: OpenParen,
     pub close_paren: CloseParen,
-    pub body: FuncBody<T>,
+    pub return_type: Option<TsTypeAnnotation<T>>,
+    pub body: Option<FuncBody<T>>,
+    pub semi_colon: Option<Semicolon>,
 }
 
 impl<T> IntoAllocated for PropGet<T>
@@ -561,27 +654,46 @@ where
     type Allocated = PropGet<String>;
     fn into_allocated(self) -> Self::Allocated {
         PropGet {
+            decorators: self
+                .decorators
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            accessibility: self.accessibility,
+            keyword_abstract: self.keyword_abstract,
             keyword_static: self.keyword_static,
             keyword_get: self.keyword_get,
             id: self.id.into_allocated(),
             open_paren: self.open_paren,
             close_paren: self.close_paren,
-            body: self.body.into_allocated(),
+            return_type: self.return_type.into_allocated(),
+            body: self.body.map(|b| b.into_allocated()),
+            semi_colon: self.semi_colon,
         }
     }
 }
 
 impl<T> Node for PropGet<T> {
     fn loc(&self) -> SourceLocation {
-        if let Some(keyword_static) = &self.keyword_static {
-            return SourceLocation {
-                start: keyword_static.start(),
-                end: self.body.loc().end,
-            };
-        }
+        let start = if let Some(first) = self.decorators.first() {
+            first.loc().start
+        } else if let Some(accessibility) = &self.accessibility {
+            accessibility.start()
+        } else if let Some(keyword) = &self.keyword_abstract {
+            keyword.start()
+        } else if let Some(keyword) = &self.keyword_static {
+            keyword.start()
+        } else {
+            self.keyword_get.start()
+        };
         SourceLocation {
-            start: self.keyword_get.start(),
-            end: self.body.loc().end,
+            start,
+            end: method_end(
+                &self.body,
+                &self.semi_colon,
+                &self.return_type,
+                &self.close_paren,
+            ),
         }
     }
 }
@@ -589,13 +701,18 @@ impl<T> Node for PropGet<T> {
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct PropSet<T> {
+    /// Only allowed on class methods and accessors
+    pub decorators: Vec<Decorator<T>>,
+    pub accessibility: Option<Accessibility>,
+    pub keyword_abstract: Option<Abstract>,
     pub keyword_static: Option<Static>,
     pub keyword_set: Set,
     pub id: PropInitKey<T>,
     pub open_paren: OpenParen,
     pub arg: ListEntry<FuncArg<T>>,
     pub close_paren: CloseParen,
-    pub body: FuncBody<T>,
+    pub body: Option<FuncBody<T>>,
+    pub semi_colon: Option<Semicolon>,
 }
 
 impl<T> IntoAllocated for PropSet<T>
@@ -605,32 +722,63 @@ where
     type Allocated = PropSet<String>;
     fn into_allocated(self) -> Self::Allocated {
         PropSet {
+            decorators: self
+                .decorators
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            accessibility: self.accessibility,
+            keyword_abstract: self.keyword_abstract,
             keyword_static: self.keyword_static,
             keyword_set: self.keyword_set,
             id: self.id.into_allocated(),
             open_paren: self.open_paren,
             arg: self.arg.into_allocated(),
             close_paren: self.close_paren,
-            body: self.body.into_allocated(),
+            body: self.body.map(|b| b.into_allocated()),
+            semi_colon: self.semi_colon,
         }
     }
 }
 
 impl<T> Node for PropSet<T> {
     fn loc(&self) -> SourceLocation {
-        if let Some(keyword_static) = &self.keyword_static {
-            return SourceLocation {
-                start: keyword_static.start(),
-                end: self.body.loc().end,
-            };
-        }
+        let start = if let Some(first) = self.decorators.first() {
+            first.loc().start
+        } else if let Some(accessibility) = &self.accessibility {
+            accessibility.start()
+        } else if let Some(keyword) = &self.keyword_abstract {
+            keyword.start()
+        } else if let Some(keyword) = &self.keyword_static {
+            keyword.start()
+        } else {
+            self.keyword_set.start()
+        };
         SourceLocation {
-            start: self.keyword_set.start(),
-            end: self.body.loc().end,
+            start,
+            end: method_end(&self.body, &self.semi_colon, &None, &self.close_paren),
         }
     }
 }
 
+/// The end of a method, which may be a signature without a body
+fn method_end<T>(
+    body: &Option<FuncBody<T>>,
+    semi_colon: &Option<Semicolon>,
+    return_type: &Option<TsTypeAnnotation<T>>,
+    close_paren: &CloseParen,
+) -> Position {
+    if let Some(body) = body {
+        body.loc().end
+    } else if let Some(semi) = semi_colon {
+        semi.end()
+    } else if let Some(return_type) = return_type {
+        return_type.loc().end
+    } else {
+        close_paren.end()
+    }
+}
+
 /// An object literal or class property identifier
 #[derive(PartialEq, Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
@@ -638,6 +786,8 @@ pub enum PropKey<T> {
     Lit(Lit<T>),
     Expr(Expr<T>),
     Pat(Pat<T>),
+    /// `#secret`, only in a class body
+    Private(PrivateName<T>),
 }
 
 impl<T> IntoAllocated for PropKey<T>
@@ -650,6 +800,7 @@ where
             PropKey::Lit(inner) => PropKey::Lit(inner.into_allocated()),
             PropKey::Expr(inner) => PropKey::Expr(inner.into_allocated()),
             PropKey::Pat(inner) => PropKey::Pat(inner.into_allocated()),
+            PropKey::Private(inner) => PropKey::Private(inner.into_allocated()),
         }
     }
 }
@@ -660,6 +811,37 @@ impl<T> Node for PropKey<T> {
             PropKey::Lit(inner) => inner.loc(),
             PropKey::Expr(inner) => inner.loc(),
             PropKey::Pat(inner) => inner.loc(),
+            PropKey::Private(inner) => inner.loc(),
+        }
+    }
+}
+
+/// The name of a private class member
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct PrivateName<T> {
+    pub hash: Hash,
+    pub id: Ident<T>,
+}
+
+impl<T> IntoAllocated for PrivateName<T>
+where
+    T: ToString,
+{
+    type Allocated = PrivateName<String>;
+    fn into_allocated(self) -> Self::Allocated {
+        PrivateName {
+            hash: self.hash,
+            id: self.id.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for PrivateName<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.hash.start(),
+            end: self.id.loc().end,
         }
     }
 }
@@ -1283,9 +1465,11 @@ impl<T> Node for ArrowParamPlaceHolder<T> {
 pub struct ArrowFuncExpr<T> {
     pub keyword: Option<Async>,
     pub star: Option<Asterisk>,
+    pub type_params: Option<TsTypeParams<T>>,
     pub open_paren: Option<OpenParen>,
     pub params: Vec<ListEntry<FuncArg<T>>>,
     pub close_paren: Option<CloseParen>,
+    pub return_type: Option<TsTypeAnnotation<T>>,
     pub arrow: FatArrow,
     pub body: ArrowFuncBody<T>,
 }
@@ -1299,6 +1483,7 @@ where
         ArrowFuncExpr {
             keyword: self.keyword,
             star: self.star,
+            type_params: self.type_params.into_allocated(),
             open_paren: self.open_paren,
             params: self
                 .params
@@ -1306,6 +1491,7 @@ where
                 .map(IntoAllocated::into_allocated)
                 .collect(),
             close_paren: self.close_paren,
+            return_type: self.return_type.into_allocated(),
             arrow: self.arrow,
             body: self.body.into_allocated(),
         }
@@ -1316,6 +1502,8 @@ impl<T> Node for ArrowFuncExpr<T> {
     fn loc(&self) -> SourceLocation {
         let start = if let Some(keyword) = &self.keyword {
             keyword.start()
+        } else if let Some(params) = &self.type_params {
+            params.open.start()
         } else if let Some(slice) = &self.open_paren {
             slice.start()
         } else if let Some(first) = self.params.first() {
@@ -1519,6 +1707,18 @@ where
             QuasiQuote::BackTick(_) | QuasiQuote::CloseBrace(_)
         ) && matches!(self.close_quote, QuasiQuote::BackTick(_))
     }
+
+    /// The runtime value of this element as UTF-16 code units, a tagged
+    /// template receives `undefined` when this is an error
+    pub fn cooked(&self) -> Result<Vec<u16>, SliceError<CookError>> {
+        cook(self.content.source.as_ref(), CookMode::Template)
+            .map_err(|e| self.content.error_at(e.index, e.index + e.len, e))
+    }
+
+    /// The value `String.raw` would see as UTF-16 code units
+    pub fn raw(&self) -> Vec<u16> {
+        template_raw(self.content.source.as_ref())
+    }
 }
 
 impl<T> Node for TemplateElement<T> {
@@ -1530,43 +1730,101 @@ impl<T> Node for TemplateElement<T> {
     }
 }
 
-/// pretty much just `new.target`
+/// A meta property, a keyword followed by a
+/// property that is only valid in that position
+#[derive(PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum MetaProp {
+    /// ```js
+    /// function Thing(one, two) {
+    ///     if (!new.target) {
+    ///         return new Thing(one, two);
+    ///     }
+    ///     this.one = one;
+    ///     this.two = two;
+    /// }
+    /// ```
+    NewTarget {
+        keyword_new: New,
+        dot: Period,
+        keyword_target: Target,
+    },
+    /// Information about the current module, only valid in a module
+    /// ```js
+    /// const data = new URL('./data.json', import.meta.url);
+    /// ```
+    ImportMeta {
+        keyword_import: Import,
+        dot: Period,
+        keyword_meta: Meta,
+    },
+}
+
+impl Node for MetaProp {
+    fn loc(&self) -> SourceLocation {
+        match self {
+            MetaProp::NewTarget {
+                keyword_new,
+                keyword_target,
+                ..
+            } => SourceLocation {
+                start: keyword_new.start(),
+                end: keyword_target.end(),
+            },
+            MetaProp::ImportMeta {
+                keyword_import,
+                keyword_meta,
+                ..
+            } => SourceLocation {
+                start: keyword_import.start(),
+                end: keyword_meta.end(),
+            },
+        }
+    }
+}
+
+/// A call to `import()`, `options` is the optional second argument
+/// that carries the import attributes
 /// ```js
-/// function Thing(one, two) {
-///     if (!new.target) {
-///         return new Thing(one, two);
-///     }
-///     this.one = one;
-///     this.two = two;
-/// }
+/// import('./stuff.js');
+/// import('./data.json', {with: {type: 'json'}});
 /// ```
 #[derive(PartialEq, Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
-pub struct MetaProp<T> {
-    pub meta: Ident<T>,
-    pub dot: Period,
-    pub property: Ident<T>,
+pub struct ImportCall<T> {
+    pub keyword_import: Import,
+    pub open_paren: OpenParen,
+    pub source: Box<Expr<T>>,
+    pub comma: Option<Comma>,
+    pub options: Option<Box<Expr<T>>>,
+    pub trailing_comma: Option<Comma>,
+    pub close_paren: CloseParen,
 }
 
-impl<T> IntoAllocated for MetaProp<T>
+impl<T> IntoAllocated for ImportCall<T>
 where
     T: ToString,
 {
-    type Allocated = MetaProp<String>;
+    type Allocated = ImportCall<String>;
+
     fn into_allocated(self) -> Self::Allocated {
-        MetaProp {
-            meta: self.meta.into_allocated(),
-            dot: self.dot,
-            property: self.property.into_allocated(),
+        ImportCall {
+            keyword_import: self.keyword_import,
+            open_paren: self.open_paren,
+            source: self.source.into_allocated(),
+            comma: self.comma,
+            options: self.options.map(|o| o.into_allocated()),
+            trailing_comma: self.trailing_comma,
+            close_paren: self.close_paren,
         }
     }
 }
 
-impl<T> Node for MetaProp<T> {
+impl<T> Node for ImportCall<T> {
     fn loc(&self) -> SourceLocation {
         SourceLocation {
-            start: self.meta.loc().start,
-            end: self.property.loc().end,
+            start: self.keyword_import.start(),
+            end: self.close_paren.end(),
         }
     }
 }
@@ -1589,6 +1847,10 @@ pub enum Lit<T> {
     /// `0o7`
     /// `0b1`
     Number(Slice<T>),
+    /// The slice includes the `n` suffix
+    /// `10n`
+    /// `0xffn`
+    BigInt(Slice<T>),
     /// `true`
     /// `false`
     Boolean(Boolean),
@@ -1610,6 +1872,7 @@ where
             Lit::Null(inner) => Lit::Null(inner),
             Lit::String(inner) => Lit::String(inner.into_allocated()),
             Lit::Number(inner) => Lit::Number(inner.into_allocated()),
+            Lit::BigInt(inner) => Lit::BigInt(inner.into_allocated()),
             Lit::Boolean(inner) => Lit::Boolean(inner),
             Lit::RegEx(inner) => Lit::RegEx(inner.into_allocated()),
             Lit::Template(inner) => Lit::Template(inner.into_allocated()),
@@ -1617,6 +1880,20 @@ where
     }
 }
 
+impl<T> Lit<T>
+where
+    T: AsRef<str>,
+{
+    /// Analyze a `Number` or `BigInt` literal, `None` for
+    /// any other kind of literal
+    pub fn numeric(&self) -> Option<Result<NumericLit, NumberError>> {
+        match self {
+            Lit::Number(raw) | Lit::BigInt(raw) => Some(NumericLit::parse(raw.source.as_ref())),
+            _ => None,
+        }
+    }
+}
+
 impl<T> Lit<T> {
     pub fn new_true(line: u32, column: u32) -> Self {
         Self::Boolean(Boolean::new_true(line, column))
@@ -1673,6 +1950,7 @@ impl<T> Node for Lit<T> {
             Lit::Null(inner) => inner.loc(),
             Lit::String(inner) => inner.loc(),
             Lit::Number(inner) => inner.loc,
+            Lit::BigInt(inner) => inner.loc,
             Lit::Boolean(inner) => inner.loc(),
             Lit::RegEx(inner) => inner.loc(),
             Lit::Template(inner) => inner.loc(),
@@ -1711,6 +1989,22 @@ impl<T> Node for StringLit<T> {
     }
 }
 
+impl<T> StringLit<T>
+where
+    T: AsRef<str>,
+{
+    /// The runtime value of this string as UTF-16 code units
+    pub fn cooked(&self, strict: bool) -> Result<Vec<u16>, SliceError<CookError>> {
+        let mode = if strict {
+            CookMode::Strict
+        } else {
+            CookMode::Sloppy
+        };
+        cook(self.content.source.as_ref(), mode)
+            .map_err(|e| self.content.error_at(e.index, e.index + e.len, e))
+    }
+}
+
 /// A regular expression literal
 #[derive(PartialEq, Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
@@ -1736,6 +2030,32 @@ where
     }
 }
 
+impl<T> RegEx<T>
+where
+    T: AsRef<str>,
+{
+    /// Validate the flags and parse the pattern into a tree, errors
+    /// point into the pattern or flags
+    pub fn parse(&self) -> Result<Regex, SliceError<RegexError>> {
+        let flags = self.flags.as_ref().map(|f| f.source.as_ref()).unwrap_or("");
+        regex::parse(self.pattern.source.as_ref(), flags).map_err(|e| match (&e, &self.flags) {
+            (RegexError::Flags(inner), Some(slice)) => {
+                let len = flags[inner.index..]
+                    .chars()
+                    .next()
+                    .map(char::len_utf8)
+                    .unwrap_or(0);
+                slice.error_at(inner.index, inner.index + len, e.clone())
+            }
+            (RegexError::Pattern(inner), _) => {
+                self.pattern
+                    .error_at(inner.span.start, inner.span.end, e.clone())
+            }
+            _ => self.pattern.error_at(0, 0, e.clone()),
+        })
+    }
+}
+
 impl<T> Node for RegEx<T> {
     fn loc(&self) -> SourceLocation {
         let end = if let Some(flags) = &self.flags {
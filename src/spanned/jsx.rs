@@ -0,0 +1,637 @@
+//! JSX extensions to `Expr`, these only appear in
+//! trees for `.jsx` or `.tsx` sources
+use crate::spanned::expr::{Expr, StringLit};
+use crate::spanned::{Ident, Node, Position, Slice, SourceLocation};
+use crate::IntoAllocated;
+
+use super::tokens::{
+    CloseBrace, Colon, Ellipsis, Equal, ForwardSlashGreaterThan, GreaterThan, LessThan,
+    LessThanForwardSlash, OpenBrace, Period, Token,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// An element with a tag name
+/// ```jsx
+/// <div className="thing">{stuff}</div>
+/// <Input value={value} />
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct JsxElement<T> {
+    pub open: JsxOpeningElement<T>,
+    pub children: Vec<JsxChild<T>>,
+    /// `None` when the opening element is self closing
+    pub close: Option<JsxClosingElement<T>>,
+}
+
+impl<T> IntoAllocated for JsxElement<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxElement<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        JsxElement {
+            open: self.open.into_allocated(),
+            children: self
+                .children
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            close: self.close.map(IntoAllocated::into_allocated),
+        }
+    }
+}
+
+impl<T> Node for JsxElement<T> {
+    fn loc(&self) -> SourceLocation {
+        let end = if let Some(close) = &self.close {
+            close.loc().end
+        } else {
+            self.open.loc().end
+        };
+        SourceLocation {
+            start: self.open.loc().start,
+            end,
+        }
+    }
+}
+
+/// `<name attributes>` or `<name attributes />`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct JsxOpeningElement<T> {
+    pub less_than: LessThan,
+    pub name: JsxElementName<T>,
+    pub attributes: Vec<JsxAttr<T>>,
+    pub end: JsxTagEnd,
+}
+
+impl<T> IntoAllocated for JsxOpeningElement<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxOpeningElement<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        JsxOpeningElement {
+            less_than: self.less_than,
+            name: self.name.into_allocated(),
+            attributes: self
+                .attributes
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            end: self.end,
+        }
+    }
+}
+
+impl<T> Node for JsxOpeningElement<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.less_than.start(),
+            end: self.end.end(),
+        }
+    }
+}
+
+/// The token that ends an opening element
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum JsxTagEnd {
+    /// `>`
+    Open(GreaterThan),
+    /// `/>`
+    SelfClosing(ForwardSlashGreaterThan),
+}
+
+impl Token for JsxTagEnd {
+    fn as_str(&self) -> &str {
+        match self {
+            JsxTagEnd::Open(inner) => inner.as_str(),
+            JsxTagEnd::SelfClosing(inner) => inner.as_str(),
+        }
+    }
+
+    fn start(&self) -> Position {
+        match self {
+            JsxTagEnd::Open(inner) => inner.start(),
+            JsxTagEnd::SelfClosing(inner) => inner.start(),
+        }
+    }
+
+    fn end(&self) -> Position {
+        match self {
+            JsxTagEnd::Open(inner) => inner.end(),
+            JsxTagEnd::SelfClosing(inner) => inner.end(),
+        }
+    }
+}
+
+impl JsxTagEnd {
+    pub fn is_self_closing(&self) -> bool {
+        matches!(self, JsxTagEnd::SelfClosing(_))
+    }
+}
+
+/// `</name>`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct JsxClosingElement<T> {
+    pub less_than_slash: LessThanForwardSlash,
+    pub name: JsxElementName<T>,
+    pub greater_than: GreaterThan,
+}
+
+impl<T> IntoAllocated for JsxClosingElement<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxClosingElement<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        JsxClosingElement {
+            less_than_slash: self.less_than_slash,
+            name: self.name.into_allocated(),
+            greater_than: self.greater_than,
+        }
+    }
+}
+
+impl<T> Node for JsxClosingElement<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.less_than_slash.start(),
+            end: self.greater_than.end(),
+        }
+    }
+}
+
+/// An element without a tag name
+/// ```jsx
+/// <><li>one</li><li>two</li></>
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct JsxFragment<T> {
+    pub open_less_than: LessThan,
+    pub open_greater_than: GreaterThan,
+    pub children: Vec<JsxChild<T>>,
+    pub close_less_than_slash: LessThanForwardSlash,
+    pub close_greater_than: GreaterThan,
+}
+
+impl<T> IntoAllocated for JsxFragment<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxFragment<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        JsxFragment {
+            open_less_than: self.open_less_than,
+            open_greater_than: self.open_greater_than,
+            children: self
+                .children
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            close_less_than_slash: self.close_less_than_slash,
+            close_greater_than: self.close_greater_than,
+        }
+    }
+}
+
+impl<T> Node for JsxFragment<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.open_less_than.start(),
+            end: self.close_greater_than.end(),
+        }
+    }
+}
+
+/// The tag name of an element
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum JsxElementName<T> {
+    /// `<div>` or `<Thing>`, may contain `-`
+    Ident(Ident<T>),
+    /// `<svg:rect>`
+    Namespaced(JsxNamespacedName<T>),
+    /// `<Context.Provider>`
+    Member(JsxMemberExpr<T>),
+}
+
+impl<T> IntoAllocated for JsxElementName<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxElementName<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            JsxElementName::Ident(inner) => JsxElementName::Ident(inner.into_allocated()),
+            JsxElementName::Namespaced(inner) => JsxElementName::Namespaced(inner.into_allocated()),
+            JsxElementName::Member(inner) => JsxElementName::Member(inner.into_allocated()),
+        }
+    }
+}
+
+impl<T> Node for JsxElementName<T> {
+    fn loc(&self) -> SourceLocation {
+        match self {
+            JsxElementName::Ident(inner) => inner.loc(),
+            JsxElementName::Namespaced(inner) => inner.loc(),
+            JsxElementName::Member(inner) => inner.loc(),
+        }
+    }
+}
+
+/// `namespace:name`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct JsxNamespacedName<T> {
+    pub namespace: Ident<T>,
+    pub colon: Colon,
+    pub name: Ident<T>,
+}
+
+impl<T> IntoAllocated for JsxNamespacedName<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxNamespacedName<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        JsxNamespacedName {
+            namespace: self.namespace.into_allocated(),
+            colon: self.colon,
+            name: self.name.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for JsxNamespacedName<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.namespace.loc().start,
+            end: self.name.loc().end,
+        }
+    }
+}
+
+/// `object.a.b`, there is always at least one property
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct JsxMemberExpr<T> {
+    pub object: Ident<T>,
+    pub properties: Vec<JsxMemberProp<T>>,
+}
+
+impl<T> IntoAllocated for JsxMemberExpr<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxMemberExpr<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        JsxMemberExpr {
+            object: self.object.into_allocated(),
+            properties: self
+                .properties
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+        }
+    }
+}
+
+impl<T> Node for JsxMemberExpr<T> {
+    fn loc(&self) -> SourceLocation {
+        let end = if let Some(last) = self.properties.last() {
+            last.loc().end
+        } else {
+            self.object.loc().end
+        };
+        SourceLocation {
+            start: self.object.loc().start,
+            end,
+        }
+    }
+}
+
+/// `.name`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct JsxMemberProp<T> {
+    pub dot: Period,
+    pub name: Ident<T>,
+}
+
+impl<T> IntoAllocated for JsxMemberProp<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxMemberProp<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        JsxMemberProp {
+            dot: self.dot,
+            name: self.name.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for JsxMemberProp<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.dot.start(),
+            end: self.name.loc().end,
+        }
+    }
+}
+
+/// A single entry in the attribute list of an element
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum JsxAttr<T> {
+    /// `name="value"`, `name={value}` or just `name`
+    Attr(JsxAttribute<T>),
+    /// `{...props}`
+    Spread(JsxSpread<T>),
+}
+
+impl<T> IntoAllocated for JsxAttr<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxAttr<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            JsxAttr::Attr(inner) => JsxAttr::Attr(inner.into_allocated()),
+            JsxAttr::Spread(inner) => JsxAttr::Spread(inner.into_allocated()),
+        }
+    }
+}
+
+impl<T> Node for JsxAttr<T> {
+    fn loc(&self) -> SourceLocation {
+        match self {
+            JsxAttr::Attr(inner) => inner.loc(),
+            JsxAttr::Spread(inner) => inner.loc(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct JsxAttribute<T> {
+    pub name: JsxAttrName<T>,
+    /// `None` for a bare attribute like `<input disabled />`
+    pub value: Option<JsxAttrInit<T>>,
+}
+
+impl<T> IntoAllocated for JsxAttribute<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxAttribute<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        JsxAttribute {
+            name: self.name.into_allocated(),
+            value: self.value.map(IntoAllocated::into_allocated),
+        }
+    }
+}
+
+impl<T> Node for JsxAttribute<T> {
+    fn loc(&self) -> SourceLocation {
+        let end = if let Some(value) = &self.value {
+            value.loc().end
+        } else {
+            self.name.loc().end
+        };
+        SourceLocation {
+            start: self.name.loc().start,
+            end,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum JsxAttrName<T> {
+    /// `className` or `aria-label`
+    Ident(Ident<T>),
+    /// `xlink:href`
+    Namespaced(JsxNamespacedName<T>),
+}
+
+impl<T> IntoAllocated for JsxAttrName<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxAttrName<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            JsxAttrName::Ident(inner) => JsxAttrName::Ident(inner.into_allocated()),
+            JsxAttrName::Namespaced(inner) => JsxAttrName::Namespaced(inner.into_allocated()),
+        }
+    }
+}
+
+impl<T> Node for JsxAttrName<T> {
+    fn loc(&self) -> SourceLocation {
+        match self {
+            JsxAttrName::Ident(inner) => inner.loc(),
+            JsxAttrName::Namespaced(inner) => inner.loc(),
+        }
+    }
+}
+
+/// `= value`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct JsxAttrInit<T> {
+    pub equal: Equal,
+    pub value: JsxAttrValue<T>,
+}
+
+impl<T> IntoAllocated for JsxAttrInit<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxAttrInit<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        JsxAttrInit {
+            equal: self.equal,
+            value: self.value.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for JsxAttrInit<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.equal.start(),
+            end: self.value.loc().end,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum JsxAttrValue<T> {
+    /// A quoted string, the content is the raw source text
+    /// which has HTML entities but no escape sequences
+    String(StringLit<T>),
+    /// `{value}`
+    Expr(JsxExprContainer<T>),
+    Element(Box<JsxElement<T>>),
+    Fragment(Box<JsxFragment<T>>),
+}
+
+impl<T> IntoAllocated for JsxAttrValue<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxAttrValue<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            JsxAttrValue::String(inner) => JsxAttrValue::String(inner.into_allocated()),
+            JsxAttrValue::Expr(inner) => JsxAttrValue::Expr(inner.into_allocated()),
+            JsxAttrValue::Element(inner) => JsxAttrValue::Element(inner.into_allocated()),
+            JsxAttrValue::Fragment(inner) => JsxAttrValue::Fragment(inner.into_allocated()),
+        }
+    }
+}
+
+impl<T> Node for JsxAttrValue<T> {
+    fn loc(&self) -> SourceLocation {
+        match self {
+            JsxAttrValue::String(inner) => inner.loc(),
+            JsxAttrValue::Expr(inner) => inner.loc(),
+            JsxAttrValue::Element(inner) => inner.loc(),
+            JsxAttrValue::Fragment(inner) => inner.loc(),
+        }
+    }
+}
+
+/// `{value}`, the expression is `None` for an empty
+/// container like `{/* comment */}`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct JsxExprContainer<T> {
+    pub open_brace: OpenBrace,
+    pub expr: Option<Expr<T>>,
+    pub close_brace: CloseBrace,
+}
+
+impl<T> IntoAllocated for JsxExprContainer<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxExprContainer<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        JsxExprContainer {
+            open_brace: self.open_brace,
+            expr: self.expr.map(IntoAllocated::into_allocated),
+            close_brace: self.close_brace,
+        }
+    }
+}
+
+impl<T> Node for JsxExprContainer<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.open_brace.start(),
+            end: self.close_brace.end(),
+        }
+    }
+}
+
+/// `{...value}` as an attribute or a child
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct JsxSpread<T> {
+    pub open_brace: OpenBrace,
+    pub ellipsis: Ellipsis,
+    pub expr: Expr<T>,
+    pub close_brace: CloseBrace,
+}
+
+impl<T> IntoAllocated for JsxSpread<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxSpread<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        JsxSpread {
+            open_brace: self.open_brace,
+            ellipsis: self.ellipsis,
+            expr: self.expr.into_allocated(),
+            close_brace: self.close_brace,
+        }
+    }
+}
+
+impl<T> Node for JsxSpread<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.open_brace.start(),
+            end: self.close_brace.end(),
+        }
+    }
+}
+
+/// A single child of an element or fragment
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum JsxChild<T> {
+    /// The raw source text between tags, including any
+    /// whitespace and HTML entities
+    Text(Slice<T>),
+    Expr(JsxExprContainer<T>),
+    Spread(JsxSpread<T>),
+    Element(JsxElement<T>),
+    Fragment(JsxFragment<T>),
+}
+
+impl<T> IntoAllocated for JsxChild<T>
+where
+    T: ToString,
+{
+    type Allocated = JsxChild<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            JsxChild::Text(inner) => JsxChild::Text(inner.into_allocated()),
+            JsxChild::Expr(inner) => JsxChild::Expr(inner.into_allocated()),
+            JsxChild::Spread(inner) => JsxChild::Spread(inner.into_allocated()),
+            JsxChild::Element(inner) => JsxChild::Element(inner.into_allocated()),
+            JsxChild::Fragment(inner) => JsxChild::Fragment(inner.into_allocated()),
+        }
+    }
+}
+
+impl<T> Node for JsxChild<T> {
+    fn loc(&self) -> SourceLocation {
+        match self {
+            JsxChild::Text(inner) => inner.loc,
+            JsxChild::Expr(inner) => inner.loc(),
+            JsxChild::Spread(inner) => inner.loc(),
+            JsxChild::Element(inner) => inner.loc(),
+            JsxChild::Fragment(inner) => inner.loc(),
+        }
+    }
+}
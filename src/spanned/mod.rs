@@ -1,12 +1,15 @@
 mod convert;
 pub mod decl;
 pub mod expr;
+pub mod jsx;
 pub mod pat;
 pub mod stmt;
 pub mod tokens;
+pub mod ts;
+pub mod visit;
 
 use decl::Decl;
-use expr::{Expr, Lit, Prop};
+use expr::{Expr, Lit, Prop, PropInitKey};
 use pat::Pat;
 use stmt::Stmt;
 
@@ -18,9 +21,11 @@ use serde::{Deserialize, Serialize};
 use self::{
     pat::RestPat,
     tokens::{
-        AssignOp, Asterisk, Async, CloseBrace, CloseParen, Comma, Const, Extends, Function, Let,
-        OpenBrace, OpenParen, Semicolon, Token, Var,
+        Abstract, AssignOp, Asterisk, Async, At, Await, CloseBrace, CloseParen, Comma, Const,
+        Ellipsis, Equal, Extends, Function, Implements, Let, OpenBrace, OpenParen, QuestionMark,
+        Readonly, Semicolon, Static, Token, Using, Var,
     },
+    ts::{Accessibility, TsTypeAnnotation, TsTypeArgs, TsTypeParams, TsTypeRef},
 };
 
 pub trait Node {
@@ -78,13 +83,19 @@ impl<T> Ident<T> {
 /// It is essentially a collection of `ProgramPart`s
 /// with a flag denoting if the representation is
 /// a ES6 Mod or a Script.
+///
+/// The second field is the hashbang, the text after `#!` on
+/// the first line, its location does not include the `#!`
+/// ```js
+/// #!/usr/bin/env node
+/// ```
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum Program<T> {
     /// An ES6 Mod
-    Mod(Vec<ProgramPart<T>>),
+    Mod(Vec<ProgramPart<T>>, Option<Slice<T>>),
     /// Not an ES6 Mod
-    Script(Vec<ProgramPart<T>>),
+    Script(Vec<ProgramPart<T>>, Option<Slice<T>>),
 }
 
 impl<T> IntoAllocated for Program<T>
@@ -94,17 +105,19 @@ where
     type Allocated = Program<String>;
     fn into_allocated(self) -> Program<String> {
         match self {
-            Program::Mod(parts) => Program::Mod(
+            Program::Mod(parts, hashbang) => Program::Mod(
                 parts
                     .into_iter()
                     .map(IntoAllocated::into_allocated)
                     .collect(),
+                hashbang.map(IntoAllocated::into_allocated),
             ),
-            Program::Script(parts) => Program::Script(
+            Program::Script(parts, hashbang) => Program::Script(
                 parts
                     .into_iter()
                     .map(IntoAllocated::into_allocated)
                     .collect(),
+                hashbang.map(IntoAllocated::into_allocated),
             ),
         }
     }
@@ -112,19 +125,97 @@ where
 
 impl<T> Node for Program<T> {
     fn loc(&self) -> SourceLocation {
-        match self {
-            Self::Mod(inner) => inner.loc(),
-            Self::Script(inner) => inner.loc(),
+        let (parts, hashbang) = match self {
+            Self::Mod(parts, hashbang) | Self::Script(parts, hashbang) => (parts, hashbang),
+        };
+        let hashbang = match hashbang {
+            Some(hashbang) => hashbang,
+            None => return parts.loc(),
+        };
+        let end = if parts.is_empty() {
+            hashbang.loc.end
+        } else {
+            parts.loc().end
+        };
+        SourceLocation {
+            start: hashbang.loc.start - 2,
+            end,
         }
     }
 }
 
 impl<T> Program<T> {
     pub fn module(parts: Vec<ProgramPart<T>>) -> Self {
-        Program::Mod(parts)
+        Program::Mod(parts, None)
     }
     pub fn script(parts: Vec<ProgramPart<T>>) -> Self {
-        Program::Script(parts)
+        Program::Script(parts, None)
+    }
+    pub fn with_hashbang(self, hashbang: Slice<T>) -> Self {
+        match self {
+            Program::Mod(parts, _) => Program::Mod(parts, Some(hashbang)),
+            Program::Script(parts, _) => Program::Script(parts, Some(hashbang)),
+        }
+    }
+    pub fn is_module(&self) -> bool {
+        matches!(self, Program::Mod(..))
+    }
+    pub fn parts(&self) -> &[ProgramPart<T>] {
+        match self {
+            Program::Mod(parts, _) | Program::Script(parts, _) => parts,
+        }
+    }
+    pub fn hashbang(&self) -> Option<&Slice<T>> {
+        match self {
+            Program::Mod(_, hashbang) | Program::Script(_, hashbang) => hashbang.as_ref(),
+        }
+    }
+}
+
+/// A whole source file, the `Program` along with the
+/// parts of the file that are not javascript
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct SourceFile<T> {
+    /// Where the file was loaded from, like a path or url
+    pub name: Option<String>,
+    /// The file starts with a `U+FEFF` byte order mark, which
+    /// is not counted in any location
+    pub bom: bool,
+    pub program: Program<T>,
+}
+
+impl<T> IntoAllocated for SourceFile<T>
+where
+    T: ToString,
+{
+    type Allocated = SourceFile<String>;
+    fn into_allocated(self) -> SourceFile<String> {
+        SourceFile {
+            name: self.name,
+            bom: self.bom,
+            program: self.program.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for SourceFile<T> {
+    fn loc(&self) -> SourceLocation {
+        self.program.loc()
+    }
+}
+
+impl<T> SourceFile<T> {
+    pub fn new(program: Program<T>) -> Self {
+        Self {
+            name: None,
+            bom: false,
+            program,
+        }
+    }
+    /// See `Program::hashbang`
+    pub fn hashbang(&self) -> Option<&Slice<T>> {
+        self.program.hashbang()
     }
 }
 
@@ -235,17 +326,28 @@ impl<T> Node for Dir<T> {
 /// var x = function() {}
 /// let y = function q() {}
 /// ```
+/// The body will be `None` for a signature without an
+/// implementation
+/// ```ts
+/// declare function thing(a);
+/// function overloaded(a);
+/// function overloaded(a, b) {}
+/// ```
 #[derive(PartialEq, Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Func<T> {
     pub keyword: Function,
     pub id: Option<Ident<T>>,
+    pub type_params: Option<TsTypeParams<T>>,
     pub open_paren: OpenParen,
     pub params: Vec<ListEntry<FuncArg<T>>>,
     pub close_paren: CloseParen,
-    pub body: FuncBody<T>,
+    pub return_type: Option<TsTypeAnnotation<T>>,
+    pub body: Option<FuncBody<T>>,
     pub star: Option<Asterisk>,
     pub keyword_async: Option<Async>,
+    /// The semicolon ending a signature
+    pub semi_colon: Option<Semicolon>,
 }
 
 impl<T> Func<T> {
@@ -255,6 +357,9 @@ impl<T> Func<T> {
     pub fn generator(&self) -> bool {
         self.star.is_some()
     }
+    pub fn is_signature(&self) -> bool {
+        self.body.is_none()
+    }
 }
 
 impl<T> IntoAllocated for Func<T>
@@ -266,6 +371,7 @@ where
         Func {
             keyword: self.keyword,
             id: self.id.map(|i| i.into_allocated()),
+            type_params: self.type_params.into_allocated(),
             open_paren: self.open_paren,
             params: self
                 .params
@@ -273,9 +379,11 @@ where
                 .map(|p| p.into_allocated())
                 .collect(),
             close_paren: self.close_paren,
-            body: self.body.into_allocated(),
+            return_type: self.return_type.into_allocated(),
+            body: self.body.map(|b| b.into_allocated()),
             star: self.star,
             keyword_async: self.keyword_async,
+            semi_colon: self.semi_colon,
         }
     }
 }
@@ -287,7 +395,15 @@ impl<T> Node for Func<T> {
         } else {
             self.keyword.start()
         };
-        let end = self.body.close_brace.end();
+        let end = if let Some(body) = &self.body {
+            body.close_brace.end()
+        } else if let Some(semi) = &self.semi_colon {
+            semi.end()
+        } else if let Some(return_type) = &self.return_type {
+            return_type.loc().end
+        } else {
+            self.close_paren.end()
+        };
         SourceLocation { start, end }
     }
 }
@@ -331,6 +447,22 @@ pub enum FuncArg<T> {
     Expr(Expr<T>),
     Pat(Pat<T>),
     Rest(Box<RestPat<T>>),
+    /// A constructor parameter with typescript style decorators
+    /// ```ts
+    /// class Thing {
+    ///     constructor(@inject(Db) db) {}
+    /// }
+    /// ```
+    Decorated(Box<DecoratedArg<T>>),
+    /// A parameter with typescript modifiers, a `?` or
+    /// a type annotation
+    /// ```ts
+    /// function thing(a?: string, ...b: number[]) {}
+    /// class Thing {
+    ///     constructor(private readonly c: number = 0) {}
+    /// }
+    /// ```
+    Typed(Box<TypedArg<T>>),
 }
 
 impl<T> IntoAllocated for FuncArg<T>
@@ -343,6 +475,8 @@ where
             FuncArg::Expr(inner) => FuncArg::Expr(inner.into_allocated()),
             FuncArg::Pat(inner) => FuncArg::Pat(inner.into_allocated()),
             FuncArg::Rest(inner) => FuncArg::Rest(inner.into_allocated()),
+            FuncArg::Decorated(inner) => FuncArg::Decorated(inner.into_allocated()),
+            FuncArg::Typed(inner) => FuncArg::Typed(inner.into_allocated()),
         }
     }
 }
@@ -353,10 +487,102 @@ impl<T> Node for FuncArg<T> {
             FuncArg::Expr(inner) => inner.loc(),
             FuncArg::Pat(inner) => inner.loc(),
             FuncArg::Rest(inner) => inner.loc(),
+            FuncArg::Decorated(inner) => inner.loc(),
+            FuncArg::Typed(inner) => inner.loc(),
         }
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct DecoratedArg<T> {
+    pub decorators: Vec<Decorator<T>>,
+    pub arg: FuncArg<T>,
+}
+
+impl<T> IntoAllocated for DecoratedArg<T>
+where
+    T: ToString,
+{
+    type Allocated = DecoratedArg<String>;
+    fn into_allocated(self) -> DecoratedArg<String> {
+        DecoratedArg {
+            decorators: self
+                .decorators
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            arg: self.arg.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for DecoratedArg<T> {
+    fn loc(&self) -> SourceLocation {
+        let end = self.arg.loc().end;
+        let start = if let Some(first) = self.decorators.first() {
+            first.loc().start
+        } else {
+            self.arg.loc().start
+        };
+        SourceLocation { start, end }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TypedArg<T> {
+    pub accessibility: Option<Accessibility>,
+    pub keyword_readonly: Option<Readonly>,
+    pub dots: Option<Ellipsis>,
+    pub pat: Pat<T>,
+    pub question: Option<QuestionMark>,
+    pub type_annotation: Option<TsTypeAnnotation<T>>,
+    pub default: Option<(Equal, Expr<T>)>,
+}
+
+impl<T> IntoAllocated for TypedArg<T>
+where
+    T: ToString,
+{
+    type Allocated = TypedArg<String>;
+    fn into_allocated(self) -> TypedArg<String> {
+        TypedArg {
+            accessibility: self.accessibility,
+            keyword_readonly: self.keyword_readonly,
+            dots: self.dots,
+            pat: self.pat.into_allocated(),
+            question: self.question,
+            type_annotation: self.type_annotation.into_allocated(),
+            default: self.default.map(|(eq, e)| (eq, e.into_allocated())),
+        }
+    }
+}
+
+impl<T> Node for TypedArg<T> {
+    fn loc(&self) -> SourceLocation {
+        let start = if let Some(accessibility) = &self.accessibility {
+            accessibility.start()
+        } else if let Some(keyword) = &self.keyword_readonly {
+            keyword.start()
+        } else if let Some(dots) = &self.dots {
+            dots.start()
+        } else {
+            self.pat.loc().start
+        };
+        let end = if let Some((_, default)) = &self.default {
+            default.loc().end
+        } else if let Some(annotation) = &self.type_annotation {
+            annotation.loc().end
+        } else if let Some(question) = &self.question {
+            question.end()
+        } else {
+            self.pat.loc().end
+        };
+        SourceLocation { start, end }
+    }
+}
+
 /// The block statement that makes up the function's body
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
@@ -418,9 +644,13 @@ impl<T> Node for FuncBody<T> {
 #[derive(PartialEq, Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Class<T> {
+    pub decorators: Vec<Decorator<T>>,
+    pub keyword_abstract: Option<Abstract>,
     pub keyword: tokens::Class,
     pub id: Option<Ident<T>>,
+    pub type_params: Option<TsTypeParams<T>>,
     pub super_class: Option<SuperClass<T>>,
+    pub implements: Option<ClassImplements<T>>,
     pub body: ClassBody<T>,
 }
 
@@ -431,9 +661,17 @@ where
     type Allocated = Class<String>;
     fn into_allocated(self) -> Class<String> {
         Class {
+            decorators: self
+                .decorators
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            keyword_abstract: self.keyword_abstract,
             keyword: self.keyword,
             id: self.id.map(|i| i.into_allocated()),
+            type_params: self.type_params.into_allocated(),
             super_class: self.super_class.map(|s| s.into_allocated()),
+            implements: self.implements.into_allocated(),
             body: self.body.into_allocated(),
         }
     }
@@ -441,18 +679,64 @@ where
 
 impl<T> Node for Class<T> {
     fn loc(&self) -> SourceLocation {
+        let start = if let Some(first) = self.decorators.first() {
+            first.loc().start
+        } else if let Some(keyword) = &self.keyword_abstract {
+            keyword.start()
+        } else {
+            self.keyword.start()
+        };
         SourceLocation {
-            start: self.keyword.start(),
+            start,
             end: self.body.close_brace.end(),
         }
     }
 }
 
+/// `@` followed by an expression, attached to a class,
+/// class member or constructor parameter
+/// ```js
+/// @injectable
+/// class Thing {
+///     @validate(positive)
+///     count = 0;
+/// }
+/// ```
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Decorator<T> {
+    pub at: At,
+    pub expr: Expr<T>,
+}
+
+impl<T> IntoAllocated for Decorator<T>
+where
+    T: ToString,
+{
+    type Allocated = Decorator<String>;
+    fn into_allocated(self) -> Decorator<String> {
+        Decorator {
+            at: self.at,
+            expr: self.expr.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for Decorator<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.at.start(),
+            end: self.expr.loc().end,
+        }
+    }
+}
+
 #[derive(PartialEq, Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct SuperClass<T> {
     pub keyword_extends: Extends,
     pub expr: Expr<T>,
+    pub type_args: Option<TsTypeArgs<T>>,
 }
 
 impl<T> IntoAllocated for SuperClass<T>
@@ -464,6 +748,35 @@ where
         SuperClass {
             keyword_extends: self.keyword_extends,
             expr: self.expr.into_allocated(),
+            type_args: self.type_args.into_allocated(),
+        }
+    }
+}
+
+/// The interfaces a class declares it satisfies
+/// ```ts
+/// class Thing implements Named, Aged {}
+/// ```
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct ClassImplements<T> {
+    pub keyword: Implements,
+    pub types: Vec<ListEntry<TsTypeRef<T>>>,
+}
+
+impl<T> IntoAllocated for ClassImplements<T>
+where
+    T: ToString,
+{
+    type Allocated = ClassImplements<String>;
+    fn into_allocated(self) -> ClassImplements<String> {
+        ClassImplements {
+            keyword: self.keyword,
+            types: self
+                .types
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
         }
     }
 }
@@ -472,7 +785,7 @@ where
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct ClassBody<T> {
     pub open_brace: OpenBrace,
-    pub props: Vec<Prop<T>>,
+    pub members: Vec<ClassMember<T>>,
     pub close_brace: CloseBrace,
 }
 
@@ -484,8 +797,8 @@ where
     fn into_allocated(self) -> ClassBody<String> {
         ClassBody {
             open_brace: self.open_brace,
-            props: self
-                .props
+            members: self
+                .members
                 .into_iter()
                 .map(IntoAllocated::into_allocated)
                 .collect(),
@@ -502,13 +815,174 @@ impl<T> Node for ClassBody<T> {
     }
 }
 
-/// The kind of variable being defined (`var`/`let`/`const`)
+/// A single part of a class body
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum ClassMember<T> {
+    /// A method, accessor or constructor
+    /// ```js
+    /// class Thing {
+    ///     constructor() {}
+    ///     get #stuff() {}
+    /// }
+    /// ```
+    Prop(Prop<T>),
+    /// A field with an optional initializer
+    /// ```js
+    /// class Thing {
+    ///     x = 1;
+    ///     static #count;
+    /// }
+    /// ```
+    Field(ClassField<T>),
+    /// A static initialization block
+    /// ```js
+    /// class Thing {
+    ///     static {
+    ///         Thing.ready = true;
+    ///     }
+    /// }
+    /// ```
+    StaticBlock(StaticBlock<T>),
+}
+
+impl<T> IntoAllocated for ClassMember<T>
+where
+    T: ToString,
+{
+    type Allocated = ClassMember<String>;
+    fn into_allocated(self) -> ClassMember<String> {
+        match self {
+            ClassMember::Prop(inner) => ClassMember::Prop(inner.into_allocated()),
+            ClassMember::Field(inner) => ClassMember::Field(inner.into_allocated()),
+            ClassMember::StaticBlock(inner) => ClassMember::StaticBlock(inner.into_allocated()),
+        }
+    }
+}
+
+impl<T> Node for ClassMember<T> {
+    fn loc(&self) -> SourceLocation {
+        match self {
+            ClassMember::Prop(inner) => inner.loc(),
+            ClassMember::Field(inner) => inner.loc(),
+            ClassMember::StaticBlock(inner) => inner.loc(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct ClassField<T> {
+    pub decorators: Vec<Decorator<T>>,
+    pub accessibility: Option<Accessibility>,
+    pub keyword_static: Option<Static>,
+    pub keyword_readonly: Option<Readonly>,
+    pub key: PropInitKey<T>,
+    pub question: Option<QuestionMark>,
+    pub type_annotation: Option<TsTypeAnnotation<T>>,
+    pub eq: Option<Equal>,
+    pub value: Option<Expr<T>>,
+    pub semi_colon: Option<Semicolon>,
+}
+
+impl<T> IntoAllocated for ClassField<T>
+where
+    T: ToString,
+{
+    type Allocated = ClassField<String>;
+    fn into_allocated(self) -> ClassField<String> {
+        ClassField {
+            decorators: self
+                .decorators
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            accessibility: self.accessibility,
+            keyword_static: self.keyword_static,
+            keyword_readonly: self.keyword_readonly,
+            key: self.key.into_allocated(),
+            question: self.question,
+            type_annotation: self.type_annotation.into_allocated(),
+            eq: self.eq,
+            value: self.value.map(IntoAllocated::into_allocated),
+            semi_colon: self.semi_colon,
+        }
+    }
+}
+
+impl<T> Node for ClassField<T> {
+    fn loc(&self) -> SourceLocation {
+        let start = if let Some(first) = self.decorators.first() {
+            first.loc().start
+        } else if let Some(accessibility) = &self.accessibility {
+            accessibility.start()
+        } else if let Some(keyword) = &self.keyword_static {
+            keyword.start()
+        } else if let Some(keyword) = &self.keyword_readonly {
+            keyword.start()
+        } else {
+            self.key.loc().start
+        };
+        let end = if let Some(semi) = &self.semi_colon {
+            semi.end()
+        } else if let Some(value) = &self.value {
+            value.loc().end
+        } else if let Some(annotation) = &self.type_annotation {
+            annotation.loc().end
+        } else if let Some(question) = &self.question {
+            question.end()
+        } else {
+            self.key.loc().end
+        };
+        SourceLocation { start, end }
+    }
+}
+
+impl<T> ClassField<T> {
+    pub fn computed(&self) -> bool {
+        self.key.brackets.is_some()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct StaticBlock<T> {
+    pub keyword_static: Static,
+    pub body: FuncBody<T>,
+}
+
+impl<T> IntoAllocated for StaticBlock<T>
+where
+    T: ToString,
+{
+    type Allocated = StaticBlock<String>;
+    fn into_allocated(self) -> StaticBlock<String> {
+        StaticBlock {
+            keyword_static: self.keyword_static,
+            body: self.body.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for StaticBlock<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.keyword_static.start(),
+            end: self.body.loc().end,
+        }
+    }
+}
+
+/// The kind of variable being defined (`var`/`let`/`const`/`using`)
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum VarKind {
     Var(Option<Var>),
     Let(Let),
     Const(Const),
+    Using(Using),
+    /// `await using`, no line break is allowed between the keywords
+    AwaitUsing(Await, Using),
 }
 
 impl Node for VarKind {
@@ -517,6 +991,11 @@ impl Node for VarKind {
             VarKind::Var(Some(tok)) => tok.loc(),
             VarKind::Let(tok) => tok.loc(),
             VarKind::Const(tok) => tok.loc(),
+            VarKind::Using(tok) => tok.loc(),
+            VarKind::AwaitUsing(keyword_await, keyword_using) => SourceLocation {
+                start: keyword_await.start(),
+                end: keyword_using.end(),
+            },
             _ => SourceLocation::zero(),
         }
     }
@@ -526,12 +1005,22 @@ impl VarKind {
     pub fn is_var(&self) -> bool {
         matches!(self, VarKind::Var(_))
     }
-    pub const fn len(&self) -> u32 {
+    /// `using` or `await using`
+    pub fn is_using(&self) -> bool {
+        matches!(self, VarKind::Using(_) | VarKind::AwaitUsing(..))
+    }
+    /// The length of the keywords in the source, including
+    /// any whitespace between `await` and `using`
+    pub fn len(&self) -> u32 {
         match self {
             VarKind::Var(Some(_)) => 3,
             VarKind::Var(None) => 0,
             VarKind::Let(_) => 3,
             VarKind::Const(_) => 4,
+            VarKind::Using(_) => 5,
+            VarKind::AwaitUsing(keyword_await, keyword_using) => {
+                keyword_using.end().column - keyword_await.start().column
+            }
         }
     }
 
@@ -569,6 +1058,64 @@ impl<T> Slice<T> {
     }
 }
 
+impl<T> Slice<T>
+where
+    T: AsRef<str>,
+{
+    /// The position of the byte offset `index` into the source of this
+    /// slice, lines after a line break start at column 1
+    pub fn position_of(&self, index: usize) -> Position {
+        let mut pos = self.loc.start;
+        let mut chars = self.source.as_ref()[..index].chars().peekable();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\r' if chars.peek() == Some(&'\n') => {}
+                '\n' | '\r' | '\u{2028}' | '\u{2029}' => {
+                    pos.line += 1;
+                    pos.column = 1;
+                }
+                ch => pos.column += ch.len_utf8() as u32,
+            }
+        }
+        pos
+    }
+
+    /// Attach the location of the byte range `start..end` of
+    /// this slice's source to `error`
+    pub fn error_at<E>(&self, start: usize, end: usize, error: E) -> SliceError<E> {
+        SliceError {
+            error,
+            loc: SourceLocation {
+                start: self.position_of(start),
+                end: self.position_of(end),
+            },
+        }
+    }
+}
+
+/// An error found in the text of a `Slice` along
+/// with the location of the offending text
+#[derive(Debug, Clone, PartialEq)]
+pub struct SliceError<E> {
+    pub error: E,
+    pub loc: SourceLocation,
+}
+
+impl<E> std::fmt::Display for SliceError<E>
+where
+    E: std::fmt::Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}:{})",
+            self.error, self.loc.start.line, self.loc.start.column
+        )
+    }
+}
+
+impl<E> std::error::Error for SliceError<E> where E: std::error::Error {}
+
 #[derive(Debug, Clone, PartialEq, Copy)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct SourceLocation {
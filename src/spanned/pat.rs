@@ -3,7 +3,7 @@ use crate::spanned::Ident;
 use crate::IntoAllocated;
 
 use super::tokens::{CloseBrace, CloseBracket, Comma, Ellipsis, OpenBrace, OpenBracket, Token};
-use super::{AssignOp, ListEntry, Node, SourceLocation};
+use super::{AssignOp, ListEntry, Node, Slice, SourceLocation};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
@@ -16,6 +16,8 @@ pub enum Pat<T> {
     Obj(ObjPat<T>),
     Array(ArrayPat<T>),
     Assign(AssignPat<T>),
+    /// Source that could not be parsed as a pattern
+    Invalid(Slice<T>),
 }
 
 impl<T> IntoAllocated for Pat<T>
@@ -29,6 +31,7 @@ where
             Pat::Obj(inner) => Pat::Obj(inner.into_allocated()),
             Pat::Array(inner) => Pat::Array(inner.into_allocated()),
             Pat::Assign(inner) => Pat::Assign(inner.into_allocated()),
+            Pat::Invalid(inner) => Pat::Invalid(inner.into_allocated()),
         }
     }
 }
@@ -40,6 +43,7 @@ impl<T> Node for Pat<T> {
             Pat::Obj(inner) => inner.loc(),
             Pat::Array(inner) => inner.loc(),
             Pat::Assign(inner) => inner.loc(),
+            Pat::Invalid(inner) => inner.loc,
         }
     }
 }
@@ -11,7 +11,7 @@ use super::tokens::{
     In, Of, OpenBrace, OpenParen, Return, Semicolon, Switch, SwitchCaseKeyword, Throw, Token, Try,
     While, With,
 };
-use super::{ListEntry, Node, SourceLocation};
+use super::{ListEntry, Node, Slice, SourceLocation};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
@@ -207,6 +207,8 @@ pub enum Stmt<T> {
         decls: VarDecls<T>,
         semi_colon: Option<Semicolon>,
     },
+    /// Source that could not be parsed as a statement
+    Invalid(Slice<T>),
 }
 
 impl<T> IntoAllocated for Stmt<T>
@@ -279,6 +281,7 @@ where
                 decls: decls.into_allocated(),
                 semi_colon,
             },
+            Stmt::Invalid(inner) => Stmt::Invalid(inner.into_allocated()),
         }
     }
 }
@@ -401,6 +404,7 @@ impl<T> Node for Stmt<T> {
                 }
                 decls.loc()
             }
+            Stmt::Invalid(inner) => inner.loc,
         }
     }
 }
@@ -99,9 +99,14 @@ where
 }
 
 // Keywords
+define_token!(Abstract, "abstract");
+define_token!(Any, "any");
 define_token!(As, "as");
+define_token!(Asserts, "asserts");
 define_token!(Async, "async");
 define_token!(Await, "await");
+define_token!(BigInt, "bigint");
+define_token!(BooleanKeyword, "boolean");
 define_token!(Break, "break");
 define_token!(Case, "case");
 define_token!(Catch, "catch");
@@ -109,36 +114,63 @@ define_token!(Class, "class");
 define_token!(Const, "const");
 define_token!(Continue, "continue");
 define_token!(Debugger, "debugger");
+define_token!(Declare, "declare");
 define_token!(Default, "default");
 define_token!(Delete, "delete");
 define_token!(Do, "do");
 define_token!(Else, "else");
+define_token!(Enum, "enum");
 define_token!(Export, "export");
 define_token!(Extends, "extends");
-define_token!(Finally, "finally");
-define_token!(From, "from");
-define_token!(Get, "get");
 define_token!(False, "false");
+define_token!(Finally, "finally");
 define_token!(For, "for");
+define_token!(From, "from");
 define_token!(Function, "function");
+define_token!(Get, "get");
+define_token!(Global, "global");
 define_token!(If, "if");
+define_token!(Implements, "implements");
 define_token!(Import, "import");
 define_token!(In, "in");
+define_token!(Infer, "infer");
 define_token!(InstanceOf, "instanceof");
+define_token!(Interface, "interface");
+define_token!(Is, "is");
+define_token!(KeyOf, "keyof");
 define_token!(Let, "let");
+define_token!(Meta, "meta");
+define_token!(Module, "module");
+define_token!(Namespace, "namespace");
+define_token!(Never, "never");
 define_token!(New, "new");
 define_token!(Null, "null");
+define_token!(Number, "number");
+define_token!(Object, "object");
 define_token!(Of, "of");
+define_token!(Private, "private");
+define_token!(Protected, "protected");
+define_token!(Public, "public");
+define_token!(Readonly, "readonly");
+define_token!(Require, "require");
 define_token!(Return, "return");
 define_token!(Set, "set");
 define_token!(Static, "static");
+define_token!(StringKeyword, "string");
 define_token!(Super, "super");
 define_token!(Switch, "switch");
+define_token!(Symbol, "symbol");
+define_token!(Target, "target");
 define_token!(This, "this");
 define_token!(Throw, "throw");
 define_token!(True, "true");
 define_token!(Try, "try");
+define_token!(Type, "type");
 define_token!(TypeOf, "typeof");
+define_token!(Undefined, "undefined");
+define_token!(Unique, "unique");
+define_token!(Unknown, "unknown");
+define_token!(Using, "using");
 define_token!(Var, "var");
 define_token!(Void, "void");
 define_token!(While, "while");
@@ -149,6 +181,7 @@ define_token!(Yield, "yield");
 define_token!(Ampersand, "&");
 define_token!(AmpersandEqual, "&=");
 define_token!(Asterisk, "*");
+define_token!(At, "@");
 define_token!(AsteriskEqual, "*=");
 define_token!(BackTick, "`");
 define_token!(Bang, "!");
@@ -181,10 +214,13 @@ define_token!(Equal, "=");
 define_token!(FatArrow, "=>");
 define_token!(ForwardSlash, "/");
 define_token!(ForwardSlashEqual, "/=");
+define_token!(ForwardSlashGreaterThan, "/>");
 define_token!(GreaterThan, ">");
 define_token!(GreaterThanEqual, ">=");
+define_token!(Hash, "#");
 define_token!(LessThan, "<");
 define_token!(LessThanEqual, "<=");
+define_token!(LessThanForwardSlash, "</");
 define_token!(Minus, "-");
 define_token!(MinusEqual, "-=");
 define_token!(OpenBrace, "{");
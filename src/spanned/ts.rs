@@ -0,0 +1,1449 @@
+//! Typescript type annotations
+//!
+//! These only describe the type system, a program with all of them
+//! removed behaves the same at runtime
+//! ```ts
+//! function first<T>(list: T[], fallback?: T): T | undefined {}
+//! let point: { x: number; y: number };
+//! ```
+use crate::spanned::decl::NamespaceName;
+use crate::spanned::expr::{Lit, PropInitKey};
+use crate::spanned::{FuncArg, Ident, ListEntry, Node, Position, SourceLocation};
+use crate::IntoAllocated;
+
+use super::tokens::{
+    Ampersand, Any, Asserts, BigInt, BooleanKeyword, CloseBrace, CloseBracket, CloseParen, Colon,
+    Comma, Ellipsis, Equal, Extends, FatArrow, GreaterThan, Infer, Is, KeyOf, LessThan, Never, New,
+    Null, Number, Object, OpenBrace, OpenBracket, OpenParen, Pipe, Private, Protected, Public,
+    QuestionMark, Readonly, Semicolon, StringKeyword, Symbol, This, Token, TypeOf, Undefined,
+    Unique, Unknown, Void,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A type, the part of an annotation after the `:`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum TsType<T> {
+    /// A built in type
+    /// ```ts
+    /// let a: any, b: number, c: void;
+    /// ```
+    Keyword(TsKeyword),
+    /// The type of `this`
+    /// ```ts
+    /// class Thing {
+    ///     clone(): this;
+    /// }
+    /// ```
+    This(This),
+    /// A named type with optional type arguments
+    /// ```ts
+    /// let a: Thing, b: Stuff.Thing<string>;
+    /// ```
+    Ref(TsTypeRef<T>),
+    /// A literal type
+    /// ```ts
+    /// let a: 'red', b: 1, c: true;
+    /// ```
+    Lit(Lit<T>),
+    /// ```ts
+    /// let a: string[];
+    /// ```
+    Array(TsArrayType<T>),
+    /// ```ts
+    /// let a: [string, number?, ...boolean[]];
+    /// ```
+    Tuple(TsTupleType<T>),
+    /// An optional tuple element
+    /// ```ts
+    /// let a: [string, number?];
+    /// ```
+    Optional(TsOptionalType<T>),
+    /// A rest tuple element
+    /// ```ts
+    /// let a: [string, ...boolean[]];
+    /// ```
+    Rest(TsRestType<T>),
+    /// ```ts
+    /// let a: string | number;
+    /// ```
+    Union(TsUnionType<T>),
+    /// ```ts
+    /// let a: Named & Aged;
+    /// ```
+    Intersection(TsIntersectionType<T>),
+    /// A function or constructor type
+    /// ```ts
+    /// let a: (b: string) => void, c: new () => Thing;
+    /// ```
+    Func(Box<TsFuncType<T>>),
+    /// An object type literal
+    /// ```ts
+    /// let a: { x: number; (): void };
+    /// ```
+    Object(TsTypeLit<T>),
+    /// The type of a value
+    /// ```ts
+    /// let a: typeof stuff.thing;
+    /// ```
+    Query(TsTypeQuery<T>),
+    /// ```ts
+    /// let a: keyof Thing, b: unique symbol, c: readonly string[];
+    /// ```
+    Operator(TsOperatorType<T>),
+    /// An indexed access type
+    /// ```ts
+    /// let a: Thing['stuff'];
+    /// ```
+    Indexed(TsIndexedType<T>),
+    /// ```ts
+    /// type A<T> = T extends string ? 'string' : 'other';
+    /// ```
+    Conditional(Box<TsConditionalType<T>>),
+    /// A type variable declared in the `extends` clause
+    /// of a conditional type
+    /// ```ts
+    /// type A<T> = T extends Array<infer U> ? U : never;
+    /// ```
+    Infer(TsInferType<T>),
+    /// A type guard or assertion, only allowed as a return type
+    /// ```ts
+    /// function isString(a): a is string;
+    /// function assert(a): asserts a;
+    /// ```
+    Predicate(Box<TsTypePredicate<T>>),
+    /// A type wrapped in parentheses
+    /// ```ts
+    /// let a: (string | number)[];
+    /// ```
+    Paren(TsParenType<T>),
+}
+
+impl<T> IntoAllocated for TsType<T>
+where
+    T: ToString,
+{
+    type Allocated = TsType<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            TsType::Keyword(inner) => TsType::Keyword(inner),
+            TsType::This(inner) => TsType::This(inner),
+            TsType::Ref(inner) => TsType::Ref(inner.into_allocated()),
+            TsType::Lit(inner) => TsType::Lit(inner.into_allocated()),
+            TsType::Array(inner) => TsType::Array(inner.into_allocated()),
+            TsType::Tuple(inner) => TsType::Tuple(inner.into_allocated()),
+            TsType::Optional(inner) => TsType::Optional(inner.into_allocated()),
+            TsType::Rest(inner) => TsType::Rest(inner.into_allocated()),
+            TsType::Union(inner) => TsType::Union(inner.into_allocated()),
+            TsType::Intersection(inner) => TsType::Intersection(inner.into_allocated()),
+            TsType::Func(inner) => TsType::Func(inner.into_allocated()),
+            TsType::Object(inner) => TsType::Object(inner.into_allocated()),
+            TsType::Query(inner) => TsType::Query(inner.into_allocated()),
+            TsType::Operator(inner) => TsType::Operator(inner.into_allocated()),
+            TsType::Indexed(inner) => TsType::Indexed(inner.into_allocated()),
+            TsType::Conditional(inner) => TsType::Conditional(inner.into_allocated()),
+            TsType::Infer(inner) => TsType::Infer(inner.into_allocated()),
+            TsType::Predicate(inner) => TsType::Predicate(inner.into_allocated()),
+            TsType::Paren(inner) => TsType::Paren(inner.into_allocated()),
+        }
+    }
+}
+
+impl<T> Node for TsType<T> {
+    fn loc(&self) -> SourceLocation {
+        match self {
+            TsType::Keyword(inner) => inner.loc(),
+            TsType::This(inner) => inner.loc(),
+            TsType::Ref(inner) => inner.loc(),
+            TsType::Lit(inner) => inner.loc(),
+            TsType::Array(inner) => inner.loc(),
+            TsType::Tuple(inner) => inner.loc(),
+            TsType::Optional(inner) => inner.loc(),
+            TsType::Rest(inner) => inner.loc(),
+            TsType::Union(inner) => inner.loc(),
+            TsType::Intersection(inner) => inner.loc(),
+            TsType::Func(inner) => inner.loc(),
+            TsType::Object(inner) => inner.loc(),
+            TsType::Query(inner) => inner.loc(),
+            TsType::Operator(inner) => inner.loc(),
+            TsType::Indexed(inner) => inner.loc(),
+            TsType::Conditional(inner) => inner.loc(),
+            TsType::Infer(inner) => inner.loc(),
+            TsType::Predicate(inner) => inner.loc(),
+            TsType::Paren(inner) => inner.loc(),
+        }
+    }
+}
+
+/// The built in types
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum TsKeyword {
+    Any(Any),
+    Unknown(Unknown),
+    Number(Number),
+    String(StringKeyword),
+    Boolean(BooleanKeyword),
+    BigInt(BigInt),
+    Symbol(Symbol),
+    Object(Object),
+    Undefined(Undefined),
+    Null(Null),
+    Never(Never),
+    Void(Void),
+}
+
+impl Token for TsKeyword {
+    fn as_str(&self) -> &str {
+        match self {
+            TsKeyword::Any(inner) => inner.as_str(),
+            TsKeyword::Unknown(inner) => inner.as_str(),
+            TsKeyword::Number(inner) => inner.as_str(),
+            TsKeyword::String(inner) => inner.as_str(),
+            TsKeyword::Boolean(inner) => inner.as_str(),
+            TsKeyword::BigInt(inner) => inner.as_str(),
+            TsKeyword::Symbol(inner) => inner.as_str(),
+            TsKeyword::Object(inner) => inner.as_str(),
+            TsKeyword::Undefined(inner) => inner.as_str(),
+            TsKeyword::Null(inner) => inner.as_str(),
+            TsKeyword::Never(inner) => inner.as_str(),
+            TsKeyword::Void(inner) => inner.as_str(),
+        }
+    }
+
+    fn start(&self) -> Position {
+        match self {
+            TsKeyword::Any(inner) => inner.start(),
+            TsKeyword::Unknown(inner) => inner.start(),
+            TsKeyword::Number(inner) => inner.start(),
+            TsKeyword::String(inner) => inner.start(),
+            TsKeyword::Boolean(inner) => inner.start(),
+            TsKeyword::BigInt(inner) => inner.start(),
+            TsKeyword::Symbol(inner) => inner.start(),
+            TsKeyword::Object(inner) => inner.start(),
+            TsKeyword::Undefined(inner) => inner.start(),
+            TsKeyword::Null(inner) => inner.start(),
+            TsKeyword::Never(inner) => inner.start(),
+            TsKeyword::Void(inner) => inner.start(),
+        }
+    }
+
+    fn end(&self) -> Position {
+        match self {
+            TsKeyword::Any(inner) => inner.end(),
+            TsKeyword::Unknown(inner) => inner.end(),
+            TsKeyword::Number(inner) => inner.end(),
+            TsKeyword::String(inner) => inner.end(),
+            TsKeyword::Boolean(inner) => inner.end(),
+            TsKeyword::BigInt(inner) => inner.end(),
+            TsKeyword::Symbol(inner) => inner.end(),
+            TsKeyword::Object(inner) => inner.end(),
+            TsKeyword::Undefined(inner) => inner.end(),
+            TsKeyword::Null(inner) => inner.end(),
+            TsKeyword::Never(inner) => inner.end(),
+            TsKeyword::Void(inner) => inner.end(),
+        }
+    }
+}
+
+/// A possibly dotted type name with its type arguments
+/// ```ts
+/// let a: Stuff.Thing<string>;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsTypeRef<T> {
+    pub name: NamespaceName<T>,
+    pub type_args: Option<TsTypeArgs<T>>,
+}
+
+impl<T> IntoAllocated for TsTypeRef<T>
+where
+    T: ToString,
+{
+    type Allocated = TsTypeRef<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsTypeRef {
+            name: self.name.into_allocated(),
+            type_args: self.type_args.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for TsTypeRef<T> {
+    fn loc(&self) -> SourceLocation {
+        let end = if let Some(args) = &self.type_args {
+            args.close.end()
+        } else {
+            self.name.loc().end
+        };
+        SourceLocation {
+            start: self.name.loc().start,
+            end,
+        }
+    }
+}
+
+/// The type arguments passed to a generic type
+/// ```ts
+/// let a: Map<string, number>;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsTypeArgs<T> {
+    pub open: LessThan,
+    pub args: Vec<ListEntry<TsType<T>>>,
+    pub close: GreaterThan,
+}
+
+impl<T> IntoAllocated for TsTypeArgs<T>
+where
+    T: ToString,
+{
+    type Allocated = TsTypeArgs<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsTypeArgs {
+            open: self.open,
+            args: self
+                .args
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            close: self.close,
+        }
+    }
+}
+
+impl<T> Node for TsTypeArgs<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.open.start(),
+            end: self.close.end(),
+        }
+    }
+}
+
+/// ```ts
+/// let a: string[];
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsArrayType<T> {
+    pub elem_type: Box<TsType<T>>,
+    pub open_bracket: OpenBracket,
+    pub close_bracket: CloseBracket,
+}
+
+impl<T> IntoAllocated for TsArrayType<T>
+where
+    T: ToString,
+{
+    type Allocated = TsArrayType<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsArrayType {
+            elem_type: self.elem_type.into_allocated(),
+            open_bracket: self.open_bracket,
+            close_bracket: self.close_bracket,
+        }
+    }
+}
+
+impl<T> Node for TsArrayType<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.elem_type.loc().start,
+            end: self.close_bracket.end(),
+        }
+    }
+}
+
+/// ```ts
+/// let a: [string, number];
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsTupleType<T> {
+    pub open_bracket: OpenBracket,
+    pub elems: Vec<ListEntry<TsType<T>>>,
+    pub close_bracket: CloseBracket,
+}
+
+impl<T> IntoAllocated for TsTupleType<T>
+where
+    T: ToString,
+{
+    type Allocated = TsTupleType<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsTupleType {
+            open_bracket: self.open_bracket,
+            elems: self
+                .elems
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            close_bracket: self.close_bracket,
+        }
+    }
+}
+
+impl<T> Node for TsTupleType<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.open_bracket.start(),
+            end: self.close_bracket.end(),
+        }
+    }
+}
+
+/// ```ts
+/// let a: [string, number?];
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsOptionalType<T> {
+    pub elem_type: Box<TsType<T>>,
+    pub question: QuestionMark,
+}
+
+impl<T> IntoAllocated for TsOptionalType<T>
+where
+    T: ToString,
+{
+    type Allocated = TsOptionalType<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsOptionalType {
+            elem_type: self.elem_type.into_allocated(),
+            question: self.question,
+        }
+    }
+}
+
+impl<T> Node for TsOptionalType<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.elem_type.loc().start,
+            end: self.question.end(),
+        }
+    }
+}
+
+/// ```ts
+/// let a: [string, ...boolean[]];
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsRestType<T> {
+    pub dots: Ellipsis,
+    pub elem_type: Box<TsType<T>>,
+}
+
+impl<T> IntoAllocated for TsRestType<T>
+where
+    T: ToString,
+{
+    type Allocated = TsRestType<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsRestType {
+            dots: self.dots,
+            elem_type: self.elem_type.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for TsRestType<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.dots.start(),
+            end: self.elem_type.loc().end,
+        }
+    }
+}
+
+/// A union with an optional leading `|`
+/// ```ts
+/// type A =
+///     | 'red'
+///     | 'blue';
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsUnionType<T> {
+    pub leading: Option<Pipe>,
+    pub first: Box<TsType<T>>,
+    pub rest: Vec<(Pipe, TsType<T>)>,
+}
+
+impl<T> IntoAllocated for TsUnionType<T>
+where
+    T: ToString,
+{
+    type Allocated = TsUnionType<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsUnionType {
+            leading: self.leading,
+            first: self.first.into_allocated(),
+            rest: self
+                .rest
+                .into_iter()
+                .map(|(p, t)| (p, t.into_allocated()))
+                .collect(),
+        }
+    }
+}
+
+impl<T> Node for TsUnionType<T> {
+    fn loc(&self) -> SourceLocation {
+        let start = if let Some(leading) = &self.leading {
+            leading.start()
+        } else {
+            self.first.loc().start
+        };
+        let end = if let Some((_, last)) = self.rest.last() {
+            last.loc().end
+        } else {
+            self.first.loc().end
+        };
+        SourceLocation { start, end }
+    }
+}
+
+/// An intersection with an optional leading `&`
+/// ```ts
+/// type A = Named & Aged;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsIntersectionType<T> {
+    pub leading: Option<Ampersand>,
+    pub first: Box<TsType<T>>,
+    pub rest: Vec<(Ampersand, TsType<T>)>,
+}
+
+impl<T> IntoAllocated for TsIntersectionType<T>
+where
+    T: ToString,
+{
+    type Allocated = TsIntersectionType<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsIntersectionType {
+            leading: self.leading,
+            first: self.first.into_allocated(),
+            rest: self
+                .rest
+                .into_iter()
+                .map(|(a, t)| (a, t.into_allocated()))
+                .collect(),
+        }
+    }
+}
+
+impl<T> Node for TsIntersectionType<T> {
+    fn loc(&self) -> SourceLocation {
+        let start = if let Some(leading) = &self.leading {
+            leading.start()
+        } else {
+            self.first.loc().start
+        };
+        let end = if let Some((_, last)) = self.rest.last() {
+            last.loc().end
+        } else {
+            self.first.loc().end
+        };
+        SourceLocation { start, end }
+    }
+}
+
+/// A function type, or a constructor type when
+/// it starts with `new`
+/// ```ts
+/// let a: <T>(b: T) => T, c: new () => Thing;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsFuncType<T> {
+    pub keyword_new: Option<New>,
+    pub type_params: Option<TsTypeParams<T>>,
+    pub open_paren: OpenParen,
+    pub params: Vec<ListEntry<FuncArg<T>>>,
+    pub close_paren: CloseParen,
+    pub arrow: FatArrow,
+    pub return_type: TsType<T>,
+}
+
+impl<T> IntoAllocated for TsFuncType<T>
+where
+    T: ToString,
+{
+    type Allocated = TsFuncType<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsFuncType {
+            keyword_new: self.keyword_new,
+            type_params: self.type_params.into_allocated(),
+            open_paren: self.open_paren,
+            params: self
+                .params
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            close_paren: self.close_paren,
+            arrow: self.arrow,
+            return_type: self.return_type.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for TsFuncType<T> {
+    fn loc(&self) -> SourceLocation {
+        let start = if let Some(keyword) = &self.keyword_new {
+            keyword.start()
+        } else if let Some(params) = &self.type_params {
+            params.open.start()
+        } else {
+            self.open_paren.start()
+        };
+        SourceLocation {
+            start,
+            end: self.return_type.loc().end,
+        }
+    }
+}
+
+/// An object type literal, also used as the body
+/// of an interface
+/// ```ts
+/// let a: { x: number; (): void };
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsTypeLit<T> {
+    pub open_brace: OpenBrace,
+    pub members: Vec<TsTypeMemberEntry<T>>,
+    pub close_brace: CloseBrace,
+}
+
+impl<T> IntoAllocated for TsTypeLit<T>
+where
+    T: ToString,
+{
+    type Allocated = TsTypeLit<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsTypeLit {
+            open_brace: self.open_brace,
+            members: self
+                .members
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            close_brace: self.close_brace,
+        }
+    }
+}
+
+impl<T> Node for TsTypeLit<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.open_brace.start(),
+            end: self.close_brace.end(),
+        }
+    }
+}
+
+/// A member of a `TsTypeLit` with the `,` or `;` after it
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsTypeMemberEntry<T> {
+    pub member: TsTypeMember<T>,
+    pub separator: Option<TsMemberSeparator>,
+}
+
+impl<T> IntoAllocated for TsTypeMemberEntry<T>
+where
+    T: ToString,
+{
+    type Allocated = TsTypeMemberEntry<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsTypeMemberEntry {
+            member: self.member.into_allocated(),
+            separator: self.separator,
+        }
+    }
+}
+
+impl<T> Node for TsTypeMemberEntry<T> {
+    fn loc(&self) -> SourceLocation {
+        if let Some(separator) = &self.separator {
+            return SourceLocation {
+                start: self.member.loc().start,
+                end: separator.end(),
+            };
+        }
+        self.member.loc()
+    }
+}
+
+/// The token between the members of a `TsTypeLit`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum TsMemberSeparator {
+    Comma(Comma),
+    Semicolon(Semicolon),
+}
+
+impl Token for TsMemberSeparator {
+    fn as_str(&self) -> &str {
+        match self {
+            TsMemberSeparator::Comma(inner) => inner.as_str(),
+            TsMemberSeparator::Semicolon(inner) => inner.as_str(),
+        }
+    }
+
+    fn start(&self) -> Position {
+        match self {
+            TsMemberSeparator::Comma(inner) => inner.start(),
+            TsMemberSeparator::Semicolon(inner) => inner.start(),
+        }
+    }
+
+    fn end(&self) -> Position {
+        match self {
+            TsMemberSeparator::Comma(inner) => inner.end(),
+            TsMemberSeparator::Semicolon(inner) => inner.end(),
+        }
+    }
+}
+
+/// ```ts
+/// let a: typeof stuff.thing;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsTypeQuery<T> {
+    pub keyword: TypeOf,
+    pub name: NamespaceName<T>,
+}
+
+impl<T> IntoAllocated for TsTypeQuery<T>
+where
+    T: ToString,
+{
+    type Allocated = TsTypeQuery<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsTypeQuery {
+            keyword: self.keyword,
+            name: self.name.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for TsTypeQuery<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.keyword.start(),
+            end: self.name.loc().end,
+        }
+    }
+}
+
+/// ```ts
+/// let a: keyof Thing, b: unique symbol, c: readonly string[];
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsOperatorType<T> {
+    pub operator: TsTypeOperator,
+    pub operand: Box<TsType<T>>,
+}
+
+impl<T> IntoAllocated for TsOperatorType<T>
+where
+    T: ToString,
+{
+    type Allocated = TsOperatorType<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsOperatorType {
+            operator: self.operator,
+            operand: self.operand.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for TsOperatorType<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.operator.start(),
+            end: self.operand.loc().end,
+        }
+    }
+}
+
+/// The operators that prefix a type
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum TsTypeOperator {
+    KeyOf(KeyOf),
+    Unique(Unique),
+    Readonly(Readonly),
+}
+
+impl Token for TsTypeOperator {
+    fn as_str(&self) -> &str {
+        match self {
+            TsTypeOperator::KeyOf(inner) => inner.as_str(),
+            TsTypeOperator::Unique(inner) => inner.as_str(),
+            TsTypeOperator::Readonly(inner) => inner.as_str(),
+        }
+    }
+
+    fn start(&self) -> Position {
+        match self {
+            TsTypeOperator::KeyOf(inner) => inner.start(),
+            TsTypeOperator::Unique(inner) => inner.start(),
+            TsTypeOperator::Readonly(inner) => inner.start(),
+        }
+    }
+
+    fn end(&self) -> Position {
+        match self {
+            TsTypeOperator::KeyOf(inner) => inner.end(),
+            TsTypeOperator::Unique(inner) => inner.end(),
+            TsTypeOperator::Readonly(inner) => inner.end(),
+        }
+    }
+}
+
+/// ```ts
+/// let a: Thing['stuff'];
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsIndexedType<T> {
+    pub object: Box<TsType<T>>,
+    pub open_bracket: OpenBracket,
+    pub index: Box<TsType<T>>,
+    pub close_bracket: CloseBracket,
+}
+
+impl<T> IntoAllocated for TsIndexedType<T>
+where
+    T: ToString,
+{
+    type Allocated = TsIndexedType<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsIndexedType {
+            object: self.object.into_allocated(),
+            open_bracket: self.open_bracket,
+            index: self.index.into_allocated(),
+            close_bracket: self.close_bracket,
+        }
+    }
+}
+
+impl<T> Node for TsIndexedType<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.object.loc().start,
+            end: self.close_bracket.end(),
+        }
+    }
+}
+
+/// ```ts
+/// type A<T> = T extends string ? 'string' : 'other';
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsConditionalType<T> {
+    pub check_type: TsType<T>,
+    pub keyword_extends: Extends,
+    pub extends_type: TsType<T>,
+    pub question: QuestionMark,
+    pub true_type: TsType<T>,
+    pub colon: Colon,
+    pub false_type: TsType<T>,
+}
+
+impl<T> IntoAllocated for TsConditionalType<T>
+where
+    T: ToString,
+{
+    type Allocated = TsConditionalType<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsConditionalType {
+            check_type: self.check_type.into_allocated(),
+            keyword_extends: self.keyword_extends,
+            extends_type: self.extends_type.into_allocated(),
+            question: self.question,
+            true_type: self.true_type.into_allocated(),
+            colon: self.colon,
+            false_type: self.false_type.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for TsConditionalType<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.check_type.loc().start,
+            end: self.false_type.loc().end,
+        }
+    }
+}
+
+/// ```ts
+/// type A<T> = T extends Array<infer U> ? U : never;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsInferType<T> {
+    pub keyword: Infer,
+    pub name: Ident<T>,
+}
+
+impl<T> IntoAllocated for TsInferType<T>
+where
+    T: ToString,
+{
+    type Allocated = TsInferType<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsInferType {
+            keyword: self.keyword,
+            name: self.name.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for TsInferType<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.keyword.start(),
+            end: self.name.loc().end,
+        }
+    }
+}
+
+/// A type guard, or an assertion when it starts
+/// with `asserts`
+/// ```ts
+/// function isString(a): a is string;
+/// function assert(a): asserts a;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsTypePredicate<T> {
+    pub keyword_asserts: Option<Asserts>,
+    pub param: TsPredicateParam<T>,
+    pub is: Option<(Is, TsType<T>)>,
+}
+
+impl<T> IntoAllocated for TsTypePredicate<T>
+where
+    T: ToString,
+{
+    type Allocated = TsTypePredicate<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsTypePredicate {
+            keyword_asserts: self.keyword_asserts,
+            param: self.param.into_allocated(),
+            is: self.is.map(|(is, t)| (is, t.into_allocated())),
+        }
+    }
+}
+
+impl<T> Node for TsTypePredicate<T> {
+    fn loc(&self) -> SourceLocation {
+        let start = if let Some(keyword) = &self.keyword_asserts {
+            keyword.start()
+        } else {
+            self.param.loc().start
+        };
+        let end = if let Some((_, ty)) = &self.is {
+            ty.loc().end
+        } else {
+            self.param.loc().end
+        };
+        SourceLocation { start, end }
+    }
+}
+
+/// What a `TsTypePredicate` narrows
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum TsPredicateParam<T> {
+    Ident(Ident<T>),
+    This(This),
+}
+
+impl<T> IntoAllocated for TsPredicateParam<T>
+where
+    T: ToString,
+{
+    type Allocated = TsPredicateParam<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            TsPredicateParam::Ident(inner) => TsPredicateParam::Ident(inner.into_allocated()),
+            TsPredicateParam::This(inner) => TsPredicateParam::This(inner),
+        }
+    }
+}
+
+impl<T> Node for TsPredicateParam<T> {
+    fn loc(&self) -> SourceLocation {
+        match self {
+            TsPredicateParam::Ident(inner) => inner.loc(),
+            TsPredicateParam::This(inner) => inner.loc(),
+        }
+    }
+}
+
+/// ```ts
+/// let a: (string | number)[];
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsParenType<T> {
+    pub open_paren: OpenParen,
+    pub inner: Box<TsType<T>>,
+    pub close_paren: CloseParen,
+}
+
+impl<T> IntoAllocated for TsParenType<T>
+where
+    T: ToString,
+{
+    type Allocated = TsParenType<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsParenType {
+            open_paren: self.open_paren,
+            inner: self.inner.into_allocated(),
+            close_paren: self.close_paren,
+        }
+    }
+}
+
+impl<T> Node for TsParenType<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.open_paren.start(),
+            end: self.close_paren.end(),
+        }
+    }
+}
+
+/// The type parameters of a generic declaration
+/// ```ts
+/// function thing<T extends object = {}, U>() {}
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsTypeParams<T> {
+    pub open: LessThan,
+    pub params: Vec<ListEntry<TsTypeParam<T>>>,
+    pub close: GreaterThan,
+}
+
+impl<T> IntoAllocated for TsTypeParams<T>
+where
+    T: ToString,
+{
+    type Allocated = TsTypeParams<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsTypeParams {
+            open: self.open,
+            params: self
+                .params
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            close: self.close,
+        }
+    }
+}
+
+impl<T> Node for TsTypeParams<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.open.start(),
+            end: self.close.end(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsTypeParam<T> {
+    pub name: Ident<T>,
+    pub constraint: Option<(Extends, TsType<T>)>,
+    pub default: Option<(Equal, TsType<T>)>,
+}
+
+impl<T> IntoAllocated for TsTypeParam<T>
+where
+    T: ToString,
+{
+    type Allocated = TsTypeParam<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsTypeParam {
+            name: self.name.into_allocated(),
+            constraint: self.constraint.map(|(e, t)| (e, t.into_allocated())),
+            default: self.default.map(|(e, t)| (e, t.into_allocated())),
+        }
+    }
+}
+
+impl<T> Node for TsTypeParam<T> {
+    fn loc(&self) -> SourceLocation {
+        let end = if let Some((_, ty)) = &self.default {
+            ty.loc().end
+        } else if let Some((_, ty)) = &self.constraint {
+            ty.loc().end
+        } else {
+            self.name.loc().end
+        };
+        SourceLocation {
+            start: self.name.loc().start,
+            end,
+        }
+    }
+}
+
+/// A `:` followed by a type
+/// ```ts
+/// let a: string;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsTypeAnnotation<T> {
+    pub colon: Colon,
+    pub type_annotation: TsType<T>,
+}
+
+impl<T> IntoAllocated for TsTypeAnnotation<T>
+where
+    T: ToString,
+{
+    type Allocated = TsTypeAnnotation<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsTypeAnnotation {
+            colon: self.colon,
+            type_annotation: self.type_annotation.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for TsTypeAnnotation<T> {
+    fn loc(&self) -> SourceLocation {
+        SourceLocation {
+            start: self.colon.start(),
+            end: self.type_annotation.loc().end,
+        }
+    }
+}
+
+/// A single member of an object type literal or interface
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum TsTypeMember<T> {
+    /// ```ts
+    /// interface Thing {
+    ///     readonly stuff?: string;
+    /// }
+    /// ```
+    Prop(TsPropSignature<T>),
+    /// ```ts
+    /// interface Thing {
+    ///     stuff<T>(a: T): void;
+    /// }
+    /// ```
+    Method(TsMethodSignature<T>),
+    /// A call signature, or a construct signature when
+    /// it starts with `new`
+    /// ```ts
+    /// interface Thing {
+    ///     (a: string): void;
+    ///     new (): Thing;
+    /// }
+    /// ```
+    Call(TsCallSignature<T>),
+    /// ```ts
+    /// interface Thing {
+    ///     [key: string]: number;
+    /// }
+    /// ```
+    Index(TsIndexSignature<T>),
+}
+
+impl<T> IntoAllocated for TsTypeMember<T>
+where
+    T: ToString,
+{
+    type Allocated = TsTypeMember<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            TsTypeMember::Prop(inner) => TsTypeMember::Prop(inner.into_allocated()),
+            TsTypeMember::Method(inner) => TsTypeMember::Method(inner.into_allocated()),
+            TsTypeMember::Call(inner) => TsTypeMember::Call(inner.into_allocated()),
+            TsTypeMember::Index(inner) => TsTypeMember::Index(inner.into_allocated()),
+        }
+    }
+}
+
+impl<T> Node for TsTypeMember<T> {
+    fn loc(&self) -> SourceLocation {
+        match self {
+            TsTypeMember::Prop(inner) => inner.loc(),
+            TsTypeMember::Method(inner) => inner.loc(),
+            TsTypeMember::Call(inner) => inner.loc(),
+            TsTypeMember::Index(inner) => inner.loc(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsPropSignature<T> {
+    pub keyword_readonly: Option<Readonly>,
+    pub key: PropInitKey<T>,
+    pub question: Option<QuestionMark>,
+    pub type_annotation: Option<TsTypeAnnotation<T>>,
+}
+
+impl<T> IntoAllocated for TsPropSignature<T>
+where
+    T: ToString,
+{
+    type Allocated = TsPropSignature<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsPropSignature {
+            keyword_readonly: self.keyword_readonly,
+            key: self.key.into_allocated(),
+            question: self.question,
+            type_annotation: self.type_annotation.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for TsPropSignature<T> {
+    fn loc(&self) -> SourceLocation {
+        let start = if let Some(keyword) = &self.keyword_readonly {
+            keyword.start()
+        } else {
+            self.key.loc().start
+        };
+        let end = if let Some(annotation) = &self.type_annotation {
+            annotation.loc().end
+        } else if let Some(question) = &self.question {
+            question.end()
+        } else {
+            self.key.loc().end
+        };
+        SourceLocation { start, end }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsMethodSignature<T> {
+    pub key: PropInitKey<T>,
+    pub question: Option<QuestionMark>,
+    pub type_params: Option<TsTypeParams<T>>,
+    pub open_paren: OpenParen,
+    pub params: Vec<ListEntry<FuncArg<T>>>,
+    pub close_paren: CloseParen,
+    pub return_type: Option<TsTypeAnnotation<T>>,
+}
+
+impl<T> IntoAllocated for TsMethodSignature<T>
+where
+    T: ToString,
+{
+    type Allocated = TsMethodSignature<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsMethodSignature {
+            key: self.key.into_allocated(),
+            question: self.question,
+            type_params: self.type_params.into_allocated(),
+            open_paren: self.open_paren,
+            params: self
+                .params
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            close_paren: self.close_paren,
+            return_type: self.return_type.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for TsMethodSignature<T> {
+    fn loc(&self) -> SourceLocation {
+        let end = if let Some(annotation) = &self.return_type {
+            annotation.loc().end
+        } else {
+            self.close_paren.end()
+        };
+        SourceLocation {
+            start: self.key.loc().start,
+            end,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsCallSignature<T> {
+    pub keyword_new: Option<New>,
+    pub type_params: Option<TsTypeParams<T>>,
+    pub open_paren: OpenParen,
+    pub params: Vec<ListEntry<FuncArg<T>>>,
+    pub close_paren: CloseParen,
+    pub return_type: Option<TsTypeAnnotation<T>>,
+}
+
+impl<T> IntoAllocated for TsCallSignature<T>
+where
+    T: ToString,
+{
+    type Allocated = TsCallSignature<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsCallSignature {
+            keyword_new: self.keyword_new,
+            type_params: self.type_params.into_allocated(),
+            open_paren: self.open_paren,
+            params: self
+                .params
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            close_paren: self.close_paren,
+            return_type: self.return_type.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for TsCallSignature<T> {
+    fn loc(&self) -> SourceLocation {
+        let start = if let Some(keyword) = &self.keyword_new {
+            keyword.start()
+        } else if let Some(params) = &self.type_params {
+            params.open.start()
+        } else {
+            self.open_paren.start()
+        };
+        let end = if let Some(annotation) = &self.return_type {
+            annotation.loc().end
+        } else {
+            self.close_paren.end()
+        };
+        SourceLocation { start, end }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsIndexSignature<T> {
+    pub keyword_readonly: Option<Readonly>,
+    pub open_bracket: OpenBracket,
+    pub param: Ident<T>,
+    pub colon: Colon,
+    pub param_type: TsType<T>,
+    pub close_bracket: CloseBracket,
+    pub type_annotation: TsTypeAnnotation<T>,
+}
+
+impl<T> IntoAllocated for TsIndexSignature<T>
+where
+    T: ToString,
+{
+    type Allocated = TsIndexSignature<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsIndexSignature {
+            keyword_readonly: self.keyword_readonly,
+            open_bracket: self.open_bracket,
+            param: self.param.into_allocated(),
+            colon: self.colon,
+            param_type: self.param_type.into_allocated(),
+            close_bracket: self.close_bracket,
+            type_annotation: self.type_annotation.into_allocated(),
+        }
+    }
+}
+
+impl<T> Node for TsIndexSignature<T> {
+    fn loc(&self) -> SourceLocation {
+        let start = if let Some(keyword) = &self.keyword_readonly {
+            keyword.start()
+        } else {
+            self.open_bracket.start()
+        };
+        SourceLocation {
+            start,
+            end: self.type_annotation.loc().end,
+        }
+    }
+}
+
+/// `public`, `protected` or `private` on a class member
+/// or constructor parameter
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum Accessibility {
+    Public(Public),
+    Protected(Protected),
+    Private(Private),
+}
+
+impl Token for Accessibility {
+    fn as_str(&self) -> &str {
+        match self {
+            Accessibility::Public(inner) => inner.as_str(),
+            Accessibility::Protected(inner) => inner.as_str(),
+            Accessibility::Private(inner) => inner.as_str(),
+        }
+    }
+
+    fn start(&self) -> Position {
+        match self {
+            Accessibility::Public(inner) => inner.start(),
+            Accessibility::Protected(inner) => inner.start(),
+            Accessibility::Private(inner) => inner.start(),
+        }
+    }
+
+    fn end(&self) -> Position {
+        match self {
+            Accessibility::Public(inner) => inner.end(),
+            Accessibility::Protected(inner) => inner.end(),
+            Accessibility::Private(inner) => inner.end(),
+        }
+    }
+}
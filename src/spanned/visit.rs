@@ -0,0 +1,578 @@
+//! Read only traversal of a spanned `Program`
+//!
+//! The same walk as `analysis::visit::Visit` over the nodes that keep
+//! their tokens, for the work that needs the location of what it finds.
+//! Type annotations are never visited since they do not exist at runtime.
+use super::decl::{
+    Decl, DefaultExportDeclValue, ModExportSpecifier, ModuleRef, NamedExportDecl, VarDecl,
+};
+use super::expr::{
+    ArrowFuncBody, AssignLeft, Expr, Lit, MemberIndexer, ObjProp, Prop, PropInitKey, PropKey,
+    PropMethod, PropValue,
+};
+use super::jsx::{JsxAttr, JsxAttrValue, JsxChild, JsxElement, JsxElementName};
+use super::pat::{ArrayPatPart, ObjPatPart, Pat};
+use super::stmt::{LoopInit, LoopLeft, Stmt};
+use super::{
+    Class, ClassMember, Decorator, Func, FuncArg, FuncBody, Ident, ListEntry, Program, ProgramPart,
+};
+
+pub trait Visit<'a, T: 'a> {
+    fn visit_program(&mut self, program: &'a Program<T>) {
+        walk_program(self, program)
+    }
+    /// Any list of statements: a program, function or block body,
+    /// a switch case or a namespace
+    fn visit_parts(&mut self, parts: &'a [ProgramPart<T>]) {
+        walk_parts(self, parts)
+    }
+    fn visit_part(&mut self, part: &'a ProgramPart<T>) {
+        walk_part(self, part)
+    }
+    fn visit_decl(&mut self, decl: &'a Decl<T>) {
+        walk_decl(self, decl)
+    }
+    fn visit_stmt(&mut self, stmt: &'a Stmt<T>) {
+        walk_stmt(self, stmt)
+    }
+    fn visit_expr(&mut self, expr: &'a Expr<T>) {
+        walk_expr(self, expr)
+    }
+    fn visit_pat(&mut self, pat: &'a Pat<T>) {
+        walk_pat(self, pat)
+    }
+    fn visit_func(&mut self, func: &'a Func<T>) {
+        walk_func(self, func)
+    }
+    fn visit_func_arg(&mut self, arg: &'a FuncArg<T>) {
+        walk_func_arg(self, arg)
+    }
+    fn visit_class(&mut self, class: &'a Class<T>) {
+        walk_class(self, class)
+    }
+    fn visit_prop(&mut self, prop: &'a Prop<T>) {
+        walk_prop(self, prop)
+    }
+    /// The tag name is not visited unless it is a `JsxMemberExpr`,
+    /// whose object is always a reference
+    fn visit_jsx_element(&mut self, element: &'a JsxElement<T>) {
+        walk_jsx_element(self, element)
+    }
+    /// An identifier in a reference or binding position
+    fn visit_ident(&mut self, _ident: &'a Ident<T>) {}
+}
+
+pub fn walk_program<'a, T, V>(v: &mut V, program: &'a Program<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    v.visit_parts(program.parts())
+}
+
+pub fn walk_parts<'a, T, V>(v: &mut V, parts: &'a [ProgramPart<T>])
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    for part in parts {
+        v.visit_part(part);
+    }
+}
+
+pub fn walk_part<'a, T, V>(v: &mut V, part: &'a ProgramPart<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    match part {
+        ProgramPart::Dir(_) => {}
+        ProgramPart::Decl(decl) => v.visit_decl(decl),
+        ProgramPart::Stmt(stmt) => v.visit_stmt(stmt),
+    }
+}
+
+pub fn walk_decl<'a, T, V>(v: &mut V, decl: &'a Decl<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    match decl {
+        Decl::Var { decls, .. } => walk_var_decls(v, &decls.decls),
+        Decl::Func(func) => v.visit_func(func),
+        Decl::Class(class) => v.visit_class(class),
+        Decl::Import { .. } | Decl::Invalid(_) => {}
+        Decl::Export { export, .. } => match &export.spec {
+            ModExportSpecifier::Default { value, .. } => match value {
+                DefaultExportDeclValue::Decl(decl) => v.visit_decl(decl),
+                DefaultExportDeclValue::Expr(expr) => v.visit_expr(expr),
+            },
+            ModExportSpecifier::Named(NamedExportDecl::Decl(decl)) => v.visit_decl(decl),
+            ModExportSpecifier::Named(NamedExportDecl::Specifier(_))
+            | ModExportSpecifier::All { .. } => {}
+            ModExportSpecifier::Assign { expr, .. } => v.visit_expr(expr),
+        },
+        Decl::ImportEquals { import, .. } => {
+            if let ModuleRef::Entity(expr) = &import.module_ref {
+                v.visit_expr(expr);
+            }
+        }
+        Decl::Declare { decl, .. } => v.visit_decl(decl),
+        Decl::Module { module, .. } => {
+            if let Some(body) = &module.body {
+                v.visit_parts(&body.parts);
+            }
+        }
+        Decl::Interface(_) | Decl::TypeAlias { .. } => {}
+        Decl::Enum(decl) => {
+            for member in &decl.members {
+                if let Some((_, init)) = &member.item.init {
+                    v.visit_expr(init);
+                }
+            }
+        }
+    }
+}
+
+fn walk_var_decls<'a, T, V>(v: &mut V, decls: &'a [ListEntry<VarDecl<T>>])
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    for decl in decls {
+        walk_var_decl(v, &decl.item);
+    }
+}
+
+fn walk_var_decl<'a, T, V>(v: &mut V, decl: &'a VarDecl<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    v.visit_pat(&decl.id);
+    if let Some(init) = &decl.init {
+        v.visit_expr(init);
+    }
+}
+
+pub fn walk_stmt<'a, T, V>(v: &mut V, stmt: &'a Stmt<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    match stmt {
+        Stmt::Expr { expr, .. } | Stmt::Throw { expr, .. } => v.visit_expr(expr),
+        Stmt::Block(block) => v.visit_parts(&block.stmts),
+        Stmt::Empty(_)
+        | Stmt::Debugger { .. }
+        | Stmt::Break { .. }
+        | Stmt::Continue { .. }
+        | Stmt::Invalid(_) => {}
+        Stmt::With(with) => {
+            v.visit_expr(&with.object);
+            v.visit_stmt(&with.body);
+        }
+        Stmt::Return { value, .. } => {
+            if let Some(expr) = value {
+                v.visit_expr(expr);
+            }
+        }
+        Stmt::Labeled(labeled) => v.visit_stmt(&labeled.body),
+        Stmt::If(stmt) => {
+            v.visit_expr(&stmt.test);
+            v.visit_stmt(&stmt.consequent);
+            if let Some(alternate) = &stmt.alternate {
+                v.visit_stmt(&alternate.body);
+            }
+        }
+        Stmt::Switch(switch) => {
+            v.visit_expr(&switch.discriminant);
+            for case in &switch.cases {
+                if let Some(test) = &case.test {
+                    v.visit_expr(test);
+                }
+                v.visit_parts(&case.consequent);
+            }
+        }
+        Stmt::Try(stmt) => {
+            v.visit_parts(&stmt.block.stmts);
+            if let Some(handler) = &stmt.handler {
+                if let Some(param) = &handler.param {
+                    v.visit_pat(&param.param);
+                }
+                v.visit_parts(&handler.body.stmts);
+            }
+            if let Some(finalizer) = &stmt.finalizer {
+                v.visit_parts(&finalizer.body.stmts);
+            }
+        }
+        Stmt::While(stmt) => {
+            v.visit_expr(&stmt.test);
+            v.visit_stmt(&stmt.body);
+        }
+        Stmt::DoWhile(stmt) => {
+            v.visit_stmt(&stmt.body);
+            v.visit_expr(&stmt.test);
+        }
+        Stmt::For(stmt) => {
+            match &stmt.init {
+                Some(LoopInit::Variable(_, decls)) => walk_var_decls(v, decls),
+                Some(LoopInit::Expr(expr)) => v.visit_expr(expr),
+                None => {}
+            }
+            if let Some(test) = &stmt.test {
+                v.visit_expr(test);
+            }
+            if let Some(update) = &stmt.update {
+                v.visit_expr(update);
+            }
+            v.visit_stmt(&stmt.body);
+        }
+        Stmt::ForIn(stmt) => {
+            walk_loop_left(v, &stmt.left);
+            v.visit_expr(&stmt.right);
+            v.visit_stmt(&stmt.body);
+        }
+        Stmt::ForOf(stmt) => {
+            walk_loop_left(v, &stmt.left);
+            v.visit_expr(&stmt.right);
+            v.visit_stmt(&stmt.body);
+        }
+        Stmt::Var { decls, .. } => walk_var_decls(v, &decls.decls),
+    }
+}
+
+fn walk_loop_left<'a, T, V>(v: &mut V, left: &'a LoopLeft<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    match left {
+        LoopLeft::Expr(expr) => v.visit_expr(expr),
+        LoopLeft::Variable(_, decl) => walk_var_decl(v, decl),
+        LoopLeft::Pat(pat) => v.visit_pat(pat),
+    }
+}
+
+pub fn walk_expr<'a, T, V>(v: &mut V, expr: &'a Expr<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    match expr {
+        Expr::Array(array) => {
+            for element in &array.elements {
+                if let Some(element) = &element.item {
+                    v.visit_expr(element);
+                }
+            }
+        }
+        Expr::ArrowFunc(arrow) => {
+            for param in &arrow.params {
+                v.visit_func_arg(&param.item);
+            }
+            match &arrow.body {
+                ArrowFuncBody::FuncBody(body) => v.visit_parts(&body.stmts),
+                ArrowFuncBody::Expr(expr) => v.visit_expr(expr),
+            }
+        }
+        Expr::ArrowParamPlaceHolder(placeholder) => {
+            for arg in &placeholder.args {
+                v.visit_func_arg(&arg.item);
+            }
+        }
+        Expr::Assign(assign) => {
+            match &assign.left {
+                AssignLeft::Pat(pat) => v.visit_pat(pat),
+                AssignLeft::Expr(expr) => v.visit_expr(expr),
+            }
+            v.visit_expr(&assign.right);
+        }
+        Expr::Await(expr) => v.visit_expr(&expr.expr),
+        Expr::Spread(spread) => v.visit_expr(&spread.expr),
+        Expr::OptionalChain(chain) => v.visit_expr(&chain.expr),
+        Expr::Wrapped(wrapped) => v.visit_expr(&wrapped.expr),
+        Expr::Binary(binary) => {
+            v.visit_expr(&binary.left);
+            v.visit_expr(&binary.right);
+        }
+        Expr::Class(class) => v.visit_class(class),
+        Expr::Call(call) => {
+            v.visit_expr(&call.callee);
+            for arg in &call.arguments {
+                v.visit_expr(&arg.item);
+            }
+        }
+        Expr::Conditional(cond) => {
+            v.visit_expr(&cond.test);
+            v.visit_expr(&cond.consequent);
+            v.visit_expr(&cond.alternate);
+        }
+        Expr::Func(func) => v.visit_func(func),
+        Expr::Ident(ident) => v.visit_ident(ident),
+        Expr::Import(import) => {
+            v.visit_expr(&import.source);
+            if let Some(options) = &import.options {
+                v.visit_expr(options);
+            }
+        }
+        Expr::JsxElement(element) => v.visit_jsx_element(element),
+        Expr::JsxFragment(fragment) => walk_jsx_children(v, &fragment.children),
+        Expr::Lit(Lit::Template(template)) => {
+            for expr in &template.expressions {
+                v.visit_expr(expr);
+            }
+        }
+        Expr::Invalid(_)
+        | Expr::Lit(_)
+        | Expr::MetaProp(_)
+        | Expr::PrivateName(_)
+        | Expr::Super(_)
+        | Expr::This(_) => {}
+        Expr::Logical(logical) => {
+            v.visit_expr(&logical.left);
+            v.visit_expr(&logical.right);
+        }
+        Expr::Member(member) => {
+            v.visit_expr(&member.object);
+            if matches!(
+                member.indexer,
+                MemberIndexer::Computed { .. } | MemberIndexer::OptionalComputed { .. }
+            ) {
+                v.visit_expr(&member.property);
+            }
+        }
+        Expr::New(new) => {
+            v.visit_expr(&new.callee);
+            for arg in &new.arguments {
+                v.visit_expr(&arg.item);
+            }
+        }
+        Expr::Obj(obj) => {
+            for prop in &obj.props {
+                match &prop.item {
+                    ObjProp::Prop(prop) => v.visit_prop(prop),
+                    ObjProp::Spread(spread) => v.visit_expr(&spread.expr),
+                }
+            }
+        }
+        Expr::Sequence(exprs) => {
+            for expr in exprs {
+                v.visit_expr(&expr.item);
+            }
+        }
+        Expr::TaggedTemplate(tagged) => {
+            v.visit_expr(&tagged.tag);
+            for expr in &tagged.quasi.expressions {
+                v.visit_expr(expr);
+            }
+        }
+        Expr::Unary(unary) => v.visit_expr(&unary.argument),
+        Expr::Update(update) => v.visit_expr(&update.argument),
+        Expr::Yield(yield_expr) => {
+            if let Some(arg) = &yield_expr.argument {
+                v.visit_expr(arg);
+            }
+        }
+    }
+}
+
+pub fn walk_pat<'a, T, V>(v: &mut V, pat: &'a Pat<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    match pat {
+        Pat::Ident(ident) => v.visit_ident(ident),
+        Pat::Obj(obj) => {
+            for part in &obj.props {
+                match &part.item {
+                    ObjPatPart::Assign(prop) => v.visit_prop(prop),
+                    ObjPatPart::Rest(rest) => v.visit_pat(&rest.pat),
+                }
+            }
+        }
+        Pat::Array(array) => {
+            for part in &array.elements {
+                match &part.item {
+                    Some(ArrayPatPart::Pat(pat)) => v.visit_pat(pat),
+                    Some(ArrayPatPart::Expr(expr)) => v.visit_expr(expr),
+                    Some(ArrayPatPart::Rest(rest)) => v.visit_pat(&rest.pat),
+                    None => {}
+                }
+            }
+        }
+        Pat::Assign(assign) => {
+            v.visit_pat(&assign.left);
+            v.visit_expr(&assign.right);
+        }
+        Pat::Invalid(_) => {}
+    }
+}
+
+pub fn walk_func<'a, T, V>(v: &mut V, func: &'a Func<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    walk_params(v, &func.params, func.body.as_ref())
+}
+
+fn walk_params<'a, T, V>(
+    v: &mut V,
+    params: &'a [ListEntry<FuncArg<T>>],
+    body: Option<&'a FuncBody<T>>,
+) where
+    V: Visit<'a, T> + ?Sized,
+{
+    for param in params {
+        v.visit_func_arg(&param.item);
+    }
+    if let Some(body) = body {
+        v.visit_parts(&body.stmts);
+    }
+}
+
+pub fn walk_func_arg<'a, T, V>(v: &mut V, arg: &'a FuncArg<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    match arg {
+        FuncArg::Expr(expr) => v.visit_expr(expr),
+        FuncArg::Pat(pat) => v.visit_pat(pat),
+        FuncArg::Rest(rest) => v.visit_pat(&rest.pat),
+        FuncArg::Decorated(decorated) => {
+            walk_decorators(v, &decorated.decorators);
+            v.visit_func_arg(&decorated.arg);
+        }
+        FuncArg::Typed(typed) => {
+            v.visit_pat(&typed.pat);
+            if let Some((_, default)) = &typed.default {
+                v.visit_expr(default);
+            }
+        }
+    }
+}
+
+fn walk_decorators<'a, T, V>(v: &mut V, decorators: &'a [Decorator<T>])
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    for decorator in decorators {
+        v.visit_expr(&decorator.expr);
+    }
+}
+
+pub fn walk_class<'a, T, V>(v: &mut V, class: &'a Class<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    walk_decorators(v, &class.decorators);
+    if let Some(super_class) = &class.super_class {
+        v.visit_expr(&super_class.expr);
+    }
+    for member in &class.body.members {
+        match member {
+            ClassMember::Prop(prop) => v.visit_prop(prop),
+            ClassMember::Field(field) => {
+                walk_decorators(v, &field.decorators);
+                walk_prop_key(v, &field.key);
+                if let Some(value) = &field.value {
+                    v.visit_expr(value);
+                }
+            }
+            ClassMember::StaticBlock(block) => v.visit_parts(&block.body.stmts),
+        }
+    }
+}
+
+pub fn walk_prop<'a, T, V>(v: &mut V, prop: &'a Prop<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    match prop {
+        Prop::Init(init) => {
+            walk_prop_key(v, &init.key);
+            match &init.value {
+                Some(PropValue::Expr(expr)) => v.visit_expr(expr),
+                Some(PropValue::Pat(pat)) => v.visit_pat(pat),
+                Some(PropValue::Method(method)) => walk_method(v, method),
+                None => {}
+            }
+        }
+        Prop::Method(method) => walk_method(v, method),
+        Prop::Ctor(ctor) => {
+            walk_prop_key(v, &ctor.keyword);
+            walk_params(v, &ctor.params, ctor.body.as_ref());
+        }
+        Prop::Get(get) => {
+            walk_decorators(v, &get.decorators);
+            walk_prop_key(v, &get.id);
+            if let Some(body) = &get.body {
+                v.visit_parts(&body.stmts);
+            }
+        }
+        Prop::Set(set) => {
+            walk_decorators(v, &set.decorators);
+            walk_prop_key(v, &set.id);
+            walk_params(v, std::slice::from_ref(&set.arg), set.body.as_ref());
+        }
+    }
+}
+
+fn walk_method<'a, T, V>(v: &mut V, method: &'a PropMethod<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    walk_decorators(v, &method.decorators);
+    walk_prop_key(v, &method.id);
+    walk_params(v, &method.params, method.body.as_ref());
+}
+
+/// Only a computed key holds anything to visit
+fn walk_prop_key<'a, T, V>(v: &mut V, key: &'a PropInitKey<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    if key.brackets.is_none() {
+        return;
+    }
+    match &key.value {
+        PropKey::Expr(expr) => v.visit_expr(expr),
+        PropKey::Pat(pat) => v.visit_pat(pat),
+        PropKey::Lit(_) | PropKey::Private(_) => {}
+    }
+}
+
+pub fn walk_jsx_element<'a, T, V>(v: &mut V, element: &'a JsxElement<T>)
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    if let JsxElementName::Member(member) = &element.open.name {
+        v.visit_ident(&member.object);
+    }
+    for attr in &element.open.attributes {
+        match attr {
+            JsxAttr::Attr(attr) => match attr.value.as_ref().map(|init| &init.value) {
+                Some(JsxAttrValue::Expr(container)) => {
+                    if let Some(expr) = &container.expr {
+                        v.visit_expr(expr);
+                    }
+                }
+                Some(JsxAttrValue::Element(element)) => v.visit_jsx_element(element),
+                Some(JsxAttrValue::Fragment(fragment)) => walk_jsx_children(v, &fragment.children),
+                Some(JsxAttrValue::String(_)) | None => {}
+            },
+            JsxAttr::Spread(spread) => v.visit_expr(&spread.expr),
+        }
+    }
+    walk_jsx_children(v, &element.children);
+}
+
+fn walk_jsx_children<'a, T, V>(v: &mut V, children: &'a [JsxChild<T>])
+where
+    V: Visit<'a, T> + ?Sized,
+{
+    for child in children {
+        match child {
+            JsxChild::Text(_) => {}
+            JsxChild::Expr(container) => {
+                if let Some(expr) = &container.expr {
+                    v.visit_expr(expr);
+                }
+            }
+            JsxChild::Spread(spread) => v.visit_expr(&spread.expr),
+            JsxChild::Element(element) => v.visit_jsx_element(element),
+            JsxChild::Fragment(fragment) => walk_jsx_children(v, &fragment.children),
+        }
+    }
+}
@@ -174,6 +174,8 @@ pub enum Stmt<T> {
     /// var x, y = 'huh?';
     /// ```
     Var(Vec<VarDecl<T>>),
+    /// Source that could not be parsed as a statement
+    Invalid(T),
 }
 
 impl<T> IntoAllocated for Stmt<T>
@@ -203,6 +205,7 @@ where
             Stmt::ForIn(inner) => Stmt::ForIn(inner.into_allocated()),
             Stmt::ForOf(inner) => Stmt::ForOf(inner.into_allocated()),
             Stmt::Var(inner) => Stmt::Var(inner.into_iter().map(|v| v.into_allocated()).collect()),
+            Stmt::Invalid(text) => Stmt::Invalid(text.to_string()),
         }
     }
 }
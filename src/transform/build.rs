@@ -0,0 +1,247 @@
+//! Shorthands for building the nodes a transform emits
+use std::collections::HashSet;
+
+use crate::analysis::scope;
+use crate::decl::{Decl, VarDecl};
+use crate::expr::{
+    ArrowFuncBody, ArrowFuncExpr, AssignExpr, AssignLeft, CallExpr, Expr, Lit, LogicalExpr,
+    MemberExpr, ObjProp, Prop, PropKey, PropValue, StringLit, UnaryExpr,
+};
+use crate::pat::Pat;
+use crate::stmt::Stmt;
+use crate::{
+    AssignOp, FuncArg, FuncBody, Ident, LogicalOp, MemberIndexer, Program, ProgramPart, PropKind,
+    UnaryOp, VarKind,
+};
+
+pub(crate) fn ident<T: From<String>>(name: &str) -> Ident<T> {
+    Ident {
+        name: T::from(name.to_string()),
+    }
+}
+
+pub(crate) fn ident_expr<T: From<String>>(name: &str) -> Expr<T> {
+    Expr::Ident(ident(name))
+}
+
+/// A double quoted string, `value` must not need any escapes
+pub(crate) fn string<T: From<String>>(value: &str) -> Expr<T> {
+    Expr::Lit(Lit::String(StringLit::Double(T::from(value.to_string()))))
+}
+
+/// A double quoted string holding `value`, escaped where needed
+pub(crate) fn string_value<T: From<String>>(value: &str) -> Expr<T> {
+    let units: Vec<u16> = value.encode_utf16().collect();
+    string_units(&units)
+}
+
+/// A double quoted string holding the UTF-16 `units`, escaped where
+/// needed, lone surrogates included
+pub(crate) fn string_units<T: From<String>>(units: &[u16]) -> Expr<T> {
+    let mut raw = String::with_capacity(units.len());
+    for ch in char::decode_utf16(units.iter().copied()) {
+        match ch {
+            Ok('\\') => raw.push_str("\\\\"),
+            Ok('"') => raw.push_str("\\\""),
+            Ok('\n') => raw.push_str("\\n"),
+            Ok('\r') => raw.push_str("\\r"),
+            Ok('\t') => raw.push_str("\\t"),
+            Ok(ch @ ('\u{2028}' | '\u{2029}')) => raw.push_str(&format!("\\u{:04x}", ch as u32)),
+            Ok(ch) if ch.is_control() => raw.push_str(&format!("\\u{:04x}", ch as u32)),
+            Ok(ch) => raw.push(ch),
+            Err(lone) => raw.push_str(&format!("\\u{:04x}", lone.unpaired_surrogate())),
+        }
+    }
+    Expr::Lit(Lit::String(StringLit::Double(T::from(raw))))
+}
+
+/// `void 0`
+pub(crate) fn undefined<T: From<String>>() -> Expr<T> {
+    Expr::Unary(UnaryExpr {
+        operator: UnaryOp::Void,
+        prefix: true,
+        argument: Box::new(Expr::Lit(Lit::Number(T::from("0".to_string())))),
+    })
+}
+
+/// `object.property`
+pub(crate) fn member<T: From<String>>(object: Expr<T>, property: &str) -> Expr<T> {
+    Expr::Member(MemberExpr {
+        object: Box::new(object),
+        property: Box::new(ident_expr(property)),
+        indexer: MemberIndexer::Period,
+    })
+}
+
+/// `object[property]`, or `object.#property` for a private name
+pub(crate) fn index<T>(object: Expr<T>, property: Expr<T>) -> Expr<T> {
+    let indexer = if matches!(property, Expr::PrivateName(_)) {
+        MemberIndexer::Period
+    } else {
+        MemberIndexer::Computed
+    };
+    Expr::Member(MemberExpr {
+        object: Box::new(object),
+        property: Box::new(property),
+        indexer,
+    })
+}
+
+pub(crate) fn call<T>(callee: Expr<T>, arguments: Vec<Expr<T>>) -> Expr<T> {
+    Expr::Call(CallExpr {
+        optional: false,
+        callee: Box::new(callee),
+        arguments,
+    })
+}
+
+/// `left = right`
+pub(crate) fn assign<T>(left: Expr<T>, right: Expr<T>) -> Expr<T> {
+    Expr::Assign(AssignExpr {
+        operator: AssignOp::Equal,
+        left: AssignLeft::Expr(Box::new(left)),
+        right: Box::new(right),
+    })
+}
+
+/// `left ?? right`
+pub(crate) fn nullish<T>(left: Expr<T>, right: Expr<T>) -> Expr<T> {
+    Expr::Logical(LogicalExpr {
+        operator: LogicalOp::NullishCoalescing,
+        left: Box::new(left),
+        right: Box::new(right),
+    })
+}
+
+/// `(params) => body`
+pub(crate) fn arrow<T: From<String>>(params: &[&str], body: Expr<T>) -> Expr<T> {
+    Expr::ArrowFunc(ArrowFuncExpr {
+        id: None,
+        type_params: Vec::new(),
+        params: arrow_params(params),
+        return_type: None,
+        body: ArrowFuncBody::Expr(Box::new(body)),
+        expression: true,
+        generator: false,
+        is_async: false,
+    })
+}
+
+/// `(params) => { body }`
+pub(crate) fn arrow_block<T: From<String>>(params: &[&str], body: Vec<ProgramPart<T>>) -> Expr<T> {
+    Expr::ArrowFunc(ArrowFuncExpr {
+        id: None,
+        type_params: Vec::new(),
+        params: arrow_params(params),
+        return_type: None,
+        body: ArrowFuncBody::FuncBody(FuncBody(body)),
+        expression: false,
+        generator: false,
+        is_async: false,
+    })
+}
+
+fn arrow_params<T: From<String>>(params: &[&str]) -> Vec<FuncArg<T>> {
+    params
+        .iter()
+        .map(|name| FuncArg::Pat(Pat::Ident(ident(name))))
+        .collect()
+}
+
+/// An object literal with identifier keys
+pub(crate) fn object<T: From<String>>(props: Vec<(&str, Expr<T>)>) -> Expr<T> {
+    Expr::Obj(
+        props
+            .into_iter()
+            .map(|(key, value)| {
+                ObjProp::Prop(Prop {
+                    key: PropKey::Expr(ident_expr(key)),
+                    value: PropValue::Expr(value),
+                    kind: PropKind::Init,
+                    method: false,
+                    computed: false,
+                    short_hand: false,
+                    is_static: false,
+                    is_abstract: false,
+                    decorators: Vec::new(),
+                    accessibility: None,
+                    is_optional: false,
+                })
+            })
+            .collect(),
+    )
+}
+
+pub(crate) fn expr_stmt<T>(expr: Expr<T>) -> ProgramPart<T> {
+    ProgramPart::Stmt(Stmt::Expr(expr))
+}
+
+/// A single declaration of `kind` with one declarator per entry
+pub(crate) fn var<T: From<String>>(
+    kind: VarKind,
+    decls: Vec<(&str, Option<Expr<T>>)>,
+) -> ProgramPart<T> {
+    ProgramPart::Decl(Decl::Var(
+        kind,
+        decls
+            .into_iter()
+            .map(|(name, init)| VarDecl {
+                id: Pat::Ident(ident(name)),
+                type_annotation: None,
+                init,
+            })
+            .collect(),
+    ))
+}
+
+/// Insert `part` after any directive prologue so `"use client"`
+/// and friends stay first
+pub(crate) fn insert_after_directives<T>(parts: &mut Vec<ProgramPart<T>>, part: ProgramPart<T>) {
+    let index = parts
+        .iter()
+        .position(|part| !matches!(part, ProgramPart::Dir(_)))
+        .unwrap_or(parts.len());
+    parts.insert(index, part);
+}
+
+/// Names for the helpers a transform declares
+pub(crate) struct Names {
+    taken: HashSet<String>,
+}
+
+impl Names {
+    /// Hands out names that no binding or reference in `program` uses
+    pub(crate) fn new<T: AsRef<str>>(program: &Program<T>) -> Self {
+        let scopes = scope::analyze(program);
+        let taken = scopes
+            .bindings()
+            .iter()
+            .map(|binding| binding.name.as_ref())
+            .chain(
+                scopes
+                    .references()
+                    .iter()
+                    .map(|reference| reference.ident.name.as_ref()),
+            )
+            .map(str::to_string)
+            .collect();
+        Names { taken }
+    }
+
+    /// Keep `name` from being handed out
+    pub(crate) fn reserve(&mut self, name: &str) {
+        self.taken.insert(name.to_string());
+    }
+
+    /// `prefix`, or `prefix2`, `prefix3`... when it is taken
+    pub(crate) fn fresh(&mut self, prefix: &str) -> String {
+        let mut name = prefix.to_string();
+        let mut count = 1;
+        while self.taken.contains(&name) {
+            count += 1;
+            name = format!("{}{}", prefix, count);
+        }
+        self.taken.insert(name.clone());
+        name
+    }
+}
@@ -0,0 +1,739 @@
+//! Dead code elimination
+//!
+//! Code that can never run or whose result is never used is removed
+//! ```js
+//! import { log } from './log.js';
+//! const DEBUG = false, unused = [1, 2];
+//! function trace(message) {
+//!     if (false) log(message);
+//! }
+//! export function run(step) {
+//!     if (typeof step === 'function') {
+//!         return step();
+//!         var pending = true;
+//!         cleanup();
+//!     }
+//!     throw new Error('not a function');
+//!     ;
+//! }
+//! ```
+//! becomes
+//! ```js
+//! import { log } from './log.js';
+//! export function run(step) {
+//!     if (typeof step === 'function') {
+//!         return step();
+//!         var pending;
+//!     }
+//!     throw new Error('not a function');
+//! }
+//! ```
+//! - statements after a `return`, `throw`, `break` or `continue` in the
+//!   same list are removed, leaving the declarations that are hoisted
+//!   out of them: functions unchanged, `var` names without a value and
+//!   `let`, `const` and `class` names as a `let` that is never reached
+//! - an `if` whose test folds to a constant, see `fold`, is replaced
+//!   with the branch it takes, with a `var` for the names declared in
+//!   the other one
+//! - empty statements and empty blocks are removed from statement lists
+//! - in a module, top level function declarations that are only used
+//!   inside of themselves and variables that are never read or written
+//!   after their declaration are removed, when their value is pure by
+//!   `analysis::purity` with the default options
+//!
+//! Nothing is removed from a program that calls `eval` or uses `with`,
+//! either can reach bindings by a name that is not written in the
+//! source, or from one with an `Invalid` node, whose bindings and
+//! references are unknown. A statement that declares a function inside
+//! of a block is kept, sloppy mode code can see those functions outside
+//! of the block.
+use crate::analysis::purity::{self, PurityOptions};
+use crate::analysis::scope::{self, RefKind, ScopeId, ScopeKind, ScopeTree};
+use crate::analysis::visit::Visit;
+use crate::decl::{Decl, VarDecl};
+use crate::expr::{Expr, Lit};
+use crate::pat::Pat;
+use crate::stmt::{BlockStmt, IfStmt, LoopInit, LoopLeft, Stmt};
+use crate::transform::fold::truthiness;
+use crate::transform::visit::{walk_parts, walk_stmt, VisitMut};
+use crate::{Func, Ident, Program, ProgramPart, VarKind};
+
+/// Remove the dead code in `program`, which is returned unchanged when
+/// it uses `eval` or `with` or has an `Invalid` node
+pub fn eliminate<T>(mut program: Program<T>) -> Program<T>
+where
+    T: Clone + AsRef<str> + From<String>,
+{
+    if uses_dynamic_scope(&program) || has_invalid(&program) {
+        return program;
+    }
+    Eliminator.visit_program(&mut program);
+    remove_unused(&mut program);
+    program
+}
+
+/// If `program` calls `eval` or uses `with`, which make the bindings
+/// a name refers to unknown until it runs
+pub(crate) fn uses_dynamic_scope<T>(program: &Program<T>) -> bool
+where
+    T: AsRef<str>,
+{
+    let mut dynamic = Dynamic { found: false };
+    dynamic.visit_program(program);
+    dynamic.found
+}
+
+/// Looks for `eval` and `with`
+struct Dynamic {
+    found: bool,
+}
+
+impl<'a, T> Visit<'a, T> for Dynamic
+where
+    T: AsRef<str> + 'a,
+{
+    fn visit_stmt(&mut self, stmt: &'a Stmt<T>) {
+        if let Stmt::With(_) = stmt {
+            self.found = true;
+        }
+        crate::analysis::visit::walk_stmt(self, stmt)
+    }
+
+    fn visit_ident(&mut self, ident: &'a Ident<T>) {
+        if ident.name.as_ref() == "eval" {
+            self.found = true;
+        }
+    }
+}
+
+/// If `program` has an `Invalid` declaration, statement, expression or
+/// pattern
+fn has_invalid<T>(program: &Program<T>) -> bool
+where
+    T: AsRef<str>,
+{
+    let mut invalid = Invalid { found: false };
+    invalid.visit_program(program);
+    invalid.found
+}
+
+/// Looks for `Invalid` nodes
+struct Invalid {
+    found: bool,
+}
+
+impl<'a, T> Visit<'a, T> for Invalid
+where
+    T: AsRef<str> + 'a,
+{
+    fn visit_decl(&mut self, decl: &'a Decl<T>) {
+        self.found |= matches!(decl, Decl::Invalid(_));
+        crate::analysis::visit::walk_decl(self, decl)
+    }
+
+    fn visit_stmt(&mut self, stmt: &'a Stmt<T>) {
+        self.found |= matches!(stmt, Stmt::Invalid(_));
+        crate::analysis::visit::walk_stmt(self, stmt)
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr<T>) {
+        self.found |= matches!(expr, Expr::Invalid(_));
+        crate::analysis::visit::walk_expr(self, expr)
+    }
+
+    fn visit_pat(&mut self, pat: &'a Pat<T>) {
+        self.found |= matches!(pat, Pat::Invalid(_));
+        crate::analysis::visit::walk_pat(self, pat)
+    }
+}
+
+struct Eliminator;
+
+impl<T> VisitMut<T> for Eliminator
+where
+    T: Clone + AsRef<str> + From<String>,
+{
+    fn visit_parts(&mut self, parts: &mut Vec<ProgramPart<T>>) {
+        walk_parts(self, parts);
+        if let Some(last) = parts.iter().position(is_terminator) {
+            let unreachable = parts.split_off(last + 1);
+            parts.extend(remnants(unreachable));
+        }
+        remove_empty(parts);
+    }
+
+    fn visit_stmt(&mut self, stmt: &mut Stmt<T>) {
+        walk_stmt(self, stmt);
+        let taken = match stmt {
+            Stmt::If(stmt) => truthiness(&stmt.test).filter(|taken| {
+                let skipped = if *taken {
+                    stmt.alternate.as_deref()
+                } else {
+                    Some(&*stmt.consequent)
+                };
+                skipped.is_none_or(|skipped| !Hoisted::of(skipped).functions)
+            }),
+            _ => None,
+        };
+        if let Some(taken) = taken {
+            if let Stmt::If(if_stmt) = std::mem::replace(stmt, Stmt::Empty) {
+                *stmt = branch(if_stmt, taken);
+            }
+        }
+    }
+}
+
+/// If control never continues past `part` to the next statement
+fn is_terminator<T>(part: &ProgramPart<T>) -> bool {
+    matches!(
+        part,
+        ProgramPart::Stmt(Stmt::Return(_) | Stmt::Throw(_) | Stmt::Break(_) | Stmt::Continue(_))
+    )
+}
+
+/// The declarations that outlive the unreachable `parts`
+fn remnants<T>(parts: Vec<ProgramPart<T>>) -> Vec<ProgramPart<T>>
+where
+    T: Clone + AsRef<str>,
+{
+    let mut vars = Hoisted::new();
+    let mut kept = Vec::new();
+    for part in parts {
+        match part {
+            ProgramPart::Dir(_) => {}
+            ProgramPart::Decl(Decl::Var(VarKind::Var, decls)) => {
+                for decl in &decls {
+                    vars.pat(&decl.id);
+                }
+            }
+            ProgramPart::Decl(Decl::Var(_, decls)) => {
+                let mut names = Hoisted::new();
+                for decl in &decls {
+                    names.pat(&decl.id);
+                }
+                kept.push(declare(VarKind::Let, names.vars));
+            }
+            ProgramPart::Decl(Decl::Class(class)) => {
+                if let Some(id) = class.id {
+                    kept.push(declare(VarKind::Let, vec![id.name]));
+                }
+            }
+            ProgramPart::Decl(decl) => kept.push(ProgramPart::Decl(decl)),
+            ProgramPart::Stmt(stmt) => {
+                let hoisted = Hoisted::of(&stmt);
+                if hoisted.functions {
+                    kept.push(ProgramPart::Stmt(stmt));
+                } else {
+                    for name in hoisted.vars {
+                        vars.name(name);
+                    }
+                }
+            }
+        }
+    }
+    if !vars.vars.is_empty() {
+        kept.insert(0, declare(VarKind::Var, vars.vars));
+    }
+    kept
+}
+
+/// Remove the empty statements and blocks in `parts`, except one that
+/// keeps a string statement out of the directive prologue
+fn remove_empty<T>(parts: &mut Vec<ProgramPart<T>>)
+where
+    T: AsRef<str>,
+{
+    let mut prologue = true;
+    let mut index = 0;
+    while index < parts.len() {
+        let empty = match &parts[index] {
+            ProgramPart::Stmt(Stmt::Empty) => true,
+            ProgramPart::Stmt(Stmt::Block(block)) => block.0.is_empty(),
+            _ => false,
+        };
+        let guards = prologue
+            && matches!(
+                parts.get(index + 1),
+                Some(ProgramPart::Stmt(Stmt::Expr(Expr::Lit(Lit::String(_)))))
+            );
+        if empty && !guards {
+            parts.remove(index);
+        } else {
+            prologue = prologue && matches!(parts[index], ProgramPart::Dir(_));
+            index += 1;
+        }
+    }
+}
+
+/// The branch of `stmt` that `taken` selects, declaring the `var`s of
+/// the other one
+fn branch<T>(stmt: IfStmt<T>, taken: bool) -> Stmt<T>
+where
+    T: Clone + AsRef<str>,
+{
+    let (chosen, skipped) = if taken {
+        (Some(stmt.consequent), stmt.alternate)
+    } else {
+        (stmt.alternate, Some(stmt.consequent))
+    };
+    let vars = match skipped {
+        Some(skipped) => Hoisted::of(&skipped).vars,
+        None => Vec::new(),
+    };
+    if vars.is_empty() {
+        return match chosen {
+            Some(chosen) => *chosen,
+            None => Stmt::Empty,
+        };
+    }
+    let chosen = match chosen {
+        Some(chosen) => *chosen,
+        None => return Stmt::Var(declarators(vars)),
+    };
+    let declaration = declare(VarKind::Var, vars);
+    match chosen {
+        Stmt::Block(BlockStmt(mut parts)) => {
+            parts.insert(0, declaration);
+            Stmt::Block(BlockStmt(parts))
+        }
+        chosen => Stmt::Block(BlockStmt(vec![declaration, ProgramPart::Stmt(chosen)])),
+    }
+}
+
+fn declare<T>(kind: VarKind, names: Vec<T>) -> ProgramPart<T> {
+    ProgramPart::Decl(Decl::Var(kind, declarators(names)))
+}
+
+fn declarators<T>(names: Vec<T>) -> Vec<VarDecl<T>> {
+    names
+        .into_iter()
+        .map(|name| VarDecl {
+            id: Pat::Ident(Ident { name }),
+            type_annotation: None,
+            init: None,
+        })
+        .collect()
+}
+
+/// The names a statement declares outside of itself
+struct Hoisted<T> {
+    vars: Vec<T>,
+    /// If a function is declared in one of its blocks
+    functions: bool,
+}
+
+impl<T> Hoisted<T>
+where
+    T: Clone + AsRef<str>,
+{
+    fn new() -> Self {
+        Hoisted {
+            vars: Vec::new(),
+            functions: false,
+        }
+    }
+
+    fn of(stmt: &Stmt<T>) -> Self {
+        let mut hoisted = Hoisted::new();
+        hoisted.stmt(stmt);
+        hoisted
+    }
+
+    fn name(&mut self, name: T) {
+        if !self.vars.iter().any(|var| var.as_ref() == name.as_ref()) {
+            self.vars.push(name);
+        }
+    }
+
+    fn pat(&mut self, pat: &Pat<T>) {
+        for ident in pat.bound_names() {
+            self.name(ident.name.clone());
+        }
+    }
+
+    fn parts(&mut self, parts: &[ProgramPart<T>]) {
+        for part in parts {
+            match part {
+                ProgramPart::Decl(Decl::Func(_)) => self.functions = true,
+                ProgramPart::Decl(Decl::Var(VarKind::Var, decls)) => {
+                    for decl in decls {
+                        self.pat(&decl.id);
+                    }
+                }
+                ProgramPart::Stmt(stmt) => self.stmt(stmt),
+                _ => {}
+            }
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt<T>) {
+        match stmt {
+            Stmt::Var(decls) => {
+                for decl in decls {
+                    self.pat(&decl.id);
+                }
+            }
+            Stmt::Block(block) => self.parts(&block.0),
+            Stmt::With(with) => self.stmt(&with.body),
+            Stmt::Labeled(labeled) => self.stmt(&labeled.body),
+            Stmt::If(stmt) => {
+                self.stmt(&stmt.consequent);
+                if let Some(alternate) = &stmt.alternate {
+                    self.stmt(alternate);
+                }
+            }
+            Stmt::Switch(switch) => {
+                for case in &switch.cases {
+                    self.parts(&case.consequent);
+                }
+            }
+            Stmt::Try(stmt) => {
+                self.parts(&stmt.block.0);
+                if let Some(handler) = &stmt.handler {
+                    self.parts(&handler.body.0);
+                }
+                if let Some(finalizer) = &stmt.finalizer {
+                    self.parts(&finalizer.0);
+                }
+            }
+            Stmt::While(stmt) => self.stmt(&stmt.body),
+            Stmt::DoWhile(stmt) => self.stmt(&stmt.body),
+            Stmt::For(stmt) => {
+                if let Some(LoopInit::Variable(VarKind::Var, decls)) = &stmt.init {
+                    for decl in decls {
+                        self.pat(&decl.id);
+                    }
+                }
+                self.stmt(&stmt.body);
+            }
+            Stmt::ForIn(stmt) => {
+                if let LoopLeft::Variable(VarKind::Var, decl) = &stmt.left {
+                    self.pat(&decl.id);
+                }
+                self.stmt(&stmt.body);
+            }
+            Stmt::ForOf(stmt) => {
+                if let LoopLeft::Variable(VarKind::Var, decl) = &stmt.left {
+                    self.pat(&decl.id);
+                }
+                self.stmt(&stmt.body);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Remove the unused declarations at the top level of a module, until
+/// removing one leaves no other unused
+fn remove_unused<T>(program: &mut Program<T>)
+where
+    T: AsRef<str>,
+{
+    loop {
+        let unused = unused(program);
+        let parts = match program {
+            Program::Mod(parts, _) if !unused.is_empty() => parts,
+            _ => return,
+        };
+        for (index, declarator) in unused.into_iter().rev() {
+            match declarator {
+                None => {
+                    parts.remove(index);
+                }
+                Some(declarator) => {
+                    if let ProgramPart::Decl(Decl::Var(_, decls)) = &mut parts[index] {
+                        decls.remove(declarator);
+                        if decls.is_empty() {
+                            parts.remove(index);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The top level functions and declarators of a module that can be
+/// removed, by the index of their part and of the declarator
+fn unused<T>(program: &Program<T>) -> Vec<(usize, Option<usize>)>
+where
+    T: AsRef<str>,
+{
+    let parts = match program {
+        Program::Mod(parts, _) => parts,
+        Program::Script(..) => return Vec::new(),
+    };
+    let tree = scope::analyze(program);
+    let purities = purity::analyze(program, &tree, &PurityOptions::default());
+    let mut unused = Vec::new();
+    for (index, part) in parts.iter().enumerate() {
+        match part {
+            ProgramPart::Decl(Decl::Func(func)) => {
+                if let Some(id) = &func.id {
+                    if !is_used(&tree, id, Some(func)) {
+                        unused.push((index, None));
+                    }
+                }
+            }
+            ProgramPart::Decl(Decl::Var(kind, decls))
+                if !matches!(kind, VarKind::Using | VarKind::AwaitUsing) =>
+            {
+                for (declarator, decl) in decls.iter().enumerate() {
+                    if let Pat::Ident(id) = &decl.id {
+                        if decl.init.as_ref().is_none_or(|init| purities.is_pure(init))
+                            && !is_used(&tree, id, None)
+                        {
+                            unused.push((index, Some(declarator)));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    unused
+}
+
+/// If the top level binding `id` is read or written anywhere outside
+/// of `func`, its initializer does not count
+fn is_used<T>(tree: &ScopeTree<'_, T>, id: &Ident<T>, func: Option<&Func<T>>) -> bool
+where
+    T: AsRef<str>,
+{
+    let binding = match tree.lookup(tree.root(), id.name.as_ref()) {
+        Some(binding) => binding,
+        None => return true,
+    };
+    tree.references_to(binding).into_iter().any(|reference| {
+        let reference = tree.reference(reference);
+        reference.kind != RefKind::Init
+            && !func.is_some_and(|func| is_inside(tree, reference.scope, func))
+    })
+}
+
+/// If `scope` is the scope of `func` or one nested in it
+fn is_inside<T>(tree: &ScopeTree<'_, T>, scope: ScopeId, func: &Func<T>) -> bool
+where
+    T: AsRef<str>,
+{
+    let mut scope = Some(scope);
+    while let Some(id) = scope {
+        if let ScopeKind::Func(outer) = tree.scope(id).kind {
+            if std::ptr::eq(outer, func) {
+                return true;
+            }
+        }
+        scope = tree.scope(id).parent;
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decl::{ModExport, NamedExportDecl};
+    use crate::transform::build::{call, expr_stmt, ident, ident_expr, var};
+    use crate::{Class, ClassBody, FuncBody};
+
+    fn func(name: &str, body: Vec<ProgramPart<String>>) -> ProgramPart<String> {
+        ProgramPart::Decl(Decl::Func(Func {
+            id: Some(ident(name)),
+            type_params: Vec::new(),
+            params: Vec::new(),
+            return_type: None,
+            body: Some(FuncBody(body)),
+            generator: false,
+            is_async: false,
+        }))
+    }
+
+    fn class(name: &str) -> ProgramPart<String> {
+        ProgramPart::Decl(Decl::Class(Class {
+            id: Some(ident(name)),
+            type_params: Vec::new(),
+            super_class: None,
+            super_type_args: Vec::new(),
+            implements: Vec::new(),
+            body: ClassBody(Vec::new()),
+            is_abstract: false,
+            decorators: Vec::new(),
+        }))
+    }
+
+    fn export(part: ProgramPart<String>) -> ProgramPart<String> {
+        match part {
+            ProgramPart::Decl(decl) => ProgramPart::Decl(Decl::Export(Box::new(ModExport::Named(
+                NamedExportDecl::Decl(decl),
+            )))),
+            part => part,
+        }
+    }
+
+    fn run(name: &str) -> ProgramPart<String> {
+        expr_stmt(call(ident_expr(name), Vec::new()))
+    }
+
+    fn number(raw: &str) -> Expr<String> {
+        Expr::Lit(Lit::Number(raw.to_string()))
+    }
+
+    fn if_stmt(
+        test: bool,
+        consequent: Stmt<String>,
+        alternate: Option<Stmt<String>>,
+    ) -> Stmt<String> {
+        Stmt::If(IfStmt {
+            test: Expr::Lit(Lit::Boolean(test)),
+            consequent: Box::new(consequent),
+            alternate: alternate.map(Box::new),
+        })
+    }
+
+    fn block(parts: Vec<ProgramPart<String>>) -> Stmt<String> {
+        Stmt::Block(BlockStmt(parts))
+    }
+
+    fn declared(kind: VarKind, names: &[&str]) -> ProgramPart<String> {
+        var(kind, names.iter().map(|name| (*name, None)).collect())
+    }
+
+    #[test]
+    fn unreachable_statements_leave_their_declarations() {
+        let program = Program::script(vec![func(
+            "f",
+            vec![
+                ProgramPart::Stmt(Stmt::Return(None)),
+                var(VarKind::Var, vec![("a", Some(number("1")))]),
+                run("cleanup"),
+                var(VarKind::Let, vec![("b", Some(number("2")))]),
+                class("C"),
+                func("g", Vec::new()),
+                ProgramPart::Stmt(block(vec![var(
+                    VarKind::Var,
+                    vec![("c", Some(number("3")))],
+                )])),
+            ],
+        )]);
+        assert_eq!(
+            eliminate(program),
+            Program::script(vec![func(
+                "f",
+                vec![
+                    ProgramPart::Stmt(Stmt::Return(None)),
+                    declared(VarKind::Var, &["a", "c"]),
+                    // still in their temporal dead zone where they are read
+                    declared(VarKind::Let, &["b"]),
+                    declared(VarKind::Let, &["C"]),
+                    func("g", Vec::new()),
+                ],
+            )])
+        );
+    }
+
+    #[test]
+    fn constant_branches_keep_the_hoisted_vars() {
+        let program = Program::script(vec![ProgramPart::Stmt(if_stmt(
+            false,
+            block(vec![var(VarKind::Var, vec![("x", Some(number("1")))])]),
+            Some(Stmt::Expr(call(ident_expr("y"), Vec::new()))),
+        ))]);
+        assert_eq!(
+            eliminate(program),
+            Program::script(vec![ProgramPart::Stmt(block(vec![
+                declared(VarKind::Var, &["x"]),
+                run("y"),
+            ]))])
+        );
+        let program = Program::script(vec![ProgramPart::Stmt(if_stmt(
+            true,
+            Stmt::Expr(call(ident_expr("y"), Vec::new())),
+            None,
+        ))]);
+        assert_eq!(eliminate(program), Program::script(vec![run("y")]));
+    }
+
+    #[test]
+    fn skipped_function_declarations_are_kept() {
+        // sloppy mode hoists `g` out of the block
+        let program = Program::script(vec![ProgramPart::Stmt(if_stmt(
+            false,
+            block(vec![func("g", Vec::new())]),
+            None,
+        ))]);
+        assert_eq!(eliminate(program.clone()), program);
+    }
+
+    #[test]
+    fn unused_module_bindings() {
+        let program = Program::module(vec![
+            var(
+                VarKind::Const,
+                vec![("unused", Some(number("1"))), ("kept", Some(number("2")))],
+            ),
+            var(
+                VarKind::Let,
+                vec![("effect", Some(call(ident_expr("f"), Vec::new())))],
+            ),
+            func("recursive", vec![run("recursive")]),
+            export(func("run", vec![run("kept")])),
+        ]);
+        assert_eq!(
+            eliminate(program),
+            Program::module(vec![
+                var(VarKind::Const, vec![("kept", Some(number("2")))]),
+                var(
+                    VarKind::Let,
+                    vec![("effect", Some(call(ident_expr("f"), Vec::new())))]
+                ),
+                export(func("run", vec![run("kept")])),
+            ])
+        );
+    }
+
+    #[test]
+    fn early_reads_keep_their_bindings() {
+        // `a` is read in its temporal dead zone and `hoisted` before its
+        // declaration, both throw or call and must stay
+        let program = Program::module(vec![
+            expr_stmt(call(ident_expr("log"), vec![ident_expr("a")])),
+            run("hoisted"),
+            var(VarKind::Let, vec![("a", Some(number("1")))]),
+            func("hoisted", Vec::new()),
+        ]);
+        assert_eq!(eliminate(program.clone()), program);
+    }
+
+    #[test]
+    fn script_bindings_are_global() {
+        let program: Program<String> = Program::script(vec![declared(VarKind::Var, &["unused"])]);
+        assert_eq!(eliminate(program.clone()), program);
+    }
+
+    #[test]
+    fn dynamic_scope_is_left_alone() {
+        let program = Program::module(vec![
+            var(VarKind::Const, vec![("unused", Some(number("1")))]),
+            expr_stmt(call(ident_expr("eval"), vec![ident_expr("code")])),
+            ProgramPart::Stmt(if_stmt(false, Stmt::Expr(ident_expr("a")), None)),
+        ]);
+        assert_eq!(eliminate(program.clone()), program);
+    }
+
+    #[test]
+    fn invalid_nodes_are_left_alone() {
+        let program = Program::module(vec![
+            var(VarKind::Const, vec![("unused", Some(number("1")))]),
+            ProgramPart::Stmt(if_stmt(
+                false,
+                Stmt::Expr(Expr::Invalid("unused +".to_string())),
+                None,
+            )),
+        ]);
+        assert_eq!(eliminate(program.clone()), program);
+        let program = Program::module(vec![
+            var(VarKind::Const, vec![("unused", Some(number("1")))]),
+            ProgramPart::Stmt(Stmt::Invalid("with (".to_string())),
+        ]);
+        assert_eq!(eliminate(program.clone()), program);
+    }
+}
@@ -0,0 +1,1414 @@
+//! Lower standard decorators to plain function calls
+//!
+//! Every class with a decorator on itself or one of its members is
+//! wrapped in an arrow function that evaluates the decorators, applies
+//! them and returns the finished class.
+//! ```js
+//! @sealed
+//! class Thing {
+//!     @logged
+//!     run() {}
+//! }
+//! ```
+//! becomes
+//! ```js
+//! let Thing = (() => {
+//!     const _dec = sealed, _dec2 = logged;
+//!     const _result = (r, v) => {
+//!         if (r === void 0) return v;
+//!         if (typeof r !== "function") throw new TypeError("...");
+//!         return r;
+//!     };
+//!     const _inits = [], _staticInits = [], _classInits = [];
+//!     let Thing;
+//!     ({
+//!         Thing: class {
+//!             static {
+//!                 this.prototype.run = _result(_dec2(this.prototype.run, {
+//!                     kind: "method", name: "run", static: false, private: false,
+//!                     access: { has: o => "run" in o, get: o => o.run },
+//!                     addInitializer: f => _inits.push(f)
+//!                 }), this.prototype.run);
+//!                 Thing = this;
+//!                 Thing = _result(_dec(Thing, {
+//!                     kind: "class", name: "Thing", addInitializer: f => _classInits.push(f)
+//!                 }), Thing);
+//!             }
+//!             #_runInits = _inits.forEach(f => f.call(this));
+//!             run() {}
+//!         }
+//!     });
+//!     _classInits.forEach(f => f.call(Thing));
+//!     return Thing;
+//! })();
+//! ```
+//! Decorators and computed keys are evaluated once, in source order,
+//! before the class is created. Member decorators are applied innermost
+//! first with methods and accessors ahead of fields, then the class's
+//! own decorators, all in a static block ahead of the static fields.
+//! The class initializers run once the static fields are done. A
+//! decorator that returns anything other than a function or `undefined`
+//! throws a `TypeError`.
+//!
+//! A class with decorators of its own, or without a name, is created
+//! as the value of a property so it is named the way `Thing = class {}`
+//! would name it without holding a binding of its own. Its static
+//! block assigns the arrow function's `Thing`, so every reference to
+//! the class in its body, static fields included, sees the decorated
+//! class. An anonymous class is named after the variable it is
+//! declared, assigned or defaulted to, the identifier key of the
+//! property or field it is the value of, or `default` for
+//! `export default`.
+//!
+//! Some parts of the proposal are not covered
+//! - decorated private methods and accessors are an error since replacing
+//!   them would need access to the class's private storage
+//! - parameter decorators are a typescript extension and are an error
+//! - `await` and `yield` in the decorators, computed keys or super class
+//!   are an error since those move into the arrow function
+//! - `context.metadata` is not provided
+use std::fmt;
+
+use crate::analysis::visit::Visit;
+use crate::decl::{Decl, DefaultExportDecl, ExportSpecifier, ModExport, NamedExportDecl, VarDecl};
+use crate::expr::{
+    AssignLeft, BinaryExpr, ConditionalExpr, Expr, Lit, NewExpr, ObjProp, PrivateName, Prop,
+    PropKey, PropValue, UnaryExpr,
+};
+use crate::pat::{ArrayPatPart, ObjPatPart, Pat};
+use crate::stmt::{IfStmt, Stmt};
+use crate::transform::build::{
+    arrow, arrow_block, assign, call, expr_stmt, ident, ident_expr, index, member, object, string,
+    undefined, var, Names,
+};
+use crate::transform::visit::{
+    walk_class, walk_decl, walk_expr, walk_func_arg, walk_parts, walk_pat, walk_prop, walk_stmt,
+    VisitMut,
+};
+use crate::{
+    AssignOp, BinaryOp, Class, ClassField, ClassMember, Decorator, Func, FuncArg, FuncBody,
+    Program, ProgramPart, PropKind, UnaryOp, VarKind,
+};
+
+/// Replace every decorated class in `program` with plain code
+/// that has the same effect
+pub fn lower<T>(mut program: Program<T>) -> Result<Program<T>, DecoratorError>
+where
+    T: Clone + AsRef<str> + From<String>,
+{
+    let mut names = Names::new(&program);
+    // a `#_runInits` field must not shadow one of an enclosing class
+    PrivateNames { names: &mut names }.visit_program(&program);
+    let mut lowering = Lowering { names, error: None };
+    lowering.visit_program(&mut program);
+    match lowering.error {
+        Some(error) => Err(error),
+        None => Ok(program),
+    }
+}
+
+/// A decorator that cannot be lowered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecoratorError {
+    /// A decorator on a private method or accessor, holds the
+    /// name without the leading `#`
+    PrivateMethod(String),
+    /// A decorator on a function or constructor parameter
+    Parameter,
+    /// A decorator on a class constructor
+    Constructor,
+    /// An `await` or `yield` in the decorators, computed keys or super
+    /// class of a decorated class
+    AwaitOrYield,
+}
+
+impl fmt::Display for DecoratorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecoratorError::PrivateMethod(name) => {
+                write!(
+                    f,
+                    "decorators on private methods are not supported: #{}",
+                    name
+                )
+            }
+            DecoratorError::Parameter => f.write_str("decorators on parameters are not supported"),
+            DecoratorError::Constructor => {
+                f.write_str("decorators are not allowed on a class constructor")
+            }
+            DecoratorError::AwaitOrYield => {
+                f.write_str("await and yield are not supported around a decorated class")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecoratorError {}
+
+/// Reserves the private names declared by every class
+struct PrivateNames<'n> {
+    names: &'n mut Names,
+}
+
+impl<'a, T> Visit<'a, T> for PrivateNames<'_>
+where
+    T: AsRef<str> + 'a,
+{
+    fn visit_class(&mut self, class: &'a Class<T>) {
+        for member in &class.body.0 {
+            let key = match member {
+                ClassMember::Prop(prop) => &prop.key,
+                ClassMember::Field(field) => &field.key,
+                ClassMember::StaticBlock(_) => continue,
+            };
+            if let PropKey::Private(private) = key {
+                self.names.reserve(private.id.name.as_ref());
+            }
+        }
+        crate::analysis::visit::walk_class(self, class)
+    }
+}
+
+/// Looks for an `await` or `yield` of the function being visited
+struct Suspends {
+    found: bool,
+}
+
+impl<'a, T> Visit<'a, T> for Suspends
+where
+    T: AsRef<str> + 'a,
+{
+    fn visit_expr(&mut self, expr: &'a Expr<T>) {
+        match expr {
+            Expr::Await(_) | Expr::Yield(_) => self.found = true,
+            Expr::ArrowFunc(_) => return,
+            _ => {}
+        }
+        crate::analysis::visit::walk_expr(self, expr)
+    }
+
+    fn visit_func(&mut self, _func: &'a Func<T>) {}
+}
+
+struct Lowering {
+    names: Names,
+    error: Option<DecoratorError>,
+}
+
+/// A class member's key as seen by the generated code
+enum Key<T> {
+    /// `name() {}`
+    Ident(String),
+    /// `"name"() {}`, `1() {}` or a computed key that has been moved
+    /// into a helper, `name` is the value handed to the decorators
+    Expr { key: Expr<T>, name: Expr<T> },
+    /// `#name() {}`
+    Private(String),
+}
+
+impl<T> Key<T>
+where
+    T: Clone + From<String>,
+{
+    /// `object.key`
+    fn access(&self, object: Expr<T>) -> Expr<T> {
+        match self {
+            Key::Ident(name) => member(object, name),
+            Key::Expr { key, .. } => index(object, key.clone()),
+            Key::Private(name) => index(object, private_name(name)),
+        }
+    }
+
+    /// `key in object`
+    fn has(&self, object: Expr<T>) -> Expr<T> {
+        let key = match self {
+            Key::Ident(name) => string(name),
+            Key::Expr { key, .. } => key.clone(),
+            Key::Private(name) => private_name(name),
+        };
+        binary(BinaryOp::In, key, object)
+    }
+
+    /// The key as a value, for `Object.defineProperty`
+    fn value(&self) -> Expr<T> {
+        match self {
+            Key::Ident(name) | Key::Private(name) => string(name),
+            Key::Expr { key, .. } => key.clone(),
+        }
+    }
+
+    fn name(&self) -> Expr<T> {
+        match self {
+            Key::Ident(name) => string(name),
+            Key::Expr { name, .. } => name.clone(),
+            Key::Private(name) => string(&format!("#{}", name)),
+        }
+    }
+}
+
+impl<T> VisitMut<T> for Lowering
+where
+    T: Clone + AsRef<str> + From<String>,
+{
+    fn visit_parts(&mut self, parts: &mut Vec<ProgramPart<T>>) {
+        walk_parts(self, parts);
+        if self.error.is_some() || !parts.iter().any(has_decorated_class) {
+            return;
+        }
+        let mut lowered = Vec::with_capacity(parts.len());
+        for part in std::mem::take(parts) {
+            if let Err(error) = self.lower_part(part, &mut lowered) {
+                self.error = Some(error);
+                return;
+            }
+        }
+        *parts = lowered;
+    }
+
+    fn visit_decl(&mut self, decl: &mut Decl<T>) {
+        if let Decl::Var(_, decls) = decl {
+            self.lower_var_decls(decls);
+        }
+        walk_decl(self, decl)
+    }
+
+    fn visit_stmt(&mut self, stmt: &mut Stmt<T>) {
+        if let Stmt::Var(decls) = stmt {
+            self.lower_var_decls(decls);
+        }
+        walk_stmt(self, stmt)
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr<T>) {
+        if let Expr::Assign(assign) = expr {
+            let target = match &assign.left {
+                AssignLeft::Pat(Pat::Ident(id)) => Some(id),
+                AssignLeft::Expr(left) => match &**left {
+                    Expr::Ident(id) => Some(id),
+                    _ => None,
+                },
+                AssignLeft::Pat(_) => None,
+            };
+            // NamedEvaluation only applies to `=` and the logical
+            // assignments
+            let is_named = matches!(
+                assign.operator,
+                AssignOp::Equal
+                    | AssignOp::DoubleAmpersandEqual
+                    | AssignOp::DoublePipeEqual
+                    | AssignOp::DoubleQuestionmarkEqual
+            );
+            if let (true, Some(id)) = (is_named, target) {
+                self.lower_named(&mut assign.right, Some(id.name.as_ref()));
+            }
+        }
+        walk_expr(self, expr);
+        self.lower_named(expr, None);
+    }
+
+    /// `a = class {}` as a default value
+    fn visit_pat(&mut self, pat: &mut Pat<T>) {
+        if let Pat::Assign(assign) = pat {
+            if let Pat::Ident(id) = &*assign.left {
+                self.lower_named(&mut assign.right, Some(id.name.as_ref()));
+            }
+        }
+        walk_pat(self, pat)
+    }
+
+    fn visit_func_arg(&mut self, arg: &mut FuncArg<T>) {
+        match arg {
+            FuncArg::Decorated(_) => {
+                self.error.get_or_insert(DecoratorError::Parameter);
+                return;
+            }
+            FuncArg::Typed(typed) => {
+                if let (Pat::Ident(id), Some(default)) = (&typed.pat, &mut typed.default) {
+                    self.lower_named(default, Some(id.name.as_ref()));
+                }
+            }
+            FuncArg::Expr(_) | FuncArg::Pat(_) => {}
+        }
+        walk_func_arg(self, arg)
+    }
+
+    /// `{ a: class {} }`, and `{ a = class {} }` in a pattern whose
+    /// key is not visited otherwise
+    fn visit_prop(&mut self, prop: &mut Prop<T>) {
+        if let (true, PropKey::Pat(pat), PropValue::None) =
+            (prop.short_hand, &mut prop.key, &prop.value)
+        {
+            return self.visit_pat(pat);
+        }
+        if let (false, PropValue::Expr(value)) = (prop.computed, &mut prop.value) {
+            self.lower_named(value, key_name(&prop.key));
+        }
+        walk_prop(self, prop)
+    }
+
+    /// `a = class {}` as a field
+    fn visit_class(&mut self, class: &mut Class<T>) {
+        for member in &mut class.body.0 {
+            if let ClassMember::Field(field) = member {
+                if let (false, Some(value)) = (field.computed, &mut field.value) {
+                    self.lower_named(value, key_name(&field.key));
+                }
+            }
+        }
+        walk_class(self, class)
+    }
+}
+
+impl Lowering {
+    /// Lower `expr` when it is a decorated class, `name` is the name
+    /// NamedEvaluation gives it when it has no name of its own
+    fn lower_named<T>(&mut self, expr: &mut Expr<T>, name: Option<&str>)
+    where
+        T: Clone + AsRef<str> + From<String>,
+    {
+        if self.error.is_some() {
+            return;
+        }
+        if let Expr::Class(class) = expr {
+            if is_decorated(class) {
+                let class = std::mem::replace(class, Class::new(None, None, Vec::new()));
+                match self.lower_class(class, name) {
+                    Ok(lowered) => *expr = lowered,
+                    Err(error) => self.error = Some(error),
+                }
+            }
+        }
+    }
+
+    /// Lower the decorated classes that initialize `decls`, naming
+    /// the anonymous ones after their variable
+    fn lower_var_decls<T>(&mut self, decls: &mut [VarDecl<T>])
+    where
+        T: Clone + AsRef<str> + From<String>,
+    {
+        for decl in decls {
+            if let (Pat::Ident(id), Some(init)) = (&decl.id, &mut decl.init) {
+                self.lower_named(init, Some(id.name.as_ref()));
+            }
+        }
+    }
+
+    fn lower_part<T>(
+        &mut self,
+        part: ProgramPart<T>,
+        out: &mut Vec<ProgramPart<T>>,
+    ) -> Result<(), DecoratorError>
+    where
+        T: Clone + AsRef<str> + From<String>,
+    {
+        match part {
+            ProgramPart::Decl(Decl::Class(class)) if is_decorated(&class) => {
+                let decl = self.class_binding(class)?;
+                out.push(ProgramPart::Decl(decl));
+            }
+            ProgramPart::Decl(Decl::Export(export)) => match *export {
+                ModExport::Named(NamedExportDecl::Decl(Decl::Class(class)))
+                    if is_decorated(&class) =>
+                {
+                    let decl = self.class_binding(class)?;
+                    out.push(ProgramPart::Decl(Decl::Export(Box::new(ModExport::Named(
+                        NamedExportDecl::Decl(decl),
+                    )))));
+                }
+                ModExport::Default(DefaultExportDecl::Decl(Decl::Class(class)))
+                    if is_decorated(&class) =>
+                {
+                    match class.id.clone() {
+                        // keep the local binding of `export default class Thing {}`
+                        Some(id) => {
+                            out.push(ProgramPart::Decl(self.class_binding(class)?));
+                            let spec = ExportSpecifier {
+                                local: id.into(),
+                                alias: Some(ident("default").into()),
+                                is_type_only: false,
+                            };
+                            out.push(ProgramPart::Decl(Decl::Export(Box::new(ModExport::Named(
+                                NamedExportDecl::Specifier(vec![spec], None, Vec::new()),
+                            )))));
+                        }
+                        None => {
+                            let expr = self.lower_class(class, Some("default"))?;
+                            out.push(ProgramPart::Decl(Decl::Export(Box::new(
+                                ModExport::Default(DefaultExportDecl::Expr(expr)),
+                            ))));
+                        }
+                    }
+                }
+                export => out.push(ProgramPart::Decl(Decl::Export(Box::new(export)))),
+            },
+            part => out.push(part),
+        }
+        Ok(())
+    }
+
+    /// `let Thing = (() => { ... })();` for a class declaration
+    fn class_binding<T>(&mut self, class: Class<T>) -> Result<Decl<T>, DecoratorError>
+    where
+        T: Clone + AsRef<str> + From<String>,
+    {
+        let id = match &class.id {
+            Some(id) => id.clone(),
+            None => ident(&self.names.fresh("_class")),
+        };
+        let init = self.lower_class(class, None)?;
+        Ok(Decl::Var(
+            VarKind::Let,
+            vec![VarDecl {
+                id: Pat::Ident(id),
+                type_annotation: None,
+                init: Some(init),
+            }],
+        ))
+    }
+
+    /// The arrow function that creates and decorates `class`, `name`
+    /// is the name NamedEvaluation gives an anonymous class
+    fn lower_class<T>(
+        &mut self,
+        mut class: Class<T>,
+        name: Option<&str>,
+    ) -> Result<Expr<T>, DecoratorError>
+    where
+        T: Clone + AsRef<str> + From<String>,
+    {
+        if suspends(&class) {
+            return Err(DecoratorError::AwaitOrYield);
+        }
+        let class_name = match &class.id {
+            Some(id) => Some(id.name.as_ref().to_string()),
+            None => name.map(str::to_string),
+        };
+        // the helper declarations, in evaluation order
+        let mut hoisted = Vec::new();
+        let class_decorators = self.hoist_decorators(&mut hoisted, &mut class.decorators);
+        let inits = self.names.fresh("_inits");
+        let static_inits = self.names.fresh("_staticInits");
+        let class_inits = self.names.fresh("_classInits");
+        let result = self.names.fresh("_result");
+        let mut lists = vec![inits.clone(), static_inits.clone(), class_inits.clone()];
+
+        let mut apply_methods = Vec::new();
+        let mut apply_fields = Vec::new();
+        let mut has_instance = false;
+        let mut has_static = false;
+        for element in &mut class.body.0 {
+            match element {
+                ClassMember::Prop(prop) => {
+                    let decorators = self.hoist_decorators(&mut hoisted, &mut prop.decorators);
+                    let key = self.hoist_key(&mut hoisted, &mut prop.key, prop.computed);
+                    if decorators.is_empty() {
+                        continue;
+                    }
+                    if prop.kind == PropKind::Ctor {
+                        return Err(DecoratorError::Constructor);
+                    }
+                    if let Key::Private(name) = &key {
+                        return Err(DecoratorError::PrivateMethod(name.clone()));
+                    }
+                    let (target, list) = if prop.is_static {
+                        has_static = true;
+                        (Expr::This, &static_inits)
+                    } else {
+                        has_instance = true;
+                        (member(Expr::This, "prototype"), &inits)
+                    };
+                    for decorator in decorators.iter().rev() {
+                        let decorator = ident_expr(decorator);
+                        let applied = match prop.kind {
+                            PropKind::Get | PropKind::Set => {
+                                let (kind, slot) = if prop.kind == PropKind::Get {
+                                    ("getter", "get")
+                                } else {
+                                    ("setter", "set")
+                                };
+                                let context = context(kind, &key, prop.is_static, list);
+                                let target = target.clone();
+                                apply_accessor(&result, decorator, target, &key, slot, context)
+                            }
+                            _ => {
+                                let context = context("method", &key, prop.is_static, list);
+                                let current = key.access(target.clone());
+                                let applied = call(decorator, vec![current.clone(), context]);
+                                assign(current.clone(), checked(&result, applied, current))
+                            }
+                        };
+                        apply_methods.push(expr_stmt(applied));
+                    }
+                }
+                ClassMember::Field(field) => {
+                    let decorators = self.hoist_decorators(&mut hoisted, &mut field.decorators);
+                    let key = self.hoist_key(&mut hoisted, &mut field.key, field.computed);
+                    if decorators.is_empty() {
+                        continue;
+                    }
+                    let list = if field.is_static {
+                        has_static = true;
+                        &static_inits
+                    } else {
+                        has_instance = true;
+                        &inits
+                    };
+                    let init = self.names.fresh("_init");
+                    for decorator in decorators.iter().rev() {
+                        let context = context("field", &key, field.is_static, list);
+                        let applied = call(ident_expr(decorator), vec![undefined(), context]);
+                        let initializer = checked(&result, applied, undefined());
+                        apply_fields.push(expr_stmt(call(
+                            member(ident_expr(&init), "push"),
+                            vec![initializer],
+                        )));
+                    }
+                    let value = field.value.take().unwrap_or_else(undefined);
+                    field.value = Some(run_initializers(&init, value));
+                    lists.push(init);
+                }
+                ClassMember::StaticBlock(_) => {}
+            }
+        }
+
+        // a class that is decorated itself or has no name is created
+        // without a binding of its own, the static block assigns
+        // `binding` instead
+        let binding = if !class_decorators.is_empty() || class.id.is_none() {
+            Some(match class.id.take() {
+                Some(id) => id.name.as_ref().to_string(),
+                None => self.names.fresh("_class"),
+            })
+        } else {
+            None
+        };
+
+        let mut block = apply_methods;
+        block.append(&mut apply_fields);
+        if let Some(binding) = &binding {
+            block.push(expr_stmt(assign(ident_expr(binding), Expr::This)));
+            for decorator in class_decorators.iter().rev() {
+                let name = match &class_name {
+                    Some(name) => string(name),
+                    None => undefined(),
+                };
+                let context = object(vec![
+                    ("kind", string("class")),
+                    ("name", name),
+                    ("addInitializer", add_initializer(&class_inits)),
+                ]);
+                let applied = call(ident_expr(decorator), vec![ident_expr(binding), context]);
+                block.push(expr_stmt(assign(
+                    ident_expr(binding),
+                    checked(&result, applied, ident_expr(binding)),
+                )));
+            }
+        }
+        if has_static {
+            block.push(expr_stmt(run_list(&static_inits, Expr::This)));
+        }
+        let mut members = Vec::new();
+        if !block.is_empty() {
+            members.push(ClassMember::StaticBlock(FuncBody(block)));
+        }
+        if has_instance {
+            members.push(ClassMember::Field(ClassField {
+                key: PropKey::Private(PrivateName {
+                    id: ident(&self.names.fresh("_runInits")),
+                }),
+                value: Some(run_list(&inits, Expr::This)),
+                computed: false,
+                is_static: false,
+                decorators: Vec::new(),
+                accessibility: None,
+                is_readonly: false,
+                is_optional: false,
+                type_annotation: None,
+            }));
+        }
+        members.append(&mut class.body.0);
+        class.body.0 = members;
+
+        let mut body = Vec::new();
+        if !hoisted.is_empty() {
+            body.push(var(
+                VarKind::Const,
+                hoisted
+                    .iter()
+                    .map(|(name, expr): &(String, Expr<T>)| (name.as_str(), Some(expr.clone())))
+                    .collect(),
+            ));
+        }
+        body.push(var(VarKind::Const, vec![(&result, Some(check_result()))]));
+        body.push(var(
+            VarKind::Const,
+            lists
+                .iter()
+                .map(|name| (name.as_str(), Some(Expr::Array(Vec::new()))))
+                .collect(),
+        ));
+        let returned = match binding {
+            Some(binding) => {
+                body.push(var(VarKind::Let, vec![(binding.as_str(), None)]));
+                let class = Expr::Class(class);
+                body.push(expr_stmt(match &class_name {
+                    Some(name) => object(vec![(name, class)]),
+                    None => class,
+                }));
+                if !class_decorators.is_empty() {
+                    body.push(expr_stmt(run_list(&class_inits, ident_expr(&binding))));
+                }
+                binding
+            }
+            None => {
+                let binding = class_name.unwrap_or_default();
+                body.push(ProgramPart::Decl(Decl::Class(class)));
+                binding
+            }
+        };
+        body.push(ProgramPart::Stmt(Stmt::Return(Some(ident_expr(&returned)))));
+        Ok(call(arrow_block(&[], body), Vec::new()))
+    }
+
+    /// Move each decorator expression into a helper and
+    /// return the helper names
+    fn hoist_decorators<T>(
+        &mut self,
+        hoisted: &mut Vec<(String, Expr<T>)>,
+        decorators: &mut Vec<Decorator<T>>,
+    ) -> Vec<String> {
+        std::mem::take(decorators)
+            .into_iter()
+            .map(|decorator| {
+                let name = self.names.fresh("_dec");
+                hoisted.push((name.clone(), decorator.expr));
+                name
+            })
+            .collect()
+    }
+
+    /// Move a computed key into a helper so it is only evaluated
+    /// once and in order with the decorators
+    fn hoist_key<T>(
+        &mut self,
+        hoisted: &mut Vec<(String, Expr<T>)>,
+        key: &mut PropKey<T>,
+        computed: bool,
+    ) -> Key<T>
+    where
+        T: Clone + AsRef<str> + From<String>,
+    {
+        if computed {
+            let name = self.names.fresh("_key");
+            let expr = match std::mem::replace(key, PropKey::Expr(ident_expr(&name))) {
+                PropKey::Lit(lit) => Expr::Lit(lit),
+                PropKey::Expr(expr) => expr,
+                PropKey::Pat(pat) => pat_expr(pat),
+                PropKey::Private(private) => Expr::PrivateName(private),
+            };
+            hoisted.push((name.clone(), expr));
+            return Key::Expr {
+                key: ident_expr(&name),
+                name: ident_expr(&name),
+            };
+        }
+        match key {
+            PropKey::Expr(Expr::Ident(id)) | PropKey::Pat(Pat::Ident(id)) => {
+                Key::Ident(id.name.as_ref().to_string())
+            }
+            PropKey::Private(private) => Key::Private(private.id.name.as_ref().to_string()),
+            PropKey::Lit(lit @ Lit::String(_)) => Key::Expr {
+                key: Expr::Lit(lit.clone()),
+                name: Expr::Lit(lit.clone()),
+            },
+            PropKey::Lit(lit) => Key::Expr {
+                key: Expr::Lit(lit.clone()),
+                name: call(ident_expr("String"), vec![Expr::Lit(lit.clone())]),
+            },
+            PropKey::Expr(expr) => Key::Expr {
+                key: expr.clone(),
+                name: expr.clone(),
+            },
+            PropKey::Pat(pat) => {
+                let expr = pat_expr(pat.clone());
+                Key::Expr {
+                    key: expr.clone(),
+                    name: expr,
+                }
+            }
+        }
+    }
+}
+
+/// The context object handed to a member decorator
+fn context<T>(kind: &str, key: &Key<T>, is_static: bool, list: &str) -> Expr<T>
+where
+    T: Clone + From<String>,
+{
+    let mut access = vec![("has", arrow(&["o"], key.has(ident_expr("o"))))];
+    if kind != "setter" {
+        access.push(("get", arrow(&["o"], key.access(ident_expr("o")))));
+    }
+    if kind == "setter" || kind == "field" {
+        let set = assign(key.access(ident_expr("o")), ident_expr("v"));
+        access.push(("set", arrow_block(&["o", "v"], vec![expr_stmt(set)])));
+    }
+    object(vec![
+        ("kind", string(kind)),
+        ("name", key.name()),
+        ("static", Expr::Lit(Lit::Boolean(is_static))),
+        (
+            "private",
+            Expr::Lit(Lit::Boolean(matches!(key, Key::Private(_)))),
+        ),
+        ("access", object(access)),
+        ("addInitializer", add_initializer(list)),
+    ])
+}
+
+/// `f => list.push(f)`
+fn add_initializer<T: From<String>>(list: &str) -> Expr<T> {
+    arrow(
+        &["f"],
+        call(member(ident_expr(list), "push"), vec![ident_expr("f")]),
+    )
+}
+
+/// `list.forEach(f => f.call(this_arg))`
+fn run_list<T: From<String>>(list: &str, this_arg: Expr<T>) -> Expr<T> {
+    let run = call(member(ident_expr("f"), "call"), vec![this_arg]);
+    call(
+        member(ident_expr(list), "forEach"),
+        vec![arrow(&["f"], run)],
+    )
+}
+
+/// `init.reduce((v, f) => f ? f.call(this, v) : v, value)`
+fn run_initializers<T: From<String>>(init: &str, value: Expr<T>) -> Expr<T> {
+    let step = Expr::Conditional(ConditionalExpr {
+        test: Box::new(ident_expr("f")),
+        consequent: Box::new(call(
+            member(ident_expr("f"), "call"),
+            vec![Expr::This, ident_expr("v")],
+        )),
+        alternate: Box::new(ident_expr("v")),
+    });
+    call(
+        member(ident_expr(init), "reduce"),
+        vec![arrow(&["v", "f"], step), value],
+    )
+}
+
+/// ```js
+/// Object.defineProperty(target, key, {
+///     get: _result(
+///         dec(Object.getOwnPropertyDescriptor(target, key).get, context),
+///         Object.getOwnPropertyDescriptor(target, key).get
+///     )
+/// })
+/// ```
+fn apply_accessor<T>(
+    result: &str,
+    decorator: Expr<T>,
+    target: Expr<T>,
+    key: &Key<T>,
+    slot: &str,
+    context: Expr<T>,
+) -> Expr<T>
+where
+    T: Clone + From<String>,
+{
+    let current = member(
+        call(
+            member(ident_expr("Object"), "getOwnPropertyDescriptor"),
+            vec![target.clone(), key.value()],
+        ),
+        slot,
+    );
+    let applied = call(decorator, vec![current.clone(), context]);
+    let replaced = checked(result, applied, current);
+    call(
+        member(ident_expr("Object"), "defineProperty"),
+        vec![target, key.value(), object(vec![(slot, replaced)])],
+    )
+}
+
+/// `result(applied, current)`, the value that replaces `current`
+fn checked<T: From<String>>(result: &str, applied: Expr<T>, current: Expr<T>) -> Expr<T> {
+    call(ident_expr(result), vec![applied, current])
+}
+
+/// The helper that checks what a decorator returned
+/// ```js
+/// (r, v) => {
+///     if (r === void 0) return v;
+///     if (typeof r !== "function") throw new TypeError("...");
+///     return r;
+/// }
+/// ```
+fn check_result<T: From<String>>() -> Expr<T> {
+    let when = |test, then| {
+        ProgramPart::Stmt(Stmt::If(IfStmt {
+            test,
+            consequent: Box::new(then),
+            alternate: None,
+        }))
+    };
+    let error = Expr::New(NewExpr {
+        callee: Box::new(ident_expr("TypeError")),
+        arguments: vec![string("decorators must return a function or undefined")],
+    });
+    let type_of = Expr::Unary(UnaryExpr {
+        operator: UnaryOp::TypeOf,
+        prefix: true,
+        argument: Box::new(ident_expr("r")),
+    });
+    arrow_block(
+        &["r", "v"],
+        vec![
+            when(
+                binary(BinaryOp::StrictEqual, ident_expr("r"), undefined()),
+                Stmt::Return(Some(ident_expr("v"))),
+            ),
+            when(
+                binary(BinaryOp::StrictNotEqual, type_of, string("function")),
+                Stmt::Throw(error),
+            ),
+            ProgramPart::Stmt(Stmt::Return(Some(ident_expr("r")))),
+        ],
+    )
+}
+
+fn binary<T>(operator: BinaryOp, left: Expr<T>, right: Expr<T>) -> Expr<T> {
+    Expr::Binary(BinaryExpr {
+        operator,
+        left: Box::new(left),
+        right: Box::new(right),
+    })
+}
+
+fn private_name<T: From<String>>(name: &str) -> Expr<T> {
+    Expr::PrivateName(PrivateName { id: ident(name) })
+}
+
+/// A computed key parsed as a pattern, only an identifier is expected
+fn pat_expr<T>(pat: Pat<T>) -> Expr<T> {
+    match pat {
+        Pat::Ident(id) => Expr::Ident(id),
+        Pat::Assign(assign) => *assign.right,
+        Pat::RestElement(pat) => pat_expr(*pat),
+        Pat::Obj(props) => Expr::Obj(
+            props
+                .into_iter()
+                .filter_map(|part| match part {
+                    ObjPatPart::Assign(prop) => Some(ObjProp::Prop(prop)),
+                    ObjPatPart::Rest(_) => None,
+                })
+                .collect(),
+        ),
+        Pat::Array(parts) => Expr::Array(
+            parts
+                .into_iter()
+                .map(|part| {
+                    part.map(|part| match part {
+                        ArrayPatPart::Pat(pat) => pat_expr(pat),
+                        ArrayPatPart::Expr(expr) => expr,
+                    })
+                })
+                .collect(),
+        ),
+        Pat::Invalid(text) => Expr::Invalid(text),
+    }
+}
+
+/// If the parts of `class` that move into the arrow function can
+/// `await` or `yield`
+fn suspends<T: AsRef<str>>(class: &Class<T>) -> bool {
+    let mut suspends = Suspends { found: false };
+    for decorator in &class.decorators {
+        suspends.visit_expr(&decorator.expr);
+    }
+    if let Some(super_class) = &class.super_class {
+        suspends.visit_expr(super_class);
+    }
+    for member in &class.body.0 {
+        let (decorators, key, computed) = match member {
+            ClassMember::Prop(prop) => (&prop.decorators, &prop.key, prop.computed),
+            ClassMember::Field(field) => (&field.decorators, &field.key, field.computed),
+            ClassMember::StaticBlock(_) => continue,
+        };
+        for decorator in decorators {
+            suspends.visit_expr(&decorator.expr);
+        }
+        match key {
+            PropKey::Expr(key) if computed => suspends.visit_expr(key),
+            PropKey::Pat(key) if computed => suspends.visit_pat(key),
+            _ => {}
+        }
+    }
+    suspends.found
+}
+
+/// The name NamedEvaluation takes from a key that is not computed,
+/// only identifiers are kept since it becomes an object literal key
+fn key_name<T: AsRef<str>>(key: &PropKey<T>) -> Option<&str> {
+    match key {
+        PropKey::Expr(Expr::Ident(id)) | PropKey::Pat(Pat::Ident(id)) => Some(id.name.as_ref()),
+        _ => None,
+    }
+}
+
+fn is_decorated<T>(class: &Class<T>) -> bool {
+    !class.decorators.is_empty()
+        || class.body.0.iter().any(|member| match member {
+            ClassMember::Prop(prop) => !prop.decorators.is_empty(),
+            ClassMember::Field(field) => !field.decorators.is_empty(),
+            ClassMember::StaticBlock(_) => false,
+        })
+}
+
+fn has_decorated_class<T>(part: &ProgramPart<T>) -> bool {
+    match part {
+        ProgramPart::Decl(Decl::Class(class)) => is_decorated(class),
+        ProgramPart::Decl(Decl::Export(export)) => matches!(
+            &**export,
+            ModExport::Named(NamedExportDecl::Decl(Decl::Class(class)))
+                | ModExport::Default(DefaultExportDecl::Decl(Decl::Class(class)))
+                if is_decorated(class)
+        ),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::{ArrowFuncBody, AssignExpr};
+    use crate::Ident;
+
+    fn decorators(names: &[&str]) -> Vec<Decorator<String>> {
+        names
+            .iter()
+            .map(|name| Decorator {
+                expr: ident_expr(name),
+            })
+            .collect()
+    }
+
+    fn method(name: &str, decorators: Vec<Decorator<String>>) -> ClassMember<String> {
+        ClassMember::Prop(Prop {
+            key: PropKey::Expr(ident_expr(name)),
+            value: PropValue::Expr(Expr::Func(Func {
+                id: None,
+                type_params: Vec::new(),
+                params: Vec::new(),
+                return_type: None,
+                body: Some(FuncBody(Vec::new())),
+                generator: false,
+                is_async: false,
+            })),
+            kind: PropKind::Method,
+            method: true,
+            computed: false,
+            short_hand: false,
+            is_static: false,
+            is_abstract: false,
+            decorators,
+            accessibility: None,
+            is_optional: false,
+        })
+    }
+
+    fn field(
+        key: PropKey<String>,
+        computed: bool,
+        is_static: bool,
+        decorators: Vec<Decorator<String>>,
+        value: Option<Expr<String>>,
+    ) -> ClassMember<String> {
+        ClassMember::Field(ClassField {
+            key,
+            computed,
+            value,
+            is_static,
+            decorators,
+            accessibility: None,
+            is_readonly: false,
+            is_optional: false,
+            type_annotation: None,
+        })
+    }
+
+    fn class(
+        id: Option<&str>,
+        decorators: Vec<Decorator<String>>,
+        members: Vec<ClassMember<String>>,
+    ) -> Class<String> {
+        let mut class = Class::new(id.map(ident), None, Vec::new());
+        class.body.0 = members;
+        class.decorators = decorators;
+        class
+    }
+
+    fn lowered(parts: Vec<ProgramPart<String>>) -> Vec<ProgramPart<String>> {
+        match lower(Program::module(parts)).unwrap() {
+            Program::Mod(parts, _) | Program::Script(parts, _) => parts,
+        }
+    }
+
+    /// The initializer of `let name = init;`
+    fn init_of(part: &ProgramPart<String>) -> &Expr<String> {
+        match part {
+            ProgramPart::Decl(Decl::Var(_, decls)) => decls[0].init.as_ref().unwrap(),
+            _ => panic!("not a variable declaration"),
+        }
+    }
+
+    /// The statements of the arrow function a class is lowered to
+    fn arrow_body(expr: &Expr<String>) -> &[ProgramPart<String>] {
+        let arrow = match expr {
+            Expr::Call(call) => &*call.callee,
+            _ => panic!("not a call"),
+        };
+        match arrow {
+            Expr::ArrowFunc(arrow) => match &arrow.body {
+                ArrowFuncBody::FuncBody(body) => &body.0,
+                ArrowFuncBody::Expr(_) => panic!("not a block"),
+            },
+            _ => panic!("not an arrow function"),
+        }
+    }
+
+    /// The names and values of `const a = x, b = y;`
+    fn declared(part: &ProgramPart<String>) -> Vec<(&str, &Expr<String>)> {
+        match part {
+            ProgramPart::Decl(Decl::Var(_, decls)) => decls
+                .iter()
+                .map(|decl| match &decl.id {
+                    Pat::Ident(id) => (id.name.as_str(), decl.init.as_ref().unwrap()),
+                    _ => panic!("not an identifier"),
+                })
+                .collect(),
+            _ => panic!("not a variable declaration"),
+        }
+    }
+
+    /// The class created by the statement `({ name: class {} });`
+    fn named_class(part: &ProgramPart<String>) -> (&str, &Class<String>) {
+        let props = match part {
+            ProgramPart::Stmt(Stmt::Expr(Expr::Obj(props))) => props,
+            _ => panic!("not an object statement"),
+        };
+        match &props[0] {
+            ObjProp::Prop(Prop {
+                key: PropKey::Expr(Expr::Ident(key)),
+                value: PropValue::Expr(Expr::Class(class)),
+                ..
+            }) => (key.name.as_str(), class),
+            _ => panic!("not a class property"),
+        }
+    }
+
+    /// Every identifier `part` reads, in order
+    struct Reads(Vec<String>);
+
+    impl<'a> Visit<'a, String> for Reads {
+        fn visit_ident(&mut self, ident: &'a Ident<String>) {
+            self.0.push(ident.name.clone());
+        }
+    }
+
+    fn reads(parts: &[ProgramPart<String>], prefix: &str) -> Vec<String> {
+        let mut reads = Reads(Vec::new());
+        reads.visit_parts(parts);
+        reads.0.retain(|name| name.starts_with(prefix));
+        reads.0
+    }
+
+    #[test]
+    fn decorators_are_evaluated_in_order_and_applied_innermost_first() {
+        // @a @b class Thing {
+        //     @c @d run() {}
+        //     @e x = 1;
+        //     @f static y;
+        //     static [k] = 2;
+        // }
+        let thing = class(
+            Some("Thing"),
+            decorators(&["a", "b"]),
+            vec![
+                method("run", decorators(&["c", "d"])),
+                field(
+                    PropKey::Expr(ident_expr("x")),
+                    false,
+                    false,
+                    decorators(&["e"]),
+                    Some(Expr::Lit(Lit::Number("1".to_string()))),
+                ),
+                field(
+                    PropKey::Expr(ident_expr("y")),
+                    false,
+                    true,
+                    decorators(&["f"]),
+                    None,
+                ),
+                field(
+                    PropKey::Expr(ident_expr("k")),
+                    true,
+                    true,
+                    Vec::new(),
+                    Some(Expr::Lit(Lit::Number("2".to_string()))),
+                ),
+            ],
+        );
+        let parts = lowered(vec![ProgramPart::Decl(Decl::Class(thing))]);
+        let body = arrow_body(init_of(&parts[0]));
+        let hoisted: Vec<_> = declared(&body[0])
+            .into_iter()
+            .map(|(name, value)| match value {
+                Expr::Ident(value) => (name, value.name.as_str()),
+                _ => panic!("not an identifier"),
+            })
+            .collect();
+        assert_eq!(
+            hoisted,
+            vec![
+                ("_dec", "a"),
+                ("_dec2", "b"),
+                ("_dec3", "c"),
+                ("_dec4", "d"),
+                ("_dec5", "e"),
+                ("_dec6", "f"),
+                ("_key", "k"),
+            ]
+        );
+        assert_eq!(declared(&body[1]), vec![("_result", &check_result())]);
+        let lists: Vec<&str> = declared(&body[2])
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(
+            lists,
+            vec!["_inits", "_staticInits", "_classInits", "_init", "_init2"]
+        );
+        let (name, class) = named_class(&body[4]);
+        assert_eq!(name, "Thing");
+        let block = match &class.body.0[0] {
+            ClassMember::StaticBlock(block) => &block.0,
+            _ => panic!("not a static block"),
+        };
+        // methods ahead of fields ahead of the class, each innermost first
+        assert_eq!(
+            reads(block, "_dec"),
+            vec!["_dec4", "_dec3", "_dec5", "_dec6", "_dec2", "_dec"]
+        );
+        assert_eq!(
+            block[block.len() - 1],
+            expr_stmt(run_list("_staticInits", Expr::This))
+        );
+        assert!(matches!(
+            &class.body.0[1],
+            ClassMember::Field(ClassField { key: PropKey::Private(private), .. })
+                if private.id.name == "_runInits"
+        ));
+        // the class initializers run after the static fields
+        assert_eq!(
+            body[5],
+            expr_stmt(run_list("_classInits", ident_expr("Thing")))
+        );
+        assert_eq!(
+            body[6],
+            ProgramPart::Stmt(Stmt::Return(Some(ident_expr("Thing"))))
+        );
+    }
+
+    #[test]
+    fn decorator_results_are_checked() {
+        let parts = lowered(vec![ProgramPart::Decl(Decl::Class(class(
+            Some("Thing"),
+            decorators(&["sealed"]),
+            Vec::new(),
+        )))]);
+        let body = arrow_body(init_of(&parts[0]));
+        let (_, class) = named_class(&body[4]);
+        let block = match &class.body.0[0] {
+            ClassMember::StaticBlock(block) => &block.0,
+            _ => panic!("not a static block"),
+        };
+        let context = object(vec![
+            ("kind", string("class")),
+            ("name", string("Thing")),
+            ("addInitializer", add_initializer("_classInits")),
+        ]);
+        let applied = call(ident_expr("_dec"), vec![ident_expr("Thing"), context]);
+        assert_eq!(
+            block[1],
+            expr_stmt(assign(
+                ident_expr("Thing"),
+                call(ident_expr("_result"), vec![applied, ident_expr("Thing")]),
+            ))
+        );
+    }
+
+    #[test]
+    fn helper_names_are_not_in_scope() {
+        // let _result; @_dec class A { #_runInits; @_dec run() {} }
+        let a = class(
+            Some("A"),
+            decorators(&["_dec"]),
+            vec![
+                field(
+                    PropKey::Private(PrivateName {
+                        id: ident("_runInits"),
+                    }),
+                    false,
+                    false,
+                    Vec::new(),
+                    None,
+                ),
+                method("run", decorators(&["_dec"])),
+            ],
+        );
+        let parts = lowered(vec![
+            var(VarKind::Let, vec![("_result", None)]),
+            ProgramPart::Decl(Decl::Class(a)),
+        ]);
+        let body = arrow_body(init_of(&parts[1]));
+        assert_eq!(
+            declared(&body[0]),
+            vec![
+                ("_dec2", &ident_expr("_dec")),
+                ("_dec3", &ident_expr("_dec"))
+            ]
+        );
+        assert_eq!(declared(&body[1])[0].0, "_result2");
+        let (_, class) = named_class(&body[4]);
+        assert!(matches!(
+            &class.body.0[1],
+            ClassMember::Field(ClassField { key: PropKey::Private(private), .. })
+                if private.id.name == "_runInits2"
+        ));
+    }
+
+    #[test]
+    fn anonymous_classes_are_named() {
+        let anonymous = || Expr::Class(class(None, decorators(&["dec"]), Vec::new()));
+        let assign_with = |operator| {
+            Expr::Assign(AssignExpr {
+                operator,
+                left: AssignLeft::Expr(Box::new(ident_expr("x"))),
+                right: Box::new(anonymous()),
+            })
+        };
+        // [z = @dec class {}]
+        let default = Pat::Array(vec![Some(ArrayPatPart::Pat(Pat::Assign(
+            crate::pat::AssignPat {
+                left: Box::new(Pat::Ident(ident("z"))),
+                right: Box::new(anonymous()),
+            },
+        )))]);
+        let parts = lowered(vec![
+            // x ||= @dec class {};
+            expr_stmt(assign_with(AssignOp::DoublePipeEqual)),
+            // ({ y: @dec class {} });
+            expr_stmt(object(vec![("y", anonymous())])),
+            // [z = @dec class {}] = [];
+            expr_stmt(Expr::Assign(AssignExpr {
+                operator: AssignOp::Equal,
+                left: AssignLeft::Pat(default),
+                right: Box::new(Expr::Array(Vec::new())),
+            })),
+            // class Holder { static w = @dec class {}; }
+            ProgramPart::Decl(Decl::Class(class(
+                Some("Holder"),
+                Vec::new(),
+                vec![field(
+                    PropKey::Expr(ident_expr("w")),
+                    false,
+                    true,
+                    Vec::new(),
+                    Some(anonymous()),
+                )],
+            ))),
+            // x += @dec class {};
+            expr_stmt(assign_with(AssignOp::PlusEqual)),
+        ]);
+        let lowered_class = |part: &ProgramPart<String>| -> Expr<String> {
+            let expr = match part {
+                ProgramPart::Stmt(Stmt::Expr(expr)) => expr,
+                ProgramPart::Decl(Decl::Class(class)) => match &class.body.0[0] {
+                    ClassMember::Field(field) => field.value.as_ref().unwrap(),
+                    _ => panic!("not a field"),
+                },
+                _ => panic!("unexpected part"),
+            };
+            match expr {
+                Expr::Assign(AssignExpr {
+                    left: AssignLeft::Pat(Pat::Array(parts)),
+                    ..
+                }) => match &parts[0] {
+                    Some(ArrayPatPart::Pat(Pat::Assign(pat))) => (*pat.right).clone(),
+                    _ => panic!("not a default"),
+                },
+                Expr::Assign(assign) => (*assign.right).clone(),
+                Expr::Obj(props) => match &props[0] {
+                    ObjProp::Prop(Prop {
+                        value: PropValue::Expr(value),
+                        ..
+                    }) => value.clone(),
+                    _ => panic!("not a property"),
+                },
+                expr => expr.clone(),
+            }
+        };
+        for (part, name) in parts.iter().zip(["x", "y", "z", "w"]) {
+            let lowered = lowered_class(part);
+            assert_eq!(named_class(&arrow_body(&lowered)[4]).0, name);
+        }
+        // a compound assignment does not name the class
+        let lowered = lowered_class(&parts[4]);
+        assert!(matches!(
+            &arrow_body(&lowered)[4],
+            ProgramPart::Stmt(Stmt::Expr(Expr::Class(_)))
+        ));
+    }
+
+    #[test]
+    fn await_and_yield_are_an_error() {
+        let awaited = Expr::Await(Box::new(ident_expr("load")));
+        let program = Program::module(vec![ProgramPart::Decl(Decl::Class(class(
+            Some("A"),
+            vec![Decorator { expr: awaited }],
+            Vec::new(),
+        )))]);
+        assert_eq!(lower(program), Err(DecoratorError::AwaitOrYield));
+        // an `await` of a nested function stays where it is
+        let nested = arrow(&[], Expr::Await(Box::new(ident_expr("load"))));
+        let program = Program::module(vec![ProgramPart::Decl(Decl::Class(class(
+            Some("A"),
+            vec![Decorator { expr: nested }],
+            Vec::new(),
+        )))]);
+        assert!(lower(program).is_ok());
+    }
+
+    #[test]
+    fn unsupported_decorators() {
+        let private = ClassMember::Prop(match method("run", decorators(&["dec"])) {
+            ClassMember::Prop(prop) => Prop {
+                key: PropKey::Private(PrivateName { id: ident("run") }),
+                ..prop
+            },
+            _ => unreachable!(),
+        });
+        let program = Program::module(vec![ProgramPart::Decl(Decl::Class(class(
+            Some("A"),
+            Vec::new(),
+            vec![private],
+        )))]);
+        assert_eq!(
+            lower(program),
+            Err(DecoratorError::PrivateMethod("run".to_string()))
+        );
+    }
+}
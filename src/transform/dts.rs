@@ -0,0 +1,984 @@
+//! Generate the declarations of a typescript `.d.ts` file
+//!
+//! The result is a new `Program` made up of only ambient
+//! declarations, ready to be handed to a printer.
+//! - statements and directives are dropped
+//! - function, method and accessor bodies are replaced with signatures,
+//!   only the overload signatures are kept when they are present
+//! - variable, field and parameter initializers are dropped
+//! - decorators are dropped
+//! - `using` and `await using` declarations become `const`
+//! - private members are collapsed into a single `#private;` field
+//!   and static blocks are dropped
+//! - `private` members and constructor parameter properties become
+//!   fields, `private` ones without their type
+//! - in a module, declarations that are neither exported nor referenced
+//!   by an export, directly or through another kept declaration, are
+//!   dropped
+//! - imports are kept as is since they may be needed by the declarations
+//!
+//! Type annotations, interfaces, type aliases and enums are kept as
+//! written, nothing is inferred so an unannotated binding is declared
+//! without a type. `async` functions and generators become plain
+//! signatures since neither is allowed in an ambient context.
+//! ```ts
+//! export function add(a: number, b = 1): number {
+//!     return a + b;
+//! }
+//! const secret = 0;
+//! ```
+//! becomes
+//! ```ts
+//! export declare function add(a: number, b?): number;
+//! ```
+use crate::decl::{
+    Decl, DefaultExportDecl, ImportSpecifier, ModExport, ModuleDecl, ModuleName, ModuleRef,
+    NamedExportDecl, VarDecl,
+};
+use crate::expr::{AssignLeft, Expr, PrivateName, Prop, PropKey, PropValue};
+use crate::pat::{ArrayPatPart, ObjPatPart, Pat};
+use crate::ts::{Accessibility, TsType, TsTypeMember, TsTypeParam, TsTypeRef};
+use crate::{
+    Class, ClassBody, ClassField, ClassMember, Func, FuncArg, Ident, Program, ProgramPart,
+    PropKind, TypedArg, VarKind,
+};
+
+/// The name given to an exported default expression that
+/// cannot be declared directly, a number is added to it when
+/// the module already declares it
+const DEFAULT_EXPORT_NAME: &str = "_default";
+/// The name of the single private field that stands in for
+/// all of a class's private members
+const PRIVATE_MEMBERS_NAME: &str = "private";
+
+/// Produce the ambient declarations for `program`
+pub fn declarations<T>(program: Program<T>) -> Program<T>
+where
+    T: Clone + PartialEq + From<String>,
+{
+    match program {
+        Program::Mod(parts, hashbang) => {
+            let mut parts = declare_parts(parts, Context::Module);
+            // without any import or export the declarations would
+            // describe globals instead of a module
+            if !parts.iter().any(is_module_syntax) {
+                parts.push(ProgramPart::Decl(Decl::Export(Box::new(ModExport::Named(
+                    NamedExportDecl::Specifier(Vec::new(), None, Vec::new()),
+                )))));
+            }
+            Program::Mod(parts, hashbang)
+        }
+        Program::Script(parts, hashbang) => {
+            Program::Script(declare_parts(parts, Context::Script), hashbang)
+        }
+    }
+}
+
+/// Where a list of program parts appears
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Context {
+    /// The top level of a script, everything is global
+    Script,
+    /// The top level of a module
+    Module,
+    /// The body of a namespace
+    Namespace,
+    /// The body of an ambient module, `declare global` or
+    /// an already ambient namespace
+    Ambient,
+}
+
+impl Context {
+    /// If declarations that are not exported should be kept
+    fn keeps_locals(self) -> bool {
+        matches!(self, Context::Script | Context::Ambient)
+    }
+
+    /// If declarations need the `declare` keyword
+    fn needs_declare(self) -> bool {
+        matches!(self, Context::Script | Context::Module)
+    }
+}
+
+fn declare_parts<T>(parts: Vec<ProgramPart<T>>, ctx: Context) -> Vec<ProgramPart<T>>
+where
+    T: Clone + PartialEq + From<String>,
+{
+    let referenced = referenced_names(&parts);
+    let overloaded = overloaded_names(&parts);
+    let default_name = default_export_name(&parts);
+    let mut ret = Vec::new();
+    for part in parts {
+        let decl = if let ProgramPart::Decl(decl) = part {
+            decl
+        } else {
+            continue;
+        };
+        match decl {
+            Decl::Import(_) | Decl::ImportEquals(_) => ret.push(ProgramPart::Decl(decl)),
+            Decl::Export(export) => {
+                ret.extend(declare_export(*export, ctx, &overloaded, &default_name))
+            }
+            Decl::Invalid(_) => {}
+            decl => {
+                let keep = ctx.keeps_locals()
+                    || is_global_augmentation(&decl)
+                    || declared_names(&decl)
+                        .into_iter()
+                        .any(|name| referenced.contains(name));
+                if !keep || is_overload_impl(&decl, &overloaded) {
+                    continue;
+                }
+                ret.push(ProgramPart::Decl(with_declare(ambient(decl), ctx)));
+            }
+        }
+    }
+    ret
+}
+
+fn declare_export<T>(
+    export: ModExport<T>,
+    ctx: Context,
+    overloaded: &[T],
+    default_name: &T,
+) -> Vec<ProgramPart<T>>
+where
+    T: Clone + PartialEq + From<String>,
+{
+    let export = match export {
+        ModExport::Named(NamedExportDecl::Decl(decl)) => {
+            if is_overload_impl(&decl, overloaded) {
+                return Vec::new();
+            }
+            ModExport::Named(NamedExportDecl::Decl(with_declare(ambient(decl), ctx)))
+        }
+        ModExport::Default(DefaultExportDecl::Decl(decl)) => {
+            if is_overload_impl(&decl, overloaded) {
+                return Vec::new();
+            }
+            ModExport::Default(DefaultExportDecl::Decl(ambient(decl)))
+        }
+        ModExport::Default(DefaultExportDecl::Expr(expr)) => match expr {
+            Expr::Ident(_) => ModExport::Default(DefaultExportDecl::Expr(expr)),
+            Expr::Func(f) => ModExport::Default(DefaultExportDecl::Decl(Decl::Func(signature(f)))),
+            Expr::Class(c) => {
+                ModExport::Default(DefaultExportDecl::Decl(Decl::Class(ambient_class(c))))
+            }
+            _ => {
+                let id = Ident {
+                    name: default_name.clone(),
+                };
+                let value = Decl::Var(
+                    VarKind::Const,
+                    vec![VarDecl {
+                        id: Pat::Ident(id.clone()),
+                        type_annotation: None,
+                        init: None,
+                    }],
+                );
+                return vec![
+                    ProgramPart::Decl(with_declare(value, ctx)),
+                    ProgramPart::Decl(Decl::Export(Box::new(ModExport::Default(
+                        DefaultExportDecl::Expr(Expr::Ident(id)),
+                    )))),
+                ];
+            }
+        },
+        export => export,
+    };
+    vec![ProgramPart::Decl(Decl::Export(Box::new(export)))]
+}
+
+/// Wrap `decl` in `declare` if the context requires it, interfaces
+/// and type aliases never need it
+fn with_declare<T>(decl: Decl<T>, ctx: Context) -> Decl<T> {
+    if ctx.needs_declare() && !matches!(decl, Decl::Interface(_) | Decl::TypeAlias(_)) {
+        Decl::Declare(Box::new(decl))
+    } else {
+        decl
+    }
+}
+
+/// Strip the implementation details from a declaration, the
+/// result will never be wrapped in `declare`
+fn ambient<T>(decl: Decl<T>) -> Decl<T>
+where
+    T: Clone + PartialEq + From<String>,
+{
+    match decl {
+        Decl::Var(kind, decls) => {
+            // `using` is not allowed in an ambient context but
+            // the binding is just as immutable as a `const`
+            let kind = if kind.is_using() {
+                VarKind::Const
+            } else {
+                kind
+            };
+            Decl::Var(kind, decls.into_iter().flat_map(names_of).collect())
+        }
+        Decl::Func(f) => Decl::Func(signature(f)),
+        Decl::Class(c) => Decl::Class(ambient_class(c)),
+        Decl::Module(m) => Decl::Module(Box::new(ambient_module(*m, false))),
+        Decl::Declare(inner) => match *inner {
+            Decl::Module(m) => Decl::Module(Box::new(ambient_module(*m, true))),
+            inner => ambient(inner),
+        },
+        decl => decl,
+    }
+}
+
+/// A declarator without its initializer, a pattern is split into
+/// its names since its annotation does not belong to any one of them
+fn names_of<T>(decl: VarDecl<T>) -> Vec<VarDecl<T>>
+where
+    T: Clone,
+{
+    match decl.id {
+        Pat::Ident(id) => vec![VarDecl {
+            id: Pat::Ident(id),
+            type_annotation: decl.type_annotation,
+            init: None,
+        }],
+        id => id
+            .bound_names()
+            .into_iter()
+            .map(|id| VarDecl {
+                id: Pat::Ident(id.clone()),
+                type_annotation: None,
+                init: None,
+            })
+            .collect(),
+    }
+}
+
+fn ambient_module<T>(module: ModuleDecl<T>, is_declared: bool) -> ModuleDecl<T>
+where
+    T: Clone + PartialEq + From<String>,
+{
+    let ctx = match module.name {
+        ModuleName::Namespace(_) if !is_declared => Context::Namespace,
+        _ => Context::Ambient,
+    };
+    ModuleDecl {
+        name: module.name,
+        body: module.body.map(|parts| declare_parts(parts, ctx)),
+    }
+}
+
+fn ambient_class<T>(class: Class<T>) -> Class<T>
+where
+    T: Clone + PartialEq + From<String>,
+{
+    let overloaded: Vec<PropKey<T>> = class
+        .body
+        .0
+        .iter()
+        .filter_map(|m| match m {
+            ClassMember::Prop(p) if is_signature_prop(p) => Some(p.key.clone()),
+            _ => None,
+        })
+        .collect();
+    let mut has_private = false;
+    let mut members: Vec<ClassMember<T>> = class
+        .body
+        .0
+        .iter()
+        .filter_map(|m| match m {
+            ClassMember::Prop(p) if p.kind == PropKind::Ctor => Some(p),
+            _ => None,
+        })
+        .flat_map(|ctor| match &ctor.value {
+            PropValue::Expr(Expr::Func(f)) => f.params.as_slice(),
+            _ => &[],
+        })
+        .filter_map(param_property)
+        .map(ClassMember::Field)
+        .collect();
+    members.extend(class.body.0.into_iter().filter_map(|member| match member {
+        ClassMember::Prop(p) if matches!(p.key, PropKey::Private(_)) => {
+            has_private = true;
+            None
+        }
+        ClassMember::Prop(p)
+            if matches!(p.kind, PropKind::Method | PropKind::Ctor)
+                && !is_signature_prop(&p)
+                && overloaded.contains(&p.key) =>
+        {
+            None
+        }
+        ClassMember::Prop(p)
+            if p.kind == PropKind::Method && p.accessibility == Some(Accessibility::Private) =>
+        {
+            Some(ClassMember::Field(ClassField {
+                key: p.key,
+                value: None,
+                computed: p.computed,
+                is_static: p.is_static,
+                decorators: Vec::new(),
+                accessibility: p.accessibility,
+                is_readonly: false,
+                is_optional: p.is_optional,
+                type_annotation: None,
+            }))
+        }
+        ClassMember::Prop(mut p) => {
+            p.value = match p.value {
+                PropValue::Expr(Expr::Func(f)) => PropValue::Expr(Expr::Func(signature(f))),
+                PropValue::Expr(_) if p.kind == PropKind::Init => PropValue::None,
+                value => value,
+            };
+            p.decorators.clear();
+            Some(ClassMember::Prop(p))
+        }
+        ClassMember::Field(field) if matches!(field.key, PropKey::Private(_)) => {
+            has_private = true;
+            None
+        }
+        ClassMember::Field(field) => Some(ClassMember::Field(ClassField {
+            value: None,
+            decorators: Vec::new(),
+            type_annotation: if field.accessibility == Some(Accessibility::Private) {
+                None
+            } else {
+                field.type_annotation
+            },
+            ..field
+        })),
+        ClassMember::StaticBlock(_) => None,
+    }));
+    if has_private {
+        members.insert(
+            0,
+            ClassMember::Field(ClassField {
+                key: PropKey::Private(PrivateName {
+                    id: Ident {
+                        name: T::from(PRIVATE_MEMBERS_NAME.to_string()),
+                    },
+                }),
+                value: None,
+                computed: false,
+                is_static: false,
+                decorators: Vec::new(),
+                accessibility: None,
+                is_readonly: false,
+                is_optional: false,
+                type_annotation: None,
+            }),
+        );
+    }
+    Class {
+        id: class.id,
+        type_params: class.type_params,
+        super_class: class.super_class,
+        super_type_args: class.super_type_args,
+        implements: class.implements,
+        body: ClassBody(members),
+        is_abstract: class.is_abstract,
+        decorators: Vec::new(),
+    }
+}
+
+/// Replace the body of `f` with a signature
+fn signature<T>(f: Func<T>) -> Func<T> {
+    Func {
+        id: f.id,
+        type_params: f.type_params,
+        params: strip_param_defaults(f.params),
+        return_type: f.return_type,
+        body: None,
+        generator: false,
+        is_async: false,
+    }
+}
+
+/// The field a constructor parameter property declares
+/// ```ts
+/// class Thing {
+///     constructor(private readonly a: number) {}
+/// }
+/// ```
+fn param_property<T>(arg: &FuncArg<T>) -> Option<ClassField<T>>
+where
+    T: Clone,
+{
+    let typed = match arg {
+        FuncArg::Typed(typed) => typed,
+        FuncArg::Decorated(decorated) => return param_property(&decorated.arg),
+        _ => return None,
+    };
+    if typed.accessibility.is_none() && !typed.is_readonly {
+        return None;
+    }
+    let id = match &typed.pat {
+        Pat::Ident(id) => id.clone(),
+        _ => return None,
+    };
+    Some(ClassField {
+        key: PropKey::Expr(Expr::Ident(id)),
+        value: None,
+        computed: false,
+        is_static: false,
+        decorators: Vec::new(),
+        accessibility: typed.accessibility,
+        is_readonly: typed.is_readonly,
+        is_optional: typed.is_optional,
+        type_annotation: if typed.accessibility == Some(Accessibility::Private) {
+            None
+        } else {
+            typed.type_annotation.clone()
+        },
+    })
+}
+
+/// Strip the defaults from a parameter list, a parameter that
+/// loses its default becomes optional unless a required parameter
+/// follows it
+/// ```ts
+/// function thing(a = 0, b, c = 1) {}
+/// ```
+/// becomes
+/// ```ts
+/// function thing(a, b, c?);
+/// ```
+fn strip_param_defaults<T>(params: Vec<FuncArg<T>>) -> Vec<FuncArg<T>> {
+    let mut has_required = false;
+    let mut ret: Vec<FuncArg<T>> = params
+        .into_iter()
+        .rev()
+        .map(|arg| {
+            let arg = strip_arg_default(arg, !has_required);
+            has_required |= is_required(&arg);
+            arg
+        })
+        .collect();
+    ret.reverse();
+    ret
+}
+
+/// Strip the default value and any parameter property
+/// modifiers from `arg`, marking it optional if it had a
+/// default and `can_be_optional` is set
+fn strip_arg_default<T>(arg: FuncArg<T>, can_be_optional: bool) -> FuncArg<T> {
+    match arg {
+        FuncArg::Pat(Pat::Assign(assign)) if can_be_optional => {
+            optional(strip_defaults(*assign.left))
+        }
+        FuncArg::Pat(pat) => FuncArg::Pat(strip_defaults(pat)),
+        FuncArg::Decorated(decorated) => strip_arg_default(decorated.arg, can_be_optional),
+        FuncArg::Typed(typed) => FuncArg::Typed(Box::new(TypedArg {
+            accessibility: None,
+            is_readonly: false,
+            pat: strip_defaults(typed.pat),
+            is_optional: typed.is_optional || (can_be_optional && typed.default.is_some()),
+            type_annotation: typed.type_annotation,
+            default: None,
+        })),
+        FuncArg::Expr(Expr::Assign(assign)) => match assign.left {
+            AssignLeft::Pat(pat) if can_be_optional => optional(strip_defaults(pat)),
+            AssignLeft::Pat(pat) => FuncArg::Pat(strip_defaults(pat)),
+            AssignLeft::Expr(expr) => match *expr {
+                Expr::Ident(id) if can_be_optional => optional(Pat::Ident(id)),
+                expr => FuncArg::Expr(expr),
+            },
+        },
+        arg => arg,
+    }
+}
+
+/// `pat` as an optional parameter, `a?`
+fn optional<T>(pat: Pat<T>) -> FuncArg<T> {
+    FuncArg::Typed(Box::new(TypedArg {
+        accessibility: None,
+        is_readonly: false,
+        pat,
+        is_optional: true,
+        type_annotation: None,
+        default: None,
+    }))
+}
+
+/// If a caller must pass `arg`, it is neither optional nor a rest
+/// parameter
+fn is_required<T>(arg: &FuncArg<T>) -> bool {
+    match arg {
+        FuncArg::Pat(Pat::RestElement(_)) => false,
+        FuncArg::Typed(typed) => !typed.is_optional && !matches!(typed.pat, Pat::RestElement(_)),
+        _ => true,
+    }
+}
+
+/// Remove every default value from a pattern, initializers
+/// are not allowed in an ambient context
+fn strip_defaults<T>(pat: Pat<T>) -> Pat<T> {
+    match pat {
+        Pat::Assign(assign) => strip_defaults(*assign.left),
+        Pat::Obj(parts) => Pat::Obj(
+            parts
+                .into_iter()
+                .map(|part| match part {
+                    ObjPatPart::Assign(mut prop) => {
+                        prop.value = match prop.value {
+                            PropValue::Pat(pat) => PropValue::Pat(strip_defaults(pat)),
+                            PropValue::Expr(Expr::Assign(assign)) => match assign.left {
+                                AssignLeft::Pat(pat) => PropValue::Pat(strip_defaults(pat)),
+                                AssignLeft::Expr(expr) => PropValue::Expr(*expr),
+                            },
+                            value => value,
+                        };
+                        ObjPatPart::Assign(prop)
+                    }
+                    ObjPatPart::Rest(pat) => ObjPatPart::Rest(Box::new(strip_defaults(*pat))),
+                })
+                .collect(),
+        ),
+        Pat::Array(parts) => Pat::Array(
+            parts
+                .into_iter()
+                .map(|part| {
+                    part.map(|part| match part {
+                        ArrayPatPart::Pat(pat) => ArrayPatPart::Pat(strip_defaults(pat)),
+                        part => part,
+                    })
+                })
+                .collect(),
+        ),
+        Pat::RestElement(pat) => Pat::RestElement(Box::new(strip_defaults(*pat))),
+        pat => pat,
+    }
+}
+
+fn is_signature_prop<T>(prop: &Prop<T>) -> bool {
+    matches!(prop.kind, PropKind::Method | PropKind::Ctor)
+        && matches!(&prop.value, PropValue::Expr(Expr::Func(f)) if f.is_signature())
+}
+
+/// The names referenced by a local export list, a default
+/// export, an export assignment or the signature of an exported
+/// declaration, along with every name a referenced local
+/// declaration references in turn
+/// ```ts
+/// class Base {}
+/// type Id = number;
+/// export class Thing extends Base {
+///     id: Id;
+/// }
+/// ```
+/// references both `Base` and `Id`
+fn referenced_names<T>(parts: &[ProgramPart<T>]) -> Vec<T>
+where
+    T: Clone + PartialEq,
+{
+    let mut names = Vec::new();
+    let mut locals = Vec::new();
+    for part in parts {
+        match part {
+            ProgramPart::Decl(Decl::Export(export)) => export_refs(export, &mut names),
+            ProgramPart::Decl(decl) => locals.push(decl),
+            _ => {}
+        }
+    }
+    loop {
+        let (referenced, rest): (Vec<_>, Vec<_>) = locals.into_iter().partition(|decl| {
+            declared_names(decl)
+                .into_iter()
+                .any(|name| names.contains(name))
+        });
+        if referenced.is_empty() {
+            break;
+        }
+        for decl in referenced {
+            decl_refs(decl, &mut names);
+        }
+        locals = rest;
+    }
+    names
+}
+
+fn export_refs<T>(export: &ModExport<T>, names: &mut Vec<T>)
+where
+    T: Clone,
+{
+    match export {
+        ModExport::Named(NamedExportDecl::Specifier(specs, None, _)) => names.extend(
+            specs
+                .iter()
+                .filter_map(|s| s.local.as_ident())
+                .map(|id| id.name.clone()),
+        ),
+        ModExport::Named(NamedExportDecl::Decl(decl))
+        | ModExport::Default(DefaultExportDecl::Decl(decl)) => decl_refs(decl, names),
+        ModExport::Default(DefaultExportDecl::Expr(Expr::Func(f))) => func_refs(f, names),
+        ModExport::Default(DefaultExportDecl::Expr(Expr::Class(c))) => class_refs(c, names),
+        ModExport::Default(DefaultExportDecl::Expr(expr)) | ModExport::Assign(expr) => {
+            names.extend(entity_root(expr).map(|root| root.name.clone()))
+        }
+        _ => {}
+    }
+}
+
+/// The names `decl` references once it is ambient, initializers
+/// and bodies are dropped so only its types and super class remain
+fn decl_refs<T>(decl: &Decl<T>, names: &mut Vec<T>)
+where
+    T: Clone,
+{
+    match decl {
+        Decl::Var(_, decls) => {
+            for decl in decls {
+                opt_type_refs(&decl.type_annotation, names);
+            }
+        }
+        Decl::Func(f) => func_refs(f, names),
+        Decl::Class(c) => class_refs(c, names),
+        Decl::Interface(interface) => {
+            type_param_refs(&interface.type_params, names);
+            for ext in &interface.extends {
+                type_ref_refs(ext, names);
+            }
+            for member in &interface.body {
+                member_refs(member, names);
+            }
+        }
+        Decl::TypeAlias(alias) => {
+            type_param_refs(&alias.type_params, names);
+            type_refs(&alias.type_annotation, names);
+        }
+        Decl::Module(m) => {
+            for part in m.body.iter().flatten() {
+                match part {
+                    ProgramPart::Decl(Decl::Export(export)) => export_refs(export, names),
+                    ProgramPart::Decl(decl) => decl_refs(decl, names),
+                    _ => {}
+                }
+            }
+        }
+        Decl::Declare(inner) => decl_refs(inner, names),
+        _ => {}
+    }
+}
+
+fn func_refs<T>(f: &Func<T>, names: &mut Vec<T>)
+where
+    T: Clone,
+{
+    type_param_refs(&f.type_params, names);
+    params_refs(&f.params, names);
+    opt_type_refs(&f.return_type, names);
+}
+
+fn params_refs<T>(params: &[FuncArg<T>], names: &mut Vec<T>)
+where
+    T: Clone,
+{
+    for param in params {
+        let mut param = param;
+        while let FuncArg::Decorated(decorated) = param {
+            param = &decorated.arg;
+        }
+        if let FuncArg::Typed(typed) = param {
+            opt_type_refs(&typed.type_annotation, names);
+        }
+    }
+}
+
+/// The super class and the types of every member that keeps its
+/// type, `private` members are declared without one
+fn class_refs<T>(c: &Class<T>, names: &mut Vec<T>)
+where
+    T: Clone,
+{
+    type_param_refs(&c.type_params, names);
+    if let Some(root) = c.super_class.as_deref().and_then(entity_root) {
+        names.push(root.name.clone());
+    }
+    for arg in &c.super_type_args {
+        type_refs(arg, names);
+    }
+    for implemented in &c.implements {
+        type_ref_refs(implemented, names);
+    }
+    for member in &c.body.0 {
+        match member {
+            ClassMember::Prop(prop) => {
+                key_refs(&prop.key, prop.computed, names);
+                if prop.accessibility == Some(Accessibility::Private) {
+                    continue;
+                }
+                if let PropValue::Expr(Expr::Func(f)) = &prop.value {
+                    func_refs(f, names);
+                }
+            }
+            ClassMember::Field(field) => {
+                key_refs(&field.key, field.computed, names);
+                if field.accessibility != Some(Accessibility::Private) {
+                    opt_type_refs(&field.type_annotation, names);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A computed key like `[Symbol.iterator]` is kept as written
+fn key_refs<T>(key: &PropKey<T>, computed: bool, names: &mut Vec<T>)
+where
+    T: Clone,
+{
+    if let (true, PropKey::Expr(expr)) = (computed, key) {
+        names.extend(entity_root(expr).map(|root| root.name.clone()));
+    }
+}
+
+fn member_refs<T>(member: &TsTypeMember<T>, names: &mut Vec<T>)
+where
+    T: Clone,
+{
+    match member {
+        TsTypeMember::Prop(prop) => {
+            key_refs(&prop.key, prop.computed, names);
+            opt_type_refs(&prop.type_annotation, names);
+        }
+        TsTypeMember::Method(method) => {
+            key_refs(&method.key, method.computed, names);
+            type_param_refs(&method.type_params, names);
+            params_refs(&method.params, names);
+            opt_type_refs(&method.return_type, names);
+        }
+        TsTypeMember::Call(call) => {
+            type_param_refs(&call.type_params, names);
+            params_refs(&call.params, names);
+            opt_type_refs(&call.return_type, names);
+        }
+        TsTypeMember::Index(index) => {
+            type_refs(&index.param_type, names);
+            type_refs(&index.type_annotation, names);
+        }
+    }
+}
+
+fn type_param_refs<T>(params: &[TsTypeParam<T>], names: &mut Vec<T>)
+where
+    T: Clone,
+{
+    for param in params {
+        opt_type_refs(&param.constraint, names);
+        opt_type_refs(&param.default, names);
+    }
+}
+
+fn type_ref_refs<T>(type_ref: &TsTypeRef<T>, names: &mut Vec<T>)
+where
+    T: Clone,
+{
+    names.extend(type_ref.name.first().map(|id| id.name.clone()));
+    for arg in &type_ref.type_args {
+        type_refs(arg, names);
+    }
+}
+
+fn opt_type_refs<T>(ty: &Option<TsType<T>>, names: &mut Vec<T>)
+where
+    T: Clone,
+{
+    if let Some(ty) = ty {
+        type_refs(ty, names);
+    }
+}
+
+/// The left most name of every type reference and `typeof` query
+/// in `ty`
+fn type_refs<T>(ty: &TsType<T>, names: &mut Vec<T>)
+where
+    T: Clone,
+{
+    match ty {
+        TsType::Ref(type_ref) => type_ref_refs(type_ref, names),
+        TsType::Query(path) => names.extend(path.first().map(|id| id.name.clone())),
+        TsType::Array(inner)
+        | TsType::Optional(inner)
+        | TsType::Rest(inner)
+        | TsType::Operator(_, inner) => type_refs(inner, names),
+        TsType::Tuple(types) | TsType::Union(types) | TsType::Intersection(types) => {
+            for ty in types {
+                type_refs(ty, names);
+            }
+        }
+        TsType::Func(f) => {
+            type_param_refs(&f.type_params, names);
+            params_refs(&f.params, names);
+            type_refs(&f.return_type, names);
+        }
+        TsType::Object(members) => {
+            for member in members {
+                member_refs(member, names);
+            }
+        }
+        TsType::Indexed(indexed) => {
+            type_refs(&indexed.object, names);
+            type_refs(&indexed.index, names);
+        }
+        TsType::Conditional(cond) => {
+            type_refs(&cond.check_type, names);
+            type_refs(&cond.extends_type, names);
+            type_refs(&cond.true_type, names);
+            type_refs(&cond.false_type, names);
+        }
+        TsType::Predicate(predicate) => opt_type_refs(&predicate.type_annotation, names),
+        TsType::Keyword(_) | TsType::This | TsType::Lit(_) | TsType::Infer(_) => {}
+    }
+}
+
+/// The left most identifier of `a` or `a.b.c`
+fn entity_root<T>(expr: &Expr<T>) -> Option<&Ident<T>> {
+    match expr {
+        Expr::Ident(ident) => Some(ident),
+        Expr::Member(member) => entity_root(&member.object),
+        _ => None,
+    }
+}
+
+/// The names of functions with overload signatures in this list
+fn overloaded_names<T>(parts: &[ProgramPart<T>]) -> Vec<T>
+where
+    T: Clone,
+{
+    parts
+        .iter()
+        .filter_map(|part| match part {
+            ProgramPart::Decl(decl) => Some(decl),
+            _ => None,
+        })
+        .filter_map(|decl| match decl {
+            Decl::Export(export) => match &**export {
+                ModExport::Named(NamedExportDecl::Decl(decl))
+                | ModExport::Default(DefaultExportDecl::Decl(decl)) => Some(decl),
+                _ => None,
+            },
+            decl => Some(decl),
+        })
+        .filter_map(|decl| match decl {
+            Decl::Func(f) if f.is_signature() => f.id.as_ref().map(|id| id.name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// `_default` unless the top level already declares that name,
+/// then `_default2`, `_default3`...
+fn default_export_name<T>(parts: &[ProgramPart<T>]) -> T
+where
+    T: PartialEq + From<String>,
+{
+    let taken = top_level_names(parts);
+    let mut count = 1;
+    loop {
+        let name = if count == 1 {
+            DEFAULT_EXPORT_NAME.to_string()
+        } else {
+            format!("{}{}", DEFAULT_EXPORT_NAME, count)
+        };
+        let name = T::from(name);
+        if !taken.contains(&&name) {
+            return name;
+        }
+        count += 1;
+    }
+}
+
+/// Every name bound at the top level, including imports and
+/// exported declarations
+fn top_level_names<T>(parts: &[ProgramPart<T>]) -> Vec<&T> {
+    let mut names = Vec::new();
+    for part in parts {
+        let decl = if let ProgramPart::Decl(decl) = part {
+            decl
+        } else {
+            continue;
+        };
+        match decl {
+            Decl::Import(import) => {
+                for spec in &import.specifiers {
+                    match spec {
+                        ImportSpecifier::Normal(specs) => names.extend(
+                            specs
+                                .iter()
+                                .filter_map(|s| s.alias.as_ref().or_else(|| s.imported.as_ident()))
+                                .map(|id| &id.name),
+                        ),
+                        ImportSpecifier::Default(id) | ImportSpecifier::Namespace(id) => {
+                            names.push(&id.name)
+                        }
+                    }
+                }
+            }
+            Decl::ImportEquals(import) => names.push(&import.id.name),
+            Decl::Export(export) => match &**export {
+                ModExport::Named(NamedExportDecl::Decl(decl))
+                | ModExport::Default(DefaultExportDecl::Decl(decl)) => {
+                    names.extend(declared_names(decl))
+                }
+                _ => {}
+            },
+            decl => names.extend(declared_names(decl)),
+        }
+    }
+    names
+}
+
+/// If `decl` is the implementation following overload signatures
+fn is_overload_impl<T>(decl: &Decl<T>, overloaded: &[T]) -> bool
+where
+    T: PartialEq,
+{
+    match decl {
+        Decl::Func(f) if !f.is_signature() => {
+            f.id.as_ref()
+                .map(|id| overloaded.contains(&id.name))
+                .unwrap_or(false)
+        }
+        _ => false,
+    }
+}
+
+fn declared_names<T>(decl: &Decl<T>) -> Vec<&T> {
+    match decl {
+        Decl::Var(_, decls) => decls
+            .iter()
+            .flat_map(|d| d.id.bound_names())
+            .map(|id| &id.name)
+            .collect(),
+        Decl::Func(Func { id: Some(id), .. }) | Decl::Class(Class { id: Some(id), .. }) => {
+            vec![&id.name]
+        }
+        Decl::Interface(interface) => vec![&interface.id.name],
+        Decl::TypeAlias(alias) => vec![&alias.id.name],
+        Decl::Enum(decl) => vec![&decl.id.name],
+        Decl::Module(m) => match &m.name {
+            ModuleName::Namespace(path) => path.iter().take(1).map(|id| &id.name).collect(),
+            _ => Vec::new(),
+        },
+        Decl::Declare(inner) => declared_names(inner),
+        _ => Vec::new(),
+    }
+}
+
+/// `declare module 'thing' {}` and `declare global {}` describe
+/// other modules so they are always kept
+fn is_global_augmentation<T>(decl: &Decl<T>) -> bool {
+    match decl {
+        Decl::Module(m) => !matches!(m.name, ModuleName::Namespace(_)),
+        Decl::Declare(inner) => is_global_augmentation(inner),
+        _ => false,
+    }
+}
+
+fn is_module_syntax<T>(part: &ProgramPart<T>) -> bool {
+    matches!(
+        part,
+        ProgramPart::Decl(Decl::Import(_)) | ProgramPart::Decl(Decl::Export(_))
+    ) || matches!(
+        part,
+        ProgramPart::Decl(Decl::ImportEquals(import))
+            if matches!(import.module_ref, ModuleRef::Require(_))
+    )
+}
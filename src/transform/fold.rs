@@ -0,0 +1,1047 @@
+//! Constant folding
+//!
+//! An operator whose operands are all literals is replaced with the
+//! value it evaluates to
+//! ```js
+//! const size = 4 * 1024, unit = 'k' + 'b', big = 2n ** 64n;
+//! const label = `${size / 1024} ${unit}`;
+//! const mode = !DEBUG && typeof 1 === 'number' ? 'fast' : 'safe';
+//! ```
+//! becomes
+//! ```js
+//! const size = 4096, unit = "kb", big = 18446744073709551616n;
+//! const label = "4 kb";
+//! const mode = !DEBUG && true ? 'fast' : 'safe';
+//! ```
+//! Identifiers are never looked up, so the `size` in the template and
+//! the `DEBUG` in the condition are not constants. Neither are
+//! `undefined`, `NaN` and `Infinity`, which can be shadowed, so values
+//! without a literal of their own are written `void 0`, `0 / 0`, `1 / 0`
+//! and `-1 / 0`, and a negative number is a negated literal.
+//!
+//! Nothing is folded away that has a side effect:
+//! - every operand of an arithmetic, comparison, unary or template
+//!   fold is a literal
+//! - `&&`, `||`, `??` and `? :` only drop the operand that would not
+//!   have been evaluated
+//! - an operator that throws, like `1n + 1`, `1n / 0n` or `'a' in 'b'`,
+//!   is left as it is
+//!
+//! A fold that leaves an identifier or a member expression is skipped
+//! where that would change the meaning: as a callee or tag, where
+//! `(true && a.b)()` calls `b` without `this`, and as the operand of
+//! `typeof` or `delete`. An expression statement is not folded into a
+//! string, which a printer could turn into a directive.
+use crate::expr::{BinaryExpr, Expr, Lit, QuasiQuote, TemplateElement, TemplateLit, UnaryExpr};
+use crate::number::{BigInt, NumericLit};
+use crate::stmt::Stmt;
+use crate::transform::build::{string_units, undefined};
+use crate::transform::visit::{walk_expr, walk_stmt, VisitMut};
+use crate::{BinaryOp, LogicalOp, Program, UnaryOp};
+
+/// Bigint results with more bits than this are not folded, their
+/// literal would be longer than the expression
+pub const MAX_BIG_INT_BITS: u64 = 1024;
+
+/// Fold every constant expression in `program`, the folds are listed
+/// innermost first
+pub fn fold<T>(mut program: Program<T>) -> (Program<T>, Vec<Fold<T>>)
+where
+    T: Clone + AsRef<str> + From<String>,
+{
+    let mut folder = Folder {
+        folds: Vec::new(),
+        position: Position::Value,
+    };
+    folder.visit_program(&mut program);
+    (program, folder.folds)
+}
+
+/// Fold `expr` and every constant expression inside of it
+pub fn fold_expr<T>(mut expr: Expr<T>) -> (Expr<T>, Vec<Fold<T>>)
+where
+    T: Clone + AsRef<str> + From<String>,
+{
+    let mut folder = Folder {
+        folds: Vec::new(),
+        position: Position::Value,
+    };
+    folder.visit_expr(&mut expr);
+    (expr, folder.folds)
+}
+
+/// If `expr` folds to a constant, whether that constant is truthy
+pub(crate) fn truthiness<T>(expr: &Expr<T>) -> Option<bool>
+where
+    T: Clone + AsRef<str> + From<String>,
+{
+    let (folded, _) = fold_expr(expr.clone());
+    constant(&folded).map(|value| truthy(&value))
+}
+
+/// One replaced expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fold<T> {
+    /// The expression as it was, with its operands already folded
+    pub before: Expr<T>,
+    pub after: Expr<T>,
+}
+
+/// A primitive value
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Undefined,
+    Null,
+    Bool(bool),
+    Number(f64),
+    BigInt(BigInt),
+    String(Vec<u16>),
+}
+
+/// The result of `ToNumeric`
+enum Numeric {
+    Number(f64),
+    BigInt(BigInt),
+}
+
+/// Where an expression is used
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Position {
+    Value,
+    /// A callee, a tag or the operand of `typeof` or `delete`
+    Reference,
+    /// The expression of an expression statement
+    Statement,
+}
+
+struct Folder<T> {
+    folds: Vec<Fold<T>>,
+    /// The position of the next expression visited
+    position: Position,
+}
+
+impl<T> VisitMut<T> for Folder<T>
+where
+    T: Clone + AsRef<str> + From<String>,
+{
+    fn visit_stmt(&mut self, stmt: &mut Stmt<T>) {
+        match stmt {
+            Stmt::Expr(expr) => {
+                self.position = Position::Statement;
+                self.visit_expr(expr);
+            }
+            _ => walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr<T>) {
+        let position = std::mem::replace(&mut self.position, Position::Value);
+        match expr {
+            Expr::Call(call) => {
+                self.position = Position::Reference;
+                self.visit_expr(&mut call.callee);
+                for arg in &mut call.arguments {
+                    self.visit_expr(arg);
+                }
+            }
+            Expr::TaggedTemplate(tagged) => {
+                self.position = Position::Reference;
+                self.visit_expr(&mut tagged.tag);
+                for expr in &mut tagged.quasi.expressions {
+                    self.visit_expr(expr);
+                }
+            }
+            Expr::Unary(unary) if matches!(unary.operator, UnaryOp::TypeOf | UnaryOp::Delete) => {
+                self.position = Position::Reference;
+                self.visit_expr(&mut unary.argument);
+            }
+            _ => walk_expr(self, expr),
+        }
+        if let Some(folded) = fold_node(expr, position) {
+            let before = std::mem::replace(expr, folded);
+            self.folds.push(Fold {
+                before,
+                after: expr.clone(),
+            });
+        }
+    }
+}
+
+/// The replacement of `expr`, whose operands are already folded
+fn fold_node<T>(expr: &Expr<T>, position: Position) -> Option<Expr<T>>
+where
+    T: Clone + AsRef<str> + From<String>,
+{
+    if is_emitted(expr) {
+        return None;
+    }
+    let folded = match expr {
+        Expr::Unary(unary) => match (unary.operator, &*unary.argument) {
+            (UnaryOp::TypeOf, Expr::Func(_) | Expr::ArrowFunc(_)) => {
+                emit(Value::String("function".encode_utf16().collect()))
+            }
+            (operator, argument) => emit(unary_op(operator, constant(argument)?)?),
+        },
+        Expr::Binary(binary) => emit(binary_op(
+            binary.operator,
+            constant(&binary.left)?,
+            constant(&binary.right)?,
+        )?),
+        Expr::Logical(logical) => {
+            let left = constant(&logical.left)?;
+            let keep_left = match logical.operator {
+                LogicalOp::Or => truthy(&left),
+                LogicalOp::And => !truthy(&left),
+                LogicalOp::NullishCoalescing => !matches!(left, Value::Undefined | Value::Null),
+            };
+            if keep_left {
+                (*logical.left).clone()
+            } else {
+                (*logical.right).clone()
+            }
+        }
+        Expr::Conditional(conditional) => {
+            if truthy(&constant(&conditional.test)?) {
+                (*conditional.consequent).clone()
+            } else {
+                (*conditional.alternate).clone()
+            }
+        }
+        Expr::Lit(Lit::Template(template)) if !template.expressions.is_empty() => {
+            fold_template(template)?
+        }
+        _ => return None,
+    };
+    match (position, &folded) {
+        (Position::Reference, Expr::Ident(_) | Expr::Member(_) | Expr::OptionalChain(_)) => None,
+        (Position::Statement, Expr::Lit(Lit::String(_))) => None,
+        _ => Some(folded),
+    }
+}
+
+/// Inline the constant expressions of a template, a template left
+/// without any expressions becomes a string
+fn fold_template<T>(template: &TemplateLit<T>) -> Option<Expr<T>>
+where
+    T: Clone + AsRef<str> + From<String>,
+{
+    // an untagged template with an invalid escape is a syntax error
+    let cooked = template
+        .quasis
+        .iter()
+        .map(|quasi| quasi.cooked().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let values: Vec<_> = template.expressions.iter().map(constant).collect();
+    if values.iter().all(Option::is_none) {
+        return None;
+    }
+    if values.iter().all(Option::is_some) {
+        let mut units = cooked.first()?.clone();
+        for (value, quasi) in values.iter().flatten().zip(&cooked[1..]) {
+            units.extend(to_string(value));
+            units.extend(quasi);
+        }
+        return Some(emit(Value::String(units)));
+    }
+    let first = template.quasis.first()?;
+    let mut quasis = Vec::new();
+    let mut expressions = Vec::new();
+    let mut open_quote = first.open_quote.clone();
+    let mut raw = first.content.as_ref().to_string();
+    for (index, expr) in template.expressions.iter().enumerate() {
+        let next = template.quasis.get(index + 1)?;
+        match &values[index] {
+            Some(value) => {
+                push_template_value(&mut raw, &to_string(value));
+                push_template_raw(&mut raw, next.content.as_ref());
+            }
+            None => {
+                quasis.push(TemplateElement {
+                    open_quote,
+                    content: T::from(std::mem::take(&mut raw)),
+                    close_quote: QuasiQuote::OpenBrace,
+                });
+                expressions.push(expr.clone());
+                open_quote = QuasiQuote::CloseBrace;
+                raw = next.content.as_ref().to_string();
+            }
+        }
+    }
+    quasis.push(TemplateElement {
+        open_quote,
+        content: T::from(raw),
+        close_quote: QuasiQuote::BackTick,
+    });
+    Some(Expr::Lit(Lit::Template(TemplateLit {
+        quasis,
+        expressions,
+    })))
+}
+
+/// Append raw template text, escaping a `{` that would start a
+/// substitution after a `$`
+fn push_template_raw(raw: &mut String, text: &str) {
+    match text.strip_prefix('{') {
+        Some(rest) if raw.ends_with('$') => {
+            raw.push_str("\\{");
+            raw.push_str(rest);
+        }
+        _ => raw.push_str(text),
+    }
+}
+
+/// Append a value to raw template text
+fn push_template_value(raw: &mut String, units: &[u16]) {
+    for ch in char::decode_utf16(units.iter().copied()) {
+        match ch {
+            Ok('\\') => raw.push_str("\\\\"),
+            Ok('`') => raw.push_str("\\`"),
+            Ok('$') => raw.push_str("\\$"),
+            Ok('{') if raw.ends_with('$') => raw.push_str("\\{"),
+            // a raw carriage return is read as a line feed
+            Ok('\r') => raw.push_str("\\r"),
+            Ok(ch) => raw.push(ch),
+            Err(lone) => raw.push_str(&format!("\\u{:04x}", lone.unpaired_surrogate())),
+        }
+    }
+}
+
+/// The value of a literal, or of an expression written the way `emit`
+/// writes values
+fn constant<T>(expr: &Expr<T>) -> Option<Value>
+where
+    T: AsRef<str>,
+{
+    match expr {
+        Expr::Lit(lit) => match lit {
+            Lit::Null => Some(Value::Null),
+            Lit::Boolean(value) => Some(Value::Bool(*value)),
+            Lit::Number(raw) => Some(Value::Number(NumericLit::parse(raw.as_ref()).ok()?.value())),
+            Lit::BigInt(raw) => Some(Value::BigInt(
+                NumericLit::parse(raw.as_ref()).ok()?.big_int()?,
+            )),
+            // sloppy mode cooking accepts everything strict mode does
+            Lit::String(string) => Some(Value::String(string.cooked(false).ok()?)),
+            Lit::Template(template) if template.expressions.is_empty() => {
+                Some(Value::String(template.quasis.first()?.cooked().ok()?))
+            }
+            Lit::Template(_) | Lit::RegEx(_) => None,
+        },
+        Expr::Unary(unary) => match unary.operator {
+            UnaryOp::Void => constant(&unary.argument).map(|_| Value::Undefined),
+            UnaryOp::Minus => match constant(&unary.argument)? {
+                Value::Number(value) => Some(Value::Number(-value)),
+                Value::BigInt(value) => Some(Value::BigInt(-value)),
+                _ => None,
+            },
+            _ => None,
+        },
+        Expr::Binary(binary) if is_emitted(expr) => binary_op(
+            binary.operator,
+            constant(&binary.left)?,
+            constant(&binary.right)?,
+        ),
+        _ => None,
+    }
+}
+
+/// If `expr` is one of the non-literal forms `emit` writes
+fn is_emitted<T>(expr: &Expr<T>) -> bool
+where
+    T: AsRef<str>,
+{
+    fn is_number<T: AsRef<str>>(expr: &Expr<T>, raw: &str) -> bool {
+        matches!(expr, Expr::Lit(Lit::Number(value)) if value.as_ref() == raw)
+    }
+    match expr {
+        Expr::Unary(unary) => match unary.operator {
+            UnaryOp::Minus => {
+                matches!(&*unary.argument, Expr::Lit(Lit::Number(_) | Lit::BigInt(_)))
+            }
+            UnaryOp::Void => is_number(&unary.argument, "0"),
+            _ => false,
+        },
+        Expr::Binary(binary) if binary.operator == BinaryOp::Over => {
+            let left = match &*binary.left {
+                Expr::Unary(unary) if unary.operator == UnaryOp::Minus => &unary.argument,
+                left => left,
+            };
+            is_number(&binary.right, "0") && (is_number(left, "0") || is_number(left, "1"))
+        }
+        _ => false,
+    }
+}
+
+/// The expression for a value
+fn emit<T>(value: Value) -> Expr<T>
+where
+    T: From<String>,
+{
+    let number = |raw: &str| Expr::Lit(Lit::Number(T::from(raw.to_string())));
+    match value {
+        Value::Undefined => undefined(),
+        Value::Null => Expr::Lit(Lit::Null),
+        Value::Bool(value) => Expr::Lit(Lit::Boolean(value)),
+        Value::Number(value) if value.is_nan() => over(number("0"), number("0")),
+        Value::Number(value) if value == f64::INFINITY => over(number("1"), number("0")),
+        Value::Number(value) if value == f64::NEG_INFINITY => {
+            over(negate(number("1")), number("0"))
+        }
+        Value::Number(value) if value.is_sign_negative() => {
+            negate(number(&number_to_string(-value)))
+        }
+        Value::Number(value) => number(&number_to_string(value)),
+        Value::BigInt(value) if value.is_negative() => {
+            negate(Expr::Lit(Lit::BigInt(T::from(format!("{}n", -value)))))
+        }
+        Value::BigInt(value) => Expr::Lit(Lit::BigInt(T::from(format!("{}n", value)))),
+        Value::String(units) => string_units(&units),
+    }
+}
+
+fn negate<T>(argument: Expr<T>) -> Expr<T> {
+    Expr::Unary(UnaryExpr {
+        operator: UnaryOp::Minus,
+        prefix: true,
+        argument: Box::new(argument),
+    })
+}
+
+fn over<T>(left: Expr<T>, right: Expr<T>) -> Expr<T> {
+    Expr::Binary(BinaryExpr {
+        operator: BinaryOp::Over,
+        left: Box::new(left),
+        right: Box::new(right),
+    })
+}
+
+fn unary_op(operator: UnaryOp, value: Value) -> Option<Value> {
+    Some(match operator {
+        UnaryOp::Not => Value::Bool(!truthy(&value)),
+        UnaryOp::Minus => match to_numeric(&value) {
+            Numeric::Number(value) => Value::Number(-value),
+            Numeric::BigInt(value) => Value::BigInt(-value),
+        },
+        UnaryOp::Plus => match to_numeric(&value) {
+            Numeric::Number(value) => Value::Number(value),
+            // a bigint can not be converted implicitly
+            Numeric::BigInt(_) => return None,
+        },
+        UnaryOp::Tilde => match to_numeric(&value) {
+            Numeric::Number(value) => Value::Number(!to_int32(value) as f64),
+            Numeric::BigInt(value) => Value::BigInt(!&value),
+        },
+        UnaryOp::TypeOf => Value::String(type_of(&value).encode_utf16().collect()),
+        UnaryOp::Void => Value::Undefined,
+        UnaryOp::Delete => return None,
+    })
+}
+
+fn binary_op(operator: BinaryOp, left: Value, right: Value) -> Option<Value> {
+    match operator {
+        BinaryOp::Plus => {
+            if matches!(left, Value::String(_)) || matches!(right, Value::String(_)) {
+                let mut units = to_string(&left);
+                units.extend(to_string(&right));
+                return Some(Value::String(units));
+            }
+            arithmetic(operator, left, right)
+        }
+        BinaryOp::Minus
+        | BinaryOp::Times
+        | BinaryOp::Over
+        | BinaryOp::Mod
+        | BinaryOp::PowerOf
+        | BinaryOp::LeftShift
+        | BinaryOp::RightShift
+        | BinaryOp::UnsignedRightShift
+        | BinaryOp::And
+        | BinaryOp::Or
+        | BinaryOp::XOr => arithmetic(operator, left, right),
+        BinaryOp::Equal => Some(Value::Bool(loose_equal(&left, &right))),
+        BinaryOp::NotEqual => Some(Value::Bool(!loose_equal(&left, &right))),
+        BinaryOp::StrictEqual => Some(Value::Bool(strict_equal(&left, &right))),
+        BinaryOp::StrictNotEqual => Some(Value::Bool(!strict_equal(&left, &right))),
+        BinaryOp::LessThan => Some(Value::Bool(less_than(&left, &right) == Some(true))),
+        BinaryOp::GreaterThan => Some(Value::Bool(less_than(&right, &left) == Some(true))),
+        BinaryOp::LessThanEqual => Some(Value::Bool(less_than(&right, &left) == Some(false))),
+        BinaryOp::GreaterThanEqual => Some(Value::Bool(less_than(&left, &right) == Some(false))),
+        // both throw when the right side is not an object
+        BinaryOp::In | BinaryOp::InstanceOf => None,
+    }
+}
+
+/// A numeric operator, mixing a number and a bigint throws
+fn arithmetic(operator: BinaryOp, left: Value, right: Value) -> Option<Value> {
+    match (to_numeric(&left), to_numeric(&right)) {
+        (Numeric::Number(left), Numeric::Number(right)) => {
+            Some(Value::Number(number_op(operator, left, right)))
+        }
+        (Numeric::BigInt(left), Numeric::BigInt(right)) => {
+            let value = big_int_op(operator, &left, &right)?;
+            if value.bit_len() > MAX_BIG_INT_BITS {
+                return None;
+            }
+            Some(Value::BigInt(value))
+        }
+        _ => None,
+    }
+}
+
+fn number_op(operator: BinaryOp, left: f64, right: f64) -> f64 {
+    let shift = to_uint32(right) & 31;
+    match operator {
+        BinaryOp::Plus => left + right,
+        BinaryOp::Minus => left - right,
+        BinaryOp::Times => left * right,
+        BinaryOp::Over => left / right,
+        // both truncate, the result has the sign of the dividend
+        BinaryOp::Mod => left % right,
+        BinaryOp::PowerOf => {
+            if right.is_nan() || (left.abs() == 1.0 && right.is_infinite()) {
+                f64::NAN
+            } else {
+                left.powf(right)
+            }
+        }
+        BinaryOp::LeftShift => to_int32(left).wrapping_shl(shift) as f64,
+        BinaryOp::RightShift => (to_int32(left) >> shift) as f64,
+        BinaryOp::UnsignedRightShift => (to_uint32(left) >> shift) as f64,
+        BinaryOp::And => (to_int32(left) & to_int32(right)) as f64,
+        BinaryOp::Or => (to_int32(left) | to_int32(right)) as f64,
+        BinaryOp::XOr => (to_int32(left) ^ to_int32(right)) as f64,
+        _ => f64::NAN,
+    }
+}
+
+/// `None` where the operator throws
+fn big_int_op(operator: BinaryOp, left: &BigInt, right: &BigInt) -> Option<BigInt> {
+    match operator {
+        BinaryOp::Plus => Some(left + right),
+        BinaryOp::Minus => Some(left - right),
+        BinaryOp::Times => Some(left * right),
+        BinaryOp::Over => left.checked_div(right),
+        BinaryOp::Mod => left.checked_rem(right),
+        BinaryOp::PowerOf => {
+            if right.is_negative() {
+                return None;
+            }
+            if left.bit_len() <= 1 {
+                // 0, 1 and -1 stay small for any exponent
+                let even = right.checked_rem(&BigInt::from(2u64))?.is_zero();
+                return Some(match (left.is_zero(), right.is_zero()) {
+                    (_, true) => BigInt::from(1u64),
+                    (true, false) => BigInt::zero(),
+                    (false, false) if left.is_negative() && !even => BigInt::from(-1i64),
+                    (false, false) => BigInt::from(1u64),
+                });
+            }
+            let exponent = u32::try_from(right.to_i64()?).ok()?;
+            if left.bit_len().saturating_mul(exponent as u64) > MAX_BIG_INT_BITS + 1 {
+                return None;
+            }
+            Some(left.pow(exponent))
+        }
+        BinaryOp::LeftShift | BinaryOp::RightShift => {
+            let mut bits = right.to_i64()?;
+            if operator == BinaryOp::RightShift {
+                bits = bits.checked_neg()?;
+            }
+            if !left.is_zero() && bits > MAX_BIG_INT_BITS as i64 {
+                return None;
+            }
+            Some(left.shift(bits))
+        }
+        BinaryOp::And => Some(left & right),
+        BinaryOp::Or => Some(left | right),
+        BinaryOp::XOr => Some(left ^ right),
+        _ => None,
+    }
+}
+
+fn strict_equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => left == right,
+        _ => left == right,
+    }
+}
+
+fn loose_equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Undefined | Value::Null, Value::Undefined | Value::Null) => true,
+        (Value::Undefined | Value::Null, _) | (_, Value::Undefined | Value::Null) => false,
+        (Value::Number(number), Value::String(string))
+        | (Value::String(string), Value::Number(number)) => *number == string_to_number(string),
+        (Value::BigInt(big_int), Value::String(string))
+        | (Value::String(string), Value::BigInt(big_int)) => {
+            string_to_big_int(string).as_ref() == Some(big_int)
+        }
+        (Value::Bool(value), other) | (other, Value::Bool(value)) => {
+            loose_equal(&Value::Number(*value as u8 as f64), other)
+        }
+        (Value::BigInt(big_int), Value::Number(number))
+        | (Value::Number(number), Value::BigInt(big_int)) => {
+            compare_big_int(big_int, *number) == Some(std::cmp::Ordering::Equal)
+        }
+        _ => strict_equal(left, right),
+    }
+}
+
+/// `IsLessThan`, `None` when either side is `NaN` or a string that is
+/// not a bigint is compared with a bigint
+fn less_than(left: &Value, right: &Value) -> Option<bool> {
+    match (left, right) {
+        (Value::String(left), Value::String(right)) => return Some(left < right),
+        (Value::BigInt(left), Value::String(right)) => {
+            return Some(*left < string_to_big_int(right)?)
+        }
+        (Value::String(left), Value::BigInt(right)) => {
+            return Some(string_to_big_int(left)? < *right)
+        }
+        _ => {}
+    }
+    match (to_numeric(left), to_numeric(right)) {
+        (Numeric::Number(left), Numeric::Number(right)) => {
+            if left.is_nan() || right.is_nan() {
+                None
+            } else {
+                Some(left < right)
+            }
+        }
+        (Numeric::BigInt(left), Numeric::BigInt(right)) => Some(left < right),
+        (Numeric::BigInt(left), Numeric::Number(right)) => {
+            Some(compare_big_int(&left, right)? == std::cmp::Ordering::Less)
+        }
+        (Numeric::Number(left), Numeric::BigInt(right)) => {
+            Some(compare_big_int(&right, left)? == std::cmp::Ordering::Greater)
+        }
+    }
+}
+
+/// Compare a bigint and a number exactly, `None` for `NaN`
+fn compare_big_int(big_int: &BigInt, number: f64) -> Option<std::cmp::Ordering> {
+    use std::cmp::Ordering;
+    if number.is_nan() {
+        return None;
+    }
+    if number.is_infinite() {
+        return Some(if number > 0.0 {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        });
+    }
+    let integer = number.trunc();
+    Some(match big_int.cmp(&BigInt::from_f64(integer)?) {
+        Ordering::Equal if number > integer => Ordering::Less,
+        Ordering::Equal if number < integer => Ordering::Greater,
+        ordering => ordering,
+    })
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Undefined | Value::Null => false,
+        Value::Bool(value) => *value,
+        Value::Number(value) => *value != 0.0 && !value.is_nan(),
+        Value::BigInt(value) => !value.is_zero(),
+        Value::String(value) => !value.is_empty(),
+    }
+}
+
+fn type_of(value: &Value) -> &'static str {
+    match value {
+        Value::Undefined => "undefined",
+        Value::Null => "object",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::BigInt(_) => "bigint",
+        Value::String(_) => "string",
+    }
+}
+
+fn to_numeric(value: &Value) -> Numeric {
+    Numeric::Number(match value {
+        Value::Undefined => f64::NAN,
+        Value::Null => 0.0,
+        Value::Bool(value) => *value as u8 as f64,
+        Value::Number(value) => *value,
+        Value::BigInt(value) => return Numeric::BigInt(value.clone()),
+        Value::String(value) => string_to_number(value),
+    })
+}
+
+fn to_string(value: &Value) -> Vec<u16> {
+    let text = match value {
+        Value::Undefined => "undefined".to_string(),
+        Value::Null => "null".to_string(),
+        Value::Bool(value) => value.to_string(),
+        Value::Number(value) => number_to_string(*value),
+        Value::BigInt(value) => value.to_string(),
+        Value::String(value) => return value.clone(),
+    };
+    text.encode_utf16().collect()
+}
+
+fn to_uint32(value: f64) -> u32 {
+    if !value.is_finite() {
+        return 0;
+    }
+    let modulo = value.trunc() % 4294967296.0;
+    if modulo < 0.0 {
+        (modulo + 4294967296.0) as u32
+    } else {
+        modulo as u32
+    }
+}
+
+fn to_int32(value: f64) -> i32 {
+    to_uint32(value) as i32
+}
+
+/// `Number::toString` with a radix of 10
+fn number_to_string(value: f64) -> String {
+    if value.is_nan() {
+        return "NaN".to_string();
+    }
+    if value == 0.0 {
+        return "0".to_string();
+    }
+    if value.is_infinite() {
+        return if value > 0.0 { "Infinity" } else { "-Infinity" }.to_string();
+    }
+    if value < 0.0 {
+        return format!("-{}", number_to_string(-value));
+    }
+    // the shortest digits that round trip and the exponent of the first
+    let formatted = format!("{:e}", value);
+    let (mantissa, exponent) = formatted.split_once('e').unwrap_or((&formatted, "0"));
+    let digits: String = mantissa.chars().filter(|ch| *ch != '.').collect();
+    let k = digits.len() as i32;
+    let n = exponent.parse::<i32>().unwrap_or(0) + 1;
+    if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat(n.unsigned_abs() as usize), digits)
+    } else {
+        let sign = if n - 1 < 0 { '-' } else { '+' };
+        let exponent = (n - 1).unsigned_abs();
+        if k == 1 {
+            format!("{}e{}{}", digits, sign, exponent)
+        } else {
+            format!("{}.{}e{}{}", &digits[..1], &digits[1..], sign, exponent)
+        }
+    }
+}
+
+/// `StringToNumber`
+fn string_to_number(units: &[u16]) -> f64 {
+    let text = match String::from_utf16(units) {
+        Ok(text) => text,
+        Err(_) => return f64::NAN,
+    };
+    let text = text.trim_matches(is_whitespace);
+    if text.is_empty() {
+        return 0.0;
+    }
+    match text {
+        "Infinity" | "+Infinity" => return f64::INFINITY,
+        "-Infinity" => return f64::NEG_INFINITY,
+        _ => {}
+    }
+    if let Some(value) = prefixed_integer(text) {
+        return value.map_or(f64::NAN, |value| value.to_f64());
+    }
+    // leaves out the `inf` and `nan` that rust accepts
+    if !text
+        .bytes()
+        .all(|byte| byte.is_ascii_digit() || matches!(byte, b'.' | b'e' | b'E' | b'+' | b'-'))
+    {
+        return f64::NAN;
+    }
+    text.parse().unwrap_or(f64::NAN)
+}
+
+/// `StringToBigInt`, `None` when the string is not an integer
+fn string_to_big_int(units: &[u16]) -> Option<BigInt> {
+    let text = String::from_utf16(units).ok()?;
+    let text = text.trim_matches(is_whitespace);
+    if text.is_empty() {
+        return Some(BigInt::zero());
+    }
+    if let Some(value) = prefixed_integer(text) {
+        return value;
+    }
+    let (negative, digits) = match text.as_bytes()[0] {
+        b'-' => (true, &text[1..]),
+        b'+' => (false, &text[1..]),
+        _ => (false, text),
+    };
+    let value = BigInt::from_str_radix(digits, 10)?;
+    Some(if negative { -value } else { value })
+}
+
+/// A `0x`, `0o` or `0b` integer, `None` without a prefix
+fn prefixed_integer(text: &str) -> Option<Option<BigInt>> {
+    let radix = match text.get(..2)? {
+        "0x" | "0X" => 16,
+        "0o" | "0O" => 8,
+        "0b" | "0B" => 2,
+        _ => return None,
+    };
+    Some(BigInt::from_str_radix(&text[2..], radix))
+}
+
+/// The white space and line terminators trimmed by `StringToNumber`
+fn is_whitespace(ch: char) -> bool {
+    matches!(
+        ch,
+        '\t' | '\n' | '\u{b}' | '\u{c}' | '\r' | ' ' | '\u{a0}' | '\u{1680}' | '\u{2000}'
+            ..='\u{200a}'
+                | '\u{2028}'
+                | '\u{2029}'
+                | '\u{202f}'
+                | '\u{205f}'
+                | '\u{3000}'
+                | '\u{feff}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::LogicalExpr;
+    use crate::transform::build::{expr_stmt, ident_expr, string};
+
+    fn number(raw: &str) -> Expr<String> {
+        Expr::Lit(Lit::Number(raw.to_string()))
+    }
+
+    fn big_int(raw: &str) -> Expr<String> {
+        Expr::Lit(Lit::BigInt(raw.to_string()))
+    }
+
+    fn binary(operator: BinaryOp, left: Expr<String>, right: Expr<String>) -> Expr<String> {
+        Expr::Binary(BinaryExpr {
+            operator,
+            left: Box::new(left),
+            right: Box::new(right),
+        })
+    }
+
+    fn unary(operator: UnaryOp, argument: Expr<String>) -> Expr<String> {
+        Expr::Unary(UnaryExpr {
+            operator,
+            prefix: true,
+            argument: Box::new(argument),
+        })
+    }
+
+    fn folded(expr: Expr<String>) -> Expr<String> {
+        fold_expr(expr).0
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(
+            folded(binary(BinaryOp::Times, number("4"), number("1024"))),
+            number("4096")
+        );
+        assert_eq!(
+            folded(binary(BinaryOp::Plus, number("0.1"), number("0.2"))),
+            number("0.30000000000000004")
+        );
+        assert_eq!(
+            folded(binary(BinaryOp::Times, number("1e21"), number("10"))),
+            number("1e+22")
+        );
+        assert_eq!(
+            folded(binary(
+                BinaryOp::UnsignedRightShift,
+                negate(number("1")),
+                number("0")
+            )),
+            number("4294967295")
+        );
+        assert_eq!(
+            folded(binary(BinaryOp::Plus, string("a"), number("1"))),
+            string("a1")
+        );
+    }
+
+    #[test]
+    fn negative_zero() {
+        let negative_zero = negate(number("0"));
+        assert_eq!(
+            folded(binary(BinaryOp::Times, number("0"), negate(number("1")))),
+            negative_zero
+        );
+        // already the form a fold emits
+        assert!(fold_expr(negative_zero.clone()).1.is_empty());
+        assert_eq!(
+            folded(binary(BinaryOp::Over, number("1"), negative_zero.clone())),
+            over(negate(number("1")), number("0"))
+        );
+        assert_eq!(
+            folded(binary(
+                BinaryOp::StrictEqual,
+                negative_zero.clone(),
+                number("0")
+            )),
+            Expr::Lit(Lit::Boolean(true))
+        );
+        assert_eq!(
+            folded(binary(BinaryOp::Plus, string(""), negative_zero)),
+            string("0")
+        );
+    }
+
+    #[test]
+    fn not_a_number() {
+        let nan = over(number("0"), number("0"));
+        assert!(fold_expr(nan.clone()).1.is_empty());
+        assert_eq!(
+            folded(binary(BinaryOp::Times, string("a"), number("1"))),
+            nan
+        );
+        assert_eq!(
+            folded(binary(BinaryOp::StrictEqual, nan.clone(), nan.clone())),
+            Expr::Lit(Lit::Boolean(false))
+        );
+        assert_eq!(
+            folded(binary(BinaryOp::GreaterThanEqual, nan.clone(), number("1"))),
+            Expr::Lit(Lit::Boolean(false))
+        );
+        assert_eq!(
+            folded(unary(UnaryOp::Not, nan.clone())),
+            Expr::Lit(Lit::Boolean(true))
+        );
+        assert_eq!(
+            folded(binary(BinaryOp::Plus, nan, string(""))),
+            string("NaN")
+        );
+    }
+
+    #[test]
+    fn big_ints() {
+        assert_eq!(
+            folded(binary(BinaryOp::PowerOf, big_int("2n"), big_int("64n"))),
+            big_int("18446744073709551616n")
+        );
+        assert_eq!(
+            folded(binary(BinaryOp::Minus, big_int("1n"), big_int("2n"))),
+            negate(big_int("1n"))
+        );
+        assert_eq!(
+            folded(binary(BinaryOp::Over, big_int("7n"), negate(big_int("2n")))),
+            negate(big_int("3n"))
+        );
+        assert_eq!(
+            folded(binary(BinaryOp::Equal, big_int("1n"), string("1"))),
+            Expr::Lit(Lit::Boolean(true))
+        );
+        assert_eq!(
+            folded(binary(BinaryOp::LessThan, big_int("1n"), number("1.5"))),
+            Expr::Lit(Lit::Boolean(true))
+        );
+        assert_eq!(
+            folded(unary(UnaryOp::TypeOf, big_int("0n"))),
+            string("bigint")
+        );
+    }
+
+    #[test]
+    fn throwing_operators_are_kept() {
+        for expr in [
+            binary(BinaryOp::Plus, big_int("1n"), number("1")),
+            binary(BinaryOp::Over, big_int("1n"), big_int("0n")),
+            binary(BinaryOp::PowerOf, big_int("2n"), negate(big_int("1n"))),
+            binary(BinaryOp::PowerOf, big_int("2n"), big_int("2048n")),
+            binary(BinaryOp::In, string("a"), string("b")),
+            unary(UnaryOp::Plus, big_int("1n")),
+        ] {
+            let (after, folds) = fold_expr(expr.clone());
+            assert_eq!(after, expr);
+            assert!(folds.is_empty());
+        }
+    }
+
+    #[test]
+    fn logical_keeps_the_evaluated_operand() {
+        let logical = |operator, left| {
+            Expr::Logical(LogicalExpr {
+                operator,
+                left: Box::new(left),
+                right: Box::new(ident_expr("b")),
+            })
+        };
+        assert_eq!(folded(logical(LogicalOp::And, number("0"))), number("0"));
+        assert_eq!(folded(logical(LogicalOp::Or, number("0"))), ident_expr("b"));
+        assert_eq!(
+            folded(logical(LogicalOp::NullishCoalescing, number("0"))),
+            number("0")
+        );
+        assert_eq!(
+            folded(logical(LogicalOp::NullishCoalescing, undefined())),
+            ident_expr("b")
+        );
+        // the left side is not a literal
+        let unknown = logical(LogicalOp::And, ident_expr("a"));
+        assert_eq!(folded(unknown.clone()), unknown);
+    }
+
+    #[test]
+    fn references_are_not_unwrapped() {
+        let reference = Expr::Logical(LogicalExpr {
+            operator: LogicalOp::And,
+            left: Box::new(Expr::Lit(Lit::Boolean(true))),
+            right: Box::new(ident_expr("a")),
+        });
+        let type_of = unary(UnaryOp::TypeOf, reference.clone());
+        assert_eq!(folded(type_of.clone()), type_of);
+        assert_eq!(folded(reference), ident_expr("a"));
+    }
+
+    #[test]
+    fn statement_strings_are_not_folded() {
+        let concat = binary(BinaryOp::Plus, string("use "), string("strict"));
+        let program = Program::script(vec![expr_stmt(concat.clone())]);
+        let (after, folds) = fold(program.clone());
+        assert_eq!(after, program);
+        assert!(folds.is_empty());
+        assert_eq!(folded(concat), string("use strict"));
+    }
+
+    #[test]
+    fn folds_are_listed_innermost_first() {
+        let inner = binary(BinaryOp::Plus, number("1"), number("2"));
+        let outer = binary(BinaryOp::Times, inner.clone(), number("3"));
+        let (after, folds) = fold_expr(outer);
+        assert_eq!(after, number("9"));
+        assert_eq!(
+            folds,
+            vec![
+                Fold {
+                    before: inner,
+                    after: number("3"),
+                },
+                Fold {
+                    before: binary(BinaryOp::Times, number("3"), number("3")),
+                    after: number("9"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn truthiness_of_constants() {
+        assert_eq!(truthiness(&number("0")), Some(false));
+        assert_eq!(truthiness(&negate(number("0"))), Some(false));
+        assert_eq!(truthiness(&over(number("0"), number("0"))), Some(false));
+        assert_eq!(truthiness(&big_int("0n")), Some(false));
+        assert_eq!(truthiness(&string::<String>("0")), Some(true));
+        assert_eq!(truthiness(&ident_expr::<String>("a")), None);
+    }
+}
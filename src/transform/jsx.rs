@@ -0,0 +1,832 @@
+//! Lower JSX to plain function calls
+//!
+//! With the classic runtime every element becomes a call to the pragma
+//! with the props and children as arguments
+//! ```jsx
+//! <a href={url}>Home</a>
+//! ```
+//! becomes
+//! ```js
+//! React.createElement("a", { href: url }, "Home");
+//! ```
+//! and with the automatic runtime the children move into the props,
+//! a `key` moves into its own argument and the helpers are imported
+//! from `<import_source>/jsx-runtime`
+//! ```js
+//! import { jsx as _jsx } from "react/jsx-runtime";
+//! _jsx("a", { href: url, children: "Home" });
+//! ```
+//! Text is trimmed the same way as other JSX compilers: lines are
+//! joined with a single space and whitespace only lines are dropped.
+//!
+//! Some parts are not covered
+//! - only the common named HTML entities are decoded, any other
+//!   `&name;` is kept as written
+//! - `/** @jsx h */` pragma comments are not read, the pragma
+//!   comes from `JsxRuntime`
+//!
+//! The helpers are named `_jsx`, `_jsxs` and `_Fragment`, or `_jsx2`
+//! and so on when the program already uses the name.
+use std::fmt;
+
+use unicode_id::UnicodeID;
+
+use crate::decl::{Decl, ImportSpecifier, ModImport, NormalImportSpec};
+use crate::expr::{Expr, Lit, ObjProp, Prop, PropKey, PropValue, StringLit};
+use crate::jsx::{
+    JsxAttr, JsxAttrName, JsxAttrValue, JsxChild, JsxElement, JsxElementName, JsxFragment,
+};
+use crate::transform::build::{
+    call, ident, ident_expr, insert_after_directives, member, string_value, var, Names,
+};
+use crate::transform::visit::{walk_expr, VisitMut};
+use crate::{Program, ProgramPart, PropKind, VarKind};
+
+/// How elements are turned into calls
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsxRuntime {
+    /// Call `pragma` with the children as trailing arguments,
+    /// fragments use `pragma_frag` as their type
+    Classic { pragma: String, pragma_frag: String },
+    /// Call `jsx` or `jsxs` imported from `<import_source>/jsx-runtime`
+    Automatic { import_source: String },
+}
+
+impl Default for JsxRuntime {
+    fn default() -> Self {
+        JsxRuntime::Classic {
+            pragma: "React.createElement".to_string(),
+            pragma_frag: "React.Fragment".to_string(),
+        }
+    }
+}
+
+impl JsxRuntime {
+    /// The automatic runtime importing from `react`
+    pub fn automatic() -> Self {
+        JsxRuntime::Automatic {
+            import_source: "react".to_string(),
+        }
+    }
+}
+
+/// Replace every JSX element and fragment in `program`
+/// with the calls `runtime` describes
+pub fn lower<T>(mut program: Program<T>, runtime: &JsxRuntime) -> Result<Program<T>, JsxError>
+where
+    T: AsRef<str> + From<String>,
+{
+    let helpers = Helpers::new(&program);
+    let mut lowering = Lowering {
+        runtime,
+        helpers,
+        used: Used::default(),
+        error: None,
+    };
+    lowering.visit_program(&mut program);
+    if let Some(error) = lowering.error {
+        return Err(error);
+    }
+    if let JsxRuntime::Automatic { import_source } = runtime {
+        let source = format!("{}/jsx-runtime", import_source);
+        let helpers = lowering.used.helpers(&lowering.helpers);
+        if !helpers.is_empty() {
+            match &mut program {
+                Program::Mod(parts, _) => insert_after_directives(parts, import(&source, &helpers)),
+                Program::Script(parts, _) => {
+                    insert_after_directives(parts, require(&source, &helpers))
+                }
+            }
+        }
+    }
+    Ok(program)
+}
+
+/// JSX that cannot be lowered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsxError {
+    /// A `{...children}` child, which no runtime accepts
+    SpreadChild,
+}
+
+impl fmt::Display for JsxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsxError::SpreadChild => f.write_str("spread children are not supported"),
+        }
+    }
+}
+
+impl std::error::Error for JsxError {}
+
+struct Lowering<'a> {
+    runtime: &'a JsxRuntime,
+    helpers: Helpers,
+    used: Used,
+    error: Option<JsxError>,
+}
+
+/// The local names of the automatic runtime helpers
+struct Helpers {
+    jsx: String,
+    jsxs: String,
+    fragment: String,
+}
+
+impl Helpers {
+    /// Names that no binding or reference in `program` uses
+    fn new<T>(program: &Program<T>) -> Self
+    where
+        T: AsRef<str>,
+    {
+        let mut names = Names::new(program);
+        Helpers {
+            jsx: names.fresh("_jsx"),
+            jsxs: names.fresh("_jsxs"),
+            fragment: names.fresh("_Fragment"),
+        }
+    }
+}
+
+/// The automatic runtime helpers that need to be imported
+#[derive(Default)]
+struct Used {
+    jsx: bool,
+    jsxs: bool,
+    fragment: bool,
+}
+
+impl Used {
+    /// Pairs of the exported name and the local helper name
+    fn helpers<'h>(&self, names: &'h Helpers) -> Vec<(&'static str, &'h str)> {
+        let mut ret = Vec::new();
+        if self.jsx {
+            ret.push(("jsx", names.jsx.as_str()));
+        }
+        if self.jsxs {
+            ret.push(("jsxs", names.jsxs.as_str()));
+        }
+        if self.fragment {
+            ret.push(("Fragment", names.fragment.as_str()));
+        }
+        ret
+    }
+}
+
+impl<'a, T> VisitMut<T> for Lowering<'a>
+where
+    T: AsRef<str> + From<String>,
+{
+    fn visit_expr(&mut self, expr: &mut Expr<T>) {
+        if self.error.is_some() {
+            return;
+        }
+        let lowered = match expr {
+            Expr::JsxElement(_) | Expr::JsxFragment(_) => {
+                match std::mem::replace(expr, Expr::This) {
+                    Expr::JsxElement(element) => self.element(element),
+                    Expr::JsxFragment(fragment) => self.fragment(fragment),
+                    _ => unreachable!(),
+                }
+            }
+            _ => return walk_expr(self, expr),
+        };
+        match lowered {
+            Ok(lowered) => *expr = lowered,
+            Err(error) => self.error = Some(error),
+        }
+    }
+}
+
+impl<'a> Lowering<'a> {
+    fn element<T>(&mut self, element: JsxElement<T>) -> Result<Expr<T>, JsxError>
+    where
+        T: AsRef<str> + From<String>,
+    {
+        let tag = tag(element.name);
+        let mut key = None;
+        let mut props = Vec::with_capacity(element.attributes.len());
+        for attr in element.attributes {
+            match attr {
+                JsxAttr::Spread(mut expr) => {
+                    self.visit_expr(&mut expr);
+                    props.push(ObjProp::Spread(expr));
+                }
+                JsxAttr::Attr(attr) => {
+                    let value = match attr.value {
+                        None => Expr::Lit(Lit::Boolean(true)),
+                        Some(value) => self.attr_value(value)?,
+                    };
+                    let name = attr_name(attr.name);
+                    if name == "key" && matches!(self.runtime, JsxRuntime::Automatic { .. }) {
+                        key = Some(value);
+                    } else {
+                        props.push(prop(&name, value));
+                    }
+                }
+            }
+        }
+        let children = self.children(element.children)?;
+        Ok(self.create(tag, props, children, key))
+    }
+
+    fn fragment<T>(&mut self, fragment: JsxFragment<T>) -> Result<Expr<T>, JsxError>
+    where
+        T: AsRef<str> + From<String>,
+    {
+        let tag = match self.runtime {
+            JsxRuntime::Classic { pragma_frag, .. } => dotted(pragma_frag),
+            JsxRuntime::Automatic { .. } => {
+                self.used.fragment = true;
+                ident_expr(&self.helpers.fragment)
+            }
+        };
+        let children = self.children(fragment.children)?;
+        Ok(self.create(tag, Vec::new(), children, None))
+    }
+
+    /// The call for a single element or fragment
+    fn create<T>(
+        &mut self,
+        tag: Expr<T>,
+        mut props: Vec<ObjProp<T>>,
+        mut children: Vec<Expr<T>>,
+        key: Option<Expr<T>>,
+    ) -> Expr<T>
+    where
+        T: From<String>,
+    {
+        match self.runtime {
+            JsxRuntime::Classic { pragma, .. } => {
+                let props = if props.is_empty() {
+                    Expr::Lit(Lit::Null)
+                } else {
+                    Expr::Obj(props)
+                };
+                let mut arguments = vec![tag, props];
+                arguments.append(&mut children);
+                call(dotted(pragma), arguments)
+            }
+            JsxRuntime::Automatic { .. } => {
+                let callee = if children.len() > 1 {
+                    self.used.jsxs = true;
+                    props.push(prop(
+                        "children",
+                        Expr::Array(children.into_iter().map(Some).collect()),
+                    ));
+                    &self.helpers.jsxs
+                } else {
+                    self.used.jsx = true;
+                    if let Some(child) = children.pop() {
+                        props.push(prop("children", child));
+                    }
+                    &self.helpers.jsx
+                };
+                let mut arguments = vec![tag, Expr::Obj(props)];
+                arguments.extend(key);
+                call(ident_expr(callee), arguments)
+            }
+        }
+    }
+
+    fn attr_value<T>(&mut self, value: JsxAttrValue<T>) -> Result<Expr<T>, JsxError>
+    where
+        T: AsRef<str> + From<String>,
+    {
+        Ok(match value {
+            JsxAttrValue::String(lit) => {
+                let raw = match &lit {
+                    StringLit::Double(raw) | StringLit::Single(raw) => raw.as_ref(),
+                };
+                string_value(&collapse_line_breaks(&decode_entities(raw)))
+            }
+            JsxAttrValue::Expr(mut expr) => {
+                self.visit_expr(&mut expr);
+                expr
+            }
+            JsxAttrValue::Element(element) => self.element(element)?,
+            JsxAttrValue::Fragment(fragment) => self.fragment(fragment)?,
+        })
+    }
+
+    fn children<T>(&mut self, children: Vec<JsxChild<T>>) -> Result<Vec<Expr<T>>, JsxError>
+    where
+        T: AsRef<str> + From<String>,
+    {
+        let mut ret = Vec::with_capacity(children.len());
+        for child in children {
+            match child {
+                JsxChild::Text(raw) => {
+                    let text = clean_text(&decode_entities(raw.as_ref()));
+                    if !text.is_empty() {
+                        ret.push(string_value(&text));
+                    }
+                }
+                JsxChild::Expr(None) => {}
+                JsxChild::Expr(Some(mut expr)) => {
+                    self.visit_expr(&mut expr);
+                    ret.push(expr);
+                }
+                JsxChild::Spread(_) => return Err(JsxError::SpreadChild),
+                JsxChild::Element(element) => ret.push(self.element(element)?),
+                JsxChild::Fragment(fragment) => ret.push(self.fragment(fragment)?),
+            }
+        }
+        Ok(ret)
+    }
+}
+
+/// The first argument of the call, a string for built in
+/// tags and a reference for components
+fn tag<T>(name: JsxElementName<T>) -> Expr<T>
+where
+    T: AsRef<str> + From<String>,
+{
+    if name.is_intrinsic() {
+        return match name {
+            JsxElementName::Ident(ident) => string_value(ident.name.as_ref()),
+            JsxElementName::Namespaced(name) => string_value(&format!(
+                "{}:{}",
+                name.namespace.name.as_ref(),
+                name.name.name.as_ref()
+            )),
+            JsxElementName::Member(_) => unreachable!(),
+        };
+    }
+    match name {
+        JsxElementName::Member(expr) => {
+            let object = if expr.object.name.as_ref() == "this" {
+                Expr::This
+            } else {
+                Expr::Ident(expr.object)
+            };
+            expr.properties
+                .into_iter()
+                .fold(object, |object, property| {
+                    member(object, property.name.as_ref())
+                })
+        }
+        JsxElementName::Ident(ident) if ident.name.as_ref() == "this" => Expr::This,
+        JsxElementName::Ident(ident) => Expr::Ident(ident),
+        JsxElementName::Namespaced(_) => unreachable!(),
+    }
+}
+
+fn attr_name<T: AsRef<str>>(name: JsxAttrName<T>) -> String {
+    match name {
+        JsxAttrName::Ident(ident) => ident.name.as_ref().to_string(),
+        JsxAttrName::Namespaced(name) => format!(
+            "{}:{}",
+            name.namespace.name.as_ref(),
+            name.name.name.as_ref()
+        ),
+    }
+}
+
+/// `name: value`, quoting `name` when it is not an identifier
+fn prop<T: From<String>>(name: &str, value: Expr<T>) -> ObjProp<T> {
+    let key = if is_ident_name(name) {
+        PropKey::Expr(ident_expr(name))
+    } else {
+        match string_value(name) {
+            Expr::Lit(lit) => PropKey::Lit(lit),
+            _ => unreachable!(),
+        }
+    };
+    ObjProp::Prop(Prop {
+        key,
+        value: PropValue::Expr(value),
+        kind: PropKind::Init,
+        method: false,
+        computed: false,
+        short_hand: false,
+        is_static: false,
+        is_abstract: false,
+        decorators: Vec::new(),
+        accessibility: None,
+        is_optional: false,
+    })
+}
+
+/// If `name` can be written as an identifier, by the ID_Start and
+/// ID_Continue properties plus the extra characters JavaScript allows
+fn is_ident_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_id_start() || c == '$' || c == '_')
+        && chars.all(|c| c.is_id_continue() || matches!(c, '$' | '\u{200C}' | '\u{200D}'))
+}
+
+/// `React.createElement` as a chain of member accesses
+fn dotted<T: From<String>>(path: &str) -> Expr<T> {
+    let mut parts = path.split('.');
+    let first = parts.next().unwrap_or_default();
+    let object = if first == "this" {
+        Expr::This
+    } else {
+        ident_expr(first)
+    };
+    parts.fold(object, member)
+}
+
+/// `import { jsx as _jsx } from "react/jsx-runtime";`
+fn import<T: From<String>>(source: &str, helpers: &[(&str, &str)]) -> ProgramPart<T> {
+    let specifiers = helpers
+        .iter()
+        .map(|(imported, local)| NormalImportSpec {
+            alias: Some(ident(local)),
+            imported: ident(imported).into(),
+            is_type_only: false,
+        })
+        .collect();
+    ProgramPart::Decl(Decl::Import(Box::new(ModImport {
+        specifiers: vec![ImportSpecifier::Normal(specifiers)],
+        source: Lit::String(StringLit::Double(T::from(source.to_string()))),
+        is_type_only: false,
+        attributes: Vec::new(),
+    })))
+}
+
+/// `const _jsx = require("react/jsx-runtime").jsx;`
+fn require<T: From<String>>(source: &str, helpers: &[(&str, &str)]) -> ProgramPart<T> {
+    var(
+        VarKind::Const,
+        helpers
+            .iter()
+            .map(|(imported, local)| {
+                let module = call(ident_expr("require"), vec![string_value(source)]);
+                (*local, Some(member(module, imported)))
+            })
+            .collect(),
+    )
+}
+
+/// Join the lines of a text child, dropping the indentation
+/// around line breaks and any whitespace only lines
+fn clean_text(text: &str) -> String {
+    let lines: Vec<&str> = text
+        .split("\r\n")
+        .flat_map(|l| l.split(['\n', '\r']))
+        .collect();
+    let last_non_empty = lines
+        .iter()
+        .rposition(|line| line.chars().any(|c| c != ' ' && c != '\t'))
+        .unwrap_or(0);
+    let mut ret = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        let mut line = line.replace('\t', " ");
+        if i != lines.len() - 1 {
+            line.truncate(line.trim_end_matches(' ').len());
+        }
+        let line = if i != 0 {
+            line.trim_start_matches(' ')
+        } else {
+            &line
+        };
+        if !line.is_empty() {
+            ret.push_str(line);
+            if i != last_non_empty {
+                ret.push(' ');
+            }
+        }
+    }
+    ret
+}
+
+/// Replace each line break and the whitespace after it with
+/// a single space, for attribute strings
+fn collapse_line_breaks(value: &str) -> String {
+    let mut ret = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == '\n' && chars.peek().is_some_and(|c| c.is_whitespace()) {
+            while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                chars.next();
+            }
+            ret.push(' ');
+        } else {
+            ret.push(ch);
+        }
+    }
+    ret
+}
+
+/// Decode `&amp;`, `&#123;` and `&#x7B;` style references
+fn decode_entities(raw: &str) -> String {
+    let mut ret = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(start) = rest.find('&') {
+        ret.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| entity(&rest[1..end + 1]).map(|ch| (ch, end + 2)));
+        match decoded {
+            Some((ch, len)) => {
+                ret.push(ch);
+                rest = &rest[len..];
+            }
+            None => {
+                ret.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    ret.push_str(rest);
+    ret
+}
+
+fn entity(name: &str) -> Option<char> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = if let Some(hex) = number.strip_prefix(['x', 'X']) {
+            u32::from_str_radix(hex, 16).ok()?
+        } else {
+            number.parse().ok()?
+        };
+        return char::from_u32(code);
+    }
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        "copy" => '\u{a9}',
+        "reg" => '\u{ae}',
+        "trade" => '\u{2122}',
+        "hellip" => '\u{2026}',
+        "mdash" => '\u{2014}',
+        "ndash" => '\u{2013}',
+        "lsquo" => '\u{2018}',
+        "rsquo" => '\u{2019}',
+        "ldquo" => '\u{201c}',
+        "rdquo" => '\u{201d}',
+        "laquo" => '\u{ab}',
+        "raquo" => '\u{bb}',
+        "bull" => '\u{2022}',
+        "middot" => '\u{b7}',
+        "times" => '\u{d7}',
+        "divide" => '\u{f7}',
+        "deg" => '\u{b0}',
+        "plusmn" => '\u{b1}',
+        "sect" => '\u{a7}',
+        "para" => '\u{b6}',
+        "cent" => '\u{a2}',
+        "pound" => '\u{a3}',
+        "yen" => '\u{a5}',
+        "euro" => '\u{20ac}',
+        "larr" => '\u{2190}',
+        "rarr" => '\u{2192}',
+        "uarr" => '\u{2191}',
+        "darr" => '\u{2193}',
+        "shy" => '\u{ad}',
+        "zwj" => '\u{200d}',
+        "zwnj" => '\u{200c}',
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jsx::{JsxAttribute, JsxMemberExpr};
+    use crate::transform::build::expr_stmt;
+    use crate::Dir;
+
+    fn element(
+        name: &str,
+        attributes: Vec<JsxAttr<String>>,
+        children: Vec<JsxChild<String>>,
+    ) -> JsxElement<String> {
+        JsxElement {
+            name: JsxElementName::Ident(ident(name)),
+            attributes,
+            self_closing: children.is_empty(),
+            children,
+        }
+    }
+
+    fn attr(name: &str, value: Option<JsxAttrValue<String>>) -> JsxAttr<String> {
+        JsxAttr::Attr(JsxAttribute {
+            name: JsxAttrName::Ident(ident(name)),
+            value,
+        })
+    }
+
+    fn text(raw: &str) -> JsxChild<String> {
+        JsxChild::Text(raw.to_string())
+    }
+
+    /// The parts of the module of `parts` after lowering it
+    fn lowered(parts: Vec<ProgramPart<String>>, runtime: &JsxRuntime) -> Vec<ProgramPart<String>> {
+        match lower(Program::module(parts), runtime).unwrap() {
+            Program::Mod(parts, _) | Program::Script(parts, _) => parts,
+        }
+    }
+
+    fn link() -> Expr<String> {
+        Expr::JsxElement(element(
+            "a",
+            vec![attr("href", Some(JsxAttrValue::Expr(ident_expr("url"))))],
+            vec![text("Home")],
+        ))
+    }
+
+    #[test]
+    fn classic_runtime() {
+        let parts = lowered(
+            vec![
+                expr_stmt(link()),
+                expr_stmt(Expr::JsxFragment(JsxFragment {
+                    children: vec![JsxChild::Element(element("br", Vec::new(), Vec::new()))],
+                })),
+            ],
+            &JsxRuntime::default(),
+        );
+        assert_eq!(
+            parts,
+            vec![
+                expr_stmt(call(
+                    dotted("React.createElement"),
+                    vec![
+                        string_value("a"),
+                        Expr::Obj(vec![prop("href", ident_expr("url"))]),
+                        string_value("Home"),
+                    ],
+                )),
+                expr_stmt(call(
+                    dotted("React.createElement"),
+                    vec![
+                        dotted("React.Fragment"),
+                        Expr::Lit(Lit::Null),
+                        call(
+                            dotted("React.createElement"),
+                            vec![string_value("br"), Expr::Lit(Lit::Null)],
+                        ),
+                    ],
+                )),
+            ]
+        );
+    }
+
+    #[test]
+    fn automatic_runtime() {
+        let list = Expr::JsxFragment(JsxFragment {
+            children: vec![
+                JsxChild::Element(element(
+                    "li",
+                    vec![attr("key", Some(JsxAttrValue::Expr(ident_expr("id"))))],
+                    vec![JsxChild::Expr(Some(ident_expr("name")))],
+                )),
+                JsxChild::Element(element("li", vec![attr("hidden", None)], Vec::new())),
+            ],
+        });
+        let parts = lowered(
+            vec![
+                ProgramPart::Dir(Dir {
+                    expr: Lit::String(StringLit::Double("use client".to_string())),
+                    dir: "use client".to_string(),
+                }),
+                expr_stmt(list),
+            ],
+            &JsxRuntime::automatic(),
+        );
+        assert_eq!(
+            parts[1],
+            import(
+                "react/jsx-runtime",
+                &[
+                    ("jsx", "_jsx"),
+                    ("jsxs", "_jsxs"),
+                    ("Fragment", "_Fragment")
+                ]
+            )
+        );
+        let item = call(
+            ident_expr("_jsx"),
+            vec![
+                string_value("li"),
+                Expr::Obj(vec![prop("children", ident_expr("name"))]),
+                ident_expr("id"),
+            ],
+        );
+        let hidden = call(
+            ident_expr("_jsx"),
+            vec![
+                string_value("li"),
+                Expr::Obj(vec![prop("hidden", Expr::Lit(Lit::Boolean(true)))]),
+            ],
+        );
+        assert_eq!(
+            parts[2],
+            expr_stmt(call(
+                ident_expr("_jsxs"),
+                vec![
+                    ident_expr("_Fragment"),
+                    Expr::Obj(vec![prop(
+                        "children",
+                        Expr::Array(vec![Some(item), Some(hidden)])
+                    )]),
+                ],
+            ))
+        );
+    }
+
+    #[test]
+    fn helpers_avoid_names_in_use() {
+        let parts = lowered(
+            vec![
+                var(VarKind::Let, vec![("_jsx", None)]),
+                expr_stmt(ident_expr("_jsx2")),
+                expr_stmt(link()),
+            ],
+            &JsxRuntime::automatic(),
+        );
+        assert_eq!(parts[0], import("react/jsx-runtime", &[("jsx", "_jsx3")]));
+        let script = lower(
+            Program::script(vec![expr_stmt(link())]),
+            &JsxRuntime::automatic(),
+        );
+        match script.unwrap() {
+            Program::Script(parts, _) => {
+                assert_eq!(parts[0], require("react/jsx-runtime", &[("jsx", "_jsx")]))
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn tags_and_attribute_names() {
+        let provider = JsxElement {
+            name: JsxElementName::Member(JsxMemberExpr {
+                object: ident("Context"),
+                properties: vec![ident("Provider")],
+            }),
+            attributes: vec![attr(
+                "aria-label",
+                Some(JsxAttrValue::String(StringLit::Double(
+                    "Fish &amp;\n    chips".to_string(),
+                ))),
+            )],
+            children: vec![JsxChild::Element(element(
+                "my-widget",
+                Vec::new(),
+                Vec::new(),
+            ))],
+            self_closing: false,
+        };
+        let parts = lowered(
+            vec![expr_stmt(Expr::JsxElement(provider))],
+            &JsxRuntime::default(),
+        );
+        assert_eq!(
+            parts,
+            vec![expr_stmt(call(
+                dotted("React.createElement"),
+                vec![
+                    member(ident_expr("Context"), "Provider"),
+                    Expr::Obj(vec![prop("aria-label", string_value("Fish & chips"))]),
+                    call(
+                        dotted("React.createElement"),
+                        vec![string_value("my-widget"), Expr::Lit(Lit::Null)],
+                    ),
+                ],
+            ))]
+        );
+        match prop::<String>("aria-label", Expr::This) {
+            ObjProp::Prop(prop) => assert!(matches!(prop.key, PropKey::Lit(_))),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn spread_children_are_an_error() {
+        let program = Program::module(vec![expr_stmt(Expr::JsxElement(element(
+            "ul",
+            Vec::new(),
+            vec![JsxChild::Spread(ident_expr("items"))],
+        )))]);
+        assert_eq!(
+            lower(program, &JsxRuntime::default()),
+            Err(JsxError::SpreadChild)
+        );
+    }
+
+    #[test]
+    fn text_is_trimmed_by_line() {
+        assert_eq!(clean_text("  Hello  "), "  Hello  ");
+        assert_eq!(clean_text("\n    Hello\n    world  \n  "), "Hello world");
+        assert_eq!(clean_text("a\r\n\t\r\n b"), "a b");
+        assert_eq!(clean_text("\n   \n"), "");
+    }
+
+    #[test]
+    fn entities_are_decoded() {
+        assert_eq!(decode_entities("&lt;b&gt; &#123;&#x7D;"), "<b> {}");
+        assert_eq!(decode_entities("a &unknown; & b"), "a &unknown; & b");
+        assert_eq!(decode_entities("&nbsp;&#xD800;"), "\u{a0}&#xD800;");
+    }
+}
@@ -0,0 +1,9 @@
+pub(crate) mod build;
+pub mod dce;
+pub mod decorators;
+pub mod dts;
+pub mod fold;
+pub mod jsx;
+pub mod shake;
+pub mod using;
+pub mod visit;
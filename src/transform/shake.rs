@@ -0,0 +1,1213 @@
+//! Tree shaking across the modules of a `ModuleGraph`
+//!
+//! Every export of the entry is used, and from there each module keeps
+//! the top level code that is used and the code that has a side effect
+//! ```js
+//! // main.js
+//! import { format } from './lib/index.js';
+//! export const label = format(1);
+//! // lib/index.js
+//! export * from './format.js';
+//! export { parse } from './parse.js';
+//! // lib/format.js
+//! const units = ['b', 'kb'];
+//! const unused = { size: 1 };
+//! export function format(size) {
+//!     return size + units[0];
+//! }
+//! export function describe(size) {
+//!     return 'size ' + format(size);
+//! }
+//! console.log('format loaded');
+//! ```
+//! leaves `main.js` as it is. The barrel `lib/index.js` keeps the
+//! `export *` that `format` is found through, and the re-export of
+//! `parse` becomes `import './parse.js'` when `parse.js` has a side
+//! effect or is removed when it has none. `lib/format.js` becomes
+//! ```js
+//! const units = ['b', 'kb'];
+//! export function format(size) {
+//!     return size + units[0];
+//! }
+//! console.log('format loaded');
+//! ```
+//! - what is kept or removed is a top level statement, a single
+//!   declarator of a variable declaration or a single import or
+//!   export specifier
+//! - a variable with a value that is not pure by `analysis::purity`,
+//!   a class that is not pure and any statement but an expression
+//!   statement with a pure expression is a side effect
+//! - a declaration whose export is not used but that the module uses
+//!   itself loses its `export`, and an `export default` expression
+//!   that is not used is reduced to its side effects
+//! - an import of a module is kept as a side effect import when none
+//!   of its names are used, unless the module and everything it
+//!   imports has no side effect
+//!
+//! A module in `ShakeOptions::side_effect_free`, like the files of a
+//! package with `"sideEffects": false`, is never kept for its side
+//! effects alone. When something it exports is used it is kept with
+//! its side effects, like any other module.
+//!
+//! Modules are not merged and imports are not pointed at the module
+//! a name is declared in, so the re-exports a used name passes through
+//! are kept. A module that calls `eval` or uses `with` keeps all of its
+//! code, and a specifier that is not one of the modules is taken to
+//! have a side effect.
+use std::collections::{HashMap, HashSet};
+
+use crate::analysis::purity::{self, Purities, Purity, PurityOptions};
+use crate::analysis::scope::{self, RefKind, ScopeTree};
+use crate::analysis::visit::{walk_expr, walk_jsx_element, walk_prop, Visit};
+use crate::decl::{
+    Decl, DefaultExportDecl, ImportAttribute, ImportSpecifier, ModExport, ModImport, ModuleRef,
+    NamedExportDecl,
+};
+use crate::expr::{Expr, Lit, Prop, PropKey, PropValue};
+use crate::jsx::{JsxElement, JsxElementName};
+use crate::modules::graph::{
+    declared_names, Module, ModuleGraph, ModuleId, Resolution, DEFAULT_BINDING,
+};
+use crate::pat::Pat;
+use crate::stmt::Stmt;
+use crate::transform::build::expr_stmt;
+use crate::transform::dce::uses_dynamic_scope;
+use crate::{Ident, Program, ProgramPart};
+
+/// Remove what `entry` does not use from the modules of `graph`, with
+/// the result of each module at its index in `ModuleGraph::modules`
+/// and `None` for a module that no longer needs to be loaded
+pub fn shake<'a, T>(
+    graph: &ModuleGraph<'a, T>,
+    entry: ModuleId,
+    options: &ShakeOptions<'a, T>,
+) -> Vec<Option<Program<T>>>
+where
+    T: Clone + AsRef<str>,
+{
+    let shapes: Vec<Shape<'a, T>> = graph
+        .modules()
+        .iter()
+        .map(|module| Shape::new(module, &options.purity))
+        .collect();
+    let count = shapes.len();
+    let mut shaker = Shaker {
+        graph,
+        effects: effects(graph, &shapes, &options.side_effect_free),
+        live: shapes
+            .iter()
+            .map(|shape| vec![false; shape.items.len()])
+            .collect(),
+        shapes,
+        evaluated: vec![false; count],
+        namespaces: vec![false; count],
+        used: vec![HashSet::new(); count],
+        forwarded: vec![HashSet::new(); count],
+        work: vec![Work::Evaluate(entry), Work::Namespace(entry)],
+    };
+    shaker.run();
+    (0..count)
+        .map(|index| shaker.rebuild(ModuleId(index), entry))
+        .collect()
+}
+
+/// What tree shaking can not tell from the modules
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShakeOptions<'a, T> {
+    /// The modules that are only loaded for their exports
+    pub side_effect_free: Vec<ModuleId>,
+    /// The options for finding side effects in each module, annotated
+    /// expressions can be from any of them
+    pub purity: PurityOptions<'a, T>,
+}
+
+impl<T> Default for ShakeOptions<'_, T> {
+    fn default() -> Self {
+        ShakeOptions {
+            side_effect_free: Vec::new(),
+            purity: PurityOptions::default(),
+        }
+    }
+}
+
+/// If each module, or a module it imports, has a side effect
+fn effects<T>(
+    graph: &ModuleGraph<'_, T>,
+    shapes: &[Shape<'_, T>],
+    side_effect_free: &[ModuleId],
+) -> Vec<bool> {
+    let mut effects: Vec<bool> = shapes
+        .iter()
+        .enumerate()
+        .map(|(index, shape)| {
+            !side_effect_free.contains(&ModuleId(index))
+                && (shape.dynamic || shape.items.iter().any(|item| item.impure))
+        })
+        .collect();
+    loop {
+        let mut changed = false;
+        for (index, module) in graph.modules().iter().enumerate() {
+            let id = ModuleId(index);
+            if effects[index] || side_effect_free.contains(&id) {
+                continue;
+            }
+            let imports = module
+                .dependencies
+                .iter()
+                .enumerate()
+                .filter(|(_, dependency)| dependency.is_evaluated())
+                .any(|(dependency, _)| {
+                    graph
+                        .target(id, dependency)
+                        .is_none_or(|target| effects[target.0])
+                });
+            if imports {
+                effects[index] = true;
+                changed = true;
+            }
+        }
+        if !changed {
+            return effects;
+        }
+    }
+}
+
+/// A piece of a module that is kept or removed as a whole
+struct Item {
+    part: usize,
+    /// The declarator or import specifier in the part
+    index: usize,
+    /// The top level names it declares
+    names: Vec<String>,
+    exports: Vec<String>,
+    /// The top level names it refers to
+    references: Vec<String>,
+    /// The dependencies it loads with `import()`
+    dynamic_imports: Vec<usize>,
+    /// The dependency of an import and the name it imports, `None`
+    /// for the namespace
+    import: Option<(usize, Option<String>)>,
+    impure: bool,
+}
+
+/// An export that does not declare what it exports
+enum Forward {
+    /// `export { local as exported }`
+    Local {
+        part: usize,
+        index: usize,
+        local: String,
+        exported: String,
+    },
+    /// `export { imported as exported } from` or, when `imported` is
+    /// `None`, `export * as exported from`
+    Indirect {
+        part: usize,
+        index: usize,
+        dependency: usize,
+        imported: Option<String>,
+        exported: String,
+    },
+    /// `export * from`
+    Star { part: usize, dependency: usize },
+}
+
+/// The items and forwards of a module
+struct Shape<'a, T> {
+    items: Vec<Item>,
+    /// The item of each declarator or specifier of a part
+    by_part: HashMap<(usize, usize), usize>,
+    /// The items that declare each name
+    declared: HashMap<String, Vec<usize>>,
+    forwards: Vec<Forward>,
+    /// The dependency of each part that imports or re-exports
+    sources: HashMap<usize, usize>,
+    /// `eval` or `with` is used
+    dynamic: bool,
+    purities: Purities<'a, T>,
+}
+
+impl<'a, T> Shape<'a, T>
+where
+    T: AsRef<str>,
+{
+    fn new(module: &Module<'a, T>, options: &PurityOptions<'a, T>) -> Self {
+        let tree = scope::analyze(module.program);
+        let purities = purity::analyze(module.program, &tree, options);
+        let mut builder = Builder {
+            module,
+            tree: &tree,
+            purities: &purities,
+            items: Vec::new(),
+            forwards: Vec::new(),
+            sources: HashMap::new(),
+        };
+        for (index, part) in module.program.parts().iter().enumerate() {
+            builder.part(index, part);
+        }
+        let Builder {
+            items,
+            forwards,
+            sources,
+            ..
+        } = builder;
+        let mut by_part = HashMap::new();
+        let mut declared: HashMap<String, Vec<usize>> = HashMap::new();
+        for (index, item) in items.iter().enumerate() {
+            by_part.insert((item.part, item.index), index);
+            for name in &item.names {
+                declared.entry(name.clone()).or_default().push(index);
+            }
+        }
+        Shape {
+            items,
+            by_part,
+            declared,
+            forwards,
+            sources,
+            dynamic: uses_dynamic_scope(module.program),
+            purities,
+        }
+    }
+}
+
+/// Splits the top level of a module into items and forwards
+struct Builder<'s, 'a, T> {
+    module: &'s Module<'a, T>,
+    tree: &'s ScopeTree<'a, T>,
+    purities: &'s Purities<'a, T>,
+    items: Vec<Item>,
+    forwards: Vec<Forward>,
+    sources: HashMap<usize, usize>,
+}
+
+impl<'a, T> Builder<'_, 'a, T>
+where
+    T: AsRef<str>,
+{
+    fn part(&mut self, part: usize, program_part: &'a ProgramPart<T>) {
+        match program_part {
+            ProgramPart::Dir(_) => {}
+            ProgramPart::Decl(Decl::Import(import)) => self.import(part, import),
+            ProgramPart::Decl(Decl::ImportEquals(import)) => {
+                let mut item = item(part, 0, vec![name(&import.id)]);
+                match &import.module_ref {
+                    ModuleRef::Require(source) => {
+                        if let Some(dependency) = self.source(part, source) {
+                            if !import.is_type_only {
+                                item.import = Some((dependency, None));
+                            }
+                        }
+                    }
+                    ModuleRef::Entity(expr) => {
+                        self.uses(&mut item, |uses| uses.visit_expr(expr));
+                        item.impure = !self.purities.is_pure(expr);
+                    }
+                }
+                self.items.push(item);
+            }
+            ProgramPart::Decl(Decl::Export(export)) => self.export(part, export),
+            ProgramPart::Decl(decl) => self.decl(part, decl, false),
+            ProgramPart::Stmt(stmt) => {
+                let mut item = item(part, 0, Vec::new());
+                item.impure = match stmt {
+                    Stmt::Empty => false,
+                    Stmt::Expr(expr) => !self.purities.is_pure(expr),
+                    _ => true,
+                };
+                self.uses(&mut item, |uses| uses.visit_stmt(stmt));
+                self.items.push(item);
+            }
+        }
+    }
+
+    /// An item for each specifier, in order
+    fn import(&mut self, part: usize, import: &'a ModImport<T>) {
+        let dependency = self.source(part, &import.source);
+        let mut index = 0;
+        for specifier in &import.specifiers {
+            let specifiers = match specifier {
+                ImportSpecifier::Normal(specifiers) => specifiers
+                    .iter()
+                    .map(|specifier| {
+                        let local = specifier
+                            .alias
+                            .as_ref()
+                            .or_else(|| specifier.imported.as_ident());
+                        let imported = match specifier.is_type_only {
+                            true => None,
+                            false => specifier.imported.value().ok().map(Some),
+                        };
+                        (local, imported)
+                    })
+                    .collect(),
+                ImportSpecifier::Default(local) => {
+                    vec![(Some(local), Some(Some("default".to_string())))]
+                }
+                ImportSpecifier::Namespace(local) => vec![(Some(local), Some(None))],
+            };
+            for (local, imported) in specifiers {
+                let mut item = item(part, index, local.map(name).into_iter().collect());
+                if let (Some(dependency), Some(imported), false) =
+                    (dependency, imported, import.is_type_only)
+                {
+                    item.import = Some((dependency, imported));
+                }
+                self.items.push(item);
+                index += 1;
+            }
+        }
+    }
+
+    fn export(&mut self, part: usize, export: &'a ModExport<T>) {
+        match export {
+            ModExport::Default(DefaultExportDecl::Decl(decl)) => {
+                let first = self.items.len();
+                self.decl(part, decl, false);
+                for item in &mut self.items[first..] {
+                    if item.names.is_empty() {
+                        item.names.push(DEFAULT_BINDING.to_string());
+                    }
+                    item.exports.push("default".to_string());
+                }
+            }
+            ModExport::Default(DefaultExportDecl::Expr(expr)) => {
+                let mut item = item(part, 0, vec![DEFAULT_BINDING.to_string()]);
+                item.exports.push("default".to_string());
+                item.impure = !self.purities.is_pure(expr);
+                self.uses(&mut item, |uses| uses.visit_expr(expr));
+                self.items.push(item);
+            }
+            ModExport::Named(NamedExportDecl::Decl(decl)) => self.decl(part, decl, true),
+            ModExport::Named(NamedExportDecl::Specifier(specifiers, source, _)) => {
+                let dependency = match source {
+                    Some(source) => match self.source(part, source) {
+                        Some(dependency) => Some(dependency),
+                        None => return,
+                    },
+                    None => None,
+                };
+                for (index, specifier) in specifiers.iter().enumerate() {
+                    if specifier.is_type_only {
+                        continue;
+                    }
+                    let local = match specifier.local.value() {
+                        Ok(local) => local,
+                        Err(_) => continue,
+                    };
+                    let exported = match specifier.alias.as_ref().map(|alias| alias.value()) {
+                        Some(Ok(alias)) => alias,
+                        Some(Err(_)) => continue,
+                        None => local.clone(),
+                    };
+                    self.forwards.push(match dependency {
+                        Some(dependency) => Forward::Indirect {
+                            part,
+                            index,
+                            dependency,
+                            imported: Some(local),
+                            exported,
+                        },
+                        None => Forward::Local {
+                            part,
+                            index,
+                            local,
+                            exported,
+                        },
+                    });
+                }
+            }
+            ModExport::All { alias, name, .. } => {
+                let dependency = match self.source(part, name) {
+                    Some(dependency) => dependency,
+                    None => return,
+                };
+                match alias.as_ref().map(|alias| alias.value()) {
+                    Some(Ok(exported)) => self.forwards.push(Forward::Indirect {
+                        part,
+                        index: 0,
+                        dependency,
+                        imported: None,
+                        exported,
+                    }),
+                    Some(Err(_)) => {}
+                    None => self.forwards.push(Forward::Star { part, dependency }),
+                }
+            }
+            ModExport::Assign(expr) => {
+                let mut item = item(part, 0, Vec::new());
+                item.impure = true;
+                self.uses(&mut item, |uses| uses.visit_expr(expr));
+                self.items.push(item);
+            }
+        }
+    }
+
+    /// The items of a declaration, which `exported` declares with
+    /// `export` in front of it
+    fn decl(&mut self, part: usize, decl: &'a Decl<T>, exported: bool) {
+        let first = self.items.len();
+        match decl {
+            Decl::Var(kind, decls) => {
+                for (index, declarator) in decls.iter().enumerate() {
+                    let names = declarator.id.bound_names().into_iter().map(name);
+                    let mut item = item(part, index, names.collect());
+                    item.impure = kind.is_using()
+                        || !matches!(declarator.id, Pat::Ident(_))
+                        || declarator
+                            .init
+                            .as_ref()
+                            .is_some_and(|init| !self.purities.is_pure(init));
+                    self.uses(&mut item, |uses| {
+                        uses.visit_pat(&declarator.id);
+                        if let Some(init) = &declarator.init {
+                            uses.visit_expr(init);
+                        }
+                    });
+                    self.items.push(item);
+                }
+            }
+            Decl::Func(func) => {
+                let mut item = item(part, 0, declared_names(decl));
+                self.uses(&mut item, |uses| uses.visit_func(func));
+                self.items.push(item);
+            }
+            Decl::Class(class) => {
+                let mut item = item(part, 0, declared_names(decl));
+                item.impure = self.purities.class(class) != Purity::Pure;
+                self.uses(&mut item, |uses| uses.visit_class(class));
+                self.items.push(item);
+            }
+            // erased
+            Decl::Declare(_) => self.items.push(item(part, 0, declared_names(decl))),
+            _ => {
+                let mut item = item(part, 0, declared_names(decl));
+                item.impure = true;
+                self.uses(&mut item, |uses| uses.visit_decl(decl));
+                self.items.push(item);
+            }
+        }
+        if exported {
+            for item in &mut self.items[first..] {
+                item.exports = item.names.clone();
+            }
+        }
+    }
+
+    /// Set the references and dynamic imports `visit` finds as those
+    /// of `item`
+    fn uses(&self, item: &mut Item, visit: impl FnOnce(&mut Uses<'_, 'a, T>)) {
+        let mut uses = Uses {
+            module: self.module,
+            tree: self.tree,
+            references: Vec::new(),
+            dynamic_imports: Vec::new(),
+        };
+        visit(&mut uses);
+        item.references = uses.references;
+        item.dynamic_imports = uses.dynamic_imports;
+    }
+
+    /// The dependency `source` is the specifier of, as the source of
+    /// `part`
+    fn source(&mut self, part: usize, source: &Lit<T>) -> Option<usize> {
+        let dependency = dependency(self.module, source)?;
+        self.sources.insert(part, dependency);
+        Some(dependency)
+    }
+}
+
+fn item(part: usize, index: usize, names: Vec<String>) -> Item {
+    Item {
+        part,
+        index,
+        names,
+        exports: Vec::new(),
+        references: Vec::new(),
+        dynamic_imports: Vec::new(),
+        import: None,
+        impure: false,
+    }
+}
+
+fn name<T: AsRef<str>>(ident: &Ident<T>) -> String {
+    ident.name.as_ref().to_string()
+}
+
+fn dependency<T>(module: &Module<'_, T>, source: &Lit<T>) -> Option<usize> {
+    module
+        .dependencies
+        .iter()
+        .position(|dependency| std::ptr::eq(dependency.source, source))
+}
+
+/// Finds the top level bindings and the dependencies some code uses
+struct Uses<'s, 'a, T> {
+    module: &'s Module<'a, T>,
+    tree: &'s ScopeTree<'a, T>,
+    references: Vec<String>,
+    dynamic_imports: Vec<usize>,
+}
+
+impl<'a, T> Visit<'a, T> for Uses<'_, 'a, T>
+where
+    T: AsRef<str>,
+{
+    fn visit_expr(&mut self, expr: &'a Expr<T>) {
+        if let Expr::Import(import) = expr {
+            if let Expr::Lit(source) = &*import.source {
+                self.dynamic_imports.extend(dependency(self.module, source));
+            }
+        }
+        walk_expr(self, expr)
+    }
+
+    fn visit_prop(&mut self, prop: &'a Prop<T>) {
+        match (&prop.value, &prop.key) {
+            (PropValue::None, PropKey::Expr(key)) if prop.short_hand => self.visit_expr(key),
+            (PropValue::None, PropKey::Pat(Pat::Ident(key))) if prop.short_hand => {
+                self.visit_ident(key)
+            }
+            _ => walk_prop(self, prop),
+        }
+    }
+
+    fn visit_jsx_element(&mut self, element: &'a JsxElement<T>) {
+        if let JsxElementName::Ident(ident) = &element.name {
+            if !element.name.is_intrinsic() {
+                self.visit_ident(ident);
+            }
+        }
+        walk_jsx_element(self, element);
+    }
+
+    fn visit_ident(&mut self, ident: &'a Ident<T>) {
+        let reference = match self.tree.reference_of(ident) {
+            Some(reference) => self.tree.reference(reference),
+            None => return,
+        };
+        // the value a declaration starts with is part of the declaration
+        if reference.kind == RefKind::Init {
+            return;
+        }
+        if let Some(binding) = reference.binding {
+            let binding = self.tree.binding(binding);
+            if binding.scope == self.tree.root() {
+                self.references.push(binding.name.as_ref().to_string());
+            }
+        }
+    }
+}
+
+enum Work {
+    /// The module is loaded
+    Evaluate(ModuleId),
+    /// The item is kept
+    Item(ModuleId, usize),
+    /// The top level binding is used
+    Binding(ModuleId, String),
+    /// The export is used
+    Export(ModuleId, String),
+    /// The namespace object is used, and with it every export
+    Namespace(ModuleId),
+}
+
+/// What is used in each module, found from the entry
+struct Shaker<'g, 'a, T> {
+    graph: &'g ModuleGraph<'a, T>,
+    shapes: Vec<Shape<'a, T>>,
+    effects: Vec<bool>,
+    evaluated: Vec<bool>,
+    namespaces: Vec<bool>,
+    /// The items that are kept
+    live: Vec<Vec<bool>>,
+    /// The exports that are used
+    used: Vec<HashSet<String>>,
+    /// The forwards that are kept, by part and specifier
+    forwarded: Vec<HashSet<(usize, usize)>>,
+    work: Vec<Work>,
+}
+
+impl<T> Shaker<'_, '_, T>
+where
+    T: Clone + AsRef<str>,
+{
+    fn run(&mut self) {
+        while let Some(work) = self.work.pop() {
+            match work {
+                Work::Evaluate(module) => self.evaluate(module),
+                Work::Item(module, item) => self.item(module, item),
+                Work::Binding(module, name) => {
+                    if let Some(items) = self.shapes[module.0].declared.get(&name) {
+                        for item in items {
+                            self.work.push(Work::Item(module, *item));
+                        }
+                    }
+                }
+                Work::Export(module, name) => self.export(module, name),
+                Work::Namespace(module) => self.namespace(module),
+            }
+        }
+    }
+
+    fn evaluate(&mut self, module: ModuleId) {
+        if self.evaluated[module.0] {
+            return;
+        }
+        self.evaluated[module.0] = true;
+        let shape = &self.shapes[module.0];
+        for (index, item) in shape.items.iter().enumerate() {
+            if shape.dynamic || item.impure {
+                self.work.push(Work::Item(module, index));
+            }
+        }
+        let dependencies = &self.graph.module(module).dependencies;
+        for (index, dependency) in dependencies.iter().enumerate() {
+            if !dependency.is_evaluated() {
+                continue;
+            }
+            if let Some(target) = self.graph.target(module, index) {
+                if self.effects[target.0] {
+                    self.work.push(Work::Evaluate(target));
+                }
+            }
+        }
+    }
+
+    fn item(&mut self, module: ModuleId, index: usize) {
+        if self.live[module.0][index] {
+            return;
+        }
+        self.live[module.0][index] = true;
+        self.work.push(Work::Evaluate(module));
+        let item = &self.shapes[module.0].items[index];
+        for name in &item.references {
+            self.work.push(Work::Binding(module, name.clone()));
+        }
+        for dependency in &item.dynamic_imports {
+            if let Some(target) = self.graph.target(module, *dependency) {
+                self.work.push(Work::Namespace(target));
+            }
+        }
+        if let Some((dependency, imported)) = &item.import {
+            if let Some(target) = self.graph.target(module, *dependency) {
+                self.work.push(match imported {
+                    Some(name) => Work::Export(target, name.clone()),
+                    None => Work::Namespace(target),
+                });
+            }
+        }
+    }
+
+    fn export(&mut self, module: ModuleId, name: String) {
+        if self.used[module.0].contains(&name) {
+            return;
+        }
+        self.work.push(Work::Evaluate(module));
+        let shape = &self.shapes[module.0];
+        let mut found = false;
+        for (index, item) in shape.items.iter().enumerate() {
+            if item.exports.contains(&name) {
+                self.work.push(Work::Item(module, index));
+                found = true;
+            }
+        }
+        for forward in &shape.forwards {
+            match forward {
+                Forward::Local {
+                    part,
+                    index,
+                    local,
+                    exported,
+                } if *exported == name => {
+                    self.forwarded[module.0].insert((*part, *index));
+                    self.work.push(Work::Binding(module, local.clone()));
+                    found = true;
+                }
+                Forward::Indirect {
+                    part,
+                    index,
+                    dependency,
+                    imported,
+                    exported,
+                } if *exported == name => {
+                    self.forwarded[module.0].insert((*part, *index));
+                    if let Some(target) = self.graph.target(module, *dependency) {
+                        self.work.push(match imported {
+                            Some(imported) => Work::Export(target, imported.clone()),
+                            None => Work::Namespace(target),
+                        });
+                    }
+                    found = true;
+                }
+                _ => {}
+            }
+        }
+        // `export *` never forwards a default export
+        if !found && name != "default" {
+            for forward in &shape.forwards {
+                let (part, dependency) = match forward {
+                    Forward::Star { part, dependency } => (*part, *dependency),
+                    _ => continue,
+                };
+                match self.graph.target(module, dependency) {
+                    Some(target) => {
+                        if matches!(
+                            self.graph.resolve_export(target, &name),
+                            Resolution::Binding { .. }
+                                | Resolution::Namespace(_)
+                                | Resolution::External { .. }
+                        ) {
+                            self.forwarded[module.0].insert((part, 0));
+                            self.work.push(Work::Export(target, name.clone()));
+                        }
+                    }
+                    None => {
+                        self.forwarded[module.0].insert((part, 0));
+                    }
+                }
+            }
+        }
+        self.used[module.0].insert(name);
+    }
+
+    fn namespace(&mut self, module: ModuleId) {
+        if self.namespaces[module.0] {
+            return;
+        }
+        self.namespaces[module.0] = true;
+        self.work.push(Work::Evaluate(module));
+        for name in self.graph.export_names(module) {
+            self.work.push(Work::Export(module, name));
+        }
+        // the names of a specifier that is not one of the modules are
+        // not known
+        for forward in &self.shapes[module.0].forwards {
+            if let Forward::Star { part, dependency } = forward {
+                if self.graph.target(module, *dependency).is_none() {
+                    self.forwarded[module.0].insert((*part, 0));
+                }
+            }
+        }
+    }
+
+    /// The program of `module` with what is not used removed
+    fn rebuild(&self, module: ModuleId, entry: ModuleId) -> Option<Program<T>> {
+        if !self.evaluated[module.0] {
+            return None;
+        }
+        let program = self.graph.module(module).program;
+        let mut parts = Vec::new();
+        for (index, part) in program.parts().iter().enumerate() {
+            match part {
+                ProgramPart::Dir(_) => parts.push(part.clone()),
+                ProgramPart::Decl(Decl::Import(import)) => {
+                    parts.extend(self.import(module, index, import))
+                }
+                ProgramPart::Decl(Decl::ImportEquals(_)) => {
+                    if self.is_live(module, index, 0) || self.loads(module, index) {
+                        parts.push(part.clone());
+                    }
+                }
+                ProgramPart::Decl(Decl::Export(export)) => {
+                    self.export_part(module, entry, index, export, &mut parts)
+                }
+                ProgramPart::Decl(decl) => {
+                    parts.extend(self.decl(module, index, decl).map(ProgramPart::Decl))
+                }
+                ProgramPart::Stmt(_) => {
+                    if self.is_live(module, index, 0) {
+                        parts.push(part.clone());
+                    }
+                }
+            }
+        }
+        Some(match program {
+            Program::Mod(_, hashbang) => Program::Mod(parts, hashbang.clone()),
+            Program::Script(_, hashbang) => Program::Script(parts, hashbang.clone()),
+        })
+    }
+
+    fn import(
+        &self,
+        module: ModuleId,
+        part: usize,
+        import: &ModImport<T>,
+    ) -> Option<ProgramPart<T>> {
+        if import.is_type_only {
+            return Some(ProgramPart::Decl(Decl::Import(Box::new(import.clone()))));
+        }
+        let mut index = 0;
+        let mut specifiers = Vec::new();
+        let mut used = false;
+        for specifier in &import.specifiers {
+            match specifier {
+                ImportSpecifier::Normal(normal) => {
+                    let mut kept = Vec::new();
+                    for specifier in normal {
+                        if specifier.is_type_only {
+                            kept.push(specifier.clone());
+                        } else if self.is_live(module, part, index) {
+                            kept.push(specifier.clone());
+                            used = true;
+                        }
+                        index += 1;
+                    }
+                    if !kept.is_empty() {
+                        specifiers.push(ImportSpecifier::Normal(kept));
+                    }
+                }
+                ImportSpecifier::Default(_) | ImportSpecifier::Namespace(_) => {
+                    if self.is_live(module, part, index) {
+                        specifiers.push(specifier.clone());
+                        used = true;
+                    }
+                    index += 1;
+                }
+            }
+        }
+        if used {
+            Some(ProgramPart::Decl(Decl::Import(Box::new(ModImport {
+                specifiers,
+                ..import.clone()
+            }))))
+        } else if self.loads(module, part) {
+            Some(side_effect_import(&import.source, &import.attributes))
+        } else {
+            None
+        }
+    }
+
+    fn export_part(
+        &self,
+        module: ModuleId,
+        entry: ModuleId,
+        part: usize,
+        export: &ModExport<T>,
+        parts: &mut Vec<ProgramPart<T>>,
+    ) {
+        let used = &self.used[module.0];
+        match export {
+            ModExport::Default(_) if used.contains("default") => {
+                parts.push(ProgramPart::Decl(Decl::Export(Box::new(export.clone()))))
+            }
+            ModExport::Default(DefaultExportDecl::Decl(decl)) => {
+                match self.decl(module, part, decl) {
+                    // a class or function declaration needs a name
+                    Some(Decl::Class(class)) if class.id.is_none() => {
+                        parts.push(expr_stmt(Expr::Class(class)))
+                    }
+                    Some(Decl::Func(func)) if func.id.is_none() => {
+                        parts.push(expr_stmt(Expr::Func(func)))
+                    }
+                    Some(kept) => parts.push(ProgramPart::Decl(kept)),
+                    None => {}
+                }
+            }
+            ModExport::Default(DefaultExportDecl::Expr(expr)) => {
+                if self.is_live(module, part, 0) {
+                    let purities = &self.shapes[module.0].purities;
+                    for effect in purities.side_effects(expr) {
+                        parts.push(expr_stmt(effect.clone()));
+                    }
+                }
+            }
+            ModExport::Named(NamedExportDecl::Decl(decl)) => {
+                if let Some(kept) = self.decl(module, part, decl) {
+                    if declared_names(&kept).iter().any(|name| used.contains(name)) {
+                        parts.push(ProgramPart::Decl(Decl::Export(Box::new(ModExport::Named(
+                            NamedExportDecl::Decl(kept),
+                        )))));
+                    } else {
+                        parts.push(ProgramPart::Decl(kept));
+                    }
+                }
+            }
+            ModExport::Named(NamedExportDecl::Specifier(specifiers, source, attributes)) => {
+                let forwarded = &self.forwarded[module.0];
+                let kept: Vec<_> = specifiers
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, specifier)| {
+                        forwarded.contains(&(part, *index))
+                            || specifier.is_type_only && module == entry
+                    })
+                    .map(|(_, specifier)| specifier.clone())
+                    .collect();
+                if !kept.is_empty() {
+                    parts.push(ProgramPart::Decl(Decl::Export(Box::new(ModExport::Named(
+                        NamedExportDecl::Specifier(kept, source.clone(), attributes.clone()),
+                    )))));
+                } else if let Some(source) = source {
+                    if self.loads(module, part) {
+                        parts.push(side_effect_import(source, attributes));
+                    }
+                }
+            }
+            ModExport::All {
+                name, attributes, ..
+            } => {
+                if self.forwarded[module.0].contains(&(part, 0)) {
+                    parts.push(ProgramPart::Decl(Decl::Export(Box::new(export.clone()))));
+                } else if self.loads(module, part) {
+                    parts.push(side_effect_import(name, attributes));
+                }
+            }
+            ModExport::Assign(_) => {
+                parts.push(ProgramPart::Decl(Decl::Export(Box::new(export.clone()))))
+            }
+        }
+    }
+
+    /// What is kept of a declaration
+    fn decl(&self, module: ModuleId, part: usize, decl: &Decl<T>) -> Option<Decl<T>> {
+        match decl {
+            Decl::Var(kind, decls) => {
+                let kept: Vec<_> = decls
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| self.is_live(module, part, *index))
+                    .map(|(_, decl)| decl.clone())
+                    .collect();
+                if kept.is_empty() {
+                    None
+                } else {
+                    Some(Decl::Var(*kind, kept))
+                }
+            }
+            _ if self.is_live(module, part, 0) => Some(decl.clone()),
+            _ => None,
+        }
+    }
+
+    fn is_live(&self, module: ModuleId, part: usize, index: usize) -> bool {
+        self.shapes[module.0]
+            .by_part
+            .get(&(part, index))
+            .is_some_and(|item| self.live[module.0][*item])
+    }
+
+    /// If the module `part` imports from has to be loaded for its side
+    /// effects
+    fn loads(&self, module: ModuleId, part: usize) -> bool {
+        match self.shapes[module.0].sources.get(&part) {
+            Some(dependency) => self
+                .graph
+                .target(module, *dependency)
+                .is_none_or(|target| self.effects[target.0]),
+            None => false,
+        }
+    }
+}
+
+/// `import 'source'`
+fn side_effect_import<T: Clone>(
+    source: &Lit<T>,
+    attributes: &[ImportAttribute<T>],
+) -> ProgramPart<T> {
+    ProgramPart::Decl(Decl::Import(Box::new(ModImport {
+        specifiers: Vec::new(),
+        source: source.clone(),
+        is_type_only: false,
+        attributes: attributes.to_vec(),
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decl::{ExportSpecifier, ModuleExportName, NormalImportSpec};
+    use crate::expr::StringLit;
+    use crate::transform::build::{call, ident, ident_expr, string, var};
+    use crate::{Func, FuncBody, VarKind};
+
+    fn source(specifier: &str) -> Lit<String> {
+        Lit::String(StringLit::Single(specifier.to_string()))
+    }
+
+    fn import(names: &[&str], specifier: &str) -> ProgramPart<String> {
+        if names.is_empty() {
+            return side_effect_import(&source(specifier), &[]);
+        }
+        let specifiers = names
+            .iter()
+            .map(|name| NormalImportSpec {
+                alias: None,
+                imported: ModuleExportName::Ident(ident(name)),
+                is_type_only: false,
+            })
+            .collect();
+        ProgramPart::Decl(Decl::Import(Box::new(ModImport {
+            specifiers: vec![ImportSpecifier::Normal(specifiers)],
+            source: source(specifier),
+            is_type_only: false,
+            attributes: Vec::new(),
+        })))
+    }
+
+    fn re_export(names: &[&str], specifier: &str) -> ProgramPart<String> {
+        let specifiers = names
+            .iter()
+            .map(|name| ExportSpecifier {
+                local: ModuleExportName::Ident(ident(name)),
+                alias: None,
+                is_type_only: false,
+            })
+            .collect();
+        ProgramPart::Decl(Decl::Export(Box::new(ModExport::Named(
+            NamedExportDecl::Specifier(specifiers, Some(source(specifier)), Vec::new()),
+        ))))
+    }
+
+    fn export(decl: ProgramPart<String>) -> ProgramPart<String> {
+        match decl {
+            ProgramPart::Decl(decl) => ProgramPart::Decl(Decl::Export(Box::new(ModExport::Named(
+                NamedExportDecl::Decl(decl),
+            )))),
+            part => part,
+        }
+    }
+
+    fn export_default(decl: Decl<String>) -> ProgramPart<String> {
+        ProgramPart::Decl(Decl::Export(Box::new(ModExport::Default(
+            DefaultExportDecl::Decl(decl),
+        ))))
+    }
+
+    fn func(name: Option<&str>, body: Vec<ProgramPart<String>>) -> Func<String> {
+        Func {
+            id: name.map(ident),
+            type_params: Vec::new(),
+            params: Vec::new(),
+            return_type: None,
+            body: Some(FuncBody(body)),
+            generator: false,
+            is_async: false,
+        }
+    }
+
+    fn func_decl(name: &str, body: Vec<ProgramPart<String>>) -> ProgramPart<String> {
+        ProgramPart::Decl(Decl::Func(func(Some(name), body)))
+    }
+
+    fn run(name: &str) -> ProgramPart<String> {
+        expr_stmt(call(ident_expr(name), Vec::new()))
+    }
+
+    fn shaken(modules: &[(&str, &Program<String>)]) -> Vec<Option<Program<String>>> {
+        let graph = ModuleGraph::new(modules.iter().copied());
+        shake(&graph, ModuleId(0), &ShakeOptions::default())
+    }
+
+    #[test]
+    fn unused_exports_are_removed() {
+        let main = Program::module(vec![
+            import(&["format"], "format"),
+            export(var(
+                VarKind::Const,
+                vec![("label", Some(call(ident_expr("format"), Vec::new())))],
+            )),
+        ]);
+        let format = Program::module(vec![
+            var(VarKind::Const, vec![("units", Some(string("b")))]),
+            var(VarKind::Const, vec![("unused", Some(string("kb")))]),
+            export(func_decl("format", vec![run("units")])),
+            export(func_decl("describe", vec![run("format")])),
+            run("load"),
+        ]);
+        let result = shaken(&[("main", &main), ("format", &format)]);
+        assert_eq!(result[0].as_ref(), Some(&main));
+        assert_eq!(
+            result[1],
+            Some(Program::module(vec![
+                var(VarKind::Const, vec![("units", Some(string("b")))]),
+                export(func_decl("format", vec![run("units")])),
+                run("load"),
+            ]))
+        );
+    }
+
+    #[test]
+    fn locally_used_exports_lose_their_export() {
+        let main = Program::module(vec![import(&["run"], "lib"), run("run")]);
+        let lib = Program::module(vec![
+            export(func_decl("helper", Vec::new())),
+            export(func_decl("run", vec![run("helper")])),
+        ]);
+        let result = shaken(&[("main", &main), ("lib", &lib)]);
+        assert_eq!(
+            result[1],
+            Some(Program::module(vec![
+                func_decl("helper", Vec::new()),
+                export(func_decl("run", vec![run("helper")])),
+            ]))
+        );
+    }
+
+    #[test]
+    fn unused_re_exports_keep_side_effects() {
+        let main = Program::module(vec![import(&["format"], "index"), run("format")]);
+        let index = Program::module(vec![
+            re_export(&["format"], "format"),
+            re_export(&["parse"], "parse"),
+            re_export(&["pure"], "pure"),
+        ]);
+        let format = Program::module(vec![export(func_decl("format", Vec::new()))]);
+        let parse = Program::module(vec![
+            run("register"),
+            export(func_decl("parse", Vec::new())),
+        ]);
+        let pure = Program::module(vec![export(func_decl("pure", Vec::new()))]);
+        let result = shaken(&[
+            ("main", &main),
+            ("index", &index),
+            ("format", &format),
+            ("parse", &parse),
+            ("pure", &pure),
+        ]);
+        assert_eq!(
+            result[1],
+            Some(Program::module(vec![
+                re_export(&["format"], "format"),
+                side_effect_import(&source("parse"), &[]),
+            ]))
+        );
+        assert_eq!(result[3], Some(Program::module(vec![run("register")])));
+        assert_eq!(result[4], None);
+    }
+
+    #[test]
+    fn side_effect_free_modules_are_not_loaded() {
+        let main = Program::module(vec![import(&[], "lib")]);
+        let lib = Program::module(vec![run("register")]);
+        let graph = ModuleGraph::new([("main", &main), ("lib", &lib)]);
+        let options = ShakeOptions {
+            side_effect_free: vec![ModuleId(1)],
+            ..ShakeOptions::default()
+        };
+        let result = shake(&graph, ModuleId(0), &options);
+        assert_eq!(result[0], Some(Program::module(Vec::new())));
+        assert_eq!(result[1], None);
+    }
+
+    #[test]
+    fn dynamic_modules_keep_everything() {
+        let main = Program::module(vec![import(&[], "lib")]);
+        let lib = Program::module(vec![
+            var(VarKind::Const, vec![("unused", Some(string("a")))]),
+            expr_stmt(call(ident_expr("eval"), vec![ident_expr("code")])),
+            export_default(Decl::Func(func(None, Vec::new()))),
+        ]);
+        let result = shaken(&[("main", &main), ("lib", &lib)]);
+        assert_eq!(result[0].as_ref(), Some(&main));
+        assert_eq!(
+            result[1],
+            Some(Program::module(vec![
+                var(VarKind::Const, vec![("unused", Some(string("a")))]),
+                expr_stmt(call(ident_expr("eval"), vec![ident_expr("code")])),
+                // a function declaration without a name is not valid
+                expr_stmt(Expr::Func(func(None, Vec::new()))),
+            ]))
+        );
+    }
+}
@@ -0,0 +1,889 @@
+//! Lower `using` and `await using` declarations to `try`/`finally`
+//!
+//! Everything after a `using` declaration in its statement list moves
+//! into a `try` block and the resource is disposed in the `finally`
+//! ```js
+//! {
+//!     using file = open(path);
+//!     read(file);
+//! }
+//! ```
+//! becomes
+//! ```js
+//! {
+//!     let _error, _hasError = false;
+//!     const file = open(path), _dispose = file != null ? file[Symbol.dispose] : null;
+//!     try {
+//!         read(file);
+//!     } catch (e) {
+//!         _error = e;
+//!         _hasError = true;
+//!     } finally {
+//!         try {
+//!             if (_dispose !== null) _dispose.call(file);
+//!         } catch (e) {
+//!             _error = _hasError ? _suppressed(e, _error) : e;
+//!             _hasError = true;
+//!         }
+//!         if (_hasError) throw _error;
+//!     }
+//! }
+//! ```
+//! `await using` looks for `Symbol.asyncDispose` before `Symbol.dispose`
+//! and awaits the call, or `undefined` for a `null` or `undefined`
+//! resource. Resources are disposed in the reverse order of
+//! their declarations. When the block and the disposals throw, the
+//! errors are chained in `SuppressedError`s the way the proposal
+//! chains them, `_suppressed` is declared at the top of the program
+//! and builds an equivalent `Error` where `SuppressedError` is missing.
+//!
+//! Declarations that follow a `using` in the same list would become
+//! local to the `try` block, so they are hoisted above it: functions,
+//! imports and export lists move up unchanged while `let`, `const` and
+//! `class` declarations become a `let` above the `try` and an assignment
+//! inside it. An `export default` expression is held in a `_default`
+//! binding.
+//!
+//! Some parts of the proposal are not covered
+//! - a resource without a dispose method is reported when it would
+//!   be disposed rather than when it is declared
+use std::fmt;
+
+use crate::decl::{Decl, DefaultExportDecl, ExportSpecifier, ModExport, NamedExportDecl, VarDecl};
+use crate::expr::{
+    AssignExpr, AssignLeft, BinaryExpr, ConditionalExpr, Expr, Lit, NewExpr, UnaryExpr,
+};
+use crate::pat::Pat;
+use crate::stmt::{BlockStmt, CatchClause, ForStmt, IfStmt, LoopInit, LoopLeft, Stmt, TryStmt};
+use crate::transform::build::{
+    assign, call, expr_stmt, ident, ident_expr, index, insert_after_directives, member, nullish,
+    object, string, undefined, var, Names,
+};
+use crate::transform::visit::{walk_parts, walk_stmt, VisitMut};
+use crate::{
+    AssignOp, BinaryOp, Func, FuncArg, FuncBody, Ident, Program, ProgramPart, UnaryOp, VarKind,
+};
+
+/// Replace every `using` and `await using` declaration in `program`
+/// with a `try`/`finally` that disposes the resource
+pub fn lower<T>(mut program: Program<T>) -> Result<Program<T>, UsingError>
+where
+    T: Clone + AsRef<str> + From<String>,
+{
+    if let Program::Script(parts, _) = &program {
+        if parts.iter().any(is_using_decl) {
+            return Err(UsingError::ScriptTopLevel);
+        }
+    }
+    let mut lowering = Lowering {
+        names: Names::new(&program),
+        suppressed: None,
+        error: None,
+    };
+    lowering.visit_program(&mut program);
+    if let Some(error) = lowering.error {
+        return Err(error);
+    }
+    if let Some(suppressed) = lowering.suppressed {
+        let (Program::Mod(parts, _) | Program::Script(parts, _)) = &mut program;
+        insert_after_directives(parts, suppressed_helper(&suppressed));
+    }
+    Ok(program)
+}
+
+/// A `using` declaration that is not allowed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UsingError {
+    /// A binding pattern instead of an identifier
+    Pattern,
+    /// A declaration without an initializer
+    MissingInit,
+    /// `for (using x in obj)`
+    ForIn,
+    /// A declaration at the top level of a script
+    ScriptTopLevel,
+}
+
+impl fmt::Display for UsingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UsingError::Pattern => f.write_str("using declarations must bind an identifier"),
+            UsingError::MissingInit => f.write_str("using declarations must be initialized"),
+            UsingError::ForIn => f.write_str("using declarations are not allowed in for-in"),
+            UsingError::ScriptTopLevel => {
+                f.write_str("using declarations are not allowed at the top level of a script")
+            }
+        }
+    }
+}
+
+impl std::error::Error for UsingError {}
+
+struct Lowering {
+    names: Names,
+    /// The name of the helper that creates a `SuppressedError`, once
+    /// one is needed
+    suppressed: Option<String>,
+    error: Option<UsingError>,
+}
+
+impl<T> VisitMut<T> for Lowering
+where
+    T: Clone + AsRef<str> + From<String>,
+{
+    fn visit_parts(&mut self, parts: &mut Vec<ProgramPart<T>>) {
+        walk_parts(self, parts);
+        if self.error.is_some() || !parts.iter().any(is_using_decl) {
+            return;
+        }
+        match self.lower_parts(std::mem::take(parts)) {
+            Ok(lowered) => *parts = lowered,
+            Err(error) => self.error = Some(error),
+        }
+    }
+
+    fn visit_stmt(&mut self, stmt: &mut Stmt<T>) {
+        walk_stmt(self, stmt);
+        if self.error.is_some() {
+            return;
+        }
+        if let Err(error) = self.lower_loop(stmt) {
+            self.error = Some(error);
+        }
+    }
+}
+
+impl Lowering {
+    /// Lower the first `using` in `parts` and everything after it
+    fn lower_parts<T>(
+        &mut self,
+        mut parts: Vec<ProgramPart<T>>,
+    ) -> Result<Vec<ProgramPart<T>>, UsingError>
+    where
+        T: Clone + AsRef<str> + From<String>,
+    {
+        let first = match parts.iter().position(is_using_decl) {
+            Some(first) => first,
+            None => return Ok(parts),
+        };
+        let rest = parts.split_off(first);
+        let mut kept = Vec::with_capacity(rest.len());
+        for part in rest {
+            self.hoist(part, &mut parts, &mut kept);
+        }
+        parts.extend(self.nest(kept)?);
+        Ok(parts)
+    }
+
+    /// Wrap everything after each `using` in a `try`
+    fn nest<T>(&mut self, parts: Vec<ProgramPart<T>>) -> Result<Vec<ProgramPart<T>>, UsingError>
+    where
+        T: Clone + AsRef<str> + From<String>,
+    {
+        let mut out = Vec::with_capacity(parts.len());
+        let mut parts = parts.into_iter();
+        while let Some(part) = parts.next() {
+            match part {
+                ProgramPart::Decl(Decl::Var(kind, decls)) if kind.is_using() => {
+                    let body = self.nest(parts.collect())?;
+                    out.extend(self.dispose_scope(kind, decls, body)?);
+                    break;
+                }
+                part => out.push(part),
+            }
+        }
+        Ok(out)
+    }
+
+    /// Declare each resource of one `using` declaration and
+    /// dispose of them once `body` is done
+    fn dispose_scope<T>(
+        &mut self,
+        kind: VarKind,
+        decls: Vec<VarDecl<T>>,
+        body: Vec<ProgramPart<T>>,
+    ) -> Result<Vec<ProgramPart<T>>, UsingError>
+    where
+        T: Clone + AsRef<str> + From<String>,
+    {
+        let mut resources = Vec::with_capacity(decls.len());
+        for decl in decls {
+            let init = decl.init.ok_or(UsingError::MissingInit)?;
+            resources.push((binding_name(&decl.id)?, Some(init)));
+        }
+        Ok(self.dispose(kind, resources, body))
+    }
+
+    /// Wrap `body` in a `try` for each resource, the resources without
+    /// an initializer are already bound
+    fn dispose<T>(
+        &mut self,
+        kind: VarKind,
+        resources: Vec<(String, Option<Expr<T>>)>,
+        body: Vec<ProgramPart<T>>,
+    ) -> Vec<ProgramPart<T>>
+    where
+        T: From<String>,
+    {
+        let is_await = kind == VarKind::AwaitUsing;
+        let resources: Vec<_> = resources
+            .into_iter()
+            .map(|(name, init)| (name, init, self.names.fresh("_dispose")))
+            .collect();
+        let error = self.names.fresh("_error");
+        let has_error = self.names.fresh("_hasError");
+        let suppressed = match &self.suppressed {
+            Some(suppressed) => suppressed.clone(),
+            None => self
+                .suppressed
+                .insert(self.names.fresh("_suppressed"))
+                .clone(),
+        };
+        let mut body = body;
+        for (name, init, dispose) in resources.into_iter().rev() {
+            let method = dispose_method(&name, is_await);
+            let mut disposal = call(
+                member(ident_expr(&dispose), "call"),
+                vec![ident_expr(&name)],
+            );
+            // `await using` awaits even for a `null` resource
+            let mut skipped = None;
+            if is_await {
+                disposal = Expr::Await(Box::new(disposal));
+                skipped = Some(Box::new(Stmt::Expr(Expr::Await(Box::new(undefined())))));
+            }
+            let disposal = Stmt::If(IfStmt {
+                test: binary(
+                    BinaryOp::StrictNotEqual,
+                    ident_expr(&dispose),
+                    Expr::Lit(Lit::Null),
+                ),
+                consequent: Box::new(Stmt::Expr(disposal)),
+                alternate: skipped,
+            });
+            // a disposal that throws after the block did suppresses
+            // the block's error
+            let combined = Expr::Conditional(ConditionalExpr {
+                test: Box::new(ident_expr(&has_error)),
+                consequent: Box::new(call(
+                    ident_expr(&suppressed),
+                    vec![ident_expr("e"), ident_expr(&error)],
+                )),
+                alternate: Box::new(ident_expr("e")),
+            });
+            let finalizer = vec![
+                ProgramPart::Stmt(Stmt::Try(TryStmt {
+                    block: BlockStmt(vec![ProgramPart::Stmt(disposal)]),
+                    handler: Some(catch_error(&error, &has_error, combined)),
+                    finalizer: None,
+                })),
+                ProgramPart::Stmt(Stmt::If(IfStmt {
+                    test: ident_expr(&has_error),
+                    consequent: Box::new(Stmt::Throw(ident_expr(&error))),
+                    alternate: None,
+                })),
+            ];
+            let mut decls = Vec::with_capacity(2);
+            if let Some(init) = init {
+                decls.push((name.as_str(), Some(init)));
+            }
+            decls.push((dispose.as_str(), Some(method)));
+            body = vec![
+                var(VarKind::Const, decls),
+                ProgramPart::Stmt(Stmt::Try(TryStmt {
+                    block: BlockStmt(body),
+                    handler: Some(catch_error(&error, &has_error, ident_expr("e"))),
+                    finalizer: Some(BlockStmt(finalizer)),
+                })),
+            ];
+        }
+        body.insert(
+            0,
+            var(
+                VarKind::Let,
+                vec![
+                    (error.as_str(), None),
+                    (has_error.as_str(), Some(Expr::Lit(Lit::Boolean(false)))),
+                ],
+            ),
+        );
+        body
+    }
+
+    /// Move the declarations of `part` into `hoisted` when they would
+    /// otherwise be hidden inside a `try` block
+    fn hoist<T>(
+        &mut self,
+        part: ProgramPart<T>,
+        hoisted: &mut Vec<ProgramPart<T>>,
+        kept: &mut Vec<ProgramPart<T>>,
+    ) where
+        T: Clone + AsRef<str> + From<String>,
+    {
+        let decl = match part {
+            ProgramPart::Decl(decl) => decl,
+            part => return kept.push(part),
+        };
+        match decl {
+            Decl::Var(VarKind::Let | VarKind::Const, decls) => {
+                hoisted.push(declare(VarKind::Let, &decls));
+                kept.extend(initializers(decls));
+            }
+            Decl::Class(class) => match class.id.clone() {
+                Some(name) => {
+                    hoisted.push(declare_names(VarKind::Let, vec![name.name.clone()]));
+                    kept.push(expr_stmt(assign(Expr::Ident(name), Expr::Class(class))));
+                }
+                None => kept.push(ProgramPart::Decl(Decl::Class(class))),
+            },
+            Decl::Func(_) | Decl::Import(_) | Decl::ImportEquals(_) | Decl::Declare(_) => {
+                hoisted.push(ProgramPart::Decl(decl))
+            }
+            Decl::Export(export) => self.hoist_export(*export, hoisted, kept),
+            decl => kept.push(ProgramPart::Decl(decl)),
+        }
+    }
+
+    fn hoist_export<T>(
+        &mut self,
+        export: ModExport<T>,
+        hoisted: &mut Vec<ProgramPart<T>>,
+        kept: &mut Vec<ProgramPart<T>>,
+    ) where
+        T: Clone + AsRef<str> + From<String>,
+    {
+        match export {
+            ModExport::Named(NamedExportDecl::Decl(Decl::Var(kind, decls))) if !kind.is_using() => {
+                let kind = if kind == VarKind::Var {
+                    VarKind::Var
+                } else {
+                    VarKind::Let
+                };
+                hoisted.push(export_decl(declare(kind, &decls)));
+                kept.extend(initializers(decls));
+            }
+            ModExport::Named(NamedExportDecl::Decl(Decl::Class(class))) => match class.id.clone() {
+                Some(name) => {
+                    hoisted.push(export_decl(declare_names(
+                        VarKind::Let,
+                        vec![name.name.clone()],
+                    )));
+                    kept.push(expr_stmt(assign(Expr::Ident(name), Expr::Class(class))));
+                }
+                None => hoisted.push(ProgramPart::Decl(Decl::Export(Box::new(ModExport::Named(
+                    NamedExportDecl::Decl(Decl::Class(class)),
+                ))))),
+            },
+            ModExport::Default(DefaultExportDecl::Decl(Decl::Class(class))) => {
+                let name = match &class.id {
+                    Some(id) => id.name.clone(),
+                    None => T::from(self.names.fresh("_default")),
+                };
+                hoisted.push(declare_names(VarKind::Let, vec![name.clone()]));
+                hoisted.push(export_default_binding(name.clone()));
+                kept.push(expr_stmt(assign(
+                    Expr::Ident(Ident { name }),
+                    Expr::Class(class),
+                )));
+            }
+            ModExport::Default(DefaultExportDecl::Expr(expr)) => {
+                let name = T::from(self.names.fresh("_default"));
+                hoisted.push(declare_names(VarKind::Let, vec![name.clone()]));
+                hoisted.push(export_default_binding(name.clone()));
+                kept.push(expr_stmt(assign(Expr::Ident(Ident { name }), expr)));
+            }
+            export => hoisted.push(ProgramPart::Decl(Decl::Export(Box::new(export)))),
+        }
+    }
+
+    /// `for (using x of xs)` disposes `x` at the end of each
+    /// iteration and `for (using x = f();;)` after the loop
+    fn lower_loop<T>(&mut self, stmt: &mut Stmt<T>) -> Result<(), UsingError>
+    where
+        T: Clone + AsRef<str> + From<String>,
+    {
+        match stmt {
+            Stmt::ForIn(for_in) => {
+                if matches!(&for_in.left, LoopLeft::Variable(kind, _) if kind.is_using()) {
+                    return Err(UsingError::ForIn);
+                }
+            }
+            Stmt::ForOf(for_of) => {
+                let (kind, name) = match &mut for_of.left {
+                    LoopLeft::Variable(kind, decl) if kind.is_using() => {
+                        let name = binding_name(&decl.id)?;
+                        (std::mem::replace(kind, VarKind::Const), name)
+                    }
+                    _ => return Ok(()),
+                };
+                let body = match std::mem::replace(&mut *for_of.body, Stmt::Empty) {
+                    Stmt::Block(BlockStmt(parts)) => parts,
+                    body => vec![ProgramPart::Stmt(body)],
+                };
+                *for_of.body = Stmt::Block(BlockStmt(self.dispose(kind, vec![(name, None)], body)));
+            }
+            Stmt::For(for_stmt) => {
+                if !matches!(&for_stmt.init, Some(LoopInit::Variable(kind, _)) if kind.is_using()) {
+                    return Ok(());
+                }
+                let (kind, decls) = match for_stmt.init.take() {
+                    Some(LoopInit::Variable(kind, decls)) => (kind, decls),
+                    _ => unreachable!(),
+                };
+                let for_stmt = ForStmt {
+                    init: None,
+                    test: for_stmt.test.take(),
+                    update: for_stmt.update.take(),
+                    body: std::mem::replace(&mut for_stmt.body, Box::new(Stmt::Empty)),
+                };
+                let body = vec![ProgramPart::Stmt(Stmt::For(for_stmt))];
+                *stmt = Stmt::Block(BlockStmt(self.dispose_scope(kind, decls, body)?));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// `name != null ? name[Symbol.dispose] : null`, looking for
+/// `Symbol.asyncDispose` first when `is_await`
+fn dispose_method<T: From<String>>(name: &str, is_await: bool) -> Expr<T> {
+    let symbol = |key| index(ident_expr(name), member(ident_expr("Symbol"), key));
+    let method = if is_await {
+        nullish(symbol("asyncDispose"), symbol("dispose"))
+    } else {
+        symbol("dispose")
+    };
+    Expr::Conditional(ConditionalExpr {
+        test: Box::new(binary(
+            BinaryOp::NotEqual,
+            ident_expr(name),
+            Expr::Lit(Lit::Null),
+        )),
+        consequent: Box::new(method),
+        alternate: Box::new(Expr::Lit(Lit::Null)),
+    })
+}
+
+/// `catch (e) { error = value; has_error = true; }`
+fn catch_error<T: From<String>>(error: &str, has_error: &str, value: Expr<T>) -> CatchClause<T> {
+    let caught = |name: &str, value| ProgramPart::Stmt(Stmt::Expr(assign(ident_expr(name), value)));
+    CatchClause {
+        param: Some(Pat::Ident(ident("e"))),
+        body: BlockStmt(vec![
+            caught(error, value),
+            caught(has_error, Expr::Lit(Lit::Boolean(true))),
+        ]),
+    }
+}
+
+/// ```js
+/// function _suppressed(error, suppressed) {
+///     return typeof SuppressedError === "function"
+///         ? new SuppressedError(error, suppressed, "")
+///         : Object.assign(new Error("An error was suppressed during disposal"), {
+///               name: "SuppressedError", error: error, suppressed: suppressed
+///           });
+/// }
+/// ```
+fn suppressed_helper<T: From<String>>(name: &str) -> ProgramPart<T> {
+    let native = Expr::New(NewExpr {
+        callee: Box::new(ident_expr("SuppressedError")),
+        arguments: vec![ident_expr("error"), ident_expr("suppressed"), string("")],
+    });
+    let error = Expr::New(NewExpr {
+        callee: Box::new(ident_expr("Error")),
+        arguments: vec![string("An error was suppressed during disposal")],
+    });
+    let fallback = call(
+        member(ident_expr("Object"), "assign"),
+        vec![
+            error,
+            object(vec![
+                ("name", string("SuppressedError")),
+                ("error", ident_expr("error")),
+                ("suppressed", ident_expr("suppressed")),
+            ]),
+        ],
+    );
+    let type_of = Expr::Unary(UnaryExpr {
+        operator: UnaryOp::TypeOf,
+        prefix: true,
+        argument: Box::new(ident_expr("SuppressedError")),
+    });
+    let value = Expr::Conditional(ConditionalExpr {
+        test: Box::new(binary(BinaryOp::StrictEqual, type_of, string("function"))),
+        consequent: Box::new(native),
+        alternate: Box::new(fallback),
+    });
+    ProgramPart::Decl(Decl::Func(Func {
+        id: Some(ident(name)),
+        type_params: Vec::new(),
+        params: ["error", "suppressed"]
+            .iter()
+            .map(|param| FuncArg::Pat(Pat::Ident(ident(param))))
+            .collect(),
+        return_type: None,
+        body: Some(FuncBody(vec![ProgramPart::Stmt(Stmt::Return(Some(value)))])),
+        generator: false,
+        is_async: false,
+    }))
+}
+
+fn binary<T>(operator: BinaryOp, left: Expr<T>, right: Expr<T>) -> Expr<T> {
+    Expr::Binary(BinaryExpr {
+        operator,
+        left: Box::new(left),
+        right: Box::new(right),
+    })
+}
+
+/// `kind a, b;` for every name bound by `decls`
+fn declare<T: Clone>(kind: VarKind, decls: &[VarDecl<T>]) -> ProgramPart<T> {
+    declare_names(
+        kind,
+        decls
+            .iter()
+            .flat_map(|decl| decl.id.bound_names())
+            .map(|id| id.name.clone())
+            .collect(),
+    )
+}
+
+fn declare_names<T>(kind: VarKind, names: Vec<T>) -> ProgramPart<T> {
+    ProgramPart::Decl(Decl::Var(
+        kind,
+        names
+            .into_iter()
+            .map(|name| VarDecl {
+                id: Pat::Ident(Ident { name }),
+                type_annotation: None,
+                init: None,
+            })
+            .collect(),
+    ))
+}
+
+/// The assignments that replace hoisted declarations
+fn initializers<T>(decls: Vec<VarDecl<T>>) -> impl Iterator<Item = ProgramPart<T>> {
+    decls.into_iter().filter_map(|decl| {
+        let init = decl.init?;
+        let expr = match decl.id {
+            Pat::Ident(id) => assign(Expr::Ident(id), init),
+            pat => Expr::Assign(AssignExpr {
+                operator: AssignOp::Equal,
+                left: AssignLeft::Pat(pat),
+                right: Box::new(init),
+            }),
+        };
+        Some(expr_stmt(expr))
+    })
+}
+
+fn export_decl<T>(part: ProgramPart<T>) -> ProgramPart<T> {
+    match part {
+        ProgramPart::Decl(decl) => ProgramPart::Decl(Decl::Export(Box::new(ModExport::Named(
+            NamedExportDecl::Decl(decl),
+        )))),
+        part => part,
+    }
+}
+
+/// `export { name as default };`
+fn export_default_binding<T: From<String>>(name: T) -> ProgramPart<T> {
+    let spec = ExportSpecifier {
+        local: Ident { name }.into(),
+        alias: Some(ident("default").into()),
+        is_type_only: false,
+    };
+    ProgramPart::Decl(Decl::Export(Box::new(ModExport::Named(
+        NamedExportDecl::Specifier(vec![spec], None, Vec::new()),
+    ))))
+}
+
+fn binding_name<T: AsRef<str>>(pat: &Pat<T>) -> Result<String, UsingError> {
+    match pat {
+        Pat::Ident(id) => Ok(id.name.as_ref().to_string()),
+        _ => Err(UsingError::Pattern),
+    }
+}
+
+fn is_using_decl<T>(part: &ProgramPart<T>) -> bool {
+    matches!(part, ProgramPart::Decl(Decl::Var(kind, _)) if kind.is_using())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stmt::{ForInStmt, ForOfStmt};
+
+    fn using(kind: VarKind, decls: Vec<(&str, Option<Expr<String>>)>) -> ProgramPart<String> {
+        var(kind, decls)
+    }
+
+    fn call_of(name: &str, arg: &str) -> Expr<String> {
+        call(ident_expr(name), vec![ident_expr(arg)])
+    }
+
+    fn lowered(parts: Vec<ProgramPart<String>>) -> Vec<ProgramPart<String>> {
+        match lower(Program::module(parts)).unwrap() {
+            Program::Mod(parts, _) | Program::Script(parts, _) => parts,
+        }
+    }
+
+    fn try_of(part: &ProgramPart<String>) -> &TryStmt<String> {
+        match part {
+            ProgramPart::Stmt(Stmt::Try(try_stmt)) => try_stmt,
+            _ => panic!("not a try statement"),
+        }
+    }
+
+    /// The `if` that disposes a resource in the `finally` of `try_stmt`
+    fn disposal(try_stmt: &TryStmt<String>) -> &IfStmt<String> {
+        let finalizer = &try_stmt.finalizer.as_ref().unwrap().0;
+        match &try_of(&finalizer[0]).block.0[0] {
+            ProgramPart::Stmt(Stmt::If(disposal)) => disposal,
+            _ => panic!("not an if statement"),
+        }
+    }
+
+    fn dispose_call(dispose: &str, name: &str) -> Expr<String> {
+        call(member(ident_expr(dispose), "call"), vec![ident_expr(name)])
+    }
+
+    fn errors() -> ProgramPart<String> {
+        var(
+            VarKind::Let,
+            vec![
+                ("_error", None),
+                ("_hasError", Some(Expr::Lit(Lit::Boolean(false)))),
+            ],
+        )
+    }
+
+    #[test]
+    fn disposal_chains_errors() {
+        // using file = open(path); read(file);
+        let parts = lowered(vec![
+            using(
+                VarKind::Using,
+                vec![("file", Some(call_of("open", "path")))],
+            ),
+            expr_stmt(call_of("read", "file")),
+        ]);
+        assert_eq!(parts.len(), 4);
+        assert_eq!(parts[0], suppressed_helper("_suppressed"));
+        assert_eq!(parts[1], errors());
+        assert_eq!(
+            parts[2],
+            var(
+                VarKind::Const,
+                vec![
+                    ("file", Some(call_of("open", "path"))),
+                    ("_dispose", Some(dispose_method("file", false))),
+                ],
+            )
+        );
+        let try_stmt = try_of(&parts[3]);
+        assert_eq!(try_stmt.block.0, vec![expr_stmt(call_of("read", "file"))]);
+        assert_eq!(
+            try_stmt.handler,
+            Some(catch_error("_error", "_hasError", ident_expr("e")))
+        );
+        let finalizer = &try_stmt.finalizer.as_ref().unwrap().0;
+        let combined = Expr::Conditional(ConditionalExpr {
+            test: Box::new(ident_expr("_hasError")),
+            consequent: Box::new(call(
+                ident_expr("_suppressed"),
+                vec![ident_expr("e"), ident_expr("_error")],
+            )),
+            alternate: Box::new(ident_expr("e")),
+        });
+        assert_eq!(
+            try_of(&finalizer[0]).handler,
+            Some(catch_error("_error", "_hasError", combined))
+        );
+        assert_eq!(
+            finalizer[1],
+            ProgramPart::Stmt(Stmt::If(IfStmt {
+                test: ident_expr("_hasError"),
+                consequent: Box::new(Stmt::Throw(ident_expr("_error"))),
+                alternate: None,
+            }))
+        );
+        let disposal = disposal(try_stmt);
+        assert_eq!(
+            *disposal.consequent,
+            Stmt::Expr(dispose_call("_dispose", "file"))
+        );
+        assert_eq!(disposal.alternate, None);
+    }
+
+    #[test]
+    fn resources_are_disposed_in_reverse() {
+        // await using a = f(), b = g(); use(b);
+        let parts = lowered(vec![
+            using(
+                VarKind::AwaitUsing,
+                vec![
+                    ("a", Some(call(ident_expr("f"), Vec::new()))),
+                    ("b", Some(call(ident_expr("g"), Vec::new()))),
+                ],
+            ),
+            expr_stmt(call_of("use", "b")),
+        ]);
+        assert_eq!(parts[1], errors());
+        let outer = try_of(&parts[3]);
+        let inner = try_of(&outer.block.0[1]);
+        assert_eq!(inner.block.0, vec![expr_stmt(call_of("use", "b"))]);
+        // the inner `try` disposes `b` first
+        let awaited =
+            |dispose, name| Stmt::Expr(Expr::Await(Box::new(dispose_call(dispose, name))));
+        assert_eq!(*disposal(inner).consequent, awaited("_dispose2", "b"));
+        assert_eq!(*disposal(outer).consequent, awaited("_dispose", "a"));
+        // a `null` resource is awaited as well
+        assert_eq!(
+            disposal(outer).alternate,
+            Some(Box::new(Stmt::Expr(Expr::Await(Box::new(undefined())))))
+        );
+    }
+
+    #[test]
+    fn later_declarations_are_hoisted() {
+        // using r = open(); let x = 1; class C {} export default 2;
+        let class = crate::Class::new(Some(ident("C")), None, Vec::new());
+        let one = Expr::Lit(Lit::Number("1".to_string()));
+        let two = Expr::Lit(Lit::Number("2".to_string()));
+        let parts = lowered(vec![
+            using(
+                VarKind::Using,
+                vec![("r", Some(call(ident_expr("open"), Vec::new())))],
+            ),
+            var(VarKind::Let, vec![("x", Some(one.clone()))]),
+            ProgramPart::Decl(Decl::Class(class.clone())),
+            ProgramPart::Decl(Decl::Export(Box::new(ModExport::Default(
+                DefaultExportDecl::Expr(two.clone()),
+            )))),
+        ]);
+        assert_eq!(parts[1], var(VarKind::Let, vec![("x", None)]));
+        assert_eq!(parts[2], var(VarKind::Let, vec![("C", None)]));
+        assert_eq!(parts[3], var(VarKind::Let, vec![("_default", None)]));
+        assert_eq!(parts[4], export_default_binding("_default".to_string()));
+        assert_eq!(
+            try_of(&parts[7]).block.0,
+            vec![
+                expr_stmt(assign(ident_expr("x"), one)),
+                expr_stmt(assign(ident_expr("C"), Expr::Class(class))),
+                expr_stmt(assign(ident_expr("_default"), two)),
+            ]
+        );
+    }
+
+    #[test]
+    fn helper_names_are_not_in_scope() {
+        let parts = lowered(vec![
+            var(
+                VarKind::Let,
+                vec![("_dispose", None), ("_suppressed", None)],
+            ),
+            expr_stmt(ident_expr("_error")),
+            using(
+                VarKind::Using,
+                vec![("r", Some(call(ident_expr("open"), Vec::new())))],
+            ),
+        ]);
+        assert_eq!(parts[0], suppressed_helper("_suppressed2"));
+        assert_eq!(
+            parts[3],
+            var(
+                VarKind::Let,
+                vec![
+                    ("_error2", None),
+                    ("_hasError", Some(Expr::Lit(Lit::Boolean(false)))),
+                ],
+            )
+        );
+        assert_eq!(
+            *disposal(try_of(&parts[5])).consequent,
+            Stmt::Expr(dispose_call("_dispose2", "r"))
+        );
+    }
+
+    #[test]
+    fn for_of_disposes_each_value() {
+        let for_of = Stmt::ForOf(ForOfStmt {
+            left: LoopLeft::Variable(
+                VarKind::Using,
+                VarDecl {
+                    id: Pat::Ident(ident("x")),
+                    type_annotation: None,
+                    init: None,
+                },
+            ),
+            right: ident_expr("xs"),
+            body: Box::new(Stmt::Block(BlockStmt(vec![expr_stmt(call_of("use", "x"))]))),
+            is_await: false,
+        });
+        let parts = lowered(vec![ProgramPart::Stmt(for_of)]);
+        let (left, body) = match &parts[1] {
+            ProgramPart::Stmt(Stmt::ForOf(for_of)) => (&for_of.left, &*for_of.body),
+            _ => panic!("not a for of"),
+        };
+        assert!(matches!(left, LoopLeft::Variable(VarKind::Const, _)));
+        let body = match body {
+            Stmt::Block(BlockStmt(body)) => body,
+            _ => panic!("not a block"),
+        };
+        assert_eq!(body[0], errors());
+        assert_eq!(
+            body[1],
+            var(
+                VarKind::Const,
+                vec![("_dispose", Some(dispose_method("x", false)))]
+            )
+        );
+        assert_eq!(
+            try_of(&body[2]).block.0,
+            vec![expr_stmt(call_of("use", "x"))]
+        );
+    }
+
+    #[test]
+    fn invalid_declarations() {
+        let program = |parts| lower(Program::module(parts));
+        assert_eq!(
+            program(vec![using(VarKind::Using, vec![("r", None)])]),
+            Err(UsingError::MissingInit)
+        );
+        let pattern = ProgramPart::Decl(Decl::Var(
+            VarKind::Using,
+            vec![VarDecl {
+                id: Pat::Array(Vec::new()),
+                type_annotation: None,
+                init: Some(ident_expr("r")),
+            }],
+        ));
+        assert_eq!(program(vec![pattern]), Err(UsingError::Pattern));
+        let for_in = Stmt::ForIn(ForInStmt {
+            left: LoopLeft::Variable(
+                VarKind::Using,
+                VarDecl {
+                    id: Pat::Ident(ident("k")),
+                    type_annotation: None,
+                    init: None,
+                },
+            ),
+            right: ident_expr("o"),
+            body: Box::new(Stmt::Empty),
+        });
+        assert_eq!(
+            program(vec![ProgramPart::Stmt(for_in)]),
+            Err(UsingError::ForIn)
+        );
+        let script = Program::script(vec![using(
+            VarKind::Using,
+            vec![("r", Some(ident_expr("r")))],
+        )]);
+        assert_eq!(lower(script), Err(UsingError::ScriptTopLevel));
+    }
+}
@@ -0,0 +1,524 @@
+//! In place traversal of a `Program`
+//!
+//! Implement `VisitMut` and override the methods for the nodes of
+//! interest. An override that still needs to reach the children of
+//! its node calls the matching `walk_` function.
+//!
+//! Only positions that can hold a reference are visited as
+//! expressions, so the property of `a.b` and the key of `{ b: 1 }`
+//! are skipped while `a[b]` and `{ [b]: 1 }` are not. Type annotations
+//! are never visited since they do not exist at runtime.
+use crate::decl::{Decl, DefaultExportDecl, ModExport, ModuleRef, NamedExportDecl};
+use crate::expr::{ArrowFuncBody, AssignLeft, Expr, Lit, ObjProp, Prop, PropKey, PropValue};
+use crate::jsx::{JsxAttr, JsxAttrValue, JsxChild, JsxElement, JsxElementName};
+use crate::pat::{ArrayPatPart, ObjPatPart, Pat};
+use crate::stmt::{LoopInit, LoopLeft, Stmt};
+use crate::{Class, ClassMember, Func, FuncArg, Ident, MemberIndexer, Program, ProgramPart};
+
+pub trait VisitMut<T> {
+    fn visit_program(&mut self, program: &mut Program<T>) {
+        walk_program(self, program)
+    }
+    /// Any list of statements: a program, function or block body,
+    /// a switch case or a namespace
+    fn visit_parts(&mut self, parts: &mut Vec<ProgramPart<T>>) {
+        walk_parts(self, parts)
+    }
+    fn visit_part(&mut self, part: &mut ProgramPart<T>) {
+        walk_part(self, part)
+    }
+    fn visit_decl(&mut self, decl: &mut Decl<T>) {
+        walk_decl(self, decl)
+    }
+    fn visit_stmt(&mut self, stmt: &mut Stmt<T>) {
+        walk_stmt(self, stmt)
+    }
+    fn visit_expr(&mut self, expr: &mut Expr<T>) {
+        walk_expr(self, expr)
+    }
+    fn visit_pat(&mut self, pat: &mut Pat<T>) {
+        walk_pat(self, pat)
+    }
+    fn visit_func(&mut self, func: &mut Func<T>) {
+        walk_func(self, func)
+    }
+    fn visit_func_arg(&mut self, arg: &mut FuncArg<T>) {
+        walk_func_arg(self, arg)
+    }
+    fn visit_class(&mut self, class: &mut Class<T>) {
+        walk_class(self, class)
+    }
+    fn visit_prop(&mut self, prop: &mut Prop<T>) {
+        walk_prop(self, prop)
+    }
+    /// The tag name is not visited unless it is a `JsxMemberExpr`,
+    /// whose object is always a reference, see
+    /// `JsxElementName::is_intrinsic` for telling the others apart
+    fn visit_jsx_element(&mut self, element: &mut JsxElement<T>) {
+        walk_jsx_element(self, element)
+    }
+    /// An identifier in a reference or binding position
+    fn visit_ident(&mut self, _ident: &mut Ident<T>) {}
+}
+
+pub fn walk_program<T, V>(v: &mut V, program: &mut Program<T>)
+where
+    V: VisitMut<T> + ?Sized,
+{
+    match program {
+        Program::Mod(parts, _) | Program::Script(parts, _) => v.visit_parts(parts),
+    }
+}
+
+pub fn walk_parts<T, V>(v: &mut V, parts: &mut Vec<ProgramPart<T>>)
+where
+    V: VisitMut<T> + ?Sized,
+{
+    for part in parts {
+        v.visit_part(part);
+    }
+}
+
+pub fn walk_part<T, V>(v: &mut V, part: &mut ProgramPart<T>)
+where
+    V: VisitMut<T> + ?Sized,
+{
+    match part {
+        ProgramPart::Dir(_) => {}
+        ProgramPart::Decl(decl) => v.visit_decl(decl),
+        ProgramPart::Stmt(stmt) => v.visit_stmt(stmt),
+    }
+}
+
+pub fn walk_decl<T, V>(v: &mut V, decl: &mut Decl<T>)
+where
+    V: VisitMut<T> + ?Sized,
+{
+    match decl {
+        Decl::Var(_, decls) => {
+            for decl in decls {
+                v.visit_pat(&mut decl.id);
+                if let Some(init) = &mut decl.init {
+                    v.visit_expr(init);
+                }
+            }
+        }
+        Decl::Func(func) => v.visit_func(func),
+        Decl::Class(class) => v.visit_class(class),
+        Decl::Import(_) | Decl::Invalid(_) => {}
+        Decl::Export(export) => match &mut **export {
+            ModExport::Default(DefaultExportDecl::Decl(decl)) => v.visit_decl(decl),
+            ModExport::Default(DefaultExportDecl::Expr(expr)) => v.visit_expr(expr),
+            ModExport::Named(NamedExportDecl::Decl(decl)) => v.visit_decl(decl),
+            ModExport::Named(NamedExportDecl::Specifier(..)) | ModExport::All { .. } => {}
+            ModExport::Assign(expr) => v.visit_expr(expr),
+        },
+        Decl::ImportEquals(import) => {
+            if let ModuleRef::Entity(expr) = &mut import.module_ref {
+                v.visit_expr(expr);
+            }
+        }
+        Decl::Declare(decl) => v.visit_decl(decl),
+        Decl::Module(module) => {
+            if let Some(body) = &mut module.body {
+                v.visit_parts(body);
+            }
+        }
+        Decl::Interface(_) | Decl::TypeAlias(_) => {}
+        Decl::Enum(decl) => {
+            for member in &mut decl.members {
+                if let Some(init) = &mut member.init {
+                    v.visit_expr(init);
+                }
+            }
+        }
+    }
+}
+
+pub fn walk_stmt<T, V>(v: &mut V, stmt: &mut Stmt<T>)
+where
+    V: VisitMut<T> + ?Sized,
+{
+    match stmt {
+        Stmt::Expr(expr) | Stmt::Throw(expr) => v.visit_expr(expr),
+        Stmt::Block(block) => v.visit_parts(&mut block.0),
+        Stmt::Empty | Stmt::Debugger | Stmt::Break(_) | Stmt::Continue(_) | Stmt::Invalid(_) => {}
+        Stmt::With(with) => {
+            v.visit_expr(&mut with.object);
+            v.visit_stmt(&mut with.body);
+        }
+        Stmt::Return(expr) => {
+            if let Some(expr) = expr {
+                v.visit_expr(expr);
+            }
+        }
+        Stmt::Labeled(labeled) => v.visit_stmt(&mut labeled.body),
+        Stmt::If(stmt) => {
+            v.visit_expr(&mut stmt.test);
+            v.visit_stmt(&mut stmt.consequent);
+            if let Some(alternate) = &mut stmt.alternate {
+                v.visit_stmt(alternate);
+            }
+        }
+        Stmt::Switch(switch) => {
+            v.visit_expr(&mut switch.discriminant);
+            for case in &mut switch.cases {
+                if let Some(test) = &mut case.test {
+                    v.visit_expr(test);
+                }
+                v.visit_parts(&mut case.consequent);
+            }
+        }
+        Stmt::Try(stmt) => {
+            v.visit_parts(&mut stmt.block.0);
+            if let Some(handler) = &mut stmt.handler {
+                if let Some(param) = &mut handler.param {
+                    v.visit_pat(param);
+                }
+                v.visit_parts(&mut handler.body.0);
+            }
+            if let Some(finalizer) = &mut stmt.finalizer {
+                v.visit_parts(&mut finalizer.0);
+            }
+        }
+        Stmt::While(stmt) => {
+            v.visit_expr(&mut stmt.test);
+            v.visit_stmt(&mut stmt.body);
+        }
+        Stmt::DoWhile(stmt) => {
+            v.visit_stmt(&mut stmt.body);
+            v.visit_expr(&mut stmt.test);
+        }
+        Stmt::For(stmt) => {
+            match &mut stmt.init {
+                Some(LoopInit::Variable(_, decls)) => {
+                    for decl in decls {
+                        v.visit_pat(&mut decl.id);
+                        if let Some(init) = &mut decl.init {
+                            v.visit_expr(init);
+                        }
+                    }
+                }
+                Some(LoopInit::Expr(expr)) => v.visit_expr(expr),
+                None => {}
+            }
+            if let Some(test) = &mut stmt.test {
+                v.visit_expr(test);
+            }
+            if let Some(update) = &mut stmt.update {
+                v.visit_expr(update);
+            }
+            v.visit_stmt(&mut stmt.body);
+        }
+        Stmt::ForIn(stmt) => {
+            walk_loop_left(v, &mut stmt.left);
+            v.visit_expr(&mut stmt.right);
+            v.visit_stmt(&mut stmt.body);
+        }
+        Stmt::ForOf(stmt) => {
+            walk_loop_left(v, &mut stmt.left);
+            v.visit_expr(&mut stmt.right);
+            v.visit_stmt(&mut stmt.body);
+        }
+        Stmt::Var(decls) => {
+            for decl in decls {
+                v.visit_pat(&mut decl.id);
+                if let Some(init) = &mut decl.init {
+                    v.visit_expr(init);
+                }
+            }
+        }
+    }
+}
+
+fn walk_loop_left<T, V>(v: &mut V, left: &mut LoopLeft<T>)
+where
+    V: VisitMut<T> + ?Sized,
+{
+    match left {
+        LoopLeft::Expr(expr) => v.visit_expr(expr),
+        LoopLeft::Variable(_, decl) => {
+            v.visit_pat(&mut decl.id);
+            if let Some(init) = &mut decl.init {
+                v.visit_expr(init);
+            }
+        }
+        LoopLeft::Pat(pat) => v.visit_pat(pat),
+    }
+}
+
+pub fn walk_expr<T, V>(v: &mut V, expr: &mut Expr<T>)
+where
+    V: VisitMut<T> + ?Sized,
+{
+    match expr {
+        Expr::Array(elements) => {
+            for element in elements.iter_mut().flatten() {
+                v.visit_expr(element);
+            }
+        }
+        Expr::ArrowFunc(arrow) => {
+            for param in &mut arrow.params {
+                v.visit_func_arg(param);
+            }
+            match &mut arrow.body {
+                ArrowFuncBody::FuncBody(body) => v.visit_parts(&mut body.0),
+                ArrowFuncBody::Expr(expr) => v.visit_expr(expr),
+            }
+        }
+        Expr::ArrowParamPlaceHolder(args, _) => {
+            for arg in args {
+                v.visit_func_arg(arg);
+            }
+        }
+        Expr::Assign(assign) => {
+            match &mut assign.left {
+                AssignLeft::Pat(pat) => v.visit_pat(pat),
+                AssignLeft::Expr(expr) => v.visit_expr(expr),
+            }
+            v.visit_expr(&mut assign.right);
+        }
+        Expr::Await(expr) | Expr::Spread(expr) | Expr::OptionalChain(expr) => v.visit_expr(expr),
+        Expr::Binary(binary) => {
+            v.visit_expr(&mut binary.left);
+            v.visit_expr(&mut binary.right);
+        }
+        Expr::Class(class) => v.visit_class(class),
+        Expr::Call(call) => {
+            v.visit_expr(&mut call.callee);
+            for arg in &mut call.arguments {
+                v.visit_expr(arg);
+            }
+        }
+        Expr::Conditional(cond) => {
+            v.visit_expr(&mut cond.test);
+            v.visit_expr(&mut cond.consequent);
+            v.visit_expr(&mut cond.alternate);
+        }
+        Expr::Func(func) => v.visit_func(func),
+        Expr::Ident(ident) => v.visit_ident(ident),
+        Expr::Import(import) => {
+            v.visit_expr(&mut import.source);
+            if let Some(options) = &mut import.options {
+                v.visit_expr(options);
+            }
+        }
+        Expr::JsxElement(element) => v.visit_jsx_element(element),
+        Expr::JsxFragment(fragment) => walk_jsx_children(v, &mut fragment.children),
+        Expr::Lit(Lit::Template(template)) => {
+            for expr in &mut template.expressions {
+                v.visit_expr(expr);
+            }
+        }
+        Expr::Invalid(_)
+        | Expr::Lit(_)
+        | Expr::MetaProp(_)
+        | Expr::PrivateName(_)
+        | Expr::Super
+        | Expr::This => {}
+        Expr::Logical(logical) => {
+            v.visit_expr(&mut logical.left);
+            v.visit_expr(&mut logical.right);
+        }
+        Expr::Member(member) => {
+            v.visit_expr(&mut member.object);
+            if matches!(
+                member.indexer,
+                MemberIndexer::Computed | MemberIndexer::OptionalComputed
+            ) {
+                v.visit_expr(&mut member.property);
+            }
+        }
+        Expr::New(new) => {
+            v.visit_expr(&mut new.callee);
+            for arg in &mut new.arguments {
+                v.visit_expr(arg);
+            }
+        }
+        Expr::Obj(props) => {
+            for prop in props {
+                match prop {
+                    ObjProp::Prop(prop) => v.visit_prop(prop),
+                    ObjProp::Spread(expr) => v.visit_expr(expr),
+                }
+            }
+        }
+        Expr::Sequence(exprs) => {
+            for expr in exprs {
+                v.visit_expr(expr);
+            }
+        }
+        Expr::TaggedTemplate(tagged) => {
+            v.visit_expr(&mut tagged.tag);
+            for expr in &mut tagged.quasi.expressions {
+                v.visit_expr(expr);
+            }
+        }
+        Expr::Unary(unary) => v.visit_expr(&mut unary.argument),
+        Expr::Update(update) => v.visit_expr(&mut update.argument),
+        Expr::Yield(yield_expr) => {
+            if let Some(arg) = &mut yield_expr.argument {
+                v.visit_expr(arg);
+            }
+        }
+    }
+}
+
+pub fn walk_pat<T, V>(v: &mut V, pat: &mut Pat<T>)
+where
+    V: VisitMut<T> + ?Sized,
+{
+    match pat {
+        Pat::Ident(ident) => v.visit_ident(ident),
+        Pat::Obj(parts) => {
+            for part in parts {
+                match part {
+                    ObjPatPart::Assign(prop) => v.visit_prop(prop),
+                    ObjPatPart::Rest(pat) => v.visit_pat(pat),
+                }
+            }
+        }
+        Pat::Array(parts) => {
+            for part in parts.iter_mut().flatten() {
+                match part {
+                    ArrayPatPart::Pat(pat) => v.visit_pat(pat),
+                    ArrayPatPart::Expr(expr) => v.visit_expr(expr),
+                }
+            }
+        }
+        Pat::RestElement(pat) => v.visit_pat(pat),
+        Pat::Assign(assign) => {
+            v.visit_pat(&mut assign.left);
+            v.visit_expr(&mut assign.right);
+        }
+        Pat::Invalid(_) => {}
+    }
+}
+
+pub fn walk_func<T, V>(v: &mut V, func: &mut Func<T>)
+where
+    V: VisitMut<T> + ?Sized,
+{
+    for param in &mut func.params {
+        v.visit_func_arg(param);
+    }
+    if let Some(body) = &mut func.body {
+        v.visit_parts(&mut body.0);
+    }
+}
+
+pub fn walk_func_arg<T, V>(v: &mut V, arg: &mut FuncArg<T>)
+where
+    V: VisitMut<T> + ?Sized,
+{
+    match arg {
+        FuncArg::Expr(expr) => v.visit_expr(expr),
+        FuncArg::Pat(pat) => v.visit_pat(pat),
+        FuncArg::Decorated(decorated) => {
+            for decorator in &mut decorated.decorators {
+                v.visit_expr(&mut decorator.expr);
+            }
+            v.visit_func_arg(&mut decorated.arg);
+        }
+        FuncArg::Typed(typed) => {
+            v.visit_pat(&mut typed.pat);
+            if let Some(default) = &mut typed.default {
+                v.visit_expr(default);
+            }
+        }
+    }
+}
+
+pub fn walk_class<T, V>(v: &mut V, class: &mut Class<T>)
+where
+    V: VisitMut<T> + ?Sized,
+{
+    for decorator in &mut class.decorators {
+        v.visit_expr(&mut decorator.expr);
+    }
+    if let Some(super_class) = &mut class.super_class {
+        v.visit_expr(super_class);
+    }
+    for member in &mut class.body.0 {
+        match member {
+            ClassMember::Prop(prop) => v.visit_prop(prop),
+            ClassMember::Field(field) => {
+                for decorator in &mut field.decorators {
+                    v.visit_expr(&mut decorator.expr);
+                }
+                if field.computed {
+                    walk_prop_key(v, &mut field.key);
+                }
+                if let Some(value) = &mut field.value {
+                    v.visit_expr(value);
+                }
+            }
+            ClassMember::StaticBlock(body) => v.visit_parts(&mut body.0),
+        }
+    }
+}
+
+pub fn walk_prop<T, V>(v: &mut V, prop: &mut Prop<T>)
+where
+    V: VisitMut<T> + ?Sized,
+{
+    for decorator in &mut prop.decorators {
+        v.visit_expr(&mut decorator.expr);
+    }
+    if prop.computed {
+        walk_prop_key(v, &mut prop.key);
+    }
+    match &mut prop.value {
+        PropValue::Expr(expr) => v.visit_expr(expr),
+        PropValue::Pat(pat) => v.visit_pat(pat),
+        PropValue::None => {}
+    }
+}
+
+fn walk_prop_key<T, V>(v: &mut V, key: &mut PropKey<T>)
+where
+    V: VisitMut<T> + ?Sized,
+{
+    match key {
+        PropKey::Expr(expr) => v.visit_expr(expr),
+        PropKey::Pat(pat) => v.visit_pat(pat),
+        PropKey::Lit(_) | PropKey::Private(_) => {}
+    }
+}
+
+pub fn walk_jsx_element<T, V>(v: &mut V, element: &mut JsxElement<T>)
+where
+    V: VisitMut<T> + ?Sized,
+{
+    if let JsxElementName::Member(member) = &mut element.name {
+        v.visit_ident(&mut member.object);
+    }
+    for attr in &mut element.attributes {
+        match attr {
+            JsxAttr::Attr(attr) => match &mut attr.value {
+                Some(JsxAttrValue::Expr(expr)) => v.visit_expr(expr),
+                Some(JsxAttrValue::Element(element)) => v.visit_jsx_element(element),
+                Some(JsxAttrValue::Fragment(fragment)) => {
+                    walk_jsx_children(v, &mut fragment.children)
+                }
+                Some(JsxAttrValue::String(_)) | None => {}
+            },
+            JsxAttr::Spread(expr) => v.visit_expr(expr),
+        }
+    }
+    walk_jsx_children(v, &mut element.children);
+}
+
+fn walk_jsx_children<T, V>(v: &mut V, children: &mut [JsxChild<T>])
+where
+    V: VisitMut<T> + ?Sized,
+{
+    for child in children {
+        match child {
+            JsxChild::Text(_) | JsxChild::Expr(None) => {}
+            JsxChild::Expr(Some(expr)) | JsxChild::Spread(expr) => v.visit_expr(expr),
+            JsxChild::Element(element) => v.visit_jsx_element(element),
+            JsxChild::Fragment(fragment) => walk_jsx_children(v, &mut fragment.children),
+        }
+    }
+}
@@ -0,0 +1,560 @@
+//! Typescript type annotations
+//!
+//! These only describe the type system, a program with all of them
+//! removed behaves the same at runtime
+//! ```ts
+//! function first<T>(list: T[], fallback?: T): T | undefined {}
+//! let point: { x: number; y: number };
+//! ```
+use crate::expr::{Lit, PropKey};
+use crate::{FuncArg, Ident, IntoAllocated};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A type, the part of an annotation after the `:`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum TsType<T> {
+    /// A built in type
+    /// ```ts
+    /// let a: any, b: number, c: void;
+    /// ```
+    Keyword(TsKeyword),
+    /// The type of `this`
+    /// ```ts
+    /// class Thing {
+    ///     clone(): this;
+    /// }
+    /// ```
+    This,
+    /// A named type with optional type arguments
+    /// ```ts
+    /// let a: Thing, b: Stuff.Thing<string>;
+    /// ```
+    Ref(TsTypeRef<T>),
+    /// A literal type
+    /// ```ts
+    /// let a: 'red', b: 1, c: true;
+    /// ```
+    Lit(Lit<T>),
+    /// ```ts
+    /// let a: string[];
+    /// ```
+    Array(Box<TsType<T>>),
+    /// ```ts
+    /// let a: [string, number?, ...boolean[]];
+    /// ```
+    Tuple(Vec<TsType<T>>),
+    /// An optional tuple element
+    /// ```ts
+    /// let a: [string, number?];
+    /// ```
+    Optional(Box<TsType<T>>),
+    /// A rest tuple element
+    /// ```ts
+    /// let a: [string, ...boolean[]];
+    /// ```
+    Rest(Box<TsType<T>>),
+    /// ```ts
+    /// let a: string | number;
+    /// ```
+    Union(Vec<TsType<T>>),
+    /// ```ts
+    /// let a: Named & Aged;
+    /// ```
+    Intersection(Vec<TsType<T>>),
+    /// A function or constructor type
+    /// ```ts
+    /// let a: (b: string) => void, c: new () => Thing;
+    /// ```
+    Func(Box<TsFuncType<T>>),
+    /// An object type literal
+    /// ```ts
+    /// let a: { x: number; (): void };
+    /// ```
+    Object(Vec<TsTypeMember<T>>),
+    /// The type of a value
+    /// ```ts
+    /// let a: typeof stuff.thing;
+    /// ```
+    Query(Vec<Ident<T>>),
+    /// ```ts
+    /// let a: keyof Thing, b: unique symbol, c: readonly string[];
+    /// ```
+    Operator(TsTypeOperator, Box<TsType<T>>),
+    /// An indexed access type
+    /// ```ts
+    /// let a: Thing['stuff'];
+    /// ```
+    Indexed(Box<TsIndexedType<T>>),
+    /// ```ts
+    /// type A<T> = T extends string ? 'string' : 'other';
+    /// ```
+    Conditional(Box<TsConditionalType<T>>),
+    /// A type variable declared in the `extends` clause
+    /// of a conditional type
+    /// ```ts
+    /// type A<T> = T extends Array<infer U> ? U : never;
+    /// ```
+    Infer(Ident<T>),
+    /// A type guard or assertion, only allowed as a return type
+    /// ```ts
+    /// function isString(a): a is string;
+    /// function assert(a): asserts a;
+    /// ```
+    Predicate(Box<TsTypePredicate<T>>),
+}
+
+impl<T> IntoAllocated for TsType<T>
+where
+    T: ToString,
+{
+    type Allocated = TsType<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            TsType::Keyword(inner) => TsType::Keyword(inner),
+            TsType::This => TsType::This,
+            TsType::Ref(inner) => TsType::Ref(inner.into_allocated()),
+            TsType::Lit(inner) => TsType::Lit(inner.into_allocated()),
+            TsType::Array(inner) => TsType::Array(inner.into_allocated()),
+            TsType::Tuple(inner) => TsType::Tuple(allocate_types(inner)),
+            TsType::Optional(inner) => TsType::Optional(inner.into_allocated()),
+            TsType::Rest(inner) => TsType::Rest(inner.into_allocated()),
+            TsType::Union(inner) => TsType::Union(allocate_types(inner)),
+            TsType::Intersection(inner) => TsType::Intersection(allocate_types(inner)),
+            TsType::Func(inner) => TsType::Func(inner.into_allocated()),
+            TsType::Object(inner) => TsType::Object(
+                inner
+                    .into_iter()
+                    .map(IntoAllocated::into_allocated)
+                    .collect(),
+            ),
+            TsType::Query(inner) => TsType::Query(
+                inner
+                    .into_iter()
+                    .map(IntoAllocated::into_allocated)
+                    .collect(),
+            ),
+            TsType::Operator(op, inner) => TsType::Operator(op, inner.into_allocated()),
+            TsType::Indexed(inner) => TsType::Indexed(inner.into_allocated()),
+            TsType::Conditional(inner) => TsType::Conditional(inner.into_allocated()),
+            TsType::Infer(inner) => TsType::Infer(inner.into_allocated()),
+            TsType::Predicate(inner) => TsType::Predicate(inner.into_allocated()),
+        }
+    }
+}
+
+fn allocate_types<T>(types: Vec<TsType<T>>) -> Vec<TsType<String>>
+where
+    T: ToString,
+{
+    types
+        .into_iter()
+        .map(IntoAllocated::into_allocated)
+        .collect()
+}
+
+/// The built in types
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum TsKeyword {
+    Any,
+    Unknown,
+    Number,
+    String,
+    Boolean,
+    BigInt,
+    Symbol,
+    Object,
+    Undefined,
+    Null,
+    Never,
+    Void,
+}
+
+/// A possibly dotted type name with its type arguments
+/// ```ts
+/// let a: Stuff.Thing<string>;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsTypeRef<T> {
+    pub name: Vec<Ident<T>>,
+    pub type_args: Vec<TsType<T>>,
+}
+
+impl<T> IntoAllocated for TsTypeRef<T>
+where
+    T: ToString,
+{
+    type Allocated = TsTypeRef<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsTypeRef {
+            name: self
+                .name
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            type_args: allocate_types(self.type_args),
+        }
+    }
+}
+
+/// A function type, or a constructor type when
+/// `is_constructor` is set
+/// ```ts
+/// let a: <T>(b: T) => T, c: new () => Thing;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsFuncType<T> {
+    pub is_constructor: bool,
+    pub type_params: Vec<TsTypeParam<T>>,
+    pub params: Vec<FuncArg<T>>,
+    pub return_type: TsType<T>,
+}
+
+impl<T> IntoAllocated for TsFuncType<T>
+where
+    T: ToString,
+{
+    type Allocated = TsFuncType<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsFuncType {
+            is_constructor: self.is_constructor,
+            type_params: allocate_type_params(self.type_params),
+            params: self
+                .params
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            return_type: self.return_type.into_allocated(),
+        }
+    }
+}
+
+/// The operators that prefix a type
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum TsTypeOperator {
+    KeyOf,
+    Unique,
+    Readonly,
+}
+
+/// ```ts
+/// let a: Thing['stuff'];
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsIndexedType<T> {
+    pub object: TsType<T>,
+    pub index: TsType<T>,
+}
+
+impl<T> IntoAllocated for TsIndexedType<T>
+where
+    T: ToString,
+{
+    type Allocated = TsIndexedType<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsIndexedType {
+            object: self.object.into_allocated(),
+            index: self.index.into_allocated(),
+        }
+    }
+}
+
+/// ```ts
+/// type A<T> = T extends string ? 'string' : 'other';
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsConditionalType<T> {
+    pub check_type: TsType<T>,
+    pub extends_type: TsType<T>,
+    pub true_type: TsType<T>,
+    pub false_type: TsType<T>,
+}
+
+impl<T> IntoAllocated for TsConditionalType<T>
+where
+    T: ToString,
+{
+    type Allocated = TsConditionalType<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsConditionalType {
+            check_type: self.check_type.into_allocated(),
+            extends_type: self.extends_type.into_allocated(),
+            true_type: self.true_type.into_allocated(),
+            false_type: self.false_type.into_allocated(),
+        }
+    }
+}
+
+/// A type guard, or an assertion when `asserts` is set,
+/// the type is `None` for `asserts a`
+/// ```ts
+/// function isString(a): a is string;
+/// function assert(a): asserts a;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsTypePredicate<T> {
+    pub asserts: bool,
+    pub param: TsPredicateParam<T>,
+    pub type_annotation: Option<TsType<T>>,
+}
+
+impl<T> IntoAllocated for TsTypePredicate<T>
+where
+    T: ToString,
+{
+    type Allocated = TsTypePredicate<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsTypePredicate {
+            asserts: self.asserts,
+            param: self.param.into_allocated(),
+            type_annotation: self.type_annotation.into_allocated(),
+        }
+    }
+}
+
+/// What a `TsTypePredicate` narrows
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum TsPredicateParam<T> {
+    Ident(Ident<T>),
+    This,
+}
+
+impl<T> IntoAllocated for TsPredicateParam<T>
+where
+    T: ToString,
+{
+    type Allocated = TsPredicateParam<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            TsPredicateParam::Ident(inner) => TsPredicateParam::Ident(inner.into_allocated()),
+            TsPredicateParam::This => TsPredicateParam::This,
+        }
+    }
+}
+
+/// A type parameter of a generic declaration
+/// ```ts
+/// function thing<T extends object = {}>() {}
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsTypeParam<T> {
+    pub name: Ident<T>,
+    pub constraint: Option<TsType<T>>,
+    pub default: Option<TsType<T>>,
+}
+
+impl<T> IntoAllocated for TsTypeParam<T>
+where
+    T: ToString,
+{
+    type Allocated = TsTypeParam<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsTypeParam {
+            name: self.name.into_allocated(),
+            constraint: self.constraint.into_allocated(),
+            default: self.default.into_allocated(),
+        }
+    }
+}
+
+pub(crate) fn allocate_type_params<T>(params: Vec<TsTypeParam<T>>) -> Vec<TsTypeParam<String>>
+where
+    T: ToString,
+{
+    params
+        .into_iter()
+        .map(IntoAllocated::into_allocated)
+        .collect()
+}
+
+/// A single member of an object type literal or interface
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum TsTypeMember<T> {
+    /// ```ts
+    /// interface Thing {
+    ///     readonly stuff?: string;
+    /// }
+    /// ```
+    Prop(TsPropSignature<T>),
+    /// ```ts
+    /// interface Thing {
+    ///     stuff<T>(a: T): void;
+    /// }
+    /// ```
+    Method(TsMethodSignature<T>),
+    /// A call signature, or a construct signature when
+    /// `is_constructor` is set
+    /// ```ts
+    /// interface Thing {
+    ///     (a: string): void;
+    ///     new (): Thing;
+    /// }
+    /// ```
+    Call(TsCallSignature<T>),
+    /// ```ts
+    /// interface Thing {
+    ///     [key: string]: number;
+    /// }
+    /// ```
+    Index(TsIndexSignature<T>),
+}
+
+impl<T> IntoAllocated for TsTypeMember<T>
+where
+    T: ToString,
+{
+    type Allocated = TsTypeMember<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        match self {
+            TsTypeMember::Prop(inner) => TsTypeMember::Prop(inner.into_allocated()),
+            TsTypeMember::Method(inner) => TsTypeMember::Method(inner.into_allocated()),
+            TsTypeMember::Call(inner) => TsTypeMember::Call(inner.into_allocated()),
+            TsTypeMember::Index(inner) => TsTypeMember::Index(inner.into_allocated()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsPropSignature<T> {
+    pub key: PropKey<T>,
+    pub computed: bool,
+    pub is_optional: bool,
+    pub is_readonly: bool,
+    pub type_annotation: Option<TsType<T>>,
+}
+
+impl<T> IntoAllocated for TsPropSignature<T>
+where
+    T: ToString,
+{
+    type Allocated = TsPropSignature<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsPropSignature {
+            key: self.key.into_allocated(),
+            computed: self.computed,
+            is_optional: self.is_optional,
+            is_readonly: self.is_readonly,
+            type_annotation: self.type_annotation.into_allocated(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsMethodSignature<T> {
+    pub key: PropKey<T>,
+    pub computed: bool,
+    pub is_optional: bool,
+    pub type_params: Vec<TsTypeParam<T>>,
+    pub params: Vec<FuncArg<T>>,
+    pub return_type: Option<TsType<T>>,
+}
+
+impl<T> IntoAllocated for TsMethodSignature<T>
+where
+    T: ToString,
+{
+    type Allocated = TsMethodSignature<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsMethodSignature {
+            key: self.key.into_allocated(),
+            computed: self.computed,
+            is_optional: self.is_optional,
+            type_params: allocate_type_params(self.type_params),
+            params: self
+                .params
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            return_type: self.return_type.into_allocated(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsCallSignature<T> {
+    pub is_constructor: bool,
+    pub type_params: Vec<TsTypeParam<T>>,
+    pub params: Vec<FuncArg<T>>,
+    pub return_type: Option<TsType<T>>,
+}
+
+impl<T> IntoAllocated for TsCallSignature<T>
+where
+    T: ToString,
+{
+    type Allocated = TsCallSignature<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsCallSignature {
+            is_constructor: self.is_constructor,
+            type_params: allocate_type_params(self.type_params),
+            params: self
+                .params
+                .into_iter()
+                .map(IntoAllocated::into_allocated)
+                .collect(),
+            return_type: self.return_type.into_allocated(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TsIndexSignature<T> {
+    pub is_readonly: bool,
+    pub param: Ident<T>,
+    pub param_type: TsType<T>,
+    pub type_annotation: TsType<T>,
+}
+
+impl<T> IntoAllocated for TsIndexSignature<T>
+where
+    T: ToString,
+{
+    type Allocated = TsIndexSignature<String>;
+
+    fn into_allocated(self) -> Self::Allocated {
+        TsIndexSignature {
+            is_readonly: self.is_readonly,
+            param: self.param.into_allocated(),
+            param_type: self.param_type.into_allocated(),
+            type_annotation: self.type_annotation.into_allocated(),
+        }
+    }
+}
+
+/// `public`, `protected` or `private` on a class member
+/// or constructor parameter
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum Accessibility {
+    Public,
+    Protected,
+    Private,
+}